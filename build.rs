@@ -0,0 +1,22 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    write_ffi_header();
+}
+
+/// Generates `dredd.h` from the functions of `dredd_rs::ffi` into `OUT_DIR`,
+/// where the tests compare it to the header checked in at `include/dredd.h`.
+/// Builds never write to the sources.
+#[cfg(feature = "ffi")]
+fn write_ffi_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).unwrap();
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{out_dir}/dredd.h"));
+        }
+        Err(error) => println!("cargo:warning=could not generate dredd.h: {error}"),
+    }
+}
@@ -0,0 +1,269 @@
+#ifndef DREDD_H
+#define DREDD_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A rule context.
+ */
+typedef struct DreddContext DreddContext;
+
+/**
+ * Named conditions and actions that rule sets refer to.
+ */
+typedef struct DreddRegistry DreddRegistry;
+
+/**
+ * A rule set loaded from JSON.
+ */
+typedef struct DreddRules DreddRules;
+
+/**
+ * A condition called with the context of the rule and the `user_data` it
+ * was registered with.
+ */
+typedef bool (*DreddCondition)(struct DreddContext *ctx, void *user_data);
+
+/**
+ * An action called with the context of the rule and the `user_data` it was
+ * registered with.
+ */
+typedef void (*DreddAction)(struct DreddContext *ctx, void *user_data);
+
+/**
+ * The reason the last failing call on this thread failed, or null when
+ * none did. The string stays valid until the next failing call on this
+ * thread, and must not be freed.
+ */
+const char *dredd_last_error(void);
+
+/**
+ * Releases a string returned by the library.
+ *
+ * # Safety
+ *
+ * `string` must be null or a string returned by the library that was not
+ * released yet.
+ */
+void dredd_string_free(char *string);
+
+/**
+ * Creates an empty context.
+ */
+struct DreddContext *dredd_context_new(void);
+
+/**
+ * Releases a context.
+ *
+ * # Safety
+ *
+ * `ctx` must be null or a context returned by `dredd_context_new` that was
+ * not released yet.
+ */
+void dredd_context_free(struct DreddContext *ctx);
+
+/**
+ * Sets a key to an integer.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `key` a NUL-terminated string.
+ */
+bool dredd_context_set_int(struct DreddContext *ctx, const char *key, int64_t value);
+
+/**
+ * Sets a key to a floating point number.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `key` a NUL-terminated string.
+ */
+bool dredd_context_set_float(struct DreddContext *ctx, const char *key, double value);
+
+/**
+ * Sets a key to a boolean.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `key` a NUL-terminated string.
+ */
+bool dredd_context_set_bool(struct DreddContext *ctx, const char *key, bool value);
+
+/**
+ * Sets a key to a string, which is copied.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context, and `key` and `value` NUL-terminated
+ * strings.
+ */
+bool dredd_context_set_string(struct DreddContext *ctx, const char *key, const char *value);
+
+/**
+ * Sets a key to a value given as JSON. Numbers are stored as integers when
+ * they have no fractional part, and arrays and objects as JSON values.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context, and `key` and `json` NUL-terminated
+ * strings.
+ */
+bool dredd_context_set_json(struct DreddContext *ctx, const char *key, const char *json);
+
+/**
+ * Records a failure of the rule being fired, which stops the run, see
+ * `RuleFailure`. Meant to be called from actions and conditions.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `message` a NUL-terminated string.
+ */
+bool dredd_context_fail(struct DreddContext *ctx, const char *message);
+
+/**
+ * Reads an integer key into `out`.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+ * valid pointer.
+ */
+bool dredd_context_get_int(const struct DreddContext *ctx, const char *key, int64_t *out);
+
+/**
+ * Reads a numeric key into `out`.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+ * valid pointer.
+ */
+bool dredd_context_get_float(const struct DreddContext *ctx, const char *key, double *out);
+
+/**
+ * Reads a boolean key into `out`.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+ * valid pointer.
+ */
+bool dredd_context_get_bool(const struct DreddContext *ctx, const char *key, bool *out);
+
+/**
+ * The value of a string key, to be released with `dredd_string_free`, or
+ * null.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `key` a NUL-terminated string.
+ */
+char *dredd_context_get_string(const struct DreddContext *ctx, const char *key);
+
+/**
+ * The value of a key as JSON, to be released with `dredd_string_free`, or
+ * null.
+ *
+ * # Safety
+ *
+ * `ctx` must be a valid context and `key` a NUL-terminated string.
+ */
+char *dredd_context_get_json(const struct DreddContext *ctx, const char *key);
+
+/**
+ * Creates an empty callback registry.
+ */
+struct DreddRegistry *dredd_registry_new(void);
+
+/**
+ * Releases a callback registry. Rule sets loaded with it remain usable.
+ *
+ * # Safety
+ *
+ * `registry` must be null or a registry returned by `dredd_registry_new`
+ * that was not released yet.
+ */
+void dredd_registry_free(struct DreddRegistry *registry);
+
+/**
+ * Registers a C function as the condition `name`.
+ *
+ * # Safety
+ *
+ * `registry` must be a valid registry and `name` a NUL-terminated string.
+ * `user_data` must stay valid for as long as rule sets loaded with the
+ * registry are used. The context handle given to the condition is only
+ * valid during the call.
+ */
+bool dredd_registry_condition(struct DreddRegistry *registry,
+                              const char *name,
+                              DreddCondition condition,
+                              void *user_data);
+
+/**
+ * Registers an expression, see `dredd_rs::expr`, as the condition `name`.
+ * An expression that fails to evaluate doesn't hold.
+ *
+ * # Safety
+ *
+ * `registry` must be a valid registry, and `name` and `expr`
+ * NUL-terminated strings.
+ */
+bool dredd_registry_condition_expr(struct DreddRegistry *registry,
+                                   const char *name,
+                                   const char *expr);
+
+/**
+ * Registers a C function as the action `name`.
+ *
+ * # Safety
+ *
+ * `registry` must be a valid registry and `name` a NUL-terminated string.
+ * `user_data` must stay valid for as long as rule sets loaded with the
+ * registry are used. The context handle given to the action is only valid
+ * during the call.
+ */
+bool dredd_registry_action(struct DreddRegistry *registry,
+                           const char *name,
+                           DreddAction action,
+                           void *user_data);
+
+/**
+ * Loads an active rule set from JSON, resolving its callbacks against the
+ * registry. Returns null when the document is invalid, isn't active or
+ * names an unknown callback.
+ *
+ * # Safety
+ *
+ * `json` must be a NUL-terminated string and `registry` a valid registry.
+ */
+struct DreddRules *dredd_rules_from_json(const char *json, const struct DreddRegistry *registry);
+
+/**
+ * Releases a rule set.
+ *
+ * # Safety
+ *
+ * `rules` must be null or a rule set returned by `dredd_rules_from_json`
+ * that was not released yet.
+ */
+void dredd_rules_free(struct DreddRules *rules);
+
+/**
+ * Fires the rules against the context. Returns `false` when a rule failed,
+ * with the failure in `dredd_last_error`. Panics of the engine are caught
+ * and reported the same way.
+ *
+ * # Safety
+ *
+ * `rules` must be a valid rule set and `ctx` a valid context.
+ */
+bool dredd_rules_fire(const struct DreddRules *rules, struct DreddContext *ctx);
+
+#endif  /* DREDD_H */
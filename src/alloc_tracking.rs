@@ -0,0 +1,95 @@
+//! Counts heap allocations so that run reports can attribute them to rules.
+//!
+//! A library can't choose the global allocator, so the application has to
+//! install `TrackingAllocator` for the counts to be collected. Without it,
+//! every count is `0`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::alloc_tracking::TrackingAllocator;
+//! use dredd_rs::rule::*;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+//!
+//! let rule = ChainRule::new()
+//!     .with_name("greeting")
+//!     .on_execute(|this| this.get_rule_context().set("greeting", "hello".to_string()));
+//!
+//! let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+//!
+//! let entry = &report.get_trace().get_entries()[0];
+//! assert!(entry.get_allocations() > 0);
+//! println!("{} allocated {} bytes", entry.get_name().unwrap(), entry.get_allocated_bytes());
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    static ALLOCATED_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A global allocator that forwards to the system allocator and counts, per
+/// thread, the allocations made and the bytes they requested.
+///
+/// Reallocations count as an allocation of the new size; deallocations are
+/// not subtracted.
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn record(size: usize) {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        let _ = ALLOCATED_BYTES.try_with(|bytes| bytes.set(bytes.get() + size as u64));
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// The allocations and bytes counted so far on the current thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AllocationCount {
+    pub(crate) allocations: u64,
+    pub(crate) bytes: u64,
+}
+
+impl AllocationCount {
+    pub(crate) fn current() -> Self {
+        AllocationCount {
+            allocations: ALLOCATIONS.try_with(Cell::get).unwrap_or_default(),
+            bytes: ALLOCATED_BYTES.try_with(Cell::get).unwrap_or_default(),
+        }
+    }
+
+    /// The allocations counted since `self` was taken.
+    pub(crate) fn elapsed(self) -> Self {
+        let now = Self::current();
+        AllocationCount {
+            allocations: now.allocations - self.allocations,
+            bytes: now.bytes - self.bytes,
+        }
+    }
+}
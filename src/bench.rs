@@ -0,0 +1,251 @@
+//! Compares two versions of a rule set on the same inputs.
+//!
+//! Each version is given as a function that runs the rule set against a
+//! context and returns the report of the run, and every input of the corpus
+//! is run by both. The comparison reports the latency and throughput of each
+//! version and the inputs on which their outcomes differ.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::bench;
+//! use dredd_rs::rule::*;
+//!
+//! let current = |rule_context| {
+//!     let rule = ChainRule::new()
+//!         .with_name("adult")
+//!         .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 18);
+//!     Engine::chain_runner().run_with_report(rule_context, vec![rule])
+//! };
+//! let candidate = |rule_context| {
+//!     let rule = ChainRule::new()
+//!         .with_name("adult")
+//!         .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 21);
+//!     Engine::chain_runner().run_with_report(rule_context, vec![rule])
+//! };
+//!
+//! let corpus: Vec<_> = [12i64, 19, 42]
+//!     .into_iter()
+//!     .map(|age| {
+//!         let mut rule_context = RuleContext::new();
+//!         rule_context.set("age", age);
+//!         rule_context
+//!     })
+//!     .collect();
+//!
+//! let comparison = bench::compare(current, candidate, &corpus);
+//!
+//! assert_eq!(comparison.get_differences().len(), 1);
+//! assert_eq!(comparison.get_differences()[0].get_index(), 1);
+//! println!("{comparison}");
+//! ```
+
+use std::{fmt, time::Duration};
+
+use crate::rule::{RuleContext, RuleContextWrapper, RunReport};
+
+/// Compares two rule sets by the names of the rules they execute.
+pub fn compare(
+    a: impl Fn(RuleContextWrapper) -> RunReport,
+    b: impl Fn(RuleContextWrapper) -> RunReport,
+    corpus: &[RuleContextWrapper],
+) -> Comparison {
+    compare_by(a, b, corpus, |_, report| {
+        report
+            .get_trace()
+            .get_executed_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Compares two rule sets by the outcome `outcome` extracts from the context
+/// and report of every run.
+///
+/// Every run gets its own copy of the input context, and the two versions
+/// are run one after the other on each input so that both see the same
+/// conditions.
+pub fn compare_by<S: PartialEq + fmt::Debug>(
+    a: impl Fn(RuleContextWrapper) -> RunReport,
+    b: impl Fn(RuleContextWrapper) -> RunReport,
+    corpus: &[RuleContextWrapper],
+    outcome: impl Fn(&RuleContextWrapper, &RunReport) -> S,
+) -> Comparison {
+    let run = |run: &dyn Fn(RuleContextWrapper) -> RunReport, input: &RuleContextWrapper| {
+        let rule_context = RuleContext::from_context_map(input.borrow().get_context_map().clone());
+        let report = run(rule_context.clone());
+        (report.get_duration(), outcome(&rule_context, &report))
+    };
+
+    let mut durations_a = Vec::with_capacity(corpus.len());
+    let mut durations_b = Vec::with_capacity(corpus.len());
+    let mut differences = Vec::new();
+
+    for (index, input) in corpus.iter().enumerate() {
+        let (duration_a, outcome_a) = run(&a, input);
+        let (duration_b, outcome_b) = run(&b, input);
+        durations_a.push(duration_a);
+        durations_b.push(duration_b);
+
+        if outcome_a != outcome_b {
+            differences.push(OutcomeDifference {
+                index,
+                a: format!("{outcome_a:?}"),
+                b: format!("{outcome_b:?}"),
+            });
+        }
+    }
+
+    Comparison {
+        a: LatencyStats::new(durations_a),
+        b: LatencyStats::new(durations_b),
+        differences,
+    }
+}
+
+/// The result of comparing two rule sets.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    a: LatencyStats,
+    b: LatencyStats,
+    differences: Vec<OutcomeDifference>,
+}
+
+impl Comparison {
+    /// The latency of the first rule set.
+    pub fn get_a(&self) -> &LatencyStats {
+        &self.a
+    }
+
+    /// The latency of the second rule set.
+    pub fn get_b(&self) -> &LatencyStats {
+        &self.b
+    }
+
+    /// The inputs on which the outcomes of the rule sets differ.
+    pub fn get_differences(&self) -> &[OutcomeDifference] {
+        &self.differences
+    }
+
+    /// The mean latency of the second rule set relative to the first, so
+    /// `1.5` means it is 50% slower.
+    pub fn get_mean_ratio(&self) -> f64 {
+        self.b.mean.as_secs_f64() / self.a.mean.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "     {:>12} {:>12} {:>12} {:>12} {:>14}",
+            "mean", "p50", "p95", "max", "runs/s"
+        )?;
+        for (name, stats) in [("a", &self.a), ("b", &self.b)] {
+            writeln!(
+                f,
+                "{name:<4} {:>12?} {:>12?} {:>12?} {:>12?} {:>14.1}",
+                stats.mean,
+                stats.p50,
+                stats.p95,
+                stats.max,
+                stats.get_throughput()
+            )?;
+        }
+        write!(
+            f,
+            "b/a mean: {:.2}, differing outcomes: {}/{}",
+            self.get_mean_ratio(),
+            self.differences.len(),
+            self.a.runs
+        )
+    }
+}
+
+/// Latency statistics of the runs of one rule set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencyStats {
+    runs: usize,
+    total: Duration,
+    mean: Duration,
+    p50: Duration,
+    p95: Duration,
+    max: Duration,
+}
+
+impl LatencyStats {
+    fn new(mut durations: Vec<Duration>) -> Self {
+        if durations.is_empty() {
+            return Self::default();
+        }
+        durations.sort_unstable();
+
+        let runs = durations.len();
+        let total: Duration = durations.iter().sum();
+        let percentile = |p: usize| durations[((runs * p).div_ceil(100)).max(1) - 1];
+
+        LatencyStats {
+            runs,
+            total,
+            mean: total / runs as u32,
+            p50: percentile(50),
+            p95: percentile(95),
+            max: durations[runs - 1],
+        }
+    }
+
+    pub fn get_runs(&self) -> usize {
+        self.runs
+    }
+
+    pub fn get_total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn get_mean(&self) -> Duration {
+        self.mean
+    }
+
+    pub fn get_p50(&self) -> Duration {
+        self.p50
+    }
+
+    pub fn get_p95(&self) -> Duration {
+        self.p95
+    }
+
+    pub fn get_max(&self) -> Duration {
+        self.max
+    }
+
+    /// Runs per second.
+    pub fn get_throughput(&self) -> f64 {
+        self.runs as f64 / self.total.as_secs_f64()
+    }
+}
+
+/// An input on which the two rule sets had different outcomes.
+#[derive(Debug, Clone)]
+pub struct OutcomeDifference {
+    index: usize,
+    a: String,
+    b: String,
+}
+
+impl OutcomeDifference {
+    /// The position of the input in the corpus.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// The outcome of the first rule set, formatted with `Debug`.
+    pub fn get_a(&self) -> &str {
+        &self.a
+    }
+
+    /// The outcome of the second rule set, formatted with `Debug`.
+    pub fn get_b(&self) -> &str {
+        &self.b
+    }
+}
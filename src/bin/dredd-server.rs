@@ -0,0 +1,31 @@
+//! Serves the rule documents of a directory over HTTP, see `dredd_rs::server`.
+//!
+//! Usage: `dredd-server <rules directory> [address]`
+
+use std::{env, process};
+
+use dredd_rs::server::RuleServer;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let Some(dir) = args.next() else {
+        eprintln!("usage: dredd-server <rules directory> [address]");
+        process::exit(2);
+    };
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+
+    let server = RuleServer::load_dir(&dir).unwrap_or_else(|e| {
+        eprintln!("dredd-server: could not load {dir}: {e}");
+        process::exit(1);
+    });
+    eprintln!(
+        "dredd-server: serving {} from {dir} on http://{address}",
+        server.get_names().join(", ")
+    );
+    if let Err(e) = server.serve(&address) {
+        eprintln!("dredd-server: {e}");
+        process::exit(1);
+    }
+}
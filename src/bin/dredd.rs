@@ -0,0 +1,10 @@
+//! Runs rule documents against JSON contexts, see `dredd_rs::cli`.
+//!
+//! Usage: `dredd run --rules <rules.json> [--context <context.json>]`
+
+use std::{env, io, process};
+
+fn main() {
+    let code = dredd_rs::cli::run(env::args().skip(1), &mut io::stdout(), &mut io::stderr());
+    process::exit(code);
+}
@@ -0,0 +1,188 @@
+//! Generates a catalog documenting the rules of registered rule sets, for
+//! rule documentation generated from the rules themselves.
+//!
+//! Each rule is documented with its name, id, description, condition, the
+//! keys it reads and writes, its owner, team and tags, as set with
+//! `RuleMetadata` and `RuleCallback::on_condition`. The catalog is a plain
+//! data structure, serialized to JSON with `to_json` or rendered as Markdown
+//! with `to_markdown`.
+//!
+//! Rule sets registered with `RuleRegistry::register_with`, such as rules
+//! loaded from JSON, keep no metadata and are listed without rules.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::catalog::RuleCatalog;
+//! use dredd_rs::rule::*;
+//!
+//! let mut registry = RuleRegistry::new();
+//! registry.register(
+//!     "checkout",
+//!     Engine::all_runner(),
+//!     vec![AllRule::new()
+//!         .with_name("adult_check")
+//!         .with_description("Approves customers that are 18 or older.")
+//!         .with_owner("ana")
+//!         .with_writes(&["approved"])
+//!         .on_condition(Condition::key_equals("adult", true))],
+//! );
+//!
+//! let catalog = RuleCatalog::from_registry(&registry);
+//! let rule = &catalog.get_rule_sets()[0].rules[0];
+//! assert_eq!(rule.condition.as_deref(), Some("`adult` == true"));
+//!
+//! assert!(catalog.to_json().contains("\"adult_check\""));
+//! assert!(catalog.to_markdown().contains("### adult_check"));
+//! ```
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::rule::{Metadata, RuleRegistry};
+
+/// The documentation of a single rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CatalogRule {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// The evaluation of the rule in plain words, see `Rule::get_condition`.
+    pub condition: Option<String>,
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+    pub owner: Option<String>,
+    pub team: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl CatalogRule {
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        CatalogRule {
+            id: metadata.get_id().map(str::to_string),
+            name: metadata.get_name().map(str::to_string),
+            description: metadata.get_description().map(str::to_string),
+            condition: metadata.get_condition().map(str::to_string),
+            reads: metadata.reads.clone(),
+            writes: metadata.writes.clone(),
+            owner: metadata.get_owner().map(str::to_string),
+            team: metadata.get_team().map(str::to_string),
+            tags: metadata.get_tags().to_vec(),
+        }
+    }
+}
+
+/// The documentation of the rules of a rule set, children included, in
+/// depth-first order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CatalogRuleSet {
+    pub name: String,
+    pub rules: Vec<CatalogRule>,
+}
+
+/// Documents rule sets, sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleCatalog {
+    rule_sets: Vec<CatalogRuleSet>,
+}
+
+impl RuleCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Documents every rule set of the registry.
+    pub fn from_registry(registry: &RuleRegistry) -> Self {
+        registry
+            .get_names()
+            .into_iter()
+            .fold(RuleCatalog::new(), |catalog, name| {
+                catalog.rule_set(name, registry.get_rules(name))
+            })
+    }
+
+    /// Adds a rule set given the metadata of its rules, replacing the one
+    /// with the same name.
+    pub fn rule_set(mut self, name: &str, rules: &[Metadata]) -> Self {
+        let rule_set = CatalogRuleSet {
+            name: name.to_string(),
+            rules: rules.iter().map(CatalogRule::from_metadata).collect(),
+        };
+        match self
+            .rule_sets
+            .binary_search_by(|other| other.name.as_str().cmp(name))
+        {
+            Ok(index) => self.rule_sets[index] = rule_set,
+            Err(index) => self.rule_sets.insert(index, rule_set),
+        }
+        self
+    }
+
+    pub fn get_rule_sets(&self) -> &[CatalogRuleSet] {
+        &self.rule_sets
+    }
+
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("catalog serialization can't fail")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("catalog serialization can't fail")
+    }
+
+    /// Renders the catalog as a Markdown document, with a section per rule
+    /// set and a subsection per rule.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Rule catalog\n");
+        for rule_set in &self.rule_sets {
+            let _ = write!(markdown, "\n## {}\n", rule_set.name);
+            if rule_set.rules.is_empty() {
+                markdown.push_str("\nNo documented rules.\n");
+            }
+            for rule in &rule_set.rules {
+                write_rule(&mut markdown, rule);
+            }
+        }
+        markdown
+    }
+}
+
+fn write_rule(markdown: &mut String, rule: &CatalogRule) {
+    let title = rule
+        .name
+        .as_deref()
+        .or(rule.id.as_deref())
+        .unwrap_or("Unnamed rule");
+    let _ = write!(markdown, "\n### {title}\n");
+    if let Some(description) = &rule.description {
+        let _ = write!(markdown, "\n{description}\n");
+    }
+    let fields = [
+        ("Id", rule.id.as_ref().map(|id| format!("`{id}`"))),
+        ("Condition", rule.condition.clone()),
+        ("Reads", code_list(&rule.reads)),
+        ("Writes", code_list(&rule.writes)),
+        ("Owner", rule.owner.clone()),
+        ("Team", rule.team.clone()),
+        ("Tags", code_list(&rule.tags)),
+    ];
+    let mut fields = fields
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .peekable();
+    if fields.peek().is_some() {
+        markdown.push('\n');
+    }
+    for (label, value) in fields {
+        let _ = writeln!(markdown, "- **{label}:** {value}");
+    }
+}
+
+fn code_list(items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let items: Vec<String> = items.iter().map(|item| format!("`{item}`")).collect();
+    Some(items.join(", "))
+}
@@ -0,0 +1,115 @@
+//! The `dredd` command line tool, for trying rule sets out and checking them
+//! in CI.
+//!
+//! ```text
+//! dredd run --rules rules.json --context ctx.json
+//! ```
+//!
+//! runs a rule document, see `dredd_rs::loader::from_document_json`, against
+//! a context read from a JSON object, and prints the rules fired, the
+//! resulting context, and the errors and warnings of the run as JSON. The
+//! exit code is `0` when the run succeeds, `1` when a rule failed and `2`
+//! when the arguments or the files are not valid.
+//!
+//! # Example
+//!
+//! ```rust
+//! let dir = std::env::temp_dir();
+//! std::fs::write(dir.join("adult.json"), r#"{
+//!     "conditions": { "is_adult": "age >= 18" },
+//!     "actions": { "approve": { "approved": true } },
+//!     "rules": { "type": "all", "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }] }
+//! }"#).unwrap();
+//! std::fs::write(dir.join("adult-context.json"), r#"{ "age": 42 }"#).unwrap();
+//!
+//! let rules = dir.join("adult.json").display().to_string();
+//! let context = dir.join("adult-context.json").display().to_string();
+//!
+//! let mut out = Vec::new();
+//! let code = dredd_rs::cli::run(["run", "--rules", rules.as_str(), "--context", context.as_str()], &mut out, &mut std::io::sink());
+//!
+//! let output: serde_json::Value = serde_json::from_slice(&out).unwrap();
+//! assert_eq!(code, 0);
+//! assert_eq!(output["fired"], serde_json::json!(["adult"]));
+//! assert_eq!(output["context"]["approved"], true);
+//! ```
+
+use std::{fs, io::Write};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    loader,
+    rule::RuleContext,
+    scenario::{context_to_json, intern, set_value},
+};
+
+const USAGE: &str = "usage: dredd run --rules <rules.json> [--context <context.json>]";
+
+/// Runs the tool with its arguments, without the program name, writing the
+/// result to `out` and problems to `err`. Returns the exit code.
+pub fn run<I>(args: I, out: &mut dyn Write, err: &mut dyn Write) -> i32
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let (output, failed) = match run_command(args.into_iter().map(Into::into).collect()) {
+        Ok(result) => result,
+        Err(message) => {
+            let _ = writeln!(err, "dredd: {message}");
+            return 2;
+        }
+    };
+    let output = serde_json::to_string_pretty(&output).expect("JSON values serialize");
+    if let Err(e) = writeln!(out, "{output}") {
+        let _ = writeln!(err, "dredd: {e}");
+        return 2;
+    }
+    if failed {
+        1
+    } else {
+        0
+    }
+}
+
+/// Returns the output of the command and whether the run failed.
+fn run_command(args: Vec<String>) -> Result<(Value, bool), String> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        Some("run") => {}
+        Some(command) => return Err(format!("unknown command `{command}`\n{USAGE}")),
+        None => return Err(USAGE.to_string()),
+    }
+
+    let (mut rules, mut context) = (None, None);
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--rules" => &mut rules,
+            "--context" => &mut context,
+            _ => return Err(format!("unexpected argument `{arg}`\n{USAGE}")),
+        };
+        *slot = Some(args.next().ok_or_else(|| format!("{arg} needs a file"))?);
+    }
+    let rules = rules.ok_or_else(|| format!("missing --rules\n{USAGE}"))?;
+
+    let read = |path: &str| fs::read_to_string(path).map_err(|e| format!("{path}: {e}"));
+    let loaded = loader::from_document_json(&read(&rules)?).map_err(|e| format!("{rules}: {e}"))?;
+    let values: Map<String, Value> = match &context {
+        Some(path) => serde_json::from_str(&read(path)?).map_err(|e| format!("{path}: {e}"))?,
+        None => Map::new(),
+    };
+
+    let mut rule_context = RuleContext::new();
+    for (key, value) in &values {
+        set_value(&mut rule_context, intern(key), value);
+    }
+    let report = loaded.run_with_report(rule_context.clone());
+
+    let output = json!({
+        "fired": report.get_trace().get_executed_names(),
+        "context": context_to_json(&rule_context),
+        "errors": report.get_errors().iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "warnings": report.get_warnings().iter().map(ToString::to_string).collect::<Vec<_>>(),
+    });
+    Ok((output, !report.get_errors().is_empty()))
+}
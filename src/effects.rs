@@ -0,0 +1,315 @@
+//! Notifications decided by rules and delivered after the run.
+//!
+//! Rules don't send emails or call webhooks themselves: their callbacks emit
+//! `Effect` values into the context, and once the run is over a
+//! `TransportRegistry` hands every effect to the transport registered for
+//! its kind. Rule sets stay free of SMTP or HTTP clients, can be tested by
+//! looking at the emitted effects, and emit nothing that outlives a rolled
+//! back run: effects are an ordinary context value, so
+//! `RuleRunner::try_run_atomic` discards the effects of a failed run and a
+//! dry run never emits any.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::{cell::RefCell, rc::Rc};
+//! use dredd_rs::effects::{ContextEffects, Effect, EffectKind, TransportRegistry};
+//! use dredd_rs::rule::*;
+//!
+//! let rule = ChainRule::new()
+//!     .on_eval(|ctx| *ctx.get::<u32>("total").unwrap() > 1000)
+//!     .on_execute(|this| {
+//!         this.get_rule_context()
+//!             .emit(Effect::email("ops@example.com", "Large order", "Please review."));
+//!     });
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("total", 1200u32);
+//! Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+//!
+//! let sent = Rc::new(RefCell::new(Vec::new()));
+//! let outbox = sent.clone();
+//! let transports = TransportRegistry::new().with_transport(EffectKind::Email, move |effect: &Effect| {
+//!     outbox.borrow_mut().push(effect.clone());
+//!     Ok(())
+//! });
+//!
+//! let report = transports.deliver(&rule_context);
+//! assert!(report.is_complete());
+//! assert_eq!(sent.borrow().len(), 1);
+//! assert!(rule_context.get_effects().is_empty());
+//! ```
+
+use std::{collections::HashMap, error::Error, fmt, rc::Rc};
+
+use crate::rule::{ContextList, RuleContext, RuleContextWrapper};
+
+/// The context key holding the emitted effects.
+pub const EFFECTS_KEY: &str = "dredd.effects";
+
+/// A notification to deliver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    Email {
+        to: String,
+        subject: String,
+        body: String,
+    },
+    Sms {
+        to: String,
+        text: String,
+    },
+    Push {
+        device: String,
+        title: String,
+        body: String,
+    },
+    /// A payload, usually JSON, to post to a URL.
+    Webhook {
+        url: String,
+        payload: String,
+    },
+}
+
+impl Effect {
+    pub fn email(to: &str, subject: &str, body: &str) -> Self {
+        Effect::Email {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn sms(to: &str, text: &str) -> Self {
+        Effect::Sms {
+            to: to.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    pub fn push(device: &str, title: &str, body: &str) -> Self {
+        Effect::Push {
+            device: device.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn webhook(url: &str, payload: &str) -> Self {
+        Effect::Webhook {
+            url: url.to_string(),
+            payload: payload.to_string(),
+        }
+    }
+
+    pub fn get_kind(&self) -> EffectKind {
+        match self {
+            Effect::Email { .. } => EffectKind::Email,
+            Effect::Sms { .. } => EffectKind::Sms,
+            Effect::Push { .. } => EffectKind::Push,
+            Effect::Webhook { .. } => EffectKind::Webhook,
+        }
+    }
+}
+
+/// The kinds of effects, each delivered by its own transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EffectKind {
+    Email,
+    Sms,
+    Push,
+    Webhook,
+}
+
+impl fmt::Display for EffectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            EffectKind::Email => "email",
+            EffectKind::Sms => "sms",
+            EffectKind::Push => "push",
+            EffectKind::Webhook => "webhook",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+/// Emits effects into the context, in order.
+pub trait ContextEffects {
+    fn emit(&mut self, effect: Effect);
+    /// The effects emitted and not delivered yet.
+    fn get_effects(&self) -> Vec<Effect>;
+    /// Removes the emitted effects from the context and returns them.
+    fn take_effects(&mut self) -> Vec<Effect>;
+}
+
+impl ContextEffects for RuleContext {
+    fn emit(&mut self, effect: Effect) {
+        self.push_to_list(EFFECTS_KEY, effect);
+    }
+
+    fn get_effects(&self) -> Vec<Effect> {
+        self.get_list::<Effect>(EFFECTS_KEY)
+            .map(|effects| effects.to_vec())
+            .unwrap_or_default()
+    }
+
+    fn take_effects(&mut self) -> Vec<Effect> {
+        let effects = self.get_effects();
+        if !effects.is_empty() {
+            self.set_list::<Effect>(EFFECTS_KEY, Vec::new());
+        }
+        effects
+    }
+}
+
+impl ContextEffects for RuleContextWrapper {
+    fn emit(&mut self, effect: Effect) {
+        self.borrow_mut().emit(effect);
+    }
+
+    fn get_effects(&self) -> Vec<Effect> {
+        self.borrow().get_effects()
+    }
+
+    fn take_effects(&mut self) -> Vec<Effect> {
+        self.borrow_mut().take_effects()
+    }
+}
+
+/// Why an effect was not delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    /// No transport is registered for the kind of the effect.
+    NoTransport(EffectKind),
+    /// The transport failed, with its message.
+    Failed(String),
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::NoTransport(kind) => write!(f, "no transport for {kind} effects"),
+            DeliveryError::Failed(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for DeliveryError {}
+
+/// Delivers effects of one kind, for example through an SMTP relay or an
+/// SMS gateway.
+pub trait Transport {
+    fn deliver(&self, effect: &Effect) -> Result<(), String>;
+
+    /// Checks that the transport can deliver, without delivering anything,
+    /// see `dredd_rs::preflight`. Transports that can't tell succeed.
+    fn ping(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<F: Fn(&Effect) -> Result<(), String>> Transport for F {
+    fn deliver(&self, effect: &Effect) -> Result<(), String> {
+        self(effect)
+    }
+}
+
+/// The transports effects are delivered with, by kind.
+///
+/// Clones share the transports.
+#[derive(Clone, Default)]
+pub struct TransportRegistry {
+    transports: HashMap<EffectKind, Rc<dyn Transport>>,
+}
+
+impl TransportRegistry {
+    pub fn new() -> Self {
+        TransportRegistry::default()
+    }
+
+    /// Registers the transport of a kind of effects, replacing any previous
+    /// one.
+    pub fn with_transport(mut self, kind: EffectKind, transport: impl Transport + 'static) -> Self {
+        self.transports.insert(kind, Rc::new(transport));
+        self
+    }
+
+    pub fn contains(&self, kind: EffectKind) -> bool {
+        self.transports.contains_key(&kind)
+    }
+
+    /// The kinds of effects with a transport, sorted.
+    pub fn get_kinds(&self) -> Vec<EffectKind> {
+        let mut kinds: Vec<_> = self.transports.keys().copied().collect();
+        kinds.sort_unstable();
+        kinds
+    }
+
+    /// Pings the transport of a kind of effects, see `Transport::ping`.
+    pub fn ping(&self, kind: EffectKind) -> Option<Result<(), String>> {
+        self.transports.get(&kind).map(|transport| transport.ping())
+    }
+
+    /// Takes the effects emitted into the context and delivers them in the
+    /// order they were emitted. An effect that can't be delivered doesn't
+    /// stop the delivery of the following ones.
+    pub fn deliver(&self, rule_context: &RuleContextWrapper) -> DeliveryReport {
+        let effects = rule_context.clone().take_effects();
+        self.deliver_all(effects)
+    }
+
+    /// Delivers effects taken from a context earlier, for example the failed
+    /// effects of a previous `DeliveryReport`.
+    pub fn deliver_all(&self, effects: Vec<Effect>) -> DeliveryReport {
+        let mut report = DeliveryReport::default();
+        for effect in effects {
+            let result = match self.transports.get(&effect.get_kind()) {
+                Some(transport) => transport.deliver(&effect).map_err(DeliveryError::Failed),
+                None => Err(DeliveryError::NoTransport(effect.get_kind())),
+            };
+            match result {
+                Ok(()) => report.delivered.push(effect),
+                Err(error) => report.failed.push((effect, error)),
+            }
+        }
+        report
+    }
+}
+
+impl fmt::Debug for TransportRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kinds: Vec<_> = self.transports.keys().collect();
+        f.debug_struct("TransportRegistry")
+            .field("transports", &kinds)
+            .finish()
+    }
+}
+
+/// The outcome of `TransportRegistry::deliver`.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryReport {
+    delivered: Vec<Effect>,
+    failed: Vec<(Effect, DeliveryError)>,
+}
+
+impl DeliveryReport {
+    pub fn get_delivered(&self) -> &[Effect] {
+        &self.delivered
+    }
+
+    /// The effects that were not delivered, with the reason.
+    pub fn get_failed(&self) -> &[(Effect, DeliveryError)] {
+        &self.failed
+    }
+
+    /// Whether every effect was delivered.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// The effects that were not delivered, to retry them with
+    /// `TransportRegistry::deliver_all`.
+    pub fn take_failed(&mut self) -> Vec<Effect> {
+        self.failed.drain(..).map(|(effect, _)| effect).collect()
+    }
+}
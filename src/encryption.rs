@@ -0,0 +1,332 @@
+//! Envelope encryption of sensitive context values before they are persisted.
+//!
+//! A `ContextEncryptor` replaces the values of the keys configured as
+//! sensitive with ciphertexts, leaving the other values readable. Every
+//! encryption draws a fresh data key, encrypts the values with AES-256-GCM
+//! under it, and stores the data key wrapped by a `KeyProvider`, which holds
+//! the key encryption keys, usually in a KMS or an HSM. Decrypting asks the
+//! provider to unwrap the data key, so rotating or revoking key encryption
+//! keys happens in the provider, and persisted state can't be read without
+//! it.
+//!
+//! An encrypted value is a JSON object holding the id of the key encryption
+//! key, the wrapped data key, the nonce and the ciphertext, all but the id
+//! in base64. The name of the key is bound to the ciphertext, so encrypted
+//! values can't be moved between keys.
+//!
+//! `EncryptingSink` applies an encryptor to the samples written by a
+//! `dredd_rs::sampling::Sampler`, and `ContextEncryptor::encrypt_snapshot`
+//! to a `ContextSnapshot`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::encryption::{ContextEncryptor, LocalKeyProvider};
+//! use serde_json::json;
+//!
+//! let provider = LocalKeyProvider::new("kek-2024", [7; 32]);
+//! let encryptor = ContextEncryptor::new(provider).with_sensitive_keys(&["ssn"]);
+//!
+//! let values = json!({ "ssn": "123-45-6789", "age": 42 });
+//! let encrypted = encryptor.encrypt(values.as_object().unwrap()).unwrap();
+//!
+//! assert_eq!(encrypted["age"], 42);
+//! assert!(!encrypted["ssn"].to_string().contains("123-45-6789"));
+//! assert_eq!(&encryptor.decrypt(&encrypted).unwrap(), values.as_object().unwrap());
+//! ```
+
+use std::{collections::HashSet, error::Error, fmt, io};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    rule::ContextSnapshot,
+    sampling::{Sample, SampleSink},
+    scenario::get_value,
+};
+
+/// The key under which an encrypted value stores its envelope.
+const ENVELOPE: &str = "$encrypted";
+
+/// Why values could not be encrypted or decrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    /// The key provider failed to wrap or unwrap a data key.
+    KeyProvider(String),
+    /// A ciphertext doesn't match its key, nonce or context key name.
+    Decryption(String),
+    /// An encrypted value is not a valid envelope.
+    Malformed(String),
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::KeyProvider(message) => write!(f, "key provider: {message}"),
+            EncryptionError::Decryption(key) => write!(f, "could not decrypt `{key}`"),
+            EncryptionError::Malformed(key) => write!(f, "malformed encrypted value `{key}`"),
+        }
+    }
+}
+
+impl Error for EncryptionError {}
+
+impl From<EncryptionError> for io::Error {
+    fn from(error: EncryptionError) -> Self {
+        io::Error::other(error)
+    }
+}
+
+/// A data key encrypted by a key encryption key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    /// The id of the key encryption key, to find it again when unwrapping.
+    pub key_id: String,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Wraps and unwraps data keys with key encryption keys it keeps to itself,
+/// typically by calling a KMS.
+pub trait KeyProvider {
+    /// Encrypts a data key with the current key encryption key.
+    fn wrap_key(&self, data_key: &[u8]) -> Result<WrappedKey, EncryptionError>;
+    /// Decrypts a data key with the key encryption key it was wrapped with.
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> Result<Vec<u8>, EncryptionError>;
+}
+
+/// A key provider holding a single key encryption key in memory, for tests
+/// and local development.
+#[derive(Clone)]
+pub struct LocalKeyProvider {
+    key_id: String,
+    cipher: Aes256Gcm,
+}
+
+impl LocalKeyProvider {
+    pub fn new(key_id: &str, key: [u8; 32]) -> Self {
+        LocalKeyProvider {
+            key_id: key_id.to_string(),
+            cipher: Aes256Gcm::new(&key.into()),
+        }
+    }
+}
+
+impl KeyProvider for LocalKeyProvider {
+    fn wrap_key(&self, data_key: &[u8]) -> Result<WrappedKey, EncryptionError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, data_key)
+            .map_err(|_| EncryptionError::KeyProvider("could not wrap the data key".into()))?;
+        Ok(WrappedKey {
+            key_id: self.key_id.clone(),
+            ciphertext: [nonce.as_slice(), &ciphertext].concat(),
+        })
+    }
+
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> Result<Vec<u8>, EncryptionError> {
+        let unknown = || EncryptionError::KeyProvider(format!("unknown key `{}`", wrapped.key_id));
+        if wrapped.key_id != self.key_id || wrapped.ciphertext.len() < 12 {
+            return Err(unknown());
+        }
+        let (nonce, ciphertext) = wrapped.ciphertext.split_at(12);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| unknown())
+    }
+}
+
+impl fmt::Debug for LocalKeyProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKeyProvider")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encrypts the values of sensitive keys, see the module documentation.
+#[derive(Debug, Clone)]
+pub struct ContextEncryptor<P: KeyProvider> {
+    provider: P,
+    sensitive_keys: HashSet<String>,
+}
+
+impl<P: KeyProvider> ContextEncryptor<P> {
+    pub fn new(provider: P) -> Self {
+        ContextEncryptor {
+            provider,
+            sensitive_keys: HashSet::new(),
+        }
+    }
+
+    /// Adds keys whose values are encrypted.
+    pub fn with_sensitive_keys(mut self, keys: &[&str]) -> Self {
+        self.sensitive_keys
+            .extend(keys.iter().map(|key| key.to_string()));
+        self
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive_keys.contains(key)
+    }
+
+    /// Returns the values with those of the sensitive keys encrypted.
+    pub fn encrypt(
+        &self,
+        values: &Map<String, Value>,
+    ) -> Result<Map<String, Value>, EncryptionError> {
+        if !values.keys().any(|key| self.is_sensitive(key)) {
+            return Ok(values.clone());
+        }
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let wrapped = self.provider.wrap_key(&data_key)?;
+        let cipher = Aes256Gcm::new(&data_key);
+
+        values
+            .iter()
+            .map(|(key, value)| {
+                if !self.is_sensitive(key) {
+                    return Ok((key.clone(), value.clone()));
+                }
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let plaintext = value.to_string();
+                let payload = Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: key.as_bytes(),
+                };
+                // Only fails for values over 64 GiB.
+                let ciphertext = cipher
+                    .encrypt(&nonce, payload)
+                    .expect("value too large to encrypt");
+                let envelope = json!({
+                    ENVELOPE: {
+                        "key_id": wrapped.key_id,
+                        "wrapped_key": STANDARD.encode(&wrapped.ciphertext),
+                        "nonce": STANDARD.encode(nonce),
+                        "ciphertext": STANDARD.encode(ciphertext),
+                    }
+                });
+                Ok((key.clone(), envelope))
+            })
+            .collect()
+    }
+
+    /// Returns the values with every encrypted value decrypted, whether its
+    /// key is still configured as sensitive or not.
+    pub fn decrypt(
+        &self,
+        values: &Map<String, Value>,
+    ) -> Result<Map<String, Value>, EncryptionError> {
+        values
+            .iter()
+            .map(|(key, value)| match value.get(ENVELOPE) {
+                Some(envelope) => Ok((key.clone(), self.decrypt_value(key, envelope)?)),
+                None => Ok((key.clone(), value.clone())),
+            })
+            .collect()
+    }
+
+    /// Returns the values of a snapshot that JSON can represent, with those
+    /// of the sensitive keys encrypted.
+    pub fn encrypt_snapshot(
+        &self,
+        snapshot: &ContextSnapshot,
+    ) -> Result<Map<String, Value>, EncryptionError> {
+        let values = snapshot
+            .get_keys()
+            .into_iter()
+            .filter_map(|key| {
+                let value = snapshot.context_map.get(key)?;
+                Some((key.to_string(), get_value(value.as_ref())?))
+            })
+            .collect();
+        self.encrypt(&values)
+    }
+
+    fn decrypt_value(&self, key: &str, envelope: &Value) -> Result<Value, EncryptionError> {
+        let malformed = || EncryptionError::Malformed(key.to_string());
+        let field = |name: &str| {
+            envelope
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(malformed)
+        };
+        let bytes = |name: &str| STANDARD.decode(field(name)?).map_err(|_| malformed());
+
+        let wrapped = WrappedKey {
+            key_id: field("key_id")?.to_string(),
+            ciphertext: bytes("wrapped_key")?,
+        };
+        let nonce = bytes("nonce")?;
+        let ciphertext = bytes("ciphertext")?;
+        if nonce.len() != 12 {
+            return Err(malformed());
+        }
+
+        let data_key = self.provider.unwrap_key(&wrapped)?;
+        let cipher = Aes256Gcm::new_from_slice(&data_key).map_err(|_| malformed())?;
+        let payload = Payload {
+            msg: &ciphertext,
+            aad: key.as_bytes(),
+        };
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| EncryptionError::Decryption(key.to_string()))?;
+        serde_json::from_slice(&plaintext).map_err(|_| malformed())
+    }
+}
+
+/// A sample sink encrypting the sensitive values of the input and outputs
+/// of every sample before passing it on.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::encryption::{ContextEncryptor, EncryptingSink, LocalKeyProvider};
+/// use dredd_rs::rule::*;
+/// use dredd_rs::sampling::{JsonLinesSink, Sampler};
+///
+/// let encryptor = ContextEncryptor::new(LocalKeyProvider::new("kek", [1; 32]))
+///     .with_sensitive_keys(&["card_number"]);
+/// let sink = EncryptingSink::new(JsonLinesSink::new(Vec::new()), encryptor);
+/// let mut sampler = Sampler::new(sink);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("card_number", "4111 1111 1111 1111");
+/// sampler.run(&Engine::all_runner(), rule_context, vec![AllRule::new()]).unwrap();
+///
+/// let lines = String::from_utf8(sampler.into_sink().into_inner().into_inner()).unwrap();
+/// assert!(lines.contains("$encrypted"));
+/// assert!(!lines.contains("4111"));
+/// ```
+#[derive(Debug)]
+pub struct EncryptingSink<S: SampleSink, P: KeyProvider> {
+    sink: S,
+    encryptor: ContextEncryptor<P>,
+}
+
+impl<S: SampleSink, P: KeyProvider> EncryptingSink<S, P> {
+    pub fn new(sink: S, encryptor: ContextEncryptor<P>) -> Self {
+        EncryptingSink { sink, encryptor }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: SampleSink, P: KeyProvider> SampleSink for EncryptingSink<S, P> {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        let sample = Sample::new(
+            self.encryptor.encrypt(sample.get_input())?,
+            sample.get_fired().to_vec(),
+            self.encryptor.encrypt(sample.get_outputs())?,
+        );
+        self.sink.write_sample(&sample)
+    }
+}
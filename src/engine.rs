@@ -1,5 +1,13 @@
+use crate::fixpoint::{self, FixpointReport};
+use crate::rule::{filter::RuleFilter, Metadata, Rule, RuleContextWrapper, Wrapper};
 use crate::runner::{
-    best_first_rule_runner::BestFirstRuleRunner, chain_rule_runner::ChainRuleRunner,
+    all_rule_runner::AllRuleRunner, best_first_rule_runner::BestFirstRuleRunner,
+    chain_rule_runner::ChainRuleRunner, RuleRunner as _, RunReport, SiblingRunner,
+};
+
+#[cfg(feature = "rayon")]
+use crate::runner::{
+    parallel_rule_runner::ParallelRuleRunner, speculative_rule_runner::SpeculativeRuleRunner,
 };
 
 /// The `Engine` struct provides methods to create instances of different rule runners.
@@ -8,6 +16,11 @@ use crate::runner::{
 ///
 /// - `best_first_runner`: Creates a new instance of `BestFirstRuleRunner`.
 /// - `chain_runner`: Creates a new instance of `ChainRuleRunner`.
+/// - `all_runner`: Creates a new instance of `AllRuleRunner`.
+/// - `parallel_runner`: Creates a new instance of `ParallelRuleRunner` (requires the `rayon` feature).
+/// - `speculative_runner`: Creates a new instance of `SpeculativeRuleRunner` (requires the `rayon` feature).
+/// - `execute_to_fixpoint`: Fires rules again and again until the context stops changing.
+/// - `execute_filtered`: Fires only the rules whose metadata passes a filter.
 ///
 pub struct Engine;
 
@@ -29,4 +42,114 @@ impl Engine {
     pub fn chain_runner() -> ChainRuleRunner {
         ChainRuleRunner
     }
+
+    /// Creates a new instance of `AllRuleRunner`.
+    ///
+    /// # Returns
+    ///
+    /// An `AllRuleRunner` instance.
+    pub fn all_runner() -> AllRuleRunner {
+        AllRuleRunner
+    }
+
+    /// Creates a new instance of `ParallelRuleRunner`.
+    ///
+    /// # Returns
+    ///
+    /// A `ParallelRuleRunner` instance.
+    #[cfg(feature = "rayon")]
+    pub fn parallel_runner() -> ParallelRuleRunner {
+        ParallelRuleRunner
+    }
+
+    /// Creates a new instance of `SpeculativeRuleRunner` evaluating at most
+    /// `width` siblings at once.
+    ///
+    /// # Returns
+    ///
+    /// A `SpeculativeRuleRunner` instance.
+    #[cfg(feature = "rayon")]
+    pub fn speculative_runner(width: usize) -> SpeculativeRuleRunner {
+        SpeculativeRuleRunner::new(width)
+    }
+
+    /// Fires the rules as siblings, like `AllRuleRunner`, pass after pass,
+    /// until a pass leaves the context unchanged, so that facts derived by
+    /// one rule can trigger rules fired before it.
+    ///
+    /// A key counts as changed when it was added, removed, or holds a value
+    /// that is not equal to the one before the pass. Values of the primitive
+    /// types, lists of them and types registered with
+    /// `RuleContext::register_comparable` are compared by value, other values
+    /// are compared by identity. The run stops early when
+    /// a pass fails, when the context comes back to the values it held after
+    /// an earlier pass, or after `max_iterations` passes.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rules = vec![
+    ///     AllRule::new()
+    ///         .with_name("vip")
+    ///         .on_eval(|ctx| ctx.get::<bool>("loyal").is_some())
+    ///         .on_execute(|this| this.get_rule_context().set("vip", true)),
+    ///     AllRule::new()
+    ///         .with_name("loyal")
+    ///         .on_eval(|ctx| *ctx.get::<u32>("orders").unwrap() > 10)
+    ///         .on_execute(|this| this.get_rule_context().set("loyal", true)),
+    /// ];
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.set("orders", 12u32);
+    /// let report = Engine::execute_to_fixpoint(rule_context.clone(), rules, 10);
+    ///
+    /// assert!(report.is_converged());
+    /// assert_eq!(report.get_changed_keys(), [vec!["loyal"], vec!["vip"], vec![]]);
+    /// assert!(*rule_context.get::<bool>("vip").unwrap());
+    /// ```
+    pub fn execute_to_fixpoint<R: Rule<R>>(
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<R>>,
+        max_iterations: usize,
+    ) -> FixpointReport {
+        fixpoint::execute_to_fixpoint(rule_context, rules, max_iterations)
+    }
+
+    /// Fires the rules as siblings, like `AllRuleRunner`, skipping every rule
+    /// whose metadata doesn't pass the filter, along with its children, so
+    /// that one rule tree can serve several jurisdictions or tenants picked
+    /// at run time. Filtered rules show up in the trace as skipped with the
+    /// `"filtered"` reason. A filtered run nested in another one only fires
+    /// the rules both filters let through.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rules = || vec![
+    ///     AllRule::new().with_name("gdpr consent").with_tags(&["eu-only"]),
+    ///     AllRule::new().with_name("ccpa notice").with_tags(&["us-only"]),
+    ///     AllRule::new().with_name("fraud check"),
+    /// ];
+    ///
+    /// let report = Engine::execute_filtered(RuleContext::new(), rules(), |meta| !meta.has_tag("us-only"));
+    ///
+    /// assert_eq!(report.get_trace().get_executed_names(), vec!["gdpr consent", "fraud check"]);
+    /// assert_eq!(report.get_trace().get_by_outcome(RuleOutcome::Skipped("filtered")).len(), 1);
+    /// ```
+    pub fn execute_filtered<R: Rule<R>>(
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<R>>,
+        filter: impl Fn(&Metadata) -> bool + 'static,
+    ) -> RunReport {
+        let mut filter = RuleFilter::new(filter);
+        if let Some(outer) = rule_context.borrow().get_filter() {
+            filter = outer.clone().and(filter);
+        }
+        let previous = rule_context.borrow_mut().set_filter(Some(filter));
+        let report = SiblingRunner::new().run_with_report(rule_context.clone(), rules);
+        rule_context.borrow_mut().set_filter(previous);
+        report
+    }
 }
@@ -0,0 +1,646 @@
+//! A small expression language for rule conditions.
+//!
+//! Expressions read `RuleContext` keys by name and support boolean logic
+//! (`&&`, `||`, `!`), comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`),
+//! arithmetic (`+`, `-`, `*`, `/`, `%`), parentheses, and number, string
+//! (`'...'` or `"..."`), `true` and `false` literals. Key names may contain
+//! letters, digits, `_` and `.`. `random()` draws a float from `[0, 1)` with
+//! the random generator of the context, see `dredd_rs::rule::Rng`.
+//! Expressions nest at most 256 deep, counting operators and parentheses:
+//! deeper ones fail to parse with `ExprError::TooDeep`.
+//!
+//! Context values are read as numbers when they are any of the primitive
+//! integer or float types, as strings when they are `String` or `&'static str`,
+//! and as booleans when they are `bool`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::expr::Expr;
+//! use dredd_rs::rule::*;
+//!
+//! let expr = Expr::parse("age >= 18 && country == 'BR'").unwrap();
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("age", 42);
+//! rule_context.set("country", "BR");
+//!
+//! assert!(expr.eval(&rule_context.borrow()).unwrap());
+//! ```
+
+use std::{any::Any, error::Error, fmt};
+
+use crate::rule::{value_types::Primitive, ContextView, RuleContext};
+
+/// A parsed expression, ready to be evaluated against a context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    source: String,
+    root: Node,
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: source.len(),
+            depth: 0,
+        };
+        let (root, _) = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(Expr {
+                source: source.to_string(),
+                root,
+            }),
+            Some((position, token)) => Err(ExprError::Parse {
+                position: *position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    /// The context keys the expression reads, in the order they appear.
+    pub fn get_keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        self.root.collect_keys(&mut keys);
+        keys
+    }
+
+    /// Evaluates the expression, which must produce a boolean.
+    pub fn eval(&self, rule_context: &RuleContext) -> Result<bool, ExprError> {
+        match self.root.eval(rule_context)? {
+            Value::Bool(value) => Ok(value),
+            value => Err(ExprError::TypeMismatch(format!(
+                "expected a boolean result, got {value}"
+            ))),
+        }
+    }
+
+    /// Evaluates the expression against the context an evaluation callback
+    /// is given, see `RuleCallback::on_eval`.
+    pub fn eval_view(&self, ctx: ContextView<'_>) -> Result<bool, ExprError> {
+        self.eval(&ctx.get_rule_context().borrow())
+    }
+
+    /// Builds the comparison of a key with a value without going through
+    /// source text, so that the key may be any string, spaces and keywords
+    /// included. `None` when `operator` is not a comparison.
+    pub(crate) fn comparison(key: &str, operator: &str, value: Value) -> Option<Self> {
+        let op = comparison_op(operator)?;
+        Some(Expr {
+            source: format!("{key} {operator} {value}"),
+            root: Node::Binary(
+                op,
+                Box::new(Node::Key(key.to_string())),
+                Box::new(Node::Literal(value)),
+            ),
+        })
+    }
+}
+
+/// Errors returned while parsing or evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprError {
+    /// The expression is not valid; `position` is a byte offset into the source.
+    Parse {
+        position: usize,
+        message: String,
+    },
+    /// A key used by the expression is not in the context.
+    MissingKey(String),
+    /// A key holds a value of a type expressions can't read, or an operator
+    /// was applied to values of the wrong type.
+    TypeMismatch(String),
+    DivisionByZero,
+    /// An integer operation overflowed `i64`, the operation it describes.
+    Overflow(String),
+    /// The expression nests deeper than the parser allows.
+    TooDeep,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Parse { position, message } => {
+                write!(f, "invalid expression at {position}: {message}")
+            }
+            ExprError::MissingKey(key) => write!(f, "missing key `{key}`"),
+            ExprError::TypeMismatch(message) => write!(f, "type mismatch: {message}"),
+            ExprError::DivisionByZero => write!(f, "division by zero"),
+            ExprError::Overflow(operation) => write!(f, "integer overflow {operation}"),
+            ExprError::TooDeep => write!(f, "expression nested deeper than {MAX_DEPTH}"),
+        }
+    }
+}
+
+impl Error for ExprError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Str(value) => write!(f, "'{value}'"),
+        }
+    }
+}
+
+impl Value {
+    fn from_any(key: &str, value: &dyn Any) -> Result<Self, ExprError> {
+        let mismatch = |message: &str| ExprError::TypeMismatch(format!("key `{key}` {message}"));
+        let value = Primitive::from_any(value)
+            .ok_or_else(|| mismatch("holds a value expressions can't read"))?;
+        if let Some(int) = value.as_int() {
+            return int
+                .try_into()
+                .map(Value::Int)
+                .map_err(|_| mismatch("is out of range"));
+        }
+        if let Some(float) = value.as_float() {
+            return Ok(Value::Float(float));
+        }
+        if let Some(text) = value.as_str() {
+            return Ok(Value::Str(text.to_string()));
+        }
+        match value {
+            Primitive::Bool(value) => Ok(Value::Bool(value)),
+            _ => Err(mismatch("holds a value expressions can't read")),
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Int(value) => Some(*value as f64),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Value),
+    Key(String),
+    Random,
+    Not(Box<Node>),
+    Neg(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, rule_context: &RuleContext) -> Result<Value, ExprError> {
+        match self {
+            Node::Literal(value) => Ok(value.clone()),
+            Node::Key(key) => match rule_context.lookup(key) {
+                Some(value) => Value::from_any(key, value.as_ref()),
+                None => Err(ExprError::MissingKey(key.clone())),
+            },
+            Node::Random => Ok(Value::Float(rule_context.random())),
+            Node::Not(node) => match node.eval(rule_context)? {
+                Value::Bool(value) => Ok(Value::Bool(!value)),
+                value => Err(ExprError::TypeMismatch(format!("cannot negate {value}"))),
+            },
+            Node::Neg(node) => match node.eval(rule_context)? {
+                Value::Int(value) => value
+                    .checked_neg()
+                    .map(Value::Int)
+                    .ok_or_else(|| ExprError::Overflow(format!("negating {value}"))),
+                Value::Float(value) => Ok(Value::Float(-value)),
+                value => Err(ExprError::TypeMismatch(format!("cannot negate {value}"))),
+            },
+            Node::Binary(Op::And, left, right) => Ok(Value::Bool(
+                left.eval_bool(rule_context)? && right.eval_bool(rule_context)?,
+            )),
+            Node::Binary(Op::Or, left, right) => Ok(Value::Bool(
+                left.eval_bool(rule_context)? || right.eval_bool(rule_context)?,
+            )),
+            Node::Binary(op, left, right) => {
+                binary(*op, left.eval(rule_context)?, right.eval(rule_context)?)
+            }
+        }
+    }
+
+    fn collect_keys<'a>(&'a self, keys: &mut Vec<&'a str>) {
+        match self {
+            Node::Key(key) if !keys.contains(&key.as_str()) => keys.push(key),
+            Node::Not(node) | Node::Neg(node) => node.collect_keys(keys),
+            Node::Binary(_, left, right) => {
+                left.collect_keys(keys);
+                right.collect_keys(keys);
+            }
+            _ => {}
+        }
+    }
+
+    fn eval_bool(&self, rule_context: &RuleContext) -> Result<bool, ExprError> {
+        match self.eval(rule_context)? {
+            Value::Bool(value) => Ok(value),
+            value => Err(ExprError::TypeMismatch(format!(
+                "expected a boolean, got {value}"
+            ))),
+        }
+    }
+}
+
+fn binary(op: Op, left: Value, right: Value) -> Result<Value, ExprError> {
+    use std::cmp::Ordering;
+
+    let ordering = match (&left, &right) {
+        (Value::Int(l), Value::Int(r)) => Some(l.cmp(r)),
+        (Value::Str(l), Value::Str(r)) => Some(l.cmp(r)),
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+        _ => match (left.as_float(), right.as_float()) {
+            (Some(l), Some(r)) => l.partial_cmp(&r),
+            _ => None,
+        },
+    };
+
+    let mismatch = || ExprError::TypeMismatch(format!("cannot apply {op:?} to {left} and {right}"));
+
+    match op {
+        Op::Eq => Ok(Value::Bool(ordering == Some(Ordering::Equal))),
+        Op::Ne => Ok(Value::Bool(ordering != Some(Ordering::Equal))),
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+            if matches!((&left, &right), (Value::Bool(_), Value::Bool(_))) {
+                return Err(mismatch());
+            }
+            let ordering = ordering.ok_or_else(mismatch)?;
+            Ok(Value::Bool(match op {
+                Op::Lt => ordering == Ordering::Less,
+                Op::Le => ordering != Ordering::Greater,
+                Op::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }))
+        }
+        Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Rem => match (&left, &right) {
+            (Value::Int(l), Value::Int(r)) => {
+                let value = match op {
+                    Op::Add => l.checked_add(*r),
+                    Op::Sub => l.checked_sub(*r),
+                    Op::Mul => l.checked_mul(*r),
+                    Op::Div if *r == 0 => return Err(ExprError::DivisionByZero),
+                    Op::Div => l.checked_div(*r),
+                    Op::Rem if *r == 0 => return Err(ExprError::DivisionByZero),
+                    _ => l.checked_rem(*r),
+                };
+                value
+                    .map(Value::Int)
+                    .ok_or_else(|| ExprError::Overflow(format!("applying {op:?} to {l} and {r}")))
+            }
+            (Value::Str(l), Value::Str(r)) if op == Op::Add => Ok(Value::Str(format!("{l}{r}"))),
+            _ => {
+                let (l, r) = match (left.as_float(), right.as_float()) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => return Err(mismatch()),
+                };
+                if matches!(op, Op::Div | Op::Rem) && r == 0.0 {
+                    return Err(ExprError::DivisionByZero);
+                }
+                Ok(Value::Float(match op {
+                    Op::Add => l + r,
+                    Op::Sub => l - r,
+                    Op::Mul => l * r,
+                    Op::Div => l / r,
+                    _ => l % r,
+                }))
+            }
+        },
+        Op::And | Op::Or => unreachable!("logical operators are evaluated lazily"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Value),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "number {value}"),
+            Token::Str(value) => write!(f, "string '{value}'"),
+            Token::Ident(value) => write!(f, "`{value}`"),
+            Token::Op(op) => write!(f, "`{op}`"),
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+        }
+    }
+}
+
+const OPERATORS: [&str; 15] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push((
+                position,
+                if c == '(' {
+                    Token::LParen
+                } else {
+                    Token::RParen
+                },
+            ));
+        } else if c.is_ascii_digit() {
+            let mut end = position;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.' || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let text = source[position..end].replace('_', "");
+            let value = if text.contains('.') {
+                text.parse().map(Value::Float).ok()
+            } else {
+                text.parse().map(Value::Int).ok()
+            };
+            let value = value.ok_or_else(|| ExprError::Parse {
+                position,
+                message: format!("invalid number `{text}`"),
+            })?;
+            tokens.push((position, Token::Number(value)));
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, q)) if q == c => break,
+                    Some((_, ch)) => value.push(ch),
+                    None => {
+                        return Err(ExprError::Parse {
+                            position,
+                            message: "unterminated string".to_string(),
+                        })
+                    }
+                }
+            }
+            tokens.push((position, Token::Str(value)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = position;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((position, Token::Ident(source[position..end].to_string())));
+        } else {
+            let op = OPERATORS
+                .iter()
+                .find(|op| source[position..].starts_with(*op))
+                .filter(|op| **op != "=")
+                .ok_or_else(|| ExprError::Parse {
+                    position,
+                    message: format!("unexpected `{c}`"),
+                })?;
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push((position, Token::Op(op)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn comparison_op(operator: &str) -> Option<Op> {
+    match operator {
+        "==" => Some(Op::Eq),
+        "!=" => Some(Op::Ne),
+        "<=" => Some(Op::Le),
+        ">=" => Some(Op::Ge),
+        "<" => Some(Op::Lt),
+        ">" => Some(Op::Gt),
+        _ => None,
+    }
+}
+
+/// How deep expressions may nest, counting operators and parentheses, so
+/// that parsing and evaluating them can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    /// How many `!`, `-` and `(` the token being parsed is nested in.
+    depth: usize,
+}
+
+/// A parsed node and the height of its tree, the nodes the longest path
+/// from it to a leaf goes through.
+type Parsed = (Node, usize);
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some((_, Token::Op(op))) if ops.contains(op) => {
+                let op = *op;
+                self.position += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses what follows a `!`, `-` or `(`, failing before it goes deeper
+    /// than `MAX_DEPTH`.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Parsed, ExprError>,
+    ) -> Result<Parsed, ExprError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(ExprError::TooDeep);
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    /// Builds the node of an operator, failing if its tree is higher than
+    /// `MAX_DEPTH`, as chains of operators like `a + b + c` make it.
+    fn node(&self, node: Node, height: usize) -> Result<Parsed, ExprError> {
+        if height > MAX_DEPTH {
+            return Err(ExprError::TooDeep);
+        }
+        Ok((node, height))
+    }
+
+    fn binary(&self, op: Op, (left, l): Parsed, (right, r): Parsed) -> Result<Parsed, ExprError> {
+        self.node(
+            Node::Binary(op, Box::new(left), Box::new(right)),
+            l.max(r) + 1,
+        )
+    }
+
+    fn parse_or(&mut self) -> Result<Parsed, ExprError> {
+        let mut parsed = self.parse_and()?;
+        while self.eat_op(&["||"]).is_some() {
+            let right = self.parse_and()?;
+            parsed = self.binary(Op::Or, parsed, right)?;
+        }
+        Ok(parsed)
+    }
+
+    fn parse_and(&mut self) -> Result<Parsed, ExprError> {
+        let mut parsed = self.parse_not()?;
+        while self.eat_op(&["&&"]).is_some() {
+            let right = self.parse_not()?;
+            parsed = self.binary(Op::And, parsed, right)?;
+        }
+        Ok(parsed)
+    }
+
+    fn parse_not(&mut self) -> Result<Parsed, ExprError> {
+        if self.eat_op(&["!"]).is_some() {
+            let (node, height) = self.nested(Self::parse_not)?;
+            return self.node(Node::Not(Box::new(node)), height + 1);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Parsed, ExprError> {
+        let parsed = self.parse_sum()?;
+        let Some(op) = self
+            .eat_op(&["==", "!=", "<=", ">=", "<", ">"])
+            .and_then(comparison_op)
+        else {
+            return Ok(parsed);
+        };
+        let right = self.parse_sum()?;
+        self.binary(op, parsed, right)
+    }
+
+    fn parse_sum(&mut self) -> Result<Parsed, ExprError> {
+        let mut parsed = self.parse_term()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" { Op::Add } else { Op::Sub };
+            let right = self.parse_term()?;
+            parsed = self.binary(op, parsed, right)?;
+        }
+        Ok(parsed)
+    }
+
+    fn parse_term(&mut self) -> Result<Parsed, ExprError> {
+        let mut parsed = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => Op::Mul,
+                "/" => Op::Div,
+                _ => Op::Rem,
+            };
+            let right = self.parse_unary()?;
+            parsed = self.binary(op, parsed, right)?;
+        }
+        Ok(parsed)
+    }
+
+    fn parse_unary(&mut self) -> Result<Parsed, ExprError> {
+        if self.eat_op(&["-"]).is_some() {
+            let (node, height) = self.nested(Self::parse_unary)?;
+            return self.node(Node::Neg(Box::new(node)), height + 1);
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Parsed, ExprError> {
+        let Some((position, token)) = self.tokens.get(self.position).cloned() else {
+            return Err(ExprError::Parse {
+                position: self.end,
+                message: "unexpected end of expression".to_string(),
+            });
+        };
+        self.position += 1;
+
+        match token {
+            Token::Number(value) => Ok((Node::Literal(value), 1)),
+            Token::Str(value) => Ok((Node::Literal(Value::Str(value)), 1)),
+            Token::Ident(name) if name == "true" => Ok((Node::Literal(Value::Bool(true)), 1)),
+            Token::Ident(name) if name == "false" => Ok((Node::Literal(Value::Bool(false)), 1)),
+            Token::Ident(name)
+                if name == "random" && self.peek().is_some_and(|(_, t)| *t == Token::LParen) =>
+            {
+                match self.tokens.get(self.position + 1) {
+                    Some((_, Token::RParen)) => {
+                        self.position += 2;
+                        Ok((Node::Random, 1))
+                    }
+                    _ => Err(ExprError::Parse {
+                        position,
+                        message: "`random` takes no arguments".to_string(),
+                    }),
+                }
+            }
+            Token::Ident(name) => Ok((Node::Key(name), 1)),
+            Token::LParen => {
+                let parsed = self.nested(Self::parse_or)?;
+                match self.tokens.get(self.position) {
+                    Some((_, Token::RParen)) => {
+                        self.position += 1;
+                        Ok(parsed)
+                    }
+                    _ => Err(ExprError::Parse {
+                        position,
+                        message: "unclosed `(`".to_string(),
+                    }),
+                }
+            }
+            token => Err(ExprError::Parse {
+                position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+}
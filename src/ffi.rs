@@ -0,0 +1,625 @@
+//! A C interface to the engine, for hosts written in C or C++.
+//!
+//! Contexts, callback registries and loaded rule sets are opaque handles
+//! created by `dredd_*_new` functions and released by the matching
+//! `dredd_*_free` ones. Rule sets are loaded from JSON, see
+//! `dredd_rs::loader`, and their callback identifiers are resolved against a
+//! registry of C callbacks or of expressions, see `dredd_rs::expr`.
+//!
+//! Functions that can fail return `false` or a null pointer, and the reason
+//! is then available from `dredd_last_error` on the same thread. Strings
+//! returned by the library are owned by the caller and released with
+//! `dredd_string_free`. All strings are NUL-terminated UTF-8.
+//!
+//! The C header is checked in at `include/dredd.h`. It is generated with
+//! cbindgen, `cbindgen --config cbindgen.toml --output include/dredd.h`,
+//! and the tests check that it matches the functions of this module.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::ffi::CString;
+//! use dredd_rs::ffi::*;
+//!
+//! let rules_json = CString::new(r#"{
+//!     "type": "all",
+//!     "state": "active",
+//!     "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }]
+//! }"#).unwrap();
+//!
+//! unsafe extern "C" fn approve(ctx: *mut DreddContext, _: *mut std::ffi::c_void) {
+//!     dredd_context_set_bool(ctx, c"approved".as_ptr(), true);
+//! }
+//!
+//! unsafe {
+//!     let registry = dredd_registry_new();
+//!     dredd_registry_condition_expr(registry, c"is_adult".as_ptr(), c"age >= 18".as_ptr());
+//!     dredd_registry_action(registry, c"approve".as_ptr(), approve, std::ptr::null_mut());
+//!     let rules = dredd_rules_from_json(rules_json.as_ptr(), registry);
+//!
+//!     let ctx = dredd_context_new();
+//!     dredd_context_set_int(ctx, c"age".as_ptr(), 42);
+//!     assert!(dredd_rules_fire(rules, ctx));
+//!
+//!     let mut approved = false;
+//!     assert!(dredd_context_get_bool(ctx, c"approved".as_ptr(), &mut approved));
+//!     assert!(approved);
+//!
+//!     dredd_context_free(ctx);
+//!     dredd_rules_free(rules);
+//!     dredd_registry_free(registry);
+//! }
+//! ```
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use serde_json::Value;
+
+use crate::{
+    expr::Expr,
+    loader::{self, CallbackRegistry, LoadedRules},
+    rule::{GetSet, RuleContext, RuleContextWrapper, RuleError, RuleFailure},
+    scenario::{get_value, intern, set_value},
+};
+
+/// A rule context.
+pub struct DreddContext {
+    rule_context: RuleContextWrapper,
+}
+
+/// Named conditions and actions that rule sets refer to.
+pub struct DreddRegistry {
+    registry: CallbackRegistry,
+}
+
+/// A rule set loaded from JSON.
+pub struct DreddRules {
+    rules: LoadedRules,
+}
+
+/// A condition called with the context of the rule and the `user_data` it
+/// was registered with.
+pub type DreddCondition =
+    unsafe extern "C" fn(ctx: *mut DreddContext, user_data: *mut c_void) -> bool;
+
+/// An action called with the context of the rule and the `user_data` it was
+/// registered with.
+pub type DreddAction = unsafe extern "C" fn(ctx: *mut DreddContext, user_data: *mut c_void);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|error| *error.borrow_mut() = CString::new(message).ok());
+}
+
+/// The reason the last failing call on this thread failed, or null when
+/// none did. The string stays valid until the next failing call on this
+/// thread, and must not be freed.
+#[no_mangle]
+pub extern "C" fn dredd_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Releases a string returned by the library.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the library that was not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Reads a string argument, recording an error when it is null or not UTF-8.
+unsafe fn read_str<'a>(string: *const c_char, name: &str) -> Option<&'a str> {
+    if string.is_null() {
+        set_last_error(format!("`{name}` is null"));
+        return None;
+    }
+    match CStr::from_ptr(string).to_str() {
+        Ok(string) => Some(string),
+        Err(_) => {
+            set_last_error(format!("`{name}` is not valid UTF-8"));
+            None
+        }
+    }
+}
+
+fn into_raw_string(string: String) -> *mut c_char {
+    match CString::new(string) {
+        Ok(string) => string.into_raw(),
+        Err(_) => {
+            set_last_error("the string contains a NUL character");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates an empty context.
+#[no_mangle]
+pub extern "C" fn dredd_context_new() -> *mut DreddContext {
+    Box::into_raw(Box::new(DreddContext {
+        rule_context: RuleContext::new(),
+    }))
+}
+
+/// Releases a context.
+///
+/// # Safety
+///
+/// `ctx` must be null or a context returned by `dredd_context_new` that was
+/// not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_free(ctx: *mut DreddContext) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Sets a key of the context to a value, if the arguments are valid.
+unsafe fn set(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    value: impl FnOnce(&mut RuleContextWrapper, &'static str),
+) -> bool {
+    let Some(ctx) = ctx.as_mut() else {
+        set_last_error("`ctx` is null");
+        return false;
+    };
+    let Some(key) = read_str(key, "key") else {
+        return false;
+    };
+    value(&mut ctx.rule_context, intern(key));
+    true
+}
+
+/// Sets a key to an integer.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_set_int(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    value: i64,
+) -> bool {
+    set(ctx, key, |rule_context, key| rule_context.set(key, value))
+}
+
+/// Sets a key to a floating point number.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_set_float(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    value: f64,
+) -> bool {
+    set(ctx, key, |rule_context, key| rule_context.set(key, value))
+}
+
+/// Sets a key to a boolean.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_set_bool(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    value: bool,
+) -> bool {
+    set(ctx, key, |rule_context, key| rule_context.set(key, value))
+}
+
+/// Sets a key to a string, which is copied.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context, and `key` and `value` NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_set_string(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    value: *const c_char,
+) -> bool {
+    let Some(value) = read_str(value, "value") else {
+        return false;
+    };
+    set(ctx, key, |rule_context, key| {
+        rule_context.set(key, value.to_string())
+    })
+}
+
+/// Sets a key to a value given as JSON. Numbers are stored as integers when
+/// they have no fractional part, and arrays and objects as JSON values.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context, and `key` and `json` NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_set_json(
+    ctx: *mut DreddContext,
+    key: *const c_char,
+    json: *const c_char,
+) -> bool {
+    let Some(json) = read_str(json, "json") else {
+        return false;
+    };
+    let value: Value = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(error) => {
+            set_last_error(error.to_string());
+            return false;
+        }
+    };
+    set(ctx, key, |rule_context, key| {
+        set_value(rule_context, key, &value)
+    })
+}
+
+/// Records a failure of the rule being fired, which stops the run, see
+/// `RuleFailure`. Meant to be called from actions and conditions.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `message` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_fail(
+    ctx: *mut DreddContext,
+    message: *const c_char,
+) -> bool {
+    let Some(ctx) = ctx.as_mut() else {
+        set_last_error("`ctx` is null");
+        return false;
+    };
+    let Some(message) = read_str(message, "message") else {
+        return false;
+    };
+    ctx.rule_context.fail(RuleError::failed(message));
+    true
+}
+
+/// The value of a key as JSON, if the arguments are valid and the key holds
+/// a value JSON can represent.
+unsafe fn get(ctx: *const DreddContext, key: *const c_char) -> Option<Value> {
+    let Some(ctx) = ctx.as_ref() else {
+        set_last_error("`ctx` is null");
+        return None;
+    };
+    let key = read_str(key, "key")?;
+    let rule_context = ctx.rule_context.borrow();
+    let Some(value) = rule_context.lookup(key) else {
+        set_last_error(format!("missing key `{key}`"));
+        return None;
+    };
+    let value = get_value(value.as_ref());
+    if value.is_none() {
+        set_last_error(format!("key `{key}` holds a value of an unsupported type"));
+    }
+    value
+}
+
+/// Writes a value read with `get` to `out`, when it has the expected type.
+unsafe fn get_as<T>(
+    ctx: *const DreddContext,
+    key: *const c_char,
+    out: *mut T,
+    read: impl FnOnce(&Value) -> Option<T>,
+    expected: &str,
+) -> bool {
+    if out.is_null() {
+        set_last_error("`out` is null");
+        return false;
+    }
+    let Some(value) = get(ctx, key) else {
+        return false;
+    };
+    match read(&value) {
+        Some(value) => {
+            *out = value;
+            true
+        }
+        None => {
+            set_last_error(format!("expected {expected}, got {value}"));
+            false
+        }
+    }
+}
+
+/// Reads an integer key into `out`.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_get_int(
+    ctx: *const DreddContext,
+    key: *const c_char,
+    out: *mut i64,
+) -> bool {
+    get_as(ctx, key, out, Value::as_i64, "an integer")
+}
+
+/// Reads a numeric key into `out`.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_get_float(
+    ctx: *const DreddContext,
+    key: *const c_char,
+    out: *mut f64,
+) -> bool {
+    get_as(ctx, key, out, Value::as_f64, "a number")
+}
+
+/// Reads a boolean key into `out`.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context, `key` a NUL-terminated string and `out` a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_get_bool(
+    ctx: *const DreddContext,
+    key: *const c_char,
+    out: *mut bool,
+) -> bool {
+    get_as(ctx, key, out, Value::as_bool, "a boolean")
+}
+
+/// The value of a string key, to be released with `dredd_string_free`, or
+/// null.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_get_string(
+    ctx: *const DreddContext,
+    key: *const c_char,
+) -> *mut c_char {
+    match get(ctx, key) {
+        Some(Value::String(value)) => into_raw_string(value),
+        Some(value) => {
+            set_last_error(format!("expected a string, got {value}"));
+            ptr::null_mut()
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// The value of a key as JSON, to be released with `dredd_string_free`, or
+/// null.
+///
+/// # Safety
+///
+/// `ctx` must be a valid context and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_context_get_json(
+    ctx: *const DreddContext,
+    key: *const c_char,
+) -> *mut c_char {
+    match get(ctx, key) {
+        Some(value) => into_raw_string(value.to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Creates an empty callback registry.
+#[no_mangle]
+pub extern "C" fn dredd_registry_new() -> *mut DreddRegistry {
+    Box::into_raw(Box::new(DreddRegistry {
+        registry: CallbackRegistry::new(),
+    }))
+}
+
+/// Releases a callback registry. Rule sets loaded with it remain usable.
+///
+/// # Safety
+///
+/// `registry` must be null or a registry returned by `dredd_registry_new`
+/// that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_registry_free(registry: *mut DreddRegistry) {
+    if !registry.is_null() {
+        drop(Box::from_raw(registry));
+    }
+}
+
+/// Calls a C callback with a context handle sharing the context of the rule.
+fn with_handle<T>(
+    rule_context: &RuleContextWrapper,
+    callback: impl FnOnce(*mut DreddContext) -> T,
+) -> T {
+    let mut handle = DreddContext {
+        rule_context: rule_context.clone(),
+    };
+    callback(&mut handle)
+}
+
+/// Registers a C function as the condition `name`.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry and `name` a NUL-terminated string.
+/// `user_data` must stay valid for as long as rule sets loaded with the
+/// registry are used. The context handle given to the condition is only
+/// valid during the call.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_registry_condition(
+    registry: *mut DreddRegistry,
+    name: *const c_char,
+    condition: DreddCondition,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(registry) = registry.as_mut() else {
+        set_last_error("`registry` is null");
+        return false;
+    };
+    let Some(name) = read_str(name, "name") else {
+        return false;
+    };
+    registry.registry.condition(name, move |rule_context| {
+        with_handle(rule_context, |ctx| unsafe { condition(ctx, user_data) })
+    });
+    true
+}
+
+/// Registers an expression, see `dredd_rs::expr`, as the condition `name`.
+/// An expression that fails to evaluate doesn't hold.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry, and `name` and `expr`
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_registry_condition_expr(
+    registry: *mut DreddRegistry,
+    name: *const c_char,
+    expr: *const c_char,
+) -> bool {
+    let Some(registry) = registry.as_mut() else {
+        set_last_error("`registry` is null");
+        return false;
+    };
+    let (Some(name), Some(expr)) = (read_str(name, "name"), read_str(expr, "expr")) else {
+        return false;
+    };
+    let expr = match Expr::parse(expr) {
+        Ok(expr) => expr,
+        Err(error) => {
+            set_last_error(format!("invalid condition `{name}`: {error}"));
+            return false;
+        }
+    };
+    registry.registry.condition(name, move |rule_context| {
+        expr.eval(&rule_context.borrow()).unwrap_or(false)
+    });
+    true
+}
+
+/// Registers a C function as the action `name`.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry and `name` a NUL-terminated string.
+/// `user_data` must stay valid for as long as rule sets loaded with the
+/// registry are used. The context handle given to the action is only valid
+/// during the call.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_registry_action(
+    registry: *mut DreddRegistry,
+    name: *const c_char,
+    action: DreddAction,
+    user_data: *mut c_void,
+) -> bool {
+    let Some(registry) = registry.as_mut() else {
+        set_last_error("`registry` is null");
+        return false;
+    };
+    let Some(name) = read_str(name, "name") else {
+        return false;
+    };
+    registry.registry.action(name, move |rule_context| {
+        with_handle(rule_context, |ctx| unsafe { action(ctx, user_data) })
+    });
+    true
+}
+
+/// Loads an active rule set from JSON, resolving its callbacks against the
+/// registry. Returns null when the document is invalid, isn't active or
+/// names an unknown callback.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string and `registry` a valid registry.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_rules_from_json(
+    json: *const c_char,
+    registry: *const DreddRegistry,
+) -> *mut DreddRules {
+    let Some(registry) = registry.as_ref() else {
+        set_last_error("`registry` is null");
+        return ptr::null_mut();
+    };
+    let Some(json) = read_str(json, "json") else {
+        return ptr::null_mut();
+    };
+    match loader::from_json(json, &registry.registry) {
+        Ok(rules) => Box::into_raw(Box::new(DreddRules { rules })),
+        Err(error) => {
+            set_last_error(error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a rule set.
+///
+/// # Safety
+///
+/// `rules` must be null or a rule set returned by `dredd_rules_from_json`
+/// that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_rules_free(rules: *mut DreddRules) {
+    if !rules.is_null() {
+        drop(Box::from_raw(rules));
+    }
+}
+
+/// Fires the rules against the context. Returns `false` when a rule failed,
+/// with the failure in `dredd_last_error`. Panics of the engine are caught
+/// and reported the same way.
+///
+/// # Safety
+///
+/// `rules` must be a valid rule set and `ctx` a valid context.
+#[no_mangle]
+pub unsafe extern "C" fn dredd_rules_fire(
+    rules: *const DreddRules,
+    ctx: *mut DreddContext,
+) -> bool {
+    let (Some(rules), Some(ctx)) = (rules.as_ref(), ctx.as_mut()) else {
+        set_last_error("`rules` or `ctx` is null");
+        return false;
+    };
+    let rule_context = ctx.rule_context.clone();
+    let fired = panic::catch_unwind(AssertUnwindSafe(|| rules.rules.run(rule_context)));
+    if fired.is_err() {
+        set_last_error("a rule panicked");
+        return false;
+    }
+    match ctx.rule_context.take_error() {
+        Some(error) => {
+            set_last_error(error.to_string());
+            false
+        }
+        None => true,
+    }
+}
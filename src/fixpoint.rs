@@ -0,0 +1,99 @@
+use crate::rule::{ContextSnapshot, Rule, RuleContextWrapper, RuleError, RunReport, Wrapper};
+use crate::runner::{RuleRunner, SiblingRunner};
+
+/// How a run to a fixpoint ended, see `Engine::execute_to_fixpoint`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixpointOutcome {
+    /// A pass left the context unchanged.
+    Converged,
+    /// A pass brought the context back to the values it held after
+    /// `repeats_pass` passes, 0 being the context before the first pass, so
+    /// the passes would repeat forever.
+    Cycle { repeats_pass: usize },
+    /// The maximum number of passes was reached while the context was still
+    /// changing.
+    IterationLimit,
+    /// A rule failed; the error is left in the context.
+    Failed(RuleError),
+}
+
+/// The passes of a run to a fixpoint.
+#[derive(Debug, Clone)]
+pub struct FixpointReport {
+    passes: Vec<RunReport>,
+    changed_keys: Vec<Vec<&'static str>>,
+    outcome: FixpointOutcome,
+}
+
+impl FixpointReport {
+    /// The report of every pass, in order.
+    pub fn get_passes(&self) -> &[RunReport] {
+        &self.passes
+    }
+
+    /// The names of the rules executed in every pass.
+    pub fn get_executed_names(&self) -> Vec<Vec<&str>> {
+        self.passes
+            .iter()
+            .map(|pass| pass.get_trace().get_executed_names())
+            .collect()
+    }
+
+    /// The context keys every pass added, removed or changed, sorted.
+    pub fn get_changed_keys(&self) -> &[Vec<&'static str>] {
+        &self.changed_keys
+    }
+
+    pub fn get_outcome(&self) -> &FixpointOutcome {
+        &self.outcome
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.outcome == FixpointOutcome::Converged
+    }
+}
+
+pub(crate) fn execute_to_fixpoint<R: Rule<R>>(
+    rule_context: RuleContextWrapper,
+    rules: Vec<Wrapper<R>>,
+    max_iterations: usize,
+) -> FixpointReport {
+    let mut report = FixpointReport {
+        passes: Vec::new(),
+        changed_keys: Vec::new(),
+        outcome: FixpointOutcome::IterationLimit,
+    };
+    let mut states: Vec<ContextSnapshot> = vec![rule_context.borrow().snapshot()];
+
+    while report.passes.len() < max_iterations {
+        let pass = SiblingRunner::new().run_with_report(rule_context.clone(), rules.clone());
+        let error = pass.get_error().cloned();
+        let state = rule_context.borrow().snapshot();
+        let changed_keys = states.last().unwrap().get_changed_keys(&state);
+        let converged = changed_keys.is_empty();
+        report.passes.push(pass);
+        report.changed_keys.push(changed_keys);
+
+        if let Some(error) = error {
+            report.outcome = FixpointOutcome::Failed(error);
+            break;
+        }
+        if converged {
+            report.outcome = FixpointOutcome::Converged;
+            break;
+        }
+
+        let repeated = states
+            .iter()
+            .position(|earlier| earlier.get_changed_keys(&state).is_empty());
+        if let Some(repeated) = repeated {
+            report.outcome = FixpointOutcome::Cycle {
+                repeats_pass: repeated,
+            };
+            break;
+        }
+        states.push(state);
+    }
+
+    report
+}
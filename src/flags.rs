@@ -0,0 +1,201 @@
+//! Conditions on remotely managed feature flags.
+//!
+//! `FlagProvider` follows the boolean resolution of OpenFeature providers: a
+//! flag key and an evaluation context, holding the targeting key of the
+//! subject, resolve to a value or to an error. An adapter for the OpenFeature
+//! SDK, LaunchDarkly or any other flag service implements it in a few lines.
+//!
+//! `Flags` wraps a provider for use in rules. Resolved values are cached per
+//! flag and targeting key for a configurable time, and when the provider
+//! fails, for example because the flag service is unreachable, the last
+//! value it resolved is used, then the default configured for the flag, then
+//! `false`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use dredd_rs::flags::{EvaluationContext, FlagError, Flags};
+//! use dredd_rs::rule::*;
+//!
+//! let provider = |flag: &str, context: &EvaluationContext| match flag {
+//!     "new_pricing" => Ok(context.get_targeting_key() == Some("beta-user")),
+//!     _ => Err(FlagError::FlagNotFound(flag.to_string())),
+//! };
+//! let flags = Flags::new(provider)
+//!     .with_targeting_key("user_id")
+//!     .with_cache_ttl(Duration::from_secs(30));
+//!
+//! let rule = ChainRule::new()
+//!     .on_eval(flags.flag_enabled("new_pricing"))
+//!     .on_execute(|this| this.get_rule_context().set("pricing", "v2"));
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("user_id", "beta-user".to_string());
+//!
+//! Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+//! assert_eq!(*rule_context.get::<&str>("pricing").unwrap(), "v2");
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::rule::{ContextView, RuleContext};
+use crate::time::Instant;
+
+/// The subject a flag is resolved for.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EvaluationContext {
+    targeting_key: Option<String>,
+}
+
+impl EvaluationContext {
+    pub fn new(targeting_key: Option<String>) -> Self {
+        EvaluationContext { targeting_key }
+    }
+
+    /// The key identifying the subject, such as a user id.
+    pub fn get_targeting_key(&self) -> Option<&str> {
+        self.targeting_key.as_deref()
+    }
+}
+
+/// Why a flag could not be resolved, after the error codes of OpenFeature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagError {
+    FlagNotFound(String),
+    /// The provider can't resolve flags yet or anymore, for example because
+    /// the flag service is unreachable.
+    ProviderNotReady,
+    General(String),
+}
+
+impl fmt::Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagError::FlagNotFound(flag) => write!(f, "flag `{flag}` not found"),
+            FlagError::ProviderNotReady => write!(f, "flag provider not ready"),
+            FlagError::General(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for FlagError {}
+
+/// Resolves boolean flags.
+pub trait FlagProvider {
+    fn resolve_bool(&self, flag: &str, context: &EvaluationContext) -> Result<bool, FlagError>;
+}
+
+impl<F: Fn(&str, &EvaluationContext) -> Result<bool, FlagError>> FlagProvider for F {
+    fn resolve_bool(&self, flag: &str, context: &EvaluationContext) -> Result<bool, FlagError> {
+        self(flag, context)
+    }
+}
+
+type CacheKey = (String, Option<String>);
+
+/// A flag provider with caching and fallbacks, for rule conditions.
+///
+/// Clones share the provider and the cache.
+#[derive(Clone)]
+pub struct Flags {
+    provider: Rc<dyn FlagProvider>,
+    targeting_key: Option<&'static str>,
+    cache_ttl: Duration,
+    defaults: BTreeMap<String, bool>,
+    cache: Rc<RefCell<HashMap<CacheKey, (bool, Instant)>>>,
+}
+
+impl Flags {
+    /// Creates flags resolved with the provider on every check, with no
+    /// targeting key and `false` as the fallback of every flag.
+    pub fn new(provider: impl FlagProvider + 'static) -> Self {
+        Flags {
+            provider: Rc::new(provider),
+            targeting_key: None,
+            cache_ttl: Duration::ZERO,
+            defaults: BTreeMap::new(),
+            cache: Default::default(),
+        }
+    }
+
+    /// Reads the targeting key from a `String` or `&'static str` context value.
+    pub fn with_targeting_key(mut self, key: &'static str) -> Self {
+        self.targeting_key = Some(key);
+        self
+    }
+
+    /// Reuses resolved values for the given time instead of asking the
+    /// provider again.
+    pub fn with_cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Sets the value of a flag when the provider fails and the flag was
+    /// never resolved.
+    pub fn with_default(mut self, flag: &str, value: bool) -> Self {
+        self.defaults.insert(flag.to_string(), value);
+        self
+    }
+
+    /// Whether the flag is enabled for the subject of the context, falling
+    /// back as described in the module documentation.
+    pub fn is_enabled(&self, flag: &str, rule_context: &RuleContext) -> bool {
+        let key = (flag.to_string(), self.get_targeting_key(rule_context));
+        let cached = self.cache.borrow().get(&key).copied();
+        if let Some((value, resolved_at)) = cached {
+            if resolved_at.elapsed() < self.cache_ttl {
+                return value;
+            }
+        }
+
+        let context = EvaluationContext::new(key.1.clone());
+        match self.provider.resolve_bool(flag, &context) {
+            Ok(value) => {
+                self.cache.borrow_mut().insert(key, (value, Instant::now()));
+                value
+            }
+            Err(_) => cached
+                .map(|(value, _)| value)
+                .or_else(|| self.defaults.get(flag).copied())
+                .unwrap_or(false),
+        }
+    }
+
+    /// A rule evaluation function passing when the flag is enabled.
+    pub fn flag_enabled(&self, flag: &str) -> impl Fn(ContextView<'_>) -> bool + 'static {
+        let (flags, flag) = (self.clone(), flag.to_string());
+        move |ctx| flags.is_enabled(&flag, &ctx.get_rule_context().borrow())
+    }
+
+    /// Forgets the resolved values, so that the next checks ask the provider.
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    fn get_targeting_key(&self, rule_context: &RuleContext) -> Option<String> {
+        let value = rule_context.lookup(self.targeting_key?)?;
+        value
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| value.downcast_ref::<&str>().map(|key| key.to_string()))
+    }
+}
+
+impl fmt::Debug for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Flags")
+            .field("targeting_key", &self.targeting_key)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("defaults", &self.defaults)
+            .finish_non_exhaustive()
+    }
+}
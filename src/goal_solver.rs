@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::rule::{
+    ExecutionTrace, Rule, RuleContext, RuleContextWrapper, RuleError, RuleFailure, Wrapper,
+};
+use crate::runner::{RuleRunner, SiblingRunner};
+
+/// Answers whether a goal key can be established, working backwards from the
+/// goal through the rules that write it.
+///
+/// Rules declare the context keys their execution writes with
+/// `RuleMetadata::with_writes` and the keys their evaluation reads with
+/// `RuleMetadata::with_reads`. To solve a goal, the solver takes the rules
+/// writing it in the order they were given. Before firing one, it solves the
+/// keys the rule reads that are missing from the context the same way, as
+/// subgoals, and skips the rule when one of them can't be established.
+/// Rules that are not needed for the goal are never fired.
+///
+/// A key is established once it is in the context, or in one of its layers,
+/// unless it holds `false`. Each rule is fired at most once per solve, with
+/// its children, and a key that is already being solved further up is not
+/// established again, so cyclic declarations end. A failing rule stops the
+/// solve.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let solver = GoalSolver::new(vec![
+///     AllRule::new()
+///         .with_name("flu")
+///         .with_reads(&["fever", "aches"])
+///         .with_writes(&["diagnosis"])
+///         .on_eval(|ctx| *ctx.get::<bool>("fever").unwrap() && *ctx.get::<bool>("aches").unwrap())
+///         .on_execute(|this| this.get_rule_context().set("diagnosis", "flu")),
+///     AllRule::new()
+///         .with_name("fever")
+///         .with_reads(&["temperature"])
+///         .with_writes(&["fever"])
+///         .on_execute(|this| {
+///             let fever = *this.get_rule_context().get::<f64>("temperature").unwrap() > 37.8;
+///             this.get_rule_context().set("fever", fever);
+///         }),
+/// ]);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("temperature", 38.5);
+/// rule_context.set("aches", true);
+/// let report = solver.solve(rule_context.clone(), "diagnosis");
+///
+/// assert!(report.is_established());
+/// assert_eq!(report.get_trace().get_executed_names(), vec!["fever", "flu"]);
+/// assert_eq!(*rule_context.get::<&str>("diagnosis").unwrap(), "flu");
+///
+/// // On an empty context, the solver tells which facts are missing.
+/// let report = solver.solve(RuleContext::new(), "diagnosis");
+/// assert!(!report.is_established());
+/// assert_eq!(report.get_missing(), ["aches", "temperature"]);
+/// ```
+pub struct GoalSolver<R> {
+    rules: Vec<Wrapper<R>>,
+    producers: HashMap<String, Vec<usize>>,
+}
+
+impl<R: Rule<R>> GoalSolver<R> {
+    pub fn new(rules: Vec<Wrapper<R>>) -> Self {
+        let mut producers: HashMap<String, Vec<usize>> = HashMap::new();
+        for (position, rule) in rules.iter().enumerate() {
+            for key in rule.borrow().get_writes() {
+                producers.entry(key.clone()).or_default().push(position);
+            }
+        }
+
+        GoalSolver { rules, producers }
+    }
+
+    /// The rules declaring that they write the key, in order.
+    pub fn get_producers(&self, key: &str) -> Vec<Wrapper<R>> {
+        self.producers
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|&position| self.rules[position].clone())
+            .collect()
+    }
+
+    /// Fires the rules needed to establish the goal, and reports whether it
+    /// was established.
+    pub fn solve(&self, rule_context: RuleContextWrapper, goal: &str) -> GoalReport {
+        let start = rule_context.borrow_mut().start_trace();
+        let mut solve = Solve {
+            solver: self,
+            rule_context: rule_context.clone(),
+            tried: HashSet::new(),
+            solving: Vec::new(),
+            missing: Vec::new(),
+        };
+        let established = solve.establish(goal);
+        let trace = rule_context.borrow_mut().finish_trace(start);
+        let mut missing = solve.missing;
+        missing.sort();
+        missing.dedup();
+
+        GoalReport {
+            established,
+            trace,
+            missing,
+            error: rule_context.get_error(),
+        }
+    }
+}
+
+/// The state of one `GoalSolver::solve`.
+struct Solve<'a, R> {
+    solver: &'a GoalSolver<R>,
+    rule_context: RuleContextWrapper,
+    tried: HashSet<usize>,
+    solving: Vec<String>,
+    missing: Vec<String>,
+}
+
+impl<R: Rule<R>> Solve<'_, R> {
+    fn establish(&mut self, key: &str) -> bool {
+        if is_established(&self.rule_context.borrow(), key) {
+            return true;
+        }
+        if self.solving.iter().any(|solving| solving == key) {
+            return false;
+        }
+        let Some(producers) = self.solver.producers.get(key) else {
+            self.missing.push(key.to_string());
+            return false;
+        };
+
+        self.solving.push(key.to_string());
+        let mut established = false;
+        for &position in producers {
+            if self.rule_context.has_failed() {
+                break;
+            }
+            if self.tried.contains(&position) {
+                continue;
+            }
+            let rule = self.solver.rules[position].clone();
+            let reads = rule.borrow().get_reads().to_vec();
+            // Every read is solved, rather than stopping at the first missing
+            // one, so that the report lists all the missing facts.
+            let mut missing_reads = false;
+            for read in &reads {
+                let present = self.rule_context.borrow().lookup(read).is_some();
+                if !present && !self.establish(read) {
+                    missing_reads = true;
+                }
+            }
+            // A rule whose reads are missing is tried again when another
+            // goal needs it, its reads being established since.
+            if missing_reads || self.rule_context.has_failed() {
+                continue;
+            }
+
+            self.tried.insert(position);
+            SiblingRunner::new().run(self.rule_context.clone(), vec![rule]);
+            if is_established(&self.rule_context.borrow(), key) {
+                established = true;
+                break;
+            }
+        }
+        self.solving.pop();
+        established
+    }
+}
+
+fn is_established(rule_context: &RuleContext, key: &str) -> bool {
+    rule_context
+        .lookup(key)
+        .is_some_and(|value| value.downcast_ref::<bool>() != Some(&false))
+}
+
+/// The outcome of `GoalSolver::solve`.
+#[derive(Debug, Clone)]
+pub struct GoalReport {
+    established: bool,
+    trace: ExecutionTrace,
+    missing: Vec<String>,
+    error: Option<RuleError>,
+}
+
+impl GoalReport {
+    /// Whether the goal is in the context, and not `false`.
+    pub fn is_established(&self) -> bool {
+        self.established
+    }
+
+    /// The rules fired while solving the goal.
+    pub fn get_trace(&self) -> &ExecutionTrace {
+        &self.trace
+    }
+
+    /// The keys, sorted, that were missing from the context and that no rule
+    /// writes, such as facts to ask for before solving again.
+    pub fn get_missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// The failure that stopped the solve, which is left in the context.
+    pub fn get_error(&self) -> Option<&RuleError> {
+        self.error.as_ref()
+    }
+}
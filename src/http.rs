@@ -0,0 +1,405 @@
+//! An action calling an HTTP service from a rule.
+//!
+//! The URL, headers and body of the request are templates in which `{key}`
+//! is replaced by the value of the context key `key`. Values are
+//! percent-encoded in the URL and inserted as JSON in the body, so that a
+//! string is quoted and escaped. Values taken from the JSON response by
+//! pointer, such as `/data/score`, are written back to the context with the
+//! same types as in scenario files.
+//!
+//! Requests are only sent to the origins allowed with `allow_origin`: after
+//! rendering, the scheme, host and port of the URL must be exactly one of
+//! them. Redirects aren't followed, since they could lead to any origin, and
+//! their 3xx statuses fail like any other. Any other URL, a missing key, a
+//! transport error or a status that isn't 2xx makes the rule fail. Transport
+//! errors and 5xx statuses are failures of optional infrastructure, on which
+//! a run may degrade instead, see `DegradationPolicy`.
+//!
+//! Requests are sent by an `HttpClient`. `UreqClient`, built with a timeout,
+//! sends them with `ureq`; other clients, or stubs in tests, implement the
+//! trait.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::http::{HttpAction, HttpRequest, HttpResponse};
+//! use dredd_rs::rule::*;
+//!
+//! let client = |request: &HttpRequest| {
+//!     assert_eq!(request.get_url(), "https://scores.example.com/users/ana%20maria");
+//!     assert_eq!(request.get_body(), Some(r#"{"amount": 250}"#));
+//!     Ok(HttpResponse::new(200, r#"{"risk": {"score": 0.7}}"#))
+//! };
+//!
+//! let action = HttpAction::post(client, "https://scores.example.com/users/{user}")
+//!     .allow_origin("https://scores.example.com")
+//!     .with_header("Content-Type", "application/json")
+//!     .with_body(r#"{"amount": {amount}}"#)
+//!     .map_response("/risk/score", "risk_score");
+//!
+//! let rule = ChainRule::new().on_execute(action.callback());
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("user", "ana maria");
+//! rule_context.set("amount", 250u32);
+//!
+//! Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+//! assert_eq!(*rule_context.get::<f64>("risk_score").unwrap(), 0.7);
+//! ```
+
+use std::{fmt, rc::Rc, time::Duration};
+
+use serde_json::Value;
+
+use crate::{
+    rule::{degradation::ActionError, GetSet, Rule, RuleContext, RuleContextWrapper, RuleError},
+    scenario::{get_value, set_value},
+};
+
+/// A rendered request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn get_method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn get_body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    status: u16,
+    body: String,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        HttpResponse {
+            status,
+            body: body.into(),
+        }
+    }
+
+    pub fn get_status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn get_body(&self) -> &str {
+        &self.body
+    }
+}
+
+/// Sends requests. Errors are transport errors; responses with any status
+/// are returned as responses.
+pub trait HttpClient {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String>;
+}
+
+impl<F: Fn(&HttpRequest) -> Result<HttpResponse, String>> HttpClient for F {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        self(request)
+    }
+}
+
+/// Sends requests with `ureq`.
+#[derive(Debug, Clone)]
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+impl UreqClient {
+    /// Creates a client giving up on requests after `timeout`, including
+    /// connecting and reading the response. Redirects are returned as
+    /// responses rather than followed, so that an allowed origin can't send
+    /// the request to one that isn't.
+    pub fn new(timeout: Duration) -> Self {
+        UreqClient {
+            agent: ureq::AgentBuilder::new()
+                .timeout(timeout)
+                .redirects(0)
+                .build(),
+        }
+    }
+}
+
+impl HttpClient for UreqClient {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let mut call = self.agent.request(&request.method, &request.url);
+        for (name, value) in &request.headers {
+            call = call.set(name, value);
+        }
+        let result = match &request.body {
+            Some(body) => call.send_string(body),
+            None => call.call(),
+        };
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => return Err(err.to_string()),
+        };
+        let status = response.status();
+        let body = response.into_string().map_err(|err| err.to_string())?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// A templated HTTP call writing parts of its response to the context.
+#[derive(Clone)]
+pub struct HttpAction {
+    client: Rc<dyn HttpClient>,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    allowed_origins: Vec<String>,
+    mappings: Vec<(String, &'static str)>,
+    status_key: Option<&'static str>,
+}
+
+impl HttpAction {
+    /// Creates an action sending a request with the given method. No origin
+    /// is allowed until some are given.
+    pub fn new(client: impl HttpClient + 'static, method: &str, url: &str) -> Self {
+        HttpAction {
+            client: Rc::new(client),
+            method: method.to_uppercase(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+            allowed_origins: Vec::new(),
+            mappings: Vec::new(),
+            status_key: None,
+        }
+    }
+
+    pub fn get(client: impl HttpClient + 'static, url: &str) -> Self {
+        Self::new(client, "GET", url)
+    }
+
+    pub fn post(client: impl HttpClient + 'static, url: &str) -> Self {
+        Self::new(client, "POST", url)
+    }
+
+    /// Allows requests to an origin, such as `https://api.example.com` or
+    /// `http://localhost:8080`.
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        self.allowed_origins
+            .push(origin.trim_end_matches('/').to_lowercase());
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, template: &str) -> Self {
+        self.headers.push((name.to_string(), template.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, template: &str) -> Self {
+        self.body = Some(template.to_string());
+        self
+    }
+
+    /// Writes the value at a JSON pointer of the response body to a context
+    /// key. A pointer missing from the response makes the rule fail.
+    pub fn map_response(mut self, pointer: &str, key: &'static str) -> Self {
+        self.mappings.push((pointer.to_string(), key));
+        self
+    }
+
+    /// Writes the status of the response, as an `i64`, to a context key.
+    pub fn with_status_key(mut self, key: &'static str) -> Self {
+        self.status_key = Some(key);
+        self
+    }
+
+    /// Renders the request from the context, checking its origin.
+    pub fn render(&self, rule_context: &RuleContext) -> Result<HttpRequest, RuleError> {
+        let url = render(&self.url, rule_context, Encoding::Url)?;
+        if !self.allowed_origins.contains(&get_origin(&url)) {
+            return Err(RuleError::failed(format!(
+                "origin of `{url}` is not allowed"
+            )));
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, template)| {
+                let value = render(template, rule_context, Encoding::Header)?;
+                Ok((name.clone(), value))
+            })
+            .collect::<Result<_, RuleError>>()?;
+        let body = self
+            .body
+            .as_ref()
+            .map(|template| render(template, rule_context, Encoding::Json))
+            .transpose()?;
+
+        Ok(HttpRequest {
+            method: self.method.clone(),
+            url,
+            headers,
+            body,
+        })
+    }
+
+    /// Sends the request and maps the response to the context.
+    pub fn run(&self, rule_context: RuleContextWrapper) -> Result<(), RuleError> {
+        self.try_run(rule_context).map_err(ActionError::into_error)
+    }
+
+    fn try_run(&self, mut rule_context: RuleContextWrapper) -> Result<(), ActionError> {
+        let request = self.render(&rule_context.borrow())?;
+        let response = self.client.send(&request).map_err(|err| {
+            ActionError::Unavailable(RuleError::failed(format!(
+                "{} {}: {err}",
+                request.method, request.url
+            )))
+        })?;
+
+        if let Some(key) = self.status_key {
+            rule_context.set(key, response.status as i64);
+        }
+        if !(200..300).contains(&response.status) {
+            let error = RuleError::failed(format!(
+                "{} {}: status {}",
+                request.method, request.url, response.status
+            ));
+            return Err(match response.status {
+                500.. => ActionError::Unavailable(error),
+                _ => ActionError::Failed(error),
+            });
+        }
+        if self.mappings.is_empty() {
+            return Ok(());
+        }
+
+        let body: Value = serde_json::from_str(&response.body).map_err(|err| {
+            RuleError::failed(format!(
+                "{} {}: invalid JSON: {err}",
+                request.method, request.url
+            ))
+        })?;
+        for (pointer, key) in &self.mappings {
+            let value = body.pointer(pointer).ok_or_else(|| {
+                RuleError::failed(format!(
+                    "`{pointer}` missing from the response of {}",
+                    request.url
+                ))
+            })?;
+            set_value(&mut rule_context, key, value);
+        }
+        Ok(())
+    }
+
+    /// An execute callback running the action, recording its error as the
+    /// failure of the rule, or a transport error or 5xx status with
+    /// `RuleFailure::fail_unavailable`.
+    pub fn callback<R: Rule<R>>(&self) -> impl Fn(&mut R) + 'static {
+        let action = self.clone();
+        move |this| {
+            if let Err(error) = action.try_run(this.get_rule_context()) {
+                error.record(this.get_rule_context());
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HttpAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpAction")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("allowed_origins", &self.allowed_origins)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Url,
+    Header,
+    Json,
+}
+
+/// Replaces every `{key}` of the template, where the key is made of ASCII
+/// letters, digits and `_`. Other braces are kept as they are.
+fn render(
+    template: &str,
+    rule_context: &RuleContext,
+    encoding: Encoding,
+) -> Result<String, RuleError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|&end| end > 0 && after[end..].starts_with('}'));
+        let Some(end) = end else {
+            rendered.push('{');
+            rest = after;
+            continue;
+        };
+
+        let key = &after[..end];
+        let value = rule_context
+            .lookup(key)
+            .and_then(|value| get_value(value.as_ref()))
+            .ok_or_else(|| RuleError::failed(format!("missing template key `{key}`")))?;
+        rendered.push_str(&encode(&value, encoding)?);
+        rest = &after[end + 1..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+fn encode(value: &Value, encoding: Encoding) -> Result<String, RuleError> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    match encoding {
+        Encoding::Json => Ok(value.to_string()),
+        Encoding::Header if text.contains(['\r', '\n']) => {
+            Err(RuleError::failed("header values can't contain line breaks"))
+        }
+        Encoding::Header => Ok(text),
+        Encoding::Url => Ok(text
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (byte as char).to_string()
+                }
+                byte => format!("%{byte:02X}"),
+            })
+            .collect()),
+    }
+}
+
+/// The scheme and authority of a URL, lowercased.
+fn get_origin(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return String::new();
+    };
+    let authority = rest.find(['/', '?', '#']).map_or(rest, |end| &rest[..end]);
+    format!("{scheme}://{authority}").to_lowercase()
+}
@@ -0,0 +1,155 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use crate::rule::{Rule, RuleContext, RuleContextWrapper, RunReport, Wrapper};
+use crate::runner::{RuleRunner, SiblingRunner};
+
+/// Runs a large set of independent rules, firing only the rules whose
+/// condition may have changed since the previous run.
+///
+/// Each rule declares the context keys its evaluation reads with
+/// `RuleMetadata::with_reads`. The engine indexes the rules by those keys
+/// and, on every run, looks up the keys whose values were set or removed
+/// since the previous run: only the rules reading one of them are fired,
+/// along with the rules that declare no keys, which are fired every time.
+/// The first run fires every rule, and so does a run with another context
+/// than the previous one.
+///
+/// A key counts as changed when it holds another value than at the start of
+/// the previous run, even if that value is equal to the old one. Rules are
+/// fired in the order they were given, like `AllRuleRunner` fires siblings,
+/// with their children, and the run stops at the first failure.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let engine = IndexedEngine::new(vec![
+///     AllRule::new()
+///         .with_name("adult")
+///         .with_reads(&["age"])
+///         .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 18),
+///     AllRule::new()
+///         .with_name("domestic")
+///         .with_reads(&["country"])
+///         .on_eval(|ctx| *ctx.get::<&str>("country").unwrap() == "BR"),
+/// ]);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 20i64);
+/// rule_context.set("country", "BR");
+/// let report = engine.run_with_report(rule_context.clone());
+/// assert_eq!(report.get_trace().get_executed_names(), vec!["adult", "domestic"]);
+///
+/// // Only the rule reading `country` is fired again.
+/// rule_context.set("country", "AR");
+/// let report = engine.run_with_report(rule_context.clone());
+/// assert_eq!(report.get_trace().get_entries().len(), 1);
+/// assert!(report.get_trace().get_executed_names().is_empty());
+/// ```
+pub struct IndexedEngine<R> {
+    rules: Vec<Wrapper<R>>,
+    index: HashMap<String, Vec<usize>>,
+    unindexed: Vec<usize>,
+    last_values: RefCell<Option<LastValues>>,
+}
+
+/// The values seen by the previous run, and the context they were read from.
+struct LastValues {
+    rule_context: Weak<RefCell<RuleContext>>,
+    values: HashMap<String, Rc<dyn Any>>,
+}
+
+impl<R: Rule<R> + 'static> IndexedEngine<R> {
+    pub fn new(rules: Vec<Wrapper<R>>) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut unindexed = Vec::new();
+        for (position, rule) in rules.iter().enumerate() {
+            let rule = rule.borrow();
+            if rule.get_reads().is_empty() {
+                unindexed.push(position);
+            }
+            for key in rule.get_reads() {
+                index.entry(key.clone()).or_default().push(position);
+            }
+        }
+
+        IndexedEngine {
+            rules,
+            index,
+            unindexed,
+            last_values: RefCell::new(None),
+        }
+    }
+
+    pub fn run(&self, rule_context: RuleContextWrapper) {
+        let rules = self.get_rules_to_fire(&rule_context);
+        SiblingRunner::new().run(rule_context, rules);
+    }
+
+    /// Runs the rules like `run` and reports the rules that were fired.
+    pub fn run_with_report(&self, rule_context: RuleContextWrapper) -> RunReport {
+        let rules = self.get_rules_to_fire(&rule_context);
+        SiblingRunner::new().run_with_report(rule_context, rules)
+    }
+
+    /// Forgets the values seen by the previous run, so that the next run
+    /// fires every rule.
+    pub fn reset(&self) {
+        self.last_values.replace(None);
+    }
+
+    /// The number of rules that declare no keys and are fired on every run.
+    pub fn get_unindexed_count(&self) -> usize {
+        self.unindexed.len()
+    }
+
+    fn get_rules_to_fire(&self, rule_context: &RuleContextWrapper) -> Vec<Wrapper<R>> {
+        let values: HashMap<String, Rc<dyn Any>> = {
+            let rule_context = rule_context.borrow();
+            self.index
+                .keys()
+                .filter_map(|key| {
+                    let value = rule_context.lookup_resolved(rule_context.resolve_alias(key))?;
+                    Some((key.clone(), value))
+                })
+                .collect()
+        };
+        let last_values = self.last_values.replace(Some(LastValues {
+            rule_context: Rc::downgrade(rule_context),
+            values: values.clone(),
+        }));
+        // Values shared with another context, such as a clone of its map,
+        // would look unchanged.
+        let last_values = last_values
+            .filter(|last| Weak::ptr_eq(&last.rule_context, &Rc::downgrade(rule_context)))
+            .map(|last| last.values);
+
+        let mut positions = match last_values {
+            None => (0..self.rules.len()).collect(),
+            Some(last_values) => {
+                let mut positions = self.unindexed.clone();
+                for (key, rules) in &self.index {
+                    let changed = match (last_values.get(key), values.get(key)) {
+                        (Some(last), Some(value)) => !Rc::ptr_eq(last, value),
+                        (last, value) => last.is_some() != value.is_some(),
+                    };
+                    if changed {
+                        positions.extend(rules);
+                    }
+                }
+                positions
+            }
+        };
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+            .into_iter()
+            .map(|position| self.rules[position].clone())
+            .collect()
+    }
+}
@@ -0,0 +1,537 @@
+//! Post-mortem inspection of recorded runs.
+//!
+//! `RunInspector::record` runs rules like `RuleRunner::run_with_report`, and
+//! also records the values every rule fired set in the context. The
+//! recording can be saved as JSON next to a production decision and loaded
+//! again later, to go back to the context as it was at any step of the run,
+//! find which rule produced a value, and see how the evaluations went up to
+//! that point.
+//!
+//! Steps are the rules fired, in the order of the execution trace. The
+//! context "as of" a step is the context once its rule, children included,
+//! was done firing. Only values JSON can represent are recorded, and
+//! recording converts the whole context to JSON around every rule fired, so
+//! it is meant for the runs worth investigating rather than for every run.
+//!
+//! `replay_against` feeds the inputs of a recording through a changed rule
+//! set and tells, step by step, where the two runs diverge.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::inspector::RunInspector;
+//! use dredd_rs::rule::*;
+//!
+//! let rules = vec![
+//!     AllRule::new()
+//!         .with_name("score")
+//!         .on_execute(|this| this.get_rule_context().set("score", 640i64)),
+//!     AllRule::new()
+//!         .with_name("approve")
+//!         .on_eval(|ctx| *ctx.get::<i64>("score").unwrap() > 600)
+//!         .on_execute(|this| this.get_rule_context().set("approved", true)),
+//! ];
+//!
+//! let recording = RunInspector::record(&Engine::all_runner(), RuleContext::new(), rules).to_json();
+//!
+//! let inspector = RunInspector::from_json(&recording).unwrap();
+//! assert_eq!(inspector.state_at(0).unwrap(), serde_json::json!({ "score": 640 }).as_object().unwrap().clone());
+//! assert_eq!(inspector.produced_by("approved", 1).unwrap().get_name(), Some("approve"));
+//! assert!(inspector.produced_by("approved", 0).is_none());
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    rule::{ExecutionTrace, RuleContext, RuleContextMap, RuleContextWrapper, RuleRunner, Wrapper},
+    scenario::{get_value, intern, set_value},
+};
+
+/// A rule fired during a recorded run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedStep {
+    index: usize,
+    id: Option<String>,
+    name: Option<String>,
+    depth: usize,
+    eval_result: bool,
+    outcome: String,
+    /// The number of changes made once the rule was done firing.
+    changes_end: usize,
+}
+
+impl RecordedStep {
+    /// The position of the step in the run.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Nesting level of the rule, `0` for the rules passed to the runner.
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// What `on_eval()` returned.
+    pub fn get_eval_result(&self) -> bool {
+        self.eval_result
+    }
+
+    /// How firing the rule turned out, as displayed by `RuleOutcome`.
+    pub fn get_outcome(&self) -> &str {
+        &self.outcome
+    }
+}
+
+/// A value set during a recorded run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedChange {
+    step: Option<usize>,
+    key: String,
+    value: Value,
+}
+
+impl RecordedChange {
+    /// The step whose rule made the change, `None` for changes made outside
+    /// of any rule, by the runner.
+    pub fn get_step(&self) -> Option<usize> {
+        self.step
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get_value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// A recorded run, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunInspector {
+    initial: Map<String, Value>,
+    steps: Vec<RecordedStep>,
+    changes: Vec<RecordedChange>,
+}
+
+impl RunInspector {
+    /// Runs the rules with the runner, recording the changes every rule
+    /// makes to the context.
+    pub fn record<T, Runner>(
+        runner: &Runner,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<T>>,
+    ) -> Self
+    where
+        Runner: RuleRunner<RuleType = T>,
+    {
+        let previous = rule_context.borrow_mut().start_recording();
+        let report = runner.run_with_report(rule_context.clone(), rules);
+        let recorder = rule_context.borrow_mut().finish_recording(previous);
+        recorder.into_inspector(report.get_trace())
+    }
+
+    /// Loads a recording saved with `to_json`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("recordings only hold JSON values")
+    }
+
+    pub fn get_steps(&self) -> &[RecordedStep] {
+        &self.steps
+    }
+
+    /// Every change made during the run, in the order it was made.
+    pub fn get_changes(&self) -> &[RecordedChange] {
+        &self.changes
+    }
+
+    /// The context the run started from.
+    pub fn get_initial_state(&self) -> &Map<String, Value> {
+        &self.initial
+    }
+
+    /// The context the run ended with.
+    pub fn get_final_state(&self) -> Map<String, Value> {
+        self.apply(self.changes.len())
+    }
+
+    /// The context as of a step: once its rule, children included, was done
+    /// firing. `None` when the run has no such step.
+    pub fn state_at(&self, step: usize) -> Option<Map<String, Value>> {
+        let step = self.steps.get(step)?;
+        Some(self.apply(step.changes_end))
+    }
+
+    /// The step whose rule set the value a key holds as of a step. `None`
+    /// when the key held its initial value, or was set by the runner rather
+    /// than by a rule.
+    pub fn produced_by(&self, key: &str, step: usize) -> Option<&RecordedStep> {
+        let end = self.steps.get(step)?.changes_end;
+        let change = self.changes[..end]
+            .iter()
+            .rev()
+            .find(|change| change.key == key)?;
+        self.steps.get(change.step?)
+    }
+
+    /// The steps evaluated up to and including a step, whose
+    /// `get_eval_result` tells how their evaluation went.
+    pub fn eval_results_until(&self, step: usize) -> Vec<&RecordedStep> {
+        self.steps.iter().take(step.saturating_add(1)).collect()
+    }
+
+    /// The last values set by the rule of a step.
+    fn produced_at(&self, step: usize) -> Map<String, Value> {
+        self.changes
+            .iter()
+            .filter(|change| change.step == Some(step))
+            .map(|change| (change.key.clone(), change.value.clone()))
+            .collect()
+    }
+
+    fn apply(&self, end: usize) -> Map<String, Value> {
+        let mut state = self.initial.clone();
+        for change in &self.changes[..end] {
+            state.insert(change.key.clone(), change.value.clone());
+        }
+        state
+    }
+}
+
+/// Feeds the inputs of a recorded run through another rule set, usually a
+/// changed version of the one recorded, and compares the two runs step by
+/// step, to find which rule change altered a past decision.
+///
+/// Steps are matched by the id of their rule, or its name, so the rules
+/// recorded should be named or given ids. Inputs are restored the way JSON
+/// values are set by the loaders: integers as `i64`, other numbers as
+/// `f64`, and strings as `String`.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::inspector::{self, RunInspector};
+/// use dredd_rs::rule::*;
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// fn rules(threshold: i64) -> Vec<Rc<RefCell<AllRule>>> {
+///     vec![
+///         AllRule::new()
+///             .with_name("approve")
+///             .on_eval(move |ctx| *ctx.get::<i64>("score").unwrap() > threshold)
+///             .on_execute(|this| this.get_rule_context().set("approved", true)),
+///     ]
+/// }
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("score", 650i64);
+/// let recorded = RunInspector::record(&Engine::all_runner(), rule_context, rules(600));
+///
+/// let diff = inspector::replay_against(&recorded, &Engine::all_runner(), rules(700));
+///
+/// let first = diff.get_first_difference().unwrap();
+/// assert_eq!(first.get_rule(), "approve");
+/// assert_eq!(first.get_original().unwrap().get_outcome(), "fired");
+/// assert_eq!(first.get_replayed().unwrap().get_outcome(), "not applicable");
+/// assert_eq!(diff.get_changed_keys(), vec!["approved"]);
+/// ```
+pub fn replay_against<T, Runner>(
+    recording: &RunInspector,
+    runner: &Runner,
+    rules: Vec<Wrapper<T>>,
+) -> ReplayDiff
+where
+    Runner: RuleRunner<RuleType = T>,
+{
+    let mut rule_context = RuleContext::new();
+    for (key, value) in &recording.initial {
+        set_value(&mut rule_context, intern(key), value);
+    }
+    let replayed = RunInspector::record(runner, rule_context, rules);
+    ReplayDiff::new(recording, replayed)
+}
+
+/// How a replayed run differs from the recorded one, see `replay_against`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDiff {
+    replayed: RunInspector,
+    differences: Vec<StepDiff>,
+    changed_keys: Vec<String>,
+}
+
+impl ReplayDiff {
+    fn new(original: &RunInspector, replayed: RunInspector) -> Self {
+        let original_steps = identify(original);
+        let replayed_steps = identify(&replayed);
+
+        let mut differences = Vec::new();
+        for (rule, step) in &original_steps {
+            let matched = replayed_steps.iter().find(|(other, _)| other == rule);
+            let replayed_step = matched.map(|(_, step)| *step);
+            let values = diff_values(
+                &original.produced_at(step.index),
+                &replayed_step
+                    .map(|step| replayed.produced_at(step.index))
+                    .unwrap_or_default(),
+            );
+            let same_outcome = replayed_step.is_some_and(|other| {
+                other.eval_result == step.eval_result && other.outcome == step.outcome
+            });
+            if !same_outcome || !values.is_empty() {
+                differences.push(StepDiff {
+                    rule: rule.clone(),
+                    original: Some((*step).clone()),
+                    replayed: replayed_step.cloned(),
+                    values,
+                });
+            }
+        }
+        for (rule, step) in &replayed_steps {
+            if !original_steps.iter().any(|(other, _)| other == rule) {
+                differences.push(StepDiff {
+                    rule: rule.clone(),
+                    original: None,
+                    replayed: Some((*step).clone()),
+                    values: diff_values(&Map::new(), &replayed.produced_at(step.index)),
+                });
+            }
+        }
+        differences.sort_by_key(|diff| diff.get_position());
+
+        let changed_keys = diff_values(&original.get_final_state(), &replayed.get_final_state())
+            .into_iter()
+            .map(|diff| diff.key)
+            .collect();
+        ReplayDiff {
+            replayed,
+            differences,
+            changed_keys,
+        }
+    }
+
+    /// The recording of the replayed run.
+    pub fn get_replayed(&self) -> &RunInspector {
+        &self.replayed
+    }
+
+    /// Whether both runs fired the same rules with the same outcomes, and
+    /// ended with the same context.
+    pub fn is_identical(&self) -> bool {
+        self.differences.is_empty() && self.changed_keys.is_empty()
+    }
+
+    /// The steps that differ, in the order they were fired.
+    pub fn get_differences(&self) -> &[StepDiff] {
+        &self.differences
+    }
+
+    /// The earliest step that differs, where the decisions started to
+    /// diverge.
+    pub fn get_first_difference(&self) -> Option<&StepDiff> {
+        self.differences.first()
+    }
+
+    /// The keys whose final values differ, sorted.
+    pub fn get_changed_keys(&self) -> Vec<&str> {
+        self.changed_keys.iter().map(String::as_str).collect()
+    }
+}
+
+/// A rule that was fired differently by the replayed run: with another
+/// outcome, producing other values, or only in one of the runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepDiff {
+    rule: String,
+    original: Option<RecordedStep>,
+    replayed: Option<RecordedStep>,
+    values: Vec<ValueDiff>,
+}
+
+impl StepDiff {
+    /// The id or the name of the rule, or its step number for rules that
+    /// have neither.
+    pub fn get_rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// The step in the recorded run, `None` when the rule was only fired by
+    /// the replayed run.
+    pub fn get_original(&self) -> Option<&RecordedStep> {
+        self.original.as_ref()
+    }
+
+    /// The step in the replayed run, `None` when the rule was only fired by
+    /// the recorded run.
+    pub fn get_replayed(&self) -> Option<&RecordedStep> {
+        self.replayed.as_ref()
+    }
+
+    /// The values the rule set differently.
+    pub fn get_values(&self) -> &[ValueDiff] {
+        &self.values
+    }
+
+    fn get_position(&self) -> usize {
+        self.replayed
+            .as_ref()
+            .or(self.original.as_ref())
+            .map_or(0, |step| step.index)
+    }
+}
+
+/// A key set to different values by two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDiff {
+    key: String,
+    original: Option<Value>,
+    replayed: Option<Value>,
+}
+
+impl ValueDiff {
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    /// The value in the recorded run, `None` when it was not set.
+    pub fn get_original(&self) -> Option<&Value> {
+        self.original.as_ref()
+    }
+
+    /// The value in the replayed run, `None` when it was not set.
+    pub fn get_replayed(&self) -> Option<&Value> {
+        self.replayed.as_ref()
+    }
+}
+
+/// Names the steps of a run after their rules, numbering the repeated names
+/// so that rules fired more than once are matched in order.
+fn identify(run: &RunInspector) -> Vec<(String, &RecordedStep)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    run.steps
+        .iter()
+        .map(|step| {
+            let rule = step
+                .id
+                .clone()
+                .or_else(|| step.name.clone())
+                .unwrap_or_else(|| format!("step {}", step.index));
+            let count = seen.entry(rule.clone()).or_default();
+            *count += 1;
+            let rule = match count {
+                1 => rule,
+                count => format!("{rule} #{count}"),
+            };
+            (rule, step)
+        })
+        .collect()
+}
+
+fn diff_values(original: &Map<String, Value>, replayed: &Map<String, Value>) -> Vec<ValueDiff> {
+    let mut keys: Vec<&String> = original.keys().chain(replayed.keys()).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| original.get(*key) != replayed.get(*key))
+        .map(|key| ValueDiff {
+            key: key.clone(),
+            original: original.get(key).cloned(),
+            replayed: replayed.get(key).cloned(),
+        })
+        .collect()
+}
+
+/// Collects the changes made to a context while a trace is collected, see
+/// `RuleContext::start_recording`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Recorder {
+    initial: Map<String, Value>,
+    last: Map<String, Value>,
+    changes: Vec<RecordedChange>,
+    /// The number of changes made once each step was done firing.
+    ends: HashMap<usize, usize>,
+    /// The trace entry of the first step.
+    first_entry: usize,
+    /// Whether the trace was started for the recording.
+    pub(crate) owns_trace: bool,
+}
+
+impl Recorder {
+    pub(crate) fn new(context_map: &RuleContextMap, first_entry: usize, owns_trace: bool) -> Self {
+        let initial = to_json(context_map);
+        Recorder {
+            last: initial.clone(),
+            initial,
+            first_entry,
+            owns_trace,
+            ..Default::default()
+        }
+    }
+
+    /// Records the changes made since the last observation, by the rule of
+    /// the trace entry being fired.
+    pub(crate) fn observe(&mut self, context_map: &RuleContextMap, entry: Option<usize>) {
+        let step = entry.and_then(|entry| entry.checked_sub(self.first_entry));
+        let current = to_json(context_map);
+        for (key, value) in &current {
+            if self.last.get(key) != Some(value) {
+                self.changes.push(RecordedChange {
+                    step,
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        self.last = current;
+    }
+
+    /// Marks the rule of a trace entry as done firing.
+    pub(crate) fn finish(&mut self, entry: usize) {
+        if let Some(step) = entry.checked_sub(self.first_entry) {
+            self.ends.insert(step, self.changes.len());
+        }
+    }
+
+    fn into_inspector(self, trace: &ExecutionTrace) -> RunInspector {
+        let steps = trace
+            .get_entries()
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| RecordedStep {
+                index,
+                id: entry.get_id().map(str::to_string),
+                name: entry.get_name().map(str::to_string),
+                depth: entry.get_depth(),
+                eval_result: entry.get_eval_result(),
+                outcome: entry.get_outcome().to_string(),
+                changes_end: self.ends.get(&index).copied().unwrap_or(self.changes.len()),
+            })
+            .collect();
+        RunInspector {
+            initial: self.initial,
+            steps,
+            changes: self.changes,
+        }
+    }
+}
+
+fn to_json(context_map: &RuleContextMap) -> Map<String, Value> {
+    context_map
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), get_value(value.as_ref())?)))
+        .collect()
+}
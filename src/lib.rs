@@ -1,3 +1,54 @@
+#[cfg(feature = "alloc-tracking")]
+pub mod alloc_tracking;
+pub mod bench;
+#[cfg(feature = "serde")]
+pub mod catalog;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod effects;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub(crate) mod engine;
+#[cfg(feature = "expr")]
+pub mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub(crate) mod fixpoint;
+pub mod flags;
+pub(crate) mod goal_solver;
+#[cfg(feature = "http")]
+pub mod http;
+pub(crate) mod indexed_engine;
+#[cfg(feature = "serde")]
+pub mod inspector;
+#[cfg(feature = "serde")]
+pub mod loader;
+mod macros;
+pub mod ownership;
+pub mod preflight;
+pub mod profiler;
+pub(crate) mod registry;
 pub mod rule;
 pub(crate) mod runner;
+#[cfg(feature = "serde")]
+pub mod sampling;
+#[cfg(feature = "serde")]
+pub mod scenario;
+#[cfg(feature = "serde")]
+pub mod schema;
+#[cfg(feature = "rhai")]
+pub mod script;
+#[cfg(feature = "server")]
+pub mod server;
+pub(crate) mod sync;
+#[cfg(feature = "templates")]
+pub mod templates;
+pub mod testing;
+pub(crate) mod time;
+#[cfg(all(feature = "serde", feature = "expr"))]
+pub mod tuning;
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+pub mod watch;
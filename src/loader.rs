@@ -0,0 +1,817 @@
+//! Loads rule trees from JSON definitions.
+//!
+//! A definition names the runner type of the whole tree and, for every rule,
+//! the identifiers of its callbacks. Identifiers are resolved against a
+//! `CallbackRegistry` filled with closures by the caller. Only active rule
+//! sets are loaded from JSON, unless another `ActivationGuard` is given.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::loader::{self, CallbackRegistry};
+//! use dredd_rs::rule::*;
+//!
+//! let json = r#"{
+//!     "type": "chain",
+//!     "state": "active",
+//!     "rules": [{
+//!         "name": "adult",
+//!         "eval": "is_adult",
+//!         "execute": "approve"
+//!     }]
+//! }"#;
+//!
+//! let mut registry = CallbackRegistry::new();
+//! registry
+//!     .condition("is_adult", |ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+//!     .action("approve", |ctx| ctx.set("approved", true));
+//!
+//! let rules = loader::from_json(json, &registry).unwrap();
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("age", 42i64);
+//! rules.run(rule_context.clone());
+//!
+//! assert!(*rule_context.get::<bool>("approved").unwrap());
+//! ```
+
+use std::{collections::HashMap, error::Error, fmt, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::rule::{
+    AllRule, BestFirstRule, ChainRule, Engine, KeyUsage, Rule, RuleCallback, RuleChildren,
+    RuleContextWrapper, RuleMetadata, RuleRunner as _, RunReport, Wrapper,
+};
+
+type Condition = Rc<dyn Fn(&mut RuleContextWrapper) -> bool>;
+type Action = Rc<dyn Fn(&mut RuleContextWrapper)>;
+
+/// The runner type a rule tree is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    Chain,
+    BestFirst,
+    All,
+}
+
+/// The approval stage of a stored rule set.
+///
+/// Rule sets move from `Draft` to `Review`, then to `Active`, and are
+/// eventually `Retired`. A set under review can be sent back to `Draft`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSetState {
+    #[default]
+    Draft,
+    Review,
+    Active,
+    Retired,
+}
+
+impl RuleSetState {
+    /// Whether a rule set in this state may be moved to `next`.
+    pub fn can_transition_to(self, next: RuleSetState) -> bool {
+        use RuleSetState::*;
+        matches!(
+            (self, next),
+            (Draft, Review) | (Review, Draft) | (Review, Active) | (Active, Retired)
+        )
+    }
+}
+
+impl fmt::Display for RuleSetState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            RuleSetState::Draft => "draft",
+            RuleSetState::Review => "review",
+            RuleSetState::Active => "active",
+            RuleSetState::Retired => "retired",
+        };
+        f.write_str(state)
+    }
+}
+
+/// A whole rule tree: its type and its top level rules.
+///
+/// Documents without a `state` are drafts.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuleSetDefinition {
+    #[serde(rename = "type")]
+    pub kind: RuleKind,
+    #[serde(default)]
+    pub state: RuleSetState,
+    #[serde(default)]
+    pub rules: Vec<RuleDefinition>,
+}
+
+/// A single rule: its metadata, callback identifiers and children.
+///
+/// Callbacks that are not given keep the rule defaults. Rules without an `id`
+/// are given an id derived from their content and their place in the tree,
+/// see `get_content_id`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuleDefinition {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+    /// Labels grouping rules across rule sets, such as `deprecated`, see
+    /// `RuleSetDefinition::exclude_tag` and `Engine::execute_filtered`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The context keys the rule reads, see `RuleMetadata::with_reads`.
+    #[serde(default)]
+    pub reads: Vec<String>,
+    /// The context keys the rule writes, see `RuleMetadata::with_writes`.
+    #[serde(default)]
+    pub writes: Vec<String>,
+    #[serde(default)]
+    pub eval: Option<String>,
+    #[serde(default)]
+    pub pre_execute: Option<String>,
+    #[serde(default)]
+    pub execute: Option<String>,
+    #[serde(default)]
+    pub post_execute: Option<String>,
+    #[serde(default)]
+    pub children: Vec<RuleDefinition>,
+}
+
+impl RuleSetDefinition {
+    /// Moves the rule set to the next stage of its approval workflow.
+    pub fn transition_to(&mut self, next: RuleSetState) -> Result<(), LoaderError> {
+        if !self.state.can_transition_to(next) {
+            return Err(LoaderError::InvalidTransition {
+                from: self.state,
+                to: next,
+            });
+        }
+        self.state = next;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String, LoaderError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Adds the top level rules of `other` that this rule set doesn't have,
+    /// after its own rules. Rules are matched by name, or by id for unnamed
+    /// ones, and rules present in both sets are kept as they are here.
+    ///
+    /// Composition keeps the type and the state of this rule set, and
+    /// matches top level rules only: children come along with their parent.
+    ///
+    /// ```rust
+    /// use dredd_rs::loader::RuleSetDefinition;
+    ///
+    /// let global: RuleSetDefinition = serde_json::from_str(r#"{ "type": "all", "rules": [
+    ///     { "name": "kyc", "execute": "check_documents" },
+    ///     { "name": "limits", "execute": "global_limits", "tags": ["deprecated"] },
+    ///     { "name": "fraud", "execute": "score_fraud" }
+    /// ] }"#).unwrap();
+    /// let brazil: RuleSetDefinition = serde_json::from_str(r#"{ "type": "all", "rules": [
+    ///     { "name": "fraud", "execute": "score_fraud_br" },
+    ///     { "name": "pix", "execute": "check_pix" }
+    /// ] }"#).unwrap();
+    /// let tenant: RuleSetDefinition = serde_json::from_str(r#"{ "type": "all", "rules": [
+    ///     { "name": "kyc", "execute": "check_documents_strict" }
+    /// ] }"#).unwrap();
+    ///
+    /// let rules = global
+    ///     .override_by_name(brazil)
+    ///     .union(tenant)
+    ///     .exclude_tag("deprecated");
+    ///
+    /// let executes: Vec<_> = rules.rules.iter().map(|rule| rule.execute.as_deref().unwrap()).collect();
+    /// assert_eq!(executes, vec!["check_documents", "score_fraud_br", "check_pix"]);
+    /// ```
+    pub fn union(mut self, other: RuleSetDefinition) -> Self {
+        for rule in other.rules {
+            if !self.rules.iter().any(|own| own.get_key() == rule.get_key()) {
+                self.rules.push(rule);
+            }
+        }
+        self
+    }
+
+    /// Replaces the top level rules of this rule set with the rules of
+    /// `other` matching them, in place, and adds the other rules of `other`
+    /// after them. Rules are matched like in `union`.
+    pub fn override_by_name(mut self, other: RuleSetDefinition) -> Self {
+        for rule in other.rules {
+            let key = rule.get_key();
+            match self.rules.iter_mut().find(|own| own.get_key() == key) {
+                Some(own) => *own = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        self
+    }
+
+    /// Removes the rules tagged with `tag`, children included, at any
+    /// depth.
+    pub fn exclude_tag(mut self, tag: &str) -> Self {
+        fn exclude(rules: &mut Vec<RuleDefinition>, tag: &str) {
+            rules.retain(|rule| !rule.tags.iter().any(|own| own == tag));
+            for rule in rules {
+                exclude(&mut rule.children, tag);
+            }
+        }
+        exclude(&mut self.rules, tag);
+        self
+    }
+}
+
+impl RuleDefinition {
+    /// An id derived from what the rule does, so that traces, metrics and
+    /// audit logs can be correlated across reloads and restarts.
+    ///
+    /// The id hashes the callback identifiers and the declared `reads` and
+    /// `writes` of the rule, along with its place in the tree: the place of
+    /// its parent, and how many of its earlier siblings have the same
+    /// content. It ignores the `id`, `name`, `description`, `owner`, `team`
+    /// and `tags` of the rule, so editing them keeps the id, while changing a
+    /// callback gives a new one. Identical rules in different places get
+    /// different ids, and editing a rule keeps the ids of its parent and
+    /// siblings. This is the id the rule gets as the first rule of its
+    /// content at the top of a rule set.
+    ///
+    /// ```rust
+    /// use dredd_rs::loader::RuleDefinition;
+    ///
+    /// let rule = RuleDefinition {
+    ///     name: Some("adult".to_string()),
+    ///     eval: Some("is_adult".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let renamed = RuleDefinition {
+    ///     name: Some("adult_check".to_string()),
+    ///     ..rule.clone()
+    /// };
+    ///
+    /// assert_eq!(rule.get_content_id(), renamed.get_content_id());
+    /// assert!(rule.get_content_id().starts_with("rule-"));
+    /// ```
+    pub fn get_content_id(&self) -> String {
+        content_id(self.get_place_hash(ROOT_PLACE, 0))
+    }
+
+    /// What identifies the rule when composing rule sets: its name, its id,
+    /// or its content for rules with neither.
+    fn get_key(&self) -> String {
+        self.name
+            .clone()
+            .or_else(|| self.id.clone())
+            .unwrap_or_else(|| self.get_content_id())
+    }
+
+    fn get_content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+        for callback in [
+            &self.eval,
+            &self.pre_execute,
+            &self.execute,
+            &self.post_execute,
+        ] {
+            hasher.write_field(callback.as_deref().unwrap_or_default().as_bytes());
+        }
+        for keys in [&self.reads, &self.writes] {
+            hasher.write_field(&(keys.len() as u64).to_le_bytes());
+            for key in keys {
+                hasher.write_field(key.as_bytes());
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Hashes the content of the rule with the place of its parent and the
+    /// number of its earlier siblings with the same content.
+    fn get_place_hash(&self, parent: u64, occurrence: u64) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hasher.write_field(&parent.to_le_bytes());
+        hasher.write_field(&occurrence.to_le_bytes());
+        hasher.write_field(&self.get_content_hash().to_le_bytes());
+        hasher.finish()
+    }
+}
+
+/// The place the top rules of a rule set hang from.
+const ROOT_PLACE: u64 = 0;
+
+fn content_id(hash: u64) -> String {
+    format!("rule-{hash:016x}")
+}
+
+/// 64-bit FNV-1a, which, unlike the hashers of the standard library, gives
+/// the same hashes across Rust versions and platforms.
+struct ContentHasher(u64);
+
+impl ContentHasher {
+    fn new() -> Self {
+        ContentHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Hashes a length-prefixed field, so that consecutive fields can't be
+    /// confused with one another.
+    fn write_field(&mut self, bytes: &[u8]) {
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Decides whether a rule set may be loaded, see `from_json_guarded`.
+///
+/// Closures taking the definition and returning `Result<(), String>` are
+/// guards as well.
+pub trait ActivationGuard {
+    /// Returns why the rule set may not be loaded, if it may not.
+    fn check(&self, definition: &RuleSetDefinition) -> Result<(), String>;
+}
+
+impl<F: Fn(&RuleSetDefinition) -> Result<(), String>> ActivationGuard for F {
+    fn check(&self, definition: &RuleSetDefinition) -> Result<(), String> {
+        self(definition)
+    }
+}
+
+/// Only lets active rule sets be loaded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActiveOnly;
+
+impl ActivationGuard for ActiveOnly {
+    fn check(&self, definition: &RuleSetDefinition) -> Result<(), String> {
+        match definition.state {
+            RuleSetState::Active => Ok(()),
+            state => Err(format!("rule set is {state}, not active")),
+        }
+    }
+}
+
+/// Lets rule sets in any state be loaded, such as drafts under test.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnyState;
+
+impl ActivationGuard for AnyState {
+    fn check(&self, _: &RuleSetDefinition) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Named conditions and actions that rule definitions refer to.
+#[derive(Clone, Default)]
+pub struct CallbackRegistry {
+    conditions: HashMap<String, Condition>,
+    actions: HashMap<String, Action>,
+    reads: HashMap<String, Vec<String>>,
+    writes: HashMap<String, Vec<String>>,
+}
+
+impl CallbackRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a condition usable as `eval`.
+    pub fn condition(
+        &mut self,
+        name: &str,
+        condition: impl Fn(&mut RuleContextWrapper) -> bool + 'static,
+    ) -> &mut Self {
+        self.conditions.insert(name.to_string(), Rc::new(condition));
+        self
+    }
+
+    /// Registers an action usable as `pre_execute`, `execute` or `post_execute`.
+    pub fn action(
+        &mut self,
+        name: &str,
+        action: impl Fn(&mut RuleContextWrapper) + 'static,
+    ) -> &mut Self {
+        self.actions.insert(name.to_string(), Rc::new(action));
+        self
+    }
+
+    /// Declares the context keys the condition `name` reads, added to the
+    /// reads of every rule evaluating it, see `RuleMetadata::with_reads`.
+    pub fn declare_reads(&mut self, name: &str, keys: &[&str]) -> &mut Self {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        self.reads.insert(name.to_string(), keys);
+        self
+    }
+
+    /// Declares the context keys the action `name` writes, added to the
+    /// writes of every rule running it, see `RuleMetadata::with_writes`.
+    pub fn declare_writes(&mut self, name: &str, keys: &[&str]) -> &mut Self {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        self.writes.insert(name.to_string(), keys);
+        self
+    }
+
+    /// Registers an expression, evaluated against the rule context, as the
+    /// condition `name` and declares the keys it reads. Evaluation errors,
+    /// such as a missing key, make the rule not execute.
+    #[cfg(feature = "expr")]
+    pub fn try_eval_expr(
+        &mut self,
+        name: &str,
+        source: &str,
+    ) -> Result<&mut Self, crate::expr::ExprError> {
+        let expr = crate::expr::Expr::parse(source)?;
+        self.declare_reads(name, &expr.get_keys());
+        Ok(self.condition(name, move |ctx| expr.eval(&ctx.borrow()).unwrap_or(false)))
+    }
+
+    fn get_condition(&self, name: &str) -> Result<Condition, LoaderError> {
+        self.conditions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| LoaderError::UnknownCondition(name.to_string()))
+    }
+
+    fn get_action(&self, name: &str) -> Result<Action, LoaderError> {
+        self.actions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| LoaderError::UnknownAction(name.to_string()))
+    }
+}
+
+/// Errors returned while loading a rule tree.
+#[derive(Debug)]
+pub enum LoaderError {
+    /// The document is not valid JSON or doesn't match the definition format.
+    Json(serde_json::Error),
+    /// An `eval` identifier has no registered condition.
+    UnknownCondition(String),
+    /// A `pre_execute`, `execute` or `post_execute` identifier has no registered action.
+    UnknownAction(String),
+    /// A chain tree has more than one rule at the same level.
+    ChainSiblings,
+    /// An `ActivationGuard` refused to load the rule set.
+    NotActivated(String),
+    /// An expression condition of a rule document doesn't parse.
+    InvalidCondition { name: String, message: String },
+    /// The approval workflow doesn't allow moving between these states.
+    InvalidTransition {
+        from: RuleSetState,
+        to: RuleSetState,
+    },
+}
+
+impl fmt::Display for LoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderError::Json(e) => write!(f, "invalid rule definition: {e}"),
+            LoaderError::UnknownCondition(name) => write!(f, "unknown condition `{name}`"),
+            LoaderError::UnknownAction(name) => write!(f, "unknown action `{name}`"),
+            LoaderError::ChainSiblings => {
+                write!(f, "chain rules can only have one rule per level")
+            }
+            LoaderError::NotActivated(reason) => write!(f, "rule set not activated: {reason}"),
+            LoaderError::InvalidCondition { name, message } => {
+                write!(f, "invalid condition `{name}`: {message}")
+            }
+            LoaderError::InvalidTransition { from, to } => {
+                write!(f, "rule set can't move from {from} to {to}")
+            }
+        }
+    }
+}
+
+impl Error for LoaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoaderError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for LoaderError {
+    fn from(e: serde_json::Error) -> Self {
+        LoaderError::Json(e)
+    }
+}
+
+/// A loaded rule tree, ready to be run by the runner matching its type.
+pub enum LoadedRules {
+    Chain(Vec<Wrapper<ChainRule>>),
+    BestFirst(Vec<Wrapper<BestFirstRule>>),
+    All(Vec<Wrapper<AllRule>>),
+}
+
+impl LoadedRules {
+    /// Runs the rules with the runner matching their type.
+    pub fn run(&self, rule_context: RuleContextWrapper) {
+        match self {
+            LoadedRules::Chain(rules) => Engine::chain_runner().run(rule_context, rules.clone()),
+            LoadedRules::BestFirst(rules) => {
+                Engine::best_first_runner().run(rule_context, rules.clone())
+            }
+            LoadedRules::All(rules) => Engine::all_runner().run(rule_context, rules.clone()),
+        }
+    }
+
+    /// The context keys the rules declare they read and write, including the
+    /// keys read by the expressions and written by the actions of a rule
+    /// document.
+    pub fn get_key_usage(&self) -> KeyUsage {
+        match self {
+            LoadedRules::Chain(rules) => KeyUsage::declared(rules),
+            LoadedRules::BestFirst(rules) => KeyUsage::declared(rules),
+            LoadedRules::All(rules) => KeyUsage::declared(rules),
+        }
+    }
+
+    /// Runs the rules like `run()` and returns a report of the run.
+    pub fn run_with_report(&self, rule_context: RuleContextWrapper) -> RunReport {
+        match self {
+            LoadedRules::Chain(rules) => {
+                Engine::chain_runner().run_with_report(rule_context, rules.clone())
+            }
+            LoadedRules::BestFirst(rules) => {
+                Engine::best_first_runner().run_with_report(rule_context, rules.clone())
+            }
+            LoadedRules::All(rules) => {
+                Engine::all_runner().run_with_report(rule_context, rules.clone())
+            }
+        }
+    }
+}
+
+/// Parses a JSON document and builds the rule tree it describes, when it is
+/// active, see `ActiveOnly`. Drafts and rule sets under review are loaded
+/// with `from_json_guarded` and `AnyState`.
+pub fn from_json(json: &str, registry: &CallbackRegistry) -> Result<LoadedRules, LoaderError> {
+    from_json_guarded(json, registry, &ActiveOnly)
+}
+
+/// Parses a JSON document and builds the rule tree it describes, once `guard`
+/// accepts it.
+///
+/// ```rust
+/// use dredd_rs::loader::{self, ActiveOnly, CallbackRegistry, LoaderError};
+///
+/// let registry = CallbackRegistry::new();
+///
+/// let draft = r#"{ "type": "all", "state": "review", "rules": [] }"#;
+/// assert!(matches!(
+///     loader::from_json_guarded(draft, &registry, &ActiveOnly),
+///     Err(LoaderError::NotActivated(_))
+/// ));
+///
+/// let active = r#"{ "type": "all", "state": "active", "rules": [] }"#;
+/// assert!(loader::from_json_guarded(active, &registry, &ActiveOnly).is_ok());
+/// ```
+pub fn from_json_guarded(
+    json: &str,
+    registry: &CallbackRegistry,
+    guard: &dyn ActivationGuard,
+) -> Result<LoadedRules, LoaderError> {
+    let definition: RuleSetDefinition = serde_json::from_str(json)?;
+    guard
+        .check(&definition)
+        .map_err(LoaderError::NotActivated)?;
+    from_definition(&definition, registry)
+}
+
+/// A rule set bundled with its callbacks, see `from_document_json`.
+#[cfg(feature = "expr")]
+#[derive(Deserialize)]
+struct RuleDocument {
+    #[serde(default)]
+    conditions: HashMap<String, String>,
+    #[serde(default)]
+    actions: HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    failures: HashMap<String, String>,
+    rules: RuleSetDefinition,
+}
+
+/// Parses a rule document, which carries its own callbacks, and builds the
+/// rule tree it describes.
+///
+/// Conditions are expressions, see `dredd_rs::expr`, and actions are the
+/// values they set. A document holds the named `conditions` and `actions`,
+/// and the rule set itself under `rules`. An expression that fails to
+/// evaluate counts as `false`. Documents may also name `failures`, actions
+/// recording a `RuleError::Failed` with their message.
+///
+/// ```rust
+/// use dredd_rs::loader;
+/// use dredd_rs::rule::*;
+///
+/// let rules = loader::from_document_json(r#"{
+///     "conditions": { "is_adult": "age >= 18" },
+///     "actions": { "approve": { "approved": true } },
+///     "rules": { "type": "all", "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }] }
+/// }"#).unwrap();
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 42i64);
+/// rules.run(rule_context.clone());
+///
+/// assert!(*rule_context.get::<bool>("approved").unwrap());
+/// ```
+#[cfg(feature = "expr")]
+pub fn from_document_json(json: &str) -> Result<LoadedRules, LoaderError> {
+    use crate::{
+        rule::{RuleError, RuleFailure},
+        scenario::{intern, set_value},
+    };
+
+    let document: RuleDocument = serde_json::from_str(json)?;
+    let mut registry = CallbackRegistry::new();
+    for (name, source) in &document.conditions {
+        registry
+            .try_eval_expr(name, source)
+            .map_err(|error| LoaderError::InvalidCondition {
+                name: name.clone(),
+                message: error.to_string(),
+            })?;
+    }
+    for (name, values) in document.actions {
+        let keys: Vec<&str> = values.keys().map(String::as_str).collect();
+        registry.declare_writes(&name, &keys);
+        registry.action(&name, move |ctx| {
+            for (key, value) in &values {
+                set_value(ctx, intern(key), value);
+            }
+        });
+    }
+    for (name, message) in document.failures {
+        registry.action(&name, move |ctx| {
+            ctx.fail(RuleError::failed(message.clone()))
+        });
+    }
+    from_definition(&document.rules, &registry)
+}
+
+/// Builds the rule tree described by an already parsed definition.
+pub fn from_definition(
+    definition: &RuleSetDefinition,
+    registry: &CallbackRegistry,
+) -> Result<LoadedRules, LoaderError> {
+    match definition.kind {
+        RuleKind::Chain => {
+            check_chain(&definition.rules)?;
+            Ok(LoadedRules::Chain(build_all(
+                &definition.rules,
+                registry,
+                ChainRule::new,
+                ROOT_PLACE,
+            )?))
+        }
+        RuleKind::BestFirst => Ok(LoadedRules::BestFirst(build_all(
+            &definition.rules,
+            registry,
+            BestFirstRule::new,
+            ROOT_PLACE,
+        )?)),
+        RuleKind::All => Ok(LoadedRules::All(build_all(
+            &definition.rules,
+            registry,
+            AllRule::new,
+            ROOT_PLACE,
+        )?)),
+    }
+}
+
+fn check_chain(rules: &[RuleDefinition]) -> Result<(), LoaderError> {
+    if rules.len() > 1 {
+        return Err(LoaderError::ChainSiblings);
+    }
+    rules
+        .iter()
+        .try_for_each(|rule| check_chain(&rule.children))
+}
+
+fn build_all<R>(
+    definitions: &[RuleDefinition],
+    registry: &CallbackRegistry,
+    new: fn() -> Wrapper<R>,
+    parent: u64,
+) -> Result<Vec<Wrapper<R>>, LoaderError>
+where
+    R: Rule<R> + 'static,
+    Wrapper<R>: RuleCallback<RuleType = R> + RuleChildren<RuleType = R>,
+{
+    let mut contents = Vec::with_capacity(definitions.len());
+    definitions
+        .iter()
+        .map(|definition| {
+            let content = definition.get_content_hash();
+            let occurrence = contents.iter().filter(|other| **other == content).count();
+            contents.push(content);
+            let place = definition.get_place_hash(parent, occurrence as u64);
+            build(definition, registry, new, place)
+        })
+        .collect()
+}
+
+/// The keys of a definition followed by the keys declared for its
+/// callbacks, without repeats.
+fn declared_keys<'a, const N: usize>(
+    keys: &'a [String],
+    declared: &'a HashMap<String, Vec<String>>,
+    callbacks: [&Option<String>; N],
+) -> Vec<&'a str> {
+    let callback_keys = callbacks
+        .into_iter()
+        .flatten()
+        .filter_map(|name| declared.get(name))
+        .flatten();
+    let mut all: Vec<&str> = Vec::new();
+    for key in keys.iter().chain(callback_keys) {
+        if !all.contains(&key.as_str()) {
+            all.push(key);
+        }
+    }
+    all
+}
+
+/// Builds the rule defined at `place`, see `RuleDefinition::get_content_id`.
+fn build<R>(
+    definition: &RuleDefinition,
+    registry: &CallbackRegistry,
+    new: fn() -> Wrapper<R>,
+    place: u64,
+) -> Result<Wrapper<R>, LoaderError>
+where
+    R: Rule<R> + 'static,
+    Wrapper<R>: RuleCallback<RuleType = R> + RuleChildren<RuleType = R>,
+{
+    let mut rule = new();
+
+    match &definition.id {
+        Some(id) => rule.with_id(id),
+        None => rule.with_id(&content_id(place)),
+    };
+    if let Some(name) = &definition.name {
+        rule.with_name(name);
+    }
+    if let Some(description) = &definition.description {
+        rule.with_description(description);
+    }
+    if let Some(owner) = &definition.owner {
+        rule.with_owner(owner);
+    }
+    if let Some(team) = &definition.team {
+        rule.with_team(team);
+    }
+    if !definition.tags.is_empty() {
+        let tags: Vec<&str> = definition.tags.iter().map(String::as_str).collect();
+        rule.with_tags(&tags);
+    }
+    let reads = declared_keys(&definition.reads, &registry.reads, [&definition.eval]);
+    if !reads.is_empty() {
+        rule.with_reads(&reads);
+    }
+    let actions = [
+        &definition.pre_execute,
+        &definition.execute,
+        &definition.post_execute,
+    ];
+    let writes = declared_keys(&definition.writes, &registry.writes, actions);
+    if !writes.is_empty() {
+        rule.with_writes(&writes);
+    }
+    if let Some(name) = &definition.eval {
+        let condition = registry.get_condition(name)?;
+        rule.on_eval(move |ctx| condition(&mut ctx.get_rule_context()));
+        rule.borrow_mut().set_condition(name);
+    }
+    if let Some(name) = &definition.pre_execute {
+        let action = registry.get_action(name)?;
+        rule.on_pre_execute(move |this| action(&mut this.get_rule_context()));
+    }
+    if let Some(name) = &definition.execute {
+        let action = registry.get_action(name)?;
+        rule.on_execute(move |this| action(&mut this.get_rule_context()));
+    }
+    if let Some(name) = &definition.post_execute {
+        let action = registry.get_action(name)?;
+        rule.on_post_execute(move |this| action(&mut this.get_rule_context()));
+    }
+
+    let children = build_all(&definition.children, registry, new, place)?;
+    if !children.is_empty() {
+        RuleChildren::add_children(&mut rule, children);
+    }
+
+    Ok(rule)
+}
@@ -0,0 +1,183 @@
+/// Declares a module of typed `ContextKey` constants.
+///
+/// Each entry is written as `CONST_NAME: Type = "key";`, optionally preceded
+/// by doc comments. Besides the constants, the generated module contains a
+/// `KEYS` slice of `ContextKeyInfo` describing every declared key.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::context_keys;
+/// use dredd_rs::rule::*;
+///
+/// context_keys! {
+///     pub mod checkout {
+///         /// Customer age in years.
+///         AGE: i64 = "checkout.age";
+///         /// ISO country code of the customer.
+///         COUNTRY: String = "checkout.country";
+///     }
+/// }
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set_key(checkout::AGE, 42);
+///
+/// assert_eq!(*rule_context.get_key(checkout::AGE).unwrap(), 42);
+/// assert_eq!(checkout::KEYS.len(), 2);
+/// assert_eq!(checkout::KEYS[1].value_type(), "String");
+/// assert_eq!(checkout::KEYS[1].doc(), "ISO country code of the customer.");
+/// ```
+#[macro_export]
+macro_rules! context_keys {
+    (
+        $(#[$mod_attr:meta])*
+        $vis:vis mod $module:ident {
+            $(
+                $(#[doc = $doc:expr])*
+                $const_name:ident : $ty:ty = $key:expr;
+            )*
+        }
+    ) => {
+        $(#[$mod_attr])*
+        $vis mod $module {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                $(#[doc = $doc])*
+                pub const $const_name: $crate::rule::ContextKey<$ty> =
+                    $crate::rule::ContextKey::new($key, concat!($($doc, "\n",)* ""));
+            )*
+
+            /// Every key declared in this module.
+            pub const KEYS: &[$crate::rule::ContextKeyInfo] = &[
+                $(
+                    $crate::rule::ContextKeyInfo::new(
+                        $key,
+                        stringify!($ty),
+                        concat!($($doc, "\n",)* ""),
+                    ),
+                )*
+            ];
+        }
+    };
+}
+
+/// Builds a rule tree inline.
+///
+/// A rule is written as its type, `chain`, `best_first` or `all`, followed by
+/// a block of comma separated fields:
+///
+/// - `id`, `name`, `description`, `owner`, `team`, `reads`, `writes`, `cost`
+///   and `optional` set the metadata;
+/// - `when`, `before`, `then` and `after` set the evaluation, pre-execution,
+///   execution and post-execution callbacks;
+/// - `child` adds a child, either written with the same syntax or given as an
+///   expression evaluating to a rule of the same type.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule;
+/// use dredd_rs::rule::*;
+///
+/// let tree = rule! {
+///     best_first {
+///         name: "checkout",
+///         child: best_first {
+///             name: "adult",
+///             when: |ctx| *ctx.get::<i64>("age").unwrap() >= 18,
+///             then: |this| this.get_rule_context().set("approved", true),
+///         },
+///         child: best_first {
+///             name: "minor",
+///             then: |this| this.get_rule_context().set("approved", false),
+///         },
+///     }
+/// };
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 42i64);
+/// Engine::best_first_runner().run(rule_context.clone(), vec![tree]);
+///
+/// assert!(*rule_context.get::<bool>("approved").unwrap());
+/// ```
+#[macro_export]
+macro_rules! rule {
+    (chain { $($fields:tt)* }) => {
+        $crate::rule!(@rule $crate::rule::ChainRule; $($fields)*)
+    };
+    (best_first { $($fields:tt)* }) => {
+        $crate::rule!(@rule $crate::rule::BestFirstRule; $($fields)*)
+    };
+    (all { $($fields:tt)* }) => {
+        $crate::rule!(@rule $crate::rule::AllRule; $($fields)*)
+    };
+
+    (@rule $ty:path; $($fields:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut rule = <$ty>::new();
+        $crate::rule!(@fields rule; $($fields)*);
+        rule
+    }};
+
+    (@fields $rule:ident;) => {};
+    (@fields $rule:ident; id: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_id(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; name: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_name(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; description: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_description(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; owner: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_owner(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; team: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_team(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; reads: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_reads(&mut $rule, &$value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; writes: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_writes(&mut $rule, &$value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; cost: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_cost(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; optional: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleMetadata::with_optional(&mut $rule, $value);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; when: $callback:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleCallback::on_eval(&mut $rule, $callback);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; before: $callback:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleCallback::on_pre_execute(&mut $rule, $callback);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; then: $callback:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleCallback::on_execute(&mut $rule, $callback);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; after: $callback:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleCallback::on_post_execute(&mut $rule, $callback);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; child: $kind:ident { $($child:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleChildren::add_child(&mut $rule, $crate::rule!($kind { $($child)* }));
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+    (@fields $rule:ident; child: $child:expr $(, $($rest:tt)*)?) => {
+        $crate::rule::RuleChildren::add_child(&mut $rule, $child);
+        $crate::rule!(@fields $rule; $($($rest)*)?);
+    };
+}
@@ -0,0 +1,202 @@
+//! Aggregates run reports by the owner and team of the rules, so that the
+//! fires, failures and time of a shared rule base can be attributed to the
+//! people responsible for them.
+//!
+//! Owners and teams are set with `RuleMetadata::with_owner` and `with_team`.
+//! Times exclude the children of each rule, and a failure is only counted
+//! against the rule whose own callback reported it, not against its parents.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::ownership::OwnershipReport;
+//! use dredd_rs::rule::*;
+//!
+//! let mut report = OwnershipReport::new();
+//!
+//! for _ in 0..3 {
+//!     let rule = AllRule::new()
+//!         .with_team("checkout")
+//!         .add_child(
+//!             AllRule::new()
+//!                 .with_team("fraud")
+//!                 .on_execute(|this| this.get_rule_context().fail(RuleError::failed("timeout"))),
+//!         );
+//!     let run = Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+//!     report.record(run.get_trace());
+//! }
+//!
+//! let fraud = report.get_team("fraud").unwrap();
+//! assert_eq!(fraud.get_fires(), 3);
+//! assert_eq!(fraud.get_failures(), 3);
+//! assert_eq!(report.get_team("checkout").unwrap().get_failures(), 0);
+//! println!("{report}");
+//! ```
+
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use crate::rule::{ExecutionTrace, TraceEntry};
+
+/// Fires, failures and time of the rules of many runs, grouped by owner and
+/// by team.
+#[derive(Debug, Clone, Default)]
+pub struct OwnershipReport {
+    owners: BTreeMap<Option<String>, OwnerStats>,
+    teams: BTreeMap<Option<String>, OwnerStats>,
+    runs: u64,
+}
+
+impl OwnershipReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every rule of the trace to the stats of its owner and team.
+    pub fn record(&mut self, trace: &ExecutionTrace) {
+        self.runs += 1;
+        for (entry, usage) in trace.get_entries().iter().zip(usages(trace)) {
+            let owner = entry.get_owner().map(str::to_string);
+            let team = entry.get_team().map(str::to_string);
+            for (groups, key) in [(&mut self.owners, owner), (&mut self.teams, team)] {
+                groups
+                    .entry(key.clone())
+                    .or_insert_with(|| OwnerStats::new(key))
+                    .add(entry, usage);
+            }
+        }
+    }
+
+    pub fn get_runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// The stats of every owner, sorted by owner, rules without one first.
+    pub fn get_by_owner(&self) -> Vec<&OwnerStats> {
+        self.owners.values().collect()
+    }
+
+    /// The stats of every team, sorted by team, rules without one first.
+    pub fn get_by_team(&self) -> Vec<&OwnerStats> {
+        self.teams.values().collect()
+    }
+
+    pub fn get_owner(&self, owner: &str) -> Option<&OwnerStats> {
+        self.owners.get(&Some(owner.to_string()))
+    }
+
+    pub fn get_team(&self, team: &str) -> Option<&OwnerStats> {
+        self.teams.get(&Some(team.to_string()))
+    }
+}
+
+impl fmt::Display for OwnershipReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, groups) in [("team", &self.teams), ("owner", &self.owners)] {
+            writeln!(
+                f,
+                "{title:<20} {:>10} {:>10} {:>10} {:>12}",
+                "fires", "executed", "failures", "time"
+            )?;
+            for stats in groups.values() {
+                writeln!(
+                    f,
+                    "{:<20} {:>10} {:>10} {:>10} {:>12?}",
+                    stats.get_owner().unwrap_or("<none>"),
+                    stats.fires,
+                    stats.executions,
+                    stats.failures,
+                    stats.duration
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the rules of one owner or team did.
+#[derive(Debug, Clone)]
+pub struct OwnerStats {
+    owner: Option<String>,
+    fires: u64,
+    executions: u64,
+    failures: u64,
+    duration: Duration,
+}
+
+impl OwnerStats {
+    fn new(owner: Option<String>) -> Self {
+        OwnerStats {
+            owner,
+            fires: 0,
+            executions: 0,
+            failures: 0,
+            duration: Duration::ZERO,
+        }
+    }
+
+    fn add(&mut self, entry: &TraceEntry, usage: Usage) {
+        self.fires += 1;
+        self.executions += entry.is_executed() as u64;
+        self.failures += usage.failed as u64;
+        self.duration += usage.self_time;
+    }
+
+    /// The owner or team, `None` for the rules without one.
+    pub fn get_owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// How many times the rules were evaluated.
+    pub fn get_fires(&self) -> u64 {
+        self.fires
+    }
+
+    /// How many times the rules were executed.
+    pub fn get_executions(&self) -> u64 {
+        self.executions
+    }
+
+    /// How many times the callbacks of the rules reported a failure.
+    pub fn get_failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Time spent in the rules, excluding their children.
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Mean time spent in a rule each time it was fired.
+    pub fn get_mean_duration(&self) -> Duration {
+        if self.fires == 0 {
+            return Duration::ZERO;
+        }
+        self.duration.div_f64(self.fires as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Usage {
+    self_time: Duration,
+    failed: bool,
+}
+
+/// The self time of every entry, and whether it failed itself rather than
+/// through one of its children, in trace order.
+fn usages(trace: &ExecutionTrace) -> Vec<Usage> {
+    let entries = trace.get_entries();
+    let mut usages: Vec<Usage> = entries
+        .iter()
+        .zip(trace.get_self_times())
+        .map(|(entry, self_time)| Usage {
+            self_time,
+            failed: entry.is_failed(),
+        })
+        .collect();
+    for (entry, parent) in entries.iter().zip(trace.get_parents()) {
+        if let Some(parent) = parent {
+            usages[parent].failed &= !entry.is_failed();
+        }
+    }
+    usages
+}
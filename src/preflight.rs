@@ -0,0 +1,303 @@
+//! Checks at startup that the rules of a service are ready to serve, so that
+//! a deploy fails fast instead of at the first request.
+//!
+//! `RuleRegistry::preflight` starts a `Preflight` of the registered rule
+//! sets, to which the sources of expressions and scripts, the resolvers the
+//! rules query and the transports their effects are delivered with are
+//! added. `run` then performs every check and returns a `ReadinessReport`:
+//!
+//! - the keys every rule reads and writes, as declared with
+//!   `RuleMetadata::with_reads` and `with_writes`, must be among the keys
+//!   given with `with_keys`, usually the `KEYS` generated by `context_keys!`;
+//! - expressions, with the `expr` feature, and scripts, with the `rhai`
+//!   feature, must compile;
+//! - resolvers must answer their ping, and so must the transports of a
+//!   `TransportRegistry`, see `Transport::ping`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::context_keys;
+//! use dredd_rs::preflight::CheckKind;
+//! use dredd_rs::rule::*;
+//!
+//! context_keys! {
+//!     pub mod checkout {
+//!         AGE: i64 = "age";
+//!         APPROVED: bool = "approved";
+//!     }
+//! }
+//!
+//! let mut registry = RuleRegistry::new();
+//! registry.register("checkout", Engine::all_runner(), vec![
+//!     AllRule::new().with_name("adult_check").with_reads(&["age"]).with_writes(&["aproved"]),
+//! ]);
+//!
+//! let report = registry
+//!     .preflight()
+//!     .with_keys(checkout::KEYS)
+//!     .resolver("customers", || Err("connection refused".to_string()))
+//!     .run();
+//!
+//! assert!(!report.is_ready());
+//! let failures: Vec<_> = report.get_failures().iter().map(|check| check.get_kind()).collect();
+//! assert_eq!(failures, [CheckKind::Schema, CheckKind::Resolver]);
+//! assert_eq!(
+//!     report.to_string(),
+//!     "not ready\n\
+//!      failed schema `checkout`: `adult_check` writes undeclared key `aproved`\n\
+//!      failed resolver `customers`: connection refused\n"
+//! );
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use crate::effects::TransportRegistry;
+use crate::rule::{ContextKeyInfo, Metadata, RuleRegistry};
+
+type Ping<'a> = Box<dyn Fn() -> Result<(), String> + 'a>;
+
+/// What a check of a `Preflight` verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CheckKind {
+    /// The keys the rules of a rule set read and write are declared.
+    Schema,
+    /// An expression compiles.
+    Expression,
+    /// A script compiles.
+    Script,
+    /// A resolver answers its ping.
+    Resolver,
+    /// The transport of a kind of effects answers its ping.
+    Transport,
+}
+
+impl fmt::Display for CheckKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckKind::Schema => write!(f, "schema"),
+            CheckKind::Expression => write!(f, "expression"),
+            CheckKind::Script => write!(f, "script"),
+            CheckKind::Resolver => write!(f, "resolver"),
+            CheckKind::Transport => write!(f, "transport"),
+        }
+    }
+}
+
+/// The outcome of a single check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    kind: CheckKind,
+    name: String,
+    error: Option<String>,
+}
+
+impl CheckResult {
+    pub fn get_kind(&self) -> CheckKind {
+        self.kind
+    }
+
+    /// The rule set, expression, script, resolver or kind of effects checked.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Why the check failed.
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "failed {} `{}`: {error}", self.kind, self.name),
+            None => write!(f, "passed {} `{}`", self.kind, self.name),
+        }
+    }
+}
+
+/// The outcome of `Preflight::run`, every check in the order it was made.
+///
+/// Displayed as `ready` or `not ready`, followed by one line per failed
+/// check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadinessReport {
+    checks: Vec<CheckResult>,
+}
+
+impl ReadinessReport {
+    /// Whether every check passed.
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(CheckResult::is_passed)
+    }
+
+    pub fn get_checks(&self) -> &[CheckResult] {
+        &self.checks
+    }
+
+    pub fn get_failures(&self) -> Vec<&CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| !check.is_passed())
+            .collect()
+    }
+}
+
+impl fmt::Display for ReadinessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_ready() {
+            "ready"
+        } else {
+            "not ready"
+        };
+        writeln!(f, "{status}")?;
+        for check in self.get_failures() {
+            writeln!(f, "{check}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The checks to run at startup, see the module documentation.
+pub struct Preflight<'a> {
+    registry: &'a RuleRegistry,
+    keys: Option<HashSet<&'static str>>,
+    expressions: Vec<(String, String)>,
+    scripts: Vec<(String, String)>,
+    pings: Vec<(CheckKind, String, Ping<'a>)>,
+}
+
+impl<'a> Preflight<'a> {
+    /// Checks the rule sets of the registry, see `RuleRegistry::preflight`.
+    pub fn new(registry: &'a RuleRegistry) -> Self {
+        Preflight {
+            registry,
+            keys: None,
+            expressions: Vec::new(),
+            scripts: Vec::new(),
+            pings: Vec::new(),
+        }
+    }
+
+    /// Declares context keys, checking that the rules only read and write
+    /// declared keys. Without declared keys, the rule sets aren't checked.
+    pub fn with_keys(mut self, keys: &[ContextKeyInfo]) -> Self {
+        self.keys
+            .get_or_insert_with(HashSet::new)
+            .extend(keys.iter().map(ContextKeyInfo::name));
+        self
+    }
+
+    /// Checks that an expression compiles, see `dredd_rs::expr::Expr`.
+    #[cfg(feature = "expr")]
+    pub fn expression(mut self, name: &str, source: &str) -> Self {
+        self.expressions
+            .push((name.to_string(), source.to_string()));
+        self
+    }
+
+    /// Checks that a script compiles, see `dredd_rs::script::Script`.
+    #[cfg(feature = "rhai")]
+    pub fn script(mut self, name: &str, source: &str) -> Self {
+        self.scripts.push((name.to_string(), source.to_string()));
+        self
+    }
+
+    /// Checks that a resolver the rules query, such as a lookup table or a
+    /// model, answers: `ping` returns an error when it doesn't.
+    pub fn resolver(mut self, name: &str, ping: impl Fn() -> Result<(), String> + 'a) -> Self {
+        self.pings
+            .push((CheckKind::Resolver, name.to_string(), Box::new(ping)));
+        self
+    }
+
+    /// Checks that every transport of the registry answers its ping.
+    pub fn transports(mut self, transports: &'a TransportRegistry) -> Self {
+        for kind in transports.get_kinds() {
+            let ping = move || transports.ping(kind).unwrap_or(Ok(()));
+            self.pings
+                .push((CheckKind::Transport, kind.to_string(), Box::new(ping)));
+        }
+        self
+    }
+
+    /// Runs the checks: the rule sets, in the order of their names, the
+    /// expressions, the scripts, then the resolvers and transports in the
+    /// order they were added.
+    pub fn run(&self) -> ReadinessReport {
+        let mut checks = Vec::new();
+        if let Some(keys) = &self.keys {
+            for name in self.registry.get_names() {
+                let problems: Vec<_> = self
+                    .registry
+                    .get_rules(name)
+                    .iter()
+                    .flat_map(|rule| undeclared_keys(rule, keys))
+                    .collect();
+                checks.push(check(CheckKind::Schema, name, problems.join("; ")));
+            }
+        }
+        #[cfg(feature = "expr")]
+        for (name, source) in &self.expressions {
+            let error = crate::expr::Expr::parse(source).err();
+            checks.push(check(
+                CheckKind::Expression,
+                name,
+                error.map(|error| error.to_string()).unwrap_or_default(),
+            ));
+        }
+        #[cfg(feature = "rhai")]
+        for (name, source) in &self.scripts {
+            let error = crate::script::Script::compile(source).err();
+            checks.push(check(
+                CheckKind::Script,
+                name,
+                error.map(|error| error.to_string()).unwrap_or_default(),
+            ));
+        }
+        for (kind, name, ping) in &self.pings {
+            checks.push(check(*kind, name, ping().err().unwrap_or_default()));
+        }
+        ReadinessReport { checks }
+    }
+}
+
+impl fmt::Debug for Preflight<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Preflight")
+            .field("rule_sets", &self.registry.get_names())
+            .field("keys", &self.keys)
+            .field("expressions", &self.expressions)
+            .field("scripts", &self.scripts)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A check failing with `error`, unless it is empty.
+fn check(kind: CheckKind, name: &str, error: String) -> CheckResult {
+    CheckResult {
+        kind,
+        name: name.to_string(),
+        error: (!error.is_empty()).then_some(error),
+    }
+}
+
+/// The keys a rule reads or writes that aren't declared.
+fn undeclared_keys(rule: &Metadata, keys: &HashSet<&'static str>) -> Vec<String> {
+    let rule_name = rule
+        .get_name()
+        .or(rule.get_id())
+        .unwrap_or("<unnamed rule>");
+    let reads = rule.reads.iter().map(|key| ("reads", key));
+    let writes = rule.writes.iter().map(|key| ("writes", key));
+    reads
+        .chain(writes)
+        .filter(|(_, key)| !keys.contains(key.as_str()))
+        .map(|(access, key)| format!("`{rule_name}` {access} undeclared key `{key}`"))
+        .collect()
+}
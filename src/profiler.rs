@@ -0,0 +1,150 @@
+//! Attributes the time spent in rules to rule paths, in the folded stacks
+//! format read by `inferno` and `flamegraph.pl`.
+//!
+//! Each line of the output is a path of rule names from a top level rule to a
+//! nested one, separated by `;`, followed by the time spent in the last rule
+//! of the path, excluding its children, in microseconds. Rules are labelled by
+//! name, then id, then `<unnamed>`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::profiler::Profiler;
+//! use dredd_rs::rule::*;
+//!
+//! // Profile one run out of ten.
+//! let mut profiler = Profiler::new().with_sample_every(10);
+//!
+//! for _ in 0..100 {
+//!     let rule = ChainRule::new()
+//!         .with_name("checkout")
+//!         .add_child(ChainRule::new().with_name("fraud_check"));
+//!     profiler.run(&Engine::chain_runner(), RuleContext::new(), vec![rule]);
+//! }
+//!
+//! assert_eq!(profiler.get_sampled_runs(), 10);
+//! let folded = profiler.to_folded();
+//! assert!(folded.contains("checkout;fraud_check "));
+//! // std::fs::write("rules.folded", folded).unwrap();
+//! // then: inferno-flamegraph rules.folded > rules.svg
+//! ```
+
+use std::{collections::BTreeMap, io, time::Duration};
+
+use crate::rule::{ExecutionTrace, RuleContextWrapper, RuleRunner, TraceEntry, Wrapper};
+
+/// Aggregates the self time of rule paths over many runs.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    stacks: BTreeMap<String, Duration>,
+    sample_every: u64,
+    runs: u64,
+    sampled_runs: u64,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler {
+            stacks: BTreeMap::new(),
+            sample_every: 1,
+            runs: 0,
+            sampled_runs: 0,
+        }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only traces one out of every `sample_every` runs started with `run`,
+    /// so that the others don't pay for tracing. `0` is treated as `1`.
+    pub fn with_sample_every(mut self, sample_every: u64) -> Self {
+        self.sample_every = sample_every.max(1);
+        self
+    }
+
+    /// Runs the rules with the given runner, recording the run when it is sampled.
+    pub fn run<R: RuleRunner>(
+        &mut self,
+        runner: &R,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<R::RuleType>>,
+    ) {
+        self.runs += 1;
+        if self.runs.is_multiple_of(self.sample_every) {
+            let report = runner.run_with_report(rule_context, rules);
+            self.record(report.get_trace());
+        } else {
+            runner.run(rule_context, rules);
+        }
+    }
+
+    /// Adds the self time of every rule in the trace to its path.
+    pub fn record(&mut self, trace: &ExecutionTrace) {
+        self.sampled_runs += 1;
+        for (path, self_time) in self_times(trace) {
+            *self.stacks.entry(path).or_default() += self_time;
+        }
+    }
+
+    pub fn get_runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// How many runs were recorded.
+    pub fn get_sampled_runs(&self) -> u64 {
+        self.sampled_runs
+    }
+
+    /// The recorded paths in the folded stacks format, sorted by path.
+    pub fn to_folded(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|(path, self_time)| format!("{path} {}\n", self_time.as_micros()))
+            .collect()
+    }
+
+    pub fn write_folded(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(self.to_folded().as_bytes())
+    }
+
+    /// Forgets everything recorded so far.
+    pub fn reset(&mut self) {
+        self.stacks.clear();
+        self.runs = 0;
+        self.sampled_runs = 0;
+    }
+}
+
+impl ExecutionTrace {
+    /// The trace in the folded stacks format, see the `profiler` module.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut profiler = Profiler::new();
+        profiler.record(self);
+        profiler.to_folded()
+    }
+}
+
+/// The path and self time of every entry, in trace order.
+fn self_times(trace: &ExecutionTrace) -> Vec<(String, Duration)> {
+    let mut paths: Vec<String> = Vec::with_capacity(trace.get_entries().len());
+    for (entry, parent) in trace.get_entries().iter().zip(trace.get_parents()) {
+        let label = label(entry);
+        let path = match parent {
+            Some(parent) => format!("{};{label}", paths[parent]),
+            None => label,
+        };
+        paths.push(path);
+    }
+    paths.into_iter().zip(trace.get_self_times()).collect()
+}
+
+fn label(entry: &TraceEntry) -> String {
+    entry
+        .get_name()
+        .or(entry.get_id())
+        .unwrap_or("<unnamed>")
+        .replace(';', ":")
+}
@@ -0,0 +1,251 @@
+use std::{collections::BTreeMap, error::Error, fmt, rc::Rc};
+
+use crate::preflight::Preflight;
+use crate::rule::{
+    Invariant, Metadata, Rule, RuleContextWrapper, RuleError, RuleFailure, RuleHandle, RuleRunner,
+    RunReport, Wrapper,
+};
+
+type RunFn = Rc<dyn Fn(RuleContextWrapper) -> RunReport>;
+
+/// Named rule sets, registered once and executed by name.
+///
+/// Each rule set is registered together with the runner it is run with, so
+/// sets of different rule types can live in the same registry. Registering a
+/// name again replaces its rule set. Like the rules it holds, a registry is
+/// cheap to clone: clones share the registered rule sets.
+///
+/// The rules of the sets registered with `register` can be switched off and
+/// on at runtime through `get_handle`, see `RuleHandle`:
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut registry = RuleRegistry::new();
+/// registry.register("checkout", Engine::all_runner(), vec![
+///     AllRule::new().with_name("fraud check").on_execute(|this| this.get_rule_context().set("checked", true)),
+/// ]);
+///
+/// registry.get_handle("checkout", "fraud check").unwrap().disable();
+///
+/// let report = registry.execute_with_report("checkout", RuleContext::new()).unwrap();
+/// assert_eq!(report.get_trace().get_by_outcome(RuleOutcome::Skipped("disabled")).len(), 1);
+/// ```
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut registry = RuleRegistry::new();
+/// registry
+///     .register(
+///         "checkout",
+///         Engine::best_first_runner(),
+///         vec![BestFirstRule::new().on_execute(|this| this.get_rule_context().set("approved", true))],
+///     )
+///     .register("audit", Engine::all_runner(), vec![AllRule::new()]);
+///
+/// assert_eq!(registry.get_names(), vec!["audit", "checkout"]);
+///
+/// let rule_context = RuleContext::new();
+/// registry.execute("checkout", rule_context.clone()).unwrap();
+/// assert!(*rule_context.get::<bool>("approved").unwrap());
+///
+/// assert!(registry.execute("pricing", RuleContext::new()).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct RuleRegistry {
+    rule_sets: BTreeMap<String, RunFn>,
+    rules: BTreeMap<String, Rc<Vec<Metadata>>>,
+    invariants: BTreeMap<String, Vec<Invariant>>,
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers rules to be run with the given runner.
+    pub fn register<R>(
+        &mut self,
+        name: &str,
+        runner: R,
+        rules: Vec<Wrapper<R::RuleType>>,
+    ) -> &mut Self
+    where
+        R: RuleRunner + 'static,
+        R::RuleType: Rule<R::RuleType> + 'static,
+    {
+        let metadata = collect_metadata(&rules);
+        self.register_with(name, move |rule_context| {
+            runner.run_with_report(rule_context, rules.clone())
+        });
+        self.rules.insert(name.to_string(), Rc::new(metadata));
+        self
+    }
+
+    /// Registers a rule set given as a function running it, such as
+    /// `LoadedRules::run_with_report` for rules loaded from JSON.
+    pub fn register_with(
+        &mut self,
+        name: &str,
+        run: impl Fn(RuleContextWrapper) -> RunReport + 'static,
+    ) -> &mut Self {
+        self.rule_sets.insert(name.to_string(), Rc::new(run));
+        self.rules.remove(name);
+        self
+    }
+
+    /// Declares an invariant of a rule set, checked after each of its runs
+    /// like `RuleRunner::run_with_invariants`. Invariants are kept when the
+    /// rule set is registered again.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let mut registry = RuleRegistry::new();
+    /// registry
+    ///     .register("credit", Engine::all_runner(), vec![
+    ///         AllRule::new().on_execute(|this| this.get_rule_context().set("approved", true)),
+    ///     ])
+    ///     .add_invariant(
+    ///         "credit",
+    ///         Invariant::when(Condition::key_equals("approved", true)).then(Condition::key_exists("limit")),
+    ///     );
+    ///
+    /// let result = registry.execute("credit", RuleContext::new());
+    /// assert_eq!(
+    ///     result.unwrap_err().to_string(),
+    ///     "invariant violated: if `approved` == true then `limit` is set"
+    /// );
+    /// ```
+    pub fn add_invariant(&mut self, rule_set: &str, invariant: Invariant) -> &mut Self {
+        self.invariants
+            .entry(rule_set.to_string())
+            .or_default()
+            .push(invariant);
+        self
+    }
+
+    /// The invariants declared for a rule set, in order.
+    pub fn get_invariants(&self, rule_set: &str) -> &[Invariant] {
+        self.invariants.get(rule_set).map_or(&[], Vec::as_slice)
+    }
+
+    /// Starts the startup checks of the registered rule sets, see
+    /// `dredd_rs::preflight`.
+    pub fn preflight(&self) -> Preflight<'_> {
+        Preflight::new(self)
+    }
+
+    /// Removes a rule set, returning whether it was registered.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.rules.remove(name);
+        self.invariants.remove(name);
+        self.rule_sets.remove(name).is_some()
+    }
+
+    /// The metadata of every rule of a rule set registered with `register`,
+    /// children included, in depth-first order. Empty for unknown rule sets
+    /// and those registered with `register_with`.
+    pub fn get_rules(&self, rule_set: &str) -> &[Metadata] {
+        self.rules
+            .get(rule_set)
+            .map_or(&[], |rules| rules.as_slice())
+    }
+
+    /// The handle of the first rule of a rule set, in depth-first order,
+    /// whose name or id is `rule`.
+    pub fn get_handle(&self, rule_set: &str, rule: &str) -> Option<RuleHandle> {
+        self.get_rules(rule_set)
+            .iter()
+            .find(|metadata| metadata.get_name() == Some(rule) || metadata.get_id() == Some(rule))
+            .map(Metadata::get_handle)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.rule_sets.contains_key(name)
+    }
+
+    /// The names of the registered rule sets, sorted.
+    pub fn get_names(&self) -> Vec<&str> {
+        self.rule_sets.keys().map(String::as_str).collect()
+    }
+
+    /// Runs a rule set and returns the failure that stopped it, taking it out
+    /// of the context like `RuleRunner::try_run`.
+    pub fn execute(
+        &self,
+        name: &str,
+        rule_context: RuleContextWrapper,
+    ) -> Result<(), RegistryError> {
+        self.execute_with_report(name, rule_context.clone())?;
+        match rule_context.borrow_mut().take_error() {
+            Some(error) => Err(RegistryError::Failed(error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs a rule set like `RuleRunner::run_with_report`, checking its
+    /// invariants like `RuleRunner::run_with_invariants`.
+    pub fn execute_with_report(
+        &self,
+        name: &str,
+        rule_context: RuleContextWrapper,
+    ) -> Result<RunReport, RegistryError> {
+        let run = self
+            .rule_sets
+            .get(name)
+            .ok_or_else(|| RegistryError::UnknownRuleSet(name.to_string()))?;
+        let mut report = run(rule_context.clone());
+        report.check_invariants(&rule_context, self.get_invariants(name));
+        Ok(report)
+    }
+}
+
+impl fmt::Debug for RuleRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuleRegistry")
+            .field("rule_sets", &self.get_names())
+            .finish()
+    }
+}
+
+/// The metadata of the rules and of their children, depth first.
+fn collect_metadata<R: Rule<R>>(rules: &[Wrapper<R>]) -> Vec<Metadata> {
+    let mut metadata = Vec::new();
+    for rule in rules {
+        metadata.push(rule.borrow().get_metadata().clone());
+        let children = rule.borrow_mut().get_children();
+        metadata.extend(collect_metadata(&children));
+    }
+    metadata
+}
+
+/// Errors returned when executing a registered rule set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// No rule set is registered under this name.
+    UnknownRuleSet(String),
+    /// The rule set was run and a rule failed.
+    Failed(RuleError),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::UnknownRuleSet(name) => write!(f, "unknown rule set `{name}`"),
+            RegistryError::Failed(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RegistryError::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
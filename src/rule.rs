@@ -1,21 +1,182 @@
-use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
+#[cfg(feature = "rayon")]
+use std::sync::{Arc, Mutex};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
+
+use budget::LimitState;
+use change_tracking::ChangeJournal;
+use compensation::{record_compensation, Compensation};
+use filter::RuleFilter;
+use key_usage::KeyRecorder;
+
+use crate::time::Instant;
 
 pub use crate::engine::Engine;
+pub use crate::fixpoint::{FixpointOutcome, FixpointReport};
+pub use crate::goal_solver::{GoalReport, GoalSolver};
+pub use crate::indexed_engine::IndexedEngine;
+pub use crate::registry::{RegistryError, RuleRegistry};
+pub use crate::rule::all_rule::AllRule;
 pub use crate::rule::best_first_rule::BestFirstRule;
+pub use crate::rule::budget::{Budget, BudgetLimit};
+pub use crate::rule::builder::{BestFirstRuleBuilder, ChainRuleBuilder, NoChildren, WithChildren};
+pub use crate::rule::canary::{Canary, CanaryOutcome};
 pub use crate::rule::chain_rule::ChainRule;
-pub use crate::runner::RuleRunner;
-
+pub use crate::rule::change_tracking::{ChangeKind, ContextChange};
+pub use crate::rule::condition::Condition;
+#[cfg(feature = "decimal")]
+pub use crate::rule::context_decimal::ContextDecimal;
+#[cfg(feature = "serde")]
+pub use crate::rule::context_json::ContextJson;
+pub use crate::rule::context_key::{AsContextKey, ContextKey, ContextKeyInfo, Key};
+pub use crate::rule::context_list::ContextList;
+pub use crate::rule::context_mut::{ContextEntry, ContextMut, IntMut, ValueMut};
+pub use crate::rule::context_object::{ContextObject, ContextPath};
+pub use crate::rule::context_view::ContextView;
+pub use crate::rule::decision_cache::{DecisionCache, DecisionCacheBuilder, DecisionCacheError};
+#[cfg(feature = "csv")]
+pub use crate::rule::decision_table_rule::{DecisionTableError, DecisionTableRule};
+pub use crate::rule::degradation::DegradationPolicy;
+pub use crate::rule::depth_guard::DEFAULT_MAX_DEPTH;
+pub use crate::rule::duration_limit_rule::DurationLimitRule;
+pub use crate::rule::error::{ErrorPolicy, RuleError, RuleFailure};
+pub use crate::rule::fallback_rule::FallbackRule;
+pub use crate::rule::fn_rule::{Decision, FnRule, FnRuleMetrics};
+pub use crate::rule::handle::RuleHandle;
+pub use crate::rule::invariant::{Invariant, InvariantWhen};
+pub use crate::rule::key_usage::KeyUsage;
+pub use crate::rule::layered_context::LayeredContext;
+#[cfg(feature = "csv")]
+pub use crate::rule::lookup::{CsvTable, CsvTableError};
+pub use crate::rule::lookup::{Lookup, LookupTable};
+pub use crate::rule::loop_rule::LoopRule;
+#[cfg(feature = "onnx")]
+pub use crate::rule::model_rule::OnnxModel;
+pub use crate::rule::model_rule::{Model, ModelRule};
+#[cfg(feature = "rayon")]
+pub use crate::rule::parallel_rule::{
+    ParallelRule, SyncRuleCallback, SyncRuleChildren, SyncRuleMetadata,
+};
+pub use crate::rule::quarantine::Quarantine;
+pub use crate::rule::quorum_rule::QuorumRule;
+pub use crate::rule::retry_rule::{Backoff, RetryRule};
+pub use crate::rule::rng::Rng;
+pub use crate::rule::scorecard_rule::ScorecardRule;
+pub use crate::rule::shared_rule_context::{SharedContextError, SharedRuleContext};
+pub use crate::rule::snapshot::ContextSnapshot;
+pub use crate::rule::switch_rule::SwitchRule;
+pub use crate::rule::threshold_rule::ThresholdRule;
+pub use crate::rule::trace::{ExecutionTrace, RuleOutcome, TraceEntry};
+pub use crate::rule::verdict::{RuleVerdict, RuleVerdicts, Verdict, Verdicts};
+pub use crate::rule::warning::{RuleWarnings, Severity, Warning};
+pub use crate::rule::weighted_choice::WeightedChoice;
+pub use crate::rule::weighted_random_rule::WeightedRandomRule;
+pub use crate::runner::{RuleRunner, RunMode, RunReport, RunStatus};
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
+
+pub(crate) mod all_rule;
 pub(crate) mod best_first_rule;
+pub(crate) mod budget;
+pub(crate) mod builder;
+pub(crate) mod canary;
 pub(crate) mod chain_rule;
+pub(crate) mod change_tracking;
+pub(crate) mod child_scope;
+pub(crate) mod compensation;
+pub(crate) mod condition;
+#[cfg(feature = "decimal")]
+pub(crate) mod context_decimal;
+#[cfg(feature = "serde")]
+pub(crate) mod context_json;
+pub(crate) mod context_key;
+pub(crate) mod context_list;
+pub(crate) mod context_mut;
+pub(crate) mod context_object;
+pub(crate) mod context_view;
+pub(crate) mod decision_cache;
+#[cfg(feature = "csv")]
+pub(crate) mod decision_table_rule;
+pub(crate) mod degradation;
+pub(crate) mod depth_guard;
+pub(crate) mod duration_limit_rule;
+pub(crate) mod error;
+pub(crate) mod fallback_rule;
+pub(crate) mod filter;
+pub(crate) mod fn_rule;
+pub(crate) mod handle;
+pub(crate) mod invariant;
+pub(crate) mod key_alias;
+pub(crate) mod key_usage;
+pub(crate) mod layered_context;
+pub(crate) mod lookup;
+pub(crate) mod loop_rule;
+pub(crate) mod model_rule;
+#[cfg(feature = "rayon")]
+pub(crate) mod parallel_rule;
+pub(crate) mod quarantine;
+pub(crate) mod quorum_rule;
+pub(crate) mod retry_rule;
+pub(crate) mod rng;
+pub(crate) mod scorecard_rule;
+pub(crate) mod shared_rule_context;
+pub(crate) mod snapshot;
+#[cfg(feature = "tracing")]
+pub(crate) mod spans;
+pub(crate) mod switch_rule;
+pub(crate) mod threshold_rule;
+pub(crate) mod trace;
+pub(crate) mod value_types;
+pub(crate) mod verdict;
+pub(crate) mod warning;
+pub(crate) mod weighted_choice;
+pub(crate) mod weighted_random_rule;
 
 pub(crate) type Wrapper<T> = Rc<RefCell<T>>;
 pub(crate) type RuleContextWrapper = Rc<RefCell<RuleContext>>;
+pub(crate) type Callback<T> = Wrapper<dyn Fn(&mut T)>;
 pub(crate) type RuleContextMap = HashMap<&'static str, Rc<dyn Any + 'static>>;
 
 pub(crate) fn wrap<T>(something: T) -> Wrapper<T> {
     Rc::new(RefCell::new(something))
 }
 
+/// Context keys are `&'static str`, so keys only known at runtime, such as
+/// those read from JSON files or set by scripts, are interned: each distinct
+/// key is leaked once and reused afterwards.
+#[cfg(any(feature = "serde", feature = "rhai"))]
+pub(crate) fn intern(key: &str) -> &'static str {
+    use std::{
+        collections::HashSet,
+        sync::{Mutex, OnceLock},
+    };
+
+    static KEYS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+    let mut keys = KEYS.get_or_init(Default::default).lock().unwrap();
+    match keys.get(key) {
+        Some(key) => key,
+        None => {
+            let key: &'static str = Box::leak(key.to_string().into_boxed_str());
+            keys.insert(key);
+            key
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+pub(crate) type SyncWrapper<T> = Arc<Mutex<T>>;
+
+#[cfg(feature = "rayon")]
+pub(crate) fn sync_wrap<T>(something: T) -> SyncWrapper<T> {
+    Arc::new(Mutex::new(something))
+}
+
 /// RuleContext is a struct that holds the context of the rule.
 /// It is used to store and retrieve values that are used by the rules.
 ///
@@ -30,28 +191,175 @@ pub(crate) fn wrap<T>(something: T) -> Wrapper<T> {
 #[derive(Debug, Clone)]
 pub struct RuleContext {
     context_map: RuleContextMap,
+    trace: Option<ExecutionTrace>,
+    error: Option<RuleError>,
+    mode: RunMode,
+    error_policy: ErrorPolicy,
+    errors: Vec<RuleError>,
+    degradation_policy: DegradationPolicy,
+    degraded: Vec<RuleError>,
+    degraded_rule: bool,
+    limits: LimitState,
+    deadline: Option<Instant>,
+    compensations: Option<Vec<Compensation>>,
+    key_recorder: Option<KeyRecorder>,
+    changes: Option<ChangeJournal>,
+    key_aliases: HashMap<&'static str, &'static str>,
+    warnings: RefCell<Vec<Warning>>,
+    verdicts: Vec<RuleVerdict>,
+    layers: Vec<RuleContextWrapper>,
+    scope: bool,
+    rng: Cell<Rng>,
+    filter: Option<RuleFilter>,
 }
 
 impl RuleContext {
     pub fn new() -> Wrapper<Self> {
+        RuleContext::from_context_map(HashMap::new())
+    }
+
+    pub(crate) fn get_context_map(&self) -> &RuleContextMap {
+        &self.context_map
+    }
+
+    pub(crate) fn from_context_map(context_map: RuleContextMap) -> Wrapper<Self> {
         wrap(RuleContext {
-            context_map: HashMap::new(),
+            context_map,
+            trace: None,
+            error: None,
+            mode: RunMode::Normal,
+            error_policy: ErrorPolicy::Abort,
+            errors: Vec::new(),
+            degradation_policy: DegradationPolicy::Fail,
+            degraded: Vec::new(),
+            degraded_rule: false,
+            limits: LimitState::default(),
+            deadline: None,
+            compensations: None,
+            key_recorder: None,
+            changes: None,
+            key_aliases: HashMap::new(),
+            warnings: RefCell::default(),
+            verdicts: Vec::new(),
+            layers: Vec::new(),
+            scope: false,
+            rng: Cell::default(),
+            filter: None,
         })
     }
+
+    /// Takes the settings of the runs of `other`, for a context standing in
+    /// for it: its mode, error and degradation policies, budget and depth,
+    /// deadline, key aliases, layers, random generator, filter and whether a
+    /// trace is collected. The values, the errors and what the runs reported
+    /// are not taken.
+    pub(crate) fn inherit_settings(&mut self, other: &RuleContext) {
+        self.mode = other.mode;
+        self.error_policy = other.error_policy;
+        self.degradation_policy = other.degradation_policy;
+        self.limits = other.limits.clone();
+        self.deadline = other.deadline;
+        self.key_aliases = other.key_aliases.clone();
+        self.layers = other.layers.clone();
+        self.rng.set(other.rng.get());
+        self.filter = other.filter.clone();
+        self.trace = other.trace.as_ref().map(|_| ExecutionTrace::default());
+    }
+
+    /// Whether the rules are being run for real or as a dry run.
+    pub fn get_run_mode(&self) -> RunMode {
+        self.mode
+    }
+
+    pub(crate) fn set_run_mode(&mut self, mode: RunMode) {
+        self.mode = mode;
+    }
+
+    /// Sets an already boxed value, see `GetSet::set`.
+    pub(crate) fn set_value(&mut self, key: &'static str, value: Rc<dyn Any>) {
+        let key = self.resolve_key(key);
+        if let Some(recorder) = &mut self.key_recorder {
+            recorder.write(key);
+        }
+        self.track_change(key, None);
+        self.context_map.insert(key, value);
+    }
+
+    /// Removes the value of the key, telling whether the context held one.
+    /// A value of a layer backing the context, see `LayeredContext`, is read
+    /// again once the context's own is removed.
+    pub fn remove(&mut self, key: &'static str) -> bool {
+        let key = self.resolve_key(key);
+        if !self.context_map.contains_key(key) {
+            return false;
+        }
+        if let Some(recorder) = &mut self.key_recorder {
+            recorder.write(key);
+        }
+        self.track_change(key, Some(ChangeKind::Remove));
+        self.context_map.remove(key);
+        true
+    }
 }
 
+type Phase<T> = fn(&mut T);
+
+/// Runs the execute callbacks of a rule whose evaluation passed, then its
+/// children, stopping as soon as one of them records a failure or degrades
+/// on one, see `DegradationPolicy`. In a dry run only the children are
+/// fired.
+pub(crate) fn run_execute_phases<T: Rule<T> + Clone + 'static>(rule: &mut T) {
+    if rule.get_rule_context().borrow().mode == RunMode::DryRun {
+        #[cfg(feature = "tracing")]
+        let _phase = spans::enter_phase("children");
+        rule.run_children();
+        return;
+    }
+    let phases: [(&str, Phase<T>); 4] = [
+        ("pre_execute", T::run_pre_execute),
+        ("execute", T::run_execute),
+        ("post_execute", T::run_post_execute),
+        ("children", T::run_children),
+    ];
+    for (name, phase) in phases {
+        let rule_context = rule.get_rule_context();
+        if rule_context.has_failed() || rule_context.borrow().is_degraded_rule() {
+            break;
+        }
+        if name == "children" {
+            record_compensation(rule);
+        }
+        #[cfg(feature = "tracing")]
+        let _phase = spans::enter_phase(name);
+        phase(rule);
+    }
+}
+
+/// Reads and writes context values.
+///
+/// Keys are either plain `&'static str` names, whose values may be of any
+/// type, or typed `ContextKey`s, with which the compiler checks the type of
+/// the values, see `AsContextKey`.
 pub trait GetSet {
-    fn set<T: 'static>(&mut self, k: &'static str, v: T);
-    fn get<T: 'static>(&self, key: &'static str) -> Option<Rc<T>>;
+    fn set<T: 'static>(&mut self, k: impl AsContextKey<T>, v: T);
+    fn get<T: 'static>(&self, key: impl AsContextKey<T>) -> Option<Rc<T>>;
+
+    fn set_key<T: 'static>(&mut self, key: ContextKey<T>, v: T) {
+        self.set(key, v);
+    }
+
+    fn get_key<T: 'static>(&self, key: ContextKey<T>) -> Option<Rc<T>> {
+        self.get(key)
+    }
 }
 
 impl GetSet for RuleContext {
-    fn set<T: 'static>(&mut self, k: &'static str, v: T) {
-        self.context_map.insert(k, Rc::new(v));
+    fn set<T: 'static>(&mut self, k: impl AsContextKey<T>, v: T) {
+        self.set_value(k.key_name(), Rc::new(v));
     }
 
-    fn get<T: 'static>(&self, key: &'static str) -> Option<Rc<T>> {
-        let val = self.context_map.get(key).cloned();
+    fn get<T: 'static>(&self, key: impl AsContextKey<T>) -> Option<Rc<T>> {
+        let val = self.lookup(key.key_name());
         if let Some(v) = val {
             if let Ok(result) = v.downcast::<T>() {
                 return Some(result.clone());
@@ -62,22 +370,25 @@ impl GetSet for RuleContext {
 }
 
 impl GetSet for RuleContextWrapper {
-    fn set<T: 'static>(&mut self, k: &'static str, v: T) {
+    fn set<T: 'static>(&mut self, k: impl AsContextKey<T>, v: T) {
         self.borrow_mut().set(k, v);
     }
 
-    fn get<T: 'static>(&self, key: &'static str) -> Option<Rc<T>> {
+    fn get<T: 'static>(&self, key: impl AsContextKey<T>) -> Option<Rc<T>> {
         self.borrow_mut().get::<T>(key)
     }
 }
 
-pub trait Rule<T> {
+pub trait Rule<T>: MetadataAccess {
     fn fire(&mut self) -> bool;
 
     fn run_eval(&self) -> bool;
     fn run_pre_execute(&mut self);
     fn run_execute(&mut self);
     fn run_post_execute(&mut self);
+    /// The callback undoing the effects of the rule, see
+    /// `RuleCallback::on_compensate`.
+    fn get_compensate(&self) -> Option<Callback<T>>;
 
     fn set_rule_context(&mut self, rule_context: RuleContextWrapper);
     fn get_rule_context(&mut self) -> RuleContextWrapper;
@@ -88,11 +399,217 @@ pub trait Rule<T> {
     fn add_children(&mut self, rules: Vec<Wrapper<T>>);
 }
 
+/// Reads and changes the metadata of a rule, see `Metadata`.
+///
+/// Rule types only give access to their `Metadata`; the other methods read
+/// and change its fields.
+pub trait MetadataAccess {
+    /// All the metadata of the rule, as seen by `Engine::execute_filtered`.
+    fn get_metadata(&self) -> &Metadata;
+    fn get_metadata_mut(&mut self) -> &mut Metadata;
+
+    fn get_id(&self) -> Option<&str> {
+        self.get_metadata().id.as_deref()
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.get_metadata_mut().id = Some(id.to_string());
+    }
+
+    fn get_name(&self) -> Option<&str> {
+        self.get_metadata().name.as_deref()
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.get_metadata_mut().name = Some(name.to_string());
+    }
+
+    fn get_description(&self) -> Option<&str> {
+        self.get_metadata().description.as_deref()
+    }
+
+    fn set_description(&mut self, description: &str) {
+        self.get_metadata_mut().description = Some(description.to_string());
+    }
+
+    /// The evaluation of the rule in plain words, as described by the
+    /// `Condition` given to `RuleCallback::on_condition`.
+    fn get_condition(&self) -> Option<&str> {
+        self.get_metadata().condition.as_deref()
+    }
+
+    fn set_condition(&mut self, condition: &str) {
+        self.get_metadata_mut().condition = Some(condition.to_string());
+    }
+
+    /// The person accountable for the rule.
+    fn get_owner(&self) -> Option<&str> {
+        self.get_metadata().owner.as_deref()
+    }
+
+    fn set_owner(&mut self, owner: &str) {
+        self.get_metadata_mut().owner = Some(owner.to_string());
+    }
+
+    /// The team accountable for the rule.
+    fn get_team(&self) -> Option<&str> {
+        self.get_metadata().team.as_deref()
+    }
+
+    fn set_team(&mut self, team: &str) {
+        self.get_metadata_mut().team = Some(team.to_string());
+    }
+
+    /// The context keys the evaluation of the rule reads, as declared with
+    /// `RuleMetadata::with_reads`.
+    fn get_reads(&self) -> &[String] {
+        &self.get_metadata().reads
+    }
+
+    fn set_reads(&mut self, keys: &[&str]) {
+        self.get_metadata_mut().reads = keys.iter().map(|key| key.to_string()).collect();
+    }
+
+    /// The context keys the execution of the rule writes, as declared with
+    /// `RuleMetadata::with_writes`.
+    fn get_writes(&self) -> &[String] {
+        &self.get_metadata().writes
+    }
+
+    fn set_writes(&mut self, keys: &[&str]) {
+        self.get_metadata_mut().writes = keys.iter().map(|key| key.to_string()).collect();
+    }
+
+    /// The abstract cost of the rule, charged to the budget of a run, see
+    /// `Budget::with_max_cost`.
+    fn get_cost(&self) -> u64 {
+        self.get_metadata().cost
+    }
+
+    fn set_cost(&mut self, cost: u64) {
+        self.get_metadata_mut().cost = cost;
+    }
+
+    /// Whether the rule only enriches the result and may be skipped when the
+    /// budget of the run is exhausted.
+    fn is_optional(&self) -> bool {
+        self.get_metadata().optional
+    }
+
+    fn set_optional(&mut self, optional: bool) {
+        self.get_metadata_mut().optional = optional;
+    }
+
+    /// The tags of the rule, as set with `RuleMetadata::with_tags`.
+    fn get_tags(&self) -> &[String] {
+        &self.get_metadata().tags
+    }
+
+    fn set_tags(&mut self, tags: &[&str]) {
+        self.get_metadata_mut().tags = tags.iter().map(|tag| tag.to_string()).collect();
+    }
+
+    fn get_attribute(&self, key: &str) -> Option<&str> {
+        self.get_metadata().get_attribute(key)
+    }
+
+    fn set_attribute(&mut self, key: &str, value: &str) {
+        self.get_metadata_mut()
+            .attributes
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+/// Identification of a rule, used to tell rules apart when debugging, and to
+/// pick the rules of a run with `Engine::execute_filtered`.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub(crate) id: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) condition: Option<String>,
+    pub(crate) owner: Option<String>,
+    pub(crate) team: Option<String>,
+    pub(crate) reads: Vec<String>,
+    pub(crate) writes: Vec<String>,
+    pub(crate) cost: u64,
+    pub(crate) optional: bool,
+    pub(crate) tags: Vec<String>,
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) handle: RuleHandle,
+}
+
+impl Metadata {
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The evaluation of the rule in plain words, see `Rule::get_condition`.
+    pub fn get_condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+
+    pub fn get_owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn get_team(&self) -> Option<&str> {
+        self.team.as_deref()
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own == tag)
+    }
+
+    /// The value of a key/value attribute, set with
+    /// `RuleMetadata::with_attribute`.
+    pub fn get_attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
+    pub fn get_attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    /// The switch enabling and disabling the rule at runtime.
+    pub fn get_handle(&self) -> RuleHandle {
+        self.handle.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.handle.is_enabled()
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, &self.id) {
+            (Some(name), _) => write!(f, "`{name}`"),
+            (None, Some(id)) => write!(f, "`{id}`"),
+            (None, None) => write!(f, "<unnamed>"),
+        }
+    }
+}
+
 pub trait RuleCallback {
     type RuleType;
+    /// Sets the evaluation function for the rule, which can only read the
+    /// context, see `ContextView`.
     fn on_eval(
         &mut self,
-        eval: impl Fn(&mut Self::RuleType) -> bool + 'static,
+        eval: impl Fn(ContextView<'_>) -> bool + 'static,
     ) -> Wrapper<Self::RuleType>;
     fn on_pre_execute(
         &mut self,
@@ -106,6 +623,134 @@ pub trait RuleCallback {
         &mut self,
         post_execute: impl Fn(&mut Self::RuleType) + 'static,
     ) -> Wrapper<Self::RuleType>;
+    /// Sets a callback undoing the effects of the rule, called when a later
+    /// rule of a run with `RuleRunner::run_with_compensation` fails.
+    fn on_compensate(
+        &mut self,
+        compensate: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType>;
+
+    /// Sets a `Condition` as the evaluation function for the rule, recording
+    /// its description, if any, as the condition of the rule.
+    fn on_condition(&mut self, condition: Condition) -> Wrapper<Self::RuleType>
+    where
+        Self::RuleType: Rule<Self::RuleType>,
+    {
+        let description = condition.get_description().map(str::to_string);
+        let rule = self.on_eval(move |ctx| condition.eval(&ctx.get_rule_context().borrow()));
+        if let Some(description) = description {
+            rule.borrow_mut().set_condition(&description);
+        }
+        rule
+    }
+}
+
+/// Chainable metadata setters, implemented for every wrapped rule type.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new()
+///     .with_id("R-001")
+///     .with_name("adult_check")
+///     .with_description("Approves customers that are 18 or older.")
+///     .with_owner("ana")
+///     .with_team("onboarding")
+///     .on_eval(|_| true);
+///
+/// assert_eq!(rule.borrow().get_name(), Some("adult_check"));
+/// ```
+pub trait RuleMetadata {
+    type RuleType;
+    fn with_id(&mut self, id: &str) -> Wrapper<Self::RuleType>;
+    fn with_name(&mut self, name: &str) -> Wrapper<Self::RuleType>;
+    fn with_description(&mut self, description: &str) -> Wrapper<Self::RuleType>;
+    fn with_owner(&mut self, owner: &str) -> Wrapper<Self::RuleType>;
+    fn with_team(&mut self, team: &str) -> Wrapper<Self::RuleType>;
+    /// Declares the context keys the evaluation of the rule reads, used by
+    /// `IndexedEngine` to skip rules whose keys didn't change.
+    fn with_reads(&mut self, keys: &[&str]) -> Wrapper<Self::RuleType>;
+    /// Declares the context keys the execution of the rule writes, used by
+    /// `GoalSolver` to find the rules that can produce a key.
+    fn with_writes(&mut self, keys: &[&str]) -> Wrapper<Self::RuleType>;
+    /// Declares the abstract cost of the rule, charged to the budget of a
+    /// run, see `Budget::with_max_cost`.
+    fn with_cost(&mut self, cost: u64) -> Wrapper<Self::RuleType>;
+    /// Marks the rule as optional, to be skipped once the budget of a run is
+    /// exhausted.
+    fn with_optional(&mut self, optional: bool) -> Wrapper<Self::RuleType>;
+    /// Tags the rule, for `Engine::execute_filtered` to pick the rules of a
+    /// run by tag, such as the jurisdictions they apply to.
+    fn with_tags(&mut self, tags: &[&str]) -> Wrapper<Self::RuleType>;
+    /// Sets a free-form key/value attribute of the rule.
+    fn with_attribute(&mut self, key: &str, value: &str) -> Wrapper<Self::RuleType>;
+    /// Enables or disables the rule, see `RuleHandle`.
+    fn with_enabled(&mut self, enabled: bool) -> Wrapper<Self::RuleType>;
+}
+
+impl<R: Rule<R>> RuleMetadata for Wrapper<R> {
+    type RuleType = R;
+
+    fn with_id(&mut self, id: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_id(id);
+        self.clone()
+    }
+
+    fn with_name(&mut self, name: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_name(name);
+        self.clone()
+    }
+
+    fn with_description(&mut self, description: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_description(description);
+        self.clone()
+    }
+
+    fn with_owner(&mut self, owner: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_owner(owner);
+        self.clone()
+    }
+
+    fn with_team(&mut self, team: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_team(team);
+        self.clone()
+    }
+
+    fn with_reads(&mut self, keys: &[&str]) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_reads(keys);
+        self.clone()
+    }
+
+    fn with_writes(&mut self, keys: &[&str]) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_writes(keys);
+        self.clone()
+    }
+
+    fn with_cost(&mut self, cost: u64) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_cost(cost);
+        self.clone()
+    }
+
+    fn with_optional(&mut self, optional: bool) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_optional(optional);
+        self.clone()
+    }
+
+    fn with_tags(&mut self, tags: &[&str]) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_tags(tags);
+        self.clone()
+    }
+
+    fn with_attribute(&mut self, key: &str, value: &str) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().set_attribute(key, value);
+        self.clone()
+    }
+
+    fn with_enabled(&mut self, enabled: bool) -> Wrapper<Self::RuleType> {
+        self.borrow().get_metadata().handle.set_enabled(enabled);
+        self.clone()
+    }
 }
 
 pub trait RuleChildren {
@@ -114,33 +759,59 @@ pub trait RuleChildren {
     fn add_children(&mut self, rules: Vec<Wrapper<Self::RuleType>>) -> Wrapper<Self::RuleType>;
 }
 
+/// Adds children that are evaluated in priority order, regardless of the
+/// order they were added in.
+pub trait RulePriority {
+    type RuleType;
+    fn add_child_with_priority(
+        &mut self,
+        rule: Wrapper<Self::RuleType>,
+        priority: i32,
+    ) -> Wrapper<Self::RuleType>;
+    /// Sets the child fired when no other child was executed, whatever the
+    /// priorities of the others.
+    fn add_default_child(&mut self, rule: Wrapper<Self::RuleType>) -> Wrapper<Self::RuleType>;
+}
+
 #[derive(Clone)]
 pub struct BaseRule<T> {
+    metadata: Metadata,
     rule_context: Option<RuleContextWrapper>,
     children: Vec<Wrapper<T>>, // Usar Self permite que a struct seja genérica
-    eval: Wrapper<dyn Fn(&mut T) -> bool>,
+    eval: Wrapper<dyn Fn(ContextView<'_>) -> bool>,
     pre_execute: Wrapper<dyn Fn(&mut T)>,
     execute: Wrapper<dyn Fn(&mut T)>,
     post_execute: Wrapper<dyn Fn(&mut T)>,
 }
 
+impl<T> MetadataAccess for BaseRule<T> {
+    fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn get_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
 impl<T> BaseRule<T> {
     pub fn new() -> Wrapper<Self> {
         wrap(BaseRule {
+            metadata: Metadata::default(),
             rule_context: None,
             children: Vec::new(),
-            eval: wrap(|_: &mut T| true),
+            eval: wrap(|_: ContextView<'_>| true),
             pre_execute: wrap(|_: &mut T| ()),
             execute: wrap(|_: &mut T| ()),
             post_execute: wrap(|_: &mut T| ()),
         })
     }
 
-    pub fn get_eval(&self) -> Wrapper<dyn Fn(&mut T) -> bool> {
+    pub fn get_eval(&self) -> Wrapper<dyn Fn(ContextView<'_>) -> bool> {
         self.eval.clone()
     }
 
-    pub fn set_eval(&mut self, eval: impl Fn(&mut T) -> bool + 'static) {
+    pub fn set_eval(&mut self, eval: impl Fn(ContextView<'_>) -> bool + 'static) {
         self.eval = wrap(eval);
     }
 
@@ -0,0 +1,286 @@
+use crate::{engine::Engine, runner::RuleRunner as _};
+
+#[cfg(feature = "tracing")]
+use super::spans;
+
+use super::{
+    run_execute_phases, wrap, Callback, ContextView, Metadata, MetadataAccess, Rule, RuleCallback,
+    RuleChildren, RuleContext, RuleContextWrapper, RuleFailure, Wrapper,
+};
+
+/// Represents an all rule in the rule evaluation system.
+///
+/// An `AllRule` consists of a context, a list of child rules, and several
+/// function wrappers for evaluation and execution phases. Unlike a `BestFirstRule`,
+/// every child whose evaluation succeeds is fired, in insertion order.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut rule = AllRule::new();
+/// let rule2 = AllRule::new();
+///
+/// rule.on_eval(|_| {
+///     println!("Eval");
+///     true
+/// })
+/// .on_pre_execute(|_| {
+///    println!("Pre Execute");
+/// })
+/// .on_execute(|_| {
+///   println!("Execute");
+/// })
+/// .on_post_execute(|_| {
+///  println!("Post Execute");
+/// })
+/// .add_child(rule2);
+///
+/// Engine::all_runner().run(RuleContext::new(), vec![rule]);
+/// ```
+///
+#[derive(Clone)]
+pub struct AllRule {
+    metadata: Metadata,
+    rule_context: Option<RuleContextWrapper>,
+    children: Vec<Wrapper<AllRule>>,
+    eval: Wrapper<dyn Fn(ContextView<'_>) -> bool>,
+    pre_execute: Wrapper<dyn Fn(&mut Self)>,
+    execute: Wrapper<dyn Fn(&mut Self)>,
+    post_execute: Wrapper<dyn Fn(&mut Self)>,
+    compensate: Option<Callback<Self>>,
+}
+
+impl AllRule {
+    pub fn new() -> Wrapper<Self> {
+        wrap(AllRule {
+            metadata: Metadata::default(),
+            rule_context: None,
+            children: Vec::new(),
+            eval: wrap(|_: ContextView<'_>| true),
+            pre_execute: wrap(|_: &mut Self| ()),
+            execute: wrap(|_: &mut Self| ()),
+            post_execute: wrap(|_: &mut Self| ()),
+            compensate: None,
+        })
+    }
+
+    pub fn on_eval(&mut self, eval: impl Fn(ContextView<'_>) -> bool + 'static) {
+        self.eval = wrap(eval);
+    }
+
+    pub fn on_pre_execute(&mut self, pre_execute: impl Fn(&mut Self) + 'static) {
+        self.pre_execute = wrap(pre_execute);
+    }
+
+    pub fn on_execute(&mut self, execute: impl Fn(&mut Self) + 'static) {
+        self.execute = wrap(execute);
+    }
+
+    pub fn on_post_execute(&mut self, post_execute: impl Fn(&mut Self) + 'static) {
+        self.post_execute = wrap(post_execute);
+    }
+
+    pub fn on_compensate(&mut self, compensate: impl Fn(&mut Self) + 'static) {
+        self.compensate = Some(wrap(compensate));
+    }
+}
+
+impl MetadataAccess for AllRule {
+    fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn get_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
+impl Rule<AllRule> for AllRule {
+    fn fire(&mut self) -> bool {
+        let entry = self
+            .get_rule_context()
+            .borrow_mut()
+            .trace_fire(&self.metadata);
+        #[cfg(feature = "tracing")]
+        let span = spans::FireSpan::enter(&self.metadata);
+        let admitted = self.get_rule_context().borrow_mut().admit(&self.metadata);
+        let eval_result = admitted
+            && {
+                #[cfg(feature = "tracing")]
+                let _phase = spans::enter_phase("eval");
+                self.run_eval()
+            }
+            && !self.get_rule_context().has_failed();
+        if eval_result {
+            run_execute_phases(self);
+        }
+        let rule_context = self.get_rule_context();
+        let mut rule_context = rule_context.borrow_mut();
+        let eval_result = !rule_context.take_degraded_rule() && eval_result;
+        rule_context.trace_fired(entry, eval_result);
+        rule_context.release(admitted);
+        #[cfg(feature = "tracing")]
+        span.finish(eval_result, rule_context.has_failed());
+        rule_context.name_failure(&self.metadata);
+        rule_context.recover_failure();
+        true
+    }
+
+    fn run_eval(&self) -> bool {
+        let rule_context = self.rule_context.clone().unwrap_or_else(RuleContext::new);
+        (self.eval.borrow_mut())(ContextView::new(&rule_context))
+    }
+
+    fn run_pre_execute(&mut self) {
+        (self.pre_execute.borrow_mut())(&mut self.clone());
+    }
+
+    fn run_execute(&mut self) {
+        (self.execute.borrow_mut())(&mut self.clone());
+    }
+
+    fn run_post_execute(&mut self) {
+        (self.post_execute.borrow_mut())(&mut self.clone());
+    }
+
+    fn get_compensate(&self) -> Option<Callback<Self>> {
+        self.compensate.clone()
+    }
+
+    fn set_rule_context(&mut self, rule_context: RuleContextWrapper) {
+        self.rule_context = Some(rule_context);
+    }
+
+    fn get_rule_context(&mut self) -> RuleContextWrapper {
+        self.rule_context.clone().unwrap()
+    }
+
+    fn run_children(&mut self) {
+        let children = self.get_children();
+        let rule_context = self.get_rule_context();
+
+        Engine::all_runner().run(rule_context, children);
+    }
+
+    fn get_children(&mut self) -> Vec<Wrapper<AllRule>> {
+        self.children.clone()
+    }
+
+    fn add_child(&mut self, rule: Wrapper<AllRule>) {
+        self.children.push(rule);
+    }
+
+    fn add_children(&mut self, rules: Vec<Wrapper<AllRule>>) {
+        self.children.extend(rules);
+    }
+}
+
+/// Implementation of the `RuleHelper` trait for `Wrapper<AllRule>`.
+///
+/// This implementation provides methods to set various callbacks for the
+/// `AllRule` wrapped inside the `Wrapper`.
+///
+/// # Type Parameters
+/// - `RuleType`: The type of the rule, which is `AllRule` in this case.
+///
+/// # Methods
+/// - `on_eval`: Sets the evaluation callback for the rule.
+/// - `on_pre_execute`: Sets the pre-execution callback for the rule.
+/// - `on_execute`: Sets the execution callback for the rule.
+/// - `on_post_execute`: Sets the post-execution callback for the rule.
+/// - `on_compensate`: Sets the compensation callback for the rule.
+///
+/// Each method takes a closure as an argument, wraps it, assigns it to the
+/// corresponding field in the rule, and returns a clone of the `Wrapper`.
+impl RuleCallback for Wrapper<AllRule> {
+    type RuleType = AllRule;
+
+    /// Sets the evaluation function for the rule.
+    fn on_eval(
+        &mut self,
+        eval: impl Fn(ContextView<'_>) -> bool + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().eval = wrap(eval);
+        self.clone()
+    }
+
+    /// Sets the pre-execution function for the rule.
+    fn on_pre_execute(
+        &mut self,
+        pre_execute: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().pre_execute = wrap(pre_execute);
+        self.clone()
+    }
+
+    /// Sets the execution function for the rule.
+    fn on_execute(
+        &mut self,
+        execute: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().execute = wrap(execute);
+        self.clone()
+    }
+    /// Sets the post-execution function for the rule.
+    fn on_post_execute(
+        &mut self,
+        post_execute: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().post_execute = wrap(post_execute);
+        self.clone()
+    }
+
+    /// Sets the compensation function for the rule.
+    fn on_compensate(
+        &mut self,
+        compensate: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().compensate = Some(wrap(compensate));
+        self.clone()
+    }
+}
+
+/// Implementation of the `AddChild` trait for `Wrapper<AllRule>`.
+///
+/// This implementation allows adding a single child rule or multiple child rules
+/// to a `Wrapper<AllRule>` instance.
+///
+/// # Type Parameters
+/// - `RuleType`: The type of the rule, which is `AllRule` in this case.
+///
+/// # Methods
+/// - `add_child`: Adds a single child rule to the current instance and returns a clone of the updated instance.
+/// - `add_children`: Adds multiple child rules to the current instance and returns a clone of the updated instance.
+impl RuleChildren for Wrapper<AllRule> {
+    type RuleType = AllRule;
+
+    /// Adds a single child rule to the current instance and returns a clone of the updated instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - A `Wrapper` containing the child rule to be added.
+    ///
+    /// # Returns
+    ///
+    /// A `Wrapper` containing a clone of the updated instance.
+    fn add_child(&mut self, rule: Wrapper<Self::RuleType>) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().add_child(rule);
+        self.clone()
+    }
+
+    /// Adds multiple child rules to the current instance and returns a clone of the updated instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - A vector of `Wrapper` containing the child rules to be added.
+    ///
+    /// # Returns
+    ///
+    /// A `Wrapper` containing a clone of the updated instance.
+    fn add_children(&mut self, rules: Vec<Wrapper<Self::RuleType>>) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().add_children(rules);
+        self.clone()
+    }
+}
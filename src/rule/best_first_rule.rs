@@ -1,6 +1,12 @@
-use crate::{engine::Engine, runner::RuleRunner as _};
+#[cfg(feature = "tracing")]
+use super::spans;
 
-use super::{wrap, Rule, RuleCallback, RuleChildren, RuleContextWrapper, Wrapper};
+use super::{
+    builder::{BestFirstRuleBuilder, NoChildren},
+    depth_guard::fire_rule,
+    run_execute_phases, wrap, Callback, ContextView, Metadata, MetadataAccess, Rule, RuleCallback,
+    RuleChildren, RuleContext, RuleContextWrapper, RuleFailure, RulePriority, Wrapper,
+};
 
 /// Represents a best first rule in the rule evaluation system.
 ///
@@ -33,29 +39,61 @@ use super::{wrap, Rule, RuleCallback, RuleChildren, RuleContextWrapper, Wrapper}
 /// Engine::best_first_runner().run(RuleContext::new(), vec![rule]);
 /// ```
 ///
+/// Children are evaluated in descending priority. Children added without one
+/// have priority `0`, and children with the same priority keep the order they
+/// were added in.
+///
+/// A default child, set with `RulePriority::add_default_child`, is fired when
+/// no other child was executed, so the "otherwise" branch doesn't depend on a
+/// trailing always-true child staying last:
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let shipping = |cost: u32| BestFirstRule::new().on_execute(move |this| this.get_rule_context().set("shipping", cost));
+///
+/// let rule = BestFirstRule::new()
+///     .add_default_child(shipping(30))
+///     .add_child(shipping(10).on_eval(|ctx| ctx.get::<&str>("country").is_some_and(|c| *c == "BR")));
+///
+/// let rule_context = RuleContext::new();
+/// Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+/// assert_eq!(*rule_context.get::<u32>("shipping").unwrap(), 30);
+/// ```
 #[derive(Clone)]
 pub struct BestFirstRule {
+    metadata: Metadata,
     rule_context: Option<RuleContextWrapper>,
-    children: Vec<Wrapper<BestFirstRule>>,
-    eval: Wrapper<dyn Fn(&mut Self) -> bool>,
+    children: Vec<(i32, Wrapper<BestFirstRule>)>,
+    default_child: Option<Wrapper<BestFirstRule>>,
+    eval: Wrapper<dyn Fn(ContextView<'_>) -> bool>,
     pre_execute: Wrapper<dyn Fn(&mut Self)>,
     execute: Wrapper<dyn Fn(&mut Self)>,
     post_execute: Wrapper<dyn Fn(&mut Self)>,
+    compensate: Option<Callback<Self>>,
 }
 
 impl BestFirstRule {
     pub fn new() -> Wrapper<Self> {
         wrap(BestFirstRule {
+            metadata: Metadata::default(),
             rule_context: None,
             children: Vec::new(),
-            eval: wrap(|_: &mut Self| true),
+            default_child: None,
+            eval: wrap(|_: ContextView<'_>| true),
             pre_execute: wrap(|_: &mut Self| ()),
             execute: wrap(|_: &mut Self| ()),
             post_execute: wrap(|_: &mut Self| ()),
+            compensate: None,
         })
     }
 
-    pub fn on_eval(&mut self, eval: impl Fn(&mut Self) -> bool + 'static) {
+    /// Creates a builder that requires at least one child before `build()`.
+    pub fn builder() -> BestFirstRuleBuilder<NoChildren> {
+        BestFirstRuleBuilder::new()
+    }
+
+    pub fn on_eval(&mut self, eval: impl Fn(ContextView<'_>) -> bool + 'static) {
         self.eval = wrap(eval);
     }
 
@@ -70,22 +108,74 @@ impl BestFirstRule {
     pub fn on_post_execute(&mut self, post_execute: impl Fn(&mut Self) + 'static) {
         self.post_execute = wrap(post_execute);
     }
+
+    pub fn on_compensate(&mut self, compensate: impl Fn(&mut Self) + 'static) {
+        self.compensate = Some(wrap(compensate));
+    }
+
+    /// Adds a child rule evaluated before the children with a lower priority.
+    pub fn add_child_with_priority(&mut self, rule: Wrapper<BestFirstRule>, priority: i32) {
+        let index = self
+            .children
+            .partition_point(|(child_priority, _)| *child_priority >= priority);
+        self.children.insert(index, (priority, rule));
+    }
+
+    /// Sets the child fired when no other child was executed, replacing any
+    /// previous one.
+    pub fn add_default_child(&mut self, rule: Wrapper<BestFirstRule>) {
+        self.default_child = Some(rule);
+    }
+
+    pub fn get_default_child(&self) -> Option<Wrapper<BestFirstRule>> {
+        self.default_child.clone()
+    }
+}
+
+impl MetadataAccess for BestFirstRule {
+    fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn get_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
 }
 
 impl Rule<BestFirstRule> for BestFirstRule {
     fn fire(&mut self) -> bool {
-        if self.run_eval() {
-            self.run_pre_execute();
-            self.run_execute();
-            self.run_post_execute();
-            self.run_children();
-            return false;
+        let entry = self
+            .get_rule_context()
+            .borrow_mut()
+            .trace_fire(&self.metadata);
+        #[cfg(feature = "tracing")]
+        let span = spans::FireSpan::enter(&self.metadata);
+        let admitted = self.get_rule_context().borrow_mut().admit(&self.metadata);
+        let eval_result = admitted
+            && {
+                #[cfg(feature = "tracing")]
+                let _phase = spans::enter_phase("eval");
+                self.run_eval()
+            }
+            && !self.get_rule_context().has_failed();
+        if eval_result {
+            run_execute_phases(self);
         }
-        true
+        let rule_context = self.get_rule_context();
+        let mut rule_context = rule_context.borrow_mut();
+        let eval_result = !rule_context.take_degraded_rule() && eval_result;
+        rule_context.trace_fired(entry, eval_result);
+        rule_context.release(admitted);
+        #[cfg(feature = "tracing")]
+        span.finish(eval_result, rule_context.has_failed());
+        rule_context.name_failure(&self.metadata);
+        rule_context.recover_failure();
+        !eval_result
     }
 
     fn run_eval(&self) -> bool {
-        (self.eval.borrow_mut())(&mut self.clone())
+        let rule_context = self.rule_context.clone().unwrap_or_else(RuleContext::new);
+        (self.eval.borrow_mut())(ContextView::new(&rule_context))
     }
 
     fn run_pre_execute(&mut self) {
@@ -100,6 +190,10 @@ impl Rule<BestFirstRule> for BestFirstRule {
         (self.post_execute.borrow_mut())(&mut self.clone());
     }
 
+    fn get_compensate(&self) -> Option<Callback<Self>> {
+        self.compensate.clone()
+    }
+
     fn set_rule_context(&mut self, rule_context: RuleContextWrapper) {
         self.rule_context = Some(rule_context);
     }
@@ -109,22 +203,37 @@ impl Rule<BestFirstRule> for BestFirstRule {
     }
 
     fn run_children(&mut self) {
-        let children = self.get_children();
         let rule_context = self.get_rule_context();
 
-        Engine::best_first_runner().run(rule_context, children);
+        // Like the best first runner, stopping at the first child executed,
+        // or falling through to the default child.
+        for (_, child) in &self.children {
+            if !fire_rule(child, &rule_context) || rule_context.has_failed() {
+                return;
+            }
+        }
+        if let Some(default_child) = &self.default_child {
+            fire_rule(default_child, &rule_context);
+        }
     }
 
+    /// The children in evaluation order, followed by the default child.
     fn get_children(&mut self) -> Vec<Wrapper<BestFirstRule>> {
-        self.children.clone()
+        self.children
+            .iter()
+            .map(|(_, rule)| rule.clone())
+            .chain(self.default_child.clone())
+            .collect()
     }
 
     fn add_child(&mut self, rule: Wrapper<BestFirstRule>) {
-        self.children.push(rule);
+        self.add_child_with_priority(rule, 0);
     }
 
     fn add_children(&mut self, rules: Vec<Wrapper<BestFirstRule>>) {
-        self.children.extend(rules);
+        for rule in rules {
+            self.add_child(rule);
+        }
     }
 }
 
@@ -141,6 +250,7 @@ impl Rule<BestFirstRule> for BestFirstRule {
 /// - `on_pre_execute`: Sets the pre-execution callback for the rule.
 /// - `on_execute`: Sets the execution callback for the rule.
 /// - `on_post_execute`: Sets the post-execution callback for the rule.
+/// - `on_compensate`: Sets the compensation callback for the rule.
 ///
 /// Each method takes a closure as an argument, wraps it, assigns it to the
 /// corresponding field in the rule, and returns a clone of the `Wrapper`.
@@ -150,7 +260,7 @@ impl RuleCallback for Wrapper<BestFirstRule> {
     /// Sets the evaluation function for the rule.
     fn on_eval(
         &mut self,
-        eval: impl Fn(&mut Self::RuleType) -> bool + 'static,
+        eval: impl Fn(ContextView<'_>) -> bool + 'static,
     ) -> Wrapper<Self::RuleType> {
         self.borrow_mut().eval = wrap(eval);
         self.clone()
@@ -181,6 +291,15 @@ impl RuleCallback for Wrapper<BestFirstRule> {
         self.borrow_mut().post_execute = wrap(post_execute);
         self.clone()
     }
+
+    /// Sets the compensation function for the rule.
+    fn on_compensate(
+        &mut self,
+        compensate: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().compensate = Some(wrap(compensate));
+        self.clone()
+    }
 }
 
 /// Implementation of the `AddChild` trait for `Wrapper<BestFirstRule>`.
@@ -225,3 +344,25 @@ impl RuleChildren for Wrapper<BestFirstRule> {
         self.clone()
     }
 }
+
+impl RulePriority for Wrapper<BestFirstRule> {
+    type RuleType = BestFirstRule;
+
+    /// Adds a child rule evaluated before the children with a lower priority
+    /// and returns a clone of the updated instance.
+    fn add_child_with_priority(
+        &mut self,
+        rule: Wrapper<Self::RuleType>,
+        priority: i32,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().add_child_with_priority(rule, priority);
+        self.clone()
+    }
+
+    /// Sets the child fired when no other child was executed and returns a
+    /// clone of the updated instance.
+    fn add_default_child(&mut self, rule: Wrapper<Self::RuleType>) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().add_default_child(rule);
+        self.clone()
+    }
+}
@@ -0,0 +1,339 @@
+use std::{fmt, time::Duration};
+
+use super::{Metadata, RuleContext, RuleError, RuleFailure, DEFAULT_MAX_DEPTH};
+use crate::time::Instant;
+
+/// Limits on the work of a run, so that a pathological rule tree can't take
+/// unbounded time, see `RuleRunner::run_with_budget`.
+///
+/// Rules declare an abstract cost with `RuleMetadata::with_cost`, and the
+/// ones that only enrich the result are marked with
+/// `RuleMetadata::with_optional`. An optional rule is skipped, as if its
+/// evaluation failed, when its cost exceeds what is left of `max_cost` or
+/// when the run has already taken longer than `max_duration`. Required rules
+/// are always charged their cost, even past `max_cost`.
+///
+/// The other limits stop the run: the first required rule fired past
+/// `max_fired` or `max_duration` is not evaluated and fails with
+/// `RuleError::BudgetExceeded`, and whatever the error policy of the run, the
+/// rules fired after it are skipped without being evaluated. A rule nested
+/// deeper than `max_depth` fails alone, its siblings still fire.
+///
+/// Limits are checked before each rule is evaluated: callbacks are not
+/// interrupted, so a run can take longer than `max_duration` by the time of
+/// the callback running when it expires.
+///
+/// Example:
+/// ```rust
+/// use std::time::Duration;
+/// use dredd_rs::rule::*;
+///
+/// let budget = Budget::new()
+///     .with_max_fired(1_000)
+///     .with_max_depth(32)
+///     .with_max_cost(100)
+///     .with_max_duration(Duration::from_millis(50));
+/// assert_eq!(budget.get_max_depth(), Some(32));
+/// assert_eq!(budget.get_max_cost(), Some(100));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Budget {
+    max_fired: Option<usize>,
+    max_depth: Option<usize>,
+    max_duration: Option<Duration>,
+    max_cost: Option<u64>,
+}
+
+impl Budget {
+    /// A budget with no limit but the depth of the context it is set on.
+    pub fn new() -> Self {
+        Budget::default()
+    }
+
+    /// Limits the number of rules fired, children included. Rules skipped
+    /// because they are disabled or filtered out don't count.
+    pub fn with_max_fired(mut self, max_fired: usize) -> Self {
+        self.max_fired = Some(max_fired);
+        self
+    }
+
+    /// Limits the nesting of the rules fired, the rules passed to the runner
+    /// being at depth 1 and their children at depth 2. Without it, the limit
+    /// of the context is kept, see `RuleContext::set_max_depth`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Limits the wall time of the run, after which optional rules are
+    /// skipped and required ones stop the run.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Limits the sum of the costs of the rules fired, past which optional
+    /// rules are skipped.
+    pub fn with_max_cost(mut self, max_cost: u64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    pub fn get_max_fired(&self) -> Option<usize> {
+        self.max_fired
+    }
+
+    pub fn get_max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    pub fn get_max_duration(&self) -> Option<Duration> {
+        self.max_duration
+    }
+
+    pub fn get_max_cost(&self) -> Option<u64> {
+        self.max_cost
+    }
+}
+
+/// The limit of a `Budget` a run went past.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    MaxFired(usize),
+    MaxDepth(usize),
+    MaxDuration(Duration),
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetLimit::MaxFired(max) => write!(f, "more than {max} rules fired"),
+            BudgetLimit::MaxDepth(max) => write!(f, "rules nested deeper than {max}"),
+            BudgetLimit::MaxDuration(max) => write!(f, "run longer than {max:?}"),
+        }
+    }
+}
+
+/// What the rules fired under a budget used so far.
+#[derive(Debug, Clone)]
+pub(crate) struct LimitState {
+    budget: Budget,
+    started: Instant,
+    fired: usize,
+    depth: usize,
+    pub(crate) spent: u64,
+    pub(crate) skipped: Vec<String>,
+    exceeded: bool,
+}
+
+impl Default for LimitState {
+    fn default() -> Self {
+        LimitState::new(Budget::new())
+    }
+}
+
+impl LimitState {
+    pub(crate) fn new(budget: Budget) -> Self {
+        LimitState {
+            budget,
+            started: Instant::now(),
+            fired: 0,
+            depth: 0,
+            spent: 0,
+            skipped: Vec::new(),
+            exceeded: false,
+        }
+    }
+
+    /// A state for `budget` taking over from `current`: the rules already
+    /// firing keep their depth, and without a `max_depth` the budget keeps the
+    /// one of `current`.
+    fn taking_over(mut budget: Budget, current: &LimitState) -> Self {
+        budget.max_depth = budget.max_depth.or(current.budget.max_depth);
+        LimitState {
+            depth: current.depth,
+            ..LimitState::new(budget)
+        }
+    }
+
+    fn get_max_depth(&self) -> usize {
+        self.budget.max_depth.unwrap_or(DEFAULT_MAX_DEPTH)
+    }
+
+    fn is_overdue(&self) -> bool {
+        self.budget
+            .max_duration
+            .is_some_and(|max| self.started.elapsed() >= max)
+    }
+
+    /// The limit another required rule would go past, if any, but the depth.
+    fn exceeded_limit(&self) -> Option<BudgetLimit> {
+        let budget = self.budget;
+        if let Some(max) = budget.max_fired.filter(|max| self.fired >= *max) {
+            return Some(BudgetLimit::MaxFired(max));
+        }
+        budget
+            .max_duration
+            .filter(|_| self.is_overdue())
+            .map(BudgetLimit::MaxDuration)
+    }
+}
+
+impl RuleContext {
+    /// Puts the rules fired with this context under a budget, whose count of
+    /// rules, cost and clock start now, and returns the budget it replaced.
+    /// `Budget::new()` lifts every limit but the depth. Runners do it for a
+    /// single run with `run_with_budget`.
+    pub fn set_execution_budget(&mut self, budget: Budget) -> Budget {
+        self.replace_budget(budget).budget
+    }
+
+    /// Puts the rules fired with this context under a budget, returning what
+    /// the one replaced used so it can be put back with `set_limits`.
+    pub(crate) fn replace_budget(&mut self, budget: Budget) -> LimitState {
+        let state = LimitState::taking_over(budget, &self.limits);
+        self.set_limits(state)
+    }
+
+    /// Replaces the state of the execution budget, keeping what the one
+    /// replaced used so it can be put back.
+    pub(crate) fn set_limits(&mut self, state: LimitState) -> LimitState {
+        std::mem::replace(&mut self.limits, state)
+    }
+
+    pub fn get_execution_budget(&self) -> Budget {
+        self.limits.budget
+    }
+
+    /// Limits how deep the rules fired with this context may nest, the rules
+    /// passed to a runner being at depth 1 and their children at depth 2.
+    /// A rule nested deeper is not evaluated and fails with
+    /// `RuleError::BudgetExceeded`, instead of the run overflowing the
+    /// stack. Defaults to `DEFAULT_MAX_DEPTH`.
+    ///
+    /// The limit is the `max_depth` of the execution budget of the context,
+    /// kept by the budgets of the runs that don't set their own, see
+    /// `Budget::with_max_depth`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = ChainRule::new()
+    ///     .add_child(ChainRule::new().add_child(ChainRule::new()));
+    ///
+    /// let rule_context = RuleContext::new();
+    /// rule_context.borrow_mut().set_max_depth(2);
+    /// let result = Engine::chain_runner().try_run(rule_context, vec![rule]);
+    ///
+    /// assert_eq!(result, Err(RuleError::BudgetExceeded(BudgetLimit::MaxDepth(2))));
+    /// ```
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.limits.budget.max_depth = Some(max_depth);
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.limits.get_max_depth()
+    }
+
+    /// Takes what the rules fired with `standin`, a context standing in for
+    /// this one since `inherit_settings`, used of the budget.
+    pub(crate) fn take_usage(&mut self, standin: &RuleContext) {
+        let depth = self.limits.depth;
+        self.limits = LimitState {
+            depth,
+            ..standin.limits.clone()
+        };
+    }
+
+    /// Called before the evaluation of a rule: tells whether it is enabled,
+    /// the filter of the run, if any, lets it through and it fits in the
+    /// budget, then charges its cost and tells whether it may fire. A rule
+    /// admitted must be released with `release` once fired.
+    pub(crate) fn admit(&mut self, metadata: &Metadata) -> bool {
+        if let Some(reason) = self.get_skip_reason(metadata) {
+            self.trace_skip(reason);
+            return false;
+        }
+        if !self.within_depth() || !self.fits(metadata) || !self.within_limits() {
+            return false;
+        }
+        self.limits.spent += metadata.cost;
+        self.limits.fired += 1;
+        self.limits.depth += 1;
+        true
+    }
+
+    /// Why the rule is left out of the run, when it is disabled or the filter
+    /// of the run doesn't let it through.
+    pub(crate) fn get_skip_reason(&self, metadata: &Metadata) -> Option<&'static str> {
+        if !metadata.is_enabled() {
+            return Some("disabled");
+        }
+        if self
+            .get_filter()
+            .is_some_and(|filter| !filter.allows(metadata))
+        {
+            return Some("filtered");
+        }
+        None
+    }
+
+    /// Tells whether the rule about to be evaluated may nest that deep,
+    /// failing it when it may not.
+    fn within_depth(&mut self) -> bool {
+        let max_depth = self.limits.get_max_depth();
+        if self.limits.depth < max_depth {
+            return true;
+        }
+        self.fail(RuleError::BudgetExceeded(BudgetLimit::MaxDepth(max_depth)));
+        self.trace_skip("max depth");
+        false
+    }
+
+    /// Tells whether the rule about to be evaluated fits in the limits of the
+    /// budget stopping the run, failing it when it is the first one that
+    /// doesn't.
+    fn within_limits(&mut self) -> bool {
+        let state = &mut self.limits;
+        if !state.exceeded {
+            let Some(limit) = state.exceeded_limit() else {
+                return true;
+            };
+            state.exceeded = true;
+            self.fail(RuleError::BudgetExceeded(limit));
+        }
+        self.trace_skip("budget exceeded");
+        false
+    }
+
+    /// Tells whether the cost of a rule fits in the budget, skipping the
+    /// optional rules that don't, or that come past the duration of the run.
+    /// Required rules always fit.
+    fn fits(&mut self, metadata: &Metadata) -> bool {
+        let state = &mut self.limits;
+        if metadata.optional {
+            let over_cost = state
+                .budget
+                .max_cost
+                .is_some_and(|max| state.spent + metadata.cost > max);
+            if over_cost || state.is_overdue() {
+                let name = metadata.name.as_ref().or(metadata.id.as_ref());
+                state
+                    .skipped
+                    .push(name.map_or("<unnamed>".to_string(), String::clone));
+                self.trace_skip("budget");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Called at the end of the firing of a rule, with the result of
+    /// `admit` for it.
+    pub(crate) fn release(&mut self, admitted: bool) {
+        if admitted {
+            self.limits.depth = self.limits.depth.saturating_sub(1);
+        }
+    }
+}
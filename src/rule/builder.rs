@@ -0,0 +1,382 @@
+use std::marker::PhantomData;
+
+#[cfg(feature = "expr")]
+use crate::expr::{Expr, ExprError};
+
+#[cfg(feature = "expr")]
+use super::RuleError;
+use super::{
+    best_first_rule::BestFirstRule, chain_rule::ChainRule, ContextView, MetadataAccess, Rule,
+    Wrapper,
+};
+
+/// Type-state marker for a builder that has no child rules yet.
+pub struct NoChildren;
+
+/// Type-state marker for a builder that has at least one child rule.
+pub struct WithChildren;
+
+/// Builds a `ChainRule` whose structure is checked at compile time.
+///
+/// A chain rule can only have one child, so `child()` is only available
+/// while no child has been added.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::builder()
+///     .on_eval(|_| true)
+///     .child(ChainRule::builder().on_execute(|_| println!("Child")).build())
+///     .build();
+///
+/// Engine::chain_runner().run(RuleContext::new(), vec![rule]);
+/// ```
+///
+/// Adding a second child does not compile:
+///
+/// ```compile_fail
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::builder()
+///     .child(ChainRule::new())
+///     .child(ChainRule::new())
+///     .build();
+/// ```
+pub struct ChainRuleBuilder<S> {
+    rule: Wrapper<ChainRule>,
+    state: PhantomData<S>,
+}
+
+impl ChainRuleBuilder<NoChildren> {
+    pub(crate) fn new() -> Self {
+        ChainRuleBuilder {
+            rule: ChainRule::new(),
+            state: PhantomData,
+        }
+    }
+
+    /// Sets the only child of the rule.
+    pub fn child(self, rule: Wrapper<ChainRule>) -> ChainRuleBuilder<WithChildren> {
+        self.rule.borrow_mut().add_child(rule);
+        ChainRuleBuilder {
+            rule: self.rule,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<S> ChainRuleBuilder<S> {
+    /// Sets the id of the rule.
+    pub fn with_id(self, id: &str) -> Self {
+        self.rule.borrow_mut().set_id(id);
+        self
+    }
+
+    /// Sets the name of the rule.
+    pub fn with_name(self, name: &str) -> Self {
+        self.rule.borrow_mut().set_name(name);
+        self
+    }
+
+    /// Sets the description of the rule.
+    pub fn with_description(self, description: &str) -> Self {
+        self.rule.borrow_mut().set_description(description);
+        self
+    }
+
+    /// Sets the owner of the rule.
+    pub fn with_owner(self, owner: &str) -> Self {
+        self.rule.borrow_mut().set_owner(owner);
+        self
+    }
+
+    /// Sets the team owning the rule.
+    pub fn with_team(self, team: &str) -> Self {
+        self.rule.borrow_mut().set_team(team);
+        self
+    }
+
+    /// Declares the context keys the evaluation of the rule reads.
+    pub fn with_reads(self, keys: &[&str]) -> Self {
+        self.rule.borrow_mut().set_reads(keys);
+        self
+    }
+
+    /// Declares the context keys the execution of the rule writes.
+    pub fn with_writes(self, keys: &[&str]) -> Self {
+        self.rule.borrow_mut().set_writes(keys);
+        self
+    }
+
+    /// Declares the abstract cost of the rule.
+    pub fn with_cost(self, cost: u64) -> Self {
+        self.rule.borrow_mut().set_cost(cost);
+        self
+    }
+
+    /// Marks the rule as optional, to be skipped once the budget of a run is
+    /// exhausted.
+    pub fn with_optional(self, optional: bool) -> Self {
+        self.rule.borrow_mut().set_optional(optional);
+        self
+    }
+
+    /// Sets the evaluation function for the rule.
+    pub fn on_eval(self, eval: impl Fn(ContextView<'_>) -> bool + 'static) -> Self {
+        self.rule.borrow_mut().on_eval(eval);
+        self
+    }
+
+    /// Sets an expression, evaluated against the rule context, as the
+    /// evaluation function for the rule. A key missing from the context makes
+    /// the rule not execute, while the other evaluation errors, such as a
+    /// type mismatch, fail it with a `RuleError::Custom` holding the
+    /// `ExprError`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression can't be parsed; use `try_eval_expr` for
+    /// expressions that aren't known to be valid.
+    #[cfg(feature = "expr")]
+    pub fn eval_expr(self, source: &str) -> Self {
+        self.try_eval_expr(source)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like `eval_expr`, but returns the error when the expression can't be
+    /// parsed.
+    #[cfg(feature = "expr")]
+    pub fn try_eval_expr(self, source: &str) -> Result<Self, ExprError> {
+        let expr = Expr::parse(source)?;
+        Ok(self.on_eval(move |ctx| eval_condition(&expr, ctx)))
+    }
+
+    /// Sets the pre-execution function for the rule.
+    pub fn on_pre_execute(self, pre_execute: impl Fn(&mut ChainRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_pre_execute(pre_execute);
+        self
+    }
+
+    /// Sets the execution function for the rule.
+    pub fn on_execute(self, execute: impl Fn(&mut ChainRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_execute(execute);
+        self
+    }
+
+    /// Sets the post-execution function for the rule.
+    pub fn on_post_execute(self, post_execute: impl Fn(&mut ChainRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_post_execute(post_execute);
+        self
+    }
+
+    /// Returns the built rule, with or without a child.
+    pub fn build(self) -> Wrapper<ChainRule> {
+        self.rule
+    }
+}
+
+/// Builds a `BestFirstRule` whose structure is checked at compile time.
+///
+/// A best first rule built this way must have at least one child, so `build()`
+/// is only available once `child()` has been called; `children()` can then add
+/// more. Leaf rules are still created with `BestFirstRule::new()`.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = BestFirstRule::builder()
+///     .on_eval(|_| true)
+///     .child(BestFirstRule::new())
+///     .children(vec![BestFirstRule::new(), BestFirstRule::new()])
+///     .build();
+///
+/// Engine::best_first_runner().run(RuleContext::new(), vec![rule]);
+/// ```
+///
+/// Building without children does not compile:
+///
+/// ```compile_fail
+/// use dredd_rs::rule::*;
+///
+/// let rule = BestFirstRule::builder().on_eval(|_| true).build();
+/// ```
+pub struct BestFirstRuleBuilder<S> {
+    rule: Wrapper<BestFirstRule>,
+    state: PhantomData<S>,
+}
+
+impl BestFirstRuleBuilder<NoChildren> {
+    pub(crate) fn new() -> Self {
+        BestFirstRuleBuilder {
+            rule: BestFirstRule::new(),
+            state: PhantomData,
+        }
+    }
+}
+
+impl BestFirstRuleBuilder<WithChildren> {
+    /// Adds multiple child rules after the first one.
+    pub fn children(self, rules: Vec<Wrapper<BestFirstRule>>) -> Self {
+        self.rule.borrow_mut().add_children(rules);
+        self
+    }
+
+    /// Sets the child fired when no other child was executed.
+    pub fn default_child(self, rule: Wrapper<BestFirstRule>) -> Self {
+        self.rule.borrow_mut().add_default_child(rule);
+        self
+    }
+
+    /// Returns the built rule.
+    pub fn build(self) -> Wrapper<BestFirstRule> {
+        self.rule
+    }
+}
+
+impl<S> BestFirstRuleBuilder<S> {
+    /// Sets the id of the rule.
+    pub fn with_id(self, id: &str) -> Self {
+        self.rule.borrow_mut().set_id(id);
+        self
+    }
+
+    /// Sets the name of the rule.
+    pub fn with_name(self, name: &str) -> Self {
+        self.rule.borrow_mut().set_name(name);
+        self
+    }
+
+    /// Sets the description of the rule.
+    pub fn with_description(self, description: &str) -> Self {
+        self.rule.borrow_mut().set_description(description);
+        self
+    }
+
+    /// Sets the owner of the rule.
+    pub fn with_owner(self, owner: &str) -> Self {
+        self.rule.borrow_mut().set_owner(owner);
+        self
+    }
+
+    /// Sets the team owning the rule.
+    pub fn with_team(self, team: &str) -> Self {
+        self.rule.borrow_mut().set_team(team);
+        self
+    }
+
+    /// Declares the context keys the evaluation of the rule reads.
+    pub fn with_reads(self, keys: &[&str]) -> Self {
+        self.rule.borrow_mut().set_reads(keys);
+        self
+    }
+
+    /// Declares the context keys the execution of the rule writes.
+    pub fn with_writes(self, keys: &[&str]) -> Self {
+        self.rule.borrow_mut().set_writes(keys);
+        self
+    }
+
+    /// Declares the abstract cost of the rule.
+    pub fn with_cost(self, cost: u64) -> Self {
+        self.rule.borrow_mut().set_cost(cost);
+        self
+    }
+
+    /// Marks the rule as optional, to be skipped once the budget of a run is
+    /// exhausted.
+    pub fn with_optional(self, optional: bool) -> Self {
+        self.rule.borrow_mut().set_optional(optional);
+        self
+    }
+
+    /// Sets the evaluation function for the rule.
+    pub fn on_eval(self, eval: impl Fn(ContextView<'_>) -> bool + 'static) -> Self {
+        self.rule.borrow_mut().on_eval(eval);
+        self
+    }
+
+    /// Sets an expression, evaluated against the rule context, as the
+    /// evaluation function for the rule. A key missing from the context makes
+    /// the rule not execute, while the other evaluation errors, such as a
+    /// type mismatch, fail it with a `RuleError::Custom` holding the
+    /// `ExprError`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression can't be parsed; use `try_eval_expr` for
+    /// expressions that aren't known to be valid.
+    #[cfg(feature = "expr")]
+    pub fn eval_expr(self, source: &str) -> Self {
+        self.try_eval_expr(source)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like `eval_expr`, but returns the error when the expression can't be
+    /// parsed.
+    #[cfg(feature = "expr")]
+    pub fn try_eval_expr(self, source: &str) -> Result<Self, ExprError> {
+        let expr = Expr::parse(source)?;
+        Ok(self.on_eval(move |ctx| eval_condition(&expr, ctx)))
+    }
+
+    /// Sets the pre-execution function for the rule.
+    pub fn on_pre_execute(self, pre_execute: impl Fn(&mut BestFirstRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_pre_execute(pre_execute);
+        self
+    }
+
+    /// Sets the execution function for the rule.
+    pub fn on_execute(self, execute: impl Fn(&mut BestFirstRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_execute(execute);
+        self
+    }
+
+    /// Sets the post-execution function for the rule.
+    pub fn on_post_execute(self, post_execute: impl Fn(&mut BestFirstRule) + 'static) -> Self {
+        self.rule.borrow_mut().on_post_execute(post_execute);
+        self
+    }
+
+    /// Adds a single child rule.
+    pub fn child(self, rule: Wrapper<BestFirstRule>) -> BestFirstRuleBuilder<WithChildren> {
+        self.rule.borrow_mut().add_child(rule);
+        BestFirstRuleBuilder {
+            rule: self.rule,
+            state: PhantomData,
+        }
+    }
+
+    /// Adds a single child rule evaluated before the children with a lower
+    /// priority. Children added with `child()` have priority `0`.
+    pub fn child_with_priority(
+        self,
+        rule: Wrapper<BestFirstRule>,
+        priority: i32,
+    ) -> BestFirstRuleBuilder<WithChildren> {
+        self.rule
+            .borrow_mut()
+            .add_child_with_priority(rule, priority);
+        BestFirstRuleBuilder {
+            rule: self.rule,
+            state: PhantomData,
+        }
+    }
+}
+
+/// Evaluates the expression of `eval_expr`, failing the rule when it can't
+/// be evaluated for another reason than a missing key.
+#[cfg(feature = "expr")]
+fn eval_condition(expr: &Expr, ctx: ContextView<'_>) -> bool {
+    match expr.eval_view(ctx) {
+        Ok(result) => result,
+        Err(ExprError::MissingKey(_)) => false,
+        Err(error) => {
+            ctx.fail(RuleError::custom(error));
+            false
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::{
+    run_execute_phases, wrap, ContextSnapshot, ErrorPolicy, Rule, RuleCallback, RuleContext,
+    RuleError, RuleFailure, RuleVerdicts, Verdicts, Wrapper,
+};
+
+/// Decorates a rule so that it only records what it would have done.
+///
+/// When a canary rule is fired, the original rule is fired against a copy of
+/// the context, children included, and the outcome is recorded in the trace
+/// entry of the rule, see `TraceEntry::get_canary`. The real context is left
+/// untouched, failures of the copy are not reported to the runner, and the
+/// canary rule itself never executes, so a new rule can be observed in a live
+/// rule set before it is activated.
+///
+/// Outcomes are only recorded when a trace is collected, that is with
+/// `RuleRunner::run_with_report`.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = Canary::wrap(
+///     BestFirstRule::new()
+///         .with_name("new_discount")
+///         .on_eval(|ctx| *ctx.get::<u32>("total").unwrap() > 100)
+///         .on_execute(|this| this.get_rule_context().set("discount", 10u32)),
+/// );
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("total", 150u32);
+///
+/// let report = Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule]);
+/// let canary = report.get_trace().get_canaries()[0].get_canary().unwrap();
+///
+/// assert!(canary.would_execute());
+/// assert_eq!(*canary.get_writes().get::<u32>("discount").unwrap(), 10);
+/// assert!(rule_context.get::<u32>("discount").is_none());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Canary;
+
+impl Canary {
+    /// Replaces the evaluation callback of the rule with one that fires the
+    /// original rule against a copy of the context, and returns the rule.
+    pub fn wrap<R>(mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_eval(move |ctx| {
+            let rule_context = ctx.get_rule_context();
+            let before = rule_context.borrow().snapshot();
+            let mut shadow = RuleContext::from_context_map(before.context_map.clone());
+            shadow.borrow_mut().inherit_settings(&rule_context.borrow());
+            // The first failure of the original is kept in the outcome.
+            shadow.borrow_mut().set_error_policy(ErrorPolicy::Abort);
+
+            let mut original = original.borrow_mut();
+            original.set_rule_context(shadow.clone());
+            let would_execute = original.run_eval() && !shadow.has_failed();
+            if would_execute {
+                run_execute_phases(&mut *original);
+            }
+
+            let after = shadow.borrow().snapshot();
+            let outcome = CanaryOutcome {
+                would_execute,
+                writes: before.get_changes(&after),
+                error: shadow.take_error(),
+                verdicts: shadow.take_verdicts(),
+            };
+            let mut rule_context = rule_context.borrow_mut();
+            rule_context.take_usage(&shadow.borrow());
+            rule_context.trace_skip("canary");
+            rule_context.trace_canary(outcome);
+            false
+        })
+    }
+}
+
+/// What a canary rule would have done when it was fired.
+#[derive(Debug, Clone)]
+pub struct CanaryOutcome {
+    would_execute: bool,
+    writes: ContextSnapshot,
+    error: Option<RuleError>,
+    verdicts: Verdicts,
+}
+
+impl CanaryOutcome {
+    /// Whether the evaluation of the rule returned true.
+    pub fn would_execute(&self) -> bool {
+        self.would_execute
+    }
+
+    /// The values the rule and its children would have set in the context.
+    pub fn get_writes(&self) -> &ContextSnapshot {
+        &self.writes
+    }
+
+    /// The failure the rule would have reported.
+    pub fn get_error(&self) -> Option<&RuleError> {
+        self.error.as_ref()
+    }
+
+    /// The verdicts the rule and its children would have pushed.
+    pub fn get_verdicts(&self) -> &Verdicts {
+        &self.verdicts
+    }
+}
@@ -1,6 +1,13 @@
 use crate::{engine::Engine, runner::RuleRunner as _};
 
-use super::{wrap, Rule, RuleCallback, RuleChildren, RuleContextWrapper, Wrapper};
+#[cfg(feature = "tracing")]
+use super::spans;
+
+use super::{
+    builder::{ChainRuleBuilder, NoChildren},
+    run_execute_phases, wrap, Callback, ContextView, Metadata, MetadataAccess, Rule, RuleCallback,
+    RuleChildren, RuleContext, RuleContextWrapper, RuleFailure, Wrapper,
+};
 
 /// Represents a chain rule in the rule evaluation system.
 ///
@@ -35,27 +42,36 @@ use super::{wrap, Rule, RuleCallback, RuleChildren, RuleContextWrapper, Wrapper}
 ///
 #[derive(Clone)]
 pub struct ChainRule {
+    metadata: Metadata,
     rule_context: Option<RuleContextWrapper>,
     children: Vec<Wrapper<ChainRule>>,
-    eval: Wrapper<dyn Fn(&mut Self) -> bool>,
+    eval: Wrapper<dyn Fn(ContextView<'_>) -> bool>,
     pre_execute: Wrapper<dyn Fn(&mut Self)>,
     execute: Wrapper<dyn Fn(&mut Self)>,
     post_execute: Wrapper<dyn Fn(&mut Self)>,
+    compensate: Option<Callback<Self>>,
 }
 
 impl ChainRule {
     pub fn new() -> Wrapper<Self> {
         wrap(ChainRule {
+            metadata: Metadata::default(),
             rule_context: None,
             children: Vec::new(),
-            eval: wrap(|_: &mut Self| true),
+            eval: wrap(|_: ContextView<'_>| true),
             pre_execute: wrap(|_: &mut Self| ()),
             execute: wrap(|_: &mut Self| ()),
             post_execute: wrap(|_: &mut Self| ()),
+            compensate: None,
         })
     }
 
-    pub fn on_eval(&mut self, eval: impl Fn(&mut Self) -> bool + 'static) {
+    /// Creates a builder that rejects a second child at compile time.
+    pub fn builder() -> ChainRuleBuilder<NoChildren> {
+        ChainRuleBuilder::new()
+    }
+
+    pub fn on_eval(&mut self, eval: impl Fn(ContextView<'_>) -> bool + 'static) {
         self.eval = wrap(eval);
     }
 
@@ -70,21 +86,56 @@ impl ChainRule {
     pub fn on_post_execute(&mut self, post_execute: impl Fn(&mut Self) + 'static) {
         self.post_execute = wrap(post_execute);
     }
+
+    pub fn on_compensate(&mut self, compensate: impl Fn(&mut Self) + 'static) {
+        self.compensate = Some(wrap(compensate));
+    }
+}
+
+impl MetadataAccess for ChainRule {
+    fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn get_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
 }
 
 impl Rule<ChainRule> for ChainRule {
     fn fire(&mut self) -> bool {
-        if self.run_eval() {
-            self.run_pre_execute();
-            self.run_execute();
-            self.run_post_execute();
-            self.run_children();
+        let entry = self
+            .get_rule_context()
+            .borrow_mut()
+            .trace_fire(&self.metadata);
+        #[cfg(feature = "tracing")]
+        let span = spans::FireSpan::enter(&self.metadata);
+        let admitted = self.get_rule_context().borrow_mut().admit(&self.metadata);
+        let eval_result = admitted
+            && {
+                #[cfg(feature = "tracing")]
+                let _phase = spans::enter_phase("eval");
+                self.run_eval()
+            }
+            && !self.get_rule_context().has_failed();
+        if eval_result {
+            run_execute_phases(self);
         }
+        let rule_context = self.get_rule_context();
+        let mut rule_context = rule_context.borrow_mut();
+        let eval_result = !rule_context.take_degraded_rule() && eval_result;
+        rule_context.trace_fired(entry, eval_result);
+        rule_context.release(admitted);
+        #[cfg(feature = "tracing")]
+        span.finish(eval_result, rule_context.has_failed());
+        rule_context.name_failure(&self.metadata);
+        rule_context.recover_failure();
         true
     }
 
     fn run_eval(&self) -> bool {
-        (self.eval.borrow_mut())(&mut self.clone())
+        let rule_context = self.rule_context.clone().unwrap_or_else(RuleContext::new);
+        (self.eval.borrow_mut())(ContextView::new(&rule_context))
     }
 
     fn run_pre_execute(&mut self) {
@@ -99,6 +150,10 @@ impl Rule<ChainRule> for ChainRule {
         (self.post_execute.borrow_mut())(&mut self.clone());
     }
 
+    fn get_compensate(&self) -> Option<Callback<Self>> {
+        self.compensate.clone()
+    }
+
     fn set_rule_context(&mut self, rule_context: RuleContextWrapper) {
         self.rule_context = Some(rule_context);
     }
@@ -120,14 +175,14 @@ impl Rule<ChainRule> for ChainRule {
 
     fn add_child(&mut self, rule: Wrapper<ChainRule>) {
         if !self.children.is_empty() {
-            panic!("Chain rule can only have one child");
+            panic!("Chain rule {} can only have one child", self.metadata);
         }
         self.children.push(rule);
     }
 
     fn add_children(&mut self, rules: Vec<Wrapper<ChainRule>>) {
         if self.children.len() + rules.len() > 1 {
-            panic!("Chain rule can only have one child.");
+            panic!("Chain rule {} can only have one child.", self.metadata);
         }
         self.children.extend(rules);
     }
@@ -155,7 +210,7 @@ impl RuleCallback for Wrapper<ChainRule> {
     /// Sets the evaluation function for the rule.
     fn on_eval(
         &mut self,
-        eval: impl Fn(&mut Self::RuleType) -> bool + 'static,
+        eval: impl Fn(ContextView<'_>) -> bool + 'static,
     ) -> Wrapper<Self::RuleType> {
         self.borrow_mut().eval = wrap(eval);
         self.clone()
@@ -187,6 +242,15 @@ impl RuleCallback for Wrapper<ChainRule> {
         self.borrow_mut().post_execute = wrap(post_execute);
         self.clone()
     }
+
+    /// Sets the compensation function for the rule.
+    fn on_compensate(
+        &mut self,
+        compensate: impl Fn(&mut Self::RuleType) + 'static,
+    ) -> Wrapper<Self::RuleType> {
+        self.borrow_mut().compensate = Some(wrap(compensate));
+        self.clone()
+    }
 }
 
 /// Implementation of the `AddChild` trait for `Wrapper<ChainRule>`.
@@ -0,0 +1,183 @@
+use std::{fmt, rc::Rc};
+
+use super::{ExecutionTrace, RuleContext, RuleContextMap};
+
+/// How a change altered the value of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The key held no value.
+    Insert,
+    /// The key held a value, which was replaced or changed in place.
+    Update,
+    /// The value was removed.
+    Remove,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Insert => write!(f, "insert"),
+            ChangeKind::Update => write!(f, "update"),
+            ChangeKind::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// A change made to a context while it was tracked, see
+/// `RuleContext::start_tracking`.
+///
+/// Displayed as `insert `key` by `rule``, without the rule when the change
+/// wasn't made by a rule being fired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextChange {
+    key: &'static str,
+    kind: ChangeKind,
+    rule: Option<String>,
+}
+
+impl ContextChange {
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn get_kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// The name, or id, of the rule whose callbacks made the change, when it
+    /// was made during a run.
+    pub fn get_rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+}
+
+impl fmt::Display for ContextChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} `{}`", self.kind, self.key)?;
+        if let Some(rule) = &self.rule {
+            write!(f, " by `{rule}`")?;
+        }
+        Ok(())
+    }
+}
+
+/// The changes journaled by a context.
+#[derive(Debug, Clone)]
+pub(crate) struct ChangeJournal {
+    changes: Vec<ContextChange>,
+    /// Whether the trace was started to find the rules making the changes.
+    owns_trace: bool,
+}
+
+impl RuleContext {
+    /// Starts journaling the changes made to the values of the context, for
+    /// instance to persist only the keys a run wrote. A journal already being
+    /// kept is cleared.
+    ///
+    /// Values set, changed through `ContextMut` or `ContextList`, removed,
+    /// applied from a `DecisionCache` or brought back by `restore` are
+    /// journaled, in the order they were made. Changes made through interior
+    /// mutability, such as a `RefCell` stored in the context, are not. The
+    /// rule making a change is known while a trace is collected, which
+    /// tracking does.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.set("total", 100);
+    /// rule_context.borrow_mut().start_tracking();
+    ///
+    /// let rule = AllRule::new().with_name("discount").on_execute(|this| {
+    ///     let mut rule_context = this.get_rule_context();
+    ///     rule_context.set("discount", 10);
+    ///     rule_context.set("total", 90);
+    /// });
+    /// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+    /// rule_context.borrow_mut().remove("discount");
+    ///
+    /// let changes: Vec<_> = rule_context.borrow().changes().iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     changes,
+    ///     ["insert `discount` by `discount`", "update `total` by `discount`", "remove `discount`"]
+    /// );
+    /// ```
+    pub fn start_tracking(&mut self) {
+        let journal = self.changes.get_or_insert_with(|| ChangeJournal {
+            changes: Vec::new(),
+            owns_trace: false,
+        });
+        journal.changes.clear();
+        // The rule making a change is found in the trace.
+        if self.trace.is_none() {
+            self.trace = Some(ExecutionTrace::default());
+            journal.owns_trace = true;
+        }
+    }
+
+    /// Stops journaling changes and returns the journal.
+    pub fn stop_tracking(&mut self) -> Vec<ContextChange> {
+        let Some(journal) = self.changes.take() else {
+            return Vec::new();
+        };
+        if journal.owns_trace {
+            self.trace = None;
+        }
+        journal.changes
+    }
+
+    pub fn is_tracking(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// The changes journaled since `start_tracking`, oldest first.
+    pub fn changes(&self) -> &[ContextChange] {
+        self.changes
+            .as_ref()
+            .map(|journal| journal.changes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Journals a change to an already resolved key, when changes are
+    /// tracked. Must be called before the value is written.
+    pub(crate) fn track_change(&mut self, key: &'static str, kind: Option<ChangeKind>) {
+        if self.changes.is_none() {
+            return;
+        }
+        let kind = kind.unwrap_or_else(|| match self.lookup_resolved(key) {
+            Some(_) => ChangeKind::Update,
+            None => ChangeKind::Insert,
+        });
+        let rule = self.current_rule();
+        if let Some(journal) = &mut self.changes {
+            journal.changes.push(ContextChange { key, kind, rule });
+        }
+    }
+
+    /// Journals the changes bringing the values of the context back to
+    /// `context_map`, in the order of the keys.
+    pub(crate) fn track_restore(&mut self, context_map: &RuleContextMap) {
+        if self.changes.is_none() {
+            return;
+        }
+        let mut keys: Vec<_> = self
+            .context_map
+            .keys()
+            .chain(context_map.keys())
+            .copied()
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        for key in keys {
+            match (self.context_map.get(key), context_map.get(key)) {
+                (Some(now), Some(then)) if !Rc::ptr_eq(now, then) => {
+                    self.track_change(key, Some(ChangeKind::Update))
+                }
+                (Some(_), None) => self.track_change(key, Some(ChangeKind::Remove)),
+                (None, Some(_)) => self.track_change(key, None),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use super::{RuleContext, RuleContextWrapper};
+
+impl RuleContext {
+    /// A new context layered over `parent`, for the scratch values of a
+    /// subtree of rules that shouldn't leak to its siblings.
+    ///
+    /// Reads fall through to the parent when the scope doesn't hold the key,
+    /// while writes stay in the scope until it is merged into the parent with
+    /// `merge_scope` or dropped with `discard_scope`. The scope runs with the
+    /// settings of the parent, such as its mode, policies, budget and key
+    /// aliases.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.set("total", 100);
+    ///
+    /// let mut scope = RuleContext::child_scope(&rule_context);
+    /// scope.set("coupon", 15);
+    /// scope.set("total", 85);
+    /// assert!(rule_context.get::<i32>("coupon").is_none());
+    /// assert_eq!(*rule_context.get::<i32>("total").unwrap(), 100);
+    ///
+    /// scope.borrow_mut().merge_scope();
+    /// assert_eq!(*rule_context.get::<i32>("coupon").unwrap(), 15);
+    /// assert_eq!(*rule_context.get::<i32>("total").unwrap(), 85);
+    /// ```
+    pub fn child_scope(parent: &RuleContextWrapper) -> RuleContextWrapper {
+        let scope = RuleContext::new();
+        {
+            let mut scope = scope.borrow_mut();
+            scope.inherit_settings(&parent.borrow());
+            scope.scope = true;
+            // The scope reads through its parent alone, backed by its own
+            // layers.
+            scope.layers = vec![parent.clone()];
+        }
+        scope
+    }
+
+    /// Whether the context is a scope made by `child_scope`.
+    pub fn is_scope(&self) -> bool {
+        self.scope
+    }
+
+    /// Writes the values set in the scope into its parent, and pushes the
+    /// verdicts pushed in the scope to it, emptying the scope. Does nothing
+    /// when the context isn't a scope.
+    pub fn merge_scope(&mut self) {
+        let Some(parent) = self.scope.then(|| self.layers.first()).flatten() else {
+            return;
+        };
+        let mut parent = parent.borrow_mut();
+        for (key, value) in self.context_map.drain() {
+            if let Some(recorder) = &mut parent.key_recorder {
+                recorder.write(key);
+            }
+            parent.track_change(key, None);
+            parent.context_map.insert(key, value);
+        }
+        parent.verdicts.append(&mut self.verdicts);
+    }
+
+    /// Drops the values set and the verdicts pushed in the scope, the parent
+    /// left as it was. Does nothing when the context isn't a scope.
+    pub fn discard_scope(&mut self) {
+        if self.scope {
+            self.context_map.clear();
+            self.verdicts.clear();
+        }
+    }
+}
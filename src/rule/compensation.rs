@@ -0,0 +1,69 @@
+use std::{fmt, rc::Rc};
+
+use super::{Rule, RuleContext};
+
+/// The compensation callback of a rule that executed, bound to a clone of
+/// the rule.
+#[derive(Clone)]
+pub(crate) struct Compensation {
+    rule: String,
+    undo: Rc<dyn Fn()>,
+}
+
+impl Compensation {
+    /// The name, or id, of the rule compensated.
+    pub(crate) fn get_rule(&self) -> &str {
+        &self.rule
+    }
+
+    pub(crate) fn run(&self) {
+        (self.undo)();
+    }
+}
+
+impl fmt::Debug for Compensation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compensation")
+            .field("rule", &self.rule)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RuleContext {
+    /// Starts recording the compensations of the rules executed, with
+    /// `Some`, or stops with `None`. Returns the compensations recorded so
+    /// far.
+    pub(crate) fn set_compensations(
+        &mut self,
+        compensations: Option<Vec<Compensation>>,
+    ) -> Option<Vec<Compensation>> {
+        std::mem::replace(&mut self.compensations, compensations)
+    }
+
+    /// Adds compensations to those being recorded, if any.
+    pub(crate) fn keep_compensations(&mut self, compensations: Vec<Compensation>) {
+        if let Some(recorded) = self.compensations.as_mut() {
+            recorded.extend(compensations);
+        }
+    }
+}
+
+/// Called once the execute callbacks of a rule succeeded: records its
+/// compensation callback, if any, when compensations are being recorded.
+pub(crate) fn record_compensation<T: Rule<T> + Clone + 'static>(rule: &mut T) {
+    let Some(compensate) = rule.get_compensate() else {
+        return;
+    };
+    let rule_context = rule.get_rule_context();
+    let mut rule_context = rule_context.borrow_mut();
+    let Some(compensations) = rule_context.compensations.as_mut() else {
+        return;
+    };
+    let metadata = rule.get_metadata();
+    let name = metadata.get_name().or(metadata.get_id());
+    let rule = rule.clone();
+    compensations.push(Compensation {
+        rule: name.unwrap_or("<unnamed>").to_string(),
+        undo: Rc::new(move || (compensate.borrow_mut())(&mut rule.clone())),
+    });
+}
@@ -0,0 +1,161 @@
+use std::{fmt, ops, rc::Rc};
+
+use super::{GetSet, RuleContext};
+
+/// A reusable condition on the context, composed with `and`, `or` and `!`.
+///
+/// Conditions replace the evaluation callback of a rule with
+/// `RuleCallback::on_condition`, so that the boolean logic shared by many
+/// rules is written once. Conditions are cheap to clone, and combining them
+/// leaves the originals usable.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let adult = Condition::new(|ctx| ctx.get::<u32>("age").is_some_and(|age| *age >= 18));
+/// let domestic = Condition::key_equals("country", "BR");
+/// let vip = Condition::key_equals("vip", true);
+///
+/// let rule = AllRule::new()
+///     .on_condition(adult.and(domestic.or(vip)))
+///     .on_execute(|this| this.get_rule_context().set("approved", true));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 30u32);
+/// rule_context.set("country", "AR");
+/// rule_context.set("vip", true);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert!(*rule_context.get::<bool>("approved").unwrap());
+/// ```
+///
+/// Conditions also describe themselves in plain words, a description that
+/// `on_condition` records in the metadata of the rule for catalogs. The
+/// conditions built by `key_equals`, `key_exists` and `with_probability`, and
+/// those combined from described ones, are described; the others are given
+/// a description with `with_description`:
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let adult = Condition::new(|ctx| ctx.get::<u32>("age").is_some_and(|age| *age >= 18))
+///     .with_description("`age` >= 18");
+/// let condition = adult.and(Condition::key_equals("country", "BR").or(Condition::key_exists("vip")));
+///
+/// assert_eq!(
+///     condition.get_description(),
+///     Some(r#"`age` >= 18 and (`country` == "BR" or `vip` is set)"#)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Condition {
+    check: Rc<dyn Fn(&RuleContext) -> bool>,
+    description: Option<Rc<str>>,
+}
+
+impl Condition {
+    pub fn new(check: impl Fn(&RuleContext) -> bool + 'static) -> Self {
+        Condition {
+            check: Rc::new(check),
+            description: None,
+        }
+    }
+
+    /// Holds when the key is set to a value of type `T` equal to `value`.
+    pub fn key_equals<T: PartialEq + fmt::Debug + 'static>(key: &'static str, value: T) -> Self {
+        let description = format!("`{key}` == {value:?}");
+        Condition::new(move |ctx| ctx.get::<T>(key).is_some_and(|current| *current == value))
+            .with_description(&description)
+    }
+
+    /// Holds when the key is set, whatever its value.
+    pub fn key_exists(key: &'static str) -> Self {
+        Condition::new(move |ctx| ctx.lookup(key).is_some())
+            .with_description(&format!("`{key}` is set"))
+    }
+
+    /// Holds at random with the given probability, drawing from the random
+    /// generator of the context, see `Rng`.
+    pub fn with_probability(probability: f64) -> Self {
+        Condition::new(move |ctx| ctx.chance(probability))
+            .with_description(&format!("with probability {probability}"))
+    }
+
+    /// Holds when both conditions hold. `other` is only checked when this
+    /// condition holds.
+    pub fn and(&self, other: Condition) -> Self {
+        let this = self.clone();
+        let description = combine(self, "and", &other);
+        Condition {
+            description,
+            ..Condition::new(move |ctx| this.eval(ctx) && other.eval(ctx))
+        }
+    }
+
+    /// Holds when either condition holds. `other` is only checked when this
+    /// condition doesn't hold.
+    pub fn or(&self, other: Condition) -> Self {
+        let this = self.clone();
+        let description = combine(self, "or", &other);
+        Condition {
+            description,
+            ..Condition::new(move |ctx| this.eval(ctx) || other.eval(ctx))
+        }
+    }
+
+    /// Describes the condition in plain words, replacing its description.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The description of the condition, `None` for one built with `new`
+    /// and not described, or combined from such a condition.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn eval(&self, rule_context: &RuleContext) -> bool {
+        (self.check)(rule_context)
+    }
+}
+
+/// Holds when the condition doesn't.
+impl ops::Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Condition {
+        let description = self
+            .description
+            .as_deref()
+            .map(|description| format!("not {}", group(description)).into());
+        Condition {
+            description,
+            ..Condition::new(move |ctx| !self.eval(ctx))
+        }
+    }
+}
+
+/// Describes two conditions joined by `operator`, when both are described.
+fn combine(left: &Condition, operator: &str, right: &Condition) -> Option<Rc<str>> {
+    let left = left.description.as_deref()?;
+    let right = right.description.as_deref()?;
+    Some(format!("{} {operator} {}", group(left), group(right)).into())
+}
+
+/// Puts a combined description in parentheses, so that it reads as one
+/// operand.
+fn group(description: &str) -> String {
+    if description.contains(" and ") || description.contains(" or ") {
+        format!("({description})")
+    } else {
+        description.to_string()
+    }
+}
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Condition").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,103 @@
+use rust_decimal::Decimal;
+
+use super::{GetSet, RuleContext, RuleContextWrapper};
+
+/// Stores exact amounts, such as prices and balances, as `Decimal` values,
+/// so that money rules don't accumulate the rounding errors of floats.
+///
+/// A decimal is an ordinary context value, so `get::<Decimal>` reads it as
+/// well. The arithmetic helpers update the amount of a key in place, a
+/// missing key counting as zero, and return the new amount. They are checked:
+/// on overflow they return `None` and leave the key unchanged.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set_decimal("total", Decimal::new(1999, 2));
+/// rule_context.add_decimal("total", Decimal::new(1, 2));
+/// rule_context.mul_decimal("total", Decimal::new(9, 1));
+///
+/// assert_eq!(rule_context.get_decimal("total"), Some(Decimal::new(18, 0)));
+/// assert_eq!(rule_context.get_decimal("total").unwrap().to_string(), "18.000");
+/// ```
+pub trait ContextDecimal {
+    fn set_decimal(&mut self, key: &'static str, amount: Decimal);
+    fn get_decimal(&self, key: &'static str) -> Option<Decimal>;
+    /// Adds `amount` to the key.
+    fn add_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal>;
+    /// Subtracts `amount` from the key.
+    fn sub_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal>;
+    /// Multiplies the key by `factor`, for rates and discounts.
+    fn mul_decimal(&mut self, key: &'static str, factor: Decimal) -> Option<Decimal>;
+    /// Rounds the key to `decimal_places`, rounding half to even as banks
+    /// do. Returns `None` when the key holds no decimal.
+    fn round_decimal(&mut self, key: &'static str, decimal_places: u32) -> Option<Decimal>;
+}
+
+impl RuleContext {
+    fn update_decimal(
+        &mut self,
+        key: &'static str,
+        update: impl FnOnce(Decimal) -> Option<Decimal>,
+    ) -> Option<Decimal> {
+        let amount = update(self.get_decimal(key).unwrap_or_default())?;
+        self.set_decimal(key, amount);
+        Some(amount)
+    }
+}
+
+impl ContextDecimal for RuleContext {
+    fn set_decimal(&mut self, key: &'static str, amount: Decimal) {
+        self.set(key, amount);
+    }
+
+    fn get_decimal(&self, key: &'static str) -> Option<Decimal> {
+        self.get::<Decimal>(key).map(|amount| *amount)
+    }
+
+    fn add_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal> {
+        self.update_decimal(key, |total| total.checked_add(amount))
+    }
+
+    fn sub_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal> {
+        self.update_decimal(key, |total| total.checked_sub(amount))
+    }
+
+    fn mul_decimal(&mut self, key: &'static str, factor: Decimal) -> Option<Decimal> {
+        self.update_decimal(key, |total| total.checked_mul(factor))
+    }
+
+    fn round_decimal(&mut self, key: &'static str, decimal_places: u32) -> Option<Decimal> {
+        let amount = self.get_decimal(key)?.round_dp(decimal_places);
+        self.set_decimal(key, amount);
+        Some(amount)
+    }
+}
+
+impl ContextDecimal for RuleContextWrapper {
+    fn set_decimal(&mut self, key: &'static str, amount: Decimal) {
+        self.borrow_mut().set_decimal(key, amount);
+    }
+
+    fn get_decimal(&self, key: &'static str) -> Option<Decimal> {
+        self.borrow().get_decimal(key)
+    }
+
+    fn add_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal> {
+        self.borrow_mut().add_decimal(key, amount)
+    }
+
+    fn sub_decimal(&mut self, key: &'static str, amount: Decimal) -> Option<Decimal> {
+        self.borrow_mut().sub_decimal(key, amount)
+    }
+
+    fn mul_decimal(&mut self, key: &'static str, factor: Decimal) -> Option<Decimal> {
+        self.borrow_mut().mul_decimal(key, factor)
+    }
+
+    fn round_decimal(&mut self, key: &'static str, decimal_places: u32) -> Option<Decimal> {
+        self.borrow_mut().round_decimal(key, decimal_places)
+    }
+}
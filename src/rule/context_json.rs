@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use super::{ContextView, GetSet, RuleContext, RuleContextWrapper};
+
+/// Stores JSON documents in the context as `serde_json::Value` values, as
+/// they arrive, instead of mapping every field to a key of its own.
+///
+/// A document is an ordinary context value, so `get::<serde_json::Value>`
+/// reads it as well. JSON objects and arrays of scenario files, HTTP
+/// responses and the contexts of the `dredd` tool are stored the same way.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+/// use serde_json::json;
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set_json("order", json!({ "items": [{ "sku": "A-1", "quantity": 2 }] }));
+///
+/// assert_eq!(rule_context.get_json_path("/order/items/0/sku"), Some(json!("A-1")));
+/// assert_eq!(rule_context.get_json_path("/order/items/1/sku"), None);
+/// assert_eq!(rule_context.get_json("order").unwrap()["items"][0]["quantity"], 2);
+/// ```
+pub trait ContextJson {
+    fn set_json(&mut self, key: &'static str, value: Value);
+    fn get_json(&self, key: &'static str) -> Option<Rc<Value>>;
+    /// The value at a JSON pointer, such as `/order/items/0/sku`, whose first
+    /// segment is a context key holding a JSON value and the rest a pointer
+    /// into it. A missing key or value, or a key holding something else than
+    /// JSON, gives `None`.
+    fn get_json_path(&self, path: &str) -> Option<Value>;
+}
+
+impl ContextJson for RuleContext {
+    fn set_json(&mut self, key: &'static str, value: Value) {
+        self.set(key, value);
+    }
+
+    fn get_json(&self, key: &'static str) -> Option<Rc<Value>> {
+        self.get::<Value>(key)
+    }
+
+    fn get_json_path(&self, path: &str) -> Option<Value> {
+        let path = path.strip_prefix('/')?;
+        let (key, pointer) = match path.find('/') {
+            Some(index) => path.split_at(index),
+            None => (path, ""),
+        };
+        let value = self.lookup(key)?;
+        value.downcast_ref::<Value>()?.pointer(pointer).cloned()
+    }
+}
+
+impl ContextJson for RuleContextWrapper {
+    fn set_json(&mut self, key: &'static str, value: Value) {
+        self.borrow_mut().set_json(key, value);
+    }
+
+    fn get_json(&self, key: &'static str) -> Option<Rc<Value>> {
+        self.borrow().get_json(key)
+    }
+
+    fn get_json_path(&self, path: &str) -> Option<Value> {
+        self.borrow().get_json_path(path)
+    }
+}
+
+impl ContextView<'_> {
+    /// Reads a JSON document, see `ContextJson::get_json`.
+    pub fn get_json(&self, key: &'static str) -> Option<Rc<Value>> {
+        self.get_rule_context().get_json(key)
+    }
+
+    /// Reads into a JSON document, see `ContextJson::get_json_path`.
+    pub fn get_json_path(&self, path: &str) -> Option<Value> {
+        self.get_rule_context().get_json_path(path)
+    }
+}
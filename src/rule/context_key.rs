@@ -0,0 +1,139 @@
+use std::{fmt, marker::PhantomData};
+
+/// A typed handle to a `RuleContext` key.
+///
+/// The value type is part of the handle, so `get` and `set` can't be called
+/// with a mismatched type, and a key name typed once can't be misspelled at
+/// every use. Keys are usually declared with the `context_keys!` macro rather
+/// than built by hand, or, without documentation, with `Key::named`.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// const AGE: ContextKey<i64> = ContextKey::new("age", "Customer age in years.");
+/// const APPROVED: Key<bool> = Key::named("approved");
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set(AGE, 42);
+/// rule_context.set(APPROVED, *rule_context.get(AGE).unwrap() >= 18);
+///
+/// assert!(*rule_context.get(APPROVED).unwrap());
+/// assert_eq!(*rule_context.get::<i64>("age").unwrap(), 42);
+/// ```
+///
+/// A value of another type doesn't compile:
+/// ```compile_fail
+/// use dredd_rs::rule::*;
+///
+/// const AGE: Key<i64> = Key::named("age");
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set(AGE, "42");
+/// ```
+pub struct ContextKey<T> {
+    name: &'static str,
+    doc: &'static str,
+    value_type: PhantomData<fn() -> T>,
+}
+
+impl<T> ContextKey<T> {
+    pub const fn new(name: &'static str, doc: &'static str) -> Self {
+        ContextKey {
+            name,
+            doc,
+            value_type: PhantomData,
+        }
+    }
+
+    /// A key without documentation.
+    pub const fn named(name: &'static str) -> Self {
+        ContextKey::new(name, "")
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn doc(&self) -> &'static str {
+        self.doc.trim()
+    }
+}
+
+/// Short name of `ContextKey`.
+pub type Key<T> = ContextKey<T>;
+
+/// A key of the context holding a `T`, as taken by `GetSet::get` and
+/// `GetSet::set`: a `&'static str`, holding values of any type, or a
+/// `ContextKey<T>`.
+pub trait AsContextKey<T> {
+    fn key_name(&self) -> &'static str;
+}
+
+impl<T> AsContextKey<T> for &'static str {
+    fn key_name(&self) -> &'static str {
+        self
+    }
+}
+
+impl<T> AsContextKey<T> for ContextKey<T> {
+    fn key_name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T, K: AsContextKey<T>> AsContextKey<T> for &K {
+    fn key_name(&self) -> &'static str {
+        (*self).key_name()
+    }
+}
+
+impl<T> Clone for ContextKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ContextKey<T> {}
+
+impl<T> fmt::Debug for ContextKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextKey")
+            .field("name", &self.name)
+            .field("value_type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Untyped description of a key declared with `context_keys!`.
+///
+/// Every module generated by the macro exposes a `KEYS` slice of these, which
+/// is the single list of keys a rule set relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextKeyInfo {
+    name: &'static str,
+    value_type: &'static str,
+    doc: &'static str,
+}
+
+impl ContextKeyInfo {
+    pub const fn new(name: &'static str, value_type: &'static str, doc: &'static str) -> Self {
+        ContextKeyInfo {
+            name,
+            value_type,
+            doc,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub const fn value_type(&self) -> &'static str {
+        self.value_type
+    }
+
+    pub fn doc(&self) -> &'static str {
+        self.doc.trim()
+    }
+}
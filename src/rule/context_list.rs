@@ -0,0 +1,94 @@
+use std::{any::type_name, rc::Rc};
+
+use super::{GetSet, RuleContext, RuleContextWrapper, Severity};
+
+/// Stores collections in the context as `Vec<T>` values.
+///
+/// A list is an ordinary context value, so `get::<Vec<T>>` reads it as well.
+/// These methods add access to single elements and appending without
+/// rebuilding the list by hand.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set_list("line_items", vec![120u32, 80]);
+/// rule_context.push_to_list("line_items", 45u32);
+///
+/// assert_eq!(rule_context.get_list_item::<u32>("line_items", 2), Some(45));
+/// assert_eq!(rule_context.get_list::<u32>("line_items").unwrap().iter().sum::<u32>(), 245);
+/// ```
+pub trait ContextList {
+    fn set_list<T: 'static>(&mut self, key: &'static str, items: Vec<T>);
+    fn get_list<T: 'static>(&self, key: &'static str) -> Option<Rc<Vec<T>>>;
+    /// The element at `index`, when the key holds a list of `T` that long.
+    fn get_list_item<T: Clone + 'static>(&self, key: &'static str, index: usize) -> Option<T>;
+    /// Appends an element to the list, creating it when the key is missing.
+    /// When the key holds something else than a list of `T`, the value is
+    /// kept, the element is dropped and a `Severity::Warning` is reported,
+    /// see `RuleWarnings`.
+    fn push_to_list<T: Clone + 'static>(&mut self, key: &'static str, item: T);
+}
+
+impl ContextList for RuleContext {
+    fn set_list<T: 'static>(&mut self, key: &'static str, items: Vec<T>) {
+        self.set(key, items);
+    }
+
+    fn get_list<T: 'static>(&self, key: &'static str) -> Option<Rc<Vec<T>>> {
+        self.get::<Vec<T>>(key)
+    }
+
+    fn get_list_item<T: Clone + 'static>(&self, key: &'static str, index: usize) -> Option<T> {
+        self.get_list::<T>(key)?.get(index).cloned()
+    }
+
+    fn push_to_list<T: Clone + 'static>(&mut self, key: &'static str, item: T) {
+        let key = self.resolve_key(key);
+        if self
+            .lookup_resolved(key)
+            .is_some_and(|value| !value.is::<Vec<T>>())
+        {
+            self.push_warning(
+                Severity::Warning,
+                format!(
+                    "key `{key}` doesn't hold a list of `{}`, the item was not pushed",
+                    type_name::<T>()
+                ),
+            );
+            return;
+        }
+        // Taking the list out of the map avoids copying it unless a snapshot
+        // still shares it. The key is kept until the list is set again, so
+        // that the change is journaled as an update.
+        let mut items = self
+            .context_map
+            .get_mut(key)
+            .map(|value| std::mem::replace(value, Rc::new(())))
+            .or_else(|| self.lookup_resolved(key))
+            .and_then(|value| value.downcast::<Vec<T>>().ok())
+            .map(Rc::unwrap_or_clone)
+            .unwrap_or_default();
+        items.push(item);
+        self.set(key, items);
+    }
+}
+
+impl ContextList for RuleContextWrapper {
+    fn set_list<T: 'static>(&mut self, key: &'static str, items: Vec<T>) {
+        self.borrow_mut().set_list(key, items);
+    }
+
+    fn get_list<T: 'static>(&self, key: &'static str) -> Option<Rc<Vec<T>>> {
+        self.borrow().get_list(key)
+    }
+
+    fn get_list_item<T: Clone + 'static>(&self, key: &'static str, index: usize) -> Option<T> {
+        self.borrow().get_list_item(key, index)
+    }
+
+    fn push_to_list<T: Clone + 'static>(&mut self, key: &'static str, item: T) {
+        self.borrow_mut().push_to_list(key, item);
+    }
+}
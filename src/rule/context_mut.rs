@@ -0,0 +1,276 @@
+use std::{
+    any::{type_name, Any},
+    cell::{RefCell, RefMut},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    thread,
+};
+
+use super::{value_types::Primitive, ChangeKind, GetSet, RuleContext, RuleContextWrapper};
+
+/// Updates context values in place, without reading, cloning and setting
+/// them back.
+///
+/// A value is only copied when it is still shared, for instance by a
+/// snapshot or by a `get` whose result is still alive; otherwise it is
+/// mutated where it is stored. The returned guards borrow the context, so
+/// they must be dropped before the context is used again, which a single
+/// statement such as `*ctx.entry("count").or_insert_int(0) += 1` does.
+///
+/// A value is only journaled and recorded as written once it is borrowed
+/// mutably: a guard only read from leaves the context as it was.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = AllRule::new().on_execute(|this| {
+///     *this.get_rule_context().entry("visits").or_insert_int(0) += 1;
+///     this.get_rule_context().entry("log").or_insert_string("").push_str("visit;");
+/// });
+///
+/// let rule_context = RuleContext::new();
+/// Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<i64>("visits").unwrap(), 2);
+/// *rule_context.get_int_mut("visits").unwrap() *= 10;
+/// assert_eq!(*rule_context.get::<i64>("visits").unwrap(), 20);
+/// assert_eq!(*rule_context.get::<String>("log").unwrap(), "visit;visit;");
+/// ```
+pub trait ContextMut {
+    /// The value of the key, when it holds a `T`.
+    fn get_mut<T: Clone + 'static>(&self, key: &'static str) -> Option<ValueMut<'_, T>>;
+
+    /// The value of the key, when it holds an integer of any width whose
+    /// value fits in an `i64`, see `IntMut`.
+    fn get_int_mut(&self, key: &'static str) -> Option<IntMut<'_>>;
+
+    fn get_string_mut(&self, key: &'static str) -> Option<ValueMut<'_, String>> {
+        self.get_mut::<String>(key)
+    }
+
+    /// The entry of the key, to update its value or insert one.
+    fn entry(&self, key: &'static str) -> ContextEntry<'_>;
+}
+
+impl ContextMut for RuleContextWrapper {
+    fn get_mut<T: Clone + 'static>(&self, key: &'static str) -> Option<ValueMut<'_, T>> {
+        ValueMut::new(self.borrow_mut(), key)
+    }
+
+    fn get_int_mut(&self, key: &'static str) -> Option<IntMut<'_>> {
+        IntMut::new(self.borrow_mut(), key)
+    }
+
+    fn entry(&self, key: &'static str) -> ContextEntry<'_> {
+        ContextEntry {
+            rule_context: self,
+            key,
+        }
+    }
+}
+
+/// A key of the context, see `ContextMut::entry`.
+///
+/// The `or_insert` methods panic when the key holds a value of another type.
+pub struct ContextEntry<'a> {
+    rule_context: &'a RefCell<RuleContext>,
+    key: &'static str,
+}
+
+impl<'a> ContextEntry<'a> {
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    /// The value of the key, after setting it to `default` if it was missing.
+    pub fn or_insert<T: Clone + 'static>(self, default: T) -> ValueMut<'a, T> {
+        self.or_insert_with(|| default)
+    }
+
+    /// The value of the key, after setting it to the result of `default` if
+    /// it was missing.
+    pub fn or_insert_with<T: Clone + 'static>(
+        self,
+        default: impl FnOnce() -> T,
+    ) -> ValueMut<'a, T> {
+        let key = self.key;
+        let rule_context = self.inserting(default);
+        ValueMut::new(rule_context, key).unwrap_or_else(|| {
+            panic!(
+                "key `{key}` doesn't hold a value of type `{}`",
+                type_name::<T>()
+            )
+        })
+    }
+
+    /// The integer of the key, of any width, after setting it to `default`
+    /// if it was missing, see `IntMut`.
+    pub fn or_insert_int(self, default: i64) -> IntMut<'a> {
+        let key = self.key;
+        let rule_context = self.inserting(|| default);
+        IntMut::new(rule_context, key)
+            .unwrap_or_else(|| panic!("key `{key}` doesn't hold an integer fitting in `i64`"))
+    }
+
+    pub fn or_insert_string(self, default: impl Into<String>) -> ValueMut<'a, String> {
+        self.or_insert(default.into())
+    }
+
+    /// The context, after setting the key to the result of `default` if it
+    /// was missing.
+    fn inserting<T: 'static>(self, default: impl FnOnce() -> T) -> RefMut<'a, RuleContext> {
+        let mut rule_context = self.rule_context.borrow_mut();
+        // Checked without recording a read: a missing key is written before
+        // it is read.
+        let resolved = rule_context.resolve_key(self.key);
+        if rule_context.lookup_resolved(resolved).is_none() {
+            rule_context.set(self.key, default());
+        }
+        rule_context
+    }
+}
+
+/// A value of the context borrowed mutably, see `ContextMut::get_mut`.
+///
+/// The value is copied to the context, when it comes from one of its layers
+/// or is shared, the first time it is borrowed mutably.
+pub struct ValueMut<'a, T> {
+    rule_context: RefMut<'a, RuleContext>,
+    key: &'static str,
+    /// The value, until it is first borrowed mutably.
+    read: Option<Rc<dyn Any>>,
+    value_type: PhantomData<T>,
+}
+
+impl<'a, T: Clone + 'static> ValueMut<'a, T> {
+    fn new(rule_context: RefMut<'a, RuleContext>, key: &'static str) -> Option<Self> {
+        let key = rule_context.resolve_key(key);
+        let value = rule_context.lookup(key).filter(|value| value.is::<T>())?;
+        Some(ValueMut {
+            rule_context,
+            key,
+            read: Some(value),
+            value_type: PhantomData,
+        })
+    }
+}
+
+impl<T: 'static> Deref for ValueMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        let value = match &self.read {
+            Some(value) => value,
+            None => &self.rule_context.context_map[self.key],
+        };
+        value.downcast_ref::<T>().unwrap()
+    }
+}
+
+impl<T: Clone + 'static> DerefMut for ValueMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        let key = self.key;
+        if let Some(value) = self.read.take() {
+            self.rule_context.record_write(key);
+            // Values of lower layers are copied to the top one to be changed.
+            self.rule_context.context_map.entry(key).or_insert(value);
+        }
+        let value = self.rule_context.context_map.get_mut(key).unwrap();
+        if Rc::get_mut(value).is_none() {
+            let copy = value.downcast_ref::<T>().unwrap().clone();
+            *value = Rc::new(copy);
+        }
+        Rc::get_mut(value).unwrap().downcast_mut::<T>().unwrap()
+    }
+}
+
+/// An integer of the context borrowed mutably as an `i64`, whatever the
+/// width it is stored with, see `ContextMut::get_int_mut`.
+///
+/// The integer is written back with its own type when the guard is dropped,
+/// if it was borrowed mutably. Like the arithmetic of that type, writing
+/// back a value that doesn't fit in it panics.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("count", 1);
+/// *rule_context.get_int_mut("count").unwrap() += 1;
+///
+/// assert_eq!(*rule_context.get::<i32>("count").unwrap(), 2);
+/// ```
+pub struct IntMut<'a> {
+    rule_context: RefMut<'a, RuleContext>,
+    key: &'static str,
+    stored: Primitive,
+    value: i64,
+    written: bool,
+}
+
+impl<'a> IntMut<'a> {
+    fn new(rule_context: RefMut<'a, RuleContext>, key: &'static str) -> Option<Self> {
+        let key = rule_context.resolve_key(key);
+        let stored = Primitive::from_any(rule_context.lookup(key)?.as_ref())?;
+        let value = stored.as_int()?.try_into().ok()?;
+        Some(IntMut {
+            rule_context,
+            key,
+            stored,
+            value,
+            written: false,
+        })
+    }
+}
+
+impl Deref for IntMut<'_> {
+    type Target = i64;
+
+    fn deref(&self) -> &i64 {
+        &self.value
+    }
+}
+
+impl DerefMut for IntMut<'_> {
+    fn deref_mut(&mut self) -> &mut i64 {
+        self.written = true;
+        &mut self.value
+    }
+}
+
+impl Drop for IntMut<'_> {
+    fn drop(&mut self) {
+        if !self.written {
+            return;
+        }
+        match self.stored.with_int(self.value.into()) {
+            Some(value) => {
+                self.rule_context.record_write(self.key);
+                self.rule_context
+                    .context_map
+                    .insert(self.key, value.into_value());
+            }
+            None if !thread::panicking() => panic!(
+                "value {} of key `{}` overflows `{}`",
+                self.value,
+                self.key,
+                self.stored.type_name()
+            ),
+            None => {}
+        }
+    }
+}
+
+impl RuleContext {
+    /// Records and journals the update of a resolved key in place.
+    fn record_write(&mut self, key: &'static str) {
+        if let Some(recorder) = &mut self.key_recorder {
+            recorder.write(key);
+        }
+        self.track_change(key, Some(ChangeKind::Update));
+    }
+}
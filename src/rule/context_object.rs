@@ -0,0 +1,124 @@
+use std::{any::Any, collections::HashMap, fmt, iter, rc::Rc};
+
+use super::{ContextView, RuleContext, RuleContextWrapper};
+
+/// A structured context value: named fields holding values of any type,
+/// including other objects.
+///
+/// Nested fields are addressed with dotted paths, such as
+/// `"order.customer.age"`, through `get_path`. Keys and fields holding dots
+/// themselves, such as `"checkout.age"`, are addressed too, the longest one
+/// a path starts with being taken first.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let order = ContextObject::new()
+///     .with("total", 250u32)
+///     .with("customer", ContextObject::new().with("age", 42i64));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("order", order);
+///
+/// assert_eq!(*rule_context.get_path::<i64>("order.customer.age").unwrap(), 42);
+/// assert!(rule_context.get_path::<i64>("order.customer.name").is_none());
+/// ```
+#[derive(Clone, Default)]
+pub struct ContextObject {
+    fields: HashMap<String, Rc<dyn Any>>,
+}
+
+impl ContextObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: 'static>(mut self, key: impl Into<String>, value: T) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn insert<T: 'static>(&mut self, key: impl Into<String>, value: T) {
+        self.fields.insert(key.into(), Rc::new(value));
+    }
+
+    pub fn get<T: 'static>(&self, key: &str) -> Option<Rc<T>> {
+        self.fields.get(key)?.clone().downcast::<T>().ok()
+    }
+
+    /// The names of the fields, sorted.
+    pub fn get_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<_> = self.fields.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// The value at a dotted path of fields, starting from this object.
+    pub fn get_path<T: 'static>(&self, path: &str) -> Option<Rc<T>> {
+        let (key, rest) = split_path(path, |key| self.fields.contains_key(key));
+        resolve(self.fields.get(key)?, rest)
+    }
+}
+
+impl fmt::Debug for ContextObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextObject")
+            .field("keys", &self.get_keys())
+            .finish()
+    }
+}
+
+/// Reads nested values of the context by dotted path.
+///
+/// The path starts with a context key and every following segment is a
+/// field of the `ContextObject` found so far. Keys and fields may hold dots:
+/// the longest key, or field, the path starts with is taken. A missing key or field,
+/// a value in the middle of the path that is not an object, or a final value
+/// of another type than `T` all give `None`.
+pub trait ContextPath {
+    fn get_path<T: 'static>(&self, path: &str) -> Option<Rc<T>>;
+}
+
+impl ContextPath for RuleContext {
+    fn get_path<T: 'static>(&self, path: &str) -> Option<Rc<T>> {
+        // Keys are probed without recording reads, only the one taken is.
+        let (key, rest) = split_path(path, |key| {
+            self.lookup_resolved(self.resolve_key(key)).is_some()
+        });
+        resolve(&self.lookup(key)?, rest)
+    }
+}
+
+impl ContextPath for RuleContextWrapper {
+    fn get_path<T: 'static>(&self, path: &str) -> Option<Rc<T>> {
+        self.borrow().get_path(path)
+    }
+}
+
+impl ContextPath for ContextView<'_> {
+    fn get_path<T: 'static>(&self, path: &str) -> Option<Rc<T>> {
+        self.get_rule_context().get_path(path)
+    }
+}
+
+/// Splits the path after the longest key it starts with, ending before a
+/// dot, that `exists`, or else after its first segment.
+fn split_path(path: &str, exists: impl Fn(&str) -> bool) -> (&str, Option<&str>) {
+    let ends = iter::once(path.len()).chain(path.rmatch_indices('.').map(|(end, _)| end));
+    let mut split = (path, None);
+    for end in ends {
+        split = (&path[..end], path.get(end + 1..));
+        if exists(split.0) {
+            break;
+        }
+    }
+    split
+}
+
+fn resolve<T: 'static>(value: &Rc<dyn Any>, path: Option<&str>) -> Option<Rc<T>> {
+    match path {
+        Some(path) => value.downcast_ref::<ContextObject>()?.get_path(path),
+        None => value.clone().downcast::<T>().ok(),
+    }
+}
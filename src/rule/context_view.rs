@@ -0,0 +1,87 @@
+use std::{fmt, rc::Rc, time::Duration};
+
+use super::{AsContextKey, ContextKey, GetSet, RuleContextWrapper, RuleError, RuleFailure};
+
+/// A read-only view of a `RuleContext`, given to the evaluation callbacks
+/// set with `RuleCallback::on_eval`.
+///
+/// The view only has getters, so that an evaluation can't change the context
+/// it evaluates: the purity of conditions is checked by the compiler rather
+/// than by review.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = AllRule::new()
+///     .on_eval(|ctx| ctx.get::<u32>("age").is_some_and(|age| *age >= 18))
+///     .on_execute(|this| this.get_rule_context().set("approved", true));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 30u32);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert!(*rule_context.get::<bool>("approved").unwrap());
+/// ```
+///
+/// Writing through the view doesn't compile:
+/// ```compile_fail
+/// use dredd_rs::rule::*;
+///
+/// AllRule::new().on_eval(|ctx| {
+///     ctx.set("approved", true);
+///     true
+/// });
+/// ```
+#[derive(Clone, Copy)]
+pub struct ContextView<'a> {
+    rule_context: &'a RuleContextWrapper,
+}
+
+impl<'a> ContextView<'a> {
+    pub fn new(rule_context: &'a RuleContextWrapper) -> Self {
+        ContextView { rule_context }
+    }
+
+    /// The viewed context, for the rules wrapping evaluation callbacks.
+    pub(crate) fn get_rule_context(&self) -> RuleContextWrapper {
+        self.rule_context.clone()
+    }
+
+    pub fn get<T: 'static>(&self, key: impl AsContextKey<T>) -> Option<Rc<T>> {
+        self.rule_context.get(key)
+    }
+
+    pub fn get_key<T: 'static>(&self, key: ContextKey<T>) -> Option<Rc<T>> {
+        self.rule_context.get(key)
+    }
+
+    /// Whether the key is set, whatever its value.
+    pub fn contains(&self, key: &'static str) -> bool {
+        self.rule_context.borrow().lookup(key).is_some()
+    }
+
+    /// See `RuleContext::is_past_deadline`.
+    pub fn is_past_deadline(&self) -> bool {
+        self.rule_context.borrow().is_past_deadline()
+    }
+
+    /// See `RuleContext::get_time_left`.
+    pub fn get_time_left(&self) -> Option<Duration> {
+        self.rule_context.borrow().get_time_left()
+    }
+
+    /// Fails the rule being evaluated, see `RuleFailure::fail`. The values of
+    /// the context are left as they are.
+    pub fn fail(&self, error: RuleError) {
+        self.rule_context.clone().fail(error);
+    }
+}
+
+impl fmt::Debug for ContextView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextView")
+            .field("keys", &self.rule_context.borrow().get_context_map().keys())
+            .finish()
+    }
+}
@@ -0,0 +1,280 @@
+use std::{any::Any, error::Error, fmt, rc::Rc};
+
+use super::{ContextSnapshot, RuleContext, RuleContextWrapper, RuleError, RuleRunner, Wrapper};
+
+type Matcher = Box<dyn Fn(&dyn Any) -> Option<usize>>;
+
+/// A categorical key and the values it can take.
+struct Segment {
+    key: &'static str,
+    values: Vec<Rc<dyn Any>>,
+    /// The position of a context value among `values`.
+    matcher: Matcher,
+}
+
+/// Why a decision cache could not be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionCacheError {
+    /// The segments have more combinations than the configured maximum.
+    TooManyCombinations(usize),
+    /// The rules failed for a combination, described as `key=value` pairs.
+    Failed(String, RuleError),
+}
+
+impl fmt::Display for DecisionCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionCacheError::TooManyCombinations(count) => {
+                write!(f, "too many segment combinations: {count}")
+            }
+            DecisionCacheError::Failed(combination, error) => {
+                write!(f, "{error} for {combination}")
+            }
+        }
+    }
+}
+
+impl Error for DecisionCacheError {}
+
+/// Describes the segments of a `DecisionCache` before compiling it.
+pub struct DecisionCacheBuilder {
+    segments: Vec<Segment>,
+    descriptions: Vec<Vec<String>>,
+    max_combinations: usize,
+}
+
+impl DecisionCacheBuilder {
+    /// Adds a categorical key and every value it can take.
+    pub fn segment<T>(mut self, key: &'static str, values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Clone + PartialEq + fmt::Debug + 'static,
+    {
+        let values: Vec<T> = values.into_iter().collect();
+        self.descriptions
+            .push(values.iter().map(|value| format!("{value:?}")).collect());
+        let erased = values
+            .iter()
+            .map(|value| Rc::new(value.clone()) as Rc<dyn Any>)
+            .collect();
+        let matcher = move |value: &dyn Any| {
+            let value = value.downcast_ref::<T>()?;
+            values.iter().position(|candidate| candidate == value)
+        };
+        self.segments.push(Segment {
+            key,
+            values: erased,
+            matcher: Box::new(matcher),
+        });
+        self
+    }
+
+    /// Sets the maximum number of combinations, `65536` by default.
+    pub fn with_max_combinations(mut self, max_combinations: usize) -> Self {
+        self.max_combinations = max_combinations;
+        self
+    }
+
+    /// Runs the rules once for every combination of segment values and
+    /// records the values each run sets.
+    pub fn compile<T, Runner>(
+        self,
+        runner: Runner,
+        rules: Vec<Wrapper<T>>,
+    ) -> Result<DecisionCache, DecisionCacheError>
+    where
+        Runner: RuleRunner<RuleType = T>,
+    {
+        let count = self
+            .segments
+            .iter()
+            .try_fold(1usize, |count, segment| {
+                count.checked_mul(segment.values.len())
+            })
+            .filter(|&count| count <= self.max_combinations)
+            .ok_or_else(|| {
+                let count = self.segments.iter().fold(1usize, |count, segment| {
+                    count.saturating_mul(segment.values.len())
+                });
+                DecisionCacheError::TooManyCombinations(count)
+            })?;
+
+        let mut outcomes = Vec::with_capacity(count);
+        for index in 0..count {
+            let positions = self.get_positions(index);
+            let rule_context = RuleContext::new();
+            for (segment, &position) in self.segments.iter().zip(&positions) {
+                rule_context
+                    .borrow_mut()
+                    .context_map
+                    .insert(segment.key, segment.values[position].clone());
+            }
+            let before = rule_context.borrow().snapshot();
+            if let Err(error) = runner.try_run(rule_context.clone(), rules.clone()) {
+                return Err(DecisionCacheError::Failed(self.describe(&positions), error));
+            }
+            let after = rule_context.borrow().snapshot();
+            outcomes.push(Outcome {
+                values: before.get_changes(&after),
+                removed: before
+                    .get_keys()
+                    .into_iter()
+                    .filter(|key| !after.context_map.contains_key(key))
+                    .collect(),
+            });
+        }
+
+        Ok(DecisionCache {
+            segments: self.segments,
+            outcomes,
+        })
+    }
+
+    /// The position of each segment value in the combination at `index`, the
+    /// last segment varying fastest.
+    fn get_positions(&self, mut index: usize) -> Vec<usize> {
+        let mut positions = vec![0; self.segments.len()];
+        for (position, segment) in positions.iter_mut().zip(&self.segments).rev() {
+            *position = index % segment.values.len();
+            index /= segment.values.len();
+        }
+        positions
+    }
+
+    fn describe(&self, positions: &[usize]) -> String {
+        let pairs: Vec<String> = self
+            .segments
+            .iter()
+            .zip(&self.descriptions)
+            .zip(positions)
+            .map(|((segment, values), &position)| format!("{}={}", segment.key, values[position]))
+            .collect();
+        pairs.join(", ")
+    }
+}
+
+/// What the rules did for a combination of segment values.
+struct Outcome {
+    /// The values set or replaced.
+    values: ContextSnapshot,
+    /// The keys removed, sorted.
+    removed: Vec<&'static str>,
+}
+
+/// The outcomes of a rule set for every combination of a few categorical
+/// keys, computed ahead of time.
+///
+/// When the conditions of a rule set only depend on keys that take a small
+/// set of values, such as a country, a plan or a flag, the rules can be run
+/// once per combination of values when the application starts. Each request
+/// then looks up the values the rules would have set instead of running
+/// them. Lookups fail, and the rules should be run instead, when the context
+/// is missing a segment key or holds a value that was not enumerated.
+///
+/// The cache records the values set or replaced by the rules, and the keys
+/// they removed. The values are shared between the contexts they are applied
+/// to, so they should not be mutated in place.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rules = vec![
+///     AllRule::new()
+///         .on_eval(|ctx| *ctx.get::<&str>("plan").unwrap() == "pro")
+///         .on_execute(|this| this.get_rule_context().set("discount", 10u32)),
+///     AllRule::new()
+///         .on_eval(|ctx| *ctx.get::<&str>("country").unwrap() == "BR")
+///         .on_execute(|this| this.get_rule_context().set("currency", "BRL")),
+/// ];
+///
+/// let cache = DecisionCache::builder()
+///     .segment("plan", ["free", "pro"])
+///     .segment("country", ["BR", "US"])
+///     .compile(Engine::all_runner(), rules)
+///     .unwrap();
+/// assert_eq!(cache.get_len(), 4);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("plan", "pro");
+/// rule_context.set("country", "BR");
+/// assert!(cache.apply(&rule_context));
+/// assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 10);
+/// assert_eq!(*rule_context.get::<&str>("currency").unwrap(), "BRL");
+///
+/// rule_context.set("country", "AR");
+/// assert!(!cache.apply(&rule_context));
+/// ```
+pub struct DecisionCache {
+    segments: Vec<Segment>,
+    outcomes: Vec<Outcome>,
+}
+
+impl DecisionCache {
+    pub fn builder() -> DecisionCacheBuilder {
+        DecisionCacheBuilder {
+            segments: Vec::new(),
+            descriptions: Vec::new(),
+            max_combinations: 65_536,
+        }
+    }
+
+    /// The number of combinations in the cache.
+    pub fn get_len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// The segment keys, in the order they were added.
+    pub fn get_keys(&self) -> Vec<&'static str> {
+        self.segments.iter().map(|segment| segment.key).collect()
+    }
+
+    /// The values the rules set for the segment values of the context, or
+    /// `None` when they are missing or were not enumerated.
+    pub fn lookup(&self, rule_context: &RuleContext) -> Option<&ContextSnapshot> {
+        self.find_outcome(rule_context)
+            .map(|outcome| &outcome.values)
+    }
+
+    /// The keys the rules removed for the segment values of the context,
+    /// sorted, or `None` when they are missing or were not enumerated.
+    pub fn lookup_removed(&self, rule_context: &RuleContext) -> Option<&[&'static str]> {
+        self.find_outcome(rule_context)
+            .map(|outcome| outcome.removed.as_slice())
+    }
+
+    fn find_outcome(&self, rule_context: &RuleContext) -> Option<&Outcome> {
+        let mut index = 0;
+        for segment in &self.segments {
+            let value = rule_context.lookup(segment.key)?;
+            let position = (segment.matcher)(value.as_ref())?;
+            index = index * segment.values.len() + position;
+        }
+        self.outcomes.get(index)
+    }
+
+    /// Writes the values the rules set for the segment values of the context
+    /// into it, and removes the keys they removed, as the rules would have,
+    /// then tells whether they were found.
+    pub fn apply(&self, rule_context: &RuleContextWrapper) -> bool {
+        let Some(outcome) = self.find_outcome(&rule_context.borrow()) else {
+            return false;
+        };
+        let mut rule_context = rule_context.borrow_mut();
+        for key in &outcome.removed {
+            rule_context.remove(key);
+        }
+        for (key, value) in &outcome.values.context_map {
+            rule_context.set_value(key, value.clone());
+        }
+        true
+    }
+}
+
+impl fmt::Debug for DecisionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecisionCache")
+            .field("keys", &self.get_keys())
+            .field("len", &self.get_len())
+            .finish()
+    }
+}
@@ -0,0 +1,446 @@
+use std::{cell::Cell, collections::HashMap, error::Error, fmt, fmt::Write, io, rc::Rc};
+
+use crate::expr::{Expr, ExprError, Value};
+
+use super::{
+    wrap, Rule, RuleCallback, RuleContext, RuleContextWrapper, RuleError, RuleFailure, Wrapper,
+};
+
+type Action = Rc<dyn Fn(&mut RuleContextWrapper)>;
+
+/// The header of the column naming the action of each row.
+const ACTION_COLUMN: &str = "action";
+
+/// The namespace of the DMN 1.3 models written by `to_dmn`.
+const DMN_NAMESPACE: &str = "https://www.omg.org/spec/DMN/20191111/MODEL/";
+
+/// Why a decision table could not be loaded or applied to a rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionTableError {
+    /// The CSV document could not be read.
+    Csv(String),
+    /// The header has no `action` column.
+    MissingActionColumn,
+    /// A condition cell holds a number that can't be read, rows counting
+    /// from `1` after the header.
+    InvalidCell {
+        row: usize,
+        column: String,
+        error: ExprError,
+    },
+    /// A row names an action that was not registered.
+    UnknownAction(String),
+}
+
+impl fmt::Display for DecisionTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionTableError::Csv(message) => write!(f, "invalid CSV: {message}"),
+            DecisionTableError::MissingActionColumn => {
+                write!(f, "missing `{ACTION_COLUMN}` column")
+            }
+            DecisionTableError::InvalidCell { row, column, error } => {
+                write!(f, "invalid cell in row {row}, column `{column}`: {error}")
+            }
+            DecisionTableError::UnknownAction(action) => write!(f, "unknown action `{action}`"),
+        }
+    }
+}
+
+impl Error for DecisionTableError {}
+
+impl From<csv::Error> for DecisionTableError {
+    fn from(error: csv::Error) -> Self {
+        DecisionTableError::Csv(error.to_string())
+    }
+}
+
+/// The comparison of a condition cell, `None` for cells matching any value.
+type Entry = Option<(&'static str, Value)>;
+
+/// A row of the table: the entries of its condition cells, one per key, the
+/// conditions compiled from them and the name of its action.
+struct Row {
+    entries: Vec<Entry>,
+    conditions: Vec<Expr>,
+    action: String,
+}
+
+impl Row {
+    /// Whether all the conditions of the row hold. A condition on a missing
+    /// key doesn't.
+    fn matches(&self, rule_context: &RuleContext) -> Result<bool, ExprError> {
+        for condition in &self.conditions {
+            match condition.eval(rule_context) {
+                Ok(true) => {}
+                Ok(false) | Err(ExprError::MissingKey(_)) => return Ok(false),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Decorates a rule with a decision table loaded from CSV, so that it only
+/// passes its evaluation when a row of the table matches the context, and
+/// then runs the action of the first matching row.
+///
+/// The header names the context key of each condition column, and one column,
+/// `action`, names the action of the row. A condition cell holds either a
+/// value the key must be equal to, or a comparison such as `>= 18` or
+/// `!= BR`. Values are read as numbers, `true` and `false` as booleans, and
+/// anything else, quotes included, as strings. Empty cells and cells holding
+/// `-` match any value. Cells are compiled into comparisons, see
+/// `dredd_rs::expr`, when the table is loaded, and a row matches when all of
+/// its conditions hold. Headers are taken as keys as they are, so they may
+/// hold spaces or any other character.
+///
+/// Action names are resolved against the actions registered with `action`
+/// when the table is applied to a rule. A row with a condition on a key
+/// missing from the context doesn't match, while a condition that can't be
+/// evaluated, because its key holds a value of another type, fails the rule.
+///
+/// The row is looked up once, by the evaluation of the rule. Its action runs
+/// after the execute callback of the decorated rule and before its
+/// post-execute callback and children. Like the other decorators,
+/// setting the evaluation or execute callback of the rule afterwards replaces
+/// the table.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let csv = "\
+/// country,age,action
+/// BR,>= 18,approve
+/// BR,-,refer
+/// ,>= 21,approve
+/// ";
+///
+/// let rule = DecisionTableRule::from_csv(csv.as_bytes())
+///     .unwrap()
+///     .action("approve", |ctx| ctx.set("decision", "approved"))
+///     .action("refer", |ctx| ctx.set("decision", "referred"))
+///     .wrap(AllRule::new())
+///     .unwrap();
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("country", "BR");
+/// rule_context.set("age", 16);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<&str>("decision").unwrap(), "referred");
+/// ```
+pub struct DecisionTableRule {
+    keys: Vec<String>,
+    rows: Vec<Row>,
+    actions: HashMap<String, Action>,
+}
+
+impl DecisionTableRule {
+    /// Reads a decision table from a CSV document with a header row.
+    pub fn from_csv(reader: impl io::Read) -> Result<Self, DecisionTableError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let action_column = headers
+            .iter()
+            .position(|header| header == ACTION_COLUMN)
+            .ok_or(DecisionTableError::MissingActionColumn)?;
+
+        let mut rows = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            let record = record?;
+            let mut entries = Vec::new();
+            let mut conditions = Vec::new();
+            for (column, (key, cell)) in headers.iter().zip(record.iter()).enumerate() {
+                if column == action_column {
+                    continue;
+                }
+                let entry = read_cell(cell).map_err(|error| DecisionTableError::InvalidCell {
+                    row: index + 1,
+                    column: key.to_string(),
+                    error,
+                })?;
+                if let Some((operator, value)) = &entry {
+                    conditions.push(
+                        Expr::comparison(key, operator, value.clone())
+                            .expect("the operators are comparisons"),
+                    );
+                }
+                entries.push(entry);
+            }
+            rows.push(Row {
+                entries,
+                conditions,
+                action: record.get(action_column).unwrap_or_default().to_string(),
+            });
+        }
+
+        let keys = headers
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| *column != action_column)
+            .map(|(_, key)| key.to_string())
+            .collect();
+        Ok(DecisionTableRule {
+            keys,
+            rows,
+            actions: HashMap::new(),
+        })
+    }
+
+    /// Registers the action run for the rows naming it.
+    pub fn action(
+        mut self,
+        name: &str,
+        action: impl Fn(&mut RuleContextWrapper) + 'static,
+    ) -> Self {
+        self.actions.insert(name.to_string(), Rc::new(action));
+        self
+    }
+
+    /// The keys of the condition columns, in order.
+    pub fn get_keys(&self) -> Vec<&str> {
+        self.keys.iter().map(String::as_str).collect()
+    }
+
+    /// The number of rows, the header excluded.
+    pub fn get_len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Writes the table as a DMN 1.3 decision named `name`, for modeling
+    /// tools to import.
+    ///
+    /// The decision table has the `FIRST` hit policy. Each condition column
+    /// is an input reading its key, typed when all its values are of one
+    /// type, and each cell the equivalent FEEL unary test: `"BR"`, `>= 18`,
+    /// `not("BR")` or `-`. Actions are code, so the output is the name of the
+    /// action of each row, as the string `action`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let csv = "country,age,action\nBR,>= 18,approve\n";
+    /// let dmn = DecisionTableRule::from_csv(csv.as_bytes()).unwrap().to_dmn("credit");
+    ///
+    /// assert!(dmn.contains(r#"<decisionTable id="decision_credit_table" hitPolicy="FIRST">"#));
+    /// assert!(dmn.contains("<text>&gt;= 18</text>"));
+    /// ```
+    pub fn to_dmn(&self, name: &str) -> String {
+        let id: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let id = format!("decision_{id}");
+        let name = escape_attribute(name);
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<definitions xmlns=\"{DMN_NAMESPACE}\" id=\"{id}_definitions\" name=\"{name}\" namespace=\"urn:dredd-rs:{id}\">"
+        );
+        let _ = writeln!(xml, "  <decision id=\"{id}\" name=\"{name}\">");
+        let _ = writeln!(
+            xml,
+            "    <decisionTable id=\"{id}_table\" hitPolicy=\"FIRST\">"
+        );
+        for (column, key) in self.keys.iter().enumerate() {
+            let type_ref = self
+                .column_type(column)
+                .map_or(String::new(), |type_ref| format!(" typeRef=\"{type_ref}\""));
+            let _ = writeln!(
+                xml,
+                "      <input id=\"{id}_input_{column}\" label=\"{}\">\n        <inputExpression id=\"{id}_input_{column}_expression\"{type_ref}>\n          <text>{}</text>\n        </inputExpression>\n      </input>",
+                escape_attribute(key),
+                escape(key)
+            );
+        }
+        let _ = writeln!(
+            xml,
+            "      <output id=\"{id}_output\" name=\"{ACTION_COLUMN}\" typeRef=\"string\"/>"
+        );
+        for (index, row) in self.rows.iter().enumerate() {
+            let _ = writeln!(xml, "      <rule id=\"{id}_rule_{index}\">");
+            for (column, entry) in row.entries.iter().enumerate() {
+                let _ = writeln!(
+                    xml,
+                    "        <inputEntry id=\"{id}_rule_{index}_input_{column}\">\n          <text>{}</text>\n        </inputEntry>",
+                    escape(&unary_test(entry))
+                );
+            }
+            let _ = writeln!(
+                xml,
+                "        <outputEntry id=\"{id}_rule_{index}_output\">\n          <text>{}</text>\n        </outputEntry>\n      </rule>",
+                escape(&feel_literal(&Value::Str(row.action.clone())))
+            );
+        }
+        xml.push_str("    </decisionTable>\n  </decision>\n</definitions>\n");
+        xml
+    }
+
+    /// The FEEL type of the values of a condition column, when they are all
+    /// of the same type.
+    fn column_type(&self, column: usize) -> Option<&'static str> {
+        let mut types = self
+            .rows
+            .iter()
+            .filter_map(|row| row.entries.get(column)?.as_ref())
+            .map(|(_, value)| match value {
+                Value::Int(_) | Value::Float(_) => "number",
+                Value::Bool(_) => "boolean",
+                Value::Str(_) => "string",
+            });
+        let first = types.next()?;
+        types.all(|type_ref| type_ref == first).then_some(first)
+    }
+
+    /// Replaces the evaluation and execute callbacks of the rule with ones
+    /// that look up the table, and returns the rule. Fails when a row names
+    /// an action that was not registered.
+    pub fn wrap<R>(self, mut rule: Wrapper<R>) -> Result<Wrapper<R>, DecisionTableError>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|row| match self.actions.get(&row.action) {
+                Some(action) => Ok((row, action.clone())),
+                None => Err(DecisionTableError::UnknownAction(row.action)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rows = Rc::new(rows);
+        // The row matched by the evaluation, for the execution to run.
+        let matched = Rc::new(Cell::new(None));
+        let original = wrap(rule.borrow().clone());
+
+        Ok(rule
+            .on_eval({
+                let rows = rows.clone();
+                let matched = matched.clone();
+                move |ctx| {
+                    matched.set(find_row(&rows, &mut ctx.get_rule_context()));
+                    matched.get().is_some()
+                }
+            })
+            .on_execute(move |this| {
+                let mut rule_context = this.get_rule_context();
+                {
+                    let mut original = original.borrow_mut();
+                    original.set_rule_context(rule_context.clone());
+                    original.run_execute();
+                }
+                if rule_context.has_failed() {
+                    return;
+                }
+                if let Some(index) = matched.take() {
+                    (rows[index].1)(&mut rule_context);
+                }
+            }))
+    }
+}
+
+impl fmt::Debug for DecisionTableRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecisionTableRule")
+            .field("keys", &self.keys)
+            .field("rows", &self.rows.len())
+            .finish()
+    }
+}
+
+/// The position of the first row matching the context. A condition that
+/// can't be evaluated fails the rule.
+fn find_row(rows: &[(Row, Action)], rule_context: &mut RuleContextWrapper) -> Option<usize> {
+    for (index, (row, _)) in rows.iter().enumerate() {
+        let matches = row.matches(&rule_context.borrow());
+        match matches {
+            Ok(true) => return Some(index),
+            Ok(false) => {}
+            Err(error) => {
+                rule_context.fail(RuleError::failed(format!(
+                    "decision table row {}: {error}",
+                    index + 1
+                )));
+                return None;
+            }
+        }
+    }
+    None
+}
+
+/// Reads a condition cell into its comparison, or `None` for cells matching
+/// any value.
+fn read_cell(cell: &str) -> Result<Entry, ExprError> {
+    const COMPARISONS: [&str; 6] = [">=", "<=", "!=", "==", ">", "<"];
+
+    if cell.is_empty() || cell == "-" {
+        return Ok(None);
+    }
+    let (operator, value) = COMPARISONS
+        .iter()
+        .find_map(|operator| Some((*operator, cell.strip_prefix(operator)?.trim())))
+        .unwrap_or(("==", cell));
+    Ok(Some((operator, read_value(value)?)))
+}
+
+/// Reads the value of a cell as a number, a boolean or else a string.
+fn read_value(value: &str) -> Result<Value, ExprError> {
+    match value {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ if is_number(value) => {
+            let number = if value.contains('.') {
+                value.parse().map(Value::Float).ok()
+            } else {
+                value.parse().map(Value::Int).ok()
+            };
+            number.ok_or_else(|| ExprError::Parse {
+                position: 0,
+                message: format!("invalid number `{value}`"),
+            })
+        }
+        _ => Ok(Value::Str(value.to_string())),
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// The FEEL unary test equivalent to the comparison of a cell.
+fn unary_test(entry: &Entry) -> String {
+    match entry {
+        None => "-".to_string(),
+        Some(("==", value)) => feel_literal(value),
+        Some(("!=", value)) => format!("not({})", feel_literal(value)),
+        Some((operator, value)) => format!("{operator} {}", feel_literal(value)),
+    }
+}
+
+fn feel_literal(value: &Value) -> String {
+    match value {
+        Value::Int(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Str(value) => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_attribute(text: &str) -> String {
+    escape(text).replace('"', "&quot;")
+}
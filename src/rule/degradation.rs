@@ -0,0 +1,89 @@
+use super::{RuleContext, RuleContextWrapper, RuleError, RuleFailure, Severity};
+
+/// What a run does when optional infrastructure a rule depends on, such as a
+/// lookup table, a script engine, a model or an HTTP service, fails, see
+/// `RuleRunner::run_with_degradation`.
+///
+/// Those failures are recorded with `RuleFailure::fail_unavailable`, which
+/// `Lookup`, `ModelRule`, `HttpAction` and scripted callbacks use, and so can
+/// the callbacks of the application querying their own data sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DegradationPolicy {
+    /// The failure fails the rule like any other.
+    #[default]
+    Fail,
+    /// The rule affected skips its remaining callbacks and its children, and
+    /// is reported as `RuleOutcome::NotApplicable`, so a best-first rule
+    /// moves on to its next sibling. The run goes on and is marked as
+    /// degraded, the failure being reported as a `Severity::Warning` warning
+    /// and in `RunReport::get_degraded`.
+    Degrade,
+}
+
+impl RuleContext {
+    /// How the current run treats failures of optional infrastructure.
+    pub fn get_degradation_policy(&self) -> DegradationPolicy {
+        self.degradation_policy
+    }
+
+    pub(crate) fn set_degradation_policy(&mut self, degradation_policy: DegradationPolicy) {
+        self.degradation_policy = degradation_policy;
+    }
+
+    /// Skips the rule being fired instead of failing it, see
+    /// `DegradationPolicy::Degrade`.
+    pub(crate) fn degrade(&mut self, error: RuleError) {
+        self.push_warning(Severity::Warning, format!("degraded: {error}"));
+        self.degraded.push(error);
+        self.degraded_rule = true;
+    }
+
+    /// Whether the rule being fired was skipped by `degrade`.
+    pub(crate) fn is_degraded_rule(&self) -> bool {
+        self.degraded_rule
+    }
+
+    /// Called once a rule has fired: tells whether it was skipped by
+    /// `degrade`, so that its parent isn't.
+    pub(crate) fn take_degraded_rule(&mut self) -> bool {
+        std::mem::take(&mut self.degraded_rule)
+    }
+
+    pub(crate) fn get_degraded_count(&self) -> usize {
+        self.degraded.len()
+    }
+
+    /// Removes the failures degraded on since `start` of them were.
+    pub(crate) fn take_degraded(&mut self, start: usize) -> Vec<RuleError> {
+        self.degraded.split_off(start.min(self.degraded.len()))
+    }
+}
+
+/// A failure of an action, telling those of the infrastructure it depends on
+/// apart from the others.
+pub(crate) enum ActionError {
+    Failed(RuleError),
+    Unavailable(RuleError),
+}
+
+impl ActionError {
+    pub(crate) fn into_error(self) -> RuleError {
+        match self {
+            ActionError::Failed(error) | ActionError::Unavailable(error) => error,
+        }
+    }
+
+    /// Records the failure as that of the rule being fired.
+    pub(crate) fn record(self, mut rule_context: RuleContextWrapper) {
+        match self {
+            ActionError::Failed(error) => rule_context.fail(error),
+            ActionError::Unavailable(error) => rule_context.fail_unavailable(error),
+        }
+    }
+}
+
+impl From<RuleError> for ActionError {
+    fn from(error: RuleError) -> Self {
+        ActionError::Failed(error)
+    }
+}
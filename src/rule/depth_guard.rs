@@ -0,0 +1,18 @@
+use super::{Rule, RuleContextWrapper, RuleError, RuleFailure, Wrapper};
+
+/// How deep rules may nest by default before firing them fails with
+/// `RuleError::BudgetExceeded`, low enough for a debug build to stay well
+/// within the stack of a thread.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Fires `rule` against the context, returning what `fire` returns. When the
+/// rule is already firing, because it is one of its own ancestors, fails with
+/// `RuleError::CycleDetected` instead and returns `true`.
+pub(crate) fn fire_rule<R: Rule<R>>(rule: &Wrapper<R>, rule_context: &RuleContextWrapper) -> bool {
+    let Ok(mut rule) = rule.try_borrow_mut() else {
+        rule_context.clone().fail(RuleError::CycleDetected);
+        return true;
+    };
+    rule.set_rule_context(rule_context.clone());
+    rule.fire()
+}
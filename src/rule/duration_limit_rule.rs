@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use super::{
+    wrap, Rule, RuleCallback, RuleContext, RuleContextWrapper, RuleError, RuleFailure,
+    RuleWarnings, Severity, Wrapper,
+};
+use crate::time::Instant;
+
+/// Decorates a rule so that its callbacks are aborted once they run longer
+/// than a time limit.
+///
+/// Rules and their context are not `Send`, so callbacks run on the thread
+/// firing the rule and can't be preempted: the timeout is cooperative. While
+/// a callback runs, the context holds its deadline, and long-running
+/// callbacks check `RuleContext::is_past_deadline` to give up, or bound
+/// their I/O with `RuleContext::get_time_left`. A callback that checks
+/// neither runs to its end, and its overrun is only reported once it
+/// returns. The runs a time-limited callback starts keep its deadline, and
+/// the limits within them can only bring it closer.
+///
+/// A callback that ran longer than the limit without recording a failure of
+/// its own fails the rule with a `RuleError::DurationExceeded` holding the
+/// elapsed time, so the rule skips its remaining callbacks and children and
+/// the run handles the failure like any other, according to its
+/// `ErrorPolicy`. An evaluation that overran does not pass. With
+/// `with_warning_at`, callbacks that run longer than a lower threshold but
+/// within the limit report a `Severity::Warning` instead.
+///
+/// # Example
+///
+/// ```rust
+/// use std::{thread, time::Duration};
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new().on_execute(|this| {
+///     // Work in steps until done, or out of time.
+///     while !this.get_rule_context().borrow().is_past_deadline() {
+///         thread::sleep(Duration::from_millis(1));
+///     }
+/// });
+/// let rule = DurationLimitRule::new(Duration::from_millis(5)).wrap(rule);
+///
+/// let rule_context = RuleContext::new();
+/// let result = Engine::chain_runner().try_run(rule_context, vec![rule]);
+///
+/// assert!(matches!(result, Err(RuleError::DurationExceeded { elapsed, .. }) if elapsed >= Duration::from_millis(5)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationLimitRule {
+    limit: Duration,
+    warning_at: Option<Duration>,
+}
+
+impl DurationLimitRule {
+    pub fn new(limit: Duration) -> Self {
+        DurationLimitRule {
+            limit,
+            warning_at: None,
+        }
+    }
+
+    /// Reports a warning, see `RuleWarnings`, for the callbacks running
+    /// longer than `threshold` without reaching the limit.
+    pub fn with_warning_at(mut self, threshold: Duration) -> Self {
+        self.warning_at = Some(threshold);
+        self
+    }
+
+    pub fn get_limit(&self) -> Duration {
+        self.limit
+    }
+
+    pub fn get_warning_at(&self) -> Option<Duration> {
+        self.warning_at
+    }
+
+    /// Replaces the callbacks of the rule with ones that time the original
+    /// callbacks, and returns the rule.
+    pub fn wrap<R>(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let limit = *self;
+
+        rule.on_eval({
+            let original = original.clone();
+            move |ctx| limit.fire(&original, ctx.get_rule_context(), |rule| rule.run_eval())
+        })
+        .on_pre_execute({
+            let original = original.clone();
+            move |this| limit.fire(&original, this.get_rule_context(), R::run_pre_execute)
+        })
+        .on_execute({
+            let original = original.clone();
+            move |this| limit.fire(&original, this.get_rule_context(), R::run_execute)
+        })
+        .on_post_execute(move |this| {
+            limit.fire(&original, this.get_rule_context(), R::run_post_execute)
+        })
+    }
+
+    fn fire<R: Rule<R>, T>(
+        &self,
+        original: &Wrapper<R>,
+        mut rule_context: RuleContextWrapper,
+        callback: impl FnOnce(&mut R) -> T,
+    ) -> T {
+        let mut original = original.borrow_mut();
+        original.set_rule_context(rule_context.clone());
+
+        let started = Instant::now();
+        let outer = rule_context.borrow().deadline;
+        let deadline = started + self.limit;
+        rule_context.borrow_mut().deadline =
+            Some(outer.map_or(deadline, |outer| outer.min(deadline)));
+        let result = callback(&mut original);
+        rule_context.borrow_mut().deadline = outer;
+        let elapsed = started.elapsed();
+        if elapsed > self.limit {
+            rule_context.fail(RuleError::DurationExceeded {
+                elapsed,
+                limit: self.limit,
+                rule: None,
+            });
+        } else if self.warning_at.is_some_and(|threshold| elapsed > threshold) {
+            rule_context.warn(
+                Severity::Warning,
+                format!(
+                    "callback ran for {elapsed:?}, close to the limit of {:?}",
+                    self.limit
+                ),
+            );
+        }
+        result
+    }
+}
+
+impl RuleContext {
+    /// Whether the callback running has gone past the deadline of a
+    /// `DurationLimitRule`, after which it should return. Always `false`
+    /// outside of a time-limited callback.
+    pub fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    /// The time left to the callback running before the deadline of a
+    /// `DurationLimitRule`, zero once it has passed, or `None` outside of a
+    /// time-limited callback.
+    pub fn get_time_left(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
@@ -0,0 +1,344 @@
+use std::{error::Error, fmt, sync::Arc, time::Duration};
+
+use super::{BudgetLimit, DegradationPolicy, Metadata, RuleContext, RuleContextWrapper};
+
+/// Why a rule could not complete.
+///
+/// The failures reported by callbacks hold the name, or else the id, of the
+/// rule that failed, see `RuleError::get_rule`. It is filled in once the
+/// failure leaves the rule, so it is `None` for the errors still being
+/// built, and for the rules with neither a name nor an id.
+#[derive(Debug, Clone)]
+pub enum RuleError {
+    /// A callback reported a failure with `RuleFailure::fail`.
+    Failed {
+        message: String,
+        rule: Option<String>,
+    },
+    /// A callback of a rule decorated by a `DurationLimitRule` ran longer
+    /// than allowed.
+    DurationExceeded {
+        elapsed: Duration,
+        limit: Duration,
+        rule: Option<String>,
+    },
+    /// The run went past a limit of its `Budget`, or a rule was nested
+    /// deeper than the maximum depth of the context, see
+    /// `RuleContext::set_max_depth`.
+    BudgetExceeded(BudgetLimit),
+    /// A rule was fired while already firing, being one of its own
+    /// ancestors.
+    CycleDetected,
+    /// An `Invariant`, given by its description, didn't hold once the run
+    /// was over.
+    InvariantViolated(String),
+    /// A callback failed with an error of its own, kept as the `source` of
+    /// the rule error, see `RuleError::custom`. Shared so that the rule error
+    /// can be cloned.
+    Custom {
+        error: Arc<dyn Error + Send + Sync>,
+        rule: Option<String>,
+    },
+}
+
+impl RuleError {
+    pub fn failed(message: impl Into<String>) -> Self {
+        RuleError::Failed {
+            message: message.into(),
+            rule: None,
+        }
+    }
+
+    /// Wraps an error returned by fallible code called from a callback,
+    /// such as an `anyhow::Error` or an `std::io::Error`, without losing it
+    /// to a message.
+    ///
+    /// Example:
+    /// ```rust
+    /// use std::error::Error;
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = ChainRule::new().on_execute(|this| {
+    ///     if let Err(error) = "12a".parse::<u32>() {
+    ///         this.get_rule_context().fail(RuleError::custom(error));
+    ///     }
+    /// });
+    ///
+    /// let error = Engine::chain_runner()
+    ///     .try_run(RuleContext::new(), vec![rule])
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.to_string(), "rule failed: invalid digit found in string");
+    /// let source = error.source().unwrap();
+    /// assert!(source.downcast_ref::<std::num::ParseIntError>().is_some());
+    /// ```
+    pub fn custom(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        RuleError::from(error.into())
+    }
+
+    /// The name, or id, of the rule that failed, for the failures reported
+    /// by callbacks.
+    pub fn get_rule(&self) -> Option<&str> {
+        match self {
+            RuleError::Failed { rule, .. }
+            | RuleError::DurationExceeded { rule, .. }
+            | RuleError::Custom { rule, .. } => rule.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The same failure, reported by the rule named `rule`. The other errors
+    /// are returned as they are.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = ChainRule::new()
+    ///     .with_name("stock")
+    ///     .on_execute(|this| this.get_rule_context().fail(RuleError::failed("no stock")));
+    ///
+    /// let error = Engine::chain_runner()
+    ///     .try_run(RuleContext::new(), vec![rule])
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error, RuleError::failed("no stock").in_rule("stock"));
+    /// assert_eq!(error.to_string(), "rule `stock` failed: no stock");
+    /// ```
+    pub fn in_rule(mut self, rule: impl Into<String>) -> Self {
+        if let Some(slot) = self.rule_mut() {
+            *slot = Some(rule.into());
+        }
+        self
+    }
+
+    fn rule_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            RuleError::Failed { rule, .. }
+            | RuleError::DurationExceeded { rule, .. }
+            | RuleError::Custom { rule, .. } => Some(rule),
+            _ => None,
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for RuleError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        RuleError::Custom {
+            error: Arc::from(error),
+            rule: None,
+        }
+    }
+}
+
+/// Custom errors are only equal to clones of themselves. Every variant is
+/// matched, so that a new one can't be left unequal to itself. Failures of
+/// different rules are different.
+impl PartialEq for RuleError {
+    fn eq(&self, other: &Self) -> bool {
+        match self {
+            RuleError::Failed { message, rule } => matches!(
+                other,
+                RuleError::Failed {
+                    message: other_message,
+                    rule: other_rule,
+                } if message == other_message && rule == other_rule
+            ),
+            RuleError::DurationExceeded {
+                elapsed,
+                limit,
+                rule,
+            } => matches!(
+                other,
+                RuleError::DurationExceeded {
+                    elapsed: other_elapsed,
+                    limit: other_limit,
+                    rule: other_rule,
+                } if elapsed == other_elapsed && limit == other_limit && rule == other_rule
+            ),
+            RuleError::BudgetExceeded(limit) => {
+                matches!(other, RuleError::BudgetExceeded(other) if limit == other)
+            }
+            RuleError::CycleDetected => matches!(other, RuleError::CycleDetected),
+            RuleError::InvariantViolated(invariant) => {
+                matches!(other, RuleError::InvariantViolated(other) if invariant == other)
+            }
+            RuleError::Custom { error, rule } => matches!(
+                other,
+                RuleError::Custom {
+                    error: other_error,
+                    rule: other_rule,
+                } if Arc::ptr_eq(error, other_error) && rule == other_rule
+            ),
+        }
+    }
+}
+
+impl Eq for RuleError {}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = match self.get_rule() {
+            Some(rule) => format!("rule `{rule}`"),
+            None => "rule".to_string(),
+        };
+        match self {
+            RuleError::Failed { message, .. } => write!(f, "{rule} failed: {message}"),
+            RuleError::DurationExceeded { elapsed, limit, .. } => {
+                write!(f, "{rule} ran for {elapsed:?}, over its limit of {limit:?}")
+            }
+            RuleError::BudgetExceeded(limit) => write!(f, "execution budget exceeded: {limit}"),
+            RuleError::CycleDetected => write!(f, "rule fired while already firing"),
+            RuleError::InvariantViolated(invariant) => write!(f, "invariant violated: {invariant}"),
+            RuleError::Custom { error, .. } => write!(f, "{rule} failed: {error}"),
+        }
+    }
+}
+
+impl Error for RuleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RuleError::Custom { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// What a run does when a rule fails, see `RuleRunner::run_with_policy`.
+///
+/// Whatever the policy, the failing rule skips its remaining callbacks and
+/// its children. A best-first rule that fails after its evaluation passed
+/// is still the one chosen among its siblings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stop the run and leave the error in the context.
+    #[default]
+    Abort,
+    /// Discard the error and go on with the following rules. The failure is
+    /// only visible in the trace of the run.
+    Skip,
+    /// Go on with the following rules and report every error in
+    /// `RunReport::get_errors`.
+    CollectAll,
+}
+
+/// Reports and inspects failures through the rule context.
+///
+/// Once a callback records a failure, the rule being fired skips its remaining
+/// callbacks and children, and the runners stop firing rules. The error stays
+/// in the context until it is taken, either with `take_error` or by
+/// `RuleRunner::try_run`.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new()
+///     .on_execute(|this| this.get_rule_context().fail(RuleError::failed("no stock")))
+///     .add_child(ChainRule::new().on_execute(|this| this.get_rule_context().set("shipped", true)));
+///
+/// let rule_context = RuleContext::new();
+/// let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(result, Err(RuleError::failed("no stock")));
+/// assert!(rule_context.get::<bool>("shipped").is_none());
+/// ```
+pub trait RuleFailure {
+    /// Records a failure. When one is already recorded, the first one is kept.
+    fn fail(&mut self, error: RuleError);
+    /// Records a failure of optional infrastructure the rule depends on,
+    /// such as a data source or a script engine: a failure like `fail`,
+    /// unless the run degrades gracefully, see `DegradationPolicy`.
+    fn fail_unavailable(&mut self, error: RuleError);
+    fn has_failed(&self) -> bool;
+    fn get_error(&self) -> Option<RuleError>;
+    /// Removes the recorded failure, letting rules fire again.
+    fn take_error(&mut self) -> Option<RuleError>;
+}
+
+impl RuleFailure for RuleContext {
+    fn fail(&mut self, error: RuleError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn fail_unavailable(&mut self, error: RuleError) {
+        match self.degradation_policy {
+            DegradationPolicy::Fail => self.fail(error),
+            DegradationPolicy::Degrade => self.degrade(error),
+        }
+    }
+
+    fn has_failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    fn get_error(&self) -> Option<RuleError> {
+        self.error.clone()
+    }
+
+    fn take_error(&mut self) -> Option<RuleError> {
+        self.error.take()
+    }
+}
+
+impl RuleFailure for RuleContextWrapper {
+    fn fail(&mut self, error: RuleError) {
+        self.borrow_mut().fail(error);
+    }
+
+    fn fail_unavailable(&mut self, error: RuleError) {
+        self.borrow_mut().fail_unavailable(error);
+    }
+
+    fn has_failed(&self) -> bool {
+        self.borrow().has_failed()
+    }
+
+    fn get_error(&self) -> Option<RuleError> {
+        self.borrow().get_error()
+    }
+
+    fn take_error(&mut self) -> Option<RuleError> {
+        self.borrow_mut().take_error()
+    }
+}
+
+impl RuleContext {
+    /// How the current run treats failures.
+    pub fn get_error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    pub(crate) fn set_error_policy(&mut self, error_policy: ErrorPolicy) {
+        self.error_policy = error_policy;
+    }
+
+    /// Called once a rule has fired: unless the run aborts on failures, takes
+    /// the failure out of the context so that the following rules fire.
+    pub(crate) fn recover_failure(&mut self) {
+        match self.error_policy {
+            ErrorPolicy::Abort => {}
+            ErrorPolicy::Skip => self.error = None,
+            ErrorPolicy::CollectAll => self.errors.extend(self.error.take()),
+        }
+    }
+
+    /// Called once a rule has fired: names the rule in the failure it
+    /// recorded, unless a child it fired already did.
+    pub(crate) fn name_failure(&mut self, metadata: &Metadata) {
+        let name = metadata.name.as_ref().or(metadata.id.as_ref());
+        if let (Some(slot), Some(name)) = (self.error.as_mut().and_then(RuleError::rule_mut), name)
+        {
+            slot.get_or_insert_with(|| name.clone());
+        }
+    }
+
+    pub(crate) fn get_collected_errors(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Removes the errors collected since `start` errors were collected.
+    pub(crate) fn take_collected_errors(&mut self, start: usize) -> Vec<RuleError> {
+        self.errors.split_off(start.min(self.errors.len()))
+    }
+}
@@ -0,0 +1,109 @@
+use super::{
+    depth_guard::fire_rule, wrap, ErrorPolicy, Rule, RuleCallback, RuleContextWrapper, RuleError,
+    RuleFailure, Wrapper,
+};
+
+/// Decorates a rule so that, when executed, it fires a list of alternatives
+/// in order until one of them succeeds.
+///
+/// An alternative succeeds when its evaluation passes and neither it nor its
+/// children record a failure. An alternative that fails is not fatal: its
+/// error is discarded, the values it set in the context are restored, and the
+/// next alternative is fired. When every alternative was tried without
+/// success, the decorated rule fails with the error of the last alternative
+/// that failed, or with a `RuleError::Failed` when none of them applied.
+/// Whatever the `ErrorPolicy` of the run, the failures of alternatives that
+/// were followed by a successful one are not reported.
+///
+/// The alternatives are fired after the execute callback of the decorated
+/// rule and before its post-execute callback and children. Like the other
+/// decorators, setting the execute callback of the rule afterwards replaces
+/// the fallback.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let live_price = ChainRule::new()
+///     .with_name("live_price")
+///     .on_execute(|this| this.get_rule_context().fail(RuleError::failed("timeout")));
+/// let cached_price = ChainRule::new()
+///     .with_name("cached_price")
+///     .on_execute(|this| this.get_rule_context().set("price", 990u32));
+///
+/// let rule = FallbackRule::new(vec![live_price, cached_price]).wrap(ChainRule::new().with_name("price"));
+///
+/// let rule_context = RuleContext::new();
+/// assert!(Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).is_ok());
+/// assert_eq!(*rule_context.get::<u32>("price").unwrap(), 990);
+/// ```
+pub struct FallbackRule<R> {
+    alternatives: Vec<Wrapper<R>>,
+}
+
+impl<R: Rule<R> + Clone + 'static> FallbackRule<R> {
+    pub fn new(alternatives: Vec<Wrapper<R>>) -> Self {
+        FallbackRule { alternatives }
+    }
+
+    pub fn get_alternatives(&self) -> &[Wrapper<R>] {
+        &self.alternatives
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then the alternatives, and returns the rule.
+    pub fn wrap(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let alternatives = self.alternatives.clone();
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            let mut original = original.borrow_mut();
+            original.set_rule_context(rule_context.clone());
+            original.run_execute();
+            if rule_context.has_failed() || alternatives.is_empty() {
+                return;
+            }
+            // Failures of the alternatives are handled here, whatever the
+            // policy of the run.
+            let error_policy = rule_context.borrow().get_error_policy();
+            rule_context
+                .borrow_mut()
+                .set_error_policy(ErrorPolicy::Abort);
+            fire_alternatives(&rule_context, &alternatives);
+            rule_context.borrow_mut().set_error_policy(error_policy);
+        })
+    }
+}
+
+fn fire_alternatives<R: Rule<R>>(rule_context: &RuleContextWrapper, alternatives: &[Wrapper<R>]) {
+    let mut last_error = None;
+    for alternative in alternatives {
+        let snapshot = rule_context.borrow().snapshot();
+        let start = rule_context.borrow_mut().start_trace();
+        fire_rule(alternative, rule_context);
+        // The entry of the alternative comes before those of its children.
+        let trace = rule_context.borrow_mut().finish_trace(start);
+        let executed = trace
+            .get_entries()
+            .first()
+            .is_some_and(|entry| entry.is_executed());
+
+        match rule_context.clone().take_error() {
+            Some(error) => {
+                rule_context.borrow_mut().restore(snapshot);
+                last_error = Some(error);
+            }
+            None if executed => return,
+            None => {}
+        }
+    }
+
+    rule_context
+        .clone()
+        .fail(last_error.unwrap_or_else(|| RuleError::failed("no fallback alternative applied")));
+}
@@ -0,0 +1,38 @@
+use std::{fmt, rc::Rc};
+
+use super::{Metadata, RuleContext};
+
+/// The rules a run may fire, see `Engine::execute_filtered`.
+#[derive(Clone)]
+pub(crate) struct RuleFilter(Rc<dyn Fn(&Metadata) -> bool>);
+
+impl RuleFilter {
+    pub(crate) fn new(filter: impl Fn(&Metadata) -> bool + 'static) -> Self {
+        RuleFilter(Rc::new(filter))
+    }
+
+    /// A filter letting through the rules both filters let through.
+    pub(crate) fn and(self, other: RuleFilter) -> Self {
+        RuleFilter::new(move |metadata| (self.0)(metadata) && (other.0)(metadata))
+    }
+
+    pub(crate) fn allows(&self, metadata: &Metadata) -> bool {
+        (self.0)(metadata)
+    }
+}
+
+impl fmt::Debug for RuleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RuleFilter")
+    }
+}
+
+impl RuleContext {
+    pub(crate) fn set_filter(&mut self, filter: Option<RuleFilter>) -> Option<RuleFilter> {
+        std::mem::replace(&mut self.filter, filter)
+    }
+
+    pub(crate) fn get_filter(&self) -> Option<&RuleFilter> {
+        self.filter.as_ref()
+    }
+}
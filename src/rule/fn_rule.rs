@@ -0,0 +1,190 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use super::{wrap, Rule, RuleCallback, RuleContext, RuleError, RuleFailure, Wrapper};
+use crate::time::Instant;
+
+/// Legacy decision code taking an input and returning an output, wrapped
+/// with `FnRule::mapped`. Implemented for functions and closures, and for
+/// boxed or shared trait objects.
+pub trait Decision<I, O> {
+    fn decide(&self, input: I) -> O;
+}
+
+impl<I, O, F: Fn(I) -> O> Decision<I, O> for F {
+    fn decide(&self, input: I) -> O {
+        self(input)
+    }
+}
+
+impl<I, O> Decision<I, O> for Box<dyn Decision<I, O>> {
+    fn decide(&self, input: I) -> O {
+        (**self).decide(input)
+    }
+}
+
+impl<I, O> Decision<I, O> for Rc<dyn Decision<I, O>> {
+    fn decide(&self, input: I) -> O {
+        (**self).decide(input)
+    }
+}
+
+/// What the legacy code wrapped by a `FnRule` did so far, see
+/// `FnRule::get_metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FnRuleMetrics {
+    calls: u64,
+    failures: u64,
+    duration: Duration,
+}
+
+impl FnRuleMetrics {
+    /// How many times the legacy code was called.
+    pub fn get_calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// How many of the calls failed the rule.
+    pub fn get_failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// The time spent in the legacy code.
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// Adapts legacy decision code into the execute callback of a rule, so that
+/// an if/else decision function can be moved into the engine piecemeal: the
+/// rule starts as a thin shell around the old code, then its conditions and
+/// actions are rewritten as rules one at a time.
+///
+/// `FnRule::from` takes a closure working on the context, while `mapped`
+/// takes a `Decision` with functions reading its input from the context and
+/// writing its output back, for code that knows nothing of the engine.
+///
+/// Each adapter counts the calls of its legacy code, their failures and the
+/// time they took, see `get_metrics`, so that the traffic still going through
+/// legacy code can be tracked during the migration. Clones share the metrics.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// fn legacy_discount(total: u32, vip: bool) -> u32 {
+///     if vip && total > 100 { 15 } else if total > 100 { 5 } else { 0 }
+/// }
+///
+/// let pricing = FnRule::from(|ctx: &mut RuleContext| {
+///     let total = ctx.get::<u32>("total").map_or(0, |total| *total);
+///     let vip = ctx.get::<bool>("vip").is_some_and(|vip| *vip);
+///     ctx.set("discount", legacy_discount(total, vip));
+/// });
+/// let rule = pricing.wrap(AllRule::new().with_name("legacy pricing"));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("total", 250u32);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 5);
+/// assert_eq!(pricing.get_metrics().get_calls(), 1);
+/// ```
+#[derive(Clone)]
+pub struct FnRule {
+    decide: Rc<dyn Fn(&mut RuleContext)>,
+    metrics: Rc<Cell<FnRuleMetrics>>,
+}
+
+impl FnRule {
+    /// Wraps legacy code working on the context. It fails the rule by
+    /// recording a failure with `RuleFailure::fail`.
+    pub fn new(decide: impl Fn(&mut RuleContext) + 'static) -> Self {
+        FnRule {
+            decide: Rc::new(decide),
+            metrics: Rc::default(),
+        }
+    }
+
+    /// Wraps legacy code with its own input and output types: `input` builds
+    /// its input from the context, failing the rule when it can't, and
+    /// `output` writes its output to the context.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// struct Applicant { age: u32 }
+    ///
+    /// let eligibility: Box<dyn Decision<Applicant, bool>> = Box::new(|applicant: Applicant| applicant.age >= 18);
+    /// let adapter = FnRule::mapped(
+    ///     eligibility,
+    ///     |ctx| {
+    ///         let age = ctx.get::<u32>("age").ok_or(RuleError::failed("missing age"))?;
+    ///         Ok(Applicant { age: *age })
+    ///     },
+    ///     |ctx, eligible| ctx.set("eligible", eligible),
+    /// );
+    /// let rule = adapter.wrap(ChainRule::new());
+    ///
+    /// let result = Engine::chain_runner().try_run(RuleContext::new(), vec![rule]);
+    /// assert_eq!(result, Err(RuleError::failed("missing age")));
+    /// assert_eq!(adapter.get_metrics().get_failures(), 1);
+    /// ```
+    pub fn mapped<I, O>(
+        decision: impl Decision<I, O> + 'static,
+        input: impl Fn(&RuleContext) -> Result<I, RuleError> + 'static,
+        output: impl Fn(&mut RuleContext, O) + 'static,
+    ) -> Self {
+        FnRule::new(move |rule_context| match input(rule_context) {
+            Ok(value) => output(rule_context, decision.decide(value)),
+            Err(error) => rule_context.fail(error),
+        })
+    }
+
+    pub fn get_metrics(&self) -> FnRuleMetrics {
+        self.metrics.get()
+    }
+
+    /// Replaces the execute callback of the rule with one running the
+    /// original callback, then the legacy code, and returns the rule.
+    pub fn wrap<R>(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let adapter = self.clone();
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            adapter.call(&mut rule_context.borrow_mut());
+        })
+    }
+
+    fn call(&self, rule_context: &mut RuleContext) {
+        let started = Instant::now();
+        (self.decide)(rule_context);
+        let mut metrics = self.metrics.get();
+        metrics.calls += 1;
+        metrics.duration += started.elapsed();
+        if rule_context.has_failed() {
+            metrics.failures += 1;
+        }
+        self.metrics.set(metrics);
+    }
+}
+
+impl<F: Fn(&mut RuleContext) + 'static> From<F> for FnRule {
+    fn from(decide: F) -> Self {
+        FnRule::new(decide)
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A switch turning a rule off and on at runtime, without rebuilding or
+/// redeploying the rule set.
+///
+/// Every rule has one, returned by `Metadata::get_handle`, and clones of the
+/// handle, like clones of the rule, share the switch. A disabled rule is not
+/// evaluated, nor are its children, and shows up in the trace as skipped with
+/// the `"disabled"` reason. Handles can be sent to other threads, such as
+/// the one serving an admin endpoint, and `RuleRegistry::get_handle` finds the
+/// handles of registered rules by name.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = AllRule::new()
+///     .with_name("fraud check")
+///     .on_execute(|this| this.get_rule_context().set("checked", true));
+/// let handle = rule.borrow().get_metadata().get_handle();
+///
+/// handle.disable();
+/// let rule_context = RuleContext::new();
+/// Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+/// assert!(rule_context.get::<bool>("checked").is_none());
+///
+/// handle.enable();
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+/// assert!(*rule_context.get::<bool>("checked").unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleHandle {
+    enabled: Arc<AtomicBool>,
+}
+
+impl RuleHandle {
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for RuleHandle {
+    fn default() -> Self {
+        RuleHandle {
+            enabled: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::rc::Rc;
+
+use super::{Condition, RuleContext, Severity};
+
+/// A property of the context that must hold once a run is over, a safety
+/// net against rules writing inconsistent combinations of values.
+///
+/// Invariants are declared alongside the rules with
+/// `RuleRegistry::add_invariant`, or given to `RuleRunner::run_with_invariants`,
+/// and checked after each run that didn't fail. A violated invariant fails
+/// the run with `RuleError::InvariantViolated`, or only reports a warning of
+/// its severity when made lenient with `as_warning`.
+///
+/// Invariants are built from conditions: `always` holds when its condition
+/// does, and `when(...).then(...)` whenever its first condition holds, the
+/// second one does too. They are described by their conditions, or named
+/// with `with_name`.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let limit = Condition::new(|ctx| ctx.get::<u32>("limit").is_some_and(|limit| *limit <= 10_000))
+///     .with_description("`limit` <= 10000");
+/// let invariant = Invariant::when(Condition::key_equals("approved", true)).then(limit);
+/// assert_eq!(invariant.get_description(), "if `approved` == true then `limit` <= 10000");
+///
+/// let rule = AllRule::new().on_execute(|this| this.get_rule_context().set("approved", true));
+///
+/// let report = Engine::all_runner().run_with_invariants(RuleContext::new(), vec![rule], &[invariant]);
+///
+/// assert_eq!(
+///     report.get_error(),
+///     Some(&RuleError::InvariantViolated("if `approved` == true then `limit` <= 10000".to_string()))
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Invariant {
+    condition: Condition,
+    name: Option<Rc<str>>,
+    severity: Severity,
+}
+
+/// The first half of an invariant built with `Invariant::when`.
+#[derive(Debug, Clone)]
+pub struct InvariantWhen {
+    when: Condition,
+}
+
+impl InvariantWhen {
+    /// Completes the invariant: `then` must hold whenever the condition
+    /// given to `when` holds.
+    pub fn then(self, then: Condition) -> Invariant {
+        let description = match (self.when.get_description(), then.get_description()) {
+            (Some(when), Some(then)) => Some(format!("if {when} then {then}")),
+            _ => None,
+        };
+        let when = self.when;
+        let condition = Condition::new(move |ctx| !when.eval(ctx) || then.eval(ctx));
+        Invariant::always(match description {
+            Some(description) => condition.with_description(&description),
+            None => condition,
+        })
+    }
+}
+
+impl Invariant {
+    /// The condition must hold after every run.
+    pub fn always(condition: Condition) -> Self {
+        Invariant {
+            condition,
+            name: None,
+            severity: Severity::Critical,
+        }
+    }
+
+    /// Starts an invariant that only constrains the runs where `when` holds,
+    /// completed with `InvariantWhen::then`.
+    pub fn when(when: Condition) -> InvariantWhen {
+        InvariantWhen { when }
+    }
+
+    /// Names the invariant, the name replacing the description of its
+    /// conditions in errors and warnings.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Reports a violation as a `Severity::Warning` warning instead of
+    /// failing the run.
+    pub fn as_warning(mut self) -> Self {
+        self.severity = Severity::Warning;
+        self
+    }
+
+    /// The name of the invariant, or else the description of its
+    /// conditions.
+    pub fn get_description(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.condition.get_description())
+            .unwrap_or("<undescribed invariant>")
+    }
+
+    /// `Severity::Critical` when a violation fails the run.
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Whether the invariant holds in the context.
+    pub fn check(&self, rule_context: &RuleContext) -> bool {
+        self.condition.eval(rule_context)
+    }
+}
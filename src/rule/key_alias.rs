@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use super::{RuleContext, Severity};
+
+impl RuleContext {
+    /// Declares `old` as a deprecated name of the key `new`, to rename a key
+    /// without updating every rule at once.
+    ///
+    /// Reads and writes of `old` go to `new` instead, and the first use of
+    /// `old` reports a `Severity::Info` warning naming the rule that used
+    /// it, see `RuleWarnings`. Aliases may be chained, `old` resolving to
+    /// whatever `new` is itself an alias of.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = ChainRule::new()
+    ///     .with_name("legacy_lookup")
+    ///     .on_eval(|ctx| ctx.get::<u64>("customer_id").is_some())
+    ///     .on_execute(|this| this.get_rule_context().set("found", true));
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.borrow_mut().deprecate_key("customer_id", "cust.id");
+    /// rule_context.set("cust.id", 42u64);
+    /// let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![rule]);
+    ///
+    /// assert!(*rule_context.get::<bool>("found").unwrap());
+    /// assert_eq!(
+    ///     report.get_warnings()[0].to_string(),
+    ///     "info in `legacy_lookup`: key `customer_id` is deprecated, use `cust.id`"
+    /// );
+    /// ```
+    pub fn deprecate_key(&mut self, old: &'static str, new: &'static str) {
+        self.key_aliases.insert(old, new);
+    }
+
+    /// The deprecated keys and the keys they stand for.
+    pub fn get_key_aliases(&self) -> &HashMap<&'static str, &'static str> {
+        &self.key_aliases
+    }
+
+    /// The key `key` stands for, warning when it is deprecated.
+    pub(crate) fn resolve_key<'a>(&self, key: &'a str) -> &'a str {
+        let resolved = self.resolve_alias(key);
+        if resolved == key {
+            return key;
+        }
+        let message = format!("key `{key}` is deprecated, use `{resolved}`");
+        if !self
+            .warnings
+            .borrow()
+            .iter()
+            .any(|warning| warning.get_message() == message)
+        {
+            self.push_warning(Severity::Info, message);
+        }
+        resolved
+    }
+
+    /// The key `key` stands for, without warning, for the reads made on
+    /// behalf of the rules rather than by them.
+    pub(crate) fn resolve_alias<'a>(&self, key: &'a str) -> &'a str {
+        let Some(mut resolved) = self.key_aliases.get(key).copied() else {
+            return key;
+        };
+        // Bounded, in case aliases form a cycle.
+        for _ in 0..self.key_aliases.len() {
+            match self.key_aliases.get(resolved) {
+                Some(next) => resolved = next,
+                None => break,
+            }
+        }
+        resolved
+    }
+}
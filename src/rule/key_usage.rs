@@ -0,0 +1,171 @@
+use std::{cell::RefCell, collections::BTreeSet};
+
+use super::{Rule, RuleContext, RuleContextWrapper, RuleRunner, Wrapper};
+
+/// The context keys a rule set reads and writes.
+///
+/// The keys can be taken from the declarations of the rules, made with
+/// `RuleMetadata::with_reads` and `with_writes`, or recorded while running
+/// the rules, from the keys their callbacks get and set. Declarations may be
+/// incomplete, and a recorded run only sees the branches it took, so usages
+/// from several sources and runs can be merged.
+///
+/// The input keys are the keys read before the rules wrote them: the keys a
+/// caller needs to provide, or prefetch, before running the rules.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rules = vec![
+///     AllRule::new()
+///         .with_reads(&["age"])
+///         .with_writes(&["adult"])
+///         .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+///         .on_execute(|this| this.get_rule_context().set("adult", true)),
+///     AllRule::new()
+///         .on_eval(|ctx| ctx.get::<bool>("adult").is_some())
+///         .on_execute(|this| this.get_rule_context().set("approved", true)),
+/// ];
+///
+/// let declared = KeyUsage::declared(&rules);
+/// assert_eq!(declared.read_keys(), vec!["age"]);
+/// assert_eq!(declared.write_keys(), vec!["adult"]);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 42i64);
+/// let recorded = KeyUsage::record(&Engine::all_runner(), rule_context, rules);
+/// assert_eq!(recorded.read_keys(), vec!["adult", "age"]);
+/// assert_eq!(recorded.write_keys(), vec!["adult", "approved"]);
+/// assert_eq!(recorded.input_keys(), vec!["age"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyUsage {
+    reads: BTreeSet<String>,
+    writes: BTreeSet<String>,
+    inputs: BTreeSet<String>,
+}
+
+impl KeyUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The keys declared by the rules and their descendants. Keys declared as
+    /// read that are not declared as written by any rule are inputs.
+    pub fn declared<R: Rule<R>>(rules: &[Wrapper<R>]) -> Self {
+        let mut usage = KeyUsage::new();
+        let mut pending = rules.to_vec();
+        while let Some(rule) = pending.pop() {
+            let mut rule = rule.borrow_mut();
+            usage.reads.extend(rule.get_reads().iter().cloned());
+            usage.writes.extend(rule.get_writes().iter().cloned());
+            pending.extend(rule.get_children());
+        }
+        usage.inputs = usage.reads.difference(&usage.writes).cloned().collect();
+        usage
+    }
+
+    /// Runs the rules with the runner and returns the keys their callbacks
+    /// read and wrote, whether through `GetSet`, conditions, expressions or
+    /// any other access to the context by key.
+    pub fn record<T, Runner>(
+        runner: &Runner,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<T>>,
+    ) -> Self
+    where
+        Runner: RuleRunner<RuleType = T>,
+    {
+        let previous = rule_context
+            .borrow_mut()
+            .key_recorder
+            .replace(KeyRecorder::default());
+        runner.run(rule_context.clone(), rules);
+        let recorder = std::mem::replace(&mut rule_context.borrow_mut().key_recorder, previous);
+        let recorder = recorder.unwrap_or_default();
+        let usage = recorder.to_usage();
+        // A run recorded inside another one is part of it.
+        if let Some(outer) = &mut rule_context.borrow_mut().key_recorder {
+            outer.merge(recorder);
+        }
+        usage
+    }
+
+    /// Adds the keys of another usage to this one, as if the rules of
+    /// `other` ran after these: its inputs that these rules write are not
+    /// inputs of the merged usage.
+    pub fn merge(&mut self, other: &KeyUsage) {
+        self.inputs
+            .extend(other.inputs.difference(&self.writes).cloned());
+        self.reads.extend(other.reads.iter().cloned());
+        self.writes.extend(other.writes.iter().cloned());
+    }
+
+    /// The keys read, sorted.
+    pub fn read_keys(&self) -> Vec<&str> {
+        self.reads.iter().map(String::as_str).collect()
+    }
+
+    /// The keys written, sorted.
+    pub fn write_keys(&self) -> Vec<&str> {
+        self.writes.iter().map(String::as_str).collect()
+    }
+
+    /// The keys read before being written, sorted.
+    pub fn input_keys(&self) -> Vec<&str> {
+        self.inputs.iter().map(String::as_str).collect()
+    }
+
+    /// The input keys missing from the context.
+    pub fn get_missing(&self, rule_context: &RuleContext) -> Vec<&str> {
+        self.inputs
+            .iter()
+            .map(String::as_str)
+            .filter(|key| rule_context.lookup_resolved(key).is_none())
+            .collect()
+    }
+}
+
+/// The keys got and set on a context while a run is recorded.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyRecorder {
+    reads: RefCell<BTreeSet<String>>,
+    writes: BTreeSet<String>,
+    inputs: RefCell<BTreeSet<String>>,
+}
+
+impl KeyRecorder {
+    pub(crate) fn read(&self, key: &str) {
+        if !self.reads.borrow().contains(key) {
+            self.reads.borrow_mut().insert(key.to_string());
+        }
+        if !self.writes.contains(key) && !self.inputs.borrow().contains(key) {
+            self.inputs.borrow_mut().insert(key.to_string());
+        }
+    }
+
+    pub(crate) fn write(&mut self, key: &str) {
+        if !self.writes.contains(key) {
+            self.writes.insert(key.to_string());
+        }
+    }
+
+    fn merge(&mut self, other: KeyRecorder) {
+        for key in other.inputs.into_inner() {
+            if !self.writes.contains(&key) {
+                self.inputs.get_mut().insert(key);
+            }
+        }
+        self.reads.get_mut().extend(other.reads.into_inner());
+        self.writes.extend(other.writes);
+    }
+
+    fn to_usage(&self) -> KeyUsage {
+        KeyUsage {
+            reads: self.reads.borrow().clone(),
+            writes: self.writes.clone(),
+            inputs: self.inputs.borrow().clone(),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{any::Any, rc::Rc};
+
+use super::{RuleContext, RuleContextWrapper};
+
+/// Stacks contexts like configuration layers: reads fall through the layers
+/// until one holds the key, and writes go to the top layer.
+///
+/// `LayeredContext::new` makes the first context of the list the top layer,
+/// backed by the others in order, and returns it, ready to be run. The lower
+/// layers are shared, not copied, and are never written by the rules, so the
+/// same tenant or global defaults can back any number of requests.
+///
+/// Values read from a lower layer with `ContextMut` or `ContextList` are
+/// copied to the top layer before being changed. Reads by key fall through,
+/// while snapshots, exports and templates only see the top layer.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut global_defaults = RuleContext::new();
+/// global_defaults.set("currency", "USD");
+/// global_defaults.set("max_discount", 0.1);
+///
+/// let mut tenant_defaults = RuleContext::new();
+/// tenant_defaults.set("max_discount", 0.25);
+///
+/// let mut request = RuleContext::new();
+/// request.set("total", 300.0);
+///
+/// let rule_context = LayeredContext::new(vec![request.clone(), tenant_defaults.clone(), global_defaults]);
+/// let rule = AllRule::new().on_execute(|this| {
+///     let mut rule_context = this.get_rule_context();
+///     let discount = *rule_context.get::<f64>("total").unwrap() * *rule_context.get::<f64>("max_discount").unwrap();
+///     rule_context.set("discount", discount);
+///     rule_context.set("max_discount", 0.0);
+/// });
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<&str>("currency").unwrap(), "USD");
+/// assert_eq!(*request.get::<f64>("discount").unwrap(), 75.0);
+/// assert_eq!(*request.get::<f64>("max_discount").unwrap(), 0.0);
+/// assert_eq!(*tenant_defaults.get::<f64>("max_discount").unwrap(), 0.25);
+/// ```
+pub struct LayeredContext;
+
+impl LayeredContext {
+    /// Returns the first context, backed by the others in order, after the
+    /// layers it already had, such as the parent of a child scope. An empty
+    /// list gives a new context.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(layers: Vec<RuleContextWrapper>) -> RuleContextWrapper {
+        let mut layers = layers.into_iter();
+        let Some(top) = layers.next() else {
+            return RuleContext::new();
+        };
+        {
+            let mut rule_context = top.borrow_mut();
+            for layer in layers {
+                // A context can't back itself, nor be backed twice by a layer.
+                if !Rc::ptr_eq(&layer, &top)
+                    && !rule_context.layers.iter().any(|l| Rc::ptr_eq(l, &layer))
+                {
+                    rule_context.layers.push(layer);
+                }
+            }
+        }
+        top
+    }
+}
+
+impl RuleContext {
+    /// The contexts backing this one, see `LayeredContext`.
+    pub fn get_layers(&self) -> &[RuleContextWrapper] {
+        &self.layers
+    }
+
+    /// The layers backing the context and, in turn, those backing them, in
+    /// the order their values are read, each once: layers backing each other
+    /// in a cycle are only visited the first time.
+    pub(crate) fn get_all_layers(&self) -> Vec<RuleContextWrapper> {
+        let mut layers = Vec::new();
+        self.collect_layers(self, &mut layers);
+        layers
+    }
+
+    fn collect_layers(&self, top: *const RuleContext, layers: &mut Vec<RuleContextWrapper>) {
+        for layer in &self.layers {
+            // Compared before borrowing, the top context being borrowed
+            // mutably while it is read.
+            if std::ptr::eq(layer.as_ptr(), top) || layers.iter().any(|l| Rc::ptr_eq(l, layer)) {
+                continue;
+            }
+            layers.push(layer.clone());
+            layer.borrow().collect_layers(top, layers);
+        }
+    }
+
+    /// The value of the key, or of the key it is a deprecated name of, from
+    /// this context or, when it doesn't hold the key, from the first layer
+    /// that does. Every read of a value by key goes through here, to be
+    /// recorded by `KeyUsage::record`.
+    pub(crate) fn lookup(&self, key: &str) -> Option<Rc<dyn Any>> {
+        let key = self.resolve_key(key);
+        if let Some(recorder) = &self.key_recorder {
+            recorder.read(key);
+        }
+        self.lookup_resolved(key)
+    }
+
+    /// The value of an already resolved key, see `lookup`.
+    pub(crate) fn lookup_resolved(&self, key: &str) -> Option<Rc<dyn Any>> {
+        if let Some(value) = self.context_map.get(key) {
+            return Some(value.clone());
+        }
+        self.get_all_layers()
+            .iter()
+            .find_map(|layer| layer.borrow().context_map.get(key).cloned())
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
+
+#[cfg(feature = "csv")]
+use std::{error::Error, io};
+
+use super::{
+    degradation::ActionError, weighted_choice::key_text, GetSet, Rule, RuleContext,
+    RuleContextWrapper, RuleError,
+};
+
+/// A table mapping keys to values, read by a `Lookup`.
+///
+/// Implemented for `HashMap` and `BTreeMap` with `String` keys, for
+/// functions, which query databases or services, and, with the `csv`
+/// feature, for `CsvTable`. Errors are reported as the message of the failure
+/// of the rule.
+pub trait LookupTable<V> {
+    /// The value of `key`, or `None` when the table has no entry for it.
+    fn get(&self, key: &str) -> Result<Option<V>, String>;
+}
+
+impl<V: Clone> LookupTable<V> for HashMap<String, V> {
+    fn get(&self, key: &str) -> Result<Option<V>, String> {
+        Ok(HashMap::get(self, key).cloned())
+    }
+}
+
+impl<V: Clone> LookupTable<V> for BTreeMap<String, V> {
+    fn get(&self, key: &str) -> Result<Option<V>, String> {
+        Ok(BTreeMap::get(self, key).cloned())
+    }
+}
+
+impl<V, F: Fn(&str) -> Result<Option<V>, String>> LookupTable<V> for F {
+    fn get(&self, key: &str) -> Result<Option<V>, String> {
+        self(key)
+    }
+}
+
+/// A part of the key template of a lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Text(&'static str),
+    Key(&'static str),
+}
+
+/// An action mapping a context value to another through a table, such as a
+/// country to its region or a plan to its limits, instead of a best-first
+/// tree with one child per entry.
+///
+/// The key looked up is a template in which `{key}` is replaced by the value
+/// of the context key `key`, so `"{country}"` looks up the country itself and
+/// `"{plan}/{region}"` combines two keys. Strings and integers can be used in
+/// templates. The value found is written to the output key, with the type of
+/// the values of the table.
+///
+/// A missing context key, a value of another type, or an error of the table
+/// makes the rule fail with a `RuleError`. So does a key without an entry in
+/// the table, unless a default value is given with `with_default`. Errors of
+/// the table are failures of optional infrastructure, on which a run may
+/// degrade instead, see `DegradationPolicy`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use dredd_rs::rule::*;
+///
+/// let regions = HashMap::from([
+///     ("PT".to_string(), "EMEA"),
+///     ("BR".to_string(), "LATAM"),
+/// ]);
+/// let lookup = Lookup::new("{country}", regions, "region").with_default("ROW");
+///
+/// let rule = AllRule::new().on_execute(lookup.callback());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("country", "BR");
+/// Engine::all_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+///
+/// assert_eq!(*rule_context.get::<&str>("region").unwrap(), "LATAM");
+/// ```
+pub struct Lookup<V> {
+    key: &'static str,
+    segments: Vec<Segment>,
+    table: Rc<dyn LookupTable<V>>,
+    output: &'static str,
+    default: Option<V>,
+}
+
+impl<V: Clone + 'static> Lookup<V> {
+    /// Looks up the key rendered from the template `key` in `table`, writing
+    /// the value found to `output`. A `{` without a closing `}` is kept as
+    /// text.
+    pub fn new(
+        key: &'static str,
+        table: impl LookupTable<V> + 'static,
+        output: &'static str,
+    ) -> Self {
+        Lookup {
+            key,
+            segments: parse_template(key),
+            table: Rc::new(table),
+            output,
+            default: None,
+        }
+    }
+
+    /// Sets the value written when the table has no entry for the key.
+    pub fn with_default(mut self, default: V) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn get_output(&self) -> &'static str {
+        self.output
+    }
+
+    /// The context keys the key template reads, in order.
+    pub fn get_inputs(&self) -> Vec<&'static str> {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Key(key) => Some(*key),
+                Segment::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Renders the key for the context, looks it up and writes the value to
+    /// the output key.
+    pub fn run(&self, rule_context: RuleContextWrapper) -> Result<(), RuleError> {
+        self.try_run(rule_context).map_err(ActionError::into_error)
+    }
+
+    fn try_run(&self, mut rule_context: RuleContextWrapper) -> Result<(), ActionError> {
+        let key = self.render(&rule_context.borrow())?;
+        let found = self
+            .table
+            .get(&key)
+            .map_err(|message| ActionError::Unavailable(RuleError::failed(message)))?;
+        let value = match found {
+            Some(value) => value,
+            None => self.default.clone().ok_or_else(|| {
+                RuleError::failed(format!("no entry for `{key}` in lookup table"))
+            })?,
+        };
+        rule_context.set(self.output, value);
+        Ok(())
+    }
+
+    /// An execute callback running the lookup, recording its error as the
+    /// failure of the rule, or an error of the table with
+    /// `RuleFailure::fail_unavailable`.
+    pub fn callback<R: Rule<R>>(&self) -> impl Fn(&mut R) + 'static {
+        let lookup = self.clone();
+        move |this| {
+            if let Err(error) = lookup.try_run(this.get_rule_context()) {
+                error.record(this.get_rule_context());
+            }
+        }
+    }
+
+    fn render(&self, rule_context: &RuleContext) -> Result<String, RuleError> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Key(key) => {
+                    let value = rule_context.lookup(key).ok_or_else(|| {
+                        RuleError::failed(format!("missing lookup input `{key}`"))
+                    })?;
+                    let text = key_text(value.as_ref()).ok_or_else(|| {
+                        RuleError::failed(format!(
+                            "lookup input `{key}` is not a string or an integer"
+                        ))
+                    })?;
+                    rendered.push_str(&text);
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+impl<V: Clone> Clone for Lookup<V> {
+    fn clone(&self) -> Self {
+        Lookup {
+            key: self.key,
+            segments: self.segments.clone(),
+            table: self.table.clone(),
+            output: self.output,
+            default: self.default.clone(),
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Lookup<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lookup")
+            .field("key", &self.key)
+            .field("output", &self.output)
+            .field("default", &self.default)
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse_template(template: &'static str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        segments.push(Segment::Key(&rest[start + 1..start + length]));
+        rest = &rest[start + length + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+/// A lookup table read from two columns of a CSV document with a header row.
+///
+/// Cells are trimmed, and when a key appears in several rows, the last one
+/// wins.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let csv = "plan,daily_limit\nfree,100\npro,10000\n";
+/// let limits = CsvTable::from_csv(csv.as_bytes(), "plan", "daily_limit").unwrap();
+///
+/// let rule = AllRule::new().on_execute(Lookup::new("{plan}", limits, "limit").callback());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("plan", "pro");
+/// Engine::all_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+///
+/// assert_eq!(*rule_context.get::<String>("limit").unwrap(), "10000");
+/// ```
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Default)]
+pub struct CsvTable {
+    entries: HashMap<String, String>,
+}
+
+#[cfg(feature = "csv")]
+impl CsvTable {
+    /// Reads the `key_column` and `value_column` columns of a CSV document.
+    pub fn from_csv(
+        reader: impl io::Read,
+        key_column: &str,
+        value_column: &str,
+    ) -> Result<Self, CsvTableError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| CsvTableError::MissingColumn(name.to_string()))
+        };
+        let (key_column, value_column) = (column(key_column)?, column(value_column)?);
+
+        let mut entries = HashMap::new();
+        for record in reader.records() {
+            let record = record?;
+            let cell = |column| record.get(column).unwrap_or_default().to_string();
+            entries.insert(cell(key_column), cell(value_column));
+        }
+        Ok(CsvTable { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(feature = "csv")]
+impl LookupTable<String> for CsvTable {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.entries.get(key).cloned())
+    }
+}
+
+/// Why a `CsvTable` could not be read.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvTableError {
+    /// The CSV document could not be read.
+    Csv(String),
+    /// The header has no column with this name.
+    MissingColumn(String),
+}
+
+#[cfg(feature = "csv")]
+impl fmt::Display for CsvTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvTableError::Csv(message) => write!(f, "invalid CSV: {message}"),
+            CsvTableError::MissingColumn(column) => write!(f, "missing `{column}` column"),
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl Error for CsvTableError {}
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for CsvTableError {
+    fn from(error: csv::Error) -> Self {
+        CsvTableError::Csv(error.to_string())
+    }
+}
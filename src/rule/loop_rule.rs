@@ -0,0 +1,109 @@
+use std::rc::Rc;
+
+use super::{
+    depth_guard::fire_rule, wrap, ContextView, Rule, RuleCallback, RuleError, RuleFailure, Wrapper,
+};
+
+type Condition = Rc<dyn Fn(ContextView<'_>) -> bool>;
+
+/// Decorates a rule so that, when executed, it fires a body rule repeatedly
+/// while a condition on the context holds.
+///
+/// The condition is checked before every iteration, so the body is not fired
+/// at all when it doesn't hold at first. Like an evaluation callback, it is
+/// given a read-only view of the context, so only the body changes it. Each iteration fires the body, and
+/// through it its children, like a runner would. The loop stops as soon as
+/// the body records a failure, which is left in the context. To guard against
+/// conditions that never stop holding, the loop fails the rule with a
+/// `RuleError::Failed` once the body was fired `max_iterations` times and the
+/// condition still holds.
+///
+/// The loop runs after the execute callback of the decorated rule and before
+/// its post-execute callback and children. Like the other decorators, setting
+/// the execute callback of the rule afterwards replaces the loop.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let drain = ChainRule::new().on_execute(|this| {
+///     let mut queue = this.get_rule_context().get::<Vec<u32>>("queue").unwrap().to_vec();
+///     let total = this.get_rule_context().get::<u32>("total").map_or(0, |total| *total);
+///     this.get_rule_context().set("total", total + queue.pop().unwrap());
+///     this.get_rule_context().set("queue", queue);
+/// });
+///
+/// let rule = LoopRule::new(
+///     |ctx| !ctx.get::<Vec<u32>>("queue").unwrap().is_empty(),
+///     drain,
+///     100,
+/// )
+/// .wrap(ChainRule::new());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("queue", vec![1u32, 2, 3]);
+/// assert!(Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).is_ok());
+/// assert_eq!(*rule_context.get::<u32>("total").unwrap(), 6);
+/// ```
+pub struct LoopRule<R> {
+    condition: Condition,
+    body: Wrapper<R>,
+    max_iterations: u32,
+}
+
+impl<R: Rule<R> + Clone + 'static> LoopRule<R> {
+    /// Creates a loop firing `body` while `condition` holds, at most
+    /// `max_iterations` times.
+    pub fn new(
+        condition: impl Fn(ContextView<'_>) -> bool + 'static,
+        body: Wrapper<R>,
+        max_iterations: u32,
+    ) -> Self {
+        LoopRule {
+            condition: Rc::new(condition),
+            body,
+            max_iterations,
+        }
+    }
+
+    pub fn get_body(&self) -> Wrapper<R> {
+        self.body.clone()
+    }
+
+    pub fn get_max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then the loop, and returns the rule.
+    pub fn wrap(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let condition = self.condition.clone();
+        let body = self.body.clone();
+        let max_iterations = self.max_iterations;
+
+        rule.on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            let mut iterations = 0;
+            while !rule_context.has_failed() && condition(ContextView::new(&rule_context)) {
+                if iterations == max_iterations {
+                    rule_context.fail(RuleError::failed(format!(
+                        "loop still running after {max_iterations} iterations"
+                    )));
+                    return;
+                }
+                fire_rule(&body, &rule_context);
+                iterations += 1;
+            }
+        })
+    }
+}
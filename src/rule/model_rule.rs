@@ -0,0 +1,233 @@
+use std::{any::Any, fmt, rc::Rc};
+
+use super::{
+    degradation::ActionError, value_types::Primitive, wrap, GetSet, Rule, RuleCallback,
+    RuleContextWrapper, RuleError, Wrapper,
+};
+
+/// A model scoring a row of numeric features.
+///
+/// Implemented for functions and, with the `onnx` feature, for `OnnxModel`.
+/// Errors are reported as the message of the failure of the rule.
+pub trait Model {
+    fn predict(&self, features: &[f32]) -> Result<Vec<f32>, String>;
+}
+
+impl<F: Fn(&[f32]) -> Result<Vec<f32>, String>> Model for F {
+    fn predict(&self, features: &[f32]) -> Result<Vec<f32>, String> {
+        self(features)
+    }
+}
+
+/// Decorates a rule so that it runs a model before its execute callback.
+///
+/// The features are read from the context keys given with `with_inputs`, in
+/// order, and may be of any primitive number type or `bool`. The values
+/// predicted by the model are written as `f32` to the keys given with
+/// `with_outputs`, in order, before the original execute callback and the
+/// children run, so that rules evaluated afterwards can use them.
+///
+/// A missing or non numeric input, a model error, or a prediction with fewer
+/// values than output keys makes the rule fail with a `RuleError`. Model
+/// errors are failures of optional infrastructure, on which a run may degrade
+/// instead, see `DegradationPolicy`.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let model = |features: &[f32]| Ok(vec![features[0] / 1000.0 + features[1] * 0.5]);
+///
+/// let rule = ChainRule::new().add_child(
+///     ChainRule::new()
+///         .on_eval(|ctx| *ctx.get::<f32>("fraud_score").unwrap() > 0.5)
+///         .on_execute(|this| this.get_rule_context().set("review", true)),
+/// );
+/// let rule = ModelRule::new(model)
+///     .with_inputs(&["amount", "new_customer"])
+///     .with_outputs(&["fraud_score"])
+///     .wrap(rule);
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("amount", 300u32);
+/// rule_context.set("new_customer", true);
+///
+/// Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+/// assert_eq!(*rule_context.get::<f32>("fraud_score").unwrap(), 0.8);
+/// assert!(*rule_context.get::<bool>("review").unwrap());
+/// ```
+#[derive(Clone)]
+pub struct ModelRule {
+    model: Rc<dyn Model>,
+    inputs: Vec<&'static str>,
+    outputs: Vec<&'static str>,
+}
+
+impl ModelRule {
+    pub fn new(model: impl Model + 'static) -> Self {
+        ModelRule {
+            model: Rc::new(model),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Sets the context keys the features are read from, in order.
+    pub fn with_inputs(mut self, inputs: &[&'static str]) -> Self {
+        self.inputs = inputs.to_vec();
+        self
+    }
+
+    /// Sets the context keys the predicted values are written to, in order.
+    pub fn with_outputs(mut self, outputs: &[&'static str]) -> Self {
+        self.outputs = outputs.to_vec();
+        self
+    }
+
+    pub fn get_inputs(&self) -> &[&'static str] {
+        &self.inputs
+    }
+
+    pub fn get_outputs(&self) -> &[&'static str] {
+        &self.outputs
+    }
+
+    /// Runs the model on the inputs of the context and writes its prediction
+    /// to the outputs.
+    pub fn predict(&self, rule_context: RuleContextWrapper) -> Result<(), RuleError> {
+        self.try_predict(rule_context)
+            .map_err(ActionError::into_error)
+    }
+
+    fn try_predict(&self, mut rule_context: RuleContextWrapper) -> Result<(), ActionError> {
+        let features = {
+            let context = rule_context.borrow();
+            self.inputs
+                .iter()
+                .map(|key| {
+                    let value = context
+                        .lookup(key)
+                        .ok_or_else(|| RuleError::failed(format!("missing model input `{key}`")))?;
+                    to_feature(value.as_ref()).ok_or_else(|| {
+                        RuleError::failed(format!("model input `{key}` is not a number"))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let prediction = self
+            .model
+            .predict(&features)
+            .map_err(|message| ActionError::Unavailable(RuleError::failed(message)))?;
+        if prediction.len() < self.outputs.len() {
+            return Err(RuleError::failed(format!(
+                "the model predicted {} values for {} outputs",
+                prediction.len(),
+                self.outputs.len()
+            ))
+            .into());
+        }
+        for (key, value) in self.outputs.iter().zip(prediction) {
+            rule_context.set(key, value);
+        }
+        Ok(())
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the model
+    /// before the original callback, and returns the rule.
+    pub fn wrap<R>(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let model = self.clone();
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            if let Err(error) = model.try_predict(rule_context.clone()) {
+                error.record(rule_context);
+                return;
+            }
+            let mut original = original.borrow_mut();
+            original.set_rule_context(rule_context);
+            original.run_execute();
+        })
+    }
+}
+
+impl fmt::Debug for ModelRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelRule")
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .finish_non_exhaustive()
+    }
+}
+
+fn to_feature(value: &dyn Any) -> Option<f32> {
+    let value = Primitive::from_any(value)?;
+    if let Some(int) = value.as_int() {
+        return Some(int as f32);
+    }
+    if let Some(float) = value.as_float() {
+        return Some(float as f32);
+    }
+    match value {
+        Primitive::Bool(value) => Some(value as u8 as f32),
+        _ => None,
+    }
+}
+
+/// An ONNX model run with ONNX Runtime.
+///
+/// The features are fed to the first input of the model as a `1 x n` tensor
+/// of `f32`, and the prediction is read from its first output, flattened.
+/// ONNX Runtime is loaded dynamically: the shared library must be installed,
+/// and found through the `ORT_DYLIB_PATH` environment variable or the library
+/// search path.
+#[cfg(feature = "onnx")]
+pub struct OnnxModel {
+    session: std::cell::RefCell<ort::session::Session>,
+}
+
+#[cfg(feature = "onnx")]
+impl OnnxModel {
+    /// Loads a model from an `.onnx` file.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> ort::Result<Self> {
+        let session = ort::session::Session::builder()?.commit_from_file(path)?;
+        Ok(Self::from_session(session))
+    }
+
+    /// Wraps a session configured with `Session::builder`.
+    pub fn from_session(session: ort::session::Session) -> Self {
+        OnnxModel {
+            session: std::cell::RefCell::new(session),
+        }
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl Model for OnnxModel {
+    fn predict(&self, features: &[f32]) -> Result<Vec<f32>, String> {
+        let run = || -> ort::Result<Vec<f32>> {
+            let input = ort::value::Tensor::from_array((
+                vec![1i64, features.len() as i64],
+                features.to_vec(),
+            ))?;
+            let mut session = self.session.borrow_mut();
+            let outputs = session.run(ort::inputs![input])?;
+            let (_, prediction) = outputs[0].try_extract_tensor::<f32>()?;
+            Ok(prediction.to_vec())
+        };
+        run().map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl fmt::Debug for OnnxModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnnxModel").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use crate::engine::Engine;
+
+use super::{sync_wrap, Metadata, MetadataAccess, SharedRuleContext, SyncWrapper};
+
+/// Represents a parallel rule in the rule evaluation system.
+///
+/// A `ParallelRule` consists of a context, a list of child rules, and several
+/// thread-safe function wrappers for evaluation and execution phases. Sibling
+/// rules are fired concurrently by the `ParallelRuleRunner`, see its documentation
+/// for how writes to the shared context are merged.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let mut rule = ParallelRule::new();
+/// let rule2 = ParallelRule::new();
+///
+/// rule.on_eval(|_| {
+///     println!("Eval");
+///     true
+/// })
+/// .on_execute(|this| {
+///     this.get_rule_context().set("executed", true);
+/// })
+/// .add_child(rule2);
+///
+/// Engine::parallel_runner().run(SharedRuleContext::new(), vec![rule]);
+/// ```
+///
+#[derive(Clone)]
+pub struct ParallelRule {
+    metadata: Metadata,
+    rule_context: Option<SharedRuleContext>,
+    children: Vec<SyncWrapper<ParallelRule>>,
+    eval: Arc<dyn Fn(&mut Self) -> bool + Send + Sync>,
+    pre_execute: Arc<dyn Fn(&mut Self) + Send + Sync>,
+    execute: Arc<dyn Fn(&mut Self) + Send + Sync>,
+    post_execute: Arc<dyn Fn(&mut Self) + Send + Sync>,
+}
+
+impl ParallelRule {
+    pub fn new() -> SyncWrapper<Self> {
+        sync_wrap(ParallelRule {
+            metadata: Metadata::default(),
+            rule_context: None,
+            children: Vec::new(),
+            eval: Arc::new(|_: &mut Self| true),
+            pre_execute: Arc::new(|_: &mut Self| ()),
+            execute: Arc::new(|_: &mut Self| ()),
+            post_execute: Arc::new(|_: &mut Self| ()),
+        })
+    }
+
+    pub fn on_eval(&mut self, eval: impl Fn(&mut Self) -> bool + Send + Sync + 'static) {
+        self.eval = Arc::new(eval);
+    }
+
+    pub fn on_pre_execute(&mut self, pre_execute: impl Fn(&mut Self) + Send + Sync + 'static) {
+        self.pre_execute = Arc::new(pre_execute);
+    }
+
+    pub fn on_execute(&mut self, execute: impl Fn(&mut Self) + Send + Sync + 'static) {
+        self.execute = Arc::new(execute);
+    }
+
+    pub fn on_post_execute(&mut self, post_execute: impl Fn(&mut Self) + Send + Sync + 'static) {
+        self.post_execute = Arc::new(post_execute);
+    }
+
+    pub(crate) fn fire(&mut self) -> bool {
+        if self.run_eval() {
+            self.run_execute_phases();
+            Engine::parallel_runner().run(self.get_rule_context(), self.get_children());
+        }
+        true
+    }
+
+    /// False without calling the evaluation callback when the rule is
+    /// disabled, see `RuleHandle`.
+    pub(crate) fn run_eval(&self) -> bool {
+        self.metadata.is_enabled() && (self.eval)(&mut self.clone())
+    }
+
+    /// Runs the pre-execute, execute and post-execute callbacks, without the
+    /// children.
+    pub(crate) fn run_execute_phases(&mut self) {
+        (self.pre_execute)(&mut self.clone());
+        (self.execute)(&mut self.clone());
+        (self.post_execute)(&mut self.clone());
+    }
+
+    pub(crate) fn set_rule_context(&mut self, rule_context: SharedRuleContext) {
+        self.rule_context = Some(rule_context);
+    }
+
+    pub fn get_rule_context(&self) -> SharedRuleContext {
+        self.rule_context.clone().unwrap()
+    }
+
+    pub fn get_children(&self) -> Vec<SyncWrapper<ParallelRule>> {
+        self.children.clone()
+    }
+
+    pub fn add_child(&mut self, rule: SyncWrapper<ParallelRule>) {
+        self.children.push(rule);
+    }
+
+    pub fn add_children(&mut self, rules: Vec<SyncWrapper<ParallelRule>>) {
+        self.children.extend(rules);
+    }
+}
+
+/// Thread-safe counterpart of `RuleCallback`, implemented for `SyncWrapper<ParallelRule>`.
+///
+/// Callbacks must be `Send + Sync` because sibling rules are fired from rayon's thread pool.
+pub trait SyncRuleCallback {
+    type RuleType;
+    fn on_eval(
+        &mut self,
+        eval: impl Fn(&mut Self::RuleType) -> bool + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType>;
+    fn on_pre_execute(
+        &mut self,
+        pre_execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType>;
+    fn on_execute(
+        &mut self,
+        execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType>;
+    fn on_post_execute(
+        &mut self,
+        post_execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType>;
+}
+
+/// Thread-safe counterpart of `RuleMetadata`, implemented for `SyncWrapper<ParallelRule>`.
+pub trait SyncRuleMetadata {
+    type RuleType;
+    fn with_id(&mut self, id: &str) -> SyncWrapper<Self::RuleType>;
+    fn with_name(&mut self, name: &str) -> SyncWrapper<Self::RuleType>;
+    fn with_description(&mut self, description: &str) -> SyncWrapper<Self::RuleType>;
+    fn with_owner(&mut self, owner: &str) -> SyncWrapper<Self::RuleType>;
+    fn with_team(&mut self, team: &str) -> SyncWrapper<Self::RuleType>;
+    fn with_reads(&mut self, keys: &[&str]) -> SyncWrapper<Self::RuleType>;
+    fn with_writes(&mut self, keys: &[&str]) -> SyncWrapper<Self::RuleType>;
+}
+
+/// Thread-safe counterpart of `RuleChildren`, implemented for `SyncWrapper<ParallelRule>`.
+pub trait SyncRuleChildren {
+    type RuleType;
+    fn add_child(&mut self, rule: SyncWrapper<Self::RuleType>) -> SyncWrapper<Self::RuleType>;
+    fn add_children(
+        &mut self,
+        rules: Vec<SyncWrapper<Self::RuleType>>,
+    ) -> SyncWrapper<Self::RuleType>;
+}
+
+impl MetadataAccess for ParallelRule {
+    fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn get_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
+impl SyncRuleCallback for SyncWrapper<ParallelRule> {
+    type RuleType = ParallelRule;
+
+    /// Sets the evaluation function for the rule.
+    fn on_eval(
+        &mut self,
+        eval: impl Fn(&mut Self::RuleType) -> bool + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().on_eval(eval);
+        self.clone()
+    }
+
+    /// Sets the pre-execution function for the rule.
+    fn on_pre_execute(
+        &mut self,
+        pre_execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().on_pre_execute(pre_execute);
+        self.clone()
+    }
+
+    /// Sets the execution function for the rule.
+    fn on_execute(
+        &mut self,
+        execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().on_execute(execute);
+        self.clone()
+    }
+
+    /// Sets the post-execution function for the rule.
+    fn on_post_execute(
+        &mut self,
+        post_execute: impl Fn(&mut Self::RuleType) + Send + Sync + 'static,
+    ) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().on_post_execute(post_execute);
+        self.clone()
+    }
+}
+
+impl SyncRuleMetadata for SyncWrapper<ParallelRule> {
+    type RuleType = ParallelRule;
+
+    fn with_id(&mut self, id: &str) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_id(id);
+        self.clone()
+    }
+
+    fn with_name(&mut self, name: &str) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_name(name);
+        self.clone()
+    }
+
+    fn with_description(&mut self, description: &str) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_description(description);
+        self.clone()
+    }
+
+    fn with_owner(&mut self, owner: &str) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_owner(owner);
+        self.clone()
+    }
+
+    fn with_team(&mut self, team: &str) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_team(team);
+        self.clone()
+    }
+
+    fn with_reads(&mut self, keys: &[&str]) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_reads(keys);
+        self.clone()
+    }
+
+    fn with_writes(&mut self, keys: &[&str]) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().set_writes(keys);
+        self.clone()
+    }
+}
+
+impl SyncRuleChildren for SyncWrapper<ParallelRule> {
+    type RuleType = ParallelRule;
+
+    /// Adds a single child rule to the current instance and returns a clone of the updated instance.
+    fn add_child(&mut self, rule: SyncWrapper<Self::RuleType>) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().add_child(rule);
+        self.clone()
+    }
+
+    /// Adds multiple child rules to the current instance and returns a clone of the updated instance.
+    fn add_children(
+        &mut self,
+        rules: Vec<SyncWrapper<Self::RuleType>>,
+    ) -> SyncWrapper<Self::RuleType> {
+        self.lock().unwrap().add_children(rules);
+        self.clone()
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, VecDeque},
+    rc::Rc,
+};
+
+use super::{wrap, Rule, RuleCallback, RuleContextWrapper, RuleFailure, Wrapper};
+
+#[derive(Debug, Default)]
+struct QuarantineState {
+    outcomes: HashMap<String, VecDeque<bool>>,
+    quarantined: BTreeSet<String>,
+}
+
+/// Skips rules that fail too often until they are released.
+///
+/// Every rule decorated with `wrap` records, each time it is fired, whether
+/// one of its own callbacks failed; failures of its children are not counted.
+/// Once a rule has been fired at least `min_samples` times and more than
+/// `max_error_rate` of its last `window` fires failed, it is quarantined: its
+/// evaluation returns false without calling the original callback, and its
+/// trace entries have the `"quarantined"` skip reason. It stays quarantined
+/// until `release` is called.
+///
+/// Rules are tracked by id, or by name when they have no id. Clones share the
+/// same state, so one quarantine can decorate many rules and be inspected
+/// from elsewhere.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let quarantine = Quarantine::new(4, 0.5);
+///
+/// let rule = quarantine.wrap(
+///     AllRule::new()
+///         .with_id("pricing")
+///         .on_execute(|this| this.get_rule_context().fail(RuleError::failed("bad deploy"))),
+/// );
+///
+/// for _ in 0..4 {
+///     let _ = Engine::all_runner().try_run(RuleContext::new(), vec![rule.clone()]);
+/// }
+/// assert_eq!(quarantine.get_quarantined(), vec!["pricing"]);
+///
+/// // Quarantined rules are skipped instead of failing.
+/// assert!(Engine::all_runner().try_run(RuleContext::new(), vec![rule]).is_ok());
+///
+/// quarantine.release("pricing");
+/// assert!(!quarantine.is_quarantined("pricing"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    window: usize,
+    max_error_rate: f64,
+    min_samples: usize,
+    state: Rc<RefCell<QuarantineState>>,
+}
+
+impl Quarantine {
+    /// Creates a quarantine looking at the last `window` fires of each rule.
+    /// By default a rule must have been fired `window` times to be quarantined.
+    pub fn new(window: usize, max_error_rate: f64) -> Self {
+        let window = window.max(1);
+        Quarantine {
+            window,
+            max_error_rate,
+            min_samples: window,
+            state: Default::default(),
+        }
+    }
+
+    /// Sets how many fires are needed before a rule can be quarantined,
+    /// capped at the window size.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.clamp(1, self.window);
+        self
+    }
+
+    pub fn is_quarantined(&self, key: &str) -> bool {
+        self.state.borrow().quarantined.contains(key)
+    }
+
+    /// The ids or names of the quarantined rules, sorted.
+    pub fn get_quarantined(&self) -> Vec<String> {
+        self.state.borrow().quarantined.iter().cloned().collect()
+    }
+
+    /// The share of failed fires in the current window of a rule.
+    pub fn get_error_rate(&self, key: &str) -> Option<f64> {
+        let state = self.state.borrow();
+        let outcomes = state
+            .outcomes
+            .get(key)
+            .filter(|outcomes| !outcomes.is_empty())?;
+        let failures = outcomes.iter().filter(|failed| **failed).count();
+        Some(failures as f64 / outcomes.len() as f64)
+    }
+
+    /// Re-enables a quarantined rule, starting a new window for it. Returns
+    /// whether the rule was quarantined.
+    pub fn release(&self, key: &str) -> bool {
+        let mut state = self.state.borrow_mut();
+        state.outcomes.remove(key);
+        state.quarantined.remove(key)
+    }
+
+    /// Replaces the callbacks of the rule with ones that record its failures
+    /// and skip it while it is quarantined, and returns the rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule has neither an id nor a name.
+    pub fn wrap<R>(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let key: Rc<str> = {
+            let original = original.borrow();
+            original
+                .get_id()
+                .or(original.get_name())
+                .expect("quarantined rules need an id or a name")
+                .into()
+        };
+
+        rule.on_eval({
+            let (quarantine, original, key) = (self.clone(), original.clone(), key.clone());
+            move |ctx| {
+                let rule_context = ctx.get_rule_context();
+                if quarantine.is_quarantined(&key) {
+                    rule_context.borrow_mut().trace_skip("quarantined");
+                    return false;
+                }
+                let (result, failed) = fire(&original, rule_context, |rule| rule.run_eval());
+                // A rule that doesn't execute completes its fire here.
+                if failed || !result {
+                    quarantine.record(&key, failed);
+                }
+                result
+            }
+        })
+        .on_pre_execute({
+            let (quarantine, original, key) = (self.clone(), original.clone(), key.clone());
+            move |this| {
+                if fire(&original, this.get_rule_context(), R::run_pre_execute).1 {
+                    quarantine.record(&key, true);
+                }
+            }
+        })
+        .on_execute({
+            let (quarantine, original, key) = (self.clone(), original.clone(), key.clone());
+            move |this| {
+                if fire(&original, this.get_rule_context(), R::run_execute).1 {
+                    quarantine.record(&key, true);
+                }
+            }
+        })
+        .on_post_execute({
+            let quarantine = self.clone();
+            move |this| {
+                let ((), failed) = fire(&original, this.get_rule_context(), R::run_post_execute);
+                quarantine.record(&key, failed);
+            }
+        })
+    }
+
+    fn record(&self, key: &str, failed: bool) {
+        let state = &mut *self.state.borrow_mut();
+        let outcomes = state.outcomes.entry(key.to_string()).or_default();
+        outcomes.push_back(failed);
+        if outcomes.len() > self.window {
+            outcomes.pop_front();
+        }
+
+        let failures = outcomes.iter().filter(|failed| **failed).count();
+        if outcomes.len() >= self.min_samples
+            && failures as f64 / outcomes.len() as f64 > self.max_error_rate
+        {
+            outcomes.clear();
+            state.quarantined.insert(key.to_string());
+        }
+    }
+}
+
+/// Fires a callback of the original rule against the context of `this`, and
+/// tells whether the context has failed afterwards.
+fn fire<R: Rule<R>, T>(
+    original: &Wrapper<R>,
+    rule_context: RuleContextWrapper,
+    callback: impl FnOnce(&mut R) -> T,
+) -> (T, bool) {
+    let mut original = original.borrow_mut();
+    original.set_rule_context(rule_context.clone());
+    let result = callback(&mut original);
+    (result, rule_context.has_failed())
+}
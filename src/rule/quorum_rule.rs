@@ -0,0 +1,88 @@
+use super::{wrap, Rule, RuleCallback, RuleFailure, Wrapper};
+
+/// Decorates a rule so that its evaluation only passes when at least `quorum`
+/// of its signals evaluate to true, for approval-style policies such as
+/// "2 of these 3 risk signals".
+///
+/// Signals are rules whose evaluation callback is called, without executing
+/// them or firing their children, so any rule can serve as one. They are
+/// evaluated in order, and the evaluation stops as soon as the quorum is
+/// reached or can no longer be reached. A signal that records a failure makes
+/// the evaluation fail. The original evaluation callback of the rule is
+/// called first and must pass as well.
+///
+/// Like the other decorators, setting the evaluation callback of the rule
+/// afterwards replaces the quorum.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let signal = |key: &'static str| AllRule::new().on_eval(move |ctx| ctx.get::<bool>(key).is_some());
+///
+/// let rule = QuorumRule::new(2, vec![signal("new_device"), signal("foreign_ip"), signal("large_amount")])
+///     .wrap(AllRule::new().on_execute(|this| this.get_rule_context().set("review", true)));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("new_device", true);
+/// rule_context.set("large_amount", true);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert!(*rule_context.get::<bool>("review").unwrap());
+/// ```
+pub struct QuorumRule<R> {
+    quorum: usize,
+    signals: Vec<Wrapper<R>>,
+}
+
+impl<R: Rule<R> + Clone + 'static> QuorumRule<R> {
+    /// Requires `quorum` of the signals to evaluate to true. A quorum of zero
+    /// always passes, and one larger than the number of signals never does.
+    pub fn new(quorum: usize, signals: Vec<Wrapper<R>>) -> Self {
+        QuorumRule { quorum, signals }
+    }
+
+    pub fn get_quorum(&self) -> usize {
+        self.quorum
+    }
+
+    pub fn get_signals(&self) -> &[Wrapper<R>] {
+        &self.signals
+    }
+
+    /// Replaces the evaluation callback of the rule with one that checks the
+    /// original callback, then the quorum, and returns the rule.
+    pub fn wrap(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_eval(move |ctx| {
+            let rule_context = ctx.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                if !original.run_eval() || rule_context.has_failed() {
+                    return false;
+                }
+            }
+            let mut passed = 0;
+            for (index, signal) in self.signals.iter().enumerate() {
+                if passed >= self.quorum || passed + self.signals.len() - index < self.quorum {
+                    break;
+                }
+                let mut signal = signal.borrow_mut();
+                signal.set_rule_context(rule_context.clone());
+                if signal.run_eval() {
+                    passed += 1;
+                }
+                if rule_context.has_failed() {
+                    return false;
+                }
+            }
+            passed >= self.quorum
+        })
+    }
+}
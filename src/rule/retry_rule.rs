@@ -0,0 +1,142 @@
+use std::{thread, time::Duration};
+
+use super::{wrap, Rule, RuleCallback, RuleContextWrapper, RuleFailure, Wrapper};
+
+/// How long to wait before retrying a failed callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backoff {
+    /// Retry right away.
+    #[default]
+    None,
+    /// Wait the same delay before every retry.
+    Fixed(Duration),
+    /// Wait `initial` before the first retry and double the delay for every
+    /// following one, up to `max`.
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// The delay before the given retry, counting from `1`.
+    pub fn get_delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => initial
+                .checked_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+                .map_or(max, |delay| delay.min(max)),
+        }
+    }
+}
+
+/// Decorates a rule so that its callbacks are retried when they fail.
+///
+/// A callback fails when it records an error with `RuleFailure::fail`. The
+/// failed callback, and only that one, is fired again up to `max_attempts`
+/// times in total, waiting according to the backoff between attempts. The
+/// errors of the attempts that are retried are discarded; if the last attempt
+/// fails too, its error is left in the context like for any other failure.
+///
+/// Children are not retried as part of their parent, they can be decorated
+/// themselves. Callbacks set on the rule after it was decorated replace the
+/// retrying ones.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new().on_execute(|this| {
+///     let calls = this.get_rule_context().get::<u32>("calls").map_or(1, |calls| *calls + 1);
+///     this.get_rule_context().set("calls", calls);
+///     if calls < 3 {
+///         this.get_rule_context().fail(RuleError::failed("service unavailable"));
+///     }
+/// });
+///
+/// let rule = RetryRule::new(3)
+///     .with_backoff(Backoff::Fixed(Duration::from_millis(1)))
+///     .wrap(rule);
+///
+/// let rule_context = RuleContext::new();
+///
+/// assert!(Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).is_ok());
+/// assert_eq!(*rule_context.get::<u32>("calls").unwrap(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryRule {
+    max_attempts: u32,
+    backoff: Backoff,
+}
+
+impl RetryRule {
+    /// Creates a decorator firing callbacks at most `max_attempts` times,
+    /// with no delay between attempts. A value of `0` is treated as `1`.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryRule {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::None,
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn get_backoff(&self) -> Backoff {
+        self.backoff
+    }
+
+    /// Replaces the callbacks of the rule with ones that retry the original
+    /// callbacks, and returns the rule.
+    pub fn wrap<R>(&self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+        let retry = *self;
+
+        rule.on_eval({
+            let original = original.clone();
+            move |ctx| retry.fire(&original, ctx.get_rule_context(), |rule| rule.run_eval())
+        })
+        .on_pre_execute({
+            let original = original.clone();
+            move |this| retry.fire(&original, this.get_rule_context(), R::run_pre_execute)
+        })
+        .on_execute({
+            let original = original.clone();
+            move |this| retry.fire(&original, this.get_rule_context(), R::run_execute)
+        })
+        .on_post_execute(move |this| {
+            retry.fire(&original, this.get_rule_context(), R::run_post_execute)
+        })
+    }
+
+    fn fire<R: Rule<R>, T>(
+        &self,
+        original: &Wrapper<R>,
+        rule_context: RuleContextWrapper,
+        mut callback: impl FnMut(&mut R) -> T,
+    ) -> T {
+        let mut original = original.borrow_mut();
+        original.set_rule_context(rule_context.clone());
+
+        let mut attempt = 1;
+        loop {
+            let result = callback(&mut original);
+            if attempt >= self.max_attempts || !rule_context.has_failed() {
+                return result;
+            }
+            rule_context.clone().take_error();
+            thread::sleep(self.backoff.get_delay(attempt));
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use super::RuleContext;
+
+/// A small seedable random number generator (SplitMix64), for rules that
+/// sample, jitter or throttle.
+///
+/// Every `RuleContext` holds one, seeded randomly, that conditions draw from
+/// with `RuleContext::random`, `Condition::with_probability` or `random()` in
+/// expressions. `RuleRunner::run_with_report` records its seed at the start
+/// of the run in `RunReport::get_seed`, so that setting the same seed again
+/// replays the run with the same draws.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let sampled = || AllRule::new()
+///     .on_condition(Condition::with_probability(0.5))
+///     .on_execute(|this| this.get_rule_context().set("sampled", true));
+///
+/// let rule_context = RuleContext::new();
+/// let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![sampled()]);
+///
+/// let replayed = RuleContext::new();
+/// replayed.borrow_mut().set_seed(report.get_seed());
+/// Engine::all_runner().run(replayed.clone(), vec![sampled()]);
+///
+/// assert_eq!(rule_context.get::<bool>("sampled"), replayed.get::<bool>("sampled"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// A generator with an unpredictable seed.
+    pub fn from_entropy() -> Self {
+        Rng::new(RandomState::new().build_hasher().finish())
+    }
+
+    /// The seed that makes a new generator draw the same numbers as this one
+    /// will from now on.
+    pub fn get_seed(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A float uniformly drawn from `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// True with the given probability: never at 0 or less, always at 1 or
+    /// more.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::from_entropy()
+    }
+}
+
+impl RuleContext {
+    /// Draws a float from `[0, 1)` with the random generator of the context.
+    pub fn random(&self) -> f64 {
+        let mut rng = self.rng.get();
+        let value = rng.next_f64();
+        self.rng.set(rng);
+        value
+    }
+
+    /// Draws true with the given probability.
+    pub fn chance(&self, probability: f64) -> bool {
+        self.random() < probability
+    }
+
+    /// The seed replaying the draws to come, see `Rng`.
+    pub fn get_seed(&self) -> u64 {
+        self.rng.get().get_seed()
+    }
+
+    /// Reseeds the random generator of the context.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng.set(Rng::new(seed));
+    }
+}
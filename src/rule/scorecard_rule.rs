@@ -0,0 +1,131 @@
+use super::{wrap, Condition, GetSet, Rule, RuleCallback, RuleContext, RuleFailure, Wrapper};
+
+type Setter = Box<dyn Fn(&mut RuleContext, &'static str)>;
+
+/// Decorates a rule so that, when executed, it scores the context the way
+/// credit and risk scorecards do: each criterion that holds contributes its
+/// points, the sum is written to a context key as an `i64`, and the band the
+/// score falls in, if any, writes its outcome to another key.
+///
+/// Points may be negative. Bands are given by the lowest score they cover,
+/// and the score falls in the band with the highest minimum it reaches, so
+/// `band(700, "A").band(600, "B")` grades 650 as `"B"` and 550 as nothing.
+///
+/// The score is computed after the execute callback of the decorated rule
+/// and before its post-execute callback and children, which can read it.
+/// Like the other decorators, setting the execute callback of the rule
+/// afterwards replaces the scorecard.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let above = |key: &'static str, limit: u32| Condition::new(move |ctx| ctx.get::<u32>(key).is_some_and(|value| *value > limit));
+///
+/// let rule = ScorecardRule::new("score")
+///     .criterion(above("years_at_address", 3), 30)
+///     .criterion(above("income", 50_000), 50)
+///     .criterion(above("missed_payments", 0), -40)
+///     .with_band_key("grade")
+///     .band(70, "A")
+///     .band(30, "B")
+///     .band(i64::MIN, "C")
+///     .wrap(AllRule::new());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("years_at_address", 5u32);
+/// rule_context.set("income", 80_000u32);
+/// rule_context.set("missed_payments", 1u32);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<i64>("score").unwrap(), 40);
+/// assert_eq!(*rule_context.get::<&str>("grade").unwrap(), "B");
+/// ```
+pub struct ScorecardRule {
+    key: &'static str,
+    criteria: Vec<(Condition, i64)>,
+    band_key: Option<&'static str>,
+    bands: Vec<(i64, Setter)>,
+}
+
+impl ScorecardRule {
+    /// Creates a scorecard writing its score to `key`, with no criteria.
+    pub fn new(key: &'static str) -> Self {
+        ScorecardRule {
+            key,
+            criteria: Vec::new(),
+            band_key: None,
+            bands: Vec::new(),
+        }
+    }
+
+    /// Adds `points` to the score when the condition holds.
+    pub fn criterion(mut self, condition: Condition, points: i64) -> Self {
+        self.criteria.push((condition, points));
+        self
+    }
+
+    /// Sets the key the outcome of the band of the score is written to.
+    /// Without one, bands are ignored.
+    pub fn with_band_key(mut self, key: &'static str) -> Self {
+        self.band_key = Some(key);
+        self
+    }
+
+    /// Adds a band covering the scores from `min_score` up to the next band.
+    pub fn band<T: Clone + 'static>(mut self, min_score: i64, outcome: T) -> Self {
+        let set: Setter = Box::new(move |rule_context, key| rule_context.set(key, outcome.clone()));
+        let index = self.bands.partition_point(|(min, _)| *min >= min_score);
+        self.bands.insert(index, (min_score, set));
+        self
+    }
+
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn get_band_key(&self) -> Option<&'static str> {
+        self.band_key
+    }
+
+    /// The score of the context: the sum of the points of the criteria that
+    /// hold.
+    pub fn score(&self, rule_context: &RuleContext) -> i64 {
+        self.criteria
+            .iter()
+            .filter(|(condition, _)| condition.eval(rule_context))
+            .map(|(_, points)| points)
+            .sum()
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then scores the context, and returns the rule.
+    pub fn wrap<R>(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        R: Rule<R> + Clone + 'static,
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            let score = self.score(&rule_context.borrow());
+            rule_context.set(self.key, score);
+            let Some(band_key) = self.band_key else {
+                return;
+            };
+            if let Some((_, set)) = self.bands.iter().find(|(min, _)| score >= *min) {
+                set(&mut rule_context.borrow_mut(), band_key);
+            }
+        })
+    }
+}
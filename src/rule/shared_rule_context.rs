@@ -0,0 +1,254 @@
+use std::{any::Any, collections::HashMap, error::Error, fmt, sync::Arc};
+
+use crate::sync::RwLock;
+
+use super::{
+    value_types, AsContextKey, Budget, ContextKey, DegradationPolicy, ErrorPolicy, RuleContext,
+    RuleContextMap, RuleContextWrapper, RunMode,
+};
+
+pub(crate) type SharedRuleContextMap = HashMap<&'static str, Arc<dyn Any + Send + Sync>>;
+
+/// SharedRuleContext is the thread-safe counterpart of `RuleContext`.
+/// Cloning it is cheap, and every clone refers to the same underlying map.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule_context = SharedRuleContext::new();
+/// rule_context.set("test", true);
+/// let test = rule_context.get::<bool>("test");
+/// ```
+///
+/// A `RuleContext` is converted to a shared context and back with
+/// `from_context` and `to_context`, which keep its key aliases and the
+/// settings of its runs. Values are converted when their type is registered
+/// with `SharedRuleContext::register`, which the primitive number types,
+/// `bool`, `char`, `String` and `&'static str`, as well as `Decimal` with the
+/// `decimal` feature and `serde_json::Value` with the `serde` feature, are
+/// by default. Registering a type registers the lists of it too, as written
+/// by `ContextList::push_to_list`:
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// #[derive(Clone)]
+/// struct Order {
+///     total: u32,
+/// }
+/// SharedRuleContext::register::<Order>();
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("order", Order { total: 42 });
+/// rule_context.push_to_list("tags", "vip");
+///
+/// let shared = SharedRuleContext::from_context(&rule_context.borrow()).unwrap();
+/// std::thread::spawn({
+///     let shared = shared.clone();
+///     move || shared.set("large", shared.get::<Order>("order").unwrap().total >= 40)
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let rule_context = shared.to_context().unwrap();
+/// assert!(*rule_context.get::<bool>("large").unwrap());
+/// assert_eq!(rule_context.get_list::<&str>("tags").unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedRuleContext {
+    context_map: Arc<RwLock<SharedRuleContextMap>>,
+    parent: Option<Box<SharedRuleContext>>,
+    settings: Option<Arc<ContextSettings>>,
+}
+
+impl SharedRuleContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set<T: Send + Sync + 'static>(&self, k: impl AsContextKey<T>, v: T) {
+        self.context_map
+            .write()
+            .unwrap()
+            .insert(k.key_name(), Arc::new(v));
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self, key: impl AsContextKey<T>) -> Option<Arc<T>> {
+        let key = key.key_name();
+        let val = self.context_map.read().unwrap().get(key).cloned();
+        match val {
+            Some(v) => v.downcast::<T>().ok(),
+            None => self.parent.as_ref().and_then(|parent| parent.get::<T>(key)),
+        }
+    }
+
+    pub fn set_key<T: Send + Sync + 'static>(&self, key: ContextKey<T>, v: T) {
+        self.set(key, v);
+    }
+
+    pub fn get_key<T: Send + Sync + 'static>(&self, key: ContextKey<T>) -> Option<Arc<T>> {
+        self.get(key)
+    }
+
+    /// Registers a type whose values, and lists of values, can be converted
+    /// by `from_context` and `to_context`, for every context. Registering a
+    /// type again does nothing.
+    pub fn register<T: Clone + Send + Sync + 'static>() {
+        value_types::register_shared::<T>();
+    }
+
+    /// A shared context holding the values of `rule_context`, including
+    /// those of the layers backing it, see `LayeredContext`, with its key
+    /// aliases, mode, policies, budget and random seed. Fails on the first
+    /// key, in sorted order, holding a value of a type not registered, see
+    /// `SharedRuleContext::register`.
+    pub fn from_context(rule_context: &RuleContext) -> Result<Self, SharedContextError> {
+        let mut values = RuleContextMap::new();
+        flatten_layers(rule_context, &mut values);
+        let context_map = convert(values, |value| value_types::to_shared(value.as_ref()))?;
+        Ok(SharedRuleContext {
+            context_map: Arc::new(RwLock::new(context_map)),
+            parent: None,
+            settings: Some(Arc::new(ContextSettings::of(rule_context))),
+        })
+    }
+
+    /// A `RuleContext` holding the values of this context and of those it
+    /// was staged from, with the key aliases and settings of the context it
+    /// was made from, if any. The filter of a run and the layers of the
+    /// context, whose values are taken, are not kept. Fails like
+    /// `from_context`.
+    pub fn to_context(&self) -> Result<RuleContextWrapper, SharedContextError> {
+        let mut values = SharedRuleContextMap::new();
+        self.flatten_parents(&mut values);
+        let context_map = convert(values, |value| value_types::to_local(value.as_ref()))?;
+        let rule_context = RuleContext::from_context_map(context_map);
+        if let Some(settings) = &self.settings {
+            settings.apply(&mut rule_context.borrow_mut());
+        }
+        Ok(rule_context)
+    }
+
+    fn flatten_parents(&self, values: &mut SharedRuleContextMap) {
+        if let Some(parent) = &self.parent {
+            parent.flatten_parents(values);
+        }
+        values.extend(
+            self.context_map
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(key, value)| (*key, value.clone())),
+        );
+    }
+
+    /// Creates an empty layer on top of this context. Reads fall through to
+    /// this context, writes stay in the new layer until it is committed.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn stage(&self) -> Self {
+        SharedRuleContext {
+            context_map: Default::default(),
+            parent: Some(Box::new(self.clone())),
+            settings: self.settings.clone(),
+        }
+    }
+
+    /// Moves every value written to this layer into the context it was staged from.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn commit(self) {
+        if let Some(parent) = self.parent {
+            let staged = std::mem::take(&mut *self.context_map.write().unwrap());
+            parent.context_map.write().unwrap().extend(staged);
+        }
+    }
+}
+
+/// Why a context couldn't be converted, see `SharedRuleContext::from_context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SharedContextError {
+    /// The key holds a value of a type not registered, see
+    /// `SharedRuleContext::register`.
+    UnsupportedValue(&'static str),
+}
+
+impl fmt::Display for SharedContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedContextError::UnsupportedValue(key) => {
+                write!(f, "the value of key `{key}` can't be converted")
+            }
+        }
+    }
+}
+
+impl Error for SharedContextError {}
+
+/// Collects the values of the context and of its layers, the values of upper
+/// layers replacing those of the lower ones.
+fn flatten_layers(rule_context: &RuleContext, values: &mut RuleContextMap) {
+    for layer in rule_context.get_all_layers().iter().rev() {
+        extend_values(&layer.borrow(), values);
+    }
+    extend_values(rule_context, values);
+}
+
+fn extend_values(rule_context: &RuleContext, values: &mut RuleContextMap) {
+    values.extend(
+        rule_context
+            .get_context_map()
+            .iter()
+            .map(|(key, value)| (*key, value.clone())),
+    );
+}
+
+/// The settings of a `RuleContext` kept by a shared context converted from
+/// it, see `SharedRuleContext::from_context`.
+#[derive(Debug)]
+struct ContextSettings {
+    mode: RunMode,
+    error_policy: ErrorPolicy,
+    degradation_policy: DegradationPolicy,
+    budget: Budget,
+    seed: u64,
+    key_aliases: HashMap<&'static str, &'static str>,
+}
+
+impl ContextSettings {
+    fn of(rule_context: &RuleContext) -> Self {
+        ContextSettings {
+            mode: rule_context.mode,
+            error_policy: rule_context.error_policy,
+            degradation_policy: rule_context.degradation_policy,
+            budget: rule_context.get_execution_budget(),
+            seed: rule_context.get_seed(),
+            key_aliases: rule_context.key_aliases.clone(),
+        }
+    }
+
+    fn apply(&self, rule_context: &mut RuleContext) {
+        rule_context.mode = self.mode;
+        rule_context.error_policy = self.error_policy;
+        rule_context.degradation_policy = self.degradation_policy;
+        rule_context.set_execution_budget(self.budget);
+        rule_context.set_seed(self.seed);
+        rule_context.key_aliases = self.key_aliases.clone();
+    }
+}
+
+/// Converts the values, failing on the first key, in sorted order, whose
+/// type isn't registered.
+fn convert<V, W>(
+    values: HashMap<&'static str, V>,
+    convert_value: impl Fn(&V) -> Option<W>,
+) -> Result<HashMap<&'static str, W>, SharedContextError> {
+    let mut keys: Vec<_> = values.keys().copied().collect();
+    keys.sort_unstable();
+    keys.into_iter()
+        .map(|key| {
+            let value =
+                convert_value(&values[key]).ok_or(SharedContextError::UnsupportedValue(key))?;
+            Ok((key, value))
+        })
+        .collect()
+}
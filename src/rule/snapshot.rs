@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use super::{value_types, RuleContext, RuleContextMap, RuleVerdict};
+
+/// The values of a `RuleContext` at a point in time, and the verdicts pushed
+/// so far, see `RuleVerdicts`.
+///
+/// Taking a snapshot is cheap: values are shared with the context, not
+/// copied. Since `set` replaces values instead of mutating them, restoring a
+/// snapshot brings back the values it was taken with, except for changes made
+/// through interior mutability, such as a `RefCell` stored in the context.
+#[derive(Debug, Clone)]
+pub struct ContextSnapshot {
+    pub(crate) context_map: RuleContextMap,
+    verdicts: Vec<RuleVerdict>,
+}
+
+impl ContextSnapshot {
+    /// The keys the context held, sorted.
+    pub fn get_keys(&self) -> Vec<&'static str> {
+        let mut keys: Vec<_> = self.context_map.keys().copied().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn get<T: 'static>(&self, key: &'static str) -> Option<Rc<T>> {
+        self.context_map.get(key)?.clone().downcast::<T>().ok()
+    }
+
+    /// The values of `other` that were added or replaced since this snapshot.
+    pub(crate) fn get_changes(&self, other: &ContextSnapshot) -> ContextSnapshot {
+        ContextSnapshot {
+            context_map: other
+                .context_map
+                .iter()
+                .filter(|(key, value)| {
+                    self.context_map
+                        .get(*key)
+                        .is_none_or(|before| !Rc::ptr_eq(before, value))
+                })
+                .map(|(key, value)| (*key, value.clone()))
+                .collect(),
+            verdicts: Vec::new(),
+        }
+    }
+
+    /// The keys added, removed or holding a different value in `other`.
+    /// Values of comparable types are compared by value, others by identity,
+    /// see `RuleContext::register_comparable`. Sorted.
+    pub(crate) fn get_changed_keys(&self, other: &ContextSnapshot) -> Vec<&'static str> {
+        let mut keys: Vec<_> = self
+            .context_map
+            .keys()
+            .chain(other.context_map.keys())
+            .copied()
+            .filter(
+                |key| match (self.context_map.get(key), other.context_map.get(key)) {
+                    (Some(before), Some(after)) => !value_types::values_equal(before, after),
+                    _ => true,
+                },
+            )
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+}
+
+impl RuleContext {
+    /// Registers a type, and the lists of it, whose values are compared by
+    /// value when looking for the keys a pass changed, for every context, see
+    /// `Engine::execute_to_fixpoint`. The values of other types are only
+    /// equal when they are the same value, so a rule setting an equal value
+    /// again changes the key. The primitive types, `Decimal` with the
+    /// `decimal` feature and `serde_json::Value` with the `serde` feature are
+    /// comparable by default.
+    pub fn register_comparable<T: PartialEq + 'static>() {
+        value_types::register_comparable::<T>();
+    }
+
+    /// Captures the current values of the context.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.set("total", 10);
+    ///
+    /// let snapshot = rule_context.borrow().snapshot();
+    /// rule_context.set("total", 20);
+    /// rule_context.set("discount", 5);
+    /// rule_context.borrow_mut().restore(snapshot);
+    ///
+    /// assert_eq!(*rule_context.get::<i32>("total").unwrap(), 10);
+    /// assert!(rule_context.get::<i32>("discount").is_none());
+    /// ```
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            context_map: self.context_map.clone(),
+            verdicts: self.verdicts.clone(),
+        }
+    }
+
+    /// Puts back the values captured by `snapshot`, dropping keys added since,
+    /// and the verdicts, dropping those pushed since. A recorded failure is
+    /// left as it is.
+    pub fn restore(&mut self, snapshot: ContextSnapshot) {
+        self.track_restore(&snapshot.context_map);
+        self.context_map = snapshot.context_map;
+        self.verdicts = snapshot.verdicts;
+    }
+}
@@ -0,0 +1,63 @@
+use std::cell::Cell;
+
+use tracing::{field, span::EnteredSpan, Level};
+
+use super::Metadata;
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The `rule` span of a fire, entered until the fire completes.
+///
+/// It carries the name and id of the rule, its depth in the rules being
+/// run, and once the fire completes, its outcome: `executed`, `skipped` when
+/// the evaluation didn't pass, or `failed`.
+pub(crate) struct FireSpan {
+    span: EnteredSpan,
+}
+
+impl FireSpan {
+    pub(crate) fn enter(metadata: &Metadata) -> Self {
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        let span = tracing::span!(
+            Level::INFO,
+            "rule",
+            name = metadata.name.as_deref(),
+            id = metadata.id.as_deref(),
+            depth,
+            outcome = field::Empty,
+        );
+        FireSpan {
+            span: span.entered(),
+        }
+    }
+
+    pub(crate) fn finish(self, eval_result: bool, failed: bool) {
+        let outcome = match (failed, eval_result) {
+            (true, _) => "failed",
+            (false, true) => "executed",
+            (false, false) => "skipped",
+        };
+        self.span.record("outcome", outcome);
+    }
+}
+
+impl Drop for FireSpan {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Enters a span for one phase of a fire: `eval`, one of the execute
+/// callbacks, or `children` for the dispatch to the children.
+pub(crate) fn enter_phase(phase: &str) -> EnteredSpan {
+    let span = match phase {
+        "eval" => tracing::debug_span!("eval"),
+        "pre_execute" => tracing::debug_span!("pre_execute"),
+        "execute" => tracing::debug_span!("execute"),
+        "post_execute" => tracing::debug_span!("post_execute"),
+        _ => tracing::debug_span!("children"),
+    };
+    span.entered()
+}
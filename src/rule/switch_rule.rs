@@ -0,0 +1,117 @@
+use std::any::Any;
+
+use super::{depth_guard::fire_rule, wrap, Rule, RuleCallback, RuleFailure, Wrapper};
+
+type Matcher = Box<dyn Fn(&dyn Any) -> bool>;
+
+/// Decorates a rule so that, when executed, it fires the branch matching the
+/// value of a context key.
+///
+/// Each case compares the value of the key with a constant, and the branch of
+/// the first matching case is fired. When no case matches, because the key is
+/// missing, holds another type or another value, the default branch is fired
+/// if there is one. Exactly one branch, or none, is fired per execution, and
+/// its evaluation, callbacks and children run as usual.
+///
+/// The branch is fired after the execute callback of the decorated rule and
+/// before its post-execute callback and children. Like the other decorators,
+/// setting the execute callback of the rule afterwards replaces the switch.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let shipping = |cost: u32| AllRule::new().on_execute(move |this| this.get_rule_context().set("shipping", cost));
+///
+/// let rule = SwitchRule::new("country")
+///     .case("BR", shipping(10))
+///     .case("AR", shipping(15))
+///     .default(shipping(30))
+///     .wrap(AllRule::new());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("country", "AR");
+/// Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+/// assert_eq!(*rule_context.get::<u32>("shipping").unwrap(), 15);
+///
+/// rule_context.set("country", "US");
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+/// assert_eq!(*rule_context.get::<u32>("shipping").unwrap(), 30);
+/// ```
+pub struct SwitchRule<R> {
+    key: &'static str,
+    cases: Vec<(Matcher, Wrapper<R>)>,
+    default: Option<Wrapper<R>>,
+}
+
+impl<R: Rule<R> + Clone + 'static> SwitchRule<R> {
+    /// Creates a switch on the value of `key`, with no cases.
+    pub fn new(key: &'static str) -> Self {
+        SwitchRule {
+            key,
+            cases: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Adds a branch fired when the key holds `value`. Cases are tried in the
+    /// order they were added.
+    pub fn case<T: PartialEq + 'static>(mut self, value: T, rule: Wrapper<R>) -> Self {
+        let matcher = move |candidate: &dyn Any| candidate.downcast_ref::<T>() == Some(&value);
+        self.cases.push((Box::new(matcher), rule));
+        self
+    }
+
+    /// Sets the branch fired when no case matches.
+    pub fn default(mut self, rule: Wrapper<R>) -> Self {
+        self.default = Some(rule);
+        self
+    }
+
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    /// The branches of the cases, in order, followed by the default branch.
+    pub fn get_branches(&self) -> Vec<Wrapper<R>> {
+        self.cases
+            .iter()
+            .map(|(_, rule)| rule.clone())
+            .chain(self.default.clone())
+            .collect()
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then the matching branch, and returns the rule.
+    pub fn wrap(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            let value = rule_context.borrow().lookup(self.key);
+            let branch = value
+                .and_then(|value| {
+                    self.cases
+                        .iter()
+                        .find(|(matcher, _)| matcher(value.as_ref()))
+                        .map(|(_, rule)| rule)
+                })
+                .or(self.default.as_ref());
+            if let Some(branch) = branch {
+                fire_rule(branch, &rule_context);
+            }
+        })
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(feature = "tracing")]
+use super::spans;
+use super::{
+    run_execute_phases, wrap, GetSet, Rule, RuleCallback, RuleContextWrapper, RuleError,
+    RuleFailure, Wrapper,
+};
+
+/// Decorates a rule so that, when executed, it evaluates every one of a list
+/// of children, records how many matched in a context key, and fires the
+/// matching ones only when at least `threshold` of them matched, for scores
+/// like "3 of these 7 risk signals".
+///
+/// The match count is written as a `usize`, whether the threshold is met or
+/// not. Each child is evaluated once: the matching ones then run their
+/// execute callbacks and children without being evaluated again. Children
+/// that are disabled or left out by the filter of the run don't count, and
+/// the depth and budget of the run apply to the children fired. A child that
+/// records a failure while being evaluated stops the rule.
+///
+/// The children are evaluated after the execute callback of the decorated
+/// rule and before its post-execute callback and own children. Like the other
+/// decorators, setting the execute callback of the rule afterwards replaces
+/// the threshold.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let signal = |key: &'static str| AllRule::new()
+///     .on_eval(move |ctx| ctx.get::<bool>(key).is_some())
+///     .on_execute(move |this| this.get_rule_context().push_to_list("flags", key));
+///
+/// let rule = ThresholdRule::new("risk_signals", 2, vec![signal("new_device"), signal("foreign_ip"), signal("vpn")])
+///     .wrap(AllRule::new());
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("new_device", true);
+/// rule_context.set("vpn", true);
+/// Engine::all_runner().run(rule_context.clone(), vec![rule]);
+///
+/// assert_eq!(*rule_context.get::<usize>("risk_signals").unwrap(), 2);
+/// assert_eq!(*rule_context.get_list::<&str>("flags").unwrap(), vec!["new_device", "vpn"]);
+/// ```
+pub struct ThresholdRule<R> {
+    key: &'static str,
+    threshold: usize,
+    children: Vec<Wrapper<R>>,
+}
+
+impl<R: Rule<R> + Clone + 'static> ThresholdRule<R> {
+    /// Fires the matching children when at least `threshold` of them match,
+    /// writing the number of matches to `key`.
+    pub fn new(key: &'static str, threshold: usize, children: Vec<Wrapper<R>>) -> Self {
+        ThresholdRule {
+            key,
+            threshold,
+            children,
+        }
+    }
+
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn get_threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn get_children(&self) -> &[Wrapper<R>] {
+        &self.children
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then counts and fires the children, and returns the
+    /// rule.
+    pub fn wrap(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            let mut matching = Vec::new();
+            for child in &self.children {
+                let matched = {
+                    let mut child = child.borrow_mut();
+                    child.set_rule_context(rule_context.clone());
+                    let skipped = rule_context
+                        .borrow()
+                        .get_skip_reason(child.get_metadata())
+                        .is_some();
+                    !skipped && child.run_eval()
+                };
+                if rule_context.has_failed() {
+                    return;
+                }
+                if matched {
+                    matching.push(child);
+                }
+            }
+            rule_context.set(self.key, matching.len());
+            if matching.len() < self.threshold {
+                return;
+            }
+            for child in matching {
+                fire_matched(child, &rule_context);
+                if rule_context.has_failed() {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+/// Fires a child whose evaluation already passed, like `Rule::fire` without
+/// evaluating it again.
+fn fire_matched<R: Rule<R> + Clone + 'static>(
+    rule: &Wrapper<R>,
+    rule_context: &RuleContextWrapper,
+) {
+    let Ok(mut rule) = rule.try_borrow_mut() else {
+        rule_context.clone().fail(RuleError::CycleDetected);
+        return;
+    };
+    rule.set_rule_context(rule_context.clone());
+    let entry = rule_context.borrow_mut().trace_fire(rule.get_metadata());
+    #[cfg(feature = "tracing")]
+    let span = spans::FireSpan::enter(rule.get_metadata());
+    let admitted = rule_context.borrow_mut().admit(rule.get_metadata());
+    if admitted {
+        run_execute_phases(&mut *rule);
+    }
+    let mut rule_context = rule_context.borrow_mut();
+    let executed = !rule_context.take_degraded_rule() && admitted;
+    rule_context.trace_fired(entry, executed);
+    rule_context.release(admitted);
+    #[cfg(feature = "tracing")]
+    span.finish(executed, rule_context.has_failed());
+    rule_context.name_failure(rule.get_metadata());
+    rule_context.recover_failure();
+}
@@ -0,0 +1,456 @@
+use std::{fmt, time::Duration};
+
+#[cfg(feature = "alloc-tracking")]
+use crate::alloc_tracking::AllocationCount;
+#[cfg(feature = "serde")]
+use crate::inspector::Recorder;
+
+use super::{CanaryOutcome, Metadata, RuleContext};
+use crate::time::{Instant, SystemTime};
+
+/// A record of a single rule being fired.
+#[derive(Debug, Clone)]
+pub struct TraceEntry {
+    id: Option<String>,
+    name: Option<String>,
+    owner: Option<String>,
+    team: Option<String>,
+    depth: usize,
+    eval_result: bool,
+    executed: bool,
+    failed: bool,
+    skip_reason: Option<&'static str>,
+    canary: Option<CanaryOutcome>,
+    started_at: SystemTime,
+    duration: Duration,
+    started: Instant,
+    #[cfg(feature = "alloc-tracking")]
+    allocations: AllocationCount,
+}
+
+impl TraceEntry {
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn get_team(&self) -> Option<&str> {
+        self.team.as_deref()
+    }
+
+    /// Nesting level of the rule, `0` for the rules passed to the runner.
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// What `on_eval()` returned.
+    pub fn get_eval_result(&self) -> bool {
+        self.eval_result
+    }
+
+    /// Whether the execute callbacks and children of the rule were run.
+    pub fn is_executed(&self) -> bool {
+        self.executed
+    }
+
+    /// Whether a failure was recorded while the rule, or one of its
+    /// children, was being fired.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Why the rule was skipped without its evaluation being run for real:
+    /// `"quarantined"`, `"budget"` or `"canary"`.
+    pub fn get_skip_reason(&self) -> Option<&str> {
+        self.skip_reason
+    }
+
+    /// How firing the rule turned out.
+    pub fn get_outcome(&self) -> RuleOutcome {
+        match self.skip_reason {
+            Some(reason) => RuleOutcome::Skipped(reason),
+            None if self.failed => RuleOutcome::Errored,
+            None if self.executed => RuleOutcome::Fired,
+            None => RuleOutcome::NotApplicable,
+        }
+    }
+
+    /// What the rule would have done, when it is a `Canary`.
+    pub fn get_canary(&self) -> Option<&CanaryOutcome> {
+        self.canary.as_ref()
+    }
+
+    pub fn get_started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// Time spent firing the rule, children included.
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Heap allocations made while firing the rule, children included.
+    /// Requires `TrackingAllocator` to be installed.
+    #[cfg(feature = "alloc-tracking")]
+    pub fn get_allocations(&self) -> u64 {
+        self.allocations.allocations
+    }
+
+    /// Bytes requested by the allocations counted in `get_allocations`.
+    #[cfg(feature = "alloc-tracking")]
+    pub fn get_allocated_bytes(&self) -> u64 {
+        self.allocations.bytes
+    }
+}
+
+/// How firing a rule turned out, see `TraceEntry::get_outcome`.
+///
+/// Rules that were not executed because of how the rule set is operated, such
+/// as quarantined rules, optional rules over budget or canaries, are
+/// `Skipped`, while `NotApplicable` rules were evaluated and didn't match.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rules = vec![
+///     AllRule::new().on_eval(|_| false),
+///     Canary::wrap(AllRule::new()),
+///     AllRule::new(),
+/// ];
+///
+/// let report = Engine::all_runner().run_with_report(RuleContext::new(), rules);
+/// let outcomes: Vec<_> = report.get_trace().get_entries().iter().map(TraceEntry::get_outcome).collect();
+///
+/// assert_eq!(
+///     outcomes,
+///     vec![RuleOutcome::NotApplicable, RuleOutcome::Skipped("canary"), RuleOutcome::Fired]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleOutcome {
+    /// The evaluation passed and the rule was executed.
+    Fired,
+    /// The evaluation didn't pass.
+    NotApplicable,
+    /// The rule was not executed for an operational reason, see
+    /// `TraceEntry::get_skip_reason`.
+    Skipped(&'static str),
+    /// A failure was recorded while the rule, or one of its children, was
+    /// being fired.
+    Errored,
+}
+
+impl fmt::Display for RuleOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleOutcome::Fired => write!(f, "fired"),
+            RuleOutcome::NotApplicable => write!(f, "not applicable"),
+            RuleOutcome::Skipped(reason) => write!(f, "skipped ({reason})"),
+            RuleOutcome::Errored => write!(f, "errored"),
+        }
+    }
+}
+
+/// The rules fired during a run, in the order they were evaluated.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionTrace {
+    entries: Vec<TraceEntry>,
+    depth: usize,
+    #[cfg(feature = "serde")]
+    recorder: Option<Recorder>,
+}
+
+impl ExecutionTrace {
+    pub fn get_entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// The position of the parent of every entry, in trace order. Traces of
+    /// nested runs don't start at depth 0, so depths are counted from the one
+    /// of the first entry.
+    pub(crate) fn get_parents(&self) -> Vec<Option<usize>> {
+        let mut parents = Vec::with_capacity(self.entries.len());
+        let mut stack: Vec<usize> = Vec::new();
+        let base = self.entries.first().map_or(0, TraceEntry::get_depth);
+        for (position, entry) in self.entries.iter().enumerate() {
+            stack.truncate(entry.depth.saturating_sub(base));
+            parents.push(stack.last().copied());
+            stack.push(position);
+        }
+        parents
+    }
+
+    /// The time spent in every entry, excluding its children, in trace order.
+    pub(crate) fn get_self_times(&self) -> Vec<Duration> {
+        let mut times: Vec<Duration> = self.entries.iter().map(|entry| entry.duration).collect();
+        for (entry, parent) in self.entries.iter().zip(self.get_parents()) {
+            if let Some(parent) = parent {
+                times[parent] = times[parent].saturating_sub(entry.duration);
+            }
+        }
+        times
+    }
+
+    /// The entries of the rules that were executed.
+    pub fn get_executed(&self) -> Vec<&TraceEntry> {
+        self.entries.iter().filter(|entry| entry.executed).collect()
+    }
+
+    /// The entries of the rules that were skipped, see `TraceEntry::get_skip_reason`.
+    pub fn get_skipped(&self) -> Vec<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.skip_reason.is_some())
+            .collect()
+    }
+
+    /// The entries of the rules with the given outcome.
+    pub fn get_by_outcome(&self, outcome: RuleOutcome) -> Vec<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.get_outcome() == outcome)
+            .collect()
+    }
+
+    /// The entries of the canary rules that were fired.
+    pub fn get_canaries(&self) -> Vec<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.canary.is_some())
+            .collect()
+    }
+
+    /// The names of the rules that were executed, skipping unnamed ones.
+    pub fn get_executed_names(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.executed)
+            .filter_map(|entry| entry.get_name())
+            .collect()
+    }
+
+    /// Renders the run as a Mermaid flowchart, each rule fired linked to the
+    /// rule that fired it and colored by its outcome, to embed in reports.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = AllRule::new()
+    ///     .with_name("order")
+    ///     .add_children(vec![
+    ///         AllRule::new().with_name("discount"),
+    ///         AllRule::new().with_name("fraud check").on_eval(|_| false),
+    ///     ]);
+    ///
+    /// let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+    /// let chart = report.get_trace().to_mermaid();
+    ///
+    /// assert!(chart.starts_with("flowchart TD\n"));
+    /// assert!(chart.contains("r0 --> r1\n"));
+    /// assert!(chart.contains("class r2 not_applicable\n"));
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut chart = String::from("flowchart TD\n");
+        // The entries being fired, by depth, to find the parent of each one.
+        let mut parents: Vec<usize> = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let label = entry
+                .get_name()
+                .or_else(|| entry.get_id())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("rule {index}"));
+            chart.push_str(&format!(
+                "    r{index}[\"{}<br/>{}\"]\n",
+                mermaid_escape(&label),
+                entry.get_outcome()
+            ));
+            parents.truncate(entry.depth);
+            if let Some(parent) = parents.last() {
+                chart.push_str(&format!("    r{parent} --> r{index}\n"));
+            }
+            parents.push(index);
+        }
+        for (index, entry) in self.entries.iter().enumerate() {
+            let class = match entry.get_outcome() {
+                RuleOutcome::Fired => "fired",
+                RuleOutcome::NotApplicable => "not_applicable",
+                RuleOutcome::Skipped(_) => "skipped",
+                RuleOutcome::Errored => "errored",
+            };
+            chart.push_str(&format!("    class r{index} {class}\n"));
+        }
+        for (class, style) in MERMAID_CLASSES {
+            chart.push_str(&format!("    classDef {class} {style}\n"));
+        }
+        chart
+    }
+}
+
+/// The styles of the outcome classes of `ExecutionTrace::to_mermaid`.
+const MERMAID_CLASSES: [(&str, &str); 4] = [
+    ("fired", "fill:#d4edda,stroke:#28a745"),
+    ("not_applicable", "fill:#eeeeee,stroke:#999999"),
+    ("skipped", "fill:#fff3cd,stroke:#ffc107"),
+    ("errored", "fill:#f8d7da,stroke:#dc3545"),
+];
+
+/// Escapes the characters Mermaid doesn't allow in quoted labels.
+fn mermaid_escape(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+impl RuleContext {
+    /// Starts collecting a trace. When one is already being collected, returns
+    /// the number of entries it holds so that nested runs only report their own.
+    pub(crate) fn start_trace(&mut self) -> Option<usize> {
+        match &self.trace {
+            Some(trace) => Some(trace.entries.len()),
+            None => {
+                self.trace = Some(ExecutionTrace::default());
+                None
+            }
+        }
+    }
+
+    /// Returns the entries recorded since `start_trace`. The trace is only
+    /// removed from the context by the call that started it.
+    pub(crate) fn finish_trace(&mut self, start: Option<usize>) -> ExecutionTrace {
+        match start {
+            None => self.trace.take().unwrap_or_default(),
+            Some(start) => ExecutionTrace {
+                entries: self
+                    .trace
+                    .as_ref()
+                    .map(|trace| trace.entries[start..].to_vec())
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Records that a rule is being fired and returns its entry index.
+    pub(crate) fn trace_fire(&mut self, metadata: &Metadata) -> Option<usize> {
+        let trace = self.trace.as_mut()?;
+        #[cfg(feature = "serde")]
+        if let Some(recorder) = &mut trace.recorder {
+            let step = innermost_entry(&trace.entries, trace.depth);
+            recorder.observe(&self.context_map, step);
+        }
+        trace.entries.push(TraceEntry {
+            id: metadata.id.clone(),
+            name: metadata.name.clone(),
+            owner: metadata.owner.clone(),
+            team: metadata.team.clone(),
+            depth: trace.depth,
+            eval_result: false,
+            executed: false,
+            failed: false,
+            skip_reason: None,
+            canary: None,
+            started_at: SystemTime::now(),
+            duration: Duration::ZERO,
+            started: Instant::now(),
+            #[cfg(feature = "alloc-tracking")]
+            allocations: AllocationCount::current(),
+        });
+        trace.depth += 1;
+        Some(trace.entries.len() - 1)
+    }
+
+    /// Flags the rule being evaluated as skipped. Must be called from its
+    /// evaluation callback, before any child is fired.
+    pub(crate) fn trace_skip(&mut self, reason: &'static str) {
+        if let Some(entry) = self
+            .trace
+            .as_mut()
+            .and_then(|trace| trace.entries.last_mut())
+        {
+            entry.skip_reason = Some(reason);
+        }
+    }
+
+    /// The name, or id, of the innermost rule being fired, when a trace is
+    /// collected.
+    pub(crate) fn current_rule(&self) -> Option<String> {
+        let trace = self.trace.as_ref()?;
+        let entry = &trace.entries[innermost_entry(&trace.entries, trace.depth)?];
+        entry.name.clone().or_else(|| entry.id.clone())
+    }
+
+    /// Starts recording the changes made to the context by every rule fired,
+    /// collecting a trace if none is, and returns the recorder it replaced.
+    #[cfg(feature = "serde")]
+    pub(crate) fn start_recording(&mut self) -> Option<Recorder> {
+        let owns_trace = self.trace.is_none();
+        let trace = self.trace.get_or_insert_with(ExecutionTrace::default);
+        let recorder = Recorder::new(&self.context_map, trace.entries.len(), owns_trace);
+        trace.recorder.replace(recorder)
+    }
+
+    /// Stops the recording started by `start_recording`, putting back the
+    /// recorder it replaced.
+    #[cfg(feature = "serde")]
+    pub(crate) fn finish_recording(&mut self, previous: Option<Recorder>) -> Recorder {
+        let trace = self
+            .trace
+            .as_mut()
+            .expect("the trace is kept while recording");
+        let mut recorder =
+            std::mem::replace(&mut trace.recorder, previous).expect("recording was started");
+        recorder.observe(&self.context_map, None);
+        if recorder.owns_trace {
+            self.trace = None;
+        }
+        recorder
+    }
+
+    /// Records the outcome of the canary rule being evaluated.
+    pub(crate) fn trace_canary(&mut self, outcome: CanaryOutcome) {
+        if let Some(entry) = self
+            .trace
+            .as_mut()
+            .and_then(|trace| trace.entries.last_mut())
+        {
+            entry.canary = Some(outcome);
+        }
+    }
+
+    /// Completes the entry returned by `trace_fire`.
+    pub(crate) fn trace_fired(&mut self, index: Option<usize>, eval_result: bool) {
+        if let (Some(trace), Some(index)) = (self.trace.as_mut(), index) {
+            trace.depth -= 1;
+            #[cfg(feature = "serde")]
+            if let Some(recorder) = &mut trace.recorder {
+                recorder.observe(&self.context_map, Some(index));
+                recorder.finish(index);
+            }
+            let entry = &mut trace.entries[index];
+            entry.eval_result = eval_result;
+            entry.executed = eval_result;
+            entry.failed = self.error.is_some();
+            entry.duration = entry.started.elapsed();
+            #[cfg(feature = "alloc-tracking")]
+            {
+                entry.allocations = entry.allocations.elapsed();
+            }
+        }
+    }
+}
+
+/// The index of the innermost entry being fired, given the current depth.
+fn innermost_entry(entries: &[TraceEntry], depth: usize) -> Option<usize> {
+    let depth = depth.checked_sub(1)?;
+    entries.iter().rposition(|entry| entry.depth == depth)
+}
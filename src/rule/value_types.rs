@@ -0,0 +1,261 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    rc::Rc,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// A value of a primitive type held by a context, downcast once for the code
+/// converting context values, such as expressions, scripts and reports.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Primitive {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Char(char),
+    String(String),
+    Str(&'static str),
+}
+
+/// Calls `$convert!` with the primitive types and their variants.
+macro_rules! primitive_types {
+    ($convert:ident) => {
+        $convert!(
+            i8 => I8,
+            i16 => I16,
+            i32 => I32,
+            i64 => I64,
+            i128 => I128,
+            isize => Isize,
+            u8 => U8,
+            u16 => U16,
+            u32 => U32,
+            u64 => U64,
+            u128 => U128,
+            usize => Usize,
+            f32 => F32,
+            f64 => F64,
+            bool => Bool,
+            char => Char,
+            String => String,
+            &'static str => Str
+        )
+    };
+}
+
+impl Primitive {
+    pub(crate) fn from_any(value: &dyn Any) -> Option<Self> {
+        macro_rules! downcast {
+            ($($ty:ty => $variant:ident),*) => {
+                $(if let Some(value) = value.downcast_ref::<$ty>() {
+                    return Some(Primitive::$variant(value.clone()));
+                })*
+            };
+        }
+        primitive_types!(downcast);
+        None
+    }
+
+    /// The value, when it is an integer of any width.
+    pub(crate) fn as_int(&self) -> Option<i128> {
+        match *self {
+            Primitive::I8(value) => Some(value.into()),
+            Primitive::I16(value) => Some(value.into()),
+            Primitive::I32(value) => Some(value.into()),
+            Primitive::I64(value) => Some(value.into()),
+            Primitive::I128(value) => Some(value),
+            Primitive::Isize(value) => Some(value as i128),
+            Primitive::U8(value) => Some(value.into()),
+            Primitive::U16(value) => Some(value.into()),
+            Primitive::U32(value) => Some(value.into()),
+            Primitive::U64(value) => Some(value.into()),
+            Primitive::U128(value) => value.try_into().ok(),
+            Primitive::Usize(value) => Some(value as i128),
+            _ => None,
+        }
+    }
+
+    /// The value, when it is a float.
+    pub(crate) fn as_float(&self) -> Option<f64> {
+        match *self {
+            Primitive::F32(value) => Some(value.into()),
+            Primitive::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value, when it is a string.
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Primitive::String(value) => Some(value),
+            Primitive::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// An integer of the same type as this one, when it fits in it.
+    pub(crate) fn with_int(&self, value: i128) -> Option<Self> {
+        Some(match self {
+            Primitive::I8(_) => Primitive::I8(value.try_into().ok()?),
+            Primitive::I16(_) => Primitive::I16(value.try_into().ok()?),
+            Primitive::I32(_) => Primitive::I32(value.try_into().ok()?),
+            Primitive::I64(_) => Primitive::I64(value.try_into().ok()?),
+            Primitive::I128(_) => Primitive::I128(value),
+            Primitive::Isize(_) => Primitive::Isize(value.try_into().ok()?),
+            Primitive::U8(_) => Primitive::U8(value.try_into().ok()?),
+            Primitive::U16(_) => Primitive::U16(value.try_into().ok()?),
+            Primitive::U32(_) => Primitive::U32(value.try_into().ok()?),
+            Primitive::U64(_) => Primitive::U64(value.try_into().ok()?),
+            Primitive::U128(_) => Primitive::U128(value.try_into().ok()?),
+            Primitive::Usize(_) => Primitive::Usize(value.try_into().ok()?),
+            _ => return None,
+        })
+    }
+
+    /// A float of the same type as this one.
+    pub(crate) fn with_float(&self, value: f64) -> Option<Self> {
+        match self {
+            Primitive::F32(_) => Some(Primitive::F32(value as f32)),
+            Primitive::F64(_) => Some(Primitive::F64(value)),
+            _ => None,
+        }
+    }
+
+    /// The name of the type of the value, as written in Rust.
+    pub(crate) fn type_name(&self) -> &'static str {
+        macro_rules! name {
+            ($($ty:ty => $variant:ident),*) => {
+                match self {
+                    $(Primitive::$variant(_) => stringify!($ty),)*
+                }
+            };
+        }
+        primitive_types!(name)
+    }
+
+    /// The value, to be held by a context.
+    pub(crate) fn into_value(self) -> Rc<dyn Any> {
+        macro_rules! upcast {
+            ($($ty:ty => $variant:ident),*) => {
+                match self {
+                    $(Primitive::$variant(value) => Rc::new(value),)*
+                }
+            };
+        }
+        primitive_types!(upcast)
+    }
+}
+
+type ToShared = fn(&dyn Any) -> Arc<dyn Any + Send + Sync>;
+type ToLocal = fn(&(dyn Any + Send + Sync)) -> Rc<dyn Any>;
+type Equals = fn(&dyn Any, &dyn Any) -> bool;
+
+/// What can be done with the values of a registered type.
+#[derive(Default, Clone, Copy)]
+struct ValueType {
+    to_shared: Option<ToShared>,
+    to_local: Option<ToLocal>,
+    eq: Option<Equals>,
+}
+
+type Registry = HashMap<TypeId, ValueType>;
+
+fn insert_shared<T: Clone + Send + Sync + 'static>(registry: &mut Registry) {
+    let value_type = registry.entry(TypeId::of::<T>()).or_default();
+    value_type.to_shared = Some(|value| Arc::new(value.downcast_ref::<T>().unwrap().clone()));
+    value_type.to_local = Some(|value| Rc::new(value.downcast_ref::<T>().unwrap().clone()));
+}
+
+fn insert_comparable<T: PartialEq + 'static>(registry: &mut Registry) {
+    registry.entry(TypeId::of::<T>()).or_default().eq =
+        Some(|a, b| a.downcast_ref::<T>() == b.downcast_ref::<T>());
+}
+
+/// The registered types, the primitive ones, `Decimal` and
+/// `serde_json::Value`, and the lists of them, registered on first use.
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let mut registry = Registry::new();
+        macro_rules! register {
+            ($($ty:ty $(=> $variant:ident)?),*) => {
+                $(
+                    insert_shared::<$ty>(&mut registry);
+                    insert_shared::<Vec<$ty>>(&mut registry);
+                    insert_comparable::<$ty>(&mut registry);
+                    insert_comparable::<Vec<$ty>>(&mut registry);
+                )*
+            };
+        }
+        primitive_types!(register);
+        #[cfg(feature = "decimal")]
+        register!(rust_decimal::Decimal);
+        #[cfg(feature = "serde")]
+        register!(serde_json::Value);
+        Mutex::new(registry)
+    })
+}
+
+/// Registers a type, and the lists of it, whose values can be moved between
+/// threads, see `SharedRuleContext::register`.
+pub(crate) fn register_shared<T: Clone + Send + Sync + 'static>() {
+    let mut registry = registry().lock().unwrap();
+    insert_shared::<T>(&mut registry);
+    insert_shared::<Vec<T>>(&mut registry);
+}
+
+/// Registers a type, and the lists of it, whose values are compared by
+/// value, see `RuleContext::register_comparable`.
+pub(crate) fn register_comparable<T: PartialEq + 'static>() {
+    let mut registry = registry().lock().unwrap();
+    insert_comparable::<T>(&mut registry);
+    insert_comparable::<Vec<T>>(&mut registry);
+}
+
+/// A copy of the value that can be moved between threads, when its type is
+/// registered.
+pub(crate) fn to_shared(value: &dyn Any) -> Option<Arc<dyn Any + Send + Sync>> {
+    let to_shared = registry()
+        .lock()
+        .unwrap()
+        .get(&value.type_id())?
+        .to_shared?;
+    Some(to_shared(value))
+}
+
+/// A copy of a value taken with `to_shared`, to be held by a context.
+pub(crate) fn to_local(value: &(dyn Any + Send + Sync)) -> Option<Rc<dyn Any>> {
+    let to_local = registry().lock().unwrap().get(&value.type_id())?.to_local?;
+    Some(to_local(value))
+}
+
+/// Whether two values of a context are equal: the same value, or values of
+/// the same comparable type that are equal.
+pub(crate) fn values_equal(a: &Rc<dyn Any>, b: &Rc<dyn Any>) -> bool {
+    if Rc::ptr_eq(a, b) {
+        return true;
+    }
+    let (a, b) = (a.as_ref(), b.as_ref());
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+    let eq = registry()
+        .lock()
+        .unwrap()
+        .get(&a.type_id())
+        .and_then(|value_type| value_type.eq);
+    eq.is_some_and(|eq| eq(a, b))
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use super::{RuleContext, RuleContextWrapper};
+
+/// A typed decision of a rule, pushed with `RuleVerdicts`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Approve,
+    /// Denies, for the reason given.
+    Deny(String),
+    /// Adds to the score of the run, such as a risk score.
+    Score(f64),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Approve => write!(f, "approve"),
+            Verdict::Deny(reason) => write!(f, "deny: {reason}"),
+            Verdict::Score(score) => write!(f, "score {score}"),
+        }
+    }
+}
+
+/// A verdict and the rule that pushed it.
+///
+/// Displayed as `deny in `rule`: reason`, without the rule when it isn't
+/// known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleVerdict {
+    verdict: Verdict,
+    rule: Option<String>,
+}
+
+impl RuleVerdict {
+    pub fn get_verdict(&self) -> &Verdict {
+        &self.verdict
+    }
+
+    /// The name, or id, of the rule being fired when the verdict was pushed.
+    /// Only known while a trace is collected, as it is by
+    /// `RuleRunner::run_with_report`.
+    pub fn get_rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+}
+
+impl fmt::Display for RuleVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(rule) = &self.rule else {
+            return write!(f, "{}", self.verdict);
+        };
+        match &self.verdict {
+            Verdict::Approve => write!(f, "approve in `{rule}`"),
+            Verdict::Deny(reason) => write!(f, "deny in `{rule}`: {reason}"),
+            Verdict::Score(score) => write!(f, "score {score} in `{rule}`"),
+        }
+    }
+}
+
+/// The verdicts pushed by the rules, in order, summed up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Verdicts {
+    verdicts: Vec<RuleVerdict>,
+}
+
+impl Verdicts {
+    pub fn get_all(&self) -> &[RuleVerdict] {
+        &self.verdicts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verdicts.is_empty()
+    }
+
+    /// Whether a rule approved and none denied.
+    pub fn is_approved(&self) -> bool {
+        !self.is_denied()
+            && self
+                .verdicts
+                .iter()
+                .any(|verdict| verdict.verdict == Verdict::Approve)
+    }
+
+    /// Whether a rule denied, whatever the others decided.
+    pub fn is_denied(&self) -> bool {
+        !self.get_denials().is_empty()
+    }
+
+    /// The reasons of the denials, in order.
+    pub fn get_denials(&self) -> Vec<&str> {
+        self.verdicts
+            .iter()
+            .filter_map(|verdict| match &verdict.verdict {
+                Verdict::Deny(reason) => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drops the verdicts pushed by the rules, given by name or id.
+    pub(crate) fn remove_rules(&mut self, rules: &[String]) {
+        self.verdicts.retain(|verdict| {
+            !verdict
+                .rule
+                .as_ref()
+                .is_some_and(|rule| rules.contains(rule))
+        });
+    }
+
+    /// The sum of the scores pushed, `0.0` when none was.
+    pub fn get_score(&self) -> f64 {
+        self.verdicts
+            .iter()
+            .filter_map(|verdict| match verdict.verdict {
+                Verdict::Score(score) => Some(score),
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+/// Records the decisions of the rules apart from the values of the context.
+///
+/// Rules pushing their results into context keys have to agree on the keys
+/// and on the types of their values, and may overwrite each other's. Verdicts
+/// are typed and pile up instead: `RuleRunner::run_with_report` moves the
+/// ones pushed during the run to `RunReport::get_verdicts`, which sums them
+/// up. Like values, they are rolled back by `RuleContext::restore` and
+/// `RuleRunner::try_run_atomic`, dropped for the rules compensated by
+/// `RuleRunner::run_with_compensation` and kept in a child scope until it is
+/// merged.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rules = vec![
+///     AllRule::new().with_name("kyc").on_execute(|this| this.get_rule_context().approve()),
+///     AllRule::new().with_name("risk").on_execute(|this| this.get_rule_context().score(0.25)),
+///     AllRule::new().with_name("sanctions").on_execute(|this| {
+///         this.get_rule_context().deny("listed country");
+///     }),
+/// ];
+///
+/// let report = Engine::all_runner().run_with_report(RuleContext::new(), rules);
+/// let verdicts = report.get_verdicts();
+///
+/// assert!(verdicts.is_denied());
+/// assert!(!verdicts.is_approved());
+/// assert_eq!(verdicts.get_score(), 0.25);
+/// assert_eq!(verdicts.get_all()[2].to_string(), "deny in `sanctions`: listed country");
+/// ```
+pub trait RuleVerdicts {
+    fn push_verdict(&mut self, verdict: Verdict);
+    /// The verdicts pushed so far and not taken yet.
+    fn get_verdicts(&self) -> Verdicts;
+    /// Removes the verdicts pushed so far.
+    fn take_verdicts(&mut self) -> Verdicts;
+
+    fn approve(&mut self) {
+        self.push_verdict(Verdict::Approve);
+    }
+
+    fn deny(&mut self, reason: impl Into<String>) {
+        self.push_verdict(Verdict::Deny(reason.into()));
+    }
+
+    fn score(&mut self, score: f64) {
+        self.push_verdict(Verdict::Score(score));
+    }
+}
+
+impl RuleVerdicts for RuleContext {
+    fn push_verdict(&mut self, verdict: Verdict) {
+        let rule = self.current_rule();
+        self.verdicts.push(RuleVerdict { verdict, rule });
+    }
+
+    fn get_verdicts(&self) -> Verdicts {
+        Verdicts {
+            verdicts: self.verdicts.clone(),
+        }
+    }
+
+    fn take_verdicts(&mut self) -> Verdicts {
+        Verdicts {
+            verdicts: std::mem::take(&mut self.verdicts),
+        }
+    }
+}
+
+impl RuleVerdicts for RuleContextWrapper {
+    fn push_verdict(&mut self, verdict: Verdict) {
+        self.borrow_mut().push_verdict(verdict);
+    }
+
+    fn get_verdicts(&self) -> Verdicts {
+        self.borrow().get_verdicts()
+    }
+
+    fn take_verdicts(&mut self) -> Verdicts {
+        self.borrow_mut().take_verdicts()
+    }
+}
+
+impl RuleContext {
+    pub(crate) fn get_pushed_verdicts(&self) -> usize {
+        self.verdicts.len()
+    }
+
+    /// Removes the verdicts pushed since `start` verdicts were pushed.
+    pub(crate) fn take_pushed_verdicts(&mut self, start: usize) -> Verdicts {
+        Verdicts {
+            verdicts: self.verdicts.split_off(start.min(self.verdicts.len())),
+        }
+    }
+}
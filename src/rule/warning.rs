@@ -0,0 +1,142 @@
+use std::fmt;
+
+use super::{RuleContext, RuleContextWrapper};
+
+/// How much attention a `Warning` deserves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing, such as a value that was converted to another type.
+    Info,
+    /// Likely a problem, such as a callback running close to its time limit.
+    Warning,
+    /// Certainly a problem, although the run could go on.
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A problem that doesn't fail the run, reported with `RuleWarnings::warn`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    severity: Severity,
+    message: String,
+    rule: Option<String>,
+}
+
+impl Warning {
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// The name, or id, of the rule being fired when the warning was
+    /// reported. Only known while a trace is collected, as it is by
+    /// `RuleRunner::run_with_report`.
+    pub fn get_rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.rule {
+            Some(rule) => write!(f, "{} in `{rule}`: {}", self.severity, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// Reports problems that should surface without failing the run.
+///
+/// Unlike failures, warnings don't stop anything: they pile up in the context
+/// and `RuleRunner::run_with_report` moves the ones reported during the run
+/// to `RunReport::get_warnings`. The engine reports its own warnings there
+/// too, for instance `DurationLimitRule` when a callback gets close to its limit.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new().with_name("legacy_score").on_execute(|this| {
+///     this.get_rule_context().warn(Severity::Info, "read deprecated key `score_v1`");
+///     this.get_rule_context().set("score", 700);
+/// });
+///
+/// let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+///
+/// assert!(report.get_error().is_none());
+/// assert_eq!(report.get_warnings().len(), 1);
+/// assert_eq!(report.get_warnings()[0].get_rule(), Some("legacy_score"));
+/// assert_eq!(
+///     report.get_warnings()[0].to_string(),
+///     "info in `legacy_score`: read deprecated key `score_v1`"
+/// );
+/// ```
+pub trait RuleWarnings {
+    fn warn(&mut self, severity: Severity, message: impl Into<String>);
+    /// The warnings reported so far and not taken yet.
+    fn get_warnings(&self) -> Vec<Warning>;
+    /// Removes the warnings reported so far.
+    fn take_warnings(&mut self) -> Vec<Warning>;
+}
+
+impl RuleWarnings for RuleContext {
+    fn warn(&mut self, severity: Severity, message: impl Into<String>) {
+        self.push_warning(severity, message.into());
+    }
+
+    fn get_warnings(&self) -> Vec<Warning> {
+        self.warnings.borrow().clone()
+    }
+
+    fn take_warnings(&mut self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+}
+
+impl RuleWarnings for RuleContextWrapper {
+    fn warn(&mut self, severity: Severity, message: impl Into<String>) {
+        self.borrow_mut().warn(severity, message);
+    }
+
+    fn get_warnings(&self) -> Vec<Warning> {
+        self.borrow().get_warnings()
+    }
+
+    fn take_warnings(&mut self) -> Vec<Warning> {
+        self.borrow_mut().take_warnings()
+    }
+}
+
+impl RuleContext {
+    /// Records a warning from code that only reads the context.
+    pub(crate) fn push_warning(&self, severity: Severity, message: String) {
+        let rule = self.current_rule();
+        self.warnings.borrow_mut().push(Warning {
+            severity,
+            message,
+            rule,
+        });
+    }
+
+    pub(crate) fn get_reported_warnings(&self) -> usize {
+        self.warnings.borrow().len()
+    }
+
+    /// Removes the warnings reported since `start` warnings were reported.
+    pub(crate) fn take_reported_warnings(&mut self, start: usize) -> Vec<Warning> {
+        let warnings = self.warnings.get_mut();
+        warnings.split_off(start.min(warnings.len()))
+    }
+}
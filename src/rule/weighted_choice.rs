@@ -0,0 +1,219 @@
+use std::any::Any;
+
+use super::{
+    depth_guard::fire_rule, value_types::Primitive, wrap, GetSet, Rng, Rule, RuleCallback,
+    RuleContext, RuleFailure, Wrapper,
+};
+
+type Setter = Box<dyn Fn(&mut RuleContext)>;
+
+struct Alternative<R> {
+    weight: u32,
+    set: Setter,
+    branch: Option<Wrapper<R>>,
+}
+
+/// Decorates a rule so that, when executed, it picks one of several
+/// alternatives by weight, writes the value of the alternative picked to a
+/// context key and fires its branch, if it has one. The backbone of traffic
+/// splitting rules.
+///
+/// Alternatives are drawn with the random generator of the context, see
+/// `Rng`, so runs are replayed from the seed of their report. With
+/// `sticky_by`, the pick is instead derived from a stable hash of the value of
+/// a context key, such as a user id, so that the same subject always gets the
+/// same alternative, across runs and processes, as long as the alternatives
+/// and their weights don't change. Subjects are hashed from strings and
+/// integers; when the key is missing or holds another type, the alternative
+/// is drawn at random.
+///
+/// The choice is made after the execute callback of the decorated rule and
+/// before its post-execute callback and children. Like the other decorators,
+/// setting the execute callback of the rule afterwards replaces the choice.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let checkout = WeightedChoice::new("checkout")
+///     .alternative("classic", 90)
+///     .branch("one-click", 10, AllRule::new().on_execute(|this| this.get_rule_context().set("express", true)))
+///     .sticky_by("user_id")
+///     .wrap(AllRule::new());
+///
+/// let variant_of = |user: &str| {
+///     let mut rule_context = RuleContext::new();
+///     rule_context.set("user_id", user.to_string());
+///     Engine::all_runner().run(rule_context.clone(), vec![checkout.clone()]);
+///     *rule_context.get::<&str>("checkout").unwrap()
+/// };
+///
+/// assert_eq!(variant_of("user-42"), variant_of("user-42"));
+/// ```
+pub struct WeightedChoice<R> {
+    key: &'static str,
+    alternatives: Vec<Alternative<R>>,
+    sticky_key: Option<&'static str>,
+}
+
+impl<R: Rule<R> + Clone + 'static> WeightedChoice<R> {
+    /// Creates a choice written to `key`, with no alternatives.
+    pub fn new(key: &'static str) -> Self {
+        WeightedChoice {
+            key,
+            alternatives: Vec::new(),
+            sticky_key: None,
+        }
+    }
+
+    /// Adds an alternative writing `value` to the key, picked with a
+    /// probability proportional to its weight.
+    pub fn alternative<T: Clone + 'static>(self, value: T, weight: u32) -> Self {
+        self.push(value, weight, None)
+    }
+
+    /// Adds an alternative writing `value` to the key and firing `rule`.
+    pub fn branch<T: Clone + 'static>(self, value: T, weight: u32, rule: Wrapper<R>) -> Self {
+        self.push(value, weight, Some(rule))
+    }
+
+    /// Derives the pick from the value of `key` instead of drawing it.
+    pub fn sticky_by(mut self, key: &'static str) -> Self {
+        self.sticky_key = Some(key);
+        self
+    }
+
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    /// The weights of the alternatives, in the order they were added.
+    pub fn get_weights(&self) -> Vec<u32> {
+        self.alternatives
+            .iter()
+            .map(|alternative| alternative.weight)
+            .collect()
+    }
+
+    /// The branches of the alternatives that have one, in order.
+    pub fn get_branches(&self) -> Vec<Wrapper<R>> {
+        self.alternatives
+            .iter()
+            .filter_map(|alternative| alternative.branch.clone())
+            .collect()
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then picks an alternative, and returns the rule.
+    /// Nothing is picked when every weight is zero.
+    pub fn wrap(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            let Some(alternative) = self.pick(&rule_context.borrow()) else {
+                return;
+            };
+            (alternative.set)(&mut rule_context.borrow_mut());
+            if let Some(branch) = &alternative.branch {
+                fire_rule(branch, &rule_context);
+            }
+        })
+    }
+
+    fn push<T: Clone + 'static>(
+        mut self,
+        value: T,
+        weight: u32,
+        branch: Option<Wrapper<R>>,
+    ) -> Self {
+        let key = self.key;
+        self.alternatives.push(Alternative {
+            weight,
+            set: Box::new(move |rule_context| rule_context.set(key, value.clone())),
+            branch,
+        });
+        self
+    }
+
+    fn pick(&self, rule_context: &RuleContext) -> Option<&Alternative<R>> {
+        if self
+            .alternatives
+            .iter()
+            .all(|alternative| alternative.weight == 0)
+        {
+            return None;
+        }
+        let subject = self.sticky_key.and_then(|key| {
+            let value = rule_context.lookup(key)?;
+            key_text(value.as_ref())
+        });
+        let fraction = match subject {
+            // Seeding a generator with the hash mixes its bits, which FNV-1a
+            // leaves poorly spread for similar subjects such as sequential ids.
+            Some(subject) => Rng::new(fnv1a(self.key, &subject)).next_f64(),
+            None => rule_context.random(),
+        };
+        let index = pick_index(
+            self.alternatives
+                .iter()
+                .map(|alternative| alternative.weight),
+            fraction,
+        )?;
+        self.alternatives.get(index)
+    }
+}
+
+/// The index of the weight a fraction of `[0, 1)` falls on, when the weights
+/// are laid end to end, or `None` when they are all zero.
+pub(crate) fn pick_index(
+    weights: impl Iterator<Item = u32> + Clone,
+    fraction: f64,
+) -> Option<usize> {
+    let total: u64 = weights.clone().map(u64::from).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut point = (fraction * total as f64) as u64;
+    weights.map(u64::from).position(|weight| {
+        if point < weight {
+            return true;
+        }
+        point -= weight;
+        false
+    })
+}
+
+/// The value of a context key as text, such as the sticky key of a choice
+/// hashed to pick an alternative, or the key of a `Lookup`. Strings and
+/// integers are converted, other types are not.
+pub(crate) fn key_text(value: &dyn Any) -> Option<String> {
+    let value = Primitive::from_any(value)?;
+    match value.as_int() {
+        Some(int) => Some(int.to_string()),
+        None => value.as_str().map(str::to_string),
+    }
+}
+
+/// The 64-bit FNV-1a hash of the choice key and the subject, stable across
+/// processes and Rust versions, unlike the hashers of the standard library.
+fn fnv1a(key: &str, subject: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes().chain([0]).chain(subject.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,107 @@
+use super::{
+    depth_guard::fire_rule, weighted_choice::pick_index, wrap, Rule, RuleCallback, RuleFailure,
+    Wrapper,
+};
+
+/// Decorates a rule so that, when executed, it fires exactly one of its
+/// weighted children, drawn with a probability proportional to its weight,
+/// for percentage rollouts and A/B splits.
+///
+/// The child is drawn with the random generator of the context, see `Rng`, so
+/// setting a seed with `RuleContext::set_seed` makes the draws of a test
+/// deterministic, and a run is replayed from the seed of its report. Children
+/// with a zero weight are never drawn, and nothing is fired when every weight
+/// is zero. The drawn child is fired like any child, so it still only executes
+/// when its evaluation passes. To keep each user in the same branch across
+/// runs, use `WeightedChoice::sticky_by` instead.
+///
+/// The child is fired after the execute callback of the decorated rule and
+/// before its post-execute callback and own children. Like the other
+/// decorators, setting the execute callback of the rule afterwards replaces
+/// the draw.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let variant = |name: &'static str| AllRule::new().on_execute(move |this| this.get_rule_context().set("variant", name));
+///
+/// let rollout = WeightedRandomRule::new()
+///     .child(variant("stable"), 95)
+///     .child(variant("experimental"), 5)
+///     .wrap(AllRule::new());
+///
+/// let rule_context = RuleContext::new();
+/// rule_context.borrow_mut().set_seed(7);
+/// Engine::all_runner().run(rule_context.clone(), vec![rollout]);
+///
+/// assert!(rule_context.get::<&str>("variant").is_some());
+/// ```
+pub struct WeightedRandomRule<R> {
+    children: Vec<(Wrapper<R>, u32)>,
+}
+
+impl<R: Rule<R> + Clone + 'static> WeightedRandomRule<R> {
+    /// Creates a draw with no children.
+    pub fn new() -> Self {
+        WeightedRandomRule {
+            children: Vec::new(),
+        }
+    }
+
+    /// Adds a child drawn with a probability proportional to `weight`.
+    pub fn child(mut self, rule: Wrapper<R>, weight: u32) -> Self {
+        self.children.push((rule, weight));
+        self
+    }
+
+    pub fn get_children(&self) -> Vec<Wrapper<R>> {
+        self.children
+            .iter()
+            .map(|(child, _)| child.clone())
+            .collect()
+    }
+
+    /// The weights of the children, in the order they were added.
+    pub fn get_weights(&self) -> Vec<u32> {
+        self.children.iter().map(|(_, weight)| *weight).collect()
+    }
+
+    /// Replaces the execute callback of the rule with one that runs the
+    /// original callback, then fires a child drawn by weight, and returns the
+    /// rule.
+    pub fn wrap(self, mut rule: Wrapper<R>) -> Wrapper<R>
+    where
+        Wrapper<R>: RuleCallback<RuleType = R>,
+    {
+        let original = wrap(rule.borrow().clone());
+
+        rule.on_execute(move |this| {
+            let rule_context = this.get_rule_context();
+            {
+                let mut original = original.borrow_mut();
+                original.set_rule_context(rule_context.clone());
+                original.run_execute();
+            }
+            if rule_context.has_failed() {
+                return;
+            }
+            if self.children.iter().all(|(_, weight)| *weight == 0) {
+                return;
+            }
+            let fraction = rule_context.borrow().random();
+            let weights = self.children.iter().map(|(_, weight)| *weight);
+            let Some(index) = pick_index(weights, fraction) else {
+                return;
+            };
+            fire_rule(&self.children[index].0, &rule_context);
+        })
+    }
+}
+
+impl<R: Rule<R> + Clone + 'static> Default for WeightedRandomRule<R> {
+    fn default() -> Self {
+        WeightedRandomRule::new()
+    }
+}
@@ -1,9 +1,556 @@
-use crate::rule::{RuleContextWrapper, Wrapper};
+use std::{fmt, marker::PhantomData, time::Duration};
 
+#[cfg(feature = "alloc-tracking")]
+use crate::alloc_tracking::AllocationCount;
+use crate::rule::{
+    depth_guard::fire_rule, Budget, DegradationPolicy, ErrorPolicy, ExecutionTrace, Invariant,
+    Rule, RuleContext, RuleContextWrapper, RuleError, RuleFailure, Severity, Verdicts, Warning,
+    Wrapper,
+};
+use crate::time::Instant;
+
+pub(crate) mod all_rule_runner;
 pub(crate) mod best_first_rule_runner;
 pub(crate) mod chain_rule_runner;
+#[cfg(feature = "rayon")]
+pub(crate) mod parallel_rule_runner;
+#[cfg(feature = "rayon")]
+pub(crate) mod speculative_rule_runner;
+
+/// How a run treats the execute callbacks of the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+    /// Rules whose evaluation passes are executed.
+    #[default]
+    Normal,
+    /// Only evaluation callbacks are called, see `RuleRunner::dry_run`.
+    DryRun,
+}
 
 pub trait RuleRunner {
     type RuleType;
     fn run(&self, rule_context: RuleContextWrapper, rules: Vec<Wrapper<Self::RuleType>>);
+
+    /// Runs the rules like `run` and reports which of them fired.
+    fn run_with_report(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+    ) -> RunReport {
+        let start = rule_context.borrow_mut().start_trace();
+        let seed = rule_context.borrow().get_seed();
+        let collected = rule_context.borrow().get_collected_errors();
+        let degraded = rule_context.borrow().get_degraded_count();
+        let reported = rule_context.borrow().get_reported_warnings();
+        let pushed = rule_context.borrow().get_pushed_verdicts();
+        #[cfg(feature = "alloc-tracking")]
+        let allocations = AllocationCount::current();
+        let started = Instant::now();
+        self.run(rule_context.clone(), rules);
+        let duration = started.elapsed();
+        #[cfg(feature = "alloc-tracking")]
+        let allocations = allocations.elapsed();
+        let trace = rule_context.borrow_mut().finish_trace(start);
+        let error = rule_context.get_error();
+        let mut errors = rule_context.borrow_mut().take_collected_errors(collected);
+        errors.extend(error.clone());
+        let degraded = rule_context.borrow_mut().take_degraded(degraded);
+        let warnings = rule_context.borrow_mut().take_reported_warnings(reported);
+        let verdicts = rule_context.borrow_mut().take_pushed_verdicts(pushed);
+        RunReport {
+            trace,
+            duration,
+            error,
+            errors,
+            degraded,
+            warnings,
+            verdicts,
+            cost: 0,
+            budget_skipped: Vec::new(),
+            compensated: Vec::new(),
+            seed,
+            #[cfg(feature = "alloc-tracking")]
+            allocations,
+        }
+    }
+
+    /// Runs the rules like `run` and returns the failure that stopped the run,
+    /// taking it out of the context.
+    fn try_run(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+    ) -> Result<(), RuleError> {
+        self.run(rule_context.clone(), rules);
+        match rule_context.borrow_mut().take_error() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the rules like `run_with_report`, handling failures according to
+    /// the policy instead of stopping at the first one.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rules = vec![
+    ///     AllRule::new().on_execute(|this| this.get_rule_context().fail(RuleError::failed("no stock"))),
+    ///     AllRule::new().on_execute(|this| this.get_rule_context().set("notified", true)),
+    ///     AllRule::new().on_execute(|this| this.get_rule_context().fail(RuleError::failed("no address"))),
+    /// ];
+    ///
+    /// let rule_context = RuleContext::new();
+    /// let report = Engine::all_runner().run_with_policy(rule_context.clone(), rules, ErrorPolicy::CollectAll);
+    ///
+    /// assert_eq!(
+    ///     report.get_errors(),
+    ///     [RuleError::failed("no stock"), RuleError::failed("no address")]
+    /// );
+    /// assert!(*rule_context.get::<bool>("notified").unwrap());
+    /// assert!(!rule_context.has_failed());
+    /// ```
+    fn run_with_policy(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+        error_policy: ErrorPolicy,
+    ) -> RunReport {
+        let previous = rule_context.borrow().get_error_policy();
+        rule_context.borrow_mut().set_error_policy(error_policy);
+        let report = self.run_with_report(rule_context.clone(), rules);
+        rule_context.borrow_mut().set_error_policy(previous);
+        report
+    }
+
+    /// Runs the rules like `run_with_report`, handling failures of optional
+    /// infrastructure according to the policy, see `DegradationPolicy`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use dredd_rs::rule::*;
+    ///
+    /// let scores = |_: &str| -> Result<Option<u32>, String> { Err("connection refused".to_string()) };
+    /// let rules = vec![
+    ///     BestFirstRule::new()
+    ///         .with_name("scored")
+    ///         .on_execute(Lookup::new("{user}", scores, "score").callback()),
+    ///     BestFirstRule::new()
+    ///         .with_name("manual review")
+    ///         .on_execute(|this| this.get_rule_context().set("review", true)),
+    /// ];
+    ///
+    /// let mut rule_context = RuleContext::new();
+    /// rule_context.set("user", "ana");
+    /// let report = Engine::best_first_runner().run_with_degradation(
+    ///     rule_context.clone(),
+    ///     rules,
+    ///     DegradationPolicy::Degrade,
+    /// );
+    ///
+    /// assert!(report.is_degraded());
+    /// assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+    /// assert_eq!(report.get_trace().get_executed_names(), vec!["manual review"]);
+    /// assert!(*rule_context.get::<bool>("review").unwrap());
+    /// ```
+    fn run_with_degradation(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+        degradation_policy: DegradationPolicy,
+    ) -> RunReport {
+        let previous = rule_context.borrow().get_degradation_policy();
+        rule_context
+            .borrow_mut()
+            .set_degradation_policy(degradation_policy);
+        let report = self.run_with_report(rule_context.clone(), rules);
+        rule_context.borrow_mut().set_degradation_policy(previous);
+        report
+    }
+
+    /// Runs the rules like `run_with_report` under a budget, skipping the
+    /// optional rules that don't fit in it and stopping the run with
+    /// `RuleError::BudgetExceeded` past its other limits, see `Budget`. The
+    /// budget holds for this run only, the one of the context is put back
+    /// after it.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rules = vec![
+    ///     AllRule::new().with_name("score").with_cost(40),
+    ///     AllRule::new().with_name("geo_lookup").with_cost(50).with_optional(true),
+    ///     AllRule::new().with_name("device_lookup").with_cost(30).with_optional(true),
+    /// ];
+    ///
+    /// let budget = Budget::new().with_max_cost(100);
+    /// let report = Engine::all_runner().run_with_budget(RuleContext::new(), rules, budget);
+    ///
+    /// assert_eq!(report.get_trace().get_executed_names(), vec!["score", "geo_lookup"]);
+    /// assert_eq!(report.get_budget_skipped(), ["device_lookup"]);
+    /// assert_eq!(report.get_cost(), 90);
+    ///
+    /// let rules = (0..10).map(|_| AllRule::new()).collect();
+    /// let budget = Budget::new().with_max_fired(3);
+    /// let report = Engine::all_runner().run_with_budget(RuleContext::new(), rules, budget);
+    ///
+    /// assert_eq!(report.get_error(), Some(&RuleError::BudgetExceeded(BudgetLimit::MaxFired(3))));
+    /// assert_eq!(report.get_trace().get_by_outcome(RuleOutcome::Fired).len(), 3);
+    /// ```
+    fn run_with_budget(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+        budget: Budget,
+    ) -> RunReport {
+        let previous = rule_context.borrow_mut().replace_budget(budget);
+        let mut report = self.run_with_report(rule_context.clone(), rules);
+        let state = rule_context.borrow_mut().set_limits(previous);
+        report.cost = state.spent;
+        report.budget_skipped = state.skipped;
+        report
+    }
+
+    /// Runs the rules like `run_with_report`, and when the run fails, calls
+    /// the compensation callbacks of the rules already executed, in reverse
+    /// order, so that the effects of a saga are undone. Only rules whose
+    /// execute callbacks completed are compensated, not the failing one, and
+    /// the verdicts they pushed are dropped from the report.
+    ///
+    /// The compensations of a run that succeeds inside another run with
+    /// compensation are kept, to be called if the outer run fails.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let step = |name: &'static str| {
+    ///     ChainRule::new()
+    ///         .with_name(name)
+    ///         .on_execute(move |this| this.get_rule_context().push_to_list("done", name))
+    ///         .on_compensate(move |this| this.get_rule_context().push_to_list("undone", name))
+    /// };
+    /// let rule = step("reserve")
+    ///     .add_child(step("charge").add_child(ChainRule::new().on_execute(|this| {
+    ///         this.get_rule_context().fail(RuleError::failed("no courier"));
+    ///     })));
+    ///
+    /// let rule_context = RuleContext::new();
+    /// let report = Engine::chain_runner().run_with_compensation(rule_context.clone(), vec![rule]);
+    ///
+    /// assert_eq!(report.get_compensated(), ["charge", "reserve"]);
+    /// assert_eq!(*rule_context.get_list::<&str>("undone").unwrap(), ["charge", "reserve"]);
+    /// ```
+    fn run_with_compensation(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+    ) -> RunReport {
+        let previous = rule_context
+            .borrow_mut()
+            .set_compensations(Some(Vec::new()));
+        let mut report = self.run_with_report(rule_context.clone(), rules);
+        let compensations = rule_context
+            .borrow_mut()
+            .set_compensations(previous)
+            .unwrap_or_default();
+        if report.errors.is_empty() {
+            rule_context.borrow_mut().keep_compensations(compensations);
+            return report;
+        }
+        for compensation in compensations.iter().rev() {
+            compensation.run();
+            report.compensated.push(compensation.get_rule().to_string());
+        }
+        report.verdicts.remove_rules(&report.compensated);
+        report
+    }
+
+    /// Runs the rules like `run_with_report`, then checks the invariants
+    /// against the context, unless the run failed. A violated invariant fails
+    /// the run with `RuleError::InvariantViolated`, left in the context and
+    /// added to the errors of the report, or is reported as a warning when
+    /// made lenient with `Invariant::as_warning`. See `Invariant`.
+    fn run_with_invariants(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+        invariants: &[Invariant],
+    ) -> RunReport {
+        let mut report = self.run_with_report(rule_context.clone(), rules);
+        report.check_invariants(&rule_context, invariants);
+        report
+    }
+
+    /// Walks the rules like `run_with_report`, calling only the evaluation
+    /// callbacks, and reports the rules that would have been executed.
+    ///
+    /// The rules are evaluated against a copy of the context, so it is never
+    /// modified. Evaluations that depend on values set by earlier executions
+    /// see the context as it was before the run.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = ChainRule::new()
+    ///     .with_name("adult_check")
+    ///     .on_execute(|this| this.get_rule_context().set("approved", true));
+    ///
+    /// let rule_context = RuleContext::new();
+    /// let report = Engine::chain_runner().dry_run(rule_context.clone(), vec![rule]);
+    ///
+    /// assert_eq!(report.get_trace().get_executed_names(), vec!["adult_check"]);
+    /// assert!(rule_context.get::<bool>("approved").is_none());
+    /// ```
+    fn dry_run(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+    ) -> RunReport {
+        let dry_context =
+            RuleContext::from_context_map(rule_context.borrow().get_context_map().clone());
+        dry_context
+            .borrow_mut()
+            .inherit_settings(&rule_context.borrow());
+        dry_context.borrow_mut().set_run_mode(RunMode::DryRun);
+        self.run_with_report(dry_context, rules)
+    }
+
+    /// Runs the rules like `try_run`, and when the run fails, restores the
+    /// context to the values it held before the run.
+    fn try_run_atomic(
+        &self,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<Self::RuleType>>,
+    ) -> Result<(), RuleError> {
+        let snapshot = rule_context.borrow().snapshot();
+        let result = self.try_run(rule_context.clone(), rules);
+        if result.is_err() {
+            rule_context.borrow_mut().restore(snapshot);
+        }
+        result
+    }
+}
+
+/// The outcome of `RuleRunner::run_with_report`.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rule = ChainRule::new().with_name("adult_check").on_eval(|_| true);
+///
+/// let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+///
+/// assert_eq!(report.get_trace().get_executed_names(), vec!["adult_check"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    trace: ExecutionTrace,
+    duration: Duration,
+    error: Option<RuleError>,
+    errors: Vec<RuleError>,
+    degraded: Vec<RuleError>,
+    warnings: Vec<Warning>,
+    verdicts: Verdicts,
+    cost: u64,
+    budget_skipped: Vec<String>,
+    compensated: Vec<String>,
+    seed: u64,
+    #[cfg(feature = "alloc-tracking")]
+    allocations: AllocationCount,
+}
+
+impl RunReport {
+    pub fn get_trace(&self) -> &ExecutionTrace {
+        &self.trace
+    }
+
+    /// The seed of the random generator of the context when the run started.
+    /// Setting it with `RuleContext::set_seed` before running the rules again
+    /// replays the same draws, see `Rng`.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Wall time of the whole run.
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Heap allocations made during the run. Requires `TrackingAllocator` to
+    /// be installed.
+    #[cfg(feature = "alloc-tracking")]
+    pub fn get_allocations(&self) -> u64 {
+        self.allocations.allocations
+    }
+
+    /// Bytes requested by the allocations counted in `get_allocations`.
+    #[cfg(feature = "alloc-tracking")]
+    pub fn get_allocated_bytes(&self) -> u64 {
+        self.allocations.bytes
+    }
+
+    /// The failure that stopped the run, which is left in the context.
+    pub fn get_error(&self) -> Option<&RuleError> {
+        self.error.as_ref()
+    }
+
+    /// Every failure of the run, in order: the one that stopped it, or all of
+    /// them when run with `ErrorPolicy::CollectAll`.
+    pub fn get_errors(&self) -> &[RuleError] {
+        &self.errors
+    }
+
+    /// Whether rules were skipped because optional infrastructure failed, see
+    /// `DegradationPolicy::Degrade`.
+    pub fn is_degraded(&self) -> bool {
+        !self.degraded.is_empty()
+    }
+
+    /// The failures of optional infrastructure the run degraded on, in
+    /// order.
+    pub fn get_degraded(&self) -> &[RuleError] {
+        &self.degraded
+    }
+
+    /// The warnings reported during the run, in order, see `RuleWarnings`.
+    pub fn get_warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// The verdicts pushed during the run, see `RuleVerdicts`.
+    pub fn get_verdicts(&self) -> &Verdicts {
+        &self.verdicts
+    }
+
+    /// The cost charged to the budget of a run with `run_with_budget`.
+    pub fn get_cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// The names, or ids, of the optional rules skipped because the budget
+    /// of the run was exhausted.
+    pub fn get_budget_skipped(&self) -> &[String] {
+        &self.budget_skipped
+    }
+
+    /// The names, or ids, of the rules whose compensation callback was
+    /// called after a failure of a run with `run_with_compensation`, in the
+    /// order they were called.
+    pub fn get_compensated(&self) -> &[String] {
+        &self.compensated
+    }
+
+    /// Sums the run up for the caller: it failed when a rule failed or
+    /// reported a `Severity::Critical` warning, and passed with warnings when
+    /// a rule reported a `Severity::Warning` one. `Severity::Info` warnings
+    /// don't count.
+    ///
+    /// Example:
+    /// ```rust
+    /// use dredd_rs::rule::*;
+    ///
+    /// let rule = AllRule::new().on_execute(|this| {
+    ///     this.get_rule_context().warn(Severity::Warning, "address not verified");
+    /// });
+    ///
+    /// let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+    ///
+    /// assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+    /// ```
+    pub fn get_status(&self) -> RunStatus {
+        let worst = self
+            .warnings
+            .iter()
+            .map(Warning::get_severity)
+            .max()
+            .unwrap_or(Severity::Info);
+        if !self.errors.is_empty() || worst == Severity::Critical {
+            RunStatus::Fail
+        } else if worst == Severity::Warning {
+            RunStatus::PassWithWarnings
+        } else {
+            RunStatus::Pass
+        }
+    }
+}
+
+impl RunReport {
+    /// Checks the invariants once the run is over, see
+    /// `RuleRunner::run_with_invariants`.
+    pub(crate) fn check_invariants(
+        &mut self,
+        rule_context: &RuleContextWrapper,
+        invariants: &[Invariant],
+    ) {
+        if self.error.is_some() {
+            return;
+        }
+        let reported = rule_context.borrow().get_reported_warnings();
+        for invariant in invariants {
+            if invariant.check(&rule_context.borrow()) {
+                continue;
+            }
+            let description = invariant.get_description().to_string();
+            if invariant.get_severity() == Severity::Critical {
+                let error = RuleError::InvariantViolated(description);
+                rule_context.borrow_mut().fail(error.clone());
+                self.error.get_or_insert(error.clone());
+                self.errors.push(error);
+            } else {
+                rule_context.borrow().push_warning(
+                    invariant.get_severity(),
+                    format!("invariant violated: {description}"),
+                );
+            }
+        }
+        let warnings = rule_context.borrow_mut().take_reported_warnings(reported);
+        self.warnings.extend(warnings);
+    }
+}
+
+/// The outcome of a run, see `RunReport::get_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunStatus {
+    Pass,
+    PassWithWarnings,
+    Fail,
+}
+
+impl fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunStatus::Pass => write!(f, "pass"),
+            RunStatus::PassWithWarnings => write!(f, "pass with warnings"),
+            RunStatus::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// Fires top-level rules of any type as siblings, like `AllRuleRunner`, for
+/// the engines that pick which rules to fire.
+pub(crate) struct SiblingRunner<R>(PhantomData<R>);
+
+impl<R> SiblingRunner<R> {
+    pub(crate) fn new() -> Self {
+        SiblingRunner(PhantomData)
+    }
+}
+
+impl<R: Rule<R>> RuleRunner for SiblingRunner<R> {
+    type RuleType = R;
+
+    fn run(&self, rule_context: RuleContextWrapper, rules: Vec<Wrapper<R>>) {
+        for rule in rules {
+            fire_rule(&rule, &rule_context);
+            if rule_context.has_failed() {
+                break;
+            }
+        }
+    }
 }
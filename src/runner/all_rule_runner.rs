@@ -0,0 +1,19 @@
+use crate::rule::{
+    all_rule::AllRule, depth_guard::fire_rule, RuleContextWrapper, RuleFailure, Wrapper,
+};
+
+use super::RuleRunner;
+
+pub struct AllRuleRunner;
+
+impl RuleRunner for AllRuleRunner {
+    type RuleType = AllRule;
+    fn run(&self, rule_context: RuleContextWrapper, rules: Vec<Wrapper<Self::RuleType>>) {
+        for rule in rules {
+            fire_rule(&rule, &rule_context);
+            if rule_context.has_failed() {
+                break;
+            }
+        }
+    }
+}
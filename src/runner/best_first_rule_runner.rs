@@ -1,4 +1,7 @@
-use crate::rule::{best_first_rule::BestFirstRule, Rule, RuleContextWrapper, Wrapper};
+use crate::rule::{
+    best_first_rule::BestFirstRule, depth_guard::fire_rule, RuleContextWrapper, RuleFailure,
+    Wrapper,
+};
 
 use super::RuleRunner;
 
@@ -9,9 +12,7 @@ impl RuleRunner for BestFirstRuleRunner {
     fn run(&self, rule_context: RuleContextWrapper, rules: Vec<Wrapper<Self::RuleType>>) {
         if !rules.is_empty() {
             for rule in rules {
-                let mut rule_borrow = rule.borrow_mut();
-                rule_borrow.set_rule_context(rule_context.clone());
-                if !rule_borrow.fire() {
+                if !fire_rule(&rule, &rule_context) || rule_context.has_failed() {
                     break;
                 }
             }
@@ -1,4 +1,4 @@
-use crate::rule::{chain_rule::ChainRule, Rule, RuleContextWrapper, Wrapper};
+use crate::rule::{chain_rule::ChainRule, depth_guard::fire_rule, RuleContextWrapper, Wrapper};
 
 use super::RuleRunner;
 
@@ -9,9 +9,7 @@ impl RuleRunner for ChainRuleRunner {
     fn run(&self, rule_context: RuleContextWrapper, rules: Vec<Wrapper<Self::RuleType>>) {
         if rules.len() <= 1 {
             if let Some(rule) = rules.first() {
-                let mut rule = rule.borrow_mut();
-                rule.set_rule_context(rule_context);
-                rule.fire();
+                fire_rule(rule, &rule_context);
             }
         } else {
             panic!("ChainRuleRunner does not support sibling rules, only child rules.");
@@ -0,0 +1,34 @@
+use rayon::prelude::*;
+
+use crate::rule::{parallel_rule::ParallelRule, SharedRuleContext, SyncWrapper};
+
+/// Fires sibling `ParallelRule`s concurrently on rayon's thread pool.
+///
+/// # Merge strategy
+///
+/// Every rule is fired against its own staging layer on top of the given context:
+/// it reads the context as it was when the run started plus its own writes, and
+/// never observes the writes of its siblings. Once all siblings have finished,
+/// the staging layers are merged back into the context in the order the rules
+/// were passed in, so when two siblings write the same key the later one wins.
+/// Children of a fired rule are run the same way on top of their parent's layer.
+pub struct ParallelRuleRunner;
+
+impl ParallelRuleRunner {
+    pub fn run(&self, rule_context: SharedRuleContext, rules: Vec<SyncWrapper<ParallelRule>>) {
+        let staged: Vec<SharedRuleContext> = rules
+            .par_iter()
+            .map(|rule| {
+                let staged_context = rule_context.stage();
+                let mut rule = rule.lock().unwrap();
+                rule.set_rule_context(staged_context.clone());
+                rule.fire();
+                staged_context
+            })
+            .collect();
+
+        for staged_context in staged {
+            staged_context.commit();
+        }
+    }
+}
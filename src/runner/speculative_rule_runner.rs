@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use rayon::prelude::*;
+
+use crate::rule::{parallel_rule::ParallelRule, MetadataAccess, SharedRuleContext, SyncWrapper};
+
+/// How often the evaluation of a rule passed.
+#[derive(Debug, Clone, Copy, Default)]
+struct HitRate {
+    hits: u64,
+    evaluations: u64,
+}
+
+impl HitRate {
+    /// The estimated chance of passing, `0.5` for rules never evaluated.
+    fn get_likelihood(&self) -> f64 {
+        (self.hits + 1) as f64 / (self.evaluations + 2) as f64
+    }
+
+    fn get(&self) -> f64 {
+        match self.evaluations {
+            0 => 0.0,
+            evaluations => self.hits as f64 / evaluations as f64,
+        }
+    }
+}
+
+/// Fires sibling `ParallelRule`s with best-first semantics, evaluating
+/// several of them concurrently to cut the latency of expensive evaluations.
+///
+/// Like the `BestFirstRuleRunner`, only the first sibling, in order, whose
+/// evaluation passes is executed, then its children are run the same way.
+/// Instead of evaluating the siblings one after the other, the runner
+/// evaluates up to `width` of them at once on rayon's thread pool: the first
+/// sibling not evaluated yet, and the ones most likely to pass according to
+/// the hit rates recorded by previous runs. Once the outcome of every sibling
+/// before a passing one is known, that sibling is executed, so the result is
+/// the same as evaluating them in order. Siblings that were never evaluated
+/// count as passing half of the time.
+///
+/// Evaluations run against a staging layer that is discarded, so they must
+/// not rely on writing to the context, and siblings evaluated speculatively
+/// may be evaluated even though an earlier sibling passes. Hit rates are
+/// tracked by rule id, or by name when the rule has no id, and clones of the
+/// runner share them.
+///
+/// Example:
+/// ```rust
+/// use dredd_rs::rule::*;
+///
+/// let rules: Vec<_> = (0..4u32)
+///     .map(|tier| {
+///         ParallelRule::new()
+///             .with_name(&format!("tier_{tier}"))
+///             .on_eval(move |this| *this.get_rule_context().get::<u32>("score").unwrap() >= 300 - tier * 100)
+///             .on_execute(move |this| this.get_rule_context().set("tier", tier))
+///     })
+///     .collect();
+///
+/// let runner = Engine::speculative_runner(2);
+/// let rule_context = SharedRuleContext::new();
+/// rule_context.set("score", 120u32);
+/// runner.run(rule_context.clone(), rules);
+///
+/// assert_eq!(*rule_context.get::<u32>("tier").unwrap(), 2);
+/// assert_eq!(runner.get_hit_rate("tier_2"), Some(1.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpeculativeRuleRunner {
+    width: usize,
+    hit_rates: Arc<Mutex<HashMap<String, HitRate>>>,
+}
+
+impl SpeculativeRuleRunner {
+    /// Creates a runner evaluating at most `width` siblings at once. A width
+    /// of `1`, or `0`, evaluates them one after the other.
+    pub fn new(width: usize) -> Self {
+        SpeculativeRuleRunner {
+            width: width.max(1),
+            hit_rates: Default::default(),
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    /// The share of the evaluations of the rule, by id or name, that passed.
+    pub fn get_hit_rate(&self, rule: &str) -> Option<f64> {
+        let hit_rates = self.hit_rates.lock().unwrap();
+        hit_rates.get(rule).map(HitRate::get)
+    }
+
+    pub fn run(&self, rule_context: SharedRuleContext, rules: Vec<SyncWrapper<ParallelRule>>) {
+        let keys: Vec<Option<String>> = rules
+            .iter()
+            .map(|rule| {
+                let rule = rule.lock().unwrap();
+                rule.get_id().or(rule.get_name()).map(str::to_string)
+            })
+            .collect();
+        let mut results: Vec<Option<bool>> = vec![None; rules.len()];
+
+        loop {
+            // Siblings are settled in order: the first one not known to fail
+            // is either executed or still to be evaluated.
+            match results.iter().position(|result| *result != Some(false)) {
+                None => return,
+                Some(position) if results[position] == Some(true) => {
+                    let rule = &rules[position];
+                    let children = {
+                        let mut rule = rule.lock().unwrap();
+                        rule.set_rule_context(rule_context.clone());
+                        rule.run_execute_phases();
+                        rule.get_children()
+                    };
+                    self.run(rule_context, children);
+                    return;
+                }
+                Some(position) => {
+                    let batch = self.get_batch(position, &keys, &results);
+                    let evaluated: Vec<(usize, bool)> = batch
+                        .into_par_iter()
+                        .map(|index| {
+                            let mut rule = rules[index].lock().unwrap();
+                            rule.set_rule_context(rule_context.stage());
+                            (index, rule.run_eval())
+                        })
+                        .collect();
+                    self.record(&keys, &evaluated);
+                    for (index, result) in evaluated {
+                        results[index] = Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The sibling at `first`, followed by the siblings not evaluated yet that
+    /// are the most likely to pass.
+    fn get_batch(
+        &self,
+        first: usize,
+        keys: &[Option<String>],
+        results: &[Option<bool>],
+    ) -> Vec<usize> {
+        let hit_rates = self.hit_rates.lock().unwrap();
+        let mut candidates: Vec<(usize, f64)> = (first + 1..results.len())
+            .filter(|&index| results[index].is_none())
+            .map(|index| {
+                let hit_rate = keys[index]
+                    .as_ref()
+                    .and_then(|key| hit_rates.get(key))
+                    .copied()
+                    .unwrap_or_default();
+                (index, hit_rate.get_likelihood())
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut batch = vec![first];
+        batch.extend(
+            candidates
+                .into_iter()
+                .take(self.width - 1)
+                .map(|(index, _)| index),
+        );
+        batch
+    }
+
+    fn record(&self, keys: &[Option<String>], evaluated: &[(usize, bool)]) {
+        let mut hit_rates = self.hit_rates.lock().unwrap();
+        for &(index, result) in evaluated {
+            if let Some(key) = &keys[index] {
+                let hit_rate = hit_rates.entry(key.clone()).or_default();
+                hit_rate.evaluations += 1;
+                hit_rate.hits += u64::from(result);
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Captures a fraction of runs as samples for offline analysis.
+//!
+//! A `Sampler` runs rule sets like a runner does and, for the configured
+//! fraction of runs, writes a `Sample` to a `SampleSink`: the input context,
+//! the names of the rules executed and the values the run set. The samples
+//! form a labeled dataset, for example to study the thresholds of the rules.
+//!
+//! Samples are JSON friendly: context values are captured when they are of a
+//! primitive number type, `bool`, `String`, `&'static str` or
+//! `serde_json::Value`, and other values are left out. `JsonLinesSink` writes
+//! one sample per line to any writer, such as a file; other destinations,
+//! such as a message queue, are reached by implementing `SampleSink`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::sampling::{JsonLinesSink, Sampler};
+//!
+//! // Sample one run out of four.
+//! let mut sampler = Sampler::new(JsonLinesSink::new(Vec::new())).with_rate(0.25);
+//!
+//! for age in 0..8i64 {
+//!     let rule = ChainRule::new()
+//!         .with_name("adult")
+//!         .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 5)
+//!         .on_execute(|this| this.get_rule_context().set("approved", true));
+//!     let mut rule_context = RuleContext::new();
+//!     rule_context.set("age", age);
+//!     sampler.run(&Engine::chain_runner(), rule_context, vec![rule]).unwrap();
+//! }
+//!
+//! assert_eq!(sampler.get_sampled_runs(), 2);
+//! let lines = String::from_utf8(sampler.into_sink().into_inner()).unwrap();
+//! assert_eq!(
+//!     lines.lines().last().unwrap(),
+//!     r#"{"input":{"age":7},"fired":["adult"],"outputs":{"approved":true}}"#
+//! );
+//! ```
+
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    rule::{ContextSnapshot, RuleContextWrapper, RuleRunner, Wrapper},
+    scenario::get_value,
+};
+
+/// One captured run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    input: Map<String, Value>,
+    fired: Vec<String>,
+    outputs: Map<String, Value>,
+}
+
+impl Sample {
+    pub fn new(input: Map<String, Value>, fired: Vec<String>, outputs: Map<String, Value>) -> Self {
+        Sample {
+            input,
+            fired,
+            outputs,
+        }
+    }
+
+    /// The context the rules were run with.
+    pub fn get_input(&self) -> &Map<String, Value> {
+        &self.input
+    }
+
+    /// The names of the rules that were executed, in order.
+    pub fn get_fired(&self) -> &[String] {
+        &self.fired
+    }
+
+    /// The values that were added or replaced by the run.
+    pub fn get_outputs(&self) -> &Map<String, Value> {
+        &self.outputs
+    }
+}
+
+/// Where samples are written.
+pub trait SampleSink {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()>;
+}
+
+/// Collects samples in memory.
+impl SampleSink for Vec<Sample> {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        self.push(sample.clone());
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&Sample) -> io::Result<()>> SampleSink for F {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        self(sample)
+    }
+}
+
+/// Writes every sample as a line of JSON.
+#[derive(Debug)]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> SampleSink for JsonLinesSink<W> {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, sample)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Runs rule sets and writes a sample of the runs to a sink.
+#[derive(Debug)]
+pub struct Sampler<S: SampleSink> {
+    sink: S,
+    rate: f64,
+    runs: u64,
+    sampled_runs: u64,
+}
+
+impl<S: SampleSink> Sampler<S> {
+    /// Creates a sampler capturing every run.
+    pub fn new(sink: S) -> Self {
+        Sampler {
+            sink,
+            rate: 1.0,
+            runs: 0,
+            sampled_runs: 0,
+        }
+    }
+
+    /// Captures the given fraction of runs, between `0.0` and `1.0`. Sampled
+    /// runs are spread evenly: with `0.25`, every fourth run is captured.
+    pub fn with_rate(mut self, rate: f64) -> Self {
+        self.rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Runs the rules with the given runner and, when the run is sampled,
+    /// writes it to the sink. Only errors of the sink are returned.
+    pub fn run<R: RuleRunner>(
+        &mut self,
+        runner: &R,
+        rule_context: RuleContextWrapper,
+        rules: Vec<Wrapper<R::RuleType>>,
+    ) -> io::Result<()> {
+        let sampled =
+            ((self.runs + 1) as f64 * self.rate).floor() > (self.runs as f64 * self.rate).floor();
+        self.runs += 1;
+        if !sampled {
+            runner.run(rule_context, rules);
+            return Ok(());
+        }
+
+        let input = rule_context.borrow().snapshot();
+        let report = runner.run_with_report(rule_context.clone(), rules);
+        let outputs = input.get_changes(&rule_context.borrow().snapshot());
+
+        self.sampled_runs += 1;
+        self.sink.write_sample(&Sample {
+            input: to_json(&input),
+            fired: report
+                .get_trace()
+                .get_executed_names()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            outputs: to_json(&outputs),
+        })
+    }
+
+    pub fn get_runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// How many runs were written to the sink.
+    pub fn get_sampled_runs(&self) -> u64 {
+        self.sampled_runs
+    }
+
+    pub fn get_sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+}
+
+fn to_json(snapshot: &ContextSnapshot) -> Map<String, Value> {
+    snapshot
+        .context_map
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), get_value(value.as_ref())?)))
+        .collect()
+}
@@ -0,0 +1,372 @@
+//! Data-driven tests for rule sets.
+//!
+//! A scenario suite lists, for every scenario, the context the rules are run
+//! with, the rules expected to fire and the values expected in the context
+//! afterwards. Running a suite produces a `ScenarioReport` that can be written
+//! as a JUnit XML report for CI tools.
+//!
+//! Suites are read from JSON with `ScenarioSuite::from_json`. The types
+//! implement `Deserialize`, so any other serde format, such as YAML, works too.
+//!
+//! Input values are stored as `bool`, `i64`, `f64` or `String`; arrays and
+//! objects are stored as `serde_json::Value`. Expected outputs are compared
+//! against context values of any primitive number type, `bool`, `String`,
+//! `&'static str` or `serde_json::Value`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::loader::{self, CallbackRegistry};
+//! use dredd_rs::rule::*;
+//! use dredd_rs::scenario::ScenarioSuite;
+//!
+//! let mut registry = CallbackRegistry::new();
+//! registry
+//!     .condition("is_adult", |ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+//!     .action("approve", |ctx| ctx.set("approved", true));
+//!
+//! let rules = loader::from_json(r#"{
+//!     "type": "chain",
+//!     "state": "active",
+//!     "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }]
+//! }"#, &registry).unwrap();
+//!
+//! let suite = ScenarioSuite::from_json(r#"{
+//!     "name": "adults",
+//!     "scenarios": [{
+//!         "name": "adult is approved",
+//!         "given": { "age": 42 },
+//!         "expect": { "fired": ["adult"], "outputs": { "approved": true } }
+//!     }]
+//! }"#).unwrap();
+//!
+//! let report = suite.run(&rules);
+//! assert!(report.is_success());
+//! println!("{}", report.to_junit_xml());
+//! ```
+
+use std::{
+    any::Any,
+    fmt::Write as _,
+    panic::{self, AssertUnwindSafe},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+pub(crate) use crate::rule::intern;
+use crate::{
+    loader::LoadedRules,
+    rule::{value_types::Primitive, GetSet, RuleContext, RuleContextWrapper, RunReport},
+    time::Instant,
+};
+
+/// A named list of scenarios.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioSuite {
+    pub name: String,
+    #[serde(default)]
+    pub scenarios: Vec<Scenario>,
+}
+
+/// A single scenario: the input context and what is expected after the run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub given: Map<String, Value>,
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+/// What a scenario expects. Expectations that are not given are not checked.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Expectation {
+    /// The names of the rules that must be executed, in order.
+    #[serde(default)]
+    pub fired: Option<Vec<String>>,
+    /// The names of rules that must not be executed.
+    #[serde(default)]
+    pub not_fired: Vec<String>,
+    /// The values the context must hold after the run.
+    #[serde(default)]
+    pub outputs: Map<String, Value>,
+}
+
+impl ScenarioSuite {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Runs every scenario against loaded rules.
+    pub fn run(&self, rules: &LoadedRules) -> ScenarioReport {
+        self.run_with(|rule_context| rules.run_with_report(rule_context))
+    }
+
+    /// Runs every scenario with a function that runs the rule set under test
+    /// against the given context and returns the report of the run.
+    pub fn run_with(&self, run: impl Fn(RuleContextWrapper) -> RunReport) -> ScenarioReport {
+        let results = self
+            .scenarios
+            .iter()
+            .map(|scenario| scenario.run(&run))
+            .collect();
+
+        ScenarioReport {
+            name: self.name.clone(),
+            results,
+        }
+    }
+}
+
+impl Scenario {
+    fn run(&self, run: &impl Fn(RuleContextWrapper) -> RunReport) -> ScenarioResult {
+        let mut rule_context = RuleContext::new();
+        for (key, value) in &self.given {
+            set_value(&mut rule_context, intern(key), value);
+        }
+
+        let started = Instant::now();
+        let report = panic::catch_unwind(AssertUnwindSafe(|| run(rule_context.clone())));
+        let duration = started.elapsed();
+
+        let mut result = ScenarioResult {
+            name: self.name.clone(),
+            failures: Vec::new(),
+            error: None,
+            duration,
+        };
+
+        let report = match report {
+            Ok(report) => report,
+            Err(payload) => {
+                result.error = Some(panic_message(payload.as_ref()));
+                return result;
+            }
+        };
+
+        let fired = report.get_trace().get_executed_names();
+        if let Some(expected) = &self.expect.fired {
+            if fired != *expected {
+                result
+                    .failures
+                    .push(format!("expected fired rules {expected:?}, got {fired:?}"));
+            }
+        }
+        for name in &self.expect.not_fired {
+            if fired.contains(&name.as_str()) {
+                result
+                    .failures
+                    .push(format!("expected rule `{name}` not to fire"));
+            }
+        }
+
+        let rule_context = rule_context.borrow();
+        for (key, expected) in &self.expect.outputs {
+            match rule_context.lookup(key) {
+                None => result.failures.push(format!("missing output `{key}`")),
+                Some(value) => match get_value(value.as_ref()) {
+                    Some(actual) if actual == *expected => {}
+                    Some(actual) => result
+                        .failures
+                        .push(format!("expected `{key}` to be {expected}, got {actual}")),
+                    None => result
+                        .failures
+                        .push(format!("output `{key}` has a type scenarios can't read")),
+                },
+            }
+        }
+
+        result
+    }
+}
+
+/// The outcome of running a scenario suite.
+#[derive(Debug, Clone)]
+pub struct ScenarioReport {
+    name: String,
+    results: Vec<ScenarioResult>,
+}
+
+impl ScenarioReport {
+    pub fn get_results(&self) -> &[ScenarioResult] {
+        &self.results
+    }
+
+    /// Whether every scenario passed.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(ScenarioResult::is_success)
+    }
+
+    /// Writes the report in the JUnit XML format.
+    pub fn to_junit_xml(&self) -> String {
+        let failures = self
+            .results
+            .iter()
+            .filter(|result| !result.failures.is_empty() && result.error.is_none())
+            .count();
+        let errors = self.results.iter().filter(|r| r.error.is_some()).count();
+        let time: Duration = self.results.iter().map(|result| result.duration).sum();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{:.6}\">",
+            escape(&self.name),
+            self.results.len(),
+            time.as_secs_f64()
+        );
+        for result in &self.results {
+            let _ = write!(
+                xml,
+                "  <testcase name=\"{}\" classname=\"{}\" time=\"{:.6}\"",
+                escape(&result.name),
+                escape(&self.name),
+                result.duration.as_secs_f64()
+            );
+            if let Some(error) = &result.error {
+                let _ = writeln!(
+                    xml,
+                    ">\n    <error message=\"{}\"/>\n  </testcase>",
+                    escape(error)
+                );
+            } else if !result.failures.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>",
+                    escape(&result.failures[0]),
+                    escape(&result.failures.join("\n"))
+                );
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+/// The outcome of a single scenario.
+#[derive(Debug, Clone)]
+pub struct ScenarioResult {
+    name: String,
+    failures: Vec<String>,
+    error: Option<String>,
+    duration: Duration,
+}
+
+impl ScenarioResult {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The expectations that were not met.
+    pub fn get_failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    /// The panic message, if running the rules panicked.
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty() && self.error.is_none()
+    }
+}
+
+pub(crate) fn set_value(rule_context: &mut RuleContextWrapper, key: &'static str, value: &Value) {
+    match value {
+        Value::Bool(value) => rule_context.set(key, *value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => rule_context.set(key, value),
+            None => rule_context.set(key, number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(value) => rule_context.set(key, value.clone()),
+        value => rule_context.set(key, value.clone()),
+    }
+}
+
+/// The values of a context that JSON can represent, as a JSON object.
+#[cfg(any(feature = "cli", feature = "server", feature = "wasm"))]
+pub(crate) fn context_to_json(rule_context: &RuleContextWrapper) -> Value {
+    let rule_context = rule_context.borrow();
+    let values = rule_context
+        .get_context_map()
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), get_value(value.as_ref())?)))
+        .collect::<Map<_, _>>();
+    Value::Object(values)
+}
+
+/// A run report, with the context the rules ran against, as JSON.
+#[cfg(any(feature = "server", feature = "wasm"))]
+pub(crate) fn report_to_json(report: &RunReport, rule_context: &RuleContextWrapper) -> Value {
+    use serde_json::json;
+
+    let trace = report
+        .get_trace()
+        .get_entries()
+        .iter()
+        .map(|entry| {
+            json!({
+                "id": entry.get_id(),
+                "name": entry.get_name(),
+                "depth": entry.get_depth(),
+                "outcome": entry.get_outcome().to_string(),
+                "duration_us": entry.get_duration().as_micros() as u64,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "fired": report.get_trace().get_executed_names(),
+        "trace": trace,
+        "errors": report.get_errors().iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "warnings": report.get_warnings().iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "context": context_to_json(rule_context),
+        "duration_us": report.get_duration().as_micros() as u64,
+    })
+}
+
+pub(crate) fn get_value(value: &dyn Any) -> Option<Value> {
+    let Some(primitive) = Primitive::from_any(value) else {
+        return value.downcast_ref::<Value>().cloned();
+    };
+    if let Some(int) = primitive.as_int() {
+        return i64::try_from(int)
+            .map(Value::from)
+            .or_else(|_| u64::try_from(int).map(Value::from))
+            .ok();
+    }
+    if let Some(float) = primitive.as_float() {
+        return Some(Value::from(float));
+    }
+    if let Some(text) = primitive.as_str() {
+        return Some(Value::from(text));
+    }
+    match primitive {
+        Primitive::Bool(value) => Some(Value::from(value)),
+        _ => None,
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "rules panicked".to_string())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,116 @@
+//! Generates a machine-readable description of the context keys a rule set uses.
+//!
+//! Keys come from the `KEYS` slices generated by `context_keys!`. Rule callbacks
+//! are plain closures, so which rules read or write a key is declared alongside.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::context_keys;
+//! use dredd_rs::schema::ContextSchema;
+//!
+//! context_keys! {
+//!     pub mod checkout {
+//!         /// Customer age in years.
+//!         AGE: i64 = "checkout.age";
+//!         APPROVED: bool = "checkout.approved";
+//!     }
+//! }
+//!
+//! let json = ContextSchema::new()
+//!     .keys(checkout::KEYS)
+//!     .reads("adult_check", [checkout::AGE.name()])
+//!     .writes("adult_check", [checkout::APPROVED.name()])
+//!     .to_json();
+//!
+//! assert!(json.contains("\"checkout.age\""));
+//! ```
+
+use serde::Serialize;
+
+use crate::rule::ContextKeyInfo;
+
+/// The schema of a single key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeySchema {
+    pub name: String,
+    /// The declared Rust type, or `None` for keys only known from `reads`/`writes`.
+    #[serde(rename = "type")]
+    pub value_type: Option<String>,
+    pub doc: String,
+    pub read_by: Vec<String>,
+    pub written_by: Vec<String>,
+}
+
+/// Collects key declarations and rule usages into a schema document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContextSchema {
+    keys: Vec<KeySchema>,
+}
+
+impl ContextSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every key of a module generated by `context_keys!`.
+    pub fn keys(mut self, keys: &[ContextKeyInfo]) -> Self {
+        for key in keys {
+            let schema = self.key_mut(key.name());
+            schema.value_type = Some(key.value_type().to_string());
+            schema.doc = key.doc().to_string();
+        }
+        self
+    }
+
+    /// Declares the keys read by a rule.
+    pub fn reads<'a>(mut self, rule: &str, keys: impl IntoIterator<Item = &'a str>) -> Self {
+        for key in keys {
+            push_unique(&mut self.key_mut(key).read_by, rule);
+        }
+        self
+    }
+
+    /// Declares the keys written by a rule.
+    pub fn writes<'a>(mut self, rule: &str, keys: impl IntoIterator<Item = &'a str>) -> Self {
+        for key in keys {
+            push_unique(&mut self.key_mut(key).written_by, rule);
+        }
+        self
+    }
+
+    pub fn get_keys(&self) -> &[KeySchema] {
+        &self.keys
+    }
+
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("schema serialization can't fail")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("schema serialization can't fail")
+    }
+
+    fn key_mut(&mut self, name: &str) -> &mut KeySchema {
+        let index = match self.keys.iter().position(|key| key.name == name) {
+            Some(index) => index,
+            None => {
+                self.keys.push(KeySchema {
+                    name: name.to_string(),
+                    value_type: None,
+                    doc: String::new(),
+                    read_by: Vec::new(),
+                    written_by: Vec::new(),
+                });
+                self.keys.len() - 1
+            }
+        };
+        &mut self.keys[index]
+    }
+}
+
+fn push_unique(rules: &mut Vec<String>, rule: &str) {
+    if !rules.iter().any(|r| r == rule) {
+        rules.push(rule.to_string());
+    }
+}
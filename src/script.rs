@@ -0,0 +1,208 @@
+//! Rule conditions and actions written as [Rhai](https://rhai.rs) scripts,
+//! so rule logic can be edited without recompiling.
+//!
+//! Scripts see the `RuleContext` as `ctx`, and read and write its keys as
+//! properties, `ctx.total`, or by index, `ctx["order.total"]`. Integers,
+//! floats, booleans, strings and chars are read as the matching Rhai types,
+//! and missing keys as `()`. Scripts set integers as `i64`, floats as `f64`,
+//! strings as `String`, and any other value as a `rhai::Dynamic`.
+//!
+//! A script that fails while running records a `RuleError::Failed` in the
+//! context, and a failed condition doesn't hold. The failure is recorded with
+//! `RuleFailure::fail_unavailable`, so a run may degrade on it instead, see
+//! `DegradationPolicy`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::script::RuleScripts;
+//!
+//! let rule = AllRule::new()
+//!     .eval_script("ctx.total > 100").unwrap()
+//!     .execute_script("ctx.discount = 0.1; ctx.tier = if ctx.total > 500 { \"gold\" } else { \"silver\" };").unwrap();
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("total", 250i64);
+//! Engine::all_runner().run(rule_context.clone(), vec![rule]);
+//!
+//! assert_eq!(*rule_context.get::<f64>("discount").unwrap(), 0.1);
+//! assert_eq!(*rule_context.get::<String>("tier").unwrap(), "silver");
+//! ```
+
+use std::{any::Any, error::Error, fmt, rc::Rc};
+
+use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Scope, AST};
+
+use crate::rule::{
+    intern, value_types::Primitive, GetSet, Rule, RuleCallback, RuleContextWrapper, RuleError,
+    RuleFailure, Wrapper,
+};
+
+thread_local! {
+    static ENGINE: Engine = engine();
+}
+
+/// Why a script could not be compiled or run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The script is not valid Rhai.
+    Compile(String),
+    /// The script failed while running.
+    Runtime(String),
+    /// A condition script returned something else than a boolean.
+    NotBoolean(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Compile(message) => write!(f, "invalid script: {message}"),
+            ScriptError::Runtime(message) => write!(f, "script failed: {message}"),
+            ScriptError::NotBoolean(type_name) => {
+                write!(f, "condition script returned {type_name}, not a boolean")
+            }
+        }
+    }
+}
+
+impl Error for ScriptError {}
+
+/// A compiled script, ready to be run against a context.
+#[derive(Debug, Clone)]
+pub struct Script {
+    source: String,
+    ast: Rc<AST>,
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        let ast = ENGINE
+            .with(|engine| engine.compile(source))
+            .map_err(|error| ScriptError::Compile(error.to_string()))?;
+        Ok(Script {
+            source: source.to_string(),
+            ast: Rc::new(ast),
+        })
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    /// Runs the script as a condition, which must return a boolean.
+    pub fn eval(&self, rule_context: &RuleContextWrapper) -> Result<bool, ScriptError> {
+        let result = self.run(rule_context)?;
+        result
+            .as_bool()
+            .map_err(|type_name| ScriptError::NotBoolean(type_name.to_string()))
+    }
+
+    /// Runs the script and returns the value of its last statement.
+    pub fn run(&self, rule_context: &RuleContextWrapper) -> Result<Dynamic, ScriptError> {
+        let mut scope = Scope::new();
+        scope.push("ctx", ScriptContext(rule_context.clone()));
+        ENGINE
+            .with(|engine| engine.eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast))
+            .map_err(|error| ScriptError::Runtime(error.to_string()))
+    }
+}
+
+/// Scripted callbacks, implemented for every wrapped rule type.
+pub trait RuleScripts {
+    type RuleType;
+    /// Compiles a script and sets it as the evaluation function of the rule.
+    fn eval_script(&mut self, source: &str) -> Result<Wrapper<Self::RuleType>, ScriptError>;
+    /// Compiles a script and sets it as the execute function of the rule.
+    fn execute_script(&mut self, source: &str) -> Result<Wrapper<Self::RuleType>, ScriptError>;
+}
+
+impl<W> RuleScripts for W
+where
+    W: RuleCallback,
+    W::RuleType: Rule<W::RuleType>,
+{
+    type RuleType = W::RuleType;
+
+    fn eval_script(&mut self, source: &str) -> Result<Wrapper<Self::RuleType>, ScriptError> {
+        let script = Script::compile(source)?;
+        Ok(self.on_eval(move |ctx| {
+            let mut rule_context = ctx.get_rule_context();
+            script.eval(&rule_context).unwrap_or_else(|error| {
+                rule_context.fail_unavailable(RuleError::failed(error.to_string()));
+                false
+            })
+        }))
+    }
+
+    fn execute_script(&mut self, source: &str) -> Result<Wrapper<Self::RuleType>, ScriptError> {
+        let script = Script::compile(source)?;
+        Ok(self.on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            if let Err(error) = script.run(&rule_context) {
+                rule_context.fail_unavailable(RuleError::failed(error.to_string()));
+            }
+        }))
+    }
+}
+
+/// The context as seen by scripts.
+#[derive(Clone)]
+struct ScriptContext(RuleContextWrapper);
+
+impl ScriptContext {
+    fn get(&mut self, key: ImmutableString) -> Result<Dynamic, Box<EvalAltResult>> {
+        let rule_context = self.0.borrow();
+        match rule_context.lookup(&key) {
+            None => Ok(Dynamic::UNIT),
+            Some(value) => to_dynamic(value.as_ref())
+                .ok_or_else(|| format!("key `{key}` holds a value scripts can't read").into()),
+        }
+    }
+
+    fn set(&mut self, key: ImmutableString, value: Dynamic) {
+        let key = intern(&key);
+        if let Ok(value) = value.as_int() {
+            self.0.set(key, value);
+        } else if let Ok(value) = value.as_float() {
+            self.0.set(key, value);
+        } else if let Ok(value) = value.as_bool() {
+            self.0.set(key, value);
+        } else if value.is_string() {
+            self.0.set(key, value.into_string().unwrap_or_default());
+        } else {
+            self.0.set(key, value);
+        }
+    }
+}
+
+fn to_dynamic(value: &dyn Any) -> Option<Dynamic> {
+    let Some(primitive) = Primitive::from_any(value) else {
+        return value.downcast_ref::<Dynamic>().cloned();
+    };
+    if let Some(int) = primitive.as_int() {
+        return i64::try_from(int).ok().map(Dynamic::from);
+    }
+    if let Some(float) = primitive.as_float() {
+        return Some(Dynamic::from(float));
+    }
+    if let Some(text) = primitive.as_str() {
+        return Some(Dynamic::from(text.to_string()));
+    }
+    match primitive {
+        Primitive::Bool(value) => Some(Dynamic::from(value)),
+        Primitive::Char(value) => Some(Dynamic::from(value)),
+        _ => None,
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .register_type_with_name::<ScriptContext>("RuleContext")
+        .register_indexer_get(ScriptContext::get)
+        .register_indexer_set(ScriptContext::set);
+    // Keep runaway scripts from hanging the rules.
+    engine.set_max_operations(1_000_000);
+    engine
+}
@@ -0,0 +1,390 @@
+//! A standalone HTTP service evaluating rule sets, run by the `dredd-server`
+//! binary.
+//!
+//! Rule sets are rule documents, see `dredd_rs::loader::from_document_json`,
+//! read from the `*.json` files of a directory and named after them:
+//! `approval.json` is served as the `approval` rule set. Evaluating a rule
+//! set runs it against a fresh context holding the values of the JSON object
+//! posted, and responds with the context the rules left behind and the
+//! execution trace of the run:
+//!
+//! ```text
+//! GET  /rulesets                  the names of the rule sets
+//! POST /rulesets/{name}/evaluate  runs a rule set against a JSON context
+//! ```
+//!
+//! Only the keys a rule set reads, see `LoadedRules::get_key_usage`, are
+//! taken from the posted object; the others are dropped. Request bodies are
+//! limited to `MAX_BODY_SIZE` bytes, and a client has `IO_TIMEOUT` to send
+//! its whole request, however slowly it trickles in, and as long again to
+//! read the response.
+//!
+//! Start it with `cargo run --features server --bin dredd-server -- <rules
+//! directory> [address]`, the address defaulting to `127.0.0.1:8080`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::loader;
+//! use dredd_rs::server::RuleServer;
+//!
+//! let mut server = RuleServer::new();
+//! server.add("approval", loader::from_document_json(r#"{
+//!     "conditions": { "is_adult": "age >= 18" },
+//!     "actions": { "approve": { "approved": true } },
+//!     "rules": { "type": "all", "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }] }
+//! }"#).unwrap());
+//!
+//! let (status, body) = server.handle("POST", "/rulesets/approval/evaluate", r#"{ "age": 42 }"#);
+//!
+//! assert_eq!(status, 200);
+//! assert_eq!(body["context"]["approved"], true);
+//! assert_eq!(body["trace"][0]["outcome"], "fired");
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    loader::{self, LoadedRules},
+    rule::RuleContext,
+    scenario::{intern, report_to_json, set_value},
+};
+
+/// The largest request body accepted, in bytes. Larger ones are answered
+/// with `413`.
+pub const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// How long a client may take to send its whole request, or to read the
+/// response, before the connection is closed, unless the server sets another
+/// with `RuleServer::with_io_timeout`.
+pub const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The largest request line and headers accepted, in bytes.
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// The connections served at once. More are answered with `503`.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Serves named rule sets over HTTP, see the module documentation.
+pub struct RuleServer {
+    rule_sets: HashMap<String, RuleSet>,
+    io_timeout: Duration,
+}
+
+impl Default for RuleServer {
+    fn default() -> Self {
+        RuleServer {
+            rule_sets: HashMap::new(),
+            io_timeout: IO_TIMEOUT,
+        }
+    }
+}
+
+/// A rule set and the keys accepted from the objects posted to it.
+struct RuleSet {
+    rules: LoadedRules,
+    keys: HashMap<String, &'static str>,
+}
+
+/// A request read from a connection, with the channel to send its response
+/// back on.
+type Pending = (HttpRequest, Sender<(u16, Value)>);
+
+struct HttpRequest {
+    method: String,
+    url: String,
+    body: String,
+}
+
+impl RuleServer {
+    pub fn new() -> Self {
+        RuleServer::default()
+    }
+
+    /// Gives clients `timeout` to send their request, and to read the
+    /// response, instead of `IO_TIMEOUT`.
+    pub fn with_io_timeout(mut self, timeout: Duration) -> Self {
+        self.io_timeout = timeout;
+        self
+    }
+
+    /// Loads every `*.json` rule document of a directory, failing on the
+    /// first one that doesn't load.
+    pub fn load_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut server = RuleServer::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let rules =
+                loader::from_document_json(&fs::read_to_string(&path)?).map_err(|error| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {error}", path.display()),
+                    )
+                })?;
+            server.add(name, rules);
+        }
+        Ok(server)
+    }
+
+    /// Serves a rule set under `name`, replacing any served under it.
+    ///
+    /// Evaluations only take the keys the rules read from the posted object,
+    /// so rules loaded with `loader::from_json` need to declare their reads,
+    /// see `RuleMetadata::with_reads`.
+    pub fn add(&mut self, name: &str, rules: LoadedRules) {
+        let keys = rules
+            .get_key_usage()
+            .read_keys()
+            .into_iter()
+            .map(|key| (key.to_string(), intern(key)))
+            .collect();
+        self.rule_sets
+            .insert(name.to_string(), RuleSet { rules, keys });
+    }
+
+    /// The names of the rule sets served, sorted.
+    pub fn get_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.rule_sets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Answers a request, returning the status code and the JSON body of the
+    /// response. Failed requests get an `error` message in the body.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match (method, segments.as_slice()) {
+            ("GET", ["rulesets"]) => (200, json!({ "rulesets": self.get_names() })),
+            ("POST", ["rulesets", name, "evaluate"]) => self.evaluate(name, body),
+            (_, ["rulesets"] | ["rulesets", _, "evaluate"]) => {
+                error(405, format!("method {method} not allowed"))
+            }
+            _ => error(404, format!("no route for {path}")),
+        }
+    }
+
+    /// Listens on `address` until the process is stopped.
+    ///
+    /// Every connection is read on its own thread, so that a slow client
+    /// doesn't hold the others up, while the rules are run on the calling
+    /// thread, one request at a time.
+    pub fn serve(&self, address: &str) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, requests) = mpsc::channel();
+        let io_timeout = self.io_timeout;
+        thread::spawn(move || accept(listener, sender, io_timeout));
+
+        for (request, reply) in requests {
+            let response = self.handle(&request.method, &request.url, &request.body);
+            // A client hanging up doesn't concern the other requests.
+            let _ = reply.send(response);
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self, name: &str, body: &str) -> (u16, Value) {
+        let Some(rule_set) = self.rule_sets.get(name) else {
+            return error(404, format!("unknown rule set `{name}`"));
+        };
+        let values: Map<String, Value> = match serde_json::from_str(body) {
+            Ok(values) => values,
+            Err(e) => return error(400, format!("the context must be a JSON object: {e}")),
+        };
+
+        let mut rule_context = RuleContext::new();
+        for (key, value) in &values {
+            if let Some(key) = rule_set.keys.get(key) {
+                set_value(&mut rule_context, key, value);
+            }
+        }
+        let report = rule_set.rules.run_with_report(rule_context.clone());
+        (200, report_to_json(&report, &rule_context))
+    }
+}
+
+fn error(status: u16, message: String) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn accept(listener: TcpListener, requests: Sender<Pending>, io_timeout: Duration) {
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        if stream.set_write_timeout(Some(io_timeout)).is_err() {
+            continue;
+        }
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, error(503, "too many connections".to_string()));
+            continue;
+        }
+        let (open, requests) = (open.clone(), requests.clone());
+        thread::spawn(move || {
+            serve_connection(stream, &requests, io_timeout);
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+/// Reads one request, waits for its response and closes the connection.
+fn serve_connection(mut stream: TcpStream, requests: &Sender<Pending>, io_timeout: Duration) {
+    let reader = DeadlineReader {
+        stream: &stream,
+        deadline: Instant::now() + io_timeout,
+    };
+    let response = match read_request(reader) {
+        Ok(request) => {
+            let (reply, response) = mpsc::channel();
+            if requests.send((request, reply)).is_err() {
+                return;
+            }
+            let Ok(response) = response.recv() else {
+                return;
+            };
+            response
+        }
+        Err(response) => response,
+    };
+    let _ = write_response(&mut stream, response);
+}
+
+/// Reads a connection until a deadline for the whole request, rather than
+/// for each read, so that a client can't hold the connection by sending a
+/// byte at a time.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+fn read_request(reader: DeadlineReader) -> Result<HttpRequest, (u16, Value)> {
+    let mut reader = BufReader::new(reader);
+    let mut head = Vec::new();
+    let mut head_size = 0;
+    loop {
+        let mut line = String::new();
+        let limit = (MAX_HEAD_SIZE - head_size) as u64;
+        head_size += (&mut reader)
+            .take(limit)
+            .read_line(&mut line)
+            .map_err(read_error)?;
+        if !line.ends_with('\n') {
+            return Err(match head_size {
+                MAX_HEAD_SIZE => error(431, "request head too large".to_string()),
+                _ => error(400, "incomplete request".to_string()),
+            });
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        head.push(line.to_string());
+    }
+
+    let request_line: Vec<&str> = head
+        .first()
+        .map_or(vec![], |line| line.split(' ').collect());
+    let [method, url, version] = request_line[..] else {
+        return Err(error(400, "malformed request line".to_string()));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(error(400, format!("unsupported version {version}")));
+    }
+
+    let mut length = 0;
+    for header in &head[1..] {
+        let (name, value) = header.split_once(':').unwrap_or((header, ""));
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(error(411, "the body needs a Content-Length".to_string()));
+        }
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .trim()
+                .parse()
+                .map_err(|_| error(400, format!("invalid Content-Length `{}`", value.trim())))?;
+        }
+    }
+    if length > MAX_BODY_SIZE {
+        return Err(error(
+            413,
+            format!("the body is larger than {MAX_BODY_SIZE} bytes"),
+        ));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(read_error)?;
+    let body = String::from_utf8(body)
+        .map_err(|_| error(400, "the body is not valid UTF-8".to_string()))?;
+    Ok(HttpRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        body,
+    })
+}
+
+fn read_error(e: io::Error) -> (u16, Value) {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            error(408, "timed out reading the request".to_string())
+        }
+        _ => error(400, format!("unreadable request: {e}")),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, (status, body): (u16, Value)) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,9 @@
+//! Synchronization primitives shared by the thread-safe types.
+//!
+//! Building with `RUSTFLAGS="--cfg loom"` swaps them for loom's, so that
+//! `tests/loom_test.rs` can model check every interleaving.
+
+#[cfg(loom)]
+pub(crate) use loom::sync::RwLock;
+#[cfg(not(loom))]
+pub(crate) use std::sync::RwLock;
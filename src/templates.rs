@@ -0,0 +1,314 @@
+//! Renders named templates from context values, for example to build the
+//! message sent to a customer once the rules have decided.
+//!
+//! Templates use the [MiniJinja](https://docs.rs/minijinja) syntax. Every
+//! context value of a primitive number type, `bool`, `String`,
+//! `&'static str` or `serde_json::Value` is available to them by key.
+//! Using a value that isn't in the context is an error rather than an empty
+//! string, and templates whose name ends with `.html` escape the values they
+//! insert.
+//!
+//! The `number` filter formats numbers with the separators of the locale
+//! held by the `locale` value, English when there is none:
+//! `{{ total | number(2) }}` renders `1234.5` as `1,234.50`, or as `1.234,50`
+//! with a `pt-BR` locale. `LocalizedTemplates` keeps a version of each
+//! template per locale and picks the one matching the customer.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::templates::Templates;
+//!
+//! let mut templates = Templates::new();
+//! templates
+//!     .add("declined", "Hi {{ name }}, your order of {{ total }} was declined{% if retry %}, please try again{% endif %}.")
+//!     .unwrap();
+//!
+//! let rule = ChainRule::new().on_execute(templates.render_action("declined", "message"));
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("name", "Ana");
+//! rule_context.set("total", 250u32);
+//! rule_context.set("retry", true);
+//!
+//! Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+//! assert_eq!(
+//!     *rule_context.get::<String>("message").unwrap(),
+//!     "Hi Ana, your order of 250 was declined, please try again."
+//! );
+//! ```
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use minijinja::{Environment, State, UndefinedBehavior};
+use serde_json::{Map, Value};
+
+use crate::{
+    rule::{GetSet, Rule, RuleContext, RuleError, RuleFailure},
+    scenario::get_value,
+};
+
+pub use minijinja::Error as TemplateError;
+
+/// A set of named templates. Clones share the templates.
+#[derive(Clone)]
+pub struct Templates {
+    environment: Rc<RefCell<Environment<'static>>>,
+}
+
+impl Templates {
+    pub fn new() -> Self {
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+        environment.add_filter("number", number);
+        Templates {
+            environment: Rc::new(RefCell::new(environment)),
+        }
+    }
+
+    /// Adds a template, replacing any template of the same name. Fails if the
+    /// template has a syntax error.
+    pub fn add(&mut self, name: &str, source: &str) -> Result<(), TemplateError> {
+        self.environment
+            .borrow_mut()
+            .add_template_owned(name.to_string(), source.to_string())
+    }
+
+    /// Whether a template of that name was added.
+    pub fn contains(&self, name: &str) -> bool {
+        self.environment.borrow().get_template(name).is_ok()
+    }
+
+    /// Renders a template with the values of the context.
+    pub fn render(&self, name: &str, rule_context: &RuleContext) -> Result<String, RuleError> {
+        self.render_values(name, context_values(rule_context))
+    }
+
+    fn render_values(&self, name: &str, values: Map<String, Value>) -> Result<String, RuleError> {
+        let environment = self.environment.borrow();
+        environment
+            .get_template(name)
+            .and_then(|template| template.render(values))
+            .map_err(|err| RuleError::failed(format!("template `{name}`: {err}")))
+    }
+
+    /// An execute callback rendering a template and writing the result, as a
+    /// `String`, to a context key. Rendering errors are recorded as the
+    /// failure of the rule.
+    pub fn render_action<R: Rule<R>>(
+        &self,
+        name: &str,
+        key: &'static str,
+    ) -> impl Fn(&mut R) + 'static {
+        let (templates, name) = (self.clone(), name.to_string());
+        move |this| {
+            let mut rule_context = this.get_rule_context();
+            let rendered = templates.render(&name, &rule_context.borrow());
+            match rendered {
+                Ok(rendered) => rule_context.set(key, rendered),
+                Err(error) => rule_context.fail(error),
+            }
+        }
+    }
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Templates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let environment = self.environment.borrow();
+        let names: Vec<_> = environment.templates().map(|(name, _)| name).collect();
+        f.debug_struct("Templates")
+            .field("templates", &names)
+            .finish()
+    }
+}
+
+fn context_values(rule_context: &RuleContext) -> Map<String, Value> {
+    rule_context
+        .get_context_map()
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), get_value(value.as_ref())?)))
+        .collect()
+}
+
+/// Templates translated into several locales, such as `en`, `pt-BR` or
+/// `fr`, for messages shown to customers in their own language.
+///
+/// A template is rendered in the most specific locale it was added for:
+/// the requested locale itself, then its language, `pt` for `pt-BR`, then
+/// the default locale. Locales are matched regardless of case, with `_` and
+/// `-` both accepted as separators. The locale the template was picked for
+/// is available to it as `locale`, which the `number` filter uses.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+/// use dredd_rs::templates::LocalizedTemplates;
+///
+/// let mut templates = LocalizedTemplates::new("en").with_locale_key("language");
+/// templates
+///     .add("en", "declined", "Your order of ${{ total | number(2) }} was declined.")
+///     .unwrap();
+/// templates
+///     .add("pt", "declined", "Seu pedido de R$ {{ total | number(2) }} foi recusado.")
+///     .unwrap();
+///
+/// let rule = ChainRule::new().on_execute(templates.render_action("declined", "reason"));
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("total", 1234.5);
+/// rule_context.set("language", "pt-BR");
+/// Engine::chain_runner().try_run(rule_context.clone(), vec![rule]).unwrap();
+///
+/// assert_eq!(
+///     *rule_context.get::<String>("reason").unwrap(),
+///     "Seu pedido de R$ 1.234,50 foi recusado."
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalizedTemplates {
+    templates: Templates,
+    default_locale: String,
+    locale_key: &'static str,
+}
+
+impl LocalizedTemplates {
+    /// Creates an empty set of templates, rendered in `default_locale` when
+    /// none was added for the requested locale.
+    pub fn new(default_locale: &str) -> Self {
+        LocalizedTemplates {
+            templates: Templates::new(),
+            default_locale: normalize_locale(default_locale),
+            locale_key: "locale",
+        }
+    }
+
+    /// Sets the context key `render_action` reads the locale from, `locale`
+    /// by default.
+    pub fn with_locale_key(mut self, key: &'static str) -> Self {
+        self.locale_key = key;
+        self
+    }
+
+    pub fn get_default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Adds the version of a template for a locale, replacing any previous
+    /// one. Fails if the template has a syntax error.
+    pub fn add(&mut self, locale: &str, name: &str, source: &str) -> Result<(), TemplateError> {
+        self.templates
+            .add(&localized_name(&normalize_locale(locale), name), source)
+    }
+
+    /// The locale a template would be rendered in for the requested locale,
+    /// if it was added for any matching one.
+    pub fn resolve_locale(&self, name: &str, locale: &str) -> Option<String> {
+        let locale = normalize_locale(locale);
+        let language = locale.split('-').next().unwrap_or_default().to_string();
+        [locale, language, self.default_locale.clone()]
+            .into_iter()
+            .find(|locale| self.templates.contains(&localized_name(locale, name)))
+    }
+
+    /// Renders a template in the requested locale with the values of the
+    /// context.
+    pub fn render(
+        &self,
+        name: &str,
+        locale: &str,
+        rule_context: &RuleContext,
+    ) -> Result<String, RuleError> {
+        let Some(locale) = self.resolve_locale(name, locale) else {
+            return Err(RuleError::failed(format!(
+                "template `{name}`: no version for locale `{locale}` or `{}`",
+                self.default_locale
+            )));
+        };
+        let mut values = context_values(rule_context);
+        values.insert("locale".to_string(), Value::from(locale.clone()));
+        self.templates
+            .render_values(&localized_name(&locale, name), values)
+    }
+
+    /// An execute callback rendering a template in the locale held by the
+    /// locale key, or the default locale when it isn't set, and writing the
+    /// result to a context key. Rendering errors are recorded as the failure
+    /// of the rule.
+    pub fn render_action<R: Rule<R>>(
+        &self,
+        name: &str,
+        key: &'static str,
+    ) -> impl Fn(&mut R) + 'static {
+        let (templates, name) = (self.clone(), name.to_string());
+        move |this| {
+            let mut rule_context = this.get_rule_context();
+            let rendered = {
+                let rule_context = rule_context.borrow();
+                let locale = rule_context
+                    .lookup(templates.locale_key)
+                    .and_then(|value| get_value(value.as_ref()))
+                    .and_then(|value| value.as_str().map(str::to_string))
+                    .unwrap_or_else(|| templates.default_locale.clone());
+                templates.render(&name, &locale, &rule_context)
+            };
+            match rendered {
+                Ok(rendered) => rule_context.set(key, rendered),
+                Err(error) => rule_context.fail(error),
+            }
+        }
+    }
+}
+
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+fn localized_name(locale: &str, name: &str) -> String {
+    format!("{locale}/{name}")
+}
+
+/// The `number` filter: formats a number with the digit grouping and
+/// decimal separators of the `locale` value, with `decimals` decimal
+/// places, or none for whole numbers and two otherwise.
+fn number(state: &State, value: f64, decimals: Option<usize>) -> String {
+    let locale = state
+        .lookup("locale")
+        .and_then(|locale| locale.as_str().map(normalize_locale))
+        .unwrap_or_default();
+    let (group, decimal) = match locale.split('-').next().unwrap_or_default() {
+        "pt" | "es" | "de" | "it" | "nl" | "id" | "tr" | "da" => (".", ","),
+        "fr" | "ru" | "pl" | "cs" | "sv" | "fi" | "nb" | "uk" => ("\u{202f}", ","),
+        _ => (",", "."),
+    };
+    let decimals = decimals.unwrap_or(if value.fract() == 0.0 { 0 } else { 2 });
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut result = String::new();
+    if value.is_sign_negative()
+        && formatted
+            .bytes()
+            .any(|digit| digit.is_ascii_digit() && digit != b'0')
+    {
+        result.push('-');
+    }
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            result.push_str(group);
+        }
+        result.push(digit);
+    }
+    if !fraction.is_empty() {
+        result.push_str(decimal);
+        result.push_str(fraction);
+    }
+    result
+}
@@ -0,0 +1,151 @@
+//! Helpers to write rule tests that read like specifications.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::testing::RuleTest;
+//!
+//! let rule = ChainRule::new()
+//!     .with_name("adult_check")
+//!     .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+//!     .on_execute(|this| this.get_rule_context().set("approved", true));
+//!
+//! RuleTest::given(|ctx| ctx.set("age", 42i64))
+//!     .when_fired(rule)
+//!     .then(|ctx, outcome| {
+//!         outcome.assert_fired("adult_check");
+//!         outcome.assert_changed("approved");
+//!         outcome.assert_unchanged("age");
+//!         assert!(*ctx.get::<bool>("approved").unwrap());
+//!     });
+//! ```
+
+use std::rc::Rc;
+
+mod contract;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod shrink;
+
+pub use contract::assert_io_contract;
+#[cfg(feature = "rayon")]
+pub use parallel::assert_parallel_deterministic;
+pub use shrink::{Reproducer, Shrinker};
+
+use crate::rule::{
+    depth_guard::fire_rule, ExecutionTrace, Rule, RuleContext, RuleContextMap, RuleContextWrapper,
+    Wrapper,
+};
+
+/// Entry point of a rule test, holding the context the rule will be fired with.
+pub struct RuleTest {
+    rule_context: RuleContextWrapper,
+}
+
+impl RuleTest {
+    /// Builds the context the rule under test is fired with.
+    pub fn given(setup: impl FnOnce(&mut RuleContextWrapper)) -> Self {
+        let mut rule_context = RuleContext::new();
+        setup(&mut rule_context);
+        RuleTest { rule_context }
+    }
+
+    /// Fires the rule, and through it its children, against the context.
+    pub fn when_fired<R: Rule<R>>(self, rule: Wrapper<R>) -> RuleTestResult {
+        let before = self.rule_context.borrow().get_context_map().clone();
+        let start = self.rule_context.borrow_mut().start_trace();
+        fire_rule(&rule, &self.rule_context);
+        let trace = self.rule_context.borrow_mut().finish_trace(start);
+        let after = self.rule_context.borrow().get_context_map().clone();
+
+        RuleTestResult {
+            rule_context: self.rule_context,
+            outcome: FireOutcome {
+                before,
+                after,
+                trace,
+            },
+        }
+    }
+}
+
+/// The state after the rule under test was fired.
+pub struct RuleTestResult {
+    rule_context: RuleContextWrapper,
+    outcome: FireOutcome,
+}
+
+impl RuleTestResult {
+    /// Runs assertions against the resulting context and outcome.
+    pub fn then(mut self, check: impl FnOnce(&mut RuleContextWrapper, &FireOutcome)) -> Self {
+        check(&mut self.rule_context, &self.outcome);
+        self
+    }
+}
+
+/// What happened while the rule under test was fired.
+///
+/// A key counts as changed when it was set, removed or added during the run,
+/// even if it was set to a value equal to the previous one.
+pub struct FireOutcome {
+    before: RuleContextMap,
+    after: RuleContextMap,
+    trace: ExecutionTrace,
+}
+
+impl FireOutcome {
+    pub fn get_trace(&self) -> &ExecutionTrace {
+        &self.trace
+    }
+
+    /// The keys that were set, removed or added during the run, sorted.
+    pub fn get_changed_keys(&self) -> Vec<&'static str> {
+        let mut keys: Vec<_> = self
+            .before
+            .keys()
+            .chain(self.after.keys())
+            .copied()
+            .filter(|key| self.is_changed(key))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    pub fn is_changed(&self, key: &str) -> bool {
+        match (self.before.get(key), self.after.get(key)) {
+            (Some(before), Some(after)) => !Rc::ptr_eq(before, after),
+            (None, None) => false,
+            _ => true,
+        }
+    }
+
+    /// Whether a rule with the given name was executed.
+    pub fn is_fired(&self, name: &str) -> bool {
+        self.trace.get_executed_names().contains(&name)
+    }
+
+    pub fn assert_changed(&self, key: &str) {
+        assert!(self.is_changed(key), "expected key `{key}` to change");
+    }
+
+    pub fn assert_unchanged(&self, key: &str) {
+        assert!(
+            !self.is_changed(key),
+            "expected key `{key}` to stay unchanged"
+        );
+    }
+
+    pub fn assert_fired(&self, name: &str) {
+        assert!(
+            self.is_fired(name),
+            "expected rule `{name}` to fire, fired rules: {:?}",
+            self.trace.get_executed_names()
+        );
+    }
+
+    pub fn assert_not_fired(&self, name: &str) {
+        assert!(!self.is_fired(name), "expected rule `{name}` not to fire");
+    }
+}
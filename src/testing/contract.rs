@@ -0,0 +1,42 @@
+use crate::rule::KeyUsage;
+
+/// Panics when a rule set reads or writes context keys outside its contract.
+///
+/// The usage is usually built with `KeyUsage::declared`, merged with the
+/// usages recorded by running the rule set over representative contexts with
+/// `KeyUsage::record`. The contract is an upper bound: keys it allows that the
+/// rule set does not use are fine, so the check only fails when the rule set
+/// starts depending on, or producing, keys another rule set did not agree to.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+/// use dredd_rs::testing::assert_io_contract;
+///
+/// let rules = vec![AllRule::new()
+///     .on_eval(|ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+///     .on_execute(|this| this.get_rule_context().set("approved", true))];
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 42i64);
+/// let mut usage = KeyUsage::declared(&rules);
+/// usage.merge(&KeyUsage::record(&Engine::all_runner(), rule_context, rules));
+///
+/// assert_io_contract(&usage, &["age", "country"], &["approved"]);
+/// ```
+pub fn assert_io_contract(usage: &KeyUsage, expected_reads: &[&str], expected_writes: &[&str]) {
+    let outside = |keys: Vec<&str>, expected: &[&str]| -> Vec<String> {
+        keys.into_iter()
+            .filter(|key| !expected.contains(key))
+            .map(str::to_string)
+            .collect()
+    };
+    let reads = outside(usage.read_keys(), expected_reads);
+    let writes = outside(usage.write_keys(), expected_writes);
+
+    assert!(
+        reads.is_empty() && writes.is_empty(),
+        "rule set breaks its IO contract: reads {reads:?} and writes {writes:?} outside of it"
+    );
+}
@@ -0,0 +1,55 @@
+use std::fmt::Debug;
+
+use crate::{
+    engine::Engine,
+    rule::{ParallelRule, SharedRuleContext, SyncWrapper},
+};
+
+/// Runs a parallel rule set `runs` times and panics unless every run leaves
+/// the context in the same state.
+///
+/// The `ParallelRuleRunner` merges the writes of sibling rules in a fixed
+/// order, so a rule set that only shares state through its context always
+/// gives the same result. A rule set that fails this check shares state some
+/// other way, such as a captured `Mutex`, and depends on thread scheduling.
+///
+/// `setup` builds a fresh context and rule set for every run, and `snapshot`
+/// extracts the values to compare from the context after the run.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+/// use dredd_rs::testing::assert_parallel_deterministic;
+///
+/// assert_parallel_deterministic(
+///     50,
+///     || {
+///         let rules = (0..8)
+///             .map(|i| ParallelRule::new().on_execute(move |this| this.get_rule_context().set("last", i)))
+///             .collect();
+///         (SharedRuleContext::new(), rules)
+///     },
+///     |rule_context| *rule_context.get::<i32>("last").unwrap(),
+/// );
+/// ```
+pub fn assert_parallel_deterministic<S: PartialEq + Debug>(
+    runs: usize,
+    setup: impl Fn() -> (SharedRuleContext, Vec<SyncWrapper<ParallelRule>>),
+    snapshot: impl Fn(&SharedRuleContext) -> S,
+) {
+    let run = || {
+        let (rule_context, rules) = setup();
+        Engine::parallel_runner().run(rule_context.clone(), rules);
+        snapshot(&rule_context)
+    };
+
+    let expected = run();
+    for index in 1..runs {
+        let actual = run();
+        assert!(
+            actual == expected,
+            "parallel run {index} differs from the first one: expected {expected:?}, got {actual:?}"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    any::Any,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use crate::rule::{value_types::Primitive, RuleContext, RuleContextMap, RuleContextWrapper};
+
+const DEFAULT_MAX_ATTEMPTS: usize = 1000;
+
+/// Minimizes a context that makes a rule set fail, keeping it failing.
+///
+/// The shrinker drops keys first, then replaces the values of integer, float,
+/// `bool`, `String` and `&'static str` keys with smaller ones. Values of other
+/// types are kept as they are. Every attempt runs the rule set against a fresh
+/// copy of the candidate context.
+///
+/// Any failure counts, so the check should only match the failure being
+/// investigated: with `panicking`, a panic caused by a key the shrinker removed
+/// is a failure too.
+///
+/// # Example
+///
+/// ```rust
+/// use dredd_rs::rule::*;
+/// use dredd_rs::testing::Shrinker;
+///
+/// let shrinker = Shrinker::panicking(|rule_context| {
+///     let rule = ChainRule::new().on_execute(|this| {
+///         let age = *this.get_rule_context().get::<i64>("age").unwrap_or_default();
+///         assert!(age < 100, "age out of range");
+///     });
+///     Engine::chain_runner().run(rule_context, vec![rule]);
+/// });
+///
+/// let mut rule_context = RuleContext::new();
+/// rule_context.set("age", 150i64);
+/// rule_context.set("name", "Joe".to_string());
+///
+/// let reproducer = shrinker.shrink(&rule_context).unwrap();
+/// assert_eq!(reproducer.get_keys(), vec!["age"]);
+/// assert_eq!(*reproducer.to_context().get::<i64>("age").unwrap(), 100);
+/// println!("{reproducer}");
+/// ```
+pub struct Shrinker {
+    fails: Box<dyn Fn(RuleContextWrapper) -> bool>,
+    max_attempts: usize,
+}
+
+impl Shrinker {
+    /// Creates a shrinker for a failure detected by `fails` returning true.
+    pub fn new(fails: impl Fn(RuleContextWrapper) -> bool + 'static) -> Self {
+        Shrinker {
+            fails: Box::new(fails),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Creates a shrinker for a failure that makes `run` panic.
+    pub fn panicking(run: impl Fn(RuleContextWrapper) + 'static) -> Self {
+        Self::new(move |rule_context| {
+            panic::catch_unwind(AssertUnwindSafe(|| run(rule_context))).is_err()
+        })
+    }
+
+    /// Sets how many times the rule set may be run while shrinking. Once they
+    /// are used up, the smallest failing context found so far is returned.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Returns the smallest failing context found, or `None` when the given
+    /// context doesn't fail.
+    pub fn shrink(&self, rule_context: &RuleContextWrapper) -> Option<Reproducer> {
+        let mut context_map = rule_context.borrow().get_context_map().clone();
+        let mut attempts = 0;
+        let mut fails = |context_map: &RuleContextMap| {
+            if attempts >= self.max_attempts.max(1) {
+                return false;
+            }
+            attempts += 1;
+            (self.fails)(RuleContext::from_context_map(context_map.clone()))
+        };
+
+        if !fails(&context_map) {
+            return None;
+        }
+
+        let mut progress = true;
+        while progress {
+            progress = false;
+            let mut keys: Vec<_> = context_map.keys().copied().collect();
+            keys.sort_unstable();
+
+            for key in keys {
+                let mut candidate = context_map.clone();
+                candidate.remove(key);
+                if fails(&candidate) {
+                    context_map = candidate;
+                    progress = true;
+                    continue;
+                }
+
+                for value in smaller_values(context_map[key].as_ref()) {
+                    let mut candidate = context_map.clone();
+                    candidate.insert(key, value);
+                    if fails(&candidate) {
+                        context_map = candidate;
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Some(Reproducer {
+            context_map,
+            attempts,
+        })
+    }
+}
+
+/// A minimized failing context.
+///
+/// Its `Display` output is a `RuleTest::given` fixture that rebuilds the
+/// context, ready to be pasted into a test.
+pub struct Reproducer {
+    context_map: RuleContextMap,
+    attempts: usize,
+}
+
+impl Reproducer {
+    /// The keys left in the context, sorted.
+    pub fn get_keys(&self) -> Vec<&'static str> {
+        let mut keys: Vec<_> = self.context_map.keys().copied().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// How many times the rule set was run while shrinking.
+    pub fn get_attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Builds a new context holding the minimized values.
+    pub fn to_context(&self) -> RuleContextWrapper {
+        RuleContext::from_context_map(self.context_map.clone())
+    }
+}
+
+impl fmt::Display for Reproducer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "RuleTest::given(|ctx| {{")?;
+        for key in self.get_keys() {
+            match literal(self.context_map[key].as_ref()) {
+                Some(literal) => writeln!(f, "    ctx.set({key:?}, {literal});")?,
+                None => writeln!(f, "    // {key:?} holds a value that can't be printed")?,
+            }
+        }
+        write!(f, "}})")
+    }
+}
+
+fn smaller_values(value: &dyn Any) -> Vec<Rc<dyn Any>> {
+    let Some(value) = Primitive::from_any(value) else {
+        return Vec::new();
+    };
+    let candidates = if let Some(int) = value.as_int() {
+        let mut candidates = Vec::new();
+        if int != 0 {
+            candidates.push(0);
+            candidates.push(int / 2);
+            candidates.push(if int > 0 { int - 1 } else { int + 1 });
+        }
+        candidates
+            .into_iter()
+            .filter_map(|candidate| value.with_int(candidate))
+            .collect()
+    } else if let Some(float) = value.as_float() {
+        let mut candidates = Vec::new();
+        if float != 0.0 {
+            candidates.push(0.0);
+            if float.is_finite() && float.fract() != 0.0 {
+                candidates.push(float.trunc());
+            } else if float.is_finite() {
+                candidates.push((float / 2.0).trunc());
+                candidates.push(float - float.signum());
+            }
+        }
+        candidates
+            .into_iter()
+            .filter_map(|candidate| value.with_float(candidate))
+            .collect()
+    } else {
+        match &value {
+            Primitive::Bool(true) => vec![Primitive::Bool(false)],
+            Primitive::String(text) => shorter_strings(text)
+                .into_iter()
+                .map(Primitive::String)
+                .collect(),
+            Primitive::Str(text) => shorter_strings(text)
+                .into_iter()
+                .map(|candidate| Primitive::Str(&text[..candidate.len()]))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    into_candidates(value, candidates)
+}
+
+fn shorter_strings(value: &str) -> Vec<String> {
+    if value.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<char> = value.chars().collect();
+    vec![
+        String::new(),
+        chars[..chars.len() / 2].iter().collect(),
+        chars[..chars.len() - 1].iter().collect(),
+    ]
+}
+
+/// Deduplicates the candidates and drops the ones equal to the current value.
+fn into_candidates(value: Primitive, candidates: Vec<Primitive>) -> Vec<Rc<dyn Any>> {
+    let mut unique: Vec<Primitive> = Vec::new();
+    for candidate in candidates {
+        if candidate != value && !unique.contains(&candidate) {
+            unique.push(candidate);
+        }
+    }
+    unique.into_iter().map(Primitive::into_value).collect()
+}
+
+fn literal(value: &dyn Any) -> Option<String> {
+    let value = Primitive::from_any(value)?;
+    match &value {
+        Primitive::F32(value) => Some(format!("{value:?}f32")),
+        Primitive::F64(value) => Some(format!("{value:?}f64")),
+        Primitive::Bool(value) => Some(value.to_string()),
+        Primitive::Char(value) => Some(format!("{value:?}")),
+        Primitive::String(value) => Some(format!("{value:?}.to_string()")),
+        Primitive::Str(value) => Some(format!("{value:?}")),
+        _ => value
+            .as_int()
+            .map(|int| format!("{int}{}", value.type_name())),
+    }
+}
@@ -0,0 +1,9 @@
+//! The clocks used for timing runs. The standard library doesn't provide
+//! them on `wasm32-unknown-unknown`, so the `wasm` feature takes them from
+//! `web-time`, which reads the browser clock there and re-exports the
+//! standard ones elsewhere.
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) use std::time::{Instant, SystemTime};
+#[cfg(feature = "wasm")]
+pub(crate) use web_time::{Instant, SystemTime};
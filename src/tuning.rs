@@ -0,0 +1,240 @@
+//! Finds the best threshold of an expression condition from sampled runs.
+//!
+//! The condition is written with the threshold as a context key, such as
+//! `score > threshold`. For every candidate value, the condition is evaluated
+//! against the input of every sample with the threshold key set to that
+//! value, and a metric callback scores the outcome of each sample, usually by
+//! comparing it to a label found in the sample. The candidates are ranked by
+//! their mean score, reported with a 95% confidence interval.
+//!
+//! Samples are typically captured in production with
+//! `dredd_rs::sampling::Sampler`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::sampling::Sample;
+//! use dredd_rs::tuning::ThresholdTuner;
+//! use serde_json::json;
+//!
+//! // Runs sampled in production, labeled with whether they were fraud.
+//! let samples: Vec<Sample> = [(0.2, false), (0.4, false), (0.6, true), (0.9, true)]
+//!     .into_iter()
+//!     .map(|(score, fraud)| {
+//!         let input = json!({ "score": score });
+//!         let outputs = json!({ "fraud": fraud });
+//!         Sample::new(input.as_object().unwrap().clone(), vec![], outputs.as_object().unwrap().clone())
+//!     })
+//!     .collect();
+//!
+//! let report = ThresholdTuner::new("score > threshold", "threshold")
+//!     .unwrap()
+//!     .with_range(0.0, 1.0, 10)
+//!     .tune(&samples, |sample, matched| {
+//!         (sample.get_outputs()["fraud"] == json!(matched)) as u8 as f64
+//!     });
+//!
+//! let best = report.get_best().unwrap();
+//! assert_eq!(best.get_mean(), 1.0);
+//! assert!(best.get_threshold() >= 0.4 && best.get_threshold() < 0.6);
+//! ```
+
+use std::fmt;
+
+use crate::{
+    expr::{Expr, ExprError},
+    rule::{GetSet, RuleContext},
+    sampling::Sample,
+    scenario::{intern, set_value},
+};
+
+/// Sweeps the threshold of an expression condition over sampled runs.
+#[derive(Debug, Clone)]
+pub struct ThresholdTuner {
+    condition: Expr,
+    threshold_key: &'static str,
+    candidates: Vec<f64>,
+}
+
+impl ThresholdTuner {
+    /// Creates a tuner for a condition reading its threshold from
+    /// `threshold_key`. No candidates are tried until some are given.
+    pub fn new(condition: &str, threshold_key: &str) -> Result<Self, ExprError> {
+        Ok(ThresholdTuner {
+            condition: Expr::parse(condition)?,
+            threshold_key: intern(threshold_key),
+            candidates: Vec::new(),
+        })
+    }
+
+    /// Tries the given threshold values.
+    pub fn with_candidates(mut self, candidates: impl IntoIterator<Item = f64>) -> Self {
+        self.candidates.extend(candidates);
+        self
+    }
+
+    /// Tries `steps + 1` evenly spaced values from `start` to `end`, both included.
+    pub fn with_range(self, start: f64, end: f64, steps: usize) -> Self {
+        let steps = steps.max(1);
+        self.with_candidates(
+            (0..=steps).map(|step| start + (end - start) * step as f64 / steps as f64),
+        )
+    }
+
+    /// Scores every candidate with `metric`, which is given each sample and
+    /// whether the condition matched it. Samples on which the condition
+    /// fails to evaluate, for example because a key is missing, count as not
+    /// matched, like in `eval_expr` rules.
+    pub fn tune(&self, samples: &[Sample], metric: impl Fn(&Sample, bool) -> f64) -> TuningReport {
+        let inputs: Vec<_> = samples
+            .iter()
+            .map(|sample| {
+                let mut rule_context = RuleContext::new();
+                for (key, value) in sample.get_input() {
+                    set_value(&mut rule_context, intern(key), value);
+                }
+                rule_context
+            })
+            .collect();
+
+        let mut results: Vec<_> = self
+            .candidates
+            .iter()
+            .map(|&threshold| {
+                let scores: Vec<_> = samples
+                    .iter()
+                    .zip(&inputs)
+                    .map(|(sample, rule_context)| {
+                        let mut rule_context = rule_context.clone();
+                        rule_context.set(self.threshold_key, threshold);
+                        let matched = self.condition.eval(&rule_context.borrow()).unwrap_or(false);
+                        (metric(sample, matched), matched)
+                    })
+                    .collect();
+                ThresholdResult::new(threshold, &scores)
+            })
+            .collect();
+
+        // Best mean first; on ties, the candidate with the narrower interval.
+        results.sort_by(|a, b| {
+            b.mean
+                .total_cmp(&a.mean)
+                .then(a.margin.total_cmp(&b.margin))
+        });
+        TuningReport { results }
+    }
+}
+
+/// The candidates of a tuning, best first.
+#[derive(Debug, Clone)]
+pub struct TuningReport {
+    results: Vec<ThresholdResult>,
+}
+
+impl TuningReport {
+    pub fn get_best(&self) -> Option<&ThresholdResult> {
+        self.results.first()
+    }
+
+    pub fn get_results(&self) -> &[ThresholdResult] {
+        &self.results
+    }
+}
+
+impl fmt::Display for TuningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>12} {:>10} {:>21} {:>8}",
+            "threshold", "mean", "95% interval", "matched"
+        )?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "{:>12.4} {:>10.4} [{:>9.4}, {:>9.4}] {:>8}",
+                result.threshold,
+                result.mean,
+                result.get_lower(),
+                result.get_upper(),
+                result.matched
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// How well the condition performed with one threshold.
+#[derive(Debug, Clone, Copy)]
+pub struct ThresholdResult {
+    threshold: f64,
+    mean: f64,
+    margin: f64,
+    samples: usize,
+    matched: usize,
+}
+
+impl ThresholdResult {
+    fn new(threshold: f64, scores: &[(f64, bool)]) -> Self {
+        let samples = scores.len();
+        let matched = scores.iter().filter(|(_, matched)| *matched).count();
+        if samples == 0 {
+            return ThresholdResult {
+                threshold,
+                mean: 0.0,
+                margin: f64::INFINITY,
+                samples,
+                matched,
+            };
+        }
+
+        let n = samples as f64;
+        let mean = scores.iter().map(|(score, _)| score).sum::<f64>() / n;
+        let margin = if samples > 1 {
+            let variance = scores
+                .iter()
+                .map(|(score, _)| (score - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            // Normal approximation of the 95% confidence interval of the mean.
+            1.96 * (variance / n).sqrt()
+        } else {
+            f64::INFINITY
+        };
+
+        ThresholdResult {
+            threshold,
+            mean,
+            margin,
+            samples,
+            matched,
+        }
+    }
+
+    pub fn get_threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// The mean score of the samples.
+    pub fn get_mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The lower bound of the 95% confidence interval of the mean.
+    pub fn get_lower(&self) -> f64 {
+        self.mean - self.margin
+    }
+
+    /// The upper bound of the 95% confidence interval of the mean.
+    pub fn get_upper(&self) -> f64 {
+        self.mean + self.margin
+    }
+
+    pub fn get_samples(&self) -> usize {
+        self.samples
+    }
+
+    /// On how many samples the condition matched.
+    pub fn get_matched(&self) -> usize {
+        self.matched
+    }
+}
@@ -0,0 +1,303 @@
+//! Building blocks for input validation rule sets.
+//!
+//! Each constructor returns a rule checking one context key: `require_key`
+//! that it is set, `in_range` that a value lies in a range, `one_of` that it
+//! is one of a set of allowed values and, with the `regex` feature,
+//! `matches_format` that a string matches a regular expression. A rule only
+//! applies when its check fails, and then records a `Violation` naming the
+//! key, the kind of check and a message, instead of failing the run, so that
+//! every problem of an input is reported at once.
+//!
+//! Except for `require_key`, checks pass when the key is missing, so that
+//! optional keys can be validated; combine them with `require_key` for the
+//! required ones. A value of another type than the one checked is a
+//! violation.
+//!
+//! Violations are pushed to the context list `VIOLATIONS`, read back with
+//! `get_violations`, and reported as warnings of the run too: a violation is
+//! an error, reported as a `Severity::Critical` warning that makes
+//! `RunReport::get_status` a fail, unless its rule is wrapped with
+//! `as_warning`, making it a `Severity::Warning` one.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::validation::{self, ViolationKind};
+//!
+//! let rules = vec![
+//!     validation::require_key("email"),
+//!     validation::require_key("age"),
+//!     validation::in_range("age", 18u32..=120),
+//!     validation::one_of("plan", ["free", "pro"]),
+//! ];
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("age", 16u32);
+//! rule_context.set("plan", "enterprise");
+//! Engine::all_runner().run(rule_context.clone(), rules);
+//!
+//! let violations = validation::get_violations(&rule_context);
+//! assert_eq!(violations.len(), 3);
+//! assert_eq!(violations[0].get_kind(), ViolationKind::Missing);
+//! assert_eq!(violations[1].to_string(), "`age` must be in 18..=120, got 16");
+//! assert_eq!(violations[2].get_key(), "plan");
+//! ```
+//!
+//! Findings that shouldn't reject the input are marked as warnings:
+//!
+//! ```rust
+//! use dredd_rs::rule::*;
+//! use dredd_rs::validation;
+//!
+//! let rules = vec![
+//!     validation::require_key("email"),
+//!     validation::as_warning(validation::require_key("phone")),
+//! ];
+//!
+//! let mut rule_context = RuleContext::new();
+//! rule_context.set("email", "ana@example.com");
+//! let report = Engine::all_runner().run_with_report(rule_context, rules);
+//!
+//! assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+//! ```
+
+use std::{fmt, ops::RangeBounds, rc::Rc};
+
+#[cfg(feature = "regex")]
+use std::any::Any;
+
+use crate::rule::{
+    wrap, AllRule, ContextList, Rule, RuleCallback, RuleContext, RuleContextWrapper, RuleMetadata,
+    Severity, Wrapper,
+};
+
+/// The context list the violations are pushed to.
+pub const VIOLATIONS: &str = "violations";
+
+/// What a validation rule checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    /// The key is not set, see `require_key`.
+    Missing,
+    /// The value doesn't have the type checked.
+    WrongType,
+    /// The value is out of range, see `in_range`.
+    OutOfRange,
+    /// The value is not allowed, see `one_of`.
+    NotAllowed,
+    /// The string doesn't match the format, see `matches_format`.
+    InvalidFormat,
+}
+
+/// A failed validation check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    key: &'static str,
+    kind: ViolationKind,
+    message: String,
+    severity: Severity,
+}
+
+impl Violation {
+    pub fn get_key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn get_kind(&self) -> ViolationKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// `Severity::Critical` for an error, `Severity::Warning` for a finding
+    /// of a rule wrapped with `as_warning`.
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The violations recorded so far, in the order the rules fired.
+pub fn get_violations(rule_context: &RuleContextWrapper) -> Vec<Violation> {
+    rule_context
+        .get_list::<Violation>(VIOLATIONS)
+        .map(|violations| violations.to_vec())
+        .unwrap_or_default()
+}
+
+/// Checks that `key` is set.
+pub fn require_key(key: &'static str) -> Wrapper<AllRule> {
+    validator("require_key", key, move |rule_context| {
+        match rule_context.lookup(key) {
+            Some(_) => None,
+            None => Some(violation(
+                key,
+                ViolationKind::Missing,
+                format!("`{key}` is required"),
+            )),
+        }
+    })
+}
+
+/// Checks that the value of `key`, of type `T`, lies in `range`.
+pub fn in_range<T, R>(key: &'static str, range: R) -> Wrapper<AllRule>
+where
+    T: PartialOrd + fmt::Debug + 'static,
+    R: RangeBounds<T> + fmt::Debug + 'static,
+{
+    validator("in_range", key, move |rule_context| {
+        check::<T>(rule_context, key, |value| {
+            (!range.contains(value)).then(|| {
+                violation(
+                    key,
+                    ViolationKind::OutOfRange,
+                    format!("`{key}` must be in {range:?}, got {value:?}"),
+                )
+            })
+        })
+    })
+}
+
+/// Checks that the value of `key`, of type `T`, is one of `allowed`.
+pub fn one_of<T: PartialEq + fmt::Debug + 'static>(
+    key: &'static str,
+    allowed: impl IntoIterator<Item = T>,
+) -> Wrapper<AllRule> {
+    let allowed: Vec<T> = allowed.into_iter().collect();
+    validator("one_of", key, move |rule_context| {
+        check::<T>(rule_context, key, |value| {
+            (!allowed.contains(value)).then(|| {
+                violation(
+                    key,
+                    ViolationKind::NotAllowed,
+                    format!("`{key}` must be one of {allowed:?}, got {value:?}"),
+                )
+            })
+        })
+    })
+}
+
+/// Checks that the value of `key`, a `String` or a `&str`, matches the
+/// regular expression `pattern`. Anchor the pattern with `^` and `$` to match
+/// the whole string.
+#[cfg(feature = "regex")]
+pub fn matches_format(key: &'static str, pattern: &str) -> Result<Wrapper<AllRule>, regex::Error> {
+    let regex = regex::Regex::new(pattern)?;
+    Ok(validator("matches_format", key, move |rule_context| {
+        let value = rule_context.lookup(key)?;
+        let Some(text) = text_of(value.as_ref()) else {
+            return Some(wrong_type(key, "a string"));
+        };
+        (!regex.is_match(text)).then(|| {
+            violation(
+                key,
+                ViolationKind::InvalidFormat,
+                format!("`{key}` must match `{}`, got {text:?}", regex.as_str()),
+            )
+        })
+    }))
+}
+
+/// Makes the violations recorded by `rule` warnings rather than errors, so
+/// that they don't fail the status of the run. Works for any rule pushing
+/// violations to `VIOLATIONS` from its execute callback.
+pub fn as_warning(mut rule: Wrapper<AllRule>) -> Wrapper<AllRule> {
+    let original = wrap(rule.borrow().clone());
+    rule.on_execute(move |this| {
+        let rule_context = this.get_rule_context();
+        let recorded = get_violations(&rule_context).len();
+        let reported = rule_context.borrow().get_reported_warnings();
+        {
+            let mut original = original.borrow_mut();
+            original.set_rule_context(rule_context.clone());
+            original.run_execute();
+        }
+        let mut violations = get_violations(&rule_context);
+        if violations.len() <= recorded {
+            return;
+        }
+        let mut rule_context = rule_context.borrow_mut();
+        for violation in &mut violations[recorded..] {
+            violation.severity = Severity::Warning;
+        }
+        for warning in rule_context.take_reported_warnings(reported) {
+            let severity = warning.get_severity().min(Severity::Warning);
+            rule_context.push_warning(severity, warning.get_message().to_string());
+        }
+        rule_context.set_list(VIOLATIONS, violations);
+    })
+}
+
+/// A rule named after the check and the key, applying when `check` finds a
+/// violation, which it then records.
+fn validator(
+    name: &str,
+    key: &'static str,
+    check: impl Fn(&RuleContext) -> Option<Violation> + 'static,
+) -> Wrapper<AllRule> {
+    let check = Rc::new(check);
+    let eval = check.clone();
+    AllRule::new()
+        .with_name(&format!("{name}({key})"))
+        .on_eval(move |ctx| eval(&ctx.get_rule_context().borrow()).is_some())
+        .on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            let found = check(&rule_context.borrow());
+            if let Some(violation) = found {
+                rule_context
+                    .borrow()
+                    .push_warning(violation.severity, violation.message.clone());
+                rule_context.push_to_list(VIOLATIONS, violation);
+            }
+        })
+}
+
+/// Runs `check` on the value of `key` when it is set, reporting a value of
+/// another type than `T` as a violation.
+fn check<T: 'static>(
+    rule_context: &RuleContext,
+    key: &'static str,
+    check: impl FnOnce(&T) -> Option<Violation>,
+) -> Option<Violation> {
+    let value = rule_context.lookup(key)?;
+    match value.downcast_ref::<T>() {
+        Some(value) => check(value),
+        None => Some(wrong_type(
+            key,
+            &format!("of type `{}`", std::any::type_name::<T>()),
+        )),
+    }
+}
+
+fn violation(key: &'static str, kind: ViolationKind, message: String) -> Violation {
+    Violation {
+        key,
+        kind,
+        message,
+        severity: Severity::Critical,
+    }
+}
+
+fn wrong_type(key: &'static str, expected: &str) -> Violation {
+    violation(
+        key,
+        ViolationKind::WrongType,
+        format!("`{key}` must be {expected}"),
+    )
+}
+
+#[cfg(feature = "regex")]
+fn text_of(value: &dyn Any) -> Option<&str> {
+    if let Some(text) = value.downcast_ref::<String>() {
+        return Some(text);
+    }
+    value.downcast_ref::<&'static str>().copied()
+}
@@ -0,0 +1,133 @@
+//! Bindings for running JSON-defined rule sets in the browser.
+//!
+//! Browser code can't register Rust closures, so the rule sets loaded here
+//! come with their own callbacks: conditions are expressions, see
+//! `dredd_rs::expr`, and actions are the values they set, in the document
+//! format read by `dredd_rs::loader::from_document_json`:
+//!
+//! ```json
+//! {
+//!   "conditions": { "is_adult": "age >= 18" },
+//!   "actions": { "approve": { "approved": true } },
+//!   "rules": { "type": "all", "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }] }
+//! }
+//! ```
+//!
+//! Contexts and reports cross the boundary as JSON strings, and errors as
+//! strings thrown to JavaScript. Build with `wasm-pack build --features wasm`
+//! to get the JavaScript module.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dredd_rs::wasm::WasmRules;
+//!
+//! let rules = WasmRules::new(r#"{
+//!     "conditions": { "is_adult": "age >= 18" },
+//!     "actions": { "approve": { "approved": true } },
+//!     "rules": { "type": "all", "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }] }
+//! }"#).unwrap();
+//!
+//! let report = rules.evaluate(r#"{ "age": 42 }"#).unwrap();
+//! let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+//!
+//! assert_eq!(report["fired"], serde_json::json!(["adult"]));
+//! assert_eq!(report["context"]["approved"], true);
+//! ```
+
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    loader::{self, LoadedRules},
+    rule::{RuleContext, RuleContextWrapper},
+    scenario::{context_to_json, get_value, intern, report_to_json, set_value},
+};
+
+/// A rule set loaded from a JSON document.
+#[wasm_bindgen]
+pub struct WasmRules {
+    rules: LoadedRules,
+}
+
+#[wasm_bindgen]
+impl WasmRules {
+    /// Loads a rule set document, failing when it is not valid JSON, when
+    /// an expression doesn't parse, or when a rule names an unknown callback.
+    #[wasm_bindgen(constructor)]
+    pub fn new(document: &str) -> Result<WasmRules, String> {
+        let rules = loader::from_document_json(document).map_err(|error| error.to_string())?;
+        Ok(WasmRules { rules })
+    }
+
+    /// Runs the rules against a context given as a JSON object and returns
+    /// the report of the run as JSON.
+    pub fn evaluate(&self, context: &str) -> Result<String, String> {
+        let mut rule_context = WasmContext::from_json(context)?;
+        Ok(self.run(&mut rule_context))
+    }
+
+    /// Runs the rules against a context, which keeps the values they set,
+    /// and returns the report of the run as JSON.
+    pub fn run(&self, context: &mut WasmContext) -> String {
+        let report = self.rules.run_with_report(context.rule_context.clone());
+        report_to_json(&report, &context.rule_context).to_string()
+    }
+}
+
+/// A `RuleContext` whose values are read and written as JSON.
+///
+/// Only values JSON can represent are visible: numbers, strings, booleans,
+/// and the arrays and objects set from JavaScript.
+#[wasm_bindgen]
+pub struct WasmContext {
+    rule_context: RuleContextWrapper,
+}
+
+#[wasm_bindgen]
+impl WasmContext {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmContext {
+        WasmContext {
+            rule_context: RuleContext::new(),
+        }
+    }
+
+    /// Creates a context holding the values of a JSON object.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmContext, String> {
+        let values: Map<String, Value> =
+            serde_json::from_str(json).map_err(|error| error.to_string())?;
+        let mut context = WasmContext::new();
+        for (key, value) in &values {
+            set_value(&mut context.rule_context, intern(key), value);
+        }
+        Ok(context)
+    }
+
+    /// Sets a key to a value given as JSON.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value: Value = serde_json::from_str(value).map_err(|error| error.to_string())?;
+        set_value(&mut self.rule_context, intern(key), &value);
+        Ok(())
+    }
+
+    /// The value of a key as JSON, if it is set to a value JSON can represent.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let rule_context = self.rule_context.borrow();
+        let value = rule_context.lookup(key)?;
+        get_value(value.as_ref()).map(|value| value.to_string())
+    }
+
+    /// The values of the context as a JSON object.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        context_to_json(&self.rule_context).to_string()
+    }
+}
+
+impl Default for WasmContext {
+    fn default() -> Self {
+        WasmContext::new()
+    }
+}
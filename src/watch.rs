@@ -0,0 +1,262 @@
+//! Reloads a rule set registered in a `RuleRegistry` when its definition
+//! file changes, without restarting.
+//!
+//! A `RuleWatcher` watches the file with `notify` and, whenever the file is
+//! written, created or renamed into place, reads it again, loads it with the
+//! function it was given, such as `loader::from_json` with a
+//! `CallbackRegistry`, and swaps the rule set registered under its name.
+//!
+//! Rules aren't `Send`, so changes are picked up on the thread that owns the
+//! registry, by calling `poll` or `poll_timeout` from its loop, between runs.
+//! The swap replaces the whole rule set at once: a run started before it
+//! finishes with the old rules, and runs started after it see the new ones.
+//! When the file can't be read or doesn't load, the rule set registered
+//! before is kept and the error is passed to the `on_error` callback, so a
+//! half-saved file never takes rules down. Removing the file keeps the rule
+//! set as well, until the file is created again.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::{fs, time::Duration};
+//!
+//! use dredd_rs::loader::{self, CallbackRegistry};
+//! use dredd_rs::rule::*;
+//! use dredd_rs::watch::RuleWatcher;
+//!
+//! let dir = std::env::temp_dir().join(format!("dredd-watch-doc-{}", std::process::id()));
+//! fs::create_dir_all(&dir).unwrap();
+//! let path = dir.join("checkout.json");
+//! fs::write(&path, r#"{ "type": "all", "state": "active", "rules": [{ "name": "old", "execute": "approve" }] }"#).unwrap();
+//!
+//! let mut callbacks = CallbackRegistry::new();
+//! callbacks.action("approve", |ctx| ctx.set("approved", true));
+//!
+//! let watcher = RuleWatcher::new(&path, "checkout", move |json| loader::from_json(json, &callbacks))
+//!     .unwrap()
+//!     .on_error(|error| eprintln!("keeping the previous rules: {error}"));
+//!
+//! let mut registry = RuleRegistry::new();
+//! watcher.reload(&mut registry).unwrap();
+//!
+//! fs::write(&path, r#"{ "type": "all", "state": "active", "rules": [] }"#).unwrap();
+//! assert!(watcher.poll_timeout(&mut registry, Duration::from_secs(5)));
+//!
+//! let rule_context = RuleContext::new();
+//! registry.execute("checkout", rule_context.clone()).unwrap();
+//! assert!(rule_context.get::<bool>("approved").is_none());
+//! # fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    loader::{LoadedRules, LoaderError},
+    rule::RuleRegistry,
+    time::Instant,
+};
+
+/// How long `poll_timeout` waits without events before reloading.
+const SETTLE: Duration = Duration::from_millis(50);
+
+type LoadFn = Box<dyn Fn(&str) -> Result<LoadedRules, LoaderError>>;
+
+/// Watches a rule definition file and reloads the rule set registered under
+/// a name when it changes.
+pub struct RuleWatcher {
+    path: PathBuf,
+    rule_set: String,
+    load: LoadFn,
+    on_error: Box<dyn Fn(&WatchError)>,
+    events: Receiver<notify::Result<Event>>,
+    // Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+}
+
+impl RuleWatcher {
+    /// Starts watching `path`, whose content is loaded with `load` into the
+    /// rule set `rule_set`. Nothing is loaded until `reload` or a change.
+    ///
+    /// The directory of the file is watched rather than the file itself, so
+    /// that editors saving by renaming a new file into place are picked up.
+    pub fn new(
+        path: impl AsRef<Path>,
+        rule_set: &str,
+        load: impl Fn(&str) -> Result<LoadedRules, LoaderError> + 'static,
+    ) -> Result<Self, WatchError> {
+        let path = path.as_ref().to_path_buf();
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+        Ok(RuleWatcher {
+            path,
+            rule_set: rule_set.to_string(),
+            load: Box::new(load),
+            on_error: Box::new(|_| {}),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Sets the callback receiving the errors of the reloads triggered by
+    /// changes. By default they are ignored.
+    pub fn on_error(mut self, on_error: impl Fn(&WatchError) + 'static) -> Self {
+        self.on_error = Box::new(on_error);
+        self
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_rule_set(&self) -> &str {
+        &self.rule_set
+    }
+
+    /// Reads and loads the file now and, if it loads, registers it in place
+    /// of the current rule set. On error, the registry is left untouched.
+    pub fn reload(&self, registry: &mut RuleRegistry) -> Result<(), WatchError> {
+        let content = fs::read_to_string(&self.path)?;
+        let rules = Rc::new((self.load)(&content)?);
+        registry.register_with(&self.rule_set, move |rule_context| {
+            rules.run_with_report(rule_context)
+        });
+        Ok(())
+    }
+
+    /// Reloads the rule set if the file changed since the last poll, without
+    /// blocking, and returns whether a new rule set was registered. Several
+    /// changes in a row are reloaded once.
+    pub fn poll(&self, registry: &mut RuleRegistry) -> bool {
+        self.drain() && self.reload_reporting(registry)
+    }
+
+    /// Like `poll`, but waits up to `timeout` for the file to change. Once it
+    /// does, waits for the events to settle so that the file is reloaded once
+    /// it has been written entirely.
+    pub fn poll_timeout(&self, registry: &mut RuleRegistry, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(event) => {
+                    if self.is_change(event) {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return false,
+            }
+        }
+        while let Ok(event) = self.events.recv_timeout(SETTLE) {
+            self.is_change(event);
+        }
+        self.reload_reporting(registry)
+    }
+
+    /// Takes the pending events, returning whether one of them is a change.
+    fn drain(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            changed |= self.is_change(event);
+        }
+        changed
+    }
+
+    fn reload_reporting(&self, registry: &mut RuleRegistry) -> bool {
+        match self.reload(registry) {
+            Ok(()) => true,
+            Err(error) => {
+                (self.on_error)(&error);
+                false
+            }
+        }
+    }
+
+    /// Whether an event is a write, creation or rename of the watched file.
+    /// Errors of the watcher itself are reported as they arrive.
+    fn is_change(&self, event: notify::Result<Event>) -> bool {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                (self.on_error)(&WatchError::Notify(error));
+                return false;
+            }
+        };
+        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == self.path.file_name())
+    }
+}
+
+impl fmt::Debug for RuleWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuleWatcher")
+            .field("path", &self.path)
+            .field("rule_set", &self.rule_set)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors of watching or reloading a rule definition file.
+#[derive(Debug)]
+pub enum WatchError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file was read but didn't load.
+    Load(LoaderError),
+    /// The file couldn't be watched.
+    Notify(notify::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::Io(e) => write!(f, "can't read rule definition: {e}"),
+            WatchError::Load(e) => write!(f, "can't load rule definition: {e}"),
+            WatchError::Notify(e) => write!(f, "can't watch rule definition: {e}"),
+        }
+    }
+}
+
+impl Error for WatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WatchError::Io(e) => Some(e),
+            WatchError::Load(e) => Some(e),
+            WatchError::Notify(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for WatchError {
+    fn from(e: io::Error) -> Self {
+        WatchError::Io(e)
+    }
+}
+
+impl From<LoaderError> for WatchError {
+    fn from(e: LoaderError) -> Self {
+        WatchError::Load(e)
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(e: notify::Error) -> Self {
+        WatchError::Notify(e)
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_all_rule_context() {
+        let mut rule = AllRule::new();
+        let mut rule2 = AllRule::new();
+
+        let evals = Rc::new(RefCell::new(Vec::new()));
+        rule.on_eval({
+            let evals = evals.clone();
+            move |ctx| {
+                evals.borrow_mut().push("eval_1");
+                *ctx.get::<bool>("start").unwrap()
+            }
+        })
+        .on_pre_execute(|this| {
+            this.get_rule_context().set("pre_execute_1", true);
+        })
+        .on_execute(|this| {
+            this.get_rule_context().set("execute_1", true);
+        })
+        .on_post_execute(|this| {
+            this.get_rule_context().set("post_execute_1", true);
+        })
+        .add_child(
+            rule2
+                .on_eval({
+                    let evals = evals.clone();
+                    move |_| {
+                        evals.borrow_mut().push("eval_2");
+                        true
+                    }
+                })
+                .on_pre_execute(|this| {
+                    this.get_rule_context().set("pre_execute_2", true);
+                })
+                .on_execute(|this| {
+                    this.get_rule_context().set("execute_2", true);
+                })
+                .on_post_execute(|this| {
+                    this.get_rule_context().set("post_execute_2", true);
+                }),
+        );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("start", true);
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("start").unwrap());
+        assert_eq!(*evals.borrow(), ["eval_1", "eval_2"]);
+        assert!(*rule_context.get::<bool>("pre_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("post_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("pre_execute_2").unwrap());
+        assert!(*rule_context.get::<bool>("execute_2").unwrap());
+        assert!(*rule_context.get::<bool>("post_execute_2").unwrap());
+    }
+
+    #[test]
+    fn test_all_rule_should_run_every_sibling_on_eval_true() {
+        let mut rule = AllRule::new();
+        rule.on_eval(|_| true)
+            .on_execute(|this| this.get_rule_context().set("rule1", true)); //TRUE
+
+        let mut rule2 = AllRule::new();
+        rule2
+            .on_eval(|_| false)
+            .on_execute(|this| this.get_rule_context().set("rule2", true)); //FALSE
+
+        let mut rule3 = AllRule::new();
+        rule3
+            .on_eval(|_| true)
+            .on_execute(|this| this.get_rule_context().set("rule3", true)); //TRUE
+
+        let rule_context = RuleContext::new();
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule, rule2, rule3]);
+
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+    }
+
+    #[test]
+    fn test_all_rule_should_run_every_child_in_order() {
+        let mut rule = AllRule::new();
+        rule.on_eval(|_| true);
+
+        let mut rule2 = AllRule::new();
+        rule2.on_eval(|_| true).on_execute(|this| {
+            let order = this.get_rule_context().get::<String>("order").unwrap();
+            this.get_rule_context().set("order", format!("{order}2"));
+        });
+
+        let mut rule3 = AllRule::new();
+        rule3.on_eval(|_| false).on_execute(|this| {
+            let order = this.get_rule_context().get::<String>("order").unwrap();
+            this.get_rule_context().set("order", format!("{order}3"));
+        });
+
+        let mut rule4 = AllRule::new();
+        rule4.on_eval(|_| true).on_execute(|this| {
+            let order = this.get_rule_context().get::<String>("order").unwrap();
+            this.get_rule_context().set("order", format!("{order}4"));
+        });
+
+        rule.add_children(vec![rule2, rule3, rule4]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("order", String::new());
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<String>("order").unwrap(), "24");
+    }
+
+    #[test]
+    fn test_all_rule_should_not_run_children_on_eval_false() {
+        let mut rule = AllRule::new();
+        rule.on_eval(|_| false);
+
+        let mut rule2 = AllRule::new();
+        rule2
+            .on_eval(|_| true)
+            .on_execute(|this| this.get_rule_context().set("rule2", true));
+
+        rule.add_child(rule2);
+
+        let rule_context = RuleContext::new();
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+    }
+}
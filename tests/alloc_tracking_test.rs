@@ -0,0 +1,34 @@
+#![cfg(feature = "alloc-tracking")]
+
+use dredd_rs::alloc_tracking::TrackingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_allocations_are_attributed_to_rules() {
+        let rule = BestFirstRule::new().with_name("parent").add_children(vec![
+            BestFirstRule::new().with_name("quiet").on_eval(|_| false),
+            BestFirstRule::new().with_name("noisy").on_execute(|this| {
+                let values: Vec<u64> = (0..1000).collect();
+                this.get_rule_context().set("values", values);
+            }),
+        ]);
+
+        let report = Engine::best_first_runner().run_with_report(RuleContext::new(), vec![rule]);
+        let entries = report.get_trace().get_entries();
+
+        let noisy = &entries[2];
+        assert_eq!(noisy.get_name(), Some("noisy"));
+        assert!(noisy.get_allocations() >= 2);
+        assert!(noisy.get_allocated_bytes() >= 8000);
+        assert!(entries[1].get_allocated_bytes() < noisy.get_allocated_bytes());
+        assert!(entries[0].get_allocated_bytes() >= noisy.get_allocated_bytes());
+        assert!(report.get_allocated_bytes() >= entries[0].get_allocated_bytes());
+        assert!(report.get_allocations() >= entries[0].get_allocations());
+    }
+}
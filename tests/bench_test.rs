@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::bench;
+    use dredd_rs::rule::*;
+
+    fn discount_rules(threshold: u32) -> impl Fn(Rc<RefCell<RuleContext>>) -> RunReport {
+        move |rule_context| {
+            let rule = BestFirstRule::new()
+                .with_name("discount")
+                .add_children(vec![
+                    BestFirstRule::new()
+                        .with_name("big")
+                        .on_eval(move |ctx| *ctx.get::<u32>("total").unwrap() > threshold)
+                        .on_execute(|this| this.get_rule_context().set("discount", 10u32)),
+                    BestFirstRule::new()
+                        .with_name("none")
+                        .on_execute(|this| this.get_rule_context().set("discount", 0u32)),
+                ]);
+            Engine::best_first_runner().run_with_report(rule_context, vec![rule])
+        }
+    }
+
+    fn corpus() -> Vec<Rc<RefCell<RuleContext>>> {
+        [50u32, 150, 250, 350]
+            .into_iter()
+            .map(|total| {
+                let mut rule_context = RuleContext::new();
+                rule_context.set("total", total);
+                rule_context
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_reports_differences_and_latency() {
+        let corpus = corpus();
+        let comparison = bench::compare(discount_rules(100), discount_rules(200), &corpus);
+
+        let differences = comparison.get_differences();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].get_index(), 1);
+        assert_eq!(differences[0].get_a(), r#"["discount", "big"]"#);
+        assert_eq!(differences[0].get_b(), r#"["discount", "none"]"#);
+
+        for stats in [comparison.get_a(), comparison.get_b()] {
+            assert_eq!(stats.get_runs(), 4);
+            assert!(stats.get_p50() <= stats.get_p95());
+            assert!(stats.get_p95() <= stats.get_max());
+            assert!(stats.get_throughput() > 0.0);
+        }
+        assert!(comparison.to_string().contains("differing outcomes: 1/4"));
+
+        // the corpus is copied for every run
+        assert!(corpus[0].get::<u32>("discount").is_none());
+    }
+
+    #[test]
+    fn test_compare_by_context_values() {
+        let comparison = bench::compare_by(
+            discount_rules(100),
+            discount_rules(300),
+            &corpus(),
+            |rule_context, _| *rule_context.get::<u32>("discount").unwrap(),
+        );
+
+        let indexes: Vec<_> = comparison
+            .get_differences()
+            .iter()
+            .map(|difference| difference.get_index())
+            .collect();
+        assert_eq!(indexes, vec![1, 2]);
+    }
+}
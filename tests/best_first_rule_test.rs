@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::{cell::RefCell, rc::Rc};
 
     use dredd_rs::rule::*;
 
@@ -9,9 +9,13 @@ mod tests {
         let mut rule = BestFirstRule::new();
         let mut rule2 = BestFirstRule::new();
 
-        rule.on_eval(|this| {
-            this.get_rule_context().set("eval_1", true);
-            return *this.get_rule_context().get::<bool>("start").unwrap();
+        let evals = Rc::new(RefCell::new(Vec::new()));
+        rule.on_eval({
+            let evals = evals.clone();
+            move |ctx| {
+                evals.borrow_mut().push("eval_1");
+                *ctx.get::<bool>("start").unwrap()
+            }
         })
         .on_pre_execute(|this| {
             this.get_rule_context().set("pre_execute_1", true);
@@ -24,9 +28,12 @@ mod tests {
         })
         .add_child(
             rule2
-                .on_eval(|this| {
-                    this.get_rule_context().set("eval_2", true);
-                    true
+                .on_eval({
+                    let evals = evals.clone();
+                    move |_| {
+                        evals.borrow_mut().push("eval_2");
+                        true
+                    }
                 })
                 .on_pre_execute(|this| {
                     this.get_rule_context().set("pre_execute_2", true);
@@ -45,11 +52,10 @@ mod tests {
         Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
 
         assert!(*rule_context.get::<bool>("start").unwrap());
-        assert!(*rule_context.get::<bool>("eval_1").unwrap());
+        assert_eq!(*evals.borrow(), ["eval_1", "eval_2"]);
         assert!(*rule_context.get::<bool>("pre_execute_1").unwrap());
         assert!(*rule_context.get::<bool>("execute_1").unwrap());
         assert!(*rule_context.get::<bool>("post_execute_1").unwrap());
-        assert!(*rule_context.get::<bool>("eval_2").unwrap());
         assert!(*rule_context.get::<bool>("pre_execute_2").unwrap());
         assert!(*rule_context.get::<bool>("execute_2").unwrap());
         assert!(*rule_context.get::<bool>("post_execute_2").unwrap());
@@ -93,22 +99,10 @@ mod tests {
 
         Engine::best_first_runner().run(rule_context.clone(), vec![rule, rule2]);
 
-        assert_eq!(
-            *rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)),
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)),
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)),
-            false
-        );
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)));
     }
 
     #[test]
@@ -139,22 +133,10 @@ mod tests {
 
         Engine::best_first_runner().run(rule_context.clone(), vec![rule, rule2]);
 
-        assert_eq!(
-            *rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)),
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)),
-            true
-        );
+        assert!(!*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)));
     }
 
     #[test]
@@ -236,53 +218,136 @@ mod tests {
 
         Engine::best_first_runner().run(rule_context.clone(), vec![rule, rule2, rule3]);
 
-        assert_eq!(
-            *rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)), //TRUE
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule5").unwrap_or(Rc::new(false)), //TRUE
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule6").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule7").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule8").unwrap_or(Rc::new(false)), //TRUE
-            true
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule9").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule10").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule11").unwrap_or(Rc::new(false)),
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false))); //TRUE
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule5").unwrap_or(Rc::new(false))); //TRUE
+        assert!(!*rule_context.get::<bool>("rule6").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule7").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule8").unwrap_or(Rc::new(false))); //TRUE
+        assert!(!*rule_context.get::<bool>("rule9").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule10").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule11").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule12").unwrap_or(Rc::new(false))); //TRUE
+    }
+
+    #[test]
+    fn test_best_first_rule_children_priority() {
+        let low = BestFirstRule::new()
+            .with_name("low")
+            .on_execute(|this| this.get_rule_context().set("winner", "low"));
+        let default = BestFirstRule::new()
+            .with_name("default")
+            .on_execute(|this| this.get_rule_context().set("winner", "default"));
+        let high = BestFirstRule::new()
+            .with_name("high")
+            .on_eval(|ctx| *ctx.get::<bool>("high").unwrap())
+            .on_execute(|this| this.get_rule_context().set("winner", "high"));
+        let higher = BestFirstRule::new().with_name("higher").on_eval(|_| false);
+
+        let rule = BestFirstRule::new()
+            .add_child_with_priority(low, -10)
+            .add_child(default)
+            .add_child_with_priority(high, 10)
+            .add_child_with_priority(higher, 20);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("high", true);
+        let report =
+            Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule.clone()]);
+
+        assert_eq!(*rule_context.get::<&str>("winner").unwrap(), "high");
+        let names: Vec<_> = report
+            .get_trace()
+            .get_entries()
+            .iter()
+            .filter_map(|entry| entry.get_name())
+            .collect();
+        assert_eq!(names, vec!["higher", "high"]);
+
+        rule_context.set("high", false);
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<&str>("winner").unwrap(), "default");
+    }
+
+    #[test]
+    fn test_best_first_builder_children_priority() {
+        let rule = BestFirstRule::builder()
+            .child(BestFirstRule::new().on_execute(|this| this.get_rule_context().set("winner", 1)))
+            .child_with_priority(
+                BestFirstRule::new().on_execute(|this| this.get_rule_context().set("winner", 2)),
+                1,
+            )
+            .build();
+
+        let rule_context = RuleContext::new();
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<i32>("winner").unwrap(), 2);
+    }
+
+    fn set_branch(branch: &'static str) -> Rc<std::cell::RefCell<BestFirstRule>> {
+        BestFirstRule::new()
+            .with_name(branch)
+            .on_execute(move |this| this.get_rule_context().set("branch", branch))
+    }
+
+    fn when_tier(
+        tier: &'static str,
+        branch: &'static str,
+    ) -> Rc<std::cell::RefCell<BestFirstRule>> {
+        set_branch(branch).on_eval(move |ctx| {
+            ctx.get::<&str>("tier")
+                .is_some_and(|current| *current == tier)
+        })
+    }
+
+    #[test]
+    fn test_best_first_default_child_fires_when_nothing_matches() {
+        let rule = BestFirstRule::new()
+            .add_default_child(set_branch("otherwise"))
+            .add_child(when_tier("gold", "gold"))
+            .add_child_with_priority(when_tier("silver", "silver"), -1);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("tier", "bronze");
+        let report =
+            Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule.clone()]);
+        assert_eq!(*rule_context.get::<&str>("branch").unwrap(), "otherwise");
+        assert_eq!(report.get_trace().get_executed_names(), vec!["otherwise"]);
+
+        rule_context.set("tier", "silver");
+        let report =
+            Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule.clone()]);
+        assert_eq!(*rule_context.get::<&str>("branch").unwrap(), "silver");
+        assert_eq!(report.get_trace().get_executed_names(), vec!["silver"]);
+
+        let children = rule.borrow_mut().get_children();
+        let names: Vec<_> = children
+            .iter()
+            .map(|child| child.borrow().get_name().unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["gold", "silver", "otherwise"]);
+    }
+
+    #[test]
+    fn test_best_first_default_child_not_fired_after_failure() {
+        let failing = BestFirstRule::new().on_eval(|ctx| {
+            ctx.fail(RuleError::failed("lookup failed"));
             false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule12").unwrap_or(Rc::new(false)), //TRUE
-            true
-        );
+        });
+        let rule = BestFirstRule::builder()
+            .child(failing)
+            .default_child(set_branch("otherwise"))
+            .build();
+
+        let rule_context = RuleContext::new();
+        let result = Engine::best_first_runner().try_run(rule_context.clone(), vec![rule.clone()]);
+
+        assert_eq!(result, Err(RuleError::failed("lookup failed")));
+        assert!(rule_context.get::<&str>("branch").is_none());
+        assert!(rule.borrow().get_default_child().is_some());
     }
 }
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_chain_rule_builder_run_child_on_eval_true() {
+        let rule = ChainRule::builder()
+            .on_eval(|_| true)
+            .on_pre_execute(|this| this.get_rule_context().set("pre_execute_1", true))
+            .on_execute(|this| this.get_rule_context().set("execute_1", true))
+            .on_post_execute(|this| this.get_rule_context().set("post_execute_1", true))
+            .child(
+                ChainRule::builder()
+                    .on_eval(|_| true)
+                    .on_execute(|this| this.get_rule_context().set("execute_2", true))
+                    .build(),
+            )
+            .build();
+
+        let rule_context = RuleContext::new();
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("pre_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("post_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("execute_2").unwrap());
+    }
+
+    #[test]
+    fn test_best_first_rule_builder_run_first_matching_child() {
+        let rule = BestFirstRule::builder()
+            .on_eval(|_| true)
+            .on_execute(|this| this.get_rule_context().set("rule1", true))
+            .child(
+                BestFirstRule::new()
+                    .on_eval(|_| false)
+                    .on_execute(|this| this.get_rule_context().set("rule2", true)),
+            )
+            .children(vec![
+                BestFirstRule::new()
+                    .on_eval(|_| true)
+                    .on_execute(|this| this.get_rule_context().set("rule3", true)),
+                BestFirstRule::new()
+                    .on_eval(|_| true)
+                    .on_execute(|this| this.get_rule_context().set("rule4", true)),
+            ])
+            .build();
+
+        let rule_context = RuleContext::new();
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule4").unwrap_or(Rc::new(false)));
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn context(total: u32) -> std::rc::Rc<std::cell::RefCell<RuleContext>> {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", total);
+        rule_context
+    }
+
+    fn discount() -> std::rc::Rc<std::cell::RefCell<BestFirstRule>> {
+        BestFirstRule::new()
+            .with_name("new_discount")
+            .on_eval(|ctx| *ctx.get::<u32>("total").unwrap() > 100)
+            .on_execute(|this| this.get_rule_context().set("discount", 10u32))
+            .add_child(
+                BestFirstRule::new()
+                    .on_execute(|this| this.get_rule_context().set("free_shipping", true)),
+            )
+    }
+
+    #[test]
+    fn test_canary_records_writes_without_touching_context() {
+        let rule_context = context(150);
+        let report = Engine::best_first_runner()
+            .run_with_report(rule_context.clone(), vec![Canary::wrap(discount())]);
+
+        let canaries = report.get_trace().get_canaries();
+        assert_eq!(canaries.len(), 1);
+        assert_eq!(canaries[0].get_name(), Some("new_discount"));
+        assert!(!canaries[0].is_executed());
+
+        let canary = canaries[0].get_canary().unwrap();
+        assert!(canary.would_execute());
+        assert_eq!(
+            canary.get_writes().get_keys(),
+            vec!["discount", "free_shipping"]
+        );
+        assert_eq!(*canary.get_writes().get::<u32>("discount").unwrap(), 10);
+
+        assert!(rule_context.get::<u32>("discount").is_none());
+        assert!(rule_context.get::<bool>("free_shipping").is_none());
+        assert_eq!(report.get_trace().get_entries().len(), 1);
+    }
+
+    #[test]
+    fn test_canary_not_executing() {
+        let report = Engine::best_first_runner()
+            .run_with_report(context(50), vec![Canary::wrap(discount())]);
+
+        let canary = report.get_trace().get_canaries()[0].get_canary().unwrap();
+        assert!(!canary.would_execute());
+        assert!(canary.get_writes().get_keys().is_empty());
+    }
+
+    #[test]
+    fn test_canary_does_not_affect_siblings() {
+        let live = BestFirstRule::new()
+            .with_name("live")
+            .on_execute(|this| this.get_rule_context().set("discount", 5u32));
+
+        let rule_context = context(150);
+        let report = Engine::best_first_runner()
+            .run_with_report(rule_context.clone(), vec![Canary::wrap(discount()), live]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["live"]);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_canary_failure_is_recorded_not_reported() {
+        let rule = Canary::wrap(
+            ChainRule::new()
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("no stock"))),
+        );
+
+        let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule.clone()]);
+        let canary = report.get_trace().get_canaries()[0].get_canary().unwrap();
+
+        assert_eq!(canary.get_error(), Some(&RuleError::failed("no stock")));
+        assert!(report.get_error().is_none());
+        assert!(Engine::chain_runner()
+            .try_run(RuleContext::new(), vec![rule])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_canary_counts_against_budget() {
+        let live = BestFirstRule::new().with_name("live");
+
+        let report = Engine::best_first_runner().run_with_budget(
+            context(150),
+            vec![Canary::wrap(discount()), live],
+            Budget::new().with_max_fired(2),
+        );
+
+        // The canary and the child fired in its shadow use up the budget.
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxFired(2)))
+        );
+        assert!(report.get_trace().get_executed_names().is_empty());
+    }
+}
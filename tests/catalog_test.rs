@@ -0,0 +1,120 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::catalog::{CatalogRule, RuleCatalog};
+    use dredd_rs::rule::*;
+
+    fn registry() -> RuleRegistry {
+        let adult = Condition::new(|ctx| ctx.get::<u32>("age").is_some_and(|age| *age >= 18))
+            .with_description("`age` >= 18");
+
+        let mut registry = RuleRegistry::new();
+        registry
+            .register(
+                "checkout",
+                Engine::all_runner(),
+                vec![AllRule::new()
+                    .with_id("R-1")
+                    .with_name("adult_check")
+                    .with_description("Approves customers that are 18 or older.")
+                    .with_owner("ana")
+                    .with_team("kyc")
+                    .with_tags(&["compliance"])
+                    .with_reads(&["age"])
+                    .with_writes(&["approved"])
+                    .on_condition(adult)
+                    .add_child(AllRule::new().with_name("notify"))],
+            )
+            .register_with("loaded", |rule_context| {
+                Engine::all_runner().run_with_report(rule_context, vec![])
+            });
+        registry
+    }
+
+    #[test]
+    fn test_catalog_from_registry() {
+        let catalog = RuleCatalog::from_registry(&registry());
+
+        let rule_sets = catalog.get_rule_sets();
+        assert_eq!(rule_sets.len(), 2);
+        assert_eq!(rule_sets[0].name, "checkout");
+        assert_eq!(
+            rule_sets[0].rules[0],
+            CatalogRule {
+                id: Some("R-1".to_string()),
+                name: Some("adult_check".to_string()),
+                description: Some("Approves customers that are 18 or older.".to_string()),
+                condition: Some("`age` >= 18".to_string()),
+                reads: vec!["age".to_string()],
+                writes: vec!["approved".to_string()],
+                owner: Some("ana".to_string()),
+                team: Some("kyc".to_string()),
+                tags: vec!["compliance".to_string()],
+            }
+        );
+        assert_eq!(rule_sets[0].rules[1].name.as_deref(), Some("notify"));
+        assert_eq!(rule_sets[0].rules[1].condition, None);
+        assert_eq!(rule_sets[1].name, "loaded");
+        assert!(rule_sets[1].rules.is_empty());
+    }
+
+    #[test]
+    fn test_catalog_to_json() {
+        let json = RuleCatalog::from_registry(&registry()).to_json_value();
+
+        let rule = &json["rule_sets"][0]["rules"][0];
+        assert_eq!(rule["name"], "adult_check");
+        assert_eq!(rule["condition"], "`age` >= 18");
+        assert_eq!(rule["writes"][0], "approved");
+        assert_eq!(json["rule_sets"][1]["name"], "loaded");
+    }
+
+    #[test]
+    fn test_catalog_to_markdown() {
+        let markdown = RuleCatalog::from_registry(&registry()).to_markdown();
+
+        assert_eq!(
+            markdown,
+            "# Rule catalog\n\
+             \n\
+             ## checkout\n\
+             \n\
+             ### adult_check\n\
+             \n\
+             Approves customers that are 18 or older.\n\
+             \n\
+             - **Id:** `R-1`\n\
+             - **Condition:** `age` >= 18\n\
+             - **Reads:** `age`\n\
+             - **Writes:** `approved`\n\
+             - **Owner:** ana\n\
+             - **Team:** kyc\n\
+             - **Tags:** `compliance`\n\
+             \n\
+             ### notify\n\
+             \n\
+             ## loaded\n\
+             \n\
+             No documented rules.\n"
+        );
+    }
+
+    #[test]
+    fn test_catalog_rule_sets_sorted_and_replaced() {
+        let metadata = registry().get_rules("checkout").to_vec();
+
+        let catalog = RuleCatalog::new()
+            .rule_set("zeta", &[])
+            .rule_set("alpha", &metadata)
+            .rule_set("zeta", &metadata[1..]);
+
+        let names: Vec<&str> = catalog
+            .get_rule_sets()
+            .iter()
+            .map(|rule_set| rule_set.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["alpha", "zeta"]);
+        assert_eq!(catalog.get_rule_sets()[1].rules.len(), 1);
+    }
+}
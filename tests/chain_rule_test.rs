@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::{cell::RefCell, rc::Rc};
 
     use dredd_rs::rule::*;
 
@@ -9,9 +9,13 @@ mod tests {
         let mut rule = ChainRule::new();
         let mut rule2 = ChainRule::new();
 
-        rule.on_eval(|this| {
-            this.get_rule_context().set("eval_1", true);
-            return *this.get_rule_context().get::<bool>("start").unwrap();
+        let evals = Rc::new(RefCell::new(Vec::new()));
+        rule.on_eval({
+            let evals = evals.clone();
+            move |ctx| {
+                evals.borrow_mut().push("eval_1");
+                *ctx.get::<bool>("start").unwrap()
+            }
         })
         .on_pre_execute(|this| {
             this.get_rule_context().set("pre_execute_1", true);
@@ -24,9 +28,12 @@ mod tests {
         })
         .add_child(
             rule2
-                .on_eval(|this| {
-                    this.get_rule_context().set("eval_2", true);
-                    true
+                .on_eval({
+                    let evals = evals.clone();
+                    move |_| {
+                        evals.borrow_mut().push("eval_2");
+                        true
+                    }
                 })
                 .on_pre_execute(|this| {
                     this.get_rule_context().set("pre_execute_2", true);
@@ -45,11 +52,10 @@ mod tests {
         Engine::chain_runner().run(rule_context.clone(), vec![rule]);
 
         assert!(*rule_context.get::<bool>("start").unwrap());
-        assert!(*rule_context.get::<bool>("eval_1").unwrap());
+        assert_eq!(*evals.borrow(), ["eval_1", "eval_2"]);
         assert!(*rule_context.get::<bool>("pre_execute_1").unwrap());
         assert!(*rule_context.get::<bool>("execute_1").unwrap());
         assert!(*rule_context.get::<bool>("post_execute_1").unwrap());
-        assert!(*rule_context.get::<bool>("eval_2").unwrap());
         assert!(*rule_context.get::<bool>("pre_execute_2").unwrap());
         assert!(*rule_context.get::<bool>("execute_2").unwrap());
         assert!(*rule_context.get::<bool>("post_execute_2").unwrap());
@@ -115,14 +121,8 @@ mod tests {
         Engine::chain_runner().run(rule_context.clone(), vec![rule]);
 
         assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
-        assert_eq!(
-            *rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)),
-            false
-        );
-        assert_eq!(
-            *rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)),
-            false
-        );
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
     }
 
     #[test]
@@ -174,4 +174,23 @@ mod tests {
         assert!(*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
         assert!(*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
     }
+
+    fn count_rules<T: Rule<T>>(rule: &std::cell::RefCell<T>) -> usize {
+        let children = rule.borrow_mut().get_children();
+        1 + children
+            .iter()
+            .map(|child| count_rules(child))
+            .sum::<usize>()
+    }
+
+    #[test]
+    fn test_chain_rule_children_traversed_through_rule_trait() {
+        let chain = ChainRule::new().add_child(ChainRule::new().add_child(ChainRule::new()));
+        let best_first =
+            BestFirstRule::new().add_children(vec![BestFirstRule::new(), BestFirstRule::new()]);
+
+        assert_eq!(count_rules(&chain), 3);
+        assert_eq!(count_rules(&ChainRule::new()), 1);
+        assert_eq!(count_rules(&best_first), 3);
+    }
 }
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn journal(rule_context: &RuleContext) -> Vec<(&'static str, ChangeKind, Option<String>)> {
+        rule_context
+            .changes()
+            .iter()
+            .map(|change| {
+                (
+                    change.get_key(),
+                    change.get_kind(),
+                    change.get_rule().map(str::to_string),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_changes_made_by_rules() {
+        let rule = ChainRule::new()
+            .with_name("pricing")
+            .on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                rule_context.set("total", 90);
+                rule_context.push_to_list("notes", "discounted".to_string());
+            })
+            .add_child(ChainRule::new().with_id("R-2").on_execute(|this| {
+                *this.get_rule_context().get_int_mut("points").unwrap() += 9;
+            }));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 100);
+        rule_context.set("points", 0i64);
+        rule_context.borrow_mut().start_tracking();
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(
+            journal(&rule_context.borrow()),
+            vec![
+                ("total", ChangeKind::Update, Some("pricing".to_string())),
+                ("notes", ChangeKind::Insert, Some("pricing".to_string())),
+                ("points", ChangeKind::Update, Some("R-2".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changes_outside_runs() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("untracked", true);
+        assert!(!rule_context.borrow().is_tracking());
+
+        rule_context.borrow_mut().start_tracking();
+        rule_context.push_to_list("tags", 1);
+        rule_context.push_to_list("tags", 2);
+        assert!(rule_context.borrow_mut().remove("untracked"));
+        assert!(!rule_context.borrow_mut().remove("missing"));
+
+        assert_eq!(
+            rule_context.borrow().changes()[2].to_string(),
+            "remove `untracked`"
+        );
+        assert!(rule_context.get::<bool>("untracked").is_none());
+
+        let changes = rule_context.borrow_mut().stop_tracking();
+        let kinds: Vec<_> = changes.iter().map(ContextChange::get_kind).collect();
+        assert_eq!(
+            kinds,
+            [ChangeKind::Insert, ChangeKind::Update, ChangeKind::Remove]
+        );
+        assert!(rule_context.borrow().changes().is_empty());
+
+        rule_context.set("after", 1);
+        assert!(rule_context.borrow().changes().is_empty());
+    }
+
+    #[test]
+    fn test_changes_of_restore() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("a", 1);
+        rule_context.set("b", 2);
+        let snapshot = rule_context.borrow().snapshot();
+
+        rule_context.set("a", 10);
+        rule_context.set("c", 30);
+        rule_context.borrow_mut().remove("b");
+        rule_context.borrow_mut().start_tracking();
+        rule_context.borrow_mut().restore(snapshot);
+
+        assert_eq!(
+            journal(&rule_context.borrow()),
+            vec![
+                ("a", ChangeKind::Update, None),
+                ("b", ChangeKind::Insert, None),
+                ("c", ChangeKind::Remove, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changes_of_layered_context() {
+        let mut defaults = RuleContext::new();
+        defaults.set("currency", "USD");
+
+        let rule_context = LayeredContext::new(vec![RuleContext::new(), defaults]);
+        rule_context.borrow_mut().start_tracking();
+        rule_context.clone().set("currency", "EUR");
+        rule_context.borrow_mut().remove("currency");
+
+        assert_eq!(*rule_context.get::<&str>("currency").unwrap(), "USD");
+        assert_eq!(
+            journal(&rule_context.borrow()),
+            vec![
+                ("currency", ChangeKind::Update, None),
+                ("currency", ChangeKind::Remove, None),
+            ]
+        );
+    }
+}
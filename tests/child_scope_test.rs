@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_child_scope_keeps_scratch_values_from_siblings() {
+        let pricing = AllRule::new().with_name("pricing").on_execute(|this| {
+            let scope = RuleContext::child_scope(&this.get_rule_context());
+            let subtotal = AllRule::new().on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                let total = *rule_context.get::<u32>("total").unwrap();
+                rule_context.set("subtotal", total - 10);
+            });
+            let price = AllRule::new().on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                let subtotal = *rule_context.get::<u32>("subtotal").unwrap();
+                rule_context.set("price", subtotal * 2);
+            });
+            Engine::all_runner().run(scope.clone(), vec![subtotal, price]);
+
+            let price = *scope.get::<u32>("price").unwrap();
+            scope.borrow_mut().discard_scope();
+            this.get_rule_context().set("price", price);
+        });
+        let sibling = AllRule::new().with_name("sibling").on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            let leaked = rule_context.get::<u32>("subtotal").is_some();
+            rule_context.set("leaked", leaked);
+        });
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 50u32);
+        Engine::all_runner().run(rule_context.clone(), vec![pricing, sibling]);
+
+        assert_eq!(*rule_context.get::<u32>("price").unwrap(), 80);
+        assert!(!*rule_context.get::<bool>("leaked").unwrap());
+        assert!(rule_context.get::<u32>("subtotal").is_none());
+    }
+
+    #[test]
+    fn test_child_scope_merge() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 100);
+        rule_context.borrow_mut().start_tracking();
+
+        let mut scope = RuleContext::child_scope(&rule_context);
+        assert!(scope.borrow().is_scope());
+        scope.set("total", 90);
+        scope.set("coupon", "SAVE10");
+        assert_eq!(*rule_context.get::<i32>("total").unwrap(), 100);
+
+        scope.borrow_mut().merge_scope();
+
+        assert_eq!(*rule_context.get::<i32>("total").unwrap(), 90);
+        assert_eq!(*rule_context.get::<&str>("coupon").unwrap(), "SAVE10");
+        let mut changed: Vec<_> = rule_context
+            .borrow()
+            .changes()
+            .iter()
+            .map(|change| (change.get_key(), change.get_kind()))
+            .collect();
+        changed.sort_by_key(|(key, _)| *key);
+        assert_eq!(
+            changed,
+            [
+                ("coupon", ChangeKind::Insert),
+                ("total", ChangeKind::Update)
+            ]
+        );
+
+        // The scope is empty once merged, reading through to the parent.
+        scope.borrow_mut().discard_scope();
+        assert_eq!(*scope.get::<i32>("total").unwrap(), 90);
+    }
+
+    #[test]
+    fn test_child_scope_inherits_settings() {
+        let rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.borrow_mut().set_seed(7);
+
+        let mut scope = RuleContext::child_scope(&rule_context);
+        scope.set("customer_id", 42u64);
+        scope.borrow_mut().merge_scope();
+
+        assert_eq!(scope.borrow().get_seed(), 7);
+        assert_eq!(scope.borrow().get_run_mode(), RunMode::Normal);
+        assert_eq!(*rule_context.get::<u64>("cust.id").unwrap(), 42);
+    }
+
+    #[test]
+    fn test_child_scope_keeps_budget_of_parent() {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_max_depth(4);
+        rule_context
+            .borrow_mut()
+            .set_execution_budget(Budget::new().with_max_fired(1));
+
+        let scope = RuleContext::child_scope(&rule_context);
+        assert_eq!(scope.borrow().get_max_depth(), 4);
+
+        let result = Engine::all_runner().try_run(scope, vec![AllRule::new(), AllRule::new()]);
+        assert_eq!(
+            result,
+            Err(RuleError::BudgetExceeded(BudgetLimit::MaxFired(1)))
+        );
+    }
+
+    #[test]
+    fn test_merge_scope_of_plain_context() {
+        let mut defaults = RuleContext::new();
+        defaults.set("currency", "USD");
+        let mut rule_context = LayeredContext::new(vec![RuleContext::new(), defaults.clone()]);
+        rule_context.set("currency", "EUR");
+
+        assert!(!rule_context.borrow().is_scope());
+        rule_context.borrow_mut().merge_scope();
+        rule_context.borrow_mut().discard_scope();
+
+        assert_eq!(*rule_context.get::<&str>("currency").unwrap(), "EUR");
+        assert_eq!(*defaults.get::<&str>("currency").unwrap(), "USD");
+    }
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use dredd_rs::cli;
+    use serde_json::{json, Value};
+
+    const RULES: &str = r#"{
+        "conditions": { "is_adult": "age >= 18", "is_minor": "age < 18" },
+        "actions": { "approve": { "approved": true } },
+        "failures": { "reject": "applicant is a minor" },
+        "rules": {
+            "type": "all",
+            "rules": [
+                { "name": "adult", "eval": "is_adult", "execute": "approve" },
+                { "name": "minor", "eval": "is_minor", "execute": "reject" }
+            ]
+        }
+    }"#;
+
+    fn write(name: &str, contents: &str) -> String {
+        let path: PathBuf =
+            env::temp_dir().join(format!("dredd-cli-{}-{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+
+    fn run(args: &[&str]) -> (i32, String, String) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = cli::run(args.iter().copied(), &mut out, &mut err);
+        (
+            code,
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_run_prints_context_and_fired_rules() {
+        let rules = write("adult-rules.json", RULES);
+        let context = write("adult-context.json", r#"{ "age": 42 }"#);
+
+        let (code, out, _) = run(&["run", "--rules", &rules, "--context", &context]);
+
+        assert_eq!(code, 0);
+        let output: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(output["fired"], json!(["adult"]));
+        assert_eq!(output["context"], json!({ "age": 42, "approved": true }));
+        assert_eq!(output["errors"], json!([]));
+    }
+
+    #[test]
+    fn test_rule_errors_exit_with_one() {
+        let rules = write("minor-rules.json", RULES);
+        let context = write("minor-context.json", r#"{ "age": 12 }"#);
+
+        let (code, out, _) = run(&["run", "--context", &context, "--rules", &rules]);
+
+        assert_eq!(code, 1);
+        let output: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            output["errors"],
+            json!(["rule `minor` failed: applicant is a minor"])
+        );
+    }
+
+    #[test]
+    fn test_context_is_optional() {
+        let rules = write("empty-rules.json", RULES);
+
+        let (code, out, _) = run(&["run", "--rules", &rules]);
+
+        assert_eq!(code, 0);
+        let output: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(output["fired"], json!([]));
+    }
+
+    #[test]
+    fn test_invalid_invocations_exit_with_two() {
+        let rules = write(
+            "invalid-rules.json",
+            r#"{ "rules": { "type": "all" }, "conditions": { "bad": "age >=" } }"#,
+        );
+
+        for args in [
+            vec![],
+            vec!["check"],
+            vec!["run"],
+            vec!["run", "--rules"],
+            vec!["run", "--rules", "/nonexistent/rules.json"],
+            vec!["run", "--rules", rules.as_str()],
+            vec!["run", "--rules", rules.as_str(), "--verbose"],
+        ] {
+            let (code, out, err) = run(&args);
+            assert_eq!(code, 2, "{args:?}");
+            assert!(out.is_empty());
+            assert!(err.starts_with("dredd: "));
+        }
+    }
+}
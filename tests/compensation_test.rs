@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn step(name: &'static str) -> Rc<RefCell<ChainRule>> {
+        ChainRule::new()
+            .with_name(name)
+            .on_execute(move |this| this.get_rule_context().push_to_list("done", name))
+            .on_compensate(move |this| this.get_rule_context().push_to_list("undone", name))
+    }
+
+    fn failing() -> Rc<RefCell<ChainRule>> {
+        ChainRule::new()
+            .with_name("ship")
+            .on_execute(|this| {
+                this.get_rule_context()
+                    .fail(RuleError::failed("no courier"))
+            })
+            .on_compensate(|this| this.get_rule_context().push_to_list("undone", "ship"))
+    }
+
+    fn undone(rule_context: &Rc<RefCell<RuleContext>>) -> Vec<&'static str> {
+        rule_context
+            .get_list::<&'static str>("undone")
+            .map(|undone| undone.to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_chain_compensated_in_reverse_order() {
+        let rule = step("reserve").add_child(step("charge").add_child(failing()));
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_compensation(rule_context.clone(), vec![rule]);
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("no courier").in_rule("ship"))
+        );
+        assert_eq!(report.get_compensated(), ["charge", "reserve"]);
+        assert_eq!(undone(&rule_context), ["charge", "reserve"]);
+    }
+
+    #[test]
+    fn test_no_compensation_on_success() {
+        let rule = step("reserve").add_child(step("charge"));
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_compensation(rule_context.clone(), vec![rule]);
+
+        assert!(report.get_compensated().is_empty());
+        assert!(undone(&rule_context).is_empty());
+        assert_eq!(rule_context.get_list::<&str>("done").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_only_executed_rules_are_compensated() {
+        let rules = vec![
+            AllRule::new()
+                .with_name("skipped")
+                .on_eval(|_| false)
+                .on_compensate(|this| this.get_rule_context().push_to_list("undone", "skipped")),
+            AllRule::new().with_name("no undo"),
+            AllRule::new()
+                .with_name("email")
+                .on_compensate(|this| this.get_rule_context().push_to_list("undone", "email")),
+            AllRule::new()
+                .with_name("failing")
+                .on_post_execute(|this| this.get_rule_context().fail(RuleError::failed("late")))
+                .on_compensate(|this| this.get_rule_context().push_to_list("undone", "failing")),
+        ];
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_compensation(rule_context.clone(), rules);
+
+        assert_eq!(report.get_compensated(), ["email"]);
+        assert_eq!(undone(&rule_context), ["email"]);
+    }
+
+    #[test]
+    fn test_compensations_not_recorded_by_default() {
+        let rule = step("reserve").add_child(failing());
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert!(report.get_error().is_some());
+        assert!(report.get_compensated().is_empty());
+        assert!(undone(&rule_context).is_empty());
+    }
+
+    #[test]
+    fn test_nested_run_compensated_by_outer_failure() {
+        let inner = AllRule::new()
+            .with_name("inner")
+            .on_execute(|this| {
+                let rule = step("reserve");
+                let report = Engine::chain_runner()
+                    .run_with_compensation(this.get_rule_context(), vec![rule]);
+                assert!(report.get_compensated().is_empty());
+            })
+            .on_compensate(|this| this.get_rule_context().push_to_list("undone", "inner"));
+        let rules = vec![
+            inner,
+            AllRule::new().on_execute(|this| this.get_rule_context().fail(RuleError::failed("x"))),
+        ];
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_compensation(rule_context.clone(), rules);
+
+        assert_eq!(report.get_compensated(), ["inner", "reserve"]);
+        assert_eq!(undone(&rule_context), ["inner", "reserve"]);
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, ops::Not, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn context(country: &'static str, age: u32) -> Rc<std::cell::RefCell<RuleContext>> {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("country", country);
+        rule_context.set("age", age);
+        rule_context
+    }
+
+    #[test]
+    fn test_condition_key_equals_and_exists() {
+        let rule_context = context("BR", 30);
+        let rule_context = rule_context.borrow();
+
+        assert!(Condition::key_equals("country", "BR").eval(&rule_context));
+        assert!(!Condition::key_equals("country", "AR").eval(&rule_context));
+        // Values of another type don't match.
+        assert!(!Condition::key_equals("age", 30i64).eval(&rule_context));
+        assert!(Condition::key_exists("age").eval(&rule_context));
+        assert!(!Condition::key_exists("vip").eval(&rule_context));
+        assert!(!Condition::key_equals("vip", true).eval(&rule_context));
+    }
+
+    #[test]
+    fn test_condition_combinators() {
+        let domestic = Condition::key_equals("country", "BR");
+        let adult = Condition::new(|ctx| *ctx.get::<u32>("age").unwrap() >= 18);
+        let rule_context = context("AR", 30);
+        let rule_context = rule_context.borrow();
+
+        assert!(!domestic.and(adult.clone()).eval(&rule_context));
+        assert!(domestic.or(adult.clone()).eval(&rule_context));
+        assert!(domestic.clone().not().eval(&rule_context));
+        assert!(!(!adult.clone()).eval(&rule_context));
+        assert!((!domestic).and(adult).eval(&rule_context));
+    }
+
+    #[test]
+    fn test_condition_short_circuits() {
+        let checks = Rc::new(Cell::new(0));
+        let counted = {
+            let checks = checks.clone();
+            Condition::new(move |_| {
+                checks.set(checks.get() + 1);
+                true
+            })
+        };
+        let rule_context = RuleContext::new();
+        let rule_context = rule_context.borrow();
+
+        Condition::new(|_| false)
+            .and(counted.clone())
+            .eval(&rule_context);
+        Condition::new(|_| true)
+            .or(counted.clone())
+            .eval(&rule_context);
+        assert_eq!(checks.get(), 0);
+
+        Condition::new(|_| true).and(counted).eval(&rule_context);
+        assert_eq!(checks.get(), 1);
+    }
+
+    #[test]
+    fn test_on_condition_sets_rule_evaluation() {
+        let minor = !Condition::new(|ctx| *ctx.get::<u32>("age").unwrap() >= 18);
+        let rule = ChainRule::new()
+            .on_condition(minor.and(Condition::key_equals("country", "BR")))
+            .on_execute(|this| this.get_rule_context().set("guardian", true));
+
+        let rule_context = context("BR", 12);
+        Engine::chain_runner().run(rule_context.clone(), vec![rule.clone()]);
+        assert!(*rule_context.get::<bool>("guardian").unwrap());
+
+        let rule_context = context("BR", 40);
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+        assert!(rule_context.get::<bool>("guardian").is_none());
+    }
+
+    #[test]
+    fn test_condition_descriptions() {
+        let adult = Condition::new(|ctx| ctx.get::<u32>("age").is_some_and(|age| *age >= 18));
+        assert_eq!(adult.get_description(), None);
+        assert_eq!(
+            adult.and(Condition::key_exists("vip")).get_description(),
+            None
+        );
+
+        let adult = adult.with_description("`age` >= 18");
+        let condition = !adult.or(Condition::key_equals("country", "BR"));
+        assert_eq!(
+            condition.get_description(),
+            Some(r#"not (`age` >= 18 or `country` == "BR")"#)
+        );
+        assert_eq!(
+            Condition::with_probability(0.25).get_description(),
+            Some("with probability 0.25")
+        );
+
+        let rule = AllRule::new().on_condition(condition);
+        assert_eq!(
+            rule.borrow().get_condition(),
+            Some(r#"not (`age` >= 18 or `country` == "BR")"#)
+        );
+        assert_eq!(AllRule::new().borrow().get_condition(), None);
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "decimal")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_context_decimal_pricing_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_decimal("price", Decimal::new(1000, 2));
+        rule_context.set_decimal("quantity", Decimal::from(3));
+
+        let rule = ChainRule::new().on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            let price = rule_context.get_decimal("price").unwrap();
+            let quantity = rule_context.get_decimal("quantity").unwrap();
+            for _ in 0..10 {
+                rule_context.add_decimal("total", Decimal::new(1, 1));
+            }
+            rule_context.add_decimal("total", price * quantity);
+            rule_context.sub_decimal("total", Decimal::new(5, 0));
+            rule_context.mul_decimal("total", Decimal::new(1075, 3));
+            rule_context.round_decimal("total", 2);
+        });
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(
+            rule_context.get_decimal("total"),
+            Some(Decimal::new(2795, 2))
+        );
+        assert_eq!(
+            *rule_context.get::<Decimal>("total").unwrap(),
+            Decimal::new(2795, 2)
+        );
+    }
+
+    #[test]
+    fn test_context_decimal_overflow_leaves_key_unchanged() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_decimal("total", Decimal::MAX);
+
+        assert_eq!(rule_context.add_decimal("total", Decimal::ONE), None);
+        assert_eq!(rule_context.get_decimal("total"), Some(Decimal::MAX));
+        assert_eq!(rule_context.round_decimal("missing", 2), None);
+        assert_eq!(rule_context.get_decimal("missing"), None);
+    }
+
+    #[test]
+    fn test_context_decimal_rewritten_converges() {
+        let rule = AllRule::new().on_execute(|this| {
+            this.get_rule_context()
+                .set_decimal("fee", Decimal::new(250, 2));
+        });
+
+        let report = Engine::execute_to_fixpoint(RuleContext::new(), vec![rule], 10);
+
+        assert!(report.is_converged());
+        assert_eq!(report.get_changed_keys(), [vec!["fee"], vec![]]);
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use serde_json::json;
+
+    fn order() -> serde_json::Value {
+        json!({
+            "customer": { "tier": "gold" },
+            "items": [
+                { "sku": "A-1", "price": 120 },
+                { "sku": "B-2", "price": 80 }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_context_json_read_by_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_json("order", order());
+
+        let rule = AllRule::new()
+            .on_eval(|ctx| ctx.get_json_path("/order/customer/tier") == Some(json!("gold")))
+            .on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                let total: i64 = rule_context.get_json("order").unwrap()["items"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(|item| item["price"].as_i64())
+                    .sum();
+                rule_context.set("total", total);
+            });
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<i64>("total").unwrap(), 200);
+    }
+
+    #[test]
+    fn test_context_json_path_misses() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_json("order", order());
+        rule_context.set("count", 2);
+
+        assert_eq!(rule_context.get_json_path("/order"), Some(order()));
+        assert_eq!(
+            rule_context.get_json_path("/order/items/1/sku"),
+            Some(json!("B-2"))
+        );
+        assert_eq!(rule_context.get_json_path("/order/items/2/sku"), None);
+        assert_eq!(rule_context.get_json_path("/missing/items"), None);
+        assert_eq!(rule_context.get_json_path("/count"), None);
+        assert_eq!(rule_context.get_json_path("order/items"), None);
+        assert!(rule_context.get_json("count").is_none());
+    }
+
+    #[test]
+    fn test_context_json_rewritten_converges() {
+        let rule = AllRule::new().on_execute(|this| {
+            this.get_rule_context()
+                .set_json("profile", json!({ "segment": "loyal" }));
+        });
+
+        let report = Engine::execute_to_fixpoint(RuleContext::new(), vec![rule], 10);
+
+        assert!(report.is_converged());
+        assert_eq!(report.get_changed_keys(), [vec!["profile"], vec![]]);
+    }
+}
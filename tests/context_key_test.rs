@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::context_keys;
+    use dredd_rs::rule::*;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Order {
+        total: u32,
+    }
+
+    context_keys! {
+        mod keys {
+            /// Customer age in years.
+            AGE: i64 = "customer.age";
+            /// The order being checked out.
+            ORDER: Order = "checkout.order";
+            APPROVED: bool = "checkout.approved";
+        }
+    }
+
+    #[test]
+    fn test_context_keys_should_describe_every_key() {
+        let names: Vec<_> = keys::KEYS.iter().map(|key| key.name()).collect();
+        let types: Vec<_> = keys::KEYS.iter().map(|key| key.value_type()).collect();
+        let docs: Vec<_> = keys::KEYS.iter().map(|key| key.doc()).collect();
+
+        assert_eq!(
+            names,
+            vec!["customer.age", "checkout.order", "checkout.approved"]
+        );
+        assert_eq!(types, vec!["i64", "Order", "bool"]);
+        assert_eq!(
+            docs,
+            vec!["Customer age in years.", "The order being checked out.", ""]
+        );
+        assert_eq!(keys::AGE.name(), "customer.age");
+        assert_eq!(keys::AGE.doc(), "Customer age in years.");
+    }
+
+    #[test]
+    fn test_context_keys_should_be_usable_from_rules() {
+        let mut rule = ChainRule::new();
+        rule.on_eval(|ctx| *ctx.get_key(keys::AGE).unwrap() >= 18)
+            .on_execute(|this| {
+                let total = this.get_rule_context().get_key(keys::ORDER).unwrap().total;
+                this.get_rule_context().set_key(keys::APPROVED, total < 100);
+            });
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set_key(keys::AGE, 21);
+        rule_context.set_key(keys::ORDER, Order { total: 42 });
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get_key(keys::APPROVED).unwrap());
+        assert!(*rule_context.get::<bool>("checkout.approved").unwrap());
+    }
+
+    #[test]
+    fn test_typed_keys_with_get_and_set() {
+        const SCORE: Key<u32> = Key::named("risk.score");
+
+        let rule = AllRule::new()
+            .on_eval(|ctx| *ctx.get(keys::AGE).unwrap() >= 18)
+            .on_execute(|this| {
+                let total = this.get_rule_context().get(keys::ORDER).unwrap().total;
+                this.get_rule_context().set(SCORE, total * 2);
+                this.get_rule_context().set(keys::APPROVED, true);
+            });
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set(keys::AGE, 30);
+        rule_context.set(keys::ORDER, Order { total: 40 });
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get(SCORE).unwrap(), 80);
+        assert_eq!(*rule_context.get::<u32>("risk.score").unwrap(), 80);
+        assert!(*rule_context.get(keys::APPROVED).unwrap());
+        assert_eq!(SCORE.doc(), "");
+    }
+
+    #[test]
+    fn test_shared_context_keys() {
+        let rule_context = SharedRuleContext::new();
+        rule_context.set_key(keys::AGE, 30);
+
+        assert_eq!(*rule_context.get_key(keys::AGE).unwrap(), 30);
+        assert_eq!(*rule_context.get(keys::AGE).unwrap(), 30);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct LineItem {
+        sku: &'static str,
+        price: u32,
+    }
+
+    #[test]
+    fn test_context_list_iterated_by_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_list(
+            "line_items",
+            vec![
+                LineItem {
+                    sku: "a",
+                    price: 120,
+                },
+                LineItem {
+                    sku: "b",
+                    price: 80,
+                },
+            ],
+        );
+
+        let rule = ChainRule::new().on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            let total: u32 = rule_context
+                .get_list::<LineItem>("line_items")
+                .unwrap()
+                .iter()
+                .map(|item| item.price)
+                .sum();
+            rule_context.set("total", total);
+            rule_context.push_to_list(
+                "line_items",
+                LineItem {
+                    sku: "shipping",
+                    price: 10,
+                },
+            );
+        });
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<u32>("total").unwrap(), 200);
+        assert_eq!(
+            rule_context
+                .get_list::<LineItem>("line_items")
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            rule_context.get_list_item::<LineItem>("line_items", 2),
+            Some(LineItem {
+                sku: "shipping",
+                price: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_context_list_item_out_of_range_or_mistyped() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_list("scores", vec![1i64, 2]);
+
+        assert_eq!(rule_context.get_list_item::<i64>("scores", 1), Some(2));
+        assert_eq!(rule_context.get_list_item::<i64>("scores", 2), None);
+        assert_eq!(rule_context.get_list_item::<u32>("scores", 0), None);
+        assert_eq!(rule_context.get_list_item::<i64>("missing", 0), None);
+    }
+
+    #[test]
+    fn test_context_list_push_creates_or_keeps_other_values() {
+        let mut rule_context = RuleContext::new();
+        rule_context.push_to_list("tags", "new");
+        rule_context.set("flags", 3u8);
+        rule_context.push_to_list("flags", "vip");
+
+        assert_eq!(*rule_context.get_list::<&str>("tags").unwrap(), vec!["new"]);
+        assert_eq!(*rule_context.get::<u8>("flags").unwrap(), 3);
+        let warnings = rule_context.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].get_severity(), Severity::Warning);
+        assert_eq!(
+            warnings[0].get_message(),
+            "key `flags` doesn't hold a list of `&str`, the item was not pushed"
+        );
+    }
+
+    #[test]
+    fn test_context_list_push_keeps_snapshot() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set_list("tags", vec!["new"]);
+
+        let snapshot = rule_context.borrow().snapshot();
+        rule_context.push_to_list("tags", "vip");
+        assert_eq!(
+            *rule_context.get_list::<&str>("tags").unwrap(),
+            vec!["new", "vip"]
+        );
+
+        rule_context.borrow_mut().restore(snapshot);
+        assert_eq!(*rule_context.get_list::<&str>("tags").unwrap(), vec!["new"]);
+    }
+}
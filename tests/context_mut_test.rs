@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_get_mut_updates_value_in_place() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1i64);
+        rule_context.set("name", "ana".to_string());
+        rule_context.set("items", vec![1u32, 2]);
+
+        *rule_context.get_int_mut("count").unwrap() += 41;
+        rule_context
+            .get_string_mut("name")
+            .unwrap()
+            .push_str(" lima");
+        rule_context.get_mut::<Vec<u32>>("items").unwrap().push(3);
+
+        assert_eq!(*rule_context.get::<i64>("count").unwrap(), 42);
+        assert_eq!(*rule_context.get::<String>("name").unwrap(), "ana lima");
+        assert_eq!(
+            *rule_context.get::<Vec<u32>>("items").unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_get_mut_missing_or_other_type() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1u32);
+        rule_context.set("name", "ana".to_string());
+        rule_context.set("big", u64::MAX);
+
+        assert!(rule_context.get_int_mut("name").is_none());
+        assert!(rule_context.get_int_mut("big").is_none());
+        assert!(rule_context.get_int_mut("missing").is_none());
+        assert!(rule_context.get_string_mut("count").is_none());
+    }
+
+    #[test]
+    fn test_get_int_mut_keeps_width() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1);
+        rule_context.set("small", 7u8);
+
+        *rule_context.get_int_mut("count").unwrap() += 41;
+        *rule_context.entry("small").or_insert_int(0) -= 2;
+
+        assert_eq!(*rule_context.get::<i32>("count").unwrap(), 42);
+        assert_eq!(*rule_context.get::<u8>("small").unwrap(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "value 256 of key `small` overflows `u8`")]
+    fn test_get_int_mut_panics_on_overflow() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("small", 255u8);
+
+        *rule_context.get_int_mut("small").unwrap() += 1;
+    }
+
+    #[test]
+    fn test_only_writes_are_journaled() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1);
+        rule_context.set("name", "ana".to_string());
+        rule_context.borrow_mut().start_tracking();
+
+        assert_eq!(*rule_context.get_int_mut("count").unwrap(), 1);
+        assert_eq!(rule_context.get_string_mut("name").unwrap().len(), 3);
+        assert!(rule_context.borrow().changes().is_empty());
+
+        rule_context.get_string_mut("name").unwrap().push('!');
+        let changes = rule_context.borrow_mut().stop_tracking();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].get_key(), "name");
+        assert_eq!(changes[0].get_kind(), ChangeKind::Update);
+    }
+
+    #[test]
+    fn test_get_mut_copies_shared_values() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1i64);
+        let before = rule_context.get::<i64>("count").unwrap();
+
+        *rule_context.get_int_mut("count").unwrap() = 2;
+
+        assert_eq!(*before, 1);
+        let after = rule_context.get::<i64>("count").unwrap();
+        assert_eq!(*after, 2);
+        assert!(!Rc::ptr_eq(&before, &after));
+    }
+
+    #[test]
+    fn test_entry_inserts_missing_values() {
+        let rule_context = RuleContext::new();
+
+        for _ in 0..3 {
+            *rule_context.entry("count").or_insert_int(10) += 1;
+        }
+        rule_context
+            .entry("tags")
+            .or_insert_with(Vec::new)
+            .push("vip");
+        rule_context.entry("name").or_insert_string("ana");
+
+        assert_eq!(*rule_context.get::<i64>("count").unwrap(), 13);
+        assert_eq!(*rule_context.get::<Vec<&str>>("tags").unwrap(), vec!["vip"]);
+        assert_eq!(*rule_context.get::<String>("name").unwrap(), "ana");
+        assert_eq!(rule_context.entry("count").get_key(), "count");
+    }
+
+    #[test]
+    #[should_panic(expected = "key `count` doesn't hold an integer fitting in `i64`")]
+    fn test_entry_panics_on_other_type() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", "1");
+
+        rule_context.entry("count").or_insert_int(0);
+    }
+
+    #[test]
+    fn test_in_place_updates_are_seen_by_snapshots() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1i64);
+
+        let result = Engine::all_runner().try_run_atomic(
+            rule_context.clone(),
+            vec![AllRule::new().on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                *rule_context.get_int_mut("count").unwrap() += 1;
+                rule_context.fail(RuleError::failed("rolled back"));
+            })],
+        );
+
+        assert!(result.is_err());
+        assert_eq!(*rule_context.get::<i64>("count").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_in_place_updates_are_recorded() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1i64);
+        let usage = KeyUsage::record(
+            &Engine::all_runner(),
+            rule_context,
+            vec![AllRule::new().on_execute(|this| {
+                *this.get_rule_context().get_int_mut("count").unwrap() += 1;
+                *this.get_rule_context().entry("total").or_insert_int(0) += 1;
+            })],
+        );
+
+        assert_eq!(usage.read_keys(), vec!["count", "total"]);
+        assert_eq!(usage.write_keys(), vec!["count", "total"]);
+        assert_eq!(usage.input_keys(), vec!["count"]);
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn order() -> ContextObject {
+        ContextObject::new().with("total", 250u32).with(
+            "customer",
+            ContextObject::new()
+                .with("age", 42i64)
+                .with("address", ContextObject::new().with("country", "BR")),
+        )
+    }
+
+    #[test]
+    fn test_context_object_get_path_in_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("order", order());
+
+        let rule = ChainRule::new()
+            .on_eval(|ctx| *ctx.get_path::<i64>("order.customer.age").unwrap() >= 18)
+            .on_execute(|this| {
+                let country = this
+                    .get_rule_context()
+                    .get_path::<&str>("order.customer.address.country")
+                    .unwrap();
+                this.get_rule_context().set("country", *country);
+            });
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<&str>("country").unwrap(), "BR");
+    }
+
+    #[test]
+    fn test_context_object_get_path_misses() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("order", order());
+        rule_context.set("count", 3u32);
+
+        assert!(rule_context
+            .get_path::<i64>("order.customer.name")
+            .is_none());
+        assert!(rule_context.get_path::<u32>("order.customer.age").is_none());
+        assert!(rule_context.get_path::<u32>("order.total.value").is_none());
+        assert!(rule_context.get_path::<u32>("count.value").is_none());
+        assert!(rule_context.get_path::<u32>("missing").is_none());
+        assert_eq!(*rule_context.get_path::<u32>("count").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_context_object_fields() {
+        let mut object = order();
+        object.insert("total", 300u32);
+
+        assert_eq!(object.get_keys(), vec!["customer", "total"]);
+        assert_eq!(*object.get::<u32>("total").unwrap(), 300);
+        assert_eq!(*object.get_path::<i64>("customer.age").unwrap(), 42);
+        assert!(object.get::<ContextObject>("customer").is_some());
+    }
+
+    #[test]
+    fn test_context_object_dotted_keys() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("checkout.age", 30i64);
+        rule_context.set(
+            "checkout",
+            ContextObject::new()
+                .with("age", 40i64)
+                .with("customer.name", "ana"),
+        );
+
+        assert_eq!(*rule_context.get_path::<i64>("checkout.age").unwrap(), 30);
+        assert_eq!(
+            *rule_context
+                .get_path::<&str>("checkout.customer.name")
+                .unwrap(),
+            "ana"
+        );
+        assert!(rule_context.get_path::<i64>("checkout.age.value").is_none());
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::context_keys;
+    use dredd_rs::rule::*;
+
+    context_keys! {
+        mod keys {
+            AGE: u32 = "age";
+        }
+    }
+
+    #[test]
+    fn test_on_eval_reads_through_view() {
+        let adult = AllRule::new()
+            .with_name("adult")
+            .on_eval(|ctx| ctx.get(keys::AGE).is_some_and(|age| *age >= 18))
+            .on_execute(|this| this.get_rule_context().set("adult", true));
+        let vip = AllRule::new()
+            .with_name("vip")
+            .on_eval(|ctx| ctx.contains("vip"))
+            .on_execute(|this| this.get_rule_context().set("vip_discount", 0.1));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set_key(keys::AGE, 30);
+
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![adult, vip]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["adult"]);
+        assert!(*rule_context.get::<bool>("adult").unwrap());
+        assert!(rule_context.get::<f64>("vip_discount").is_none());
+    }
+
+    #[test]
+    fn test_view_of_context() {
+        let mut defaults = RuleContext::new();
+        defaults.set("currency", "USD");
+        let mut rule_context = LayeredContext::new(vec![RuleContext::new(), defaults]);
+        rule_context.set("age", 17u32);
+
+        let view = ContextView::new(&rule_context);
+
+        assert_eq!(*view.get_key(keys::AGE).unwrap(), 17);
+        assert_eq!(*view.get::<&str>("currency").unwrap(), "USD");
+        assert!(view.get::<i64>("age").is_none());
+        assert!(view.contains("currency"));
+        assert!(!view.contains("vip"));
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use dredd_rs::rule;
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_required_rules_fire_past_budget() {
+        let rules = vec![
+            AllRule::new()
+                .with_name("enrich")
+                .with_cost(30)
+                .with_optional(true),
+            AllRule::new().with_name("score").with_cost(80),
+            AllRule::new()
+                .with_name("enrich_more")
+                .with_cost(1)
+                .with_optional(true),
+            AllRule::new().with_name("decide"),
+        ];
+
+        let report = Engine::all_runner().run_with_budget(
+            RuleContext::new(),
+            rules,
+            Budget::new().with_max_cost(100),
+        );
+
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["enrich", "score", "decide"]
+        );
+        assert_eq!(report.get_budget_skipped(), ["enrich_more"]);
+        assert_eq!(report.get_cost(), 110);
+    }
+
+    #[test]
+    fn test_skipped_optional_rule_skips_children() {
+        let rule = rule!(chain {
+            name: "lookup",
+            cost: 10,
+            optional: true,
+            then: |this: &mut ChainRule| this.get_rule_context().set("looked_up", true),
+            child: chain { name: "use_lookup" },
+        });
+        assert!(rule.borrow().is_optional());
+        assert_eq!(rule.borrow().get_cost(), 10);
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_budget(
+            rule_context.clone(),
+            vec![rule.clone()],
+            Budget::new().with_max_cost(5),
+        );
+
+        assert!(report.get_trace().get_executed_names().is_empty());
+        assert!(rule_context.get::<bool>("looked_up").is_none());
+
+        // The budget only applies to the budgeted run.
+        let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![rule]);
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["lookup", "use_lookup"]
+        );
+        assert_eq!(report.get_cost(), 0);
+    }
+
+    #[test]
+    fn test_duration_budget_skips_optional_rules() {
+        let rules = vec![
+            AllRule::new()
+                .with_name("slow")
+                .on_execute(|_| thread::sleep(Duration::from_millis(20))),
+            AllRule::new().with_name("enrich").with_optional(true),
+            AllRule::new().with_name("decide"),
+        ];
+
+        let budget = Budget::new().with_max_duration(Duration::from_millis(5));
+        let report = Engine::all_runner().run_with_budget(RuleContext::new(), rules, budget);
+
+        // Optional rules are skipped, required ones stop the run.
+        assert_eq!(report.get_trace().get_executed_names(), vec!["slow"]);
+        assert_eq!(report.get_budget_skipped(), ["enrich"]);
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxDuration(
+                Duration::from_millis(5)
+            )))
+        );
+    }
+
+    #[test]
+    fn test_cost_and_limits_in_one_budget() {
+        let rules = vec![
+            AllRule::new().with_name("score").with_cost(10),
+            AllRule::new()
+                .with_name("enrich")
+                .with_cost(10)
+                .with_optional(true),
+            AllRule::new().with_name("decide").with_cost(10),
+            AllRule::new().with_name("notify"),
+        ];
+
+        let budget = Budget::new().with_max_cost(15).with_max_fired(2);
+        let report = Engine::all_runner().run_with_budget(RuleContext::new(), rules, budget);
+
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["score", "decide"]
+        );
+        assert_eq!(report.get_budget_skipped(), ["enrich"]);
+        assert_eq!(report.get_cost(), 20);
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxFired(2)))
+        );
+    }
+
+    #[test]
+    fn test_unlimited_budget_skips_nothing() {
+        let rules = vec![BestFirstRule::new()
+            .with_cost(1_000)
+            .with_optional(true)
+            .with_name("expensive")];
+
+        let report =
+            Engine::best_first_runner().run_with_budget(RuleContext::new(), rules, Budget::new());
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["expensive"]);
+        assert_eq!(report.get_cost(), 1_000);
+    }
+}
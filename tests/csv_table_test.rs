@@ -0,0 +1,57 @@
+#![cfg(feature = "csv")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    const PLANS: &str = "\
+plan, daily_limit, support
+free, 100, community
+pro, 10000, email
+pro, 20000, phone
+";
+
+    #[test]
+    fn test_csv_table_lookup() {
+        let limits = CsvTable::from_csv(PLANS.as_bytes(), "plan", "daily_limit").unwrap();
+        assert_eq!(limits.len(), 2);
+
+        let rule = AllRule::new().on_execute(Lookup::new("{plan}", limits, "limit").callback());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan", "free");
+        Engine::all_runner()
+            .try_run(rule_context.clone(), vec![rule.clone()])
+            .unwrap();
+        assert_eq!(*rule_context.get::<String>("limit").unwrap(), "100");
+
+        // The last row of a key wins.
+        rule_context.set("plan", "pro");
+        Engine::all_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap();
+        assert_eq!(*rule_context.get::<String>("limit").unwrap(), "20000");
+    }
+
+    #[test]
+    fn test_csv_table_missing_column() {
+        assert_eq!(
+            CsvTable::from_csv(PLANS.as_bytes(), "plan", "price").unwrap_err(),
+            CsvTableError::MissingColumn("price".to_string())
+        );
+        assert_eq!(
+            CsvTableError::MissingColumn("price".to_string()).to_string(),
+            "missing `price` column"
+        );
+    }
+
+    #[test]
+    fn test_csv_table_invalid_document() {
+        let csv = "plan,daily_limit\nfree,100,extra\n";
+
+        assert!(matches!(
+            CsvTable::from_csv(csv.as_bytes(), "plan", "daily_limit"),
+            Err(CsvTableError::Csv(_))
+        ));
+    }
+}
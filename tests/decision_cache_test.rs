@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn pricing_rules() -> Vec<Rc<RefCell<BestFirstRule>>> {
+        vec![
+            BestFirstRule::new()
+                .on_eval(|ctx| {
+                    *ctx.get::<bool>("vip").unwrap() && *ctx.get::<u8>("tier").unwrap() >= 2
+                })
+                .on_execute(|this| this.get_rule_context().set("discount", 20u32)),
+            BestFirstRule::new()
+                .on_eval(|ctx| *ctx.get::<&str>("region").unwrap() != "EU")
+                .on_execute(|this| this.get_rule_context().set("discount", 5u32)),
+        ]
+    }
+
+    #[test]
+    fn test_decision_cache_matches_running_the_rules() {
+        let cache = DecisionCache::builder()
+            .segment("vip", [false, true])
+            .segment("tier", [1u8, 2, 3])
+            .segment("region", ["EU", "US", "LATAM"])
+            .compile(Engine::best_first_runner(), pricing_rules())
+            .unwrap();
+        assert_eq!(cache.get_len(), 18);
+        assert_eq!(cache.get_keys(), vec!["vip", "tier", "region"]);
+
+        for vip in [false, true] {
+            for tier in [1u8, 2, 3] {
+                for region in ["EU", "US", "LATAM"] {
+                    let mut cached = RuleContext::new();
+                    cached.set("vip", vip);
+                    cached.set("tier", tier);
+                    cached.set("region", region);
+                    cached.set("order_id", 42u64);
+                    let evaluated = RuleContext::new();
+                    evaluated.borrow_mut().restore(cached.borrow().snapshot());
+
+                    assert!(cache.apply(&cached));
+                    Engine::best_first_runner().run(evaluated.clone(), pricing_rules());
+
+                    assert_eq!(
+                        cached.get::<u32>("discount"),
+                        evaluated.get::<u32>("discount"),
+                    );
+                    assert_eq!(*cached.get::<u64>("order_id").unwrap(), 42);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decision_cache_misses_unknown_values() {
+        let cache = DecisionCache::builder()
+            .segment("vip", [false, true])
+            .segment("tier", [1u8, 2, 3])
+            .segment("region", ["EU", "US"])
+            .compile(Engine::best_first_runner(), pricing_rules())
+            .unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("vip", true);
+        rule_context.set("tier", 2u8);
+        assert!(cache.lookup(&rule_context.borrow()).is_none());
+
+        rule_context.set("region", "APAC");
+        assert!(!cache.apply(&rule_context));
+
+        // A value of another type than the enumerated ones is a miss too.
+        rule_context.set("region", "US".to_string());
+        assert!(!cache.apply(&rule_context));
+        assert!(rule_context.get::<u32>("discount").is_none());
+    }
+
+    #[test]
+    fn test_decision_cache_compile_errors() {
+        let result = DecisionCache::builder()
+            .segment("a", 0..100)
+            .segment("b", 0..100)
+            .with_max_combinations(1_000)
+            .compile(Engine::all_runner(), vec![AllRule::new()]);
+        assert_eq!(
+            result.unwrap_err(),
+            DecisionCacheError::TooManyCombinations(10_000)
+        );
+
+        let rule = AllRule::new()
+            .on_eval(|ctx| *ctx.get::<&str>("plan").unwrap() == "legacy")
+            .on_execute(|this| {
+                this.get_rule_context()
+                    .fail(RuleError::failed("unsupported"))
+            });
+        let result = DecisionCache::builder()
+            .segment("plan", ["free", "legacy"])
+            .compile(Engine::all_runner(), vec![rule]);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error,
+            DecisionCacheError::Failed(
+                "plan=\"legacy\"".to_string(),
+                RuleError::failed("unsupported")
+            )
+        );
+        assert_eq!(
+            error.to_string(),
+            "rule failed: unsupported for plan=\"legacy\""
+        );
+    }
+
+    #[test]
+    fn test_decision_cache_applies_like_the_rules() {
+        let rules = vec![AllRule::new()
+            .on_eval(|ctx| *ctx.get::<&str>("coupon").unwrap() == "WELCOME")
+            .on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                rule_context.set("discount", 10u32);
+                rule_context.borrow_mut().remove("coupon");
+            })];
+        let cache = DecisionCache::builder()
+            .segment("coupon", ["WELCOME", "NONE"])
+            .compile(Engine::all_runner(), rules)
+            .unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("discount", "discount_v2");
+        rule_context.set("coupon", "WELCOME");
+        rule_context.borrow_mut().start_tracking();
+        assert_eq!(
+            cache.lookup_removed(&rule_context.borrow()),
+            Some(&["coupon"][..])
+        );
+
+        assert!(cache.apply(&rule_context));
+
+        assert!(rule_context.get::<&str>("coupon").is_none());
+        assert_eq!(*rule_context.get::<u32>("discount_v2").unwrap(), 10);
+        let changes: Vec<_> = rule_context
+            .borrow()
+            .changes()
+            .iter()
+            .map(|change| (change.get_key(), change.get_kind()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("coupon", ChangeKind::Remove),
+                ("discount_v2", ChangeKind::Insert)
+            ]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+#![cfg(feature = "csv")]
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    const TABLE: &str = "\
+segment,total,vip,action
+retail,>= 1000,true,big_vip
+retail,>= 1000,,big
+wholesale,< 500,-,small_wholesale
+\"o'neil\",,,named
+";
+
+    fn rule() -> Rc<RefCell<ChainRule>> {
+        DecisionTableRule::from_csv(TABLE.as_bytes())
+            .unwrap()
+            .action("big_vip", |ctx| ctx.set("discount", 20u32))
+            .action("big", |ctx| ctx.set("discount", 10u32))
+            .action("small_wholesale", |ctx| ctx.set("discount", 2u32))
+            .action("named", |ctx| ctx.set("discount", 1u32))
+            .wrap(ChainRule::new().add_child(
+                ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+            ))
+            .unwrap()
+    }
+
+    fn run(
+        segment: &'static str,
+        total: i64,
+        vip: bool,
+    ) -> (Rc<RefCell<RuleContext>>, Result<(), RuleError>) {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("segment", segment);
+        rule_context.set("total", total);
+        rule_context.set("vip", vip);
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule()]);
+        (rule_context, result)
+    }
+
+    #[test]
+    fn test_decision_table_first_matching_row_wins() {
+        let (rule_context, result) = run("retail", 1500, true);
+
+        assert!(result.is_ok());
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 20);
+        assert!(*rule_context.get::<bool>("child").unwrap());
+
+        let (rule_context, _) = run("retail", 1500, false);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 10);
+
+        let (rule_context, _) = run("wholesale", 100, true);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 2);
+
+        let (rule_context, _) = run("o'neil", 0, false);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_decision_table_no_matching_row_fails_eval() {
+        let (rule_context, result) = run("retail", 10, true);
+
+        assert!(result.is_ok());
+        assert!(rule_context.get::<u32>("discount").is_none());
+        assert!(rule_context.get::<bool>("child").is_none());
+    }
+
+    #[test]
+    fn test_decision_table_missing_key_skips_row() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("segment", "retail");
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule()]);
+
+        assert!(result.is_ok());
+        assert!(rule_context.get::<u32>("discount").is_none());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("segment", "o'neil");
+        Engine::chain_runner().run(rule_context.clone(), vec![rule()]);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_decision_table_other_type_fails_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("segment", "retail");
+        rule_context.set("total", "lots");
+        let result = Engine::chain_runner().try_run(rule_context, vec![rule()]);
+
+        let error = result.unwrap_err().to_string();
+        assert!(
+            error.contains("decision table row 1: type mismatch"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_decision_table_row_is_found_once() {
+        let rule = DecisionTableRule::from_csv(TABLE.as_bytes())
+            .unwrap()
+            .action("big_vip", |ctx| ctx.set("discount", 20u32))
+            .action("big", |ctx| ctx.set("discount", 10u32))
+            .action("small_wholesale", |ctx| ctx.set("discount", 2u32))
+            .action("named", |ctx| ctx.set("discount", 1u32))
+            .wrap(ChainRule::new().on_execute(|this| this.get_rule_context().set("vip", false)))
+            .unwrap();
+
+        let (rule_context, _) = run("retail", 1500, true);
+        rule_context.borrow_mut().remove("discount");
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        // The row matched when the rule was evaluated, before `vip` changed.
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 20);
+    }
+
+    #[test]
+    fn test_decision_table_keys_and_rows() {
+        let table = DecisionTableRule::from_csv(TABLE.as_bytes()).unwrap();
+
+        assert_eq!(table.get_keys(), vec!["segment", "total", "vip"]);
+        assert_eq!(table.get_len(), 4);
+    }
+
+    #[test]
+    fn test_decision_table_errors() {
+        let missing_action = DecisionTableRule::from_csv("segment,total\nretail,1\n".as_bytes());
+        assert_eq!(
+            missing_action.unwrap_err(),
+            DecisionTableError::MissingActionColumn
+        );
+
+        let invalid =
+            DecisionTableRule::from_csv("total,action\n-,small\n>= 1.2.3,big\n".as_bytes());
+        assert!(matches!(
+            invalid.unwrap_err(),
+            DecisionTableError::InvalidCell { row: 2, ref column, .. } if column == "total"
+        ));
+
+        let unknown = DecisionTableRule::from_csv("total,action\n1,big\n".as_bytes())
+            .unwrap()
+            .wrap(AllRule::new());
+        assert_eq!(
+            unknown.err(),
+            Some(DecisionTableError::UnknownAction("big".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_decision_table_any_header_and_value() {
+        let csv = "\
+plan type,customer-age,true,note,action
+gold,>= 18,true,\"it's \"\"quoted\"\"\",match
+";
+        let rule = DecisionTableRule::from_csv(csv.as_bytes())
+            .unwrap()
+            .action("match", |ctx| ctx.set("matched", true))
+            .wrap(AllRule::new())
+            .unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan type", "gold");
+        rule_context.set("customer-age", 30);
+        rule_context.set("true", true);
+        rule_context.set("note", "it's \"quoted\"".to_string());
+        Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+        assert!(*rule_context.get::<bool>("matched").unwrap());
+
+        // `customer-age` is the key, not `customer` minus `age`.
+        rule_context.borrow_mut().remove("matched");
+        rule_context.set("customer-age", 12);
+        rule_context.set("customer", 40);
+        rule_context.set("age", 10);
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+        assert!(rule_context.get::<bool>("matched").is_none());
+    }
+
+    #[test]
+    fn test_decision_table_to_dmn() {
+        let csv = "\
+plan type,age,action
+gold,>= 18,approve
+!= \"basic\",-,refer
+";
+        let table = DecisionTableRule::from_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            table.to_dmn("Credit & risk"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<definitions xmlns="https://www.omg.org/spec/DMN/20191111/MODEL/" id="decision_Credit___risk_definitions" name="Credit &amp; risk" namespace="urn:dredd-rs:decision_Credit___risk">
+  <decision id="decision_Credit___risk" name="Credit &amp; risk">
+    <decisionTable id="decision_Credit___risk_table" hitPolicy="FIRST">
+      <input id="decision_Credit___risk_input_0" label="plan type">
+        <inputExpression id="decision_Credit___risk_input_0_expression" typeRef="string">
+          <text>plan type</text>
+        </inputExpression>
+      </input>
+      <input id="decision_Credit___risk_input_1" label="age">
+        <inputExpression id="decision_Credit___risk_input_1_expression" typeRef="number">
+          <text>age</text>
+        </inputExpression>
+      </input>
+      <output id="decision_Credit___risk_output" name="action" typeRef="string"/>
+      <rule id="decision_Credit___risk_rule_0">
+        <inputEntry id="decision_Credit___risk_rule_0_input_0">
+          <text>"gold"</text>
+        </inputEntry>
+        <inputEntry id="decision_Credit___risk_rule_0_input_1">
+          <text>&gt;= 18</text>
+        </inputEntry>
+        <outputEntry id="decision_Credit___risk_rule_0_output">
+          <text>"approve"</text>
+        </outputEntry>
+      </rule>
+      <rule id="decision_Credit___risk_rule_1">
+        <inputEntry id="decision_Credit___risk_rule_1_input_0">
+          <text>not("\"basic\"")</text>
+        </inputEntry>
+        <inputEntry id="decision_Credit___risk_rule_1_input_1">
+          <text>-</text>
+        </inputEntry>
+        <outputEntry id="decision_Credit___risk_rule_1_output">
+          <text>"refer"</text>
+        </outputEntry>
+      </rule>
+    </decisionTable>
+  </decision>
+</definitions>
+"#
+        );
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn scores(key: &str) -> Result<Option<u32>, String> {
+        match key {
+            "ana" => Ok(Some(700)),
+            _ => Err("connection refused".to_string()),
+        }
+    }
+
+    fn scored_rules() -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        vec![
+            AllRule::new()
+                .with_name("score")
+                .on_execute(Lookup::new("{user}", scores, "score").callback())
+                .add_child(
+                    AllRule::new()
+                        .with_name("approve")
+                        .on_execute(|this| this.get_rule_context().set("approved", true)),
+                ),
+            AllRule::new()
+                .with_name("notify")
+                .on_execute(|this| this.get_rule_context().set("notified", true)),
+        ]
+    }
+
+    #[test]
+    fn test_degradation_fails_by_default() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", "bob");
+
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), scored_rules());
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("connection refused").in_rule("score"))
+        );
+        assert!(!report.is_degraded());
+    }
+
+    #[test]
+    fn test_degradation_skips_affected_rule() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", "bob");
+
+        let report = Engine::all_runner().run_with_degradation(
+            rule_context.clone(),
+            scored_rules(),
+            DegradationPolicy::Degrade,
+        );
+
+        assert!(report.get_error().is_none());
+        assert!(!rule_context.has_failed());
+        assert!(report.is_degraded());
+        assert_eq!(
+            report.get_degraded(),
+            [RuleError::failed("connection refused")]
+        );
+        assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+        assert_eq!(
+            report.get_warnings()[0].to_string(),
+            "warning in `score`: degraded: rule failed: connection refused"
+        );
+        assert_eq!(
+            report
+                .get_trace()
+                .get_by_outcome(RuleOutcome::NotApplicable)[0]
+                .get_name(),
+            Some("score")
+        );
+        assert_eq!(report.get_trace().get_executed_names(), vec!["notify"]);
+        assert!(rule_context.get::<bool>("approved").is_none());
+        assert!(*rule_context.get::<bool>("notified").unwrap());
+        assert_eq!(
+            rule_context.borrow().get_degradation_policy(),
+            DegradationPolicy::Fail
+        );
+    }
+
+    #[test]
+    fn test_degradation_not_degraded_when_backend_answers() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", "ana");
+
+        let report = Engine::all_runner().run_with_degradation(
+            rule_context.clone(),
+            scored_rules(),
+            DegradationPolicy::Degrade,
+        );
+
+        assert!(!report.is_degraded());
+        assert_eq!(report.get_status(), RunStatus::Pass);
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["score", "approve", "notify"]
+        );
+    }
+
+    #[test]
+    fn test_degradation_keeps_other_failures() {
+        let rules = vec![
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context()
+                    .fail_unavailable(RuleError::failed("cache down"));
+            }),
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context().fail(RuleError::failed("declined"));
+            }),
+        ];
+
+        let report = Engine::all_runner().run_with_degradation(
+            RuleContext::new(),
+            rules,
+            DegradationPolicy::Degrade,
+        );
+
+        assert_eq!(report.get_degraded(), [RuleError::failed("cache down")]);
+        assert_eq!(report.get_errors(), [RuleError::failed("declined")]);
+        assert_eq!(report.get_status(), RunStatus::Fail);
+    }
+}
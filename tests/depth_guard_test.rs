@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    /// A chain of rules nested `depth` deep.
+    fn nested(depth: usize) -> Rc<RefCell<ChainRule>> {
+        let mut rule = ChainRule::new().with_name(&format!("level {depth}"));
+        for level in (1..depth).rev() {
+            rule = ChainRule::new()
+                .with_name(&format!("level {level}"))
+                .add_child(rule);
+        }
+        rule
+    }
+
+    #[test]
+    fn test_max_depth_stops_deep_rules() {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_max_depth(3);
+
+        let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![nested(10)]);
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxDepth(3)))
+        );
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["level 1", "level 2", "level 3"]
+        );
+        assert_eq!(
+            report
+                .get_trace()
+                .get_by_outcome(RuleOutcome::Skipped("max depth"))
+                .len(),
+            1
+        );
+
+        // The depth is back to 0 once the run is over.
+        rule_context.borrow_mut().take_error();
+        assert!(Engine::chain_runner()
+            .try_run(rule_context, vec![nested(3)])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_default_max_depth() {
+        let rule_context = RuleContext::new();
+        assert_eq!(rule_context.borrow().get_max_depth(), DEFAULT_MAX_DEPTH);
+
+        let result =
+            Engine::chain_runner().try_run(rule_context.clone(), vec![nested(DEFAULT_MAX_DEPTH)]);
+        assert_eq!(result, Ok(()));
+
+        let result = Engine::chain_runner()
+            .try_run(rule_context.clone(), vec![nested(DEFAULT_MAX_DEPTH + 1)]);
+        assert_eq!(
+            result,
+            Err(RuleError::BudgetExceeded(BudgetLimit::MaxDepth(
+                DEFAULT_MAX_DEPTH
+            )))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "execution budget exceeded: rules nested deeper than 256"
+        );
+    }
+
+    #[test]
+    fn test_depth_counts_nesting_not_siblings() {
+        let rules = (0..5)
+            .map(|_| AllRule::new().add_child(AllRule::new()))
+            .collect();
+
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_max_depth(2);
+
+        assert_eq!(Engine::all_runner().try_run(rule_context, rules), Ok(()));
+    }
+
+    #[test]
+    fn test_rule_firing_itself_is_a_cycle() {
+        let mut rule = AllRule::new().on_execute(|this| {
+            let count = this
+                .get_rule_context()
+                .get::<u32>("count")
+                .map_or(0, |count| *count);
+            this.get_rule_context().set("count", count + 1);
+        });
+        rule.add_child(rule.clone());
+
+        let rule_context = RuleContext::new();
+        let result = Engine::all_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::CycleDetected));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "rule fired while already firing"
+        );
+        assert_eq!(*rule_context.get::<u32>("count").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_cycle_through_children() {
+        let mut first = BestFirstRule::new().with_name("first");
+        let second = BestFirstRule::new()
+            .with_name("second")
+            .add_child(first.clone());
+        first.add_child(second);
+
+        let rule_context = RuleContext::new();
+        let report = Engine::best_first_runner().run_with_report(rule_context, vec![first]);
+
+        assert_eq!(report.get_error(), Some(&RuleError::CycleDetected));
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["first", "second"]
+        );
+    }
+
+    #[test]
+    fn test_cycle_collected_like_other_errors() {
+        let mut rule = AllRule::new();
+        rule.add_child(rule.clone());
+
+        let report = Engine::all_runner().run_with_policy(
+            RuleContext::new(),
+            vec![rule.clone(), AllRule::new().with_name("after")],
+            ErrorPolicy::CollectAll,
+        );
+
+        assert_eq!(report.get_errors(), [RuleError::CycleDetected]);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["after"]);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use dredd_rs::rule;
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_dry_run_best_first_tree() {
+        let tree = rule! {
+            best_first {
+                name: "root",
+                then: |this| this.get_rule_context().set("root", true),
+                child: best_first { name: "minor", when: |_| false },
+                child: best_first {
+                    name: "adult",
+                    then: |this| this.get_rule_context().set("adult", true),
+                },
+                child: best_first { name: "fallback" },
+            }
+        };
+
+        let rule_context = RuleContext::new();
+        let report = Engine::best_first_runner().dry_run(rule_context.clone(), vec![tree]);
+
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["root", "adult"]
+        );
+        assert_eq!(report.get_trace().get_entries().len(), 3);
+        assert!(rule_context.get::<bool>("root").is_none());
+        assert!(rule_context.get::<bool>("adult").is_none());
+    }
+
+    #[test]
+    fn test_dry_run_all_and_chain() {
+        let rules = vec![
+            AllRule::new().with_name("a"),
+            AllRule::new().with_name("b").on_eval(|_| false),
+            AllRule::new()
+                .with_name("c")
+                .add_child(AllRule::new().with_name("d")),
+        ];
+        let report = Engine::all_runner().dry_run(RuleContext::new(), rules);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["a", "c", "d"]);
+
+        let chain = ChainRule::new()
+            .with_name("first")
+            .add_child(ChainRule::new().with_name("second").on_eval(|_| false));
+        let report = Engine::chain_runner().dry_run(RuleContext::new(), vec![chain]);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["first"]);
+    }
+
+    #[test]
+    fn test_dry_run_evaluates_without_executing() {
+        let evals = Rc::new(Cell::new(0));
+        let rule = ChainRule::new()
+            .with_name("counting")
+            .on_eval({
+                let evals = evals.clone();
+                move |_| {
+                    evals.set(evals.get() + 1);
+                    true
+                }
+            })
+            .on_execute(|this| this.get_rule_context().set("executed", true));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 42i64);
+        Engine::chain_runner().dry_run(rule_context.clone(), vec![rule.clone()]);
+        assert_eq!(evals.get(), 1);
+        assert!(rule_context.get::<bool>("executed").is_none());
+        assert_eq!(*rule_context.get::<i64>("age").unwrap(), 42);
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+        assert_eq!(evals.get(), 2);
+        assert!(*rule_context.get::<bool>("executed").unwrap());
+    }
+}
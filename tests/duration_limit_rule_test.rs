@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use dredd_rs::rule::*;
+
+    fn slow(delay: Duration) -> impl Fn(&mut ChainRule) + 'static {
+        move |this| {
+            thread::sleep(delay);
+            this.get_rule_context().set("slow", true);
+        }
+    }
+
+    #[test]
+    fn test_duration_limit_rule_fails_overrunning_callback() {
+        let rule = ChainRule::new()
+            .with_name("slow")
+            .on_execute(slow(Duration::from_millis(30)))
+            .on_post_execute(|this| this.get_rule_context().set("post", true))
+            .add_child(
+                ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+            );
+        let rule = DurationLimitRule::new(Duration::from_millis(5)).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule]);
+
+        match result {
+            Err(RuleError::DurationExceeded { elapsed, limit, .. }) => {
+                assert!(elapsed >= Duration::from_millis(30));
+                assert_eq!(limit, Duration::from_millis(5));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        // The callback is not interrupted, but the rule stops after it.
+        assert!(*rule_context.get::<bool>("slow").unwrap());
+        assert!(rule_context.get::<bool>("post").is_none());
+        assert!(rule_context.get::<bool>("child").is_none());
+    }
+
+    #[test]
+    fn test_duration_limit_rule_keeps_fast_callbacks() {
+        let rule = ChainRule::new().on_execute(slow(Duration::ZERO)).add_child(
+            ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+        );
+        let rule = DurationLimitRule::new(Duration::from_secs(5)).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert!(result.is_ok());
+        assert!(*rule_context.get::<bool>("slow").unwrap());
+        assert!(*rule_context.get::<bool>("child").unwrap());
+    }
+
+    #[test]
+    fn test_duration_limit_rule_evaluation_overrun_does_not_pass() {
+        let rule = AllRule::new()
+            .on_eval(|_| {
+                thread::sleep(Duration::from_millis(20));
+                true
+            })
+            .on_execute(|this| this.get_rule_context().set("executed", true));
+        let rule = DurationLimitRule::new(Duration::from_millis(1)).wrap(rule);
+        let next = AllRule::new().on_execute(|this| this.get_rule_context().set("next", true));
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_policy(
+            rule_context.clone(),
+            vec![rule, next],
+            ErrorPolicy::CollectAll,
+        );
+
+        assert_eq!(report.get_errors().len(), 1);
+        assert!(matches!(
+            report.get_errors()[0],
+            RuleError::DurationExceeded { .. }
+        ));
+        assert!(rule_context.get::<bool>("executed").is_none());
+        assert!(*rule_context.get::<bool>("next").unwrap());
+    }
+
+    #[test]
+    fn test_duration_limit_rule_aborts_callback_checking_deadline() {
+        let rule = ChainRule::new()
+            .on_execute(|this| {
+                let mut steps = 0;
+                while !this.get_rule_context().borrow().is_past_deadline() {
+                    steps += 1;
+                    thread::sleep(Duration::from_millis(1));
+                }
+                this.get_rule_context().set("steps", steps);
+            })
+            .add_child(
+                ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+            );
+        let rule = DurationLimitRule::new(Duration::from_millis(20)).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule]);
+
+        match result {
+            Err(RuleError::DurationExceeded { elapsed, .. }) => {
+                assert!(elapsed < Duration::from_secs(5));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(*rule_context.get::<i32>("steps").unwrap() > 0);
+        assert!(rule_context.get::<bool>("child").is_none());
+        // The deadline only holds while the callback runs.
+        assert_eq!(rule_context.borrow().get_time_left(), None);
+        assert!(!rule_context.borrow().is_past_deadline());
+    }
+
+    #[test]
+    fn test_duration_limit_rule_evaluation_sees_deadline() {
+        let rule = AllRule::new().on_eval(|ctx| {
+            while !ctx.is_past_deadline() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            true
+        });
+        let rule = DurationLimitRule::new(Duration::from_millis(10)).wrap(rule);
+
+        let result = Engine::all_runner().try_run(RuleContext::new(), vec![rule]);
+
+        assert!(matches!(result, Err(RuleError::DurationExceeded { .. })));
+    }
+
+    #[test]
+    fn test_nested_duration_limits_keep_earliest_deadline() {
+        let rule = ChainRule::new().on_execute(|this| {
+            let time_left = this.get_rule_context().borrow().get_time_left().unwrap();
+            this.get_rule_context().set("time_left", time_left);
+        });
+        let rule = DurationLimitRule::new(Duration::from_secs(60)).wrap(rule);
+        let outer = ChainRule::new().on_execute(move |this| {
+            let result =
+                Engine::chain_runner().try_run(this.get_rule_context(), vec![rule.clone()]);
+            assert!(result.is_ok());
+        });
+        let outer = DurationLimitRule::new(Duration::from_secs(5)).wrap(outer);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![outer]);
+
+        assert!(result.is_ok());
+        assert!(*rule_context.get::<Duration>("time_left").unwrap() <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_duration_exceeded_error_display() {
+        let error = RuleError::DurationExceeded {
+            elapsed: Duration::from_millis(120),
+            limit: Duration::from_millis(100),
+            rule: None,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "rule ran for 120ms, over its limit of 100ms"
+        );
+        assert_eq!(
+            error.in_rule("slow").to_string(),
+            "rule `slow` ran for 120ms, over its limit of 100ms"
+        );
+    }
+}
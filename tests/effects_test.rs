@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::effects::*;
+    use dredd_rs::rule::*;
+
+    fn notifying_rules() -> Vec<Rc<RefCell<AllRule>>> {
+        vec![
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context()
+                    .emit(Effect::sms("+5511999990000", "Your order shipped"));
+            }),
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context().emit(Effect::webhook(
+                    "https://hooks.example.com/orders",
+                    "{\"id\":7}",
+                ));
+            }),
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context()
+                    .emit(Effect::push("device-1", "Shipped", "On its way"));
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_effects_are_delivered_in_order_by_kind() {
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), notifying_rules());
+        assert_eq!(rule_context.get_effects().len(), 3);
+
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let (sms, webhooks) = (sent.clone(), sent.clone());
+        let transports = TransportRegistry::new()
+            .with_transport(EffectKind::Sms, move |effect: &Effect| {
+                sms.borrow_mut().push(effect.get_kind());
+                Ok(())
+            })
+            .with_transport(EffectKind::Webhook, move |effect: &Effect| {
+                webhooks.borrow_mut().push(effect.get_kind());
+                Err("503 Service Unavailable".to_string())
+            });
+
+        let mut report = transports.deliver(&rule_context);
+
+        assert_eq!(*sent.borrow(), [EffectKind::Sms, EffectKind::Webhook]);
+        assert_eq!(
+            report.get_delivered(),
+            [Effect::sms("+5511999990000", "Your order shipped")]
+        );
+        assert!(!report.is_complete());
+        assert_eq!(
+            report.get_failed()[0].1,
+            DeliveryError::Failed("503 Service Unavailable".to_string())
+        );
+        assert_eq!(
+            report.get_failed()[1].1,
+            DeliveryError::NoTransport(EffectKind::Push)
+        );
+        assert!(rule_context.get_effects().is_empty());
+
+        let retry = transports.deliver_all(report.take_failed());
+        assert_eq!(retry.get_failed().len(), 2);
+    }
+
+    #[test]
+    fn test_effects_of_failed_atomic_run_are_discarded() {
+        let mut rules = notifying_rules();
+        rules.push(AllRule::new().on_execute(|this| {
+            this.get_rule_context()
+                .fail(RuleError::failed("payment declined"))
+        }));
+
+        let rule_context = RuleContext::new();
+        let result = Engine::all_runner().try_run_atomic(rule_context.clone(), rules);
+
+        assert!(result.is_err());
+        assert!(rule_context.get_effects().is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_emits_no_effects() {
+        let rule_context = RuleContext::new();
+        Engine::all_runner().dry_run(rule_context.clone(), notifying_rules());
+
+        assert!(rule_context.get_effects().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "encryption")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::encryption::*;
+    use dredd_rs::rule::*;
+    use dredd_rs::sampling::{Sample, Sampler};
+    use serde_json::{json, Map, Value};
+
+    fn encryptor() -> ContextEncryptor<LocalKeyProvider> {
+        ContextEncryptor::new(LocalKeyProvider::new("kek-1", [3; 32]))
+            .with_sensitive_keys(&["ssn", "income"])
+    }
+
+    fn values() -> Map<String, Value> {
+        json!({ "ssn": "123-45-6789", "income": 5200.5, "age": 42 })
+            .as_object()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let encryptor = encryptor();
+
+        let encrypted = encryptor.encrypt(&values()).unwrap();
+
+        assert_eq!(encrypted["age"], json!(42));
+        for key in ["ssn", "income"] {
+            let envelope = &encrypted[key]["$encrypted"];
+            assert_eq!(envelope["key_id"], json!("kek-1"));
+            assert!(envelope["ciphertext"].is_string());
+        }
+        assert_eq!(encryptor.decrypt(&encrypted).unwrap(), values());
+    }
+
+    #[test]
+    fn test_encryptions_use_fresh_keys() {
+        let encryptor = encryptor();
+
+        let first = encryptor.encrypt(&values()).unwrap();
+        let second = encryptor.encrypt(&values()).unwrap();
+
+        assert_ne!(first["ssn"], second["ssn"]);
+    }
+
+    #[test]
+    fn test_values_cannot_be_moved_between_keys() {
+        let encryptor = encryptor();
+        let mut encrypted = encryptor.encrypt(&values()).unwrap();
+
+        let ssn = encrypted["ssn"].clone();
+        encrypted.insert("income".to_string(), ssn);
+
+        assert_eq!(
+            encryptor.decrypt(&encrypted),
+            Err(EncryptionError::Decryption("income".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_decrypt_requires_key_encryption_key() {
+        let encrypted = encryptor().encrypt(&values()).unwrap();
+        let other = ContextEncryptor::new(LocalKeyProvider::new("kek-2", [3; 32]));
+
+        assert_eq!(
+            other.decrypt(&encrypted),
+            Err(EncryptionError::KeyProvider(
+                "unknown key `kek-1`".to_string()
+            ))
+        );
+
+        let mut malformed = encrypted.clone();
+        malformed.insert(
+            "ssn".to_string(),
+            json!({ "$encrypted": { "key_id": "kek-1" } }),
+        );
+        assert_eq!(
+            encryptor().decrypt(&malformed),
+            Err(EncryptionError::Malformed("ssn".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_encrypt_snapshot_and_samples() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("ssn", "123-45-6789");
+        rule_context.set("age", 42);
+
+        let encrypted = encryptor()
+            .encrypt_snapshot(&rule_context.borrow().snapshot())
+            .unwrap();
+        assert_eq!(encrypted["age"], json!(42));
+        assert!(encrypted["ssn"].get("$encrypted").is_some());
+
+        let rule = AllRule::new()
+            .with_name("score")
+            .on_execute(|this| this.get_rule_context().set("income", 1000));
+        let mut sampler = Sampler::new(EncryptingSink::new(Vec::<Sample>::new(), encryptor()));
+        sampler
+            .run(&Engine::all_runner(), rule_context, vec![rule])
+            .unwrap();
+
+        let samples = sampler.into_sink().into_inner();
+        assert_eq!(samples[0].get_fired(), ["score"]);
+        assert!(samples[0].get_input()["ssn"].get("$encrypted").is_some());
+        assert!(samples[0].get_outputs()["income"]
+            .get("$encrypted")
+            .is_some());
+        assert_eq!(
+            encryptor().decrypt(samples[0].get_outputs()).unwrap()["income"],
+            json!(1000)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn failing(name: &str, message: &'static str) -> std::rc::Rc<std::cell::RefCell<AllRule>> {
+        AllRule::new()
+            .with_name(name)
+            .on_execute(move |this| this.get_rule_context().fail(RuleError::failed(message)))
+            .add_child(AllRule::new().with_name("after_failure"))
+    }
+
+    fn rules() -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        vec![
+            failing("stock", "no stock"),
+            AllRule::new().with_name("shipping").add_children(vec![
+                failing("address", "no address"),
+                AllRule::new().with_name("carrier"),
+            ]),
+            AllRule::new().with_name("notify"),
+        ]
+    }
+
+    #[test]
+    fn test_abort_stops_at_first_error() {
+        let rule_context = RuleContext::new();
+        let report =
+            Engine::all_runner().run_with_policy(rule_context.clone(), rules(), ErrorPolicy::Abort);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["stock"]);
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("no stock").in_rule("stock"))
+        );
+        assert_eq!(
+            report.get_errors(),
+            [RuleError::failed("no stock").in_rule("stock")]
+        );
+        assert!(rule_context.has_failed());
+    }
+
+    #[test]
+    fn test_skip_and_collect_all_continue() {
+        for policy in [ErrorPolicy::Skip, ErrorPolicy::CollectAll] {
+            let rule_context = RuleContext::new();
+            let report =
+                Engine::all_runner().run_with_policy(rule_context.clone(), rules(), policy);
+
+            // Failing rules don't run their children, the others all run.
+            assert_eq!(
+                report.get_trace().get_executed_names(),
+                vec!["stock", "shipping", "address", "carrier", "notify"]
+            );
+            let failed: Vec<_> = report
+                .get_trace()
+                .get_entries()
+                .iter()
+                .filter(|entry| entry.is_failed())
+                .filter_map(|entry| entry.get_name())
+                .collect();
+            assert_eq!(failed, vec!["stock", "address"]);
+            assert!(report.get_error().is_none());
+            assert!(!rule_context.has_failed());
+
+            let expected = match policy {
+                ErrorPolicy::CollectAll => vec![
+                    RuleError::failed("no stock").in_rule("stock"),
+                    RuleError::failed("no address").in_rule("address"),
+                ],
+                _ => vec![],
+            };
+            assert_eq!(report.get_errors(), expected);
+        }
+    }
+
+    #[test]
+    fn test_policy_only_applies_to_its_run() {
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run_with_policy(rule_context.clone(), rules(), ErrorPolicy::Skip);
+        assert_eq!(rule_context.borrow().get_error_policy(), ErrorPolicy::Abort);
+
+        let result = Engine::all_runner().try_run(rule_context, rules());
+        assert_eq!(result, Err(RuleError::failed("no stock").in_rule("stock")));
+    }
+
+    #[test]
+    fn test_collect_all_with_chain_and_best_first() {
+        let rule = ChainRule::new()
+            .on_execute(|this| this.get_rule_context().fail(RuleError::failed("chain")))
+            .add_child(
+                ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+            );
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_policy(
+            rule_context.clone(),
+            vec![rule],
+            ErrorPolicy::CollectAll,
+        );
+        assert_eq!(report.get_errors(), [RuleError::failed("chain")]);
+        assert!(rule_context.get::<bool>("child").is_none());
+
+        let rules = vec![
+            BestFirstRule::new().on_eval(|ctx| {
+                ctx.fail(RuleError::failed("eval"));
+                true
+            }),
+            BestFirstRule::new().on_execute(|this| this.get_rule_context().set("fallback", true)),
+        ];
+        let rule_context = RuleContext::new();
+        let report = Engine::best_first_runner().run_with_policy(
+            rule_context.clone(),
+            rules,
+            ErrorPolicy::CollectAll,
+        );
+        assert_eq!(report.get_errors(), [RuleError::failed("eval")]);
+        assert!(*rule_context.get::<bool>("fallback").unwrap());
+    }
+}
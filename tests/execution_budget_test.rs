@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, thread, time::Duration};
+
+    use dredd_rs::rule::*;
+
+    /// A chain of rules nested `depth` deep.
+    fn nested(depth: usize) -> Rc<RefCell<ChainRule>> {
+        let mut rule = ChainRule::new().with_name(&format!("level {depth}"));
+        for level in (1..depth).rev() {
+            rule = ChainRule::new()
+                .with_name(&format!("level {level}"))
+                .add_child(rule);
+        }
+        rule
+    }
+
+    #[test]
+    fn test_max_fired_stops_run() {
+        let rules = (0..10)
+            .map(|index| {
+                AllRule::new()
+                    .with_name(&format!("rule {index}"))
+                    .on_execute(|this| this.get_rule_context().push_to_list("fired", true))
+            })
+            .collect();
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_budget(
+            rule_context.clone(),
+            rules,
+            Budget::new().with_max_fired(4),
+        );
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxFired(4)))
+        );
+        assert_eq!(rule_context.get_list::<bool>("fired").unwrap().len(), 4);
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["rule 0", "rule 1", "rule 2", "rule 3"]
+        );
+    }
+
+    #[test]
+    fn test_max_depth_stops_run() {
+        let report = Engine::chain_runner().run_with_budget(
+            RuleContext::new(),
+            vec![nested(10)],
+            Budget::new().with_max_depth(3),
+        );
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxDepth(3)))
+        );
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["level 1", "level 2", "level 3"]
+        );
+
+        let report = Engine::chain_runner().run_with_budget(
+            RuleContext::new(),
+            vec![nested(3)],
+            Budget::new().with_max_depth(3),
+        );
+        assert!(report.get_error().is_none());
+    }
+
+    #[test]
+    fn test_depth_counts_nesting_not_siblings() {
+        let rule = BestFirstRule::new()
+            .add_child(BestFirstRule::new().on_eval(|_| false))
+            .add_child(BestFirstRule::new().on_eval(|_| false))
+            .add_child(BestFirstRule::new());
+
+        let report = Engine::best_first_runner().run_with_budget(
+            RuleContext::new(),
+            vec![rule],
+            Budget::new().with_max_depth(2),
+        );
+
+        assert!(report.get_error().is_none());
+    }
+
+    #[test]
+    fn test_max_duration_stops_run() {
+        let slow = || AllRule::new().on_execute(|_| thread::sleep(Duration::from_millis(20)));
+
+        let report = Engine::all_runner().run_with_budget(
+            RuleContext::new(),
+            vec![slow(), slow(), slow(), slow()],
+            Budget::new().with_max_duration(Duration::from_millis(30)),
+        );
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxDuration(
+                Duration::from_millis(30)
+            )))
+        );
+        // Two rules fit, unless the machine is too busy to wake up on time.
+        let fired = report.get_trace().get_by_outcome(RuleOutcome::Fired).len();
+        assert!((1..=2).contains(&fired), "{fired}");
+    }
+
+    #[test]
+    fn test_budget_exceeded_once_whatever_the_policy() {
+        let rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .set_execution_budget(Budget::new().with_max_fired(2));
+        let rules = (0..5).map(|_| AllRule::new()).collect();
+
+        let report =
+            Engine::all_runner().run_with_policy(rule_context, rules, ErrorPolicy::CollectAll);
+
+        assert_eq!(
+            report.get_errors(),
+            [RuleError::BudgetExceeded(BudgetLimit::MaxFired(2))]
+        );
+        assert_eq!(
+            report
+                .get_trace()
+                .get_by_outcome(RuleOutcome::Skipped("budget exceeded"))
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_execution_budget_on_context() {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_max_depth(8);
+        let budget = Budget::new().with_max_fired(1);
+
+        assert_eq!(
+            rule_context.borrow_mut().set_execution_budget(budget),
+            Budget::new().with_max_depth(8)
+        );
+        // The budget keeps the depth limit of the context.
+        assert_eq!(
+            rule_context.borrow().get_execution_budget(),
+            budget.with_max_depth(8)
+        );
+
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![nested(2)]);
+        assert_eq!(
+            result,
+            Err(RuleError::BudgetExceeded(BudgetLimit::MaxFired(1)))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "execution budget exceeded: more than 1 rules fired"
+        );
+
+        rule_context
+            .borrow_mut()
+            .set_execution_budget(Budget::new());
+        assert!(Engine::chain_runner()
+            .try_run(rule_context, vec![nested(2)])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_run_with_budget_restores_previous_budget() {
+        let rule_context = RuleContext::new();
+        let outer = Budget::new().with_max_depth(8);
+        rule_context.borrow_mut().set_execution_budget(outer);
+
+        Engine::all_runner().run_with_budget(
+            rule_context.clone(),
+            vec![AllRule::new()],
+            Budget::new().with_max_fired(1),
+        );
+
+        assert_eq!(rule_context.borrow().get_execution_budget(), outer);
+    }
+
+    #[test]
+    fn test_run_budget_keeps_max_depth_of_context() {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_max_depth(2);
+
+        let report = Engine::chain_runner().run_with_budget(
+            rule_context.clone(),
+            vec![nested(3)],
+            Budget::new().with_max_fired(10),
+        );
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::BudgetExceeded(BudgetLimit::MaxDepth(2)))
+        );
+        assert_eq!(rule_context.borrow().get_max_depth(), 2);
+    }
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "expr")]
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, error::Error, rc::Rc};
+
+    use dredd_rs::expr::{Expr, ExprError};
+    use dredd_rs::rule::*;
+
+    fn eval(source: &str, rule_context: &Rc<RefCell<RuleContext>>) -> Result<bool, ExprError> {
+        Expr::parse(source)?.eval(&rule_context.borrow())
+    }
+
+    #[test]
+    fn test_expr_reads_context_values() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 0.75f64);
+        rule_context.set("age", 42u32);
+        rule_context.set("customer.country", "BR".to_string());
+        rule_context.set("vip", true);
+
+        assert!(eval("score > 0.5", &rule_context).unwrap());
+        assert!(eval("age * 2 - 4 == 80 && age % 2 == 0", &rule_context).unwrap());
+        assert!(eval("customer.country == 'BR' && vip", &rule_context).unwrap());
+        assert!(eval("!(age < 18 || score <= 0.5)", &rule_context).unwrap());
+        assert!(!eval("age >= 18 && !vip", &rule_context).unwrap());
+        assert!(eval("-age < 0 && \"a\" + \"b\" == 'ab'", &rule_context).unwrap());
+    }
+
+    #[test]
+    fn test_expr_short_circuits() {
+        let rule_context = RuleContext::new();
+
+        assert!(!eval("false && missing > 1", &rule_context).unwrap());
+        assert!(eval("true || missing > 1", &rule_context).unwrap());
+    }
+
+    #[test]
+    fn test_expr_errors() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 42i64);
+        rule_context.set("tags", vec!["a"]);
+
+        assert_eq!(
+            eval("missing > 1", &rule_context),
+            Err(ExprError::MissingKey("missing".to_string()))
+        );
+        assert_eq!(
+            eval("age / 0 > 1", &rule_context),
+            Err(ExprError::DivisionByZero)
+        );
+        assert!(matches!(
+            eval("age + 1", &rule_context),
+            Err(ExprError::TypeMismatch(_))
+        ));
+        assert!(matches!(
+            eval("tags == 1", &rule_context),
+            Err(ExprError::TypeMismatch(_))
+        ));
+        assert!(matches!(
+            Expr::parse("age > "),
+            Err(ExprError::Parse { position: 6, .. })
+        ));
+        assert!(matches!(
+            Expr::parse("age = 1"),
+            Err(ExprError::Parse { position: 4, .. })
+        ));
+        assert!(matches!(
+            Expr::parse("(age > 1"),
+            Err(ExprError::Parse { position: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_expr_random() {
+        let mut rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_seed(7);
+        let draws: Vec<_> = (0..100)
+            .map(|_| eval("random() < 0.25", &rule_context).unwrap())
+            .collect();
+
+        rule_context.borrow_mut().set_seed(7);
+        let replayed: Vec<_> = (0..100)
+            .map(|_| eval("random() < 0.25", &rule_context).unwrap())
+            .collect();
+
+        let hits = draws.iter().filter(|hit| **hit).count();
+        assert_eq!(draws, replayed);
+        assert!((10..40).contains(&hits));
+        assert!(eval("random() >= 0 && random() < 1", &rule_context).unwrap());
+        assert!(matches!(
+            eval("random(1) < 0.5", &rule_context),
+            Err(ExprError::Parse { position: 0, .. })
+        ));
+
+        rule_context.set("random", 0.5);
+        assert!(eval("random == 0.5", &rule_context).unwrap());
+    }
+
+    #[test]
+    fn test_builder_eval_expr() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 0.75f64);
+
+        let rule = ChainRule::builder()
+            .eval_expr("score > 0.5")
+            .on_execute(|this| this.get_rule_context().set("approved", true))
+            .child(
+                ChainRule::builder()
+                    .eval_expr("missing > 0.5")
+                    .on_execute(|this| this.get_rule_context().set("child", true))
+                    .build(),
+            )
+            .build();
+
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+        assert!(rule_context.get::<bool>("child").is_none());
+    }
+
+    #[test]
+    fn test_builder_eval_expr_fails_on_evaluation_error() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", "high");
+
+        let rule = ChainRule::builder()
+            .with_name("scored")
+            .eval_expr("score > 0.5")
+            .on_execute(|this| this.get_rule_context().set("approved", true))
+            .build();
+        let error = Engine::chain_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap_err();
+
+        assert_eq!(error.get_rule(), Some("scored"));
+        assert!(matches!(
+            error.source().unwrap().downcast_ref::<ExprError>(),
+            Some(ExprError::TypeMismatch(_))
+        ));
+        assert!(rule_context.get::<bool>("approved").is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid expression")]
+    fn test_builder_eval_expr_panics_on_invalid_expression() {
+        BestFirstRule::builder().eval_expr("score >");
+    }
+
+    #[test]
+    fn test_builder_try_eval_expr() {
+        assert!(matches!(
+            BestFirstRule::builder().try_eval_expr("score >"),
+            Err(ExprError::Parse { .. })
+        ));
+        assert!(ChainRule::builder().try_eval_expr("score > 0.5").is_ok());
+    }
+
+    #[test]
+    fn test_expr_negation_overflow() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("min", i64::MIN);
+
+        assert!(matches!(
+            eval("-min < 0", &rule_context),
+            Err(ExprError::Overflow(_))
+        ));
+        assert!(eval("-(min + 1) > 0", &rule_context).unwrap());
+        assert!(matches!(
+            eval("min - 1 < 0", &rule_context),
+            Err(ExprError::Overflow(_))
+        ));
+        assert_eq!(
+            ExprError::Overflow("negating 1".to_string()).to_string(),
+            "integer overflow negating 1"
+        );
+    }
+
+    #[test]
+    fn test_expr_too_deep() {
+        let nested = |open: &str, inner: &str, close: &str, count: usize| {
+            format!("{}{inner}{}", open.repeat(count), close.repeat(count))
+        };
+
+        for source in [
+            nested("!", "x", "", 100_000),
+            nested("-", "1 > 0", "", 100_000),
+            nested("(", "1 > 0", ")", 100_000),
+            format!("1{} > 0", " + 1".repeat(100_000)),
+            format!("x{}", " && x".repeat(100_000)),
+        ] {
+            assert_eq!(Expr::parse(&source), Err(ExprError::TooDeep));
+        }
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("x", true);
+        assert!(eval(&nested("(", "x", ")", 200), &rule_context).unwrap());
+        assert!(eval(&nested("!!", "x", "", 100), &rule_context).unwrap());
+        assert!(eval(&format!("x{}", " && x".repeat(200)), &rule_context).unwrap());
+    }
+}
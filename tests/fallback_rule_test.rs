@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn source(name: &str, price: Option<u32>) -> std::rc::Rc<std::cell::RefCell<AllRule>> {
+        AllRule::new().with_name(name).on_execute(move |this| {
+            let mut rule_context = this.get_rule_context();
+            rule_context.set("attempted", true);
+            match price {
+                Some(price) => rule_context.set("price", price),
+                None => rule_context.fail(RuleError::failed("unavailable")),
+            }
+        })
+    }
+
+    #[test]
+    fn test_fallback_stops_at_first_success() {
+        let rule = FallbackRule::new(vec![
+            source("live", None),
+            AllRule::new().with_name("skipped").on_eval(|_| false),
+            source("cache", Some(990)),
+            source("default", Some(1000)),
+        ])
+        .wrap(AllRule::new().with_name("price"));
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert!(report.get_error().is_none());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["price", "live", "cache"]
+        );
+        assert_eq!(*rule_context.get::<u32>("price").unwrap(), 990);
+    }
+
+    #[test]
+    fn test_failed_alternative_changes_are_restored() {
+        let rule = FallbackRule::new(vec![
+            source("live", None),
+            AllRule::new()
+                .with_name("flag")
+                .on_execute(|this| this.get_rule_context().set("fallback", true)),
+        ])
+        .wrap(AllRule::new());
+
+        let rule_context = RuleContext::new();
+        Engine::all_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap();
+
+        assert!(rule_context.get::<bool>("attempted").is_none());
+        assert!(*rule_context.get::<bool>("fallback").unwrap());
+    }
+
+    #[test]
+    fn test_fallback_fails_when_no_alternative_succeeds() {
+        let rule = FallbackRule::new(vec![
+            ChainRule::new()
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("live down"))),
+            ChainRule::new().on_execute(|this| {
+                this.get_rule_context()
+                    .fail(RuleError::failed("cache down"))
+            }),
+        ])
+        .wrap(ChainRule::new().add_child(
+            ChainRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+        ));
+
+        let rule_context = RuleContext::new();
+        let result = Engine::chain_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::failed("cache down")));
+        assert!(rule_context.get::<bool>("child").is_none());
+
+        let rule = FallbackRule::new(vec![BestFirstRule::new().on_eval(|_| false)])
+            .wrap(BestFirstRule::new());
+        let result = Engine::best_first_runner().try_run(RuleContext::new(), vec![rule]);
+        assert_eq!(
+            result,
+            Err(RuleError::failed("no fallback alternative applied"))
+        );
+    }
+
+    #[test]
+    fn test_handled_failures_are_not_collected() {
+        let rule = FallbackRule::new(vec![source("live", None), source("cache", Some(990))])
+            .wrap(AllRule::new());
+
+        let report = Engine::all_runner().run_with_policy(
+            RuleContext::new(),
+            vec![rule],
+            ErrorPolicy::CollectAll,
+        );
+
+        assert!(report.get_errors().is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "ffi")]
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{c_void, CStr, CString},
+        ptr,
+    };
+
+    use dredd_rs::ffi::*;
+
+    const RULES: &str = r#"{
+        "type": "all",
+        "state": "active",
+        "rules": [
+            { "name": "adult", "eval": "is_adult", "execute": "count" },
+            { "name": "broken", "eval": "is_broken", "execute": "fail" }
+        ]
+    }"#;
+
+    unsafe extern "C" fn count(ctx: *mut DreddContext, user_data: *mut c_void) {
+        *(user_data as *mut u32) += 1;
+        let mut age = 0;
+        assert!(dredd_context_get_int(ctx, c"age".as_ptr(), &mut age));
+        dredd_context_set_string(ctx, c"segment".as_ptr(), c"adult".as_ptr());
+    }
+
+    unsafe extern "C" fn is_broken(ctx: *mut DreddContext, _: *mut c_void) -> bool {
+        let mut broken = false;
+        dredd_context_get_bool(ctx, c"broken".as_ptr(), &mut broken) && broken
+    }
+
+    unsafe extern "C" fn fail(ctx: *mut DreddContext, _: *mut c_void) {
+        dredd_context_fail(ctx, c"service unavailable".as_ptr());
+    }
+
+    unsafe fn last_error() -> String {
+        CStr::from_ptr(dredd_last_error())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    unsafe fn load(counter: &mut u32) -> (*mut DreddRegistry, *mut DreddRules) {
+        let registry = dredd_registry_new();
+        assert!(dredd_registry_condition_expr(
+            registry,
+            c"is_adult".as_ptr(),
+            c"age >= 18".as_ptr()
+        ));
+        assert!(dredd_registry_condition(
+            registry,
+            c"is_broken".as_ptr(),
+            is_broken,
+            ptr::null_mut()
+        ));
+        assert!(dredd_registry_action(
+            registry,
+            c"count".as_ptr(),
+            count,
+            counter as *mut u32 as *mut c_void
+        ));
+        assert!(dredd_registry_action(
+            registry,
+            c"fail".as_ptr(),
+            fail,
+            ptr::null_mut()
+        ));
+        let json = CString::new(RULES).unwrap();
+        let rules = dredd_rules_from_json(json.as_ptr(), registry);
+        assert!(!rules.is_null());
+        (registry, rules)
+    }
+
+    #[test]
+    fn test_fire_rules_with_c_callbacks() {
+        let mut counter = 0u32;
+        unsafe {
+            let (registry, rules) = load(&mut counter);
+            let ctx = dredd_context_new();
+            dredd_context_set_int(ctx, c"age".as_ptr(), 30);
+
+            assert!(dredd_rules_fire(rules, ctx));
+            assert!(dredd_rules_fire(rules, ctx));
+
+            let segment = dredd_context_get_string(ctx, c"segment".as_ptr());
+            assert_eq!(CStr::from_ptr(segment).to_str().unwrap(), "adult");
+            dredd_string_free(segment);
+
+            dredd_context_free(ctx);
+            dredd_rules_free(rules);
+            dredd_registry_free(registry);
+        }
+        assert_eq!(counter, 2);
+    }
+
+    #[test]
+    fn test_failure_is_reported() {
+        let mut counter = 0u32;
+        unsafe {
+            let (registry, rules) = load(&mut counter);
+            let ctx = dredd_context_new();
+            dredd_context_set_bool(ctx, c"broken".as_ptr(), true);
+
+            assert!(!dredd_rules_fire(rules, ctx));
+            assert_eq!(last_error(), "rule `broken` failed: service unavailable");
+
+            dredd_context_free(ctx);
+            dredd_rules_free(rules);
+            dredd_registry_free(registry);
+        }
+    }
+
+    #[test]
+    fn test_context_values() {
+        unsafe {
+            let ctx = dredd_context_new();
+            assert!(dredd_context_set_float(ctx, c"score".as_ptr(), 0.75));
+            assert!(dredd_context_set_json(
+                ctx,
+                c"tags".as_ptr(),
+                c"[\"vip\", \"new\"]".as_ptr()
+            ));
+
+            let mut score = 0.0;
+            assert!(dredd_context_get_float(ctx, c"score".as_ptr(), &mut score));
+            assert_eq!(score, 0.75);
+
+            let tags = dredd_context_get_json(ctx, c"tags".as_ptr());
+            assert_eq!(CStr::from_ptr(tags).to_str().unwrap(), r#"["vip","new"]"#);
+            dredd_string_free(tags);
+
+            let mut flag = false;
+            assert!(!dredd_context_get_bool(ctx, c"score".as_ptr(), &mut flag));
+            assert_eq!(last_error(), "expected a boolean, got 0.75");
+            assert!(!dredd_context_get_bool(ctx, c"missing".as_ptr(), &mut flag));
+            assert_eq!(last_error(), "missing key `missing`");
+
+            dredd_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments_are_reported() {
+        unsafe {
+            assert!(!dredd_context_set_int(ptr::null_mut(), c"age".as_ptr(), 1));
+            assert_eq!(last_error(), "`ctx` is null");
+
+            let registry = dredd_registry_new();
+            assert!(!dredd_registry_condition_expr(
+                registry,
+                c"broken".as_ptr(),
+                c"age >=".as_ptr()
+            ));
+            assert!(last_error().starts_with("invalid condition `broken`"));
+
+            let json = CString::new(RULES).unwrap();
+            assert!(dredd_rules_from_json(json.as_ptr(), registry).is_null());
+            assert!(last_error().contains("is_adult"), "{}", last_error());
+
+            dredd_registry_free(registry);
+        }
+    }
+
+    #[test]
+    fn test_checked_in_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/dredd.h"));
+        let checked_in = include_str!("../include/dredd.h");
+
+        assert!(
+            generated == checked_in,
+            "include/dredd.h is out of date, regenerate it with cbindgen"
+        );
+    }
+}
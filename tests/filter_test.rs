@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn jurisdiction_rules() -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        vec![
+            AllRule::new().with_name("checkout").add_children(vec![
+                AllRule::new()
+                    .with_name("vat")
+                    .with_tags(&["eu-only"])
+                    .add_child(AllRule::new().with_name("vat reverse charge")),
+                AllRule::new()
+                    .with_name("sales tax")
+                    .with_tags(&["us-only"])
+                    .with_attribute("regulator", "IRS"),
+            ]),
+            AllRule::new()
+                .with_name("gdpr")
+                .with_tags(&["eu-only", "privacy"]),
+        ]
+    }
+
+    #[test]
+    fn test_tags_and_attributes() {
+        let rule = AllRule::new()
+            .with_name("sales tax")
+            .with_tags(&["us-only", "tax"])
+            .with_attribute("regulator", "IRS")
+            .with_attribute("jurisdiction", "US");
+        let rule = rule.borrow();
+        let metadata = rule.get_metadata();
+
+        assert_eq!(rule.get_tags(), ["us-only", "tax"]);
+        assert!(metadata.has_tag("tax"));
+        assert!(!metadata.has_tag("eu-only"));
+        assert_eq!(rule.get_attribute("regulator"), Some("IRS"));
+        assert_eq!(metadata.get_attribute("missing"), None);
+        assert_eq!(
+            metadata.get_attributes().keys().collect::<Vec<_>>(),
+            ["jurisdiction", "regulator"]
+        );
+        assert_eq!(metadata.get_name(), Some("sales tax"));
+    }
+
+    #[test]
+    fn test_execute_filtered_skips_rules_and_their_children() {
+        let rules = jurisdiction_rules();
+
+        let eu = Engine::execute_filtered(RuleContext::new(), rules.clone(), |meta| {
+            !meta.has_tag("us-only")
+        });
+        let us =
+            Engine::execute_filtered(RuleContext::new(), rules, |meta| !meta.has_tag("eu-only"));
+
+        assert_eq!(
+            eu.get_trace().get_executed_names(),
+            vec!["checkout", "vat", "vat reverse charge", "gdpr"]
+        );
+        assert_eq!(
+            us.get_trace().get_executed_names(),
+            vec!["checkout", "sales tax"]
+        );
+        let skipped: Vec<_> = us
+            .get_trace()
+            .get_skipped()
+            .iter()
+            .map(|entry| (entry.get_name().unwrap(), entry.get_outcome()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                ("vat", RuleOutcome::Skipped("filtered")),
+                ("gdpr", RuleOutcome::Skipped("filtered")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_attribute_and_is_removed_after_the_run() {
+        let rule_context = RuleContext::new();
+
+        let report = Engine::execute_filtered(rule_context.clone(), jurisdiction_rules(), |meta| {
+            meta.get_attribute("regulator") != Some("IRS")
+        });
+        assert!(!report
+            .get_trace()
+            .get_executed_names()
+            .contains(&"sales tax"));
+
+        let report = Engine::all_runner().run_with_report(rule_context, jurisdiction_rules());
+        assert!(report
+            .get_trace()
+            .get_executed_names()
+            .contains(&"sales tax"));
+    }
+
+    #[test]
+    fn test_nested_filters_combine() {
+        let rule_context = RuleContext::new();
+        let inner_context = rule_context.clone();
+        let rules = vec![AllRule::new().with_name("tenant").on_execute(move |_| {
+            let report =
+                Engine::execute_filtered(inner_context.clone(), jurisdiction_rules(), |meta| {
+                    !meta.has_tag("privacy")
+                });
+            let names = report.get_trace().get_executed_names().join(",");
+            inner_context.clone().set("fired", names);
+        })];
+
+        Engine::execute_filtered(rule_context.clone(), rules, |meta| !meta.has_tag("us-only"));
+
+        assert_eq!(
+            *rule_context.get::<String>("fired").unwrap(),
+            "checkout,vat,vat reverse charge"
+        );
+    }
+}
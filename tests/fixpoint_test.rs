@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_fixpoint_derives_facts_until_converged() {
+        let rules = vec![
+            AllRule::new()
+                .with_name("discount")
+                .on_eval(|ctx| ctx.get::<bool>("vip").is_some())
+                .on_execute(|this| this.get_rule_context().set("discount", 10u32)),
+            AllRule::new()
+                .with_name("vip")
+                .on_eval(|ctx| ctx.get::<bool>("loyal").is_some())
+                .on_execute(|this| this.get_rule_context().set("vip", true)),
+            AllRule::new()
+                .with_name("loyal")
+                .on_eval(|ctx| *ctx.get::<u32>("orders").unwrap() > 10)
+                .on_execute(|this| this.get_rule_context().set("loyal", true)),
+        ];
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("orders", 12u32);
+        let report = Engine::execute_to_fixpoint(rule_context.clone(), rules, 10);
+
+        assert_eq!(*report.get_outcome(), FixpointOutcome::Converged);
+        assert_eq!(report.get_passes().len(), 4);
+        assert_eq!(
+            report.get_changed_keys(),
+            [vec!["loyal"], vec!["vip"], vec!["discount"], vec![]]
+        );
+        assert_eq!(report.get_executed_names()[0], vec!["loyal"]);
+        assert_eq!(
+            report.get_executed_names()[3],
+            vec!["discount", "vip", "loyal"]
+        );
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 10);
+    }
+
+    #[test]
+    fn test_fixpoint_stops_at_iteration_limit() {
+        let rule = AllRule::new().on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            let count = rule_context.get::<u32>("count").map_or(0, |count| *count);
+            rule_context.set("count", count + 1);
+        });
+
+        let rule_context = RuleContext::new();
+        let report = Engine::execute_to_fixpoint(rule_context.clone(), vec![rule], 5);
+
+        assert_eq!(*report.get_outcome(), FixpointOutcome::IterationLimit);
+        assert!(!report.is_converged());
+        assert_eq!(report.get_passes().len(), 5);
+        assert_eq!(*rule_context.get::<u32>("count").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_fixpoint_detects_cycle() {
+        let rule = AllRule::new().on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            let on = rule_context.get::<bool>("on").is_some_and(|on| *on);
+            rule_context.set("on", !on);
+        });
+
+        let rule_context = RuleContext::new();
+        rule_context.clone().set("on", false);
+        let report = Engine::execute_to_fixpoint(rule_context, vec![rule], 100);
+
+        // The second pass brings the context back to its initial values.
+        assert_eq!(report.get_passes().len(), 2);
+        assert_eq!(
+            *report.get_outcome(),
+            FixpointOutcome::Cycle { repeats_pass: 0 }
+        );
+    }
+
+    #[test]
+    fn test_fixpoint_stops_at_failure() {
+        let rules = vec![
+            AllRule::new().on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                let count = rule_context.get::<u32>("count").map_or(0, |count| *count);
+                rule_context.set("count", count + 1);
+            }),
+            AllRule::new()
+                .on_eval(|ctx| *ctx.get::<u32>("count").unwrap() == 2)
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("too many"))),
+        ];
+
+        let rule_context = RuleContext::new();
+        let report = Engine::execute_to_fixpoint(rule_context.clone(), rules, 10);
+
+        assert_eq!(
+            *report.get_outcome(),
+            FixpointOutcome::Failed(RuleError::failed("too many"))
+        );
+        assert_eq!(report.get_passes().len(), 2);
+        assert_eq!(report.get_changed_keys(), [vec!["count"], vec!["count"]]);
+        assert!(rule_context.has_failed());
+    }
+
+    #[test]
+    fn test_fixpoint_compares_lists_and_registered_types_by_value() {
+        #[derive(PartialEq)]
+        struct Tier(&'static str);
+        RuleContext::register_comparable::<Tier>();
+
+        let rules = vec![AllRule::new().on_execute(|this| {
+            let mut rule_context = this.get_rule_context();
+            rule_context.set("tags", vec!["vip".to_string()]);
+            rule_context.set("tier", Tier("gold"));
+        })];
+
+        let report = Engine::execute_to_fixpoint(RuleContext::new(), rules, 10);
+
+        assert_eq!(*report.get_outcome(), FixpointOutcome::Converged);
+        assert_eq!(report.get_changed_keys(), [vec!["tags", "tier"], vec![]]);
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use dredd_rs::flags::{EvaluationContext, FlagError, Flags};
+    use dredd_rs::rule::*;
+
+    /// A provider counting its calls, enabled for "beta" users until it goes offline.
+    fn provider(
+        calls: Rc<Cell<u32>>,
+        online: Rc<Cell<bool>>,
+    ) -> impl Fn(&str, &EvaluationContext) -> Result<bool, FlagError> {
+        move |flag, context| {
+            calls.set(calls.get() + 1);
+            if !online.get() {
+                return Err(FlagError::ProviderNotReady);
+            }
+            match flag {
+                "new_pricing" => Ok(context.get_targeting_key() == Some("beta")),
+                _ => Err(FlagError::FlagNotFound(flag.to_string())),
+            }
+        }
+    }
+
+    fn context(user: &'static str) -> std::rc::Rc<std::cell::RefCell<RuleContext>> {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", user);
+        rule_context
+    }
+
+    #[test]
+    fn test_flags_resolve_per_targeting_key() {
+        let (calls, online) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(true)));
+        let flags = Flags::new(provider(calls.clone(), online)).with_targeting_key("user");
+
+        assert!(flags.is_enabled("new_pricing", &context("beta").borrow()));
+        assert!(!flags.is_enabled("new_pricing", &context("other").borrow()));
+        assert!(!flags.is_enabled("new_pricing", &RuleContext::new().borrow()));
+        // Without a cache every check asks the provider.
+        assert!(flags.is_enabled("new_pricing", &context("beta").borrow()));
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_flags_cache() {
+        let (calls, online) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(true)));
+        let flags = Flags::new(provider(calls.clone(), online))
+            .with_targeting_key("user")
+            .with_cache_ttl(Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert!(flags.is_enabled("new_pricing", &context("beta").borrow()));
+            assert!(!flags.is_enabled("new_pricing", &context("other").borrow()));
+        }
+        assert_eq!(calls.get(), 2);
+
+        flags.clear_cache();
+        assert!(flags.is_enabled("new_pricing", &context("beta").borrow()));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_flags_offline_fallbacks() {
+        let (calls, online) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(true)));
+        let flags = Flags::new(provider(calls, online.clone()))
+            .with_targeting_key("user")
+            .with_default("new_pricing", true);
+
+        assert!(!flags.is_enabled("new_pricing", &context("other").borrow()));
+        online.set(false);
+        // The last resolved value, then the default, then false.
+        assert!(!flags.is_enabled("new_pricing", &context("other").borrow()));
+        assert!(flags.is_enabled("new_pricing", &context("beta").borrow()));
+        assert!(!flags.is_enabled("missing", &context("beta").borrow()));
+    }
+
+    #[test]
+    fn test_flag_enabled_condition() {
+        let (calls, online) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(true)));
+        let flags = Flags::new(provider(calls, online)).with_targeting_key("user");
+
+        let rules = || {
+            vec![
+                BestFirstRule::new()
+                    .on_eval(flags.flag_enabled("new_pricing"))
+                    .on_execute(|this| this.get_rule_context().set("pricing", "v2")),
+                BestFirstRule::new()
+                    .on_execute(|this| this.get_rule_context().set("pricing", "v1")),
+            ]
+        };
+
+        for (user, pricing) in [("beta", "v2"), ("other", "v1")] {
+            let rule_context = context(user);
+            Engine::best_first_runner().run(rule_context.clone(), rules());
+            assert_eq!(*rule_context.get::<&str>("pricing").unwrap(), pricing);
+        }
+    }
+}
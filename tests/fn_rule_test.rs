@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dredd_rs::rule::*;
+
+    fn legacy_discount(total: u32, vip: bool) -> u32 {
+        if vip && total > 100 {
+            15
+        } else if total > 100 {
+            5
+        } else {
+            0
+        }
+    }
+
+    struct Order {
+        total: u32,
+        vip: bool,
+    }
+
+    struct LegacyPricing;
+
+    impl Decision<Order, u32> for LegacyPricing {
+        fn decide(&self, order: Order) -> u32 {
+            legacy_discount(order.total, order.vip)
+        }
+    }
+
+    fn order(ctx: &RuleContext) -> Result<Order, RuleError> {
+        let total = ctx
+            .get::<u32>("total")
+            .ok_or(RuleError::failed("missing total"))?;
+        let vip = ctx.get::<bool>("vip").is_some_and(|vip| *vip);
+        Ok(Order { total: *total, vip })
+    }
+
+    #[test]
+    fn test_fn_rule_from_closure() {
+        let legacy = FnRule::from(|ctx: &mut RuleContext| {
+            let total = ctx.get::<u32>("total").map_or(0, |total| *total);
+            ctx.set("discount", legacy_discount(total, true));
+        });
+        let rule = legacy
+            .wrap(AllRule::new().on_execute(|this| this.get_rule_context().set("total", 150u32)));
+
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 15);
+        let metrics = legacy.get_metrics();
+        assert_eq!(metrics.get_calls(), 1);
+        assert_eq!(metrics.get_failures(), 0);
+    }
+
+    #[test]
+    fn test_fn_rule_mapped_trait_object() {
+        let decision: Rc<dyn Decision<Order, u32>> = Rc::new(LegacyPricing);
+        let legacy = FnRule::mapped(decision, order, |ctx, discount| {
+            ctx.set("discount", discount)
+        });
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 250u32);
+        Engine::chain_runner().run(rule_context.clone(), vec![legacy.wrap(ChainRule::new())]);
+
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 5);
+        assert_eq!(legacy.get_metrics().get_calls(), 1);
+    }
+
+    #[test]
+    fn test_fn_rule_metrics_count_failures() {
+        let legacy = FnRule::mapped(LegacyPricing, order, |ctx, discount| {
+            ctx.set("discount", discount)
+        });
+        let runner = Engine::all_runner();
+
+        let mut rule_context = RuleContext::new();
+        let report =
+            runner.run_with_report(rule_context.clone(), vec![legacy.wrap(AllRule::new())]);
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("missing total"))
+        );
+        rule_context.take_error();
+
+        rule_context.set("total", 50u32);
+        runner.run(
+            rule_context.clone(),
+            vec![legacy.clone().wrap(AllRule::new())],
+        );
+
+        let metrics = legacy.get_metrics();
+        assert_eq!(metrics.get_calls(), 2);
+        assert_eq!(metrics.get_failures(), 1);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fn_rule_skipped_when_rule_does_not_apply() {
+        let legacy = FnRule::from(|ctx: &mut RuleContext| ctx.set("decided", true));
+        let skipped = legacy.wrap(AllRule::new().on_eval(|_| false));
+        let failing = legacy.wrap(
+            AllRule::new().on_execute(|this| this.get_rule_context().fail(RuleError::failed("x"))),
+        );
+
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run_with_report(rule_context.clone(), vec![skipped, failing]);
+
+        assert!(rule_context.get::<bool>("decided").is_none());
+        assert_eq!(legacy.get_metrics().get_calls(), 0);
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule;
+    use dredd_rs::rule::*;
+
+    fn set_true(key: &'static str) -> impl Fn(&mut AllRule) {
+        move |this| this.get_rule_context().set(key, true)
+    }
+
+    #[test]
+    fn test_goal_solver_fires_only_needed_rules() {
+        let solver = GoalSolver::new(vec![
+            AllRule::new()
+                .with_name("unrelated")
+                .with_writes(&["discount"])
+                .on_execute(set_true("discount")),
+            AllRule::new()
+                .with_name("fraud")
+                .with_reads(&["new_device", "high_value"])
+                .with_writes(&["fraud"])
+                .on_execute(set_true("fraud")),
+            AllRule::new()
+                .with_name("high_value")
+                .with_reads(&["total"])
+                .with_writes(&["high_value"])
+                .on_eval(|ctx| *ctx.get::<u32>("total").unwrap() > 1000)
+                .on_execute(set_true("high_value")),
+        ]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 5000u32);
+        rule_context.set("new_device", true);
+        let report = solver.solve(rule_context.clone(), "fraud");
+
+        assert!(report.is_established());
+        assert!(report.get_missing().is_empty());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["high_value", "fraud"]
+        );
+        assert!(rule_context.get::<bool>("discount").is_none());
+        assert_eq!(solver.get_producers("fraud").len(), 1);
+    }
+
+    #[test]
+    fn test_goal_solver_tries_next_producer() {
+        let solver = GoalSolver::new(vec![
+            rule!(all {
+                name: "from_score",
+                reads: ["score"],
+                writes: ["approved"],
+                then: set_true("approved"),
+            }),
+            rule!(all {
+                name: "from_whitelist",
+                reads: ["whitelisted"],
+                writes: ["approved"],
+                when: |ctx: ContextView| *ctx.get::<bool>("whitelisted").unwrap(),
+                then: set_true("approved"),
+            }),
+            rule!(all {
+                name: "denied",
+                writes: ["approved"],
+                then: |this: &mut AllRule| this.get_rule_context().set("approved", false),
+            }),
+        ]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("whitelisted", false);
+        let report = solver.solve(rule_context.clone(), "approved");
+
+        // `score` is missing and the whitelist rule doesn't pass, so only the
+        // last rule sets the goal, to false.
+        assert!(!report.is_established());
+        assert_eq!(report.get_missing(), ["score"]);
+        assert!(!*rule_context.get::<bool>("approved").unwrap());
+    }
+
+    #[test]
+    fn test_goal_solver_ends_on_cycles() {
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let record = |name: &'static str| {
+            let fired = fired.clone();
+            move |_: &mut AllRule| fired.borrow_mut().push(name)
+        };
+        let solver = GoalSolver::new(vec![
+            AllRule::new()
+                .with_reads(&["b"])
+                .with_writes(&["a"])
+                .on_execute(record("a")),
+            AllRule::new()
+                .with_reads(&["a"])
+                .with_writes(&["b"])
+                .on_execute(record("b")),
+        ]);
+
+        let report = solver.solve(RuleContext::new(), "a");
+
+        assert!(!report.is_established());
+        assert!(report.get_missing().is_empty());
+        assert!(fired.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_goal_solver_stops_at_failure() {
+        let solver = GoalSolver::new(vec![
+            AllRule::new()
+                .with_reads(&["credit"])
+                .with_writes(&["approved"])
+                .on_execute(set_true("approved")),
+            AllRule::new().with_writes(&["credit"]).on_execute(|this| {
+                this.get_rule_context()
+                    .fail(RuleError::failed("bureau unavailable"))
+            }),
+        ]);
+
+        let rule_context = RuleContext::new();
+        let report = solver.solve(rule_context.clone(), "approved");
+
+        assert!(!report.is_established());
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("bureau unavailable"))
+        );
+        assert!(rule_context.get::<bool>("approved").is_none());
+    }
+
+    #[test]
+    fn test_goal_solver_retries_rules_missing_reads() {
+        let solver = GoalSolver::new(vec![
+            rule!(all {
+                name: "goal",
+                reads: ["h", "k"],
+                writes: ["goal"],
+                then: set_true("goal")
+            }),
+            rule!(all {
+                name: "h_from_k",
+                reads: ["k"],
+                writes: ["h"],
+                then: set_true("h")
+            }),
+            rule!(all {
+                name: "h",
+                writes: ["h"],
+                then: set_true("h")
+            }),
+            rule!(all {
+                name: "k",
+                reads: ["h"],
+                writes: ["k"],
+                then: set_true("k")
+            }),
+        ]);
+
+        let report = solver.solve(RuleContext::new(), "goal");
+
+        // `k` is first solved for `h_from_k`, while `h` is being solved, and
+        // again once `h` is established.
+        assert!(report.is_established());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["h", "k", "goal"]
+        );
+    }
+
+    #[test]
+    fn test_goal_solver_reads_layers() {
+        let solver = GoalSolver::new(vec![rule!(all {
+            name: "approve",
+            reads: ["score"],
+            writes: ["approved"],
+            then: set_true("approved"),
+        })]);
+        let mut defaults = RuleContext::new();
+        defaults.set("score", 700);
+
+        let report = solver.solve(
+            LayeredContext::new(vec![RuleContext::new(), defaults]),
+            "approved",
+        );
+
+        assert!(report.is_established());
+        assert!(report.get_missing().is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+#![cfg(feature = "http")]
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        rc::Rc,
+        thread,
+        time::Duration,
+    };
+
+    use dredd_rs::http::{HttpAction, HttpRequest, HttpResponse, UreqClient};
+    use dredd_rs::rule::*;
+
+    type Requests = Rc<RefCell<Vec<HttpRequest>>>;
+
+    /// A client recording its requests and answering with the given response.
+    fn stub(
+        status: u16,
+        body: &'static str,
+    ) -> (
+        Requests,
+        impl Fn(&HttpRequest) -> Result<HttpResponse, String>,
+    ) {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let recorded = requests.clone();
+        let client = move |request: &HttpRequest| {
+            recorded.borrow_mut().push(request.clone());
+            Ok(HttpResponse::new(status, body))
+        };
+        (requests, client)
+    }
+
+    fn run(
+        action: &HttpAction,
+        rule_context: std::rc::Rc<std::cell::RefCell<RuleContext>>,
+    ) -> Result<(), RuleError> {
+        let rule = AllRule::new().on_execute(action.callback());
+        Engine::all_runner().try_run(rule_context, vec![rule])
+    }
+
+    #[test]
+    fn test_http_action_renders_and_maps() {
+        let (requests, client) = stub(
+            200,
+            r#"{"decision": {"approved": true, "limit": 500, "note": "ok"}}"#,
+        );
+        let action = HttpAction::new(
+            client,
+            "put",
+            "https://api.example.com/orders/{order}?note={note}",
+        )
+        .allow_origin("https://API.example.com/")
+        .with_header("Authorization", "Bearer {token}")
+        .with_body(r#"{"note": {note}, "total": {total}, "literal": "{not a key}"}"#)
+        .map_response("/decision/approved", "approved")
+        .map_response("/decision/limit", "limit")
+        .with_status_key("status");
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("order", "a/b");
+        rule_context.set("note", "say \"hi\"&bye");
+        rule_context.set("token", "secret".to_string());
+        rule_context.set("total", 12.5f64);
+
+        assert_eq!(run(&action, rule_context.clone()), Ok(()));
+        let request = requests.borrow()[0].clone();
+        assert_eq!(request.get_method(), "PUT");
+        assert_eq!(
+            request.get_url(),
+            "https://api.example.com/orders/a%2Fb?note=say%20%22hi%22%26bye"
+        );
+        assert_eq!(
+            request.get_headers(),
+            [("Authorization".to_string(), "Bearer secret".to_string())]
+        );
+        assert_eq!(
+            request.get_body(),
+            Some(r#"{"note": "say \"hi\"&bye", "total": 12.5, "literal": "{not a key}"}"#)
+        );
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+        assert_eq!(*rule_context.get::<i64>("limit").unwrap(), 500);
+        assert_eq!(*rule_context.get::<i64>("status").unwrap(), 200);
+    }
+
+    #[test]
+    fn test_http_action_allow_list() {
+        let (requests, client) = stub(200, "{}");
+        let action =
+            HttpAction::get(client, "{base}/check").allow_origin("https://api.example.com");
+
+        for base in [
+            "https://api.example.com.evil.com",
+            "https://api.example.com@evil.com",
+            "http://api.example.com",
+            "https://api.example.com:8443",
+            "api.example.com",
+        ] {
+            let mut rule_context = RuleContext::new();
+            rule_context.set("base", base);
+            // Values are encoded in the URL, so they can't change its origin.
+            assert!(run(&action, rule_context).is_err(), "{base}");
+        }
+
+        let action = HttpAction::get(
+            |_: &HttpRequest| Ok(HttpResponse::new(200, "{}")),
+            "https://{host}/check",
+        )
+        .allow_origin("https://api.example.com");
+        let mut rule_context = RuleContext::new();
+        rule_context.set("host", "evil.com");
+        assert_eq!(
+            run(&action, rule_context),
+            Err(RuleError::failed(
+                "origin of `https://evil.com/check` is not allowed"
+            ))
+        );
+        assert!(requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_http_action_failures() {
+        let (_, client) = stub(503, "unavailable");
+        let action = HttpAction::get(client, "https://api.example.com/{id}")
+            .allow_origin("https://api.example.com")
+            .with_status_key("status");
+
+        let rule_context = RuleContext::new();
+        assert_eq!(
+            run(&action, rule_context),
+            Err(RuleError::failed("missing template key `id`"))
+        );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("id", 7u8);
+        assert_eq!(
+            run(&action, rule_context.clone()),
+            Err(RuleError::failed(
+                "GET https://api.example.com/7: status 503"
+            ))
+        );
+        assert_eq!(*rule_context.get::<i64>("status").unwrap(), 503);
+
+        let (_, client) = stub(200, r#"{"other": 1}"#);
+        let action = HttpAction::get(client, "https://api.example.com/")
+            .allow_origin("https://api.example.com")
+            .with_header("X-User", "{user}")
+            .map_response("/score", "score");
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", "ana");
+        assert_eq!(
+            run(&action, rule_context.clone()),
+            Err(RuleError::failed(
+                "`/score` missing from the response of https://api.example.com/"
+            ))
+        );
+        rule_context.set("user", "ana\r\nX-Admin: true");
+        assert_eq!(
+            run(&action, rule_context),
+            Err(RuleError::failed("header values can't contain line breaks"))
+        );
+    }
+
+    #[test]
+    fn test_ureq_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = r#"{"score": 0.25}"#;
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+            (request_line, String::from_utf8(body).unwrap())
+        });
+
+        let action = HttpAction::post(
+            UreqClient::new(Duration::from_secs(5)),
+            &format!("{origin}/score"),
+        )
+        .allow_origin(&origin)
+        .with_body(r#"{"user": {user}}"#)
+        .map_response("/score", "score");
+        let mut rule_context = RuleContext::new();
+        rule_context.set("user", "ana");
+
+        assert_eq!(run(&action, rule_context.clone()), Ok(()));
+        assert_eq!(*rule_context.get::<f64>("score").unwrap(), 0.25);
+        let (request_line, body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /score HTTP/1.1\r\n");
+        assert_eq!(body, r#"{"user": "ana"}"#);
+    }
+
+    #[test]
+    fn test_ureq_client_does_not_follow_redirects() {
+        let disallowed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = format!("http://{}/steal", disallowed.local_addr().unwrap());
+        disallowed.set_nonblocking(true).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 302 Found\r\nLocation: {target}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+        });
+
+        let action = HttpAction::get(
+            UreqClient::new(Duration::from_secs(5)),
+            &format!("{origin}/score"),
+        )
+        .allow_origin(&origin)
+        .with_status_key("status");
+        let rule_context = RuleContext::new();
+
+        assert_eq!(
+            run(&action, rule_context.clone()),
+            Err(RuleError::failed(format!("GET {origin}/score: status 302")))
+        );
+        assert_eq!(*rule_context.get::<i64>("status").unwrap(), 302);
+        server.join().unwrap();
+        assert!(disallowed.accept().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use dredd_rs::rule;
+    use dredd_rs::rule::*;
+
+    /// One rule per key `k0`..`k99`, counting the evaluations.
+    fn rules(evals: Rc<Cell<u32>>) -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        const KEYS: [&str; 4] = ["k0", "k1", "k2", "k3"];
+        (0..100)
+            .map(|i| {
+                let key = KEYS[i % 4];
+                let evals = evals.clone();
+                AllRule::new()
+                    .with_name(key)
+                    .with_reads(&[key])
+                    .on_eval(move |ctx| {
+                        evals.set(evals.get() + 1);
+                        ctx.get::<bool>(key).is_some_and(|value| *value)
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_indexed_engine_fires_rules_of_changed_keys() {
+        let evals = Rc::new(Cell::new(0));
+        let engine = IndexedEngine::new(rules(evals.clone()));
+        let mut rule_context = RuleContext::new();
+        rule_context.set("k0", true);
+
+        engine.run(rule_context.clone());
+        assert_eq!(evals.get(), 100);
+
+        engine.run(rule_context.clone());
+        assert_eq!(evals.get(), 100);
+
+        rule_context.set("k2", false);
+        let report = engine.run_with_report(rule_context.clone());
+        assert_eq!(evals.get(), 125);
+        assert!(report.get_trace().get_executed_names().is_empty());
+
+        // Removing a key is a change too, and so is setting an equal value.
+        rule_context
+            .borrow_mut()
+            .restore(RuleContext::new().borrow().snapshot());
+        rule_context.set("k2", false);
+        let report = engine.run_with_report(rule_context.clone());
+        assert_eq!(evals.get(), 175);
+        assert_eq!(report.get_trace().get_entries()[0].get_name(), Some("k0"));
+
+        engine.reset();
+        engine.run(rule_context);
+        assert_eq!(evals.get(), 275);
+    }
+
+    #[test]
+    fn test_indexed_engine_fires_every_rule_for_another_context() {
+        let evals = Rc::new(Cell::new(0));
+        let engine = IndexedEngine::new(rules(evals.clone()));
+        let mut rule_context = RuleContext::new();
+        rule_context.set("k0", true);
+        engine.run(rule_context.clone());
+        assert_eq!(evals.get(), 100);
+
+        // The copy shares its values with the first context.
+        let copy = RuleContext::new();
+        copy.borrow_mut().restore(rule_context.borrow().snapshot());
+        let report = engine.run_with_report(copy.clone());
+        assert_eq!(evals.get(), 200);
+        assert_eq!(report.get_trace().get_executed_names().len(), 25);
+
+        engine.run(copy);
+        assert_eq!(evals.get(), 200);
+    }
+
+    #[test]
+    fn test_indexed_engine_unindexed_rules_and_failures() {
+        let evals = Rc::new(Cell::new(0));
+        let counted = evals.clone();
+        let engine = IndexedEngine::new(vec![
+            rule!(all {
+                name: "always",
+                when: move |_| {
+                    counted.set(counted.get() + 1);
+                    true
+                }
+            }),
+            rule!(all {
+                name: "limit",
+                reads: ["amount"],
+                when: |ctx| *ctx.get::<u32>("amount").unwrap() > 100,
+                then: |this| this
+                    .get_rule_context()
+                    .fail(RuleError::failed("over limit")),
+            }),
+            rule!(all {
+                name: "after",
+                reads: ["amount"]
+            }),
+        ]);
+        assert_eq!(engine.get_unindexed_count(), 1);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("amount", 50u32);
+        let report = engine.run_with_report(rule_context.clone());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["always", "after"]
+        );
+
+        let report = engine.run_with_report(rule_context.clone());
+        assert_eq!(report.get_trace().get_executed_names(), vec!["always"]);
+        assert_eq!(evals.get(), 2);
+
+        rule_context.set("amount", 500u32);
+        let report = engine.run_with_report(rule_context);
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["always", "limit"]
+        );
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("over limit").in_rule("limit"))
+        );
+    }
+
+    #[test]
+    fn test_indexed_engine_resolves_aliased_keys() {
+        let evals = Rc::new(Cell::new(0));
+        let counted = evals.clone();
+        let engine = IndexedEngine::new(vec![AllRule::new()
+            .with_name("legacy")
+            .with_reads(&["customer_id"])
+            .on_eval(move |ctx| {
+                counted.set(counted.get() + 1);
+                ctx.get::<u64>("customer_id").is_some()
+            })]);
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.set("cust.id", 1u64);
+
+        let report = engine.run_with_report(rule_context.clone());
+        // The deprecation is reported by the rule reading the key.
+        assert_eq!(report.get_warnings()[0].get_rule(), Some("legacy"));
+        engine.run(rule_context.clone());
+        assert_eq!(evals.get(), 1);
+
+        rule_context.set("cust.id", 2u64);
+        let report = engine.run_with_report(rule_context);
+        assert_eq!(evals.get(), 2);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["legacy"]);
+    }
+
+    #[test]
+    fn test_indexed_engine_reads_lower_layers() {
+        let evals = Rc::new(Cell::new(0));
+        let engine = IndexedEngine::new(rules(evals.clone()));
+        let mut defaults = RuleContext::new();
+        defaults.set("k1", true);
+        let rule_context = LayeredContext::new(vec![RuleContext::new(), defaults.clone()]);
+
+        engine.run(rule_context.clone());
+        engine.run(rule_context.clone());
+        assert_eq!(evals.get(), 100);
+
+        defaults.set("k1", false);
+        let report = engine.run_with_report(rule_context);
+        assert_eq!(evals.get(), 125);
+        assert!(report.get_trace().get_executed_names().is_empty());
+    }
+}
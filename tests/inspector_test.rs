@@ -0,0 +1,217 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::inspector::{self, RunInspector};
+    use dredd_rs::rule::*;
+    use serde_json::{json, Map, Value};
+    use std::{cell::RefCell, rc::Rc};
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn record() -> RunInspector {
+        let rule = AllRule::new()
+            .with_name("score")
+            .on_execute(|this| this.get_rule_context().set("score", 640i64))
+            .on_post_execute(|this| this.get_rule_context().set("scored", true));
+        rule.borrow_mut().add_child(
+            AllRule::new()
+                .with_name("tier")
+                .on_execute(|this| this.get_rule_context().set("tier", "gold")),
+        );
+        let rules = vec![
+            rule,
+            AllRule::new()
+                .with_name("approve")
+                .on_eval(|ctx| *ctx.get::<i64>("score").unwrap() > 700)
+                .on_execute(|this| this.get_rule_context().set("approved", true)),
+        ];
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("applicant", "Ann");
+        RunInspector::record(&Engine::all_runner(), rule_context, rules)
+    }
+
+    #[test]
+    fn test_steps_follow_the_trace() {
+        let inspector = record();
+
+        let steps: Vec<_> = inspector
+            .get_steps()
+            .iter()
+            .map(|step| {
+                (
+                    step.get_name().unwrap(),
+                    step.get_depth(),
+                    step.get_outcome(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("score", 0, "fired"),
+                ("tier", 1, "fired"),
+                ("approve", 0, "not applicable"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_at_step() {
+        let inspector = record();
+
+        assert_eq!(
+            inspector.get_initial_state(),
+            &object(json!({ "applicant": "Ann" }))
+        );
+        // A parent is done once its children are.
+        assert_eq!(
+            inspector.state_at(0).unwrap(),
+            object(json!({ "applicant": "Ann", "score": 640, "tier": "gold", "scored": true }))
+        );
+        assert_eq!(inspector.state_at(1), inspector.state_at(0));
+        assert_eq!(inspector.state_at(2).unwrap(), inspector.get_final_state());
+        assert!(inspector.state_at(3).is_none());
+    }
+
+    #[test]
+    fn test_produced_by() {
+        let inspector = record();
+
+        assert_eq!(
+            inspector.produced_by("tier", 2).unwrap().get_name(),
+            Some("tier")
+        );
+        assert_eq!(inspector.produced_by("scored", 2).unwrap().get_index(), 0);
+        assert!(inspector.produced_by("applicant", 2).is_none());
+        assert!(inspector.produced_by("approved", 2).is_none());
+
+        let change = &inspector.get_changes()[0];
+        assert_eq!(change.get_key(), "score");
+        assert_eq!(change.get_value(), &json!(640));
+        assert_eq!(change.get_step(), Some(0));
+    }
+
+    #[test]
+    fn test_eval_results_until() {
+        let inspector = record();
+
+        let results: Vec<_> = inspector
+            .eval_results_until(1)
+            .iter()
+            .map(|step| step.get_eval_result())
+            .collect();
+        assert_eq!(results, vec![true, true]);
+        assert_eq!(inspector.eval_results_until(9).len(), 3);
+    }
+
+    #[test]
+    fn test_recording_round_trips_through_json() {
+        let inspector = record();
+
+        let loaded = RunInspector::from_json(&inspector.to_json()).unwrap();
+
+        assert_eq!(loaded, inspector);
+        assert!(RunInspector::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_recording_leaves_no_trace_behind() {
+        let rule_context = RuleContext::new();
+        RunInspector::record(
+            &Engine::all_runner(),
+            rule_context.clone(),
+            vec![AllRule::new()],
+        );
+
+        let report = Engine::all_runner().run_with_report(rule_context, vec![AllRule::new()]);
+        assert_eq!(report.get_trace().get_entries().len(), 1);
+    }
+
+    fn pricing(discount: i64, with_loyalty: bool) -> Vec<Rc<RefCell<AllRule>>> {
+        let mut rules = vec![
+            AllRule::new()
+                .with_name("base")
+                .on_execute(|this| this.get_rule_context().set("price", 100i64)),
+            AllRule::new()
+                .with_name("discount")
+                .on_eval(|ctx| *ctx.get::<bool>("member").unwrap())
+                .on_execute(move |this| this.get_rule_context().set("discount", discount)),
+        ];
+        if with_loyalty {
+            rules.push(
+                AllRule::new()
+                    .with_name("loyalty")
+                    .on_execute(|this| this.get_rule_context().set("points", 10i64)),
+            );
+        }
+        rules
+    }
+
+    fn record_pricing() -> RunInspector {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("member", true);
+        RunInspector::record(&Engine::all_runner(), rule_context, pricing(10, false))
+    }
+
+    #[test]
+    fn test_replay_against_same_rules_is_identical() {
+        let recorded = record_pricing();
+
+        let diff = inspector::replay_against(&recorded, &Engine::all_runner(), pricing(10, false));
+
+        assert!(diff.is_identical());
+        assert_eq!(
+            diff.get_replayed().get_final_state(),
+            recorded.get_final_state()
+        );
+    }
+
+    #[test]
+    fn test_replay_against_pinpoints_changed_values() {
+        let recorded = RunInspector::from_json(&record_pricing().to_json()).unwrap();
+
+        let diff = inspector::replay_against(&recorded, &Engine::all_runner(), pricing(15, true));
+
+        let rules: Vec<_> = diff
+            .get_differences()
+            .iter()
+            .map(|d| d.get_rule())
+            .collect();
+        assert_eq!(rules, vec!["discount", "loyalty"]);
+
+        let discount = diff.get_first_difference().unwrap();
+        assert_eq!(discount.get_original().unwrap().get_outcome(), "fired");
+        assert_eq!(discount.get_replayed().unwrap().get_outcome(), "fired");
+        let value = &discount.get_values()[0];
+        assert_eq!(value.get_key(), "discount");
+        assert_eq!(value.get_original(), Some(&json!(10)));
+        assert_eq!(value.get_replayed(), Some(&json!(15)));
+
+        let loyalty = &diff.get_differences()[1];
+        assert!(loyalty.get_original().is_none());
+        assert_eq!(loyalty.get_values()[0].get_replayed(), Some(&json!(10)));
+
+        assert_eq!(diff.get_changed_keys(), vec!["discount", "points"]);
+    }
+
+    #[test]
+    fn test_replay_against_removed_rule() {
+        let recorded = record_pricing();
+
+        let diff = inspector::replay_against(
+            &recorded,
+            &Engine::all_runner(),
+            pricing(10, false)[..1].to_vec(),
+        );
+
+        let removed = diff.get_first_difference().unwrap();
+        assert_eq!(removed.get_rule(), "discount");
+        assert!(removed.get_replayed().is_none());
+        assert_eq!(removed.get_values()[0].get_replayed(), None);
+        assert_eq!(diff.get_changed_keys(), vec!["discount"]);
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn limit_invariant() -> Invariant {
+        let limit = Condition::new(|ctx| {
+            ctx.get::<u32>("limit")
+                .is_some_and(|limit| *limit <= 10_000)
+        })
+        .with_description("`limit` <= 10000");
+        Invariant::when(Condition::key_equals("approved", true))
+            .then(Condition::key_exists("limit").and(limit))
+    }
+
+    fn approve(limit: u32) -> std::rc::Rc<std::cell::RefCell<AllRule>> {
+        AllRule::new().on_execute(move |this| {
+            this.get_rule_context().set("approved", true);
+            this.get_rule_context().set("limit", limit);
+        })
+    }
+
+    #[test]
+    fn test_invariant_holds() {
+        let report = Engine::all_runner().run_with_invariants(
+            RuleContext::new(),
+            vec![approve(5_000)],
+            &[limit_invariant()],
+        );
+
+        assert_eq!(report.get_status(), RunStatus::Pass);
+        assert!(report.get_errors().is_empty());
+    }
+
+    #[test]
+    fn test_invariant_violated_fails_run() {
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_invariants(
+            rule_context.clone(),
+            vec![approve(20_000)],
+            &[limit_invariant()],
+        );
+
+        let error = RuleError::InvariantViolated(
+            "if `approved` == true then `limit` is set and `limit` <= 10000".to_string(),
+        );
+        assert_eq!(report.get_error(), Some(&error));
+        assert_eq!(report.get_errors().len(), 1);
+        assert_eq!(report.get_status(), RunStatus::Fail);
+        assert_eq!(rule_context.get_error(), Some(error));
+    }
+
+    #[test]
+    fn test_invariant_unconstrained_when_condition_does_not_hold() {
+        let rule = AllRule::new().on_execute(|this| this.get_rule_context().set("approved", false));
+
+        let report = Engine::all_runner().run_with_invariants(
+            RuleContext::new(),
+            vec![rule],
+            &[limit_invariant()],
+        );
+
+        assert!(report.get_error().is_none());
+    }
+
+    #[test]
+    fn test_invariant_as_warning() {
+        let invariant = Invariant::always(Condition::key_exists("reason"))
+            .with_name("decisions have a reason")
+            .as_warning();
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_invariants(
+            rule_context.clone(),
+            vec![approve(1)],
+            &[invariant],
+        );
+
+        assert!(report.get_error().is_none());
+        assert!(!rule_context.has_failed());
+        assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+        assert_eq!(
+            report.get_warnings()[0].to_string(),
+            "warning: invariant violated: decisions have a reason"
+        );
+    }
+
+    #[test]
+    fn test_invariants_not_checked_after_failed_run() {
+        let rule = AllRule::new().on_execute(|this| {
+            this.get_rule_context().set("approved", true);
+            this.get_rule_context().fail(RuleError::failed("declined"));
+        });
+
+        let report = Engine::all_runner().run_with_invariants(
+            RuleContext::new(),
+            vec![rule],
+            &[limit_invariant()],
+        );
+
+        assert_eq!(report.get_errors(), [RuleError::failed("declined")]);
+    }
+
+    #[test]
+    fn test_registry_checks_invariants() {
+        let mut registry = RuleRegistry::new();
+        registry
+            .add_invariant("credit", limit_invariant())
+            .register("credit", Engine::all_runner(), vec![approve(20_000)]);
+        assert_eq!(registry.get_invariants("credit").len(), 1);
+
+        let result = registry.execute("credit", RuleContext::new());
+        assert!(matches!(
+            result,
+            Err(RegistryError::Failed(RuleError::InvariantViolated(_)))
+        ));
+
+        registry.register("credit", Engine::all_runner(), vec![approve(100)]);
+        assert!(registry.execute("credit", RuleContext::new()).is_ok());
+
+        registry.remove("credit");
+        assert!(registry.get_invariants("credit").is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_deprecated_key_reads_and_writes_new_key() {
+        let rule = AllRule::new().with_name("legacy").on_execute(|this| {
+            let id = *this.get_rule_context().get::<u64>("customer_id").unwrap();
+            this.get_rule_context().set("customer_id", id + 1);
+        });
+
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.set("cust.id", 41u64);
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<u64>("cust.id").unwrap(), 42);
+        let snapshot = rule_context.borrow().snapshot();
+        assert!(snapshot.get::<u64>("customer_id").is_none());
+        // Reported once per run, however often the key is used.
+        assert_eq!(report.get_warnings().len(), 1);
+        assert_eq!(report.get_warnings()[0].get_severity(), Severity::Info);
+        assert_eq!(report.get_warnings()[0].get_rule(), Some("legacy"));
+    }
+
+    #[test]
+    fn test_chained_aliases_resolve_to_last_key() {
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("cid", "customer_id");
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.set("cust.id", 7u64);
+
+        assert_eq!(*rule_context.get::<u64>("cid").unwrap(), 7);
+        let messages: Vec<String> = rule_context
+            .take_warnings()
+            .iter()
+            .map(|warning| warning.get_message().to_string())
+            .collect();
+        assert_eq!(messages, ["key `cid` is deprecated, use `cust.id`"]);
+    }
+
+    #[test]
+    fn test_new_key_reads_do_not_warn() {
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.set("cust.id", 7u64);
+
+        assert_eq!(*rule_context.get::<u64>("cust.id").unwrap(), 7);
+        assert!(rule_context.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_key_in_conditions_and_updates() {
+        let rule = AllRule::new()
+            .on_condition(Condition::key_exists("visits_v1"))
+            .on_execute(|this| *this.get_rule_context().entry("visits_v1").or_insert_int(0) += 1);
+
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("visits_v1", "visits");
+        rule_context.set("visits", 1i64);
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get_int_mut("visits").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_dry_run_keeps_aliases() {
+        let rule = AllRule::new()
+            .with_name("legacy")
+            .on_eval(|ctx| ctx.get::<u64>("customer_id").is_some());
+
+        let rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.borrow_mut().set("cust.id", 1u64);
+        let report = Engine::all_runner().dry_run(rule_context, vec![rule]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["legacy"]);
+        assert_eq!(report.get_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_deprecated_key_in_lists_and_paths() {
+        let mut customer = ContextObject::new();
+        customer.insert("age", 30u32);
+
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("old_items", "items");
+        rule_context
+            .borrow_mut()
+            .deprecate_key("client", "customer");
+        rule_context.set_list("items", vec![1, 2, 3]);
+        rule_context.set("customer", customer);
+        rule_context.push_to_list("old_items", 4);
+
+        assert_eq!(
+            *rule_context.get_list::<i32>("items").unwrap(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(*rule_context.get_path::<u32>("client.age").unwrap(), 30);
+    }
+
+    #[cfg(feature = "expr")]
+    #[test]
+    fn test_deprecated_key_in_expressions() {
+        let mut rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_age", "age");
+        rule_context.set("age", 42u32);
+
+        let expr = dredd_rs::expr::Expr::parse("customer_age > 18").unwrap();
+        assert_eq!(expr.eval(&rule_context.borrow()), Ok(true));
+        assert_eq!(rule_context.take_warnings().len(), 1);
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn rules() -> Vec<std::rc::Rc<std::cell::RefCell<BestFirstRule>>> {
+        vec![BestFirstRule::new()
+            .with_reads(&["score"])
+            .on_eval(|ctx| *ctx.get::<u32>("score").unwrap() > 500)
+            .on_execute(|this| this.get_rule_context().set("tier", "gold"))
+            .add_child(
+                BestFirstRule::new()
+                    .with_reads(&["tier", "country"])
+                    .with_writes(&["discount"])
+                    .on_eval(|ctx| *ctx.get::<&str>("country").unwrap() == "BR")
+                    .on_execute(|this| {
+                        let tier = this.get_rule_context().get::<&str>("tier").unwrap();
+                        this.get_rule_context()
+                            .set("discount", if *tier == "gold" { 20u32 } else { 5 });
+                    }),
+            )]
+    }
+
+    #[test]
+    fn test_declared_key_usage_walks_children() {
+        let usage = KeyUsage::declared(&rules());
+
+        assert_eq!(usage.read_keys(), vec!["country", "score", "tier"]);
+        assert_eq!(usage.write_keys(), vec!["discount"]);
+        // "tier" is written by a rule that does not declare it.
+        assert_eq!(usage.input_keys(), vec!["country", "score", "tier"]);
+    }
+
+    #[test]
+    fn test_recorded_key_usage_only_sees_taken_branches() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 100u32);
+        let usage = KeyUsage::record(&Engine::best_first_runner(), rule_context, rules());
+
+        assert_eq!(usage.read_keys(), vec!["score"]);
+        assert!(usage.write_keys().is_empty());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 900u32);
+        rule_context.set("country", "BR");
+        let recorded =
+            KeyUsage::record(&Engine::best_first_runner(), rule_context.clone(), rules());
+
+        assert_eq!(recorded.read_keys(), vec!["country", "score", "tier"]);
+        assert_eq!(recorded.write_keys(), vec!["discount", "tier"]);
+        assert_eq!(recorded.input_keys(), vec!["country", "score"]);
+        assert_eq!(*rule_context.get::<u32>("discount").unwrap(), 20);
+    }
+
+    #[test]
+    fn test_key_usage_merge_and_missing_inputs() {
+        let mut usage = KeyUsage::declared(&rules());
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 900u32);
+        rule_context.set("country", "BR");
+        usage.merge(&KeyUsage::record(
+            &Engine::best_first_runner(),
+            rule_context,
+            rules(),
+        ));
+
+        assert_eq!(usage.write_keys(), vec!["discount", "tier"]);
+
+        // Declarations don't say "tier" is written, so it stays an input.
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", 900u32);
+        assert_eq!(
+            usage.get_missing(&rule_context.borrow()),
+            vec!["country", "tier"]
+        );
+    }
+
+    #[test]
+    fn test_nested_recording_is_part_of_outer_one() {
+        let inner = AllRule::new().on_execute(|this| {
+            let usage = KeyUsage::record(
+                &Engine::all_runner(),
+                this.get_rule_context(),
+                vec![AllRule::new().on_execute(|this| this.get_rule_context().set("inner", true))],
+            );
+            assert_eq!(usage.write_keys(), vec!["inner"]);
+        });
+
+        let usage = KeyUsage::record(&Engine::all_runner(), RuleContext::new(), vec![inner]);
+
+        assert_eq!(usage.write_keys(), vec!["inner"]);
+    }
+
+    #[test]
+    fn test_recorded_key_usage_sees_conditions_and_paths() {
+        let rule = || {
+            AllRule::new().on_condition(Condition::key_exists("vip").and(Condition::new(|ctx| {
+                ctx.get_path::<u32>("customer.age").is_some()
+            })))
+        };
+
+        let usage = KeyUsage::record(&Engine::all_runner(), RuleContext::new(), vec![rule()]);
+        assert_eq!(usage.read_keys(), vec!["vip"]);
+
+        let mut customer = ContextObject::new();
+        customer.insert("age", 30u32);
+        let mut rule_context = RuleContext::new();
+        rule_context.set("vip", true);
+        rule_context.set("customer", customer);
+        let usage = KeyUsage::record(&Engine::all_runner(), rule_context, vec![rule()]);
+        assert_eq!(usage.read_keys(), vec!["customer", "vip"]);
+    }
+
+    #[cfg(feature = "expr")]
+    #[test]
+    fn test_recorded_key_usage_sees_expressions() {
+        let expr = dredd_rs::expr::Expr::parse("age > 18").unwrap();
+        let rule = AllRule::new().on_eval(move |ctx| expr.eval_view(ctx).unwrap_or(false));
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 42i64);
+
+        let usage = KeyUsage::record(&Engine::all_runner(), rule_context, vec![rule]);
+
+        assert_eq!(usage.read_keys(), vec!["age"]);
+        assert_eq!(usage.input_keys(), vec!["age"]);
+    }
+
+    #[test]
+    fn test_merged_inputs_exclude_keys_written_before() {
+        let writer = AllRule::new().on_execute(|this| this.get_rule_context().set("tier", "gold"));
+        let reader = AllRule::new().on_eval(|ctx| ctx.get::<&str>("tier").is_some());
+
+        let mut usage = KeyUsage::record(&Engine::all_runner(), RuleContext::new(), vec![writer]);
+        let read = KeyUsage::record(&Engine::all_runner(), RuleContext::new(), vec![reader]);
+        assert_eq!(read.input_keys(), vec!["tier"]);
+        usage.merge(&read);
+
+        assert_eq!(usage.read_keys(), vec!["tier"]);
+        assert_eq!(usage.write_keys(), vec!["tier"]);
+        assert!(usage.input_keys().is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    type RuleContextWrapper = std::rc::Rc<std::cell::RefCell<RuleContext>>;
+
+    fn layers() -> (RuleContextWrapper, RuleContextWrapper, RuleContextWrapper) {
+        let mut request = RuleContext::new();
+        request.set("total", 120i64);
+
+        let mut tenant = RuleContext::new();
+        tenant.set("limit", 100i64);
+        tenant.set("tags", vec!["tenant"]);
+
+        let mut global = RuleContext::new();
+        global.set("limit", 50i64);
+        global.set("currency", "EUR".to_string());
+        (request, tenant, global)
+    }
+
+    #[test]
+    fn test_reads_fall_through_layers() {
+        let (request, tenant, global) = layers();
+        let rule_context = LayeredContext::new(vec![request.clone(), tenant, global]);
+
+        assert!(std::rc::Rc::ptr_eq(&rule_context, &request));
+        assert_eq!(rule_context.borrow().get_layers().len(), 2);
+        assert_eq!(*rule_context.get::<i64>("total").unwrap(), 120);
+        assert_eq!(*rule_context.get::<i64>("limit").unwrap(), 100);
+        assert_eq!(*rule_context.get::<String>("currency").unwrap(), "EUR");
+        assert!(rule_context.get::<i64>("missing").is_none());
+    }
+
+    #[test]
+    fn test_writes_go_to_the_top_layer() {
+        let (request, tenant, global) = layers();
+        let rule_context = LayeredContext::new(vec![request, tenant.clone(), global.clone()]);
+
+        let rule = AllRule::new()
+            .on_condition(Condition::key_exists("currency"))
+            .on_execute(|this| {
+                let mut rule_context = this.get_rule_context();
+                rule_context.set("limit", 200i64);
+                *rule_context.entry("total").or_insert(0i64) += 1;
+                rule_context.push_to_list("tags", "request");
+            });
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<i64>("limit").unwrap(), 200);
+        assert_eq!(*rule_context.get::<i64>("total").unwrap(), 121);
+        assert_eq!(
+            *rule_context.get_list::<&str>("tags").unwrap(),
+            vec!["tenant", "request"]
+        );
+
+        assert_eq!(*tenant.get::<i64>("limit").unwrap(), 100);
+        assert_eq!(*tenant.get_list::<&str>("tags").unwrap(), vec!["tenant"]);
+        assert_eq!(*global.get::<i64>("limit").unwrap(), 50);
+    }
+
+    #[test]
+    fn test_entry_copies_lower_values_up() {
+        let (request, tenant, global) = layers();
+        let rule_context = LayeredContext::new(vec![request, tenant.clone(), global]);
+
+        *rule_context.entry("limit").or_insert(0i64) *= 2;
+
+        assert_eq!(*rule_context.get::<i64>("limit").unwrap(), 200);
+        assert_eq!(*tenant.get::<i64>("limit").unwrap(), 100);
+    }
+
+    #[test]
+    fn test_layers_are_seen_by_dry_runs() {
+        let (request, tenant, global) = layers();
+        let rule_context = LayeredContext::new(vec![request, tenant, global]);
+
+        let rule = AllRule::new()
+            .with_name("eur")
+            .on_condition(Condition::key_equals("currency", "EUR".to_string()));
+        let report = Engine::all_runner().dry_run(rule_context, vec![rule]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["eur"]);
+    }
+
+    #[test]
+    fn test_empty_and_self_layers() {
+        let rule_context = LayeredContext::new(vec![]);
+        assert!(rule_context.borrow().get_layers().is_empty());
+
+        let (request, ..) = layers();
+        let rule_context = LayeredContext::new(vec![request.clone(), request]);
+        assert!(rule_context.borrow().get_layers().is_empty());
+    }
+
+    #[test]
+    fn test_layers_in_a_cycle() {
+        let (request, tenant, global) = layers();
+        LayeredContext::new(vec![tenant.clone(), global.clone()]);
+        LayeredContext::new(vec![global.clone(), tenant.clone()]);
+        let rule_context = LayeredContext::new(vec![request, tenant.clone()]);
+
+        assert_eq!(*rule_context.get::<String>("currency").unwrap(), "EUR");
+        assert!(rule_context.get::<i64>("missing").is_none());
+        assert!(global.get::<i64>("missing").is_none());
+        assert!(tenant.borrow_mut().get::<i64>("missing").is_none());
+        let shared = SharedRuleContext::from_context(&rule_context.borrow()).unwrap();
+        assert_eq!(*shared.get::<i64>("limit").unwrap(), 100);
+    }
+
+    #[test]
+    fn test_layers_are_appended() {
+        let (request, tenant, global) = layers();
+        let mut scope = RuleContext::child_scope(&request);
+        let rule_context = LayeredContext::new(vec![scope.clone(), tenant, global]);
+        LayeredContext::new(vec![scope.clone(), request.clone()]);
+
+        assert_eq!(rule_context.borrow().get_layers().len(), 3);
+        assert_eq!(*rule_context.get::<i64>("total").unwrap(), 120);
+        assert_eq!(*rule_context.get::<i64>("limit").unwrap(), 100);
+
+        scope.set("discount", 10i64);
+        scope.borrow_mut().merge_scope();
+        assert_eq!(*request.get::<i64>("discount").unwrap(), 10);
+    }
+}
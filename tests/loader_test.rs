@@ -0,0 +1,447 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dredd_rs::loader::{
+        self, ActiveOnly, AnyState, CallbackRegistry, LoadedRules, LoaderError, RuleDefinition,
+        RuleSetDefinition, RuleSetState,
+    };
+    use dredd_rs::rule::*;
+
+    fn registry() -> CallbackRegistry {
+        let mut registry = CallbackRegistry::new();
+        registry
+            .condition("always", |_| true)
+            .condition("never", |_| false)
+            .action("mark_1", |ctx| ctx.set("rule1", true))
+            .action("mark_2", |ctx| ctx.set("rule2", true))
+            .action("mark_3", |ctx| ctx.set("rule3", true));
+        registry
+    }
+
+    #[test]
+    fn test_loader_best_first_tree() {
+        let json = r#"{
+            "type": "best_first",
+            "state": "active",
+            "rules": [
+                {
+                    "name": "rule1",
+                    "description": "Root rule",
+                    "eval": "always",
+                    "execute": "mark_1",
+                    "children": [
+                        { "eval": "never", "execute": "mark_2" },
+                        { "eval": "always", "execute": "mark_3" }
+                    ]
+                }
+            ]
+        }"#;
+
+        let rules = loader::from_json(json, &registry()).unwrap();
+        let LoadedRules::BestFirst(tree) = &rules else {
+            panic!("expected a best first tree");
+        };
+        assert_eq!(tree[0].borrow().get_name(), Some("rule1"));
+        assert_eq!(tree[0].borrow().get_description(), Some("Root rule"));
+
+        let rule_context = RuleContext::new();
+        rules.run(rule_context.clone());
+
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+    }
+
+    #[test]
+    fn test_loader_chain_tree() {
+        let json = r#"{
+            "type": "chain",
+            "state": "active",
+            "rules": [
+                {
+                    "pre_execute": "mark_1",
+                    "children": [
+                        { "post_execute": "mark_2", "children": [{ "eval": "never", "execute": "mark_3" }] }
+                    ]
+                }
+            ]
+        }"#;
+
+        let rules = loader::from_json(json, &registry()).unwrap();
+
+        let rule_context = RuleContext::new();
+        rules.run(rule_context.clone());
+
+        assert!(*rule_context.get::<bool>("rule1").unwrap_or(Rc::new(false)));
+        assert!(*rule_context.get::<bool>("rule2").unwrap_or(Rc::new(false)));
+        assert!(!*rule_context.get::<bool>("rule3").unwrap_or(Rc::new(false)));
+    }
+
+    #[test]
+    fn test_loader_should_reject_chain_siblings() {
+        let json = r#"{ "type": "chain", "state": "active", "rules": [{ "children": [{}, {}] }] }"#;
+
+        let result = loader::from_json(json, &registry());
+
+        assert!(matches!(result, Err(LoaderError::ChainSiblings)));
+    }
+
+    #[test]
+    fn test_loader_should_reject_unknown_identifiers() {
+        let json = r#"{ "type": "all", "state": "active", "rules": [{ "eval": "missing" }] }"#;
+        let result = loader::from_json(json, &registry());
+        assert!(matches!(result, Err(LoaderError::UnknownCondition(name)) if name == "missing"));
+
+        let json = r#"{ "type": "all", "state": "active", "rules": [{ "execute": "missing" }] }"#;
+        let result = loader::from_json(json, &registry());
+        assert!(matches!(result, Err(LoaderError::UnknownAction(name)) if name == "missing"));
+    }
+
+    #[test]
+    fn test_loader_should_reject_invalid_json() {
+        let result = loader::from_json(r#"{ "type": "unknown" }"#, &registry());
+
+        assert!(matches!(result, Err(LoaderError::Json(_))));
+    }
+
+    #[test]
+    fn test_loader_guard_only_loads_active_rule_sets() {
+        let json = r#"{ "type": "all", "rules": [{ "execute": "mark_1" }] }"#;
+        let mut definition: RuleSetDefinition = serde_json::from_str(json).unwrap();
+        assert_eq!(definition.state, RuleSetState::Draft);
+
+        for state in [RuleSetState::Review, RuleSetState::Active] {
+            let result =
+                loader::from_json_guarded(&definition.to_json().unwrap(), &registry(), &ActiveOnly);
+            assert!(matches!(result, Err(LoaderError::NotActivated(_))));
+            definition.transition_to(state).unwrap();
+        }
+
+        let rules =
+            loader::from_json_guarded(&definition.to_json().unwrap(), &registry(), &ActiveOnly)
+                .unwrap();
+        let rule_context = RuleContext::new();
+        rules.run(rule_context.clone());
+        assert!(*rule_context.get::<bool>("rule1").unwrap());
+
+        definition.transition_to(RuleSetState::Retired).unwrap();
+        let result =
+            loader::from_json_guarded(&definition.to_json().unwrap(), &registry(), &ActiveOnly);
+        assert!(
+            matches!(result, Err(LoaderError::NotActivated(reason)) if reason == "rule set is retired, not active")
+        );
+    }
+
+    #[test]
+    fn test_loader_only_loads_active_rule_sets_by_default() {
+        let json = r#"{ "type": "all", "rules": [{ "execute": "mark_1" }] }"#;
+
+        let result = loader::from_json(json, &registry());
+        assert!(
+            matches!(result, Err(LoaderError::NotActivated(reason)) if reason == "rule set is draft, not active")
+        );
+
+        let rules = loader::from_json_guarded(json, &registry(), &AnyState).unwrap();
+        let rule_context = RuleContext::new();
+        rules.run(rule_context.clone());
+        assert!(*rule_context.get::<bool>("rule1").unwrap());
+    }
+
+    #[test]
+    fn test_loader_rejects_skipping_review() {
+        let mut definition: RuleSetDefinition =
+            serde_json::from_str(r#"{ "type": "all", "rules": [] }"#).unwrap();
+
+        let result = definition.transition_to(RuleSetState::Active);
+
+        assert!(matches!(
+            result,
+            Err(LoaderError::InvalidTransition {
+                from: RuleSetState::Draft,
+                to: RuleSetState::Active
+            })
+        ));
+        assert_eq!(definition.state, RuleSetState::Draft);
+    }
+
+    #[test]
+    fn test_loader_custom_guard() {
+        let guard = |definition: &RuleSetDefinition| {
+            if definition.rules.iter().all(|rule| rule.id.is_some()) {
+                Ok(())
+            } else {
+                Err("every rule needs an id".to_string())
+            }
+        };
+
+        let json = r#"{ "type": "all", "rules": [{ "id": "a" }, {}] }"#;
+        let result = loader::from_json_guarded(json, &registry(), &guard);
+        assert!(
+            matches!(result, Err(LoaderError::NotActivated(reason)) if reason == "every rule needs an id")
+        );
+
+        let json = r#"{ "type": "all", "rules": [{ "id": "a" }] }"#;
+        assert!(loader::from_json_guarded(json, &registry(), &guard).is_ok());
+    }
+
+    #[test]
+    fn test_loader_key_usage() {
+        let json = r#"{
+            "type": "all",
+            "state": "active",
+            "rules": [
+                {
+                    "reads": ["age"],
+                    "writes": ["rule1"],
+                    "execute": "mark_1",
+                    "children": [{ "reads": ["rule1", "country"], "writes": ["rule2"] }]
+                }
+            ]
+        }"#;
+
+        let rules = loader::from_json(json, &registry()).unwrap();
+        let usage = rules.get_key_usage();
+
+        assert_eq!(usage.read_keys(), vec!["age", "country", "rule1"]);
+        assert_eq!(usage.write_keys(), vec!["rule1", "rule2"]);
+        assert_eq!(usage.input_keys(), vec!["age", "country"]);
+    }
+
+    #[cfg(feature = "expr")]
+    #[test]
+    fn test_document_key_usage_comes_from_expressions() {
+        let rules = loader::from_document_json(
+            r#"{
+                "conditions": { "is_adult": "age >= 18 && country == 'BR' && age < 120" },
+                "actions": { "approve": { "approved": true } },
+                "rules": {
+                    "type": "all",
+                    "rules": [{ "reads": ["score"], "eval": "is_adult", "execute": "approve" }]
+                }
+            }"#,
+        )
+        .unwrap();
+        let usage = rules.get_key_usage();
+
+        assert_eq!(usage.read_keys(), vec!["age", "country", "score"]);
+        assert_eq!(usage.write_keys(), vec!["approved"]);
+        assert_eq!(usage.input_keys(), vec!["age", "country", "score"]);
+    }
+
+    #[test]
+    fn test_loader_content_ids() {
+        let json = r#"{
+            "type": "all",
+            "state": "active",
+            "rules": [
+                { "id": "explicit", "execute": "mark_1" },
+                { "name": "second", "execute": "mark_2" }
+            ]
+        }"#;
+        let renamed = json.replace("second", "renamed");
+        let edited = json.replace("mark_2", "mark_3");
+
+        let ids = |json: &str| {
+            let LoadedRules::All(rules) = loader::from_json(json, &registry()).unwrap() else {
+                panic!("expected an all tree");
+            };
+            rules
+                .iter()
+                .map(|rule| rule.borrow().get_id().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let original = ids(json);
+        assert_eq!(original[0], "explicit");
+        assert!(original[1].starts_with("rule-"));
+        assert_eq!(ids(&renamed), original);
+        assert_ne!(ids(&edited)[1], original[1]);
+    }
+
+    #[test]
+    fn test_content_ids_depend_on_place() {
+        let json = r#"{
+            "type": "all",
+            "state": "active",
+            "rules": [
+                {
+                    "eval": "always",
+                    "children": [{ "execute": "mark_1" }, { "execute": "mark_1" }]
+                },
+                { "execute": "mark_1" },
+                { "eval": "never", "children": [{ "execute": "mark_1" }] }
+            ]
+        }"#;
+        let edited = json.replacen(
+            r#"{ "execute": "mark_1" }]"#,
+            r#"{ "execute": "mark_2" }]"#,
+            1,
+        );
+
+        let ids = |json: &str| {
+            let LoadedRules::All(rules) = loader::from_json(json, &registry()).unwrap() else {
+                panic!("expected an all tree");
+            };
+            let mut ids = Vec::new();
+            let mut pending = rules;
+            while !pending.is_empty() {
+                let rule = pending.remove(0);
+                ids.push(rule.borrow().get_id().unwrap().to_string());
+                pending.splice(0..0, rule.borrow_mut().get_children());
+            }
+            ids
+        };
+
+        let original = ids(json);
+        let mut unique = original.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), original.len());
+
+        // Only the edited leaf gets a new id, its parent keeps its own.
+        let changed: Vec<_> = (0..original.len())
+            .filter(|index| ids(&edited)[*index] != original[*index])
+            .collect();
+        assert_eq!(changed, vec![2]);
+
+        // Callbacks are hashed by their role, not only by their identifier.
+        let child = RuleDefinition {
+            execute: Some("mark_1".to_string()),
+            ..Default::default()
+        };
+        let moved = RuleDefinition {
+            pre_execute: Some("mark_1".to_string()),
+            ..Default::default()
+        };
+        assert_ne!(child.get_content_id(), moved.get_content_id());
+        assert_eq!(child.get_content_id(), original[3]);
+    }
+
+    fn rule_set(json: &str) -> RuleSetDefinition {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn executes(rules: &RuleSetDefinition) -> Vec<&str> {
+        rules
+            .rules
+            .iter()
+            .map(|rule| rule.execute.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_union_keeps_own_rules_first() {
+        let base = rule_set(
+            r#"{ "type": "all", "state": "active", "rules": [
+                { "name": "a", "execute": "mark_1" },
+                { "id": "R-2", "execute": "mark_2" }
+            ] }"#,
+        );
+        let regional = rule_set(
+            r#"{ "type": "best_first", "rules": [
+                { "name": "a", "execute": "mark_3" },
+                { "id": "R-2", "execute": "mark_3" },
+                { "execute": "mark_3" },
+                { "execute": "mark_3" }
+            ] }"#,
+        );
+
+        let rules = base.union(regional);
+
+        assert_eq!(rules.kind, loader::RuleKind::All);
+        assert_eq!(rules.state, RuleSetState::Active);
+        // Unnamed rules without ids are matched by content.
+        assert_eq!(executes(&rules), vec!["mark_1", "mark_2", "mark_3"]);
+    }
+
+    #[test]
+    fn test_override_by_name_replaces_in_place() {
+        let base = rule_set(
+            r#"{ "type": "all", "rules": [
+                { "name": "a", "execute": "mark_1" },
+                { "name": "b", "execute": "mark_2" }
+            ] }"#,
+        );
+        let tenant = rule_set(
+            r#"{ "type": "all", "rules": [
+                { "name": "c", "execute": "mark_1" },
+                { "name": "a", "execute": "mark_3" }
+            ] }"#,
+        );
+
+        let rules = base.override_by_name(tenant);
+
+        let names: Vec<_> = rules
+            .rules
+            .iter()
+            .map(|rule| rule.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(executes(&rules), vec!["mark_3", "mark_2", "mark_1"]);
+    }
+
+    #[test]
+    fn test_exclude_tag_at_any_depth() {
+        let base = rule_set(
+            r#"{ "type": "all", "rules": [
+                { "name": "a", "execute": "mark_1", "children": [
+                    { "name": "old", "execute": "mark_2", "tags": ["deprecated"] }
+                ] },
+                { "name": "b", "execute": "mark_2", "tags": ["beta", "deprecated"] },
+                { "name": "c", "execute": "mark_3", "tags": ["beta"] }
+            ] }"#,
+        );
+
+        let rules = base.exclude_tag("deprecated");
+
+        assert_eq!(executes(&rules), vec!["mark_1", "mark_3"]);
+        assert!(rules.rules[0].children.is_empty());
+
+        let rule_context = RuleContext::new();
+        loader::from_definition(&rules, &registry())
+            .unwrap()
+            .run(rule_context.clone());
+        assert!(rule_context.get::<bool>("rule2").is_none());
+        assert!(rule_context.get::<bool>("rule3").is_some());
+    }
+
+    #[test]
+    fn test_tags_do_not_change_content_ids() {
+        let rule = RuleDefinition {
+            execute: Some("mark_1".to_string()),
+            ..Default::default()
+        };
+        let tagged = RuleDefinition {
+            tags: vec!["beta".to_string()],
+            ..rule.clone()
+        };
+
+        assert_eq!(rule.get_content_id(), tagged.get_content_id());
+    }
+
+    #[test]
+    fn test_loaded_rules_carry_their_tags() {
+        let json = r#"{
+            "type": "all",
+            "state": "active",
+            "rules": [
+                { "name": "vat", "tags": ["eu-only"], "execute": "mark_1" },
+                { "name": "sales tax", "tags": ["us-only"], "execute": "mark_2" }
+            ]
+        }"#;
+
+        let LoadedRules::All(rules) = loader::from_json(json, &registry()).unwrap() else {
+            panic!("expected all rules");
+        };
+        assert_eq!(rules[0].borrow().get_tags(), ["eu-only"]);
+
+        let rule_context = RuleContext::new();
+        Engine::execute_filtered(rule_context.clone(), rules, |meta| meta.has_tag("eu-only"));
+
+        assert!(rule_context.get::<bool>("rule1").is_some());
+        assert!(rule_context.get::<bool>("rule2").is_none());
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::{BTreeMap, HashMap},
+        rc::Rc,
+    };
+
+    use dredd_rs::rule::*;
+
+    type RuleContextWrapper = Rc<RefCell<RuleContext>>;
+
+    fn regions() -> HashMap<String, &'static str> {
+        HashMap::from([
+            ("PT".to_string(), "EMEA"),
+            ("DE".to_string(), "EMEA"),
+            ("BR".to_string(), "LATAM"),
+        ])
+    }
+
+    fn run<V: Clone + 'static>(
+        lookup: &Lookup<V>,
+        rule_context: RuleContextWrapper,
+    ) -> Result<(), RuleError> {
+        let rule = AllRule::new().on_execute(lookup.callback());
+        Engine::all_runner().try_run(rule_context, vec![rule])
+    }
+
+    #[test]
+    fn test_lookup_writes_value() {
+        let lookup = Lookup::new("{country}", regions(), "region");
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("country", "PT".to_string());
+
+        assert_eq!(run(&lookup, rule_context.clone()), Ok(()));
+        assert_eq!(*rule_context.get::<&str>("region").unwrap(), "EMEA");
+    }
+
+    #[test]
+    fn test_lookup_combines_keys() {
+        let limits = BTreeMap::from([
+            ("free/EU".to_string(), 100u32),
+            ("pro/EU".to_string(), 10_000),
+            ("pro/42".to_string(), 5_000),
+        ]);
+        let lookup = Lookup::new("{plan}/{zone}", limits, "limit");
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan", "pro");
+        rule_context.set("zone", "EU");
+        run(&lookup, rule_context.clone()).unwrap();
+        assert_eq!(*rule_context.get::<u32>("limit").unwrap(), 10_000);
+
+        rule_context.set("zone", 42i64);
+        run(&lookup, rule_context.clone()).unwrap();
+        assert_eq!(*rule_context.get::<u32>("limit").unwrap(), 5_000);
+
+        assert_eq!(lookup.get_inputs(), vec!["plan", "zone"]);
+        assert_eq!(lookup.get_key(), "{plan}/{zone}");
+        assert_eq!(lookup.get_output(), "limit");
+    }
+
+    #[test]
+    fn test_lookup_missing_entry() {
+        let lookup = Lookup::new("{country}", regions(), "region");
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("country", "JP");
+
+        assert_eq!(
+            run(&lookup, rule_context.clone()),
+            Err(RuleError::failed("no entry for `JP` in lookup table"))
+        );
+        assert!(rule_context.get::<&str>("region").is_none());
+
+        let lookup = lookup.with_default("ROW");
+        run(&lookup, rule_context.clone()).unwrap();
+        assert_eq!(*rule_context.get::<&str>("region").unwrap(), "ROW");
+    }
+
+    #[test]
+    fn test_lookup_invalid_input() {
+        let lookup = Lookup::new("{country}", regions(), "region").with_default("ROW");
+
+        assert_eq!(
+            run(&lookup, RuleContext::new()),
+            Err(RuleError::failed("missing lookup input `country`"))
+        );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("country", 1.5f64);
+        assert_eq!(
+            run(&lookup, rule_context),
+            Err(RuleError::failed(
+                "lookup input `country` is not a string or an integer"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_lookup_with_function_table() {
+        let queries = Rc::new(Cell::new(0));
+        let table = {
+            let queries = queries.clone();
+            move |key: &str| {
+                queries.set(queries.get() + 1);
+                match key {
+                    "down" => Err("connection refused".to_string()),
+                    "gold" => Ok(Some(0.2f64)),
+                    _ => Ok(None),
+                }
+            }
+        };
+        let lookup = Lookup::new("{tier}", table, "discount").with_default(0.0);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("tier", "gold");
+        run(&lookup, rule_context.clone()).unwrap();
+        assert_eq!(*rule_context.get::<f64>("discount").unwrap(), 0.2);
+
+        rule_context.set("tier", "down");
+        assert_eq!(
+            run(&lookup, rule_context.clone()),
+            Err(RuleError::failed("connection refused"))
+        );
+        assert_eq!(queries.get(), 2);
+    }
+
+    #[test]
+    fn test_lookup_literal_text_and_aliases() {
+        let table = HashMap::from([
+            ("country:PT".to_string(), "EMEA"),
+            ("{x".to_string(), "open"),
+        ]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.borrow_mut().deprecate_key("nation", "country");
+        rule_context.set("country", "PT");
+        run(
+            &Lookup::new("country:{nation}", table.clone(), "region"),
+            rule_context.clone(),
+        )
+        .unwrap();
+        assert_eq!(*rule_context.get::<&str>("region").unwrap(), "EMEA");
+
+        run(&Lookup::new("{x", table, "brace"), rule_context.clone()).unwrap();
+        assert_eq!(*rule_context.get::<&str>("brace").unwrap(), "open");
+    }
+}
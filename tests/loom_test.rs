@@ -0,0 +1,69 @@
+#![cfg(loom)]
+
+//! Model checks of `SharedRuleContext`, run with:
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom_test
+//! ```
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use loom::thread;
+
+    #[test]
+    fn test_concurrent_writes_are_all_visible() {
+        loom::model(|| {
+            let rule_context = SharedRuleContext::new();
+
+            let writer = {
+                let rule_context = rule_context.clone();
+                thread::spawn(move || rule_context.set("a", 1))
+            };
+            rule_context.set("b", 2);
+            writer.join().unwrap();
+
+            assert_eq!(*rule_context.get::<i32>("a").unwrap(), 1);
+            assert_eq!(*rule_context.get::<i32>("b").unwrap(), 2);
+        });
+    }
+
+    #[test]
+    fn test_reader_sees_old_or_new_value() {
+        loom::model(|| {
+            let rule_context = SharedRuleContext::new();
+            rule_context.set("value", 1);
+
+            let writer = {
+                let rule_context = rule_context.clone();
+                thread::spawn(move || rule_context.set("value", 2))
+            };
+            let read = *rule_context.get::<i32>("value").unwrap();
+            writer.join().unwrap();
+
+            assert!(read == 1 || read == 2);
+            assert_eq!(*rule_context.get::<i32>("value").unwrap(), 2);
+        });
+    }
+
+    #[test]
+    fn test_racing_writes_keep_one_value() {
+        loom::model(|| {
+            let rule_context = SharedRuleContext::new();
+
+            let writers: Vec<_> = [1, 2]
+                .into_iter()
+                .map(|value| {
+                    let rule_context = rule_context.clone();
+                    thread::spawn(move || rule_context.set("value", value))
+                })
+                .collect();
+            for writer in writers {
+                writer.join().unwrap();
+            }
+
+            let value = *rule_context.get::<i32>("value").unwrap();
+            assert!(value == 1 || value == 2);
+        });
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn pending(ctx: ContextView<'_>) -> bool {
+        *ctx.get::<u32>("pending").unwrap() > 0
+    }
+
+    fn drain() -> Rc<RefCell<AllRule>> {
+        AllRule::new()
+            .with_name("drain")
+            .on_execute(|this| {
+                let pending = *this.get_rule_context().get::<u32>("pending").unwrap();
+                this.get_rule_context().set("pending", pending - 1);
+            })
+            .add_child(AllRule::new().on_execute(|this| {
+                let drained = this
+                    .get_rule_context()
+                    .get::<u32>("drained")
+                    .map_or(0, |d| *d);
+                this.get_rule_context().set("drained", drained + 1);
+            }))
+    }
+
+    fn run(pending: u32, max_iterations: u32) -> (Rc<RefCell<RuleContext>>, RunReport) {
+        let rule = LoopRule::new(self::pending, drain(), max_iterations).wrap(
+            AllRule::new()
+                .with_name("batch")
+                .on_post_execute(|this| this.get_rule_context().set("post", true)),
+        );
+        let mut rule_context = RuleContext::new();
+        rule_context.set("pending", pending);
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![rule]);
+        (rule_context, report)
+    }
+
+    #[test]
+    fn test_loop_rule_fires_body_while_condition_holds() {
+        let (rule_context, report) = run(3, 10);
+
+        assert!(report.get_error().is_none());
+        assert_eq!(*rule_context.get::<u32>("drained").unwrap(), 3);
+        assert!(*rule_context.get::<bool>("post").unwrap());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["batch", "drain", "drain", "drain"]
+        );
+    }
+
+    #[test]
+    fn test_loop_rule_skips_body_when_condition_fails_first() {
+        let (rule_context, report) = run(0, 10);
+
+        assert!(report.get_error().is_none());
+        assert!(rule_context.get::<u32>("drained").is_none());
+    }
+
+    #[test]
+    fn test_loop_rule_fails_past_max_iterations() {
+        let (rule_context, report) = run(5, 2);
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("loop still running after 2 iterations").in_rule("batch"))
+        );
+        assert_eq!(*rule_context.get::<u32>("drained").unwrap(), 2);
+        assert!(rule_context.get::<bool>("post").is_none());
+    }
+
+    #[test]
+    fn test_loop_rule_exactly_max_iterations_succeeds() {
+        let (rule_context, report) = run(2, 2);
+
+        assert!(report.get_error().is_none());
+        assert_eq!(*rule_context.get::<u32>("drained").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_loop_rule_stops_on_body_failure() {
+        let body = AllRule::new().on_execute(|this| {
+            let calls = this
+                .get_rule_context()
+                .get::<u32>("calls")
+                .map_or(1, |c| *c + 1);
+            this.get_rule_context().set("calls", calls);
+            if calls == 2 {
+                this.get_rule_context()
+                    .fail(RuleError::failed("broken item"));
+            }
+        });
+        let rule = LoopRule::new(|_| true, body, 10).wrap(AllRule::new());
+
+        let rule_context = RuleContext::new();
+        let result = Engine::all_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::failed("broken item")));
+        assert_eq!(*rule_context.get::<u32>("calls").unwrap(), 2);
+    }
+}
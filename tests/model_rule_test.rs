@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn score(features: &[f32]) -> Result<Vec<f32>, String> {
+        Ok(vec![features.iter().sum(), features.len() as f32])
+    }
+
+    #[test]
+    fn test_model_rule_writes_prediction_before_children() {
+        let rule = BestFirstRule::new()
+            .on_execute(|this| {
+                let score = *this.get_rule_context().get::<f32>("score").unwrap();
+                this.get_rule_context().set("seen_by_parent", score);
+            })
+            .add_children(vec![
+                BestFirstRule::new()
+                    .with_name("high")
+                    .on_eval(|ctx| *ctx.get::<f32>("score").unwrap() > 10.0),
+                BestFirstRule::new().with_name("low"),
+            ]);
+        let rule = ModelRule::new(score)
+            .with_inputs(&["amount", "items", "member"])
+            .with_outputs(&["score", "count"])
+            .wrap(rule);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("amount", 9.5f64);
+        rule_context.set("items", 2i64);
+        rule_context.set("member", false);
+
+        let report = Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule]);
+        assert_eq!(*rule_context.get::<f32>("score").unwrap(), 11.5);
+        assert_eq!(*rule_context.get::<f32>("count").unwrap(), 3.0);
+        assert_eq!(*rule_context.get::<f32>("seen_by_parent").unwrap(), 11.5);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["high"]);
+    }
+
+    #[test]
+    fn test_model_rule_failures() {
+        let run = |model: ModelRule, rule_context| {
+            let rule = model.wrap(
+                ChainRule::new().on_execute(|this| this.get_rule_context().set("executed", true)),
+            );
+            Engine::chain_runner().try_run(rule_context, vec![rule])
+        };
+
+        let model = ModelRule::new(score)
+            .with_inputs(&["amount"])
+            .with_outputs(&["score"]);
+        let rule_context = RuleContext::new();
+        assert_eq!(
+            run(model.clone(), rule_context.clone()),
+            Err(RuleError::failed("missing model input `amount`"))
+        );
+        assert!(rule_context.get::<bool>("executed").is_none());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("amount", "ten");
+        assert_eq!(
+            run(model.clone(), rule_context),
+            Err(RuleError::failed("model input `amount` is not a number"))
+        );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("amount", 10u8);
+        let model = model.with_outputs(&["a", "b", "c"]);
+        assert_eq!(
+            run(model, rule_context.clone()),
+            Err(RuleError::failed(
+                "the model predicted 2 values for 3 outputs"
+            ))
+        );
+
+        let failing = |_: &[f32]| Err("model unavailable".to_string());
+        assert_eq!(
+            run(ModelRule::new(failing), rule_context),
+            Err(RuleError::failed("model unavailable"))
+        );
+    }
+}
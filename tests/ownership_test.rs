@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dredd_rs::ownership::OwnershipReport;
+    use dredd_rs::rule::*;
+
+    fn rules() -> Vec<std::rc::Rc<std::cell::RefCell<BestFirstRule>>> {
+        let root = BestFirstRule::new()
+            .with_owner("ana")
+            .with_team("checkout")
+            .on_execute(|_| std::thread::sleep(Duration::from_millis(2)))
+            .add_children(vec![
+                BestFirstRule::new()
+                    .with_owner("bo")
+                    .with_team("fraud")
+                    .on_eval(|_| false),
+                BestFirstRule::new()
+                    .with_team("fraud")
+                    .on_execute(|this| this.get_rule_context().fail(RuleError::failed("timeout"))),
+            ]);
+        vec![root]
+    }
+
+    #[test]
+    fn test_ownership_report_by_team_and_owner() {
+        let mut report = OwnershipReport::new();
+        for _ in 0..2 {
+            let run = Engine::best_first_runner().run_with_report(RuleContext::new(), rules());
+            assert!(run.get_error().is_some());
+            report.record(run.get_trace());
+        }
+
+        assert_eq!(report.get_runs(), 2);
+
+        let checkout = report.get_team("checkout").unwrap();
+        assert_eq!(checkout.get_fires(), 2);
+        assert_eq!(checkout.get_executions(), 2);
+        assert_eq!(checkout.get_failures(), 0);
+        assert!(checkout.get_mean_duration() >= Duration::from_millis(2));
+
+        let fraud = report.get_team("fraud").unwrap();
+        assert_eq!(fraud.get_fires(), 4);
+        assert_eq!(fraud.get_executions(), 2);
+        assert_eq!(fraud.get_failures(), 2);
+        assert!(fraud.get_duration() < checkout.get_duration());
+
+        let owners: Vec<_> = report
+            .get_by_owner()
+            .iter()
+            .map(|stats| stats.get_owner())
+            .collect();
+        assert_eq!(owners, vec![None, Some("ana"), Some("bo")]);
+        assert_eq!(report.get_owner("bo").unwrap().get_executions(), 0);
+        assert_eq!(report.get_by_owner()[0].get_failures(), 2);
+        assert!(report.get_team("payments").is_none());
+    }
+
+    #[test]
+    fn test_ownership_report_display() {
+        let mut report = OwnershipReport::new();
+        let run = Engine::best_first_runner().run_with_report(RuleContext::new(), rules());
+        report.record(run.get_trace());
+
+        let output = report.to_string();
+        assert!(output.contains("checkout"));
+        assert!(output.contains("<none>"));
+    }
+}
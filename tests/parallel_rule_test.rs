@@ -0,0 +1,169 @@
+#![cfg(feature = "rayon")]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use dredd_rs::rule::*;
+    use dredd_rs::testing::assert_parallel_deterministic;
+
+    #[test]
+    fn test_parallel_rule_context() {
+        let mut rule = ParallelRule::new();
+        let mut rule2 = ParallelRule::new();
+
+        rule.on_eval(|this| *this.get_rule_context().get::<bool>("start").unwrap())
+            .on_pre_execute(|this| {
+                this.get_rule_context().set("pre_execute_1", true);
+            })
+            .on_execute(|this| {
+                this.get_rule_context().set("execute_1", true);
+            })
+            .on_post_execute(|this| {
+                this.get_rule_context().set("post_execute_1", true);
+            })
+            .add_child(
+                rule2
+                    .on_eval(|this| *this.get_rule_context().get::<bool>("execute_1").unwrap())
+                    .on_execute(|this| {
+                        this.get_rule_context().set("execute_2", true);
+                    }),
+            );
+
+        let rule_context = SharedRuleContext::new();
+        rule_context.set("start", true);
+
+        Engine::parallel_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("pre_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("post_execute_1").unwrap());
+        assert!(*rule_context.get::<bool>("execute_2").unwrap());
+    }
+
+    #[test]
+    fn test_parallel_rule_should_run_every_sibling_on_eval_true() {
+        let rules = (0..64)
+            .map(|i| {
+                ParallelRule::new()
+                    .on_eval(move |_| i % 2 == 0)
+                    .on_execute(move |this| this.get_rule_context().set(KEYS[i], i))
+            })
+            .collect();
+
+        let rule_context = SharedRuleContext::new();
+
+        Engine::parallel_runner().run(rule_context.clone(), rules);
+
+        for (i, key) in KEYS.iter().enumerate() {
+            assert_eq!(rule_context.get::<usize>(key).is_some(), i % 2 == 0);
+        }
+    }
+
+    #[test]
+    fn test_parallel_rule_siblings_should_not_see_each_other_writes() {
+        let mut rule = ParallelRule::new();
+        rule.on_execute(|this| this.get_rule_context().set("rule1", true));
+
+        let mut rule2 = ParallelRule::new();
+        rule2.on_execute(|this| {
+            let seen = this.get_rule_context().get::<bool>("rule1").is_some();
+            this.get_rule_context().set("rule2_saw_rule1", seen);
+        });
+
+        let rule_context = SharedRuleContext::new();
+
+        Engine::parallel_runner().run(rule_context.clone(), vec![rule, rule2]);
+
+        assert!(*rule_context.get::<bool>("rule1").unwrap());
+        assert!(!*rule_context.get::<bool>("rule2_saw_rule1").unwrap());
+    }
+
+    #[test]
+    fn test_parallel_rule_later_sibling_should_win_on_conflicting_writes() {
+        let rules = (0..16usize)
+            .map(|i| {
+                ParallelRule::new().on_execute(move |this| this.get_rule_context().set("winner", i))
+            })
+            .collect();
+
+        let rule_context = SharedRuleContext::new();
+        rule_context.set("winner", usize::MAX);
+
+        Engine::parallel_runner().run(rule_context.clone(), rules);
+
+        assert_eq!(*rule_context.get::<usize>("winner").unwrap(), 15);
+    }
+
+    #[test]
+    fn test_parallel_rule_set_is_deterministic() {
+        assert_parallel_deterministic(
+            20,
+            || {
+                let rules = (0..8usize)
+                    .map(|i| {
+                        ParallelRule::new().on_execute(move |this| {
+                            let seen = this.get_rule_context().get::<usize>("total").is_some();
+                            this.get_rule_context().set("total", i);
+                            this.get_rule_context().set(KEYS[i], seen);
+                        })
+                    })
+                    .collect();
+                (SharedRuleContext::new(), rules)
+            },
+            |rule_context| {
+                let seen: Vec<_> = KEYS[..8]
+                    .iter()
+                    .map(|key| *rule_context.get::<bool>(key).unwrap())
+                    .collect();
+                (*rule_context.get::<usize>("total").unwrap(), seen)
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from the first one")]
+    fn test_parallel_rule_set_sharing_state_outside_context_is_not_deterministic() {
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        assert_parallel_deterministic(
+            2,
+            || {
+                let runs = runs.clone();
+                let rule = ParallelRule::new().on_execute(move |this| {
+                    let run = runs.fetch_add(1, Ordering::SeqCst);
+                    this.get_rule_context().set("run", run);
+                });
+                (SharedRuleContext::new(), vec![rule])
+            },
+            |rule_context| *rule_context.get::<usize>("run").unwrap(),
+        );
+    }
+
+    const KEYS: [&str; 64] = [
+        "k0", "k1", "k2", "k3", "k4", "k5", "k6", "k7", "k8", "k9", "k10", "k11", "k12", "k13",
+        "k14", "k15", "k16", "k17", "k18", "k19", "k20", "k21", "k22", "k23", "k24", "k25", "k26",
+        "k27", "k28", "k29", "k30", "k31", "k32", "k33", "k34", "k35", "k36", "k37", "k38", "k39",
+        "k40", "k41", "k42", "k43", "k44", "k45", "k46", "k47", "k48", "k49", "k50", "k51", "k52",
+        "k53", "k54", "k55", "k56", "k57", "k58", "k59", "k60", "k61", "k62", "k63",
+    ];
+
+    #[test]
+    fn test_parallel_rule_disabled() {
+        let rule = ParallelRule::new().on_execute(|this| {
+            this.get_rule_context().set("executed", true);
+        });
+        rule.lock().unwrap().get_metadata().get_handle().disable();
+
+        let rule_context = SharedRuleContext::new();
+        Engine::parallel_runner().run(rule_context.clone(), vec![rule.clone()]);
+        assert!(rule_context.get::<bool>("executed").is_none());
+
+        rule.lock().unwrap().get_metadata().get_handle().enable();
+        Engine::parallel_runner().run(rule_context.clone(), vec![rule]);
+        assert!(*rule_context.get::<bool>("executed").unwrap());
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::context_keys;
+    use dredd_rs::effects::{Effect, EffectKind, Transport, TransportRegistry};
+    use dredd_rs::preflight::CheckKind;
+    use dredd_rs::rule::*;
+
+    context_keys! {
+        mod checkout {
+            AGE: i64 = "age";
+            APPROVED: bool = "approved";
+        }
+    }
+
+    struct Smtp {
+        reachable: bool,
+    }
+
+    impl Transport for Smtp {
+        fn deliver(&self, _: &Effect) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn ping(&self) -> Result<(), String> {
+            match self.reachable {
+                true => Ok(()),
+                false => Err("relay unreachable".to_string()),
+            }
+        }
+    }
+
+    fn registry() -> RuleRegistry {
+        let mut registry = RuleRegistry::new();
+        registry.register(
+            "checkout",
+            Engine::all_runner(),
+            vec![AllRule::new()
+                .with_name("adult_check")
+                .with_reads(&["age"])
+                .with_writes(&["approved"])],
+        );
+        registry
+    }
+
+    #[test]
+    fn test_preflight_ready() {
+        let registry = registry();
+        let transports = TransportRegistry::new()
+            .with_transport(EffectKind::Email, Smtp { reachable: true })
+            .with_transport(EffectKind::Sms, |_: &Effect| Ok(()));
+
+        let report = registry
+            .preflight()
+            .with_keys(checkout::KEYS)
+            .resolver("customers", || Ok(()))
+            .transports(&transports)
+            .run();
+
+        assert!(report.is_ready());
+        assert_eq!(report.to_string(), "ready\n");
+        let checks: Vec<_> = report
+            .get_checks()
+            .iter()
+            .map(|check| (check.get_kind(), check.get_name()))
+            .collect();
+        assert_eq!(
+            checks,
+            [
+                (CheckKind::Schema, "checkout"),
+                (CheckKind::Resolver, "customers"),
+                (CheckKind::Transport, "email"),
+                (CheckKind::Transport, "sms"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preflight_reports_every_failure() {
+        let mut registry = registry();
+        registry.register(
+            "pricing",
+            Engine::all_runner(),
+            vec![AllRule::new()
+                .with_id("discount")
+                .with_reads(&["total", "age"])
+                .with_writes(&["discount"])],
+        );
+        let transports =
+            TransportRegistry::new().with_transport(EffectKind::Email, Smtp { reachable: false });
+
+        let report = registry
+            .preflight()
+            .with_keys(checkout::KEYS)
+            .transports(&transports)
+            .run();
+
+        assert!(!report.is_ready());
+        assert!(report.get_checks()[0].is_passed());
+        assert_eq!(
+            report.get_failures()[0].get_error(),
+            Some("`discount` reads undeclared key `total`; `discount` writes undeclared key `discount`")
+        );
+        assert_eq!(
+            report.get_failures()[1].to_string(),
+            "failed transport `email`: relay unreachable"
+        );
+    }
+
+    #[test]
+    fn test_preflight_without_keys_skips_schema() {
+        let report = registry().preflight().run();
+
+        assert!(report.is_ready());
+        assert!(report.get_checks().is_empty());
+    }
+
+    #[cfg(feature = "expr")]
+    #[test]
+    fn test_preflight_compiles_expressions() {
+        let report = registry()
+            .preflight()
+            .expression("adult", "age >= 18")
+            .expression("broken", "age >=")
+            .run();
+
+        let failures = report.get_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].get_kind(), CheckKind::Expression);
+        assert_eq!(failures[0].get_name(), "broken");
+    }
+
+    #[cfg(feature = "rhai")]
+    #[test]
+    fn test_preflight_compiles_scripts() {
+        let report = registry()
+            .preflight()
+            .script("discount", "ctx.discount = 0.1;")
+            .script("broken", "ctx.discount = ;")
+            .run();
+
+        let failures = report.get_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].get_kind(), CheckKind::Script);
+        assert_eq!(failures[0].get_name(), "broken");
+    }
+}
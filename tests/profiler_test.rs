@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread, time::Duration};
+
+    use dredd_rs::profiler::Profiler;
+    use dredd_rs::rule::*;
+
+    fn sleep_ms(ms: u64) -> impl Fn(&mut AllRule) + 'static {
+        move |_| thread::sleep(Duration::from_millis(ms))
+    }
+
+    fn rules() -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        vec![
+            AllRule::new()
+                .with_name("root")
+                .on_execute(sleep_ms(2))
+                .add_children(vec![
+                    AllRule::new().with_name("a;b").on_execute(sleep_ms(5)),
+                    AllRule::new().with_id("R-2").on_execute(sleep_ms(1)),
+                ]),
+            AllRule::new(),
+        ]
+    }
+
+    fn parse(folded: &str) -> Vec<(&str, u128)> {
+        folded
+            .lines()
+            .map(|line| {
+                let (path, count) = line.rsplit_once(' ').unwrap();
+                (path, count.parse().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trace_to_folded_stacks() {
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), rules());
+        let folded = report.get_trace().to_folded_stacks();
+        let stacks = parse(&folded);
+
+        let paths: Vec<_> = stacks.iter().map(|(path, _)| *path).collect();
+        assert_eq!(paths, vec!["<unnamed>", "root", "root;R-2", "root;a:b"]);
+
+        let micros = |path| stacks.iter().find(|(p, _)| *p == path).unwrap().1;
+        assert!(micros("root") >= 2_000);
+        assert!(micros("root") < micros("root;a:b"));
+        assert!(micros("root;a:b") >= 5_000);
+        assert!(micros("root;R-2") >= 1_000);
+    }
+
+    #[test]
+    fn test_profiler_samples_and_aggregates_runs() {
+        let mut profiler = Profiler::new().with_sample_every(2);
+
+        for _ in 0..4 {
+            profiler.run(&Engine::all_runner(), RuleContext::new(), rules());
+        }
+
+        assert_eq!(profiler.get_runs(), 4);
+        assert_eq!(profiler.get_sampled_runs(), 2);
+
+        let folded = profiler.to_folded();
+        let stacks: HashMap<_, _> = parse(&folded).into_iter().collect();
+        assert!(stacks["root;a:b"] >= 10_000);
+
+        let mut written = Vec::new();
+        profiler.write_folded(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), profiler.to_folded());
+
+        profiler.reset();
+        assert!(profiler.to_folded().is_empty());
+    }
+
+    #[test]
+    fn test_folded_stacks_of_nested_runs() {
+        let folded = std::rc::Rc::new(std::cell::RefCell::new(String::new()));
+        let rule = AllRule::new().with_name("outer").on_execute({
+            let folded = folded.clone();
+            move |this| {
+                let nested = vec![
+                    AllRule::new()
+                        .with_name("a")
+                        .add_child(AllRule::new().with_name("b")),
+                    AllRule::new().with_name("c"),
+                ];
+                let report = Engine::all_runner().run_with_report(this.get_rule_context(), nested);
+                *folded.borrow_mut() = report.get_trace().to_folded_stacks();
+            }
+        });
+
+        // The outer run collects a trace too, in which the nested run is deeper.
+        Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+
+        let folded = folded.borrow();
+        let paths: Vec<_> = parse(&folded).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec!["a", "a;b", "c"]);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn failing_when(key: &'static str) -> impl Fn(&mut AllRule) + 'static {
+        move |this| {
+            if this.get_rule_context().get::<bool>(key).is_some() {
+                this.get_rule_context().fail(RuleError::failed(key));
+            }
+        }
+    }
+
+    fn fire(rule: &Wrapper<AllRule>, fail: bool) -> RunReport {
+        let mut rule_context = RuleContext::new();
+        if fail {
+            rule_context.set("fail", true);
+        }
+        Engine::all_runner().run_with_report(rule_context, vec![rule.clone()])
+    }
+
+    type Wrapper<T> = std::rc::Rc<std::cell::RefCell<T>>;
+
+    #[test]
+    fn test_quarantine_after_error_rate_exceeded() {
+        let quarantine = Quarantine::new(4, 0.5);
+        let rule = quarantine.wrap(
+            AllRule::new()
+                .with_id("pricing")
+                .on_execute(failing_when("fail")),
+        );
+
+        fire(&rule, true);
+        fire(&rule, false);
+        fire(&rule, true);
+        assert!(!quarantine.is_quarantined("pricing"));
+        assert_eq!(quarantine.get_error_rate("pricing"), Some(2.0 / 3.0));
+
+        fire(&rule, false);
+        assert!(!quarantine.is_quarantined("pricing"));
+
+        // The first failure leaves the window.
+        fire(&rule, true);
+        assert!(!quarantine.is_quarantined("pricing"));
+
+        fire(&rule, true);
+        assert_eq!(quarantine.get_quarantined(), vec!["pricing"]);
+    }
+
+    #[test]
+    fn test_quarantined_rule_is_skipped_and_flagged() {
+        let quarantine = Quarantine::new(1, 0.0);
+        let rule = quarantine.wrap(
+            AllRule::new()
+                .with_name("pricing")
+                .on_execute(failing_when("fail"))
+                .add_child(
+                    AllRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+                ),
+        );
+
+        assert!(fire(&rule, true).get_error().is_some());
+        assert!(quarantine.is_quarantined("pricing"));
+
+        let report = fire(&rule, true);
+        assert!(report.get_error().is_none());
+        assert!(report.get_trace().get_executed().is_empty());
+        let skipped = report.get_trace().get_skipped();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].get_name(), Some("pricing"));
+        assert_eq!(skipped[0].get_skip_reason(), Some("quarantined"));
+    }
+
+    #[test]
+    fn test_quarantine_release_starts_a_new_window() {
+        let quarantine = Quarantine::new(2, 0.5).with_min_samples(1);
+        let rule = quarantine.wrap(AllRule::new().with_id("pricing").on_eval(|ctx| {
+            ctx.fail(RuleError::failed("down"));
+            true
+        }));
+
+        fire(&rule, false);
+        assert!(quarantine.is_quarantined("pricing"));
+
+        assert!(quarantine.release("pricing"));
+        assert!(!quarantine.release("pricing"));
+        assert_eq!(quarantine.get_error_rate("pricing"), None);
+        assert!(fire(&rule, false).get_error().is_some());
+    }
+
+    #[test]
+    fn test_quarantine_ignores_failures_of_children() {
+        let quarantine = Quarantine::new(1, 0.0);
+        let rule = quarantine.wrap(
+            AllRule::new()
+                .with_id("parent")
+                .add_child(AllRule::new().on_execute(failing_when("fail"))),
+        );
+
+        assert!(fire(&rule, true).get_error().is_some());
+        assert!(!quarantine.is_quarantined("parent"));
+        assert_eq!(quarantine.get_error_rate("parent"), Some(0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "quarantined rules need an id or a name")]
+    fn test_quarantine_requires_an_identifier() {
+        Quarantine::new(1, 0.0).wrap(AllRule::new());
+    }
+}
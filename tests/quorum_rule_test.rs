@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    type RuleContextWrapper = Rc<std::cell::RefCell<RuleContext>>;
+
+    fn signal(key: &'static str, calls: Rc<Cell<u32>>) -> Rc<std::cell::RefCell<ChainRule>> {
+        ChainRule::new()
+            .on_eval(move |ctx| {
+                calls.set(calls.get() + 1);
+                ctx.get::<bool>(key).is_some()
+            })
+            .on_execute(|this| this.get_rule_context().set("signal_executed", true))
+    }
+
+    fn approval(calls: &Rc<Cell<u32>>) -> Rc<std::cell::RefCell<ChainRule>> {
+        QuorumRule::new(
+            2,
+            vec![
+                signal("manager", calls.clone()),
+                signal("finance", calls.clone()),
+                signal("legal", calls.clone()),
+            ],
+        )
+        .wrap(
+            ChainRule::new()
+                .with_name("approve")
+                .on_execute(|this| this.get_rule_context().set("approved", true)),
+        )
+    }
+
+    fn run(keys: &[&'static str]) -> (RuleContextWrapper, u32) {
+        let calls = Rc::new(Cell::new(0));
+        let mut rule_context = RuleContext::new();
+        for key in keys {
+            rule_context.set(key, true);
+        }
+        Engine::chain_runner().run(rule_context.clone(), vec![approval(&calls)]);
+        (rule_context, calls.get())
+    }
+
+    #[test]
+    fn test_quorum_reached() {
+        let (rule_context, calls) = run(&["manager", "finance"]);
+
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+        assert!(rule_context.get::<bool>("signal_executed").is_none());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_quorum_not_reached() {
+        let (rule_context, calls) = run(&["finance"]);
+        assert!(rule_context.get::<bool>("approved").is_none());
+        assert_eq!(calls, 3);
+
+        // After two failed signals, one more can't make the quorum.
+        let (rule_context, calls) = run(&[]);
+        assert!(rule_context.get::<bool>("approved").is_none());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_quorum_respects_original_eval_and_failures() {
+        let rule = QuorumRule::new(0, vec![]).wrap(AllRule::new().on_eval(|_| false));
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+        assert!(report.get_trace().get_executed().is_empty());
+
+        let failing = AllRule::new().on_eval(|ctx| {
+            ctx.fail(RuleError::failed("no score"));
+            true
+        });
+        let quorum = QuorumRule::new(1, vec![failing]);
+        assert_eq!(quorum.get_quorum(), 1);
+        assert_eq!(quorum.get_signals().len(), 1);
+        let rule = quorum
+            .wrap(AllRule::new().on_execute(|this| this.get_rule_context().set("approved", true)));
+        let rule_context = RuleContext::new();
+        let result = Engine::all_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::failed("no score")));
+        assert!(rule_context.get::<bool>("approved").is_none());
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn registry() -> RuleRegistry {
+        let mut registry = RuleRegistry::new();
+        registry
+            .register(
+                "checkout",
+                Engine::chain_runner(),
+                vec![ChainRule::new()
+                    .with_name("approve")
+                    .on_execute(|this| this.get_rule_context().set("approved", true))],
+            )
+            .register(
+                "pricing",
+                Engine::all_runner(),
+                vec![AllRule::new().on_execute(|this| {
+                    this.get_rule_context().fail(RuleError::failed("no price"))
+                })],
+            );
+        registry
+    }
+
+    #[test]
+    fn test_registry_executes_by_name() {
+        let registry = registry();
+
+        let rule_context = RuleContext::new();
+        assert_eq!(registry.execute("checkout", rule_context.clone()), Ok(()));
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+
+        // Registered rules can be run again for every request.
+        let report = registry
+            .execute_with_report("checkout", RuleContext::new())
+            .unwrap();
+        assert_eq!(report.get_trace().get_executed_names(), vec!["approve"]);
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        let registry = registry();
+
+        let rule_context = RuleContext::new();
+        let result = registry.execute("pricing", rule_context.clone());
+        assert_eq!(
+            result,
+            Err(RegistryError::Failed(RuleError::failed("no price")))
+        );
+        assert!(!rule_context.has_failed());
+
+        let result = registry.execute("missing", RuleContext::new());
+        assert_eq!(
+            result,
+            Err(RegistryError::UnknownRuleSet("missing".to_string()))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "unknown rule set `missing`"
+        );
+    }
+
+    #[test]
+    fn test_registry_listing_replacement_and_removal() {
+        let mut registry = registry();
+        assert_eq!(registry.get_names(), vec!["checkout", "pricing"]);
+
+        registry.register_with("checkout", |rule_context| {
+            Engine::chain_runner()
+                .run_with_report(rule_context, vec![ChainRule::new().with_name("v2")])
+        });
+        let report = registry
+            .execute_with_report("checkout", RuleContext::new())
+            .unwrap();
+        assert_eq!(report.get_trace().get_executed_names(), vec!["v2"]);
+
+        assert!(registry.remove("pricing"));
+        assert!(!registry.remove("pricing"));
+        assert!(!registry.contains("pricing"));
+        assert_eq!(registry.get_names(), vec!["checkout"]);
+    }
+
+    #[test]
+    fn test_registry_rule_handles() {
+        let mut registry = RuleRegistry::new();
+        registry.register(
+            "checkout",
+            Engine::best_first_runner(),
+            vec![BestFirstRule::new()
+                .with_name("checkout")
+                .add_child(
+                    BestFirstRule::new()
+                        .with_id("R-7")
+                        .with_name("express")
+                        .on_execute(|this| this.get_rule_context().set("express", true)),
+                )
+                .add_default_child(BestFirstRule::new().with_name("standard"))],
+        );
+
+        let names: Vec<_> = registry
+            .get_rules("checkout")
+            .iter()
+            .map(|rule| rule.get_name().unwrap())
+            .collect();
+        assert_eq!(names, ["checkout", "express", "standard"]);
+        assert!(registry.get_handle("checkout", "missing").is_none());
+        assert!(registry.get_handle("audit", "express").is_none());
+
+        let handle = registry.get_handle("checkout", "R-7").unwrap();
+        handle.disable();
+        assert!(!registry.get_rules("checkout")[1].is_enabled());
+
+        let rule_context = RuleContext::new();
+        let report = registry
+            .execute_with_report("checkout", rule_context.clone())
+            .unwrap();
+        assert!(rule_context.get::<bool>("express").is_none());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["checkout", "standard"]
+        );
+
+        registry.get_handle("checkout", "express").unwrap().enable();
+        registry.execute("checkout", rule_context.clone()).unwrap();
+        assert!(*rule_context.get::<bool>("express").unwrap());
+
+        registry.register_with("checkout", |rule_context| {
+            Engine::all_runner().run_with_report(rule_context, vec![])
+        });
+        assert!(registry.get_rules("checkout").is_empty());
+    }
+}
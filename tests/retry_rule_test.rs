@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use dredd_rs::rule::*;
+
+    fn flaky(failures: u32) -> impl Fn(&mut BestFirstRule) + 'static {
+        move |this| {
+            let calls = this
+                .get_rule_context()
+                .get::<u32>("calls")
+                .map_or(1, |calls| *calls + 1);
+            this.get_rule_context().set("calls", calls);
+            if calls <= failures {
+                this.get_rule_context()
+                    .fail(RuleError::failed(format!("call {calls}")));
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_rule_retries_until_success() {
+        let rule = BestFirstRule::new()
+            .with_name("flaky")
+            .on_execute(flaky(2))
+            .add_child(
+                BestFirstRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+            );
+        let rule = RetryRule::new(3).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        let report = Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert!(report.get_error().is_none());
+        assert_eq!(*rule_context.get::<u32>("calls").unwrap(), 3);
+        assert!(*rule_context.get::<bool>("child").unwrap());
+        assert_eq!(report.get_trace().get_executed_names(), vec!["flaky"]);
+    }
+
+    #[test]
+    fn test_retry_rule_surfaces_last_error_after_exhaustion() {
+        let rule = BestFirstRule::new().on_execute(flaky(5)).add_child(
+            BestFirstRule::new().on_execute(|this| this.get_rule_context().set("child", true)),
+        );
+        let rule = RetryRule::new(3).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::best_first_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::failed("call 3")));
+        assert_eq!(*rule_context.get::<u32>("calls").unwrap(), 3);
+        assert!(rule_context.get::<bool>("child").is_none());
+    }
+
+    #[test]
+    fn test_retry_rule_only_retries_the_failed_callback() {
+        let evals = Rc::new(Cell::new(0));
+        let rule = ChainRule::new()
+            .on_pre_execute(|this| {
+                let pre = this
+                    .get_rule_context()
+                    .get::<u32>("pre")
+                    .map_or(1, |pre| *pre + 1);
+                this.get_rule_context().set("pre", pre);
+            })
+            .on_eval({
+                let evals = evals.clone();
+                move |ctx| {
+                    evals.set(evals.get() + 1);
+                    if evals.get() == 1 {
+                        ctx.fail(RuleError::failed("eval"));
+                    }
+                    true
+                }
+            });
+        let rule = RetryRule::new(2).wrap(rule);
+
+        let rule_context = RuleContext::new();
+        Engine::chain_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap();
+
+        assert_eq!(evals.get(), 2);
+        assert_eq!(*rule_context.get::<u32>("pre").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_retry_rule_waits_between_attempts() {
+        let rule = BestFirstRule::new().on_execute(flaky(2));
+        let rule = RetryRule::new(3)
+            .with_backoff(Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                max: Duration::from_millis(15),
+            })
+            .wrap(rule);
+
+        let started = Instant::now();
+        Engine::best_first_runner()
+            .try_run(RuleContext::new(), vec![rule])
+            .unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(25));
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let backoff = Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+        };
+
+        assert_eq!(backoff.get_delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.get_delay(3), Duration::from_millis(400));
+        assert_eq!(backoff.get_delay(5), Duration::from_secs(1));
+        assert_eq!(backoff.get_delay(64), Duration::from_secs(1));
+        assert_eq!(
+            Backoff::Fixed(Duration::from_millis(5)).get_delay(9),
+            Duration::from_millis(5)
+        );
+        assert_eq!(Backoff::None.get_delay(1), Duration::ZERO);
+    }
+}
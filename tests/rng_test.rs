@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut rng = Rng::new(42);
+        let mut replayed = Rng::new(rng.get_seed());
+        let draws: Vec<u64> = (0..10).map(|_| rng.next_u64()).collect();
+
+        assert_eq!(
+            draws,
+            (0..10).map(|_| replayed.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(draws[0], draws[1]);
+
+        let mut resumed = Rng::new(rng.get_seed());
+        assert_eq!(rng.next_u64(), resumed.next_u64());
+        assert!((0..1000)
+            .map(|_| rng.next_f64())
+            .all(|x| (0.0..1.0).contains(&x)));
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+
+    #[test]
+    fn test_with_probability() {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_seed(1);
+        let sampled = Condition::with_probability(0.1);
+
+        let hits = (0..1000)
+            .filter(|_| sampled.eval(&rule_context.borrow()))
+            .count();
+
+        assert!((50..150).contains(&hits), "{hits} hits");
+        assert!(!Condition::with_probability(0.0).eval(&rule_context.borrow()));
+        assert!(Condition::with_probability(1.0).eval(&rule_context.borrow()));
+    }
+
+    #[test]
+    fn test_report_seed_replays_the_run() {
+        let rules = || {
+            (0..20)
+                .map(|i| {
+                    AllRule::new()
+                        .with_name(&format!("sample {i}"))
+                        .on_condition(Condition::with_probability(0.5))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let rule_context = RuleContext::new();
+        rule_context.borrow().random();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), rules());
+
+        let replayed_context = RuleContext::new();
+        replayed_context.borrow_mut().set_seed(report.get_seed());
+        let replayed = Engine::all_runner().run_with_report(replayed_context, rules());
+
+        assert_eq!(replayed.get_seed(), report.get_seed());
+        assert_eq!(
+            replayed.get_trace().get_executed_names(),
+            report.get_trace().get_executed_names()
+        );
+    }
+
+    #[test]
+    fn test_dry_run_draws_like_the_run() {
+        let rules = || {
+            vec![AllRule::new()
+                .with_name("sampled")
+                .on_condition(Condition::with_probability(0.5))]
+        };
+        let rule_context = RuleContext::new();
+
+        for seed in 0..20 {
+            rule_context.borrow_mut().set_seed(seed);
+            let dry = Engine::all_runner().dry_run(rule_context.clone(), rules());
+            let report = Engine::all_runner().run_with_report(rule_context.clone(), rules());
+
+            assert_eq!(dry.get_seed(), seed);
+            assert_eq!(
+                dry.get_trace().get_executed_names(),
+                report.get_trace().get_executed_names()
+            );
+        }
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, fmt, io, time::Duration};
+
+    use dredd_rs::rule::*;
+
+    #[derive(Debug)]
+    struct PaymentError {
+        code: u16,
+        source: io::Error,
+    }
+
+    impl fmt::Display for PaymentError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "payment gateway returned {}", self.code)
+        }
+    }
+
+    impl Error for PaymentError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.source)
+        }
+    }
+
+    #[test]
+    fn test_failure_stops_chain() {
+        let rule = ChainRule::new()
+            .with_name("rule1")
+            .on_pre_execute(|this| this.get_rule_context().fail(RuleError::failed("boom")))
+            .on_execute(|this| this.get_rule_context().set("execute_1", true))
+            .add_child(
+                ChainRule::new()
+                    .with_name("rule2")
+                    .on_execute(|this| this.get_rule_context().set("execute_2", true)),
+            );
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert_eq!(
+            report.get_error(),
+            Some(&RuleError::failed("boom").in_rule("rule1"))
+        );
+        assert!(rule_context.has_failed());
+        assert!(rule_context.get::<bool>("execute_1").is_none());
+        assert!(rule_context.get::<bool>("execute_2").is_none());
+        assert_eq!(report.get_trace().get_entries().len(), 1);
+        assert!(report.get_trace().get_entries()[0].is_failed());
+    }
+
+    #[test]
+    fn test_failure_in_eval_stops_best_first_siblings() {
+        let rule1 = BestFirstRule::new().on_eval(|ctx| {
+            ctx.fail(RuleError::failed("eval"));
+            true
+        });
+        let rule2 =
+            BestFirstRule::new().on_execute(|this| this.get_rule_context().set("rule2", true));
+
+        let rule_context = RuleContext::new();
+        let result = Engine::best_first_runner().try_run(rule_context.clone(), vec![rule1, rule2]);
+
+        assert_eq!(result, Err(RuleError::failed("eval")));
+        assert!(!rule_context.has_failed());
+        assert!(rule_context.get::<bool>("rule2").is_none());
+    }
+
+    #[test]
+    fn test_failure_in_child_stops_all_rule_siblings() {
+        let parent = AllRule::new()
+            .with_name("parent")
+            .on_post_execute(|this| this.get_rule_context().set("post", true))
+            .add_children(vec![
+                AllRule::new()
+                    .with_name("child")
+                    .on_execute(|this| this.get_rule_context().fail(RuleError::failed("child"))),
+                AllRule::new().on_execute(|this| this.get_rule_context().set("sibling", true)),
+            ]);
+        let next = AllRule::new().on_execute(|this| this.get_rule_context().set("next", true));
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![parent, next]);
+
+        assert!(*rule_context.get::<bool>("post").unwrap());
+        assert!(rule_context.get::<bool>("sibling").is_none());
+        assert!(rule_context.get::<bool>("next").is_none());
+        let failed: Vec<_> = report
+            .get_trace()
+            .get_entries()
+            .iter()
+            .filter(|entry| entry.is_failed())
+            .filter_map(|entry| entry.get_name())
+            .collect();
+        assert_eq!(failed, vec!["parent", "child"]);
+    }
+
+    #[test]
+    fn test_first_failure_is_kept() {
+        let mut rule_context = RuleContext::new();
+        rule_context.fail(RuleError::failed("first"));
+        rule_context.fail(RuleError::failed("second"));
+
+        assert_eq!(rule_context.get_error(), Some(RuleError::failed("first")));
+        assert_eq!(
+            rule_context.take_error().unwrap().to_string(),
+            "rule failed: first"
+        );
+        assert!(!rule_context.has_failed());
+    }
+
+    #[test]
+    fn test_custom_error_keeps_source_chain() {
+        let rule = ChainRule::new().on_execute(|this| {
+            let error = PaymentError {
+                code: 503,
+                source: io::Error::new(io::ErrorKind::TimedOut, "connect timed out"),
+            };
+            this.get_rule_context().fail(RuleError::custom(error));
+        });
+
+        let error = Engine::chain_runner()
+            .try_run(RuleContext::new(), vec![rule])
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "rule failed: payment gateway returned 503"
+        );
+        let payment = error
+            .source()
+            .and_then(|source| source.downcast_ref::<PaymentError>())
+            .unwrap();
+        assert_eq!(payment.code, 503);
+        let io_error = payment.source().unwrap();
+        assert_eq!(io_error.to_string(), "connect timed out");
+        assert!(RuleError::failed("boom").source().is_none());
+    }
+
+    #[test]
+    fn test_failure_names_the_rule_that_failed() {
+        let rule = ChainRule::new().with_name("checkout").add_child(
+            ChainRule::new().with_id("R-7").on_execute(|this| {
+                this.get_rule_context()
+                    .fail(RuleError::custom("payment declined"))
+            }),
+        );
+
+        let error = Engine::chain_runner()
+            .try_run(RuleContext::new(), vec![rule])
+            .unwrap_err();
+
+        // The innermost rule is named, by its id for lack of a name.
+        assert_eq!(error.get_rule(), Some("R-7"));
+        assert_eq!(error.to_string(), "rule `R-7` failed: payment declined");
+        assert!(error.source().is_some());
+        assert_ne!(error, RuleError::failed("payment declined").in_rule("R-7"));
+        assert_eq!(RuleError::CycleDetected.in_rule("R-7").get_rule(), None);
+    }
+
+    #[test]
+    fn test_custom_error_from_boxed_error() {
+        let boxed: Box<dyn Error + Send + Sync> = "quota exhausted".into();
+        let error = RuleError::from(boxed);
+
+        assert!(matches!(error, RuleError::Custom { .. }));
+        assert_eq!(error.to_string(), "rule failed: quota exhausted");
+    }
+
+    #[test]
+    fn test_custom_error_equality() {
+        let error = RuleError::custom("out of stock");
+
+        assert_eq!(error, error.clone());
+        assert_ne!(error, RuleError::custom("out of stock"));
+        assert_ne!(error, RuleError::failed("out of stock"));
+    }
+
+    #[test]
+    fn test_errors_equal_to_themselves_only() {
+        let errors = [
+            RuleError::failed("out of stock"),
+            RuleError::DurationExceeded {
+                elapsed: Duration::from_millis(20),
+                limit: Duration::from_millis(10),
+                rule: None,
+            },
+            RuleError::BudgetExceeded(BudgetLimit::MaxFired(3)),
+            RuleError::CycleDetected,
+            RuleError::InvariantViolated("total >= 0".to_string()),
+            RuleError::custom("out of stock"),
+        ];
+
+        for (i, error) in errors.iter().enumerate() {
+            for (j, other) in errors.iter().enumerate() {
+                assert_eq!(error == other, i == j, "{error} == {other}");
+            }
+        }
+    }
+}
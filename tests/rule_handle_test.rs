@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_disabled_rules_and_children_are_skipped() {
+        let rule = AllRule::new()
+            .with_name("kyc")
+            .add_child(AllRule::new().with_name("kyc documents"));
+        let sibling = AllRule::new().with_name("limits");
+        rule.clone().with_enabled(false);
+
+        let report =
+            Engine::all_runner().run_with_report(RuleContext::new(), vec![rule.clone(), sibling]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["limits"]);
+        assert_eq!(
+            report.get_trace().get_entries()[0].get_outcome(),
+            RuleOutcome::Skipped("disabled")
+        );
+        assert!(!rule.borrow().get_metadata().is_enabled());
+    }
+
+    #[test]
+    fn test_handles_are_shared_by_clones_and_threads() {
+        let rule = ChainRule::new().with_name("scoring");
+        let copy = std::rc::Rc::new(std::cell::RefCell::new(rule.borrow().clone()));
+        let handle = rule.borrow().get_metadata().get_handle();
+
+        thread::spawn(move || handle.set_enabled(false))
+            .join()
+            .unwrap();
+
+        assert!(!rule.borrow().get_metadata().is_enabled());
+        assert!(!copy.borrow().get_metadata().is_enabled());
+        let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![copy]);
+        assert!(report.get_trace().get_executed_names().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_best_first_rule_falls_through_to_its_sibling() {
+        let first = BestFirstRule::new().with_name("first").with_enabled(false);
+        let second = BestFirstRule::new().with_name("second");
+
+        let report =
+            Engine::best_first_runner().run_with_report(RuleContext::new(), vec![first, second]);
+
+        assert_eq!(report.get_trace().get_executed_names(), vec!["second"]);
+    }
+}
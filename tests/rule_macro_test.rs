@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule;
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_rule_macro_chain_tree() {
+        let tree = rule! {
+            chain {
+                id: "R-1",
+                name: "root",
+                description: "Root rule",
+                owner: "ana",
+                team: "checkout",
+                before: |this| this.get_rule_context().set("before", true),
+                then: |this| this.get_rule_context().set("then", true),
+                after: |this| this.get_rule_context().set("after", true),
+                child: chain {
+                    name: "child",
+                    when: |ctx| ctx.get::<bool>("then").is_some(),
+                    child: chain {
+                        name: "grandchild",
+                        then: |this| this.get_rule_context().set("grandchild", true)
+                    }
+                }
+            }
+        };
+
+        {
+            let root = tree.borrow();
+            assert_eq!(root.get_id(), Some("R-1"));
+            assert_eq!(root.get_name(), Some("root"));
+            assert_eq!(root.get_description(), Some("Root rule"));
+            assert_eq!(root.get_owner(), Some("ana"));
+            assert_eq!(root.get_team(), Some("checkout"));
+        }
+
+        let rule_context = RuleContext::new();
+        let report = Engine::chain_runner().run_with_report(rule_context.clone(), vec![tree]);
+
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["root", "child", "grandchild"]
+        );
+        for key in ["before", "then", "after", "grandchild"] {
+            assert!(*rule_context.get::<bool>(key).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rule_macro_children_from_expressions() {
+        let shared = AllRule::new()
+            .with_name("shared")
+            .on_execute(|this| this.get_rule_context().set("shared", true));
+
+        let tree = rule! {
+            all {
+                child: all { name: "first", when: |_| false },
+                child: shared,
+                child: rule! { all { name: "third" } },
+            }
+        };
+
+        assert_eq!(tree.borrow_mut().get_children().len(), 3);
+
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![tree]);
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["shared", "third"]
+        );
+    }
+
+    #[test]
+    fn test_rule_macro_empty_rule() {
+        let rule = rule! { best_first {} };
+
+        assert_eq!(rule.borrow().get_name(), None);
+        assert!(rule.borrow_mut().get_children().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_rule_metadata_defaults_to_none() {
+        let rule = ChainRule::new();
+
+        assert_eq!(rule.borrow().get_id(), None);
+        assert_eq!(rule.borrow().get_name(), None);
+        assert_eq!(rule.borrow().get_description(), None);
+        assert_eq!(rule.borrow().get_owner(), None);
+        assert_eq!(rule.borrow().get_team(), None);
+    }
+
+    #[test]
+    fn test_rule_metadata_on_all_rule_types() {
+        let chain = ChainRule::new()
+            .with_id("C-1")
+            .with_name("chain")
+            .with_description("A chain rule");
+        let best_first = BestFirstRule::new()
+            .with_id("B-1")
+            .with_name("best_first")
+            .with_description("A best first rule");
+        let all = AllRule::new()
+            .with_id("A-1")
+            .with_name("all")
+            .with_description("An all rule");
+
+        assert_eq!(chain.borrow().get_id(), Some("C-1"));
+        assert_eq!(chain.borrow().get_name(), Some("chain"));
+        assert_eq!(chain.borrow().get_description(), Some("A chain rule"));
+        assert_eq!(best_first.borrow().get_id(), Some("B-1"));
+        assert_eq!(best_first.borrow().get_name(), Some("best_first"));
+        assert_eq!(
+            best_first.borrow().get_description(),
+            Some("A best first rule")
+        );
+        assert_eq!(all.borrow().get_id(), Some("A-1"));
+        assert_eq!(all.borrow().get_name(), Some("all"));
+        assert_eq!(all.borrow().get_description(), Some("An all rule"));
+
+        let base = BaseRule::<ChainRule>::new();
+        base.borrow_mut().set_name("base");
+        assert_eq!(base.borrow().get_name(), Some("base"));
+    }
+
+    #[test]
+    fn test_rule_metadata_visible_from_callbacks() {
+        let rule = BestFirstRule::builder()
+            .with_name("parent")
+            .on_execute(|this| {
+                let name = this.get_name().unwrap().to_string();
+                this.get_rule_context().set("fired", name);
+            })
+            .child(BestFirstRule::new())
+            .build();
+
+        let rule_context = RuleContext::new();
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<String>("fired").unwrap(), "parent");
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain rule `parent` can only have one child")]
+    fn test_chain_rule_panic_message_names_the_rule() {
+        let mut rule = ChainRule::new().with_name("parent");
+
+        rule.add_child(ChainRule::new());
+        rule.add_child(ChainRule::new());
+    }
+
+    #[test]
+    fn test_rule_metadata_owner_and_team_in_trace() {
+        let rule = AllRule::new().with_owner("ana").with_team("checkout");
+        assert_eq!(rule.borrow().get_owner(), Some("ana"));
+        assert_eq!(rule.borrow().get_team(), Some("checkout"));
+
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![rule]);
+        let entry = &report.get_trace().get_entries()[0];
+
+        assert_eq!(entry.get_owner(), Some("ana"));
+        assert_eq!(entry.get_team(), Some("checkout"));
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn warning(severity: Severity) -> Rc<RefCell<AllRule>> {
+        AllRule::new().on_execute(move |this| this.get_rule_context().warn(severity, "check"))
+    }
+
+    #[test]
+    fn test_status_pass() {
+        let report = Engine::all_runner().run_with_report(
+            RuleContext::new(),
+            vec![AllRule::new(), warning(Severity::Info)],
+        );
+
+        assert_eq!(report.get_status(), RunStatus::Pass);
+        assert_eq!(report.get_status().to_string(), "pass");
+    }
+
+    #[test]
+    fn test_status_pass_with_warnings() {
+        let report = Engine::all_runner().run_with_report(
+            RuleContext::new(),
+            vec![warning(Severity::Info), warning(Severity::Warning)],
+        );
+
+        assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+        assert_eq!(report.get_status().to_string(), "pass with warnings");
+    }
+
+    #[test]
+    fn test_status_fail() {
+        let report = Engine::all_runner().run_with_report(
+            RuleContext::new(),
+            vec![warning(Severity::Warning), warning(Severity::Critical)],
+        );
+        assert_eq!(report.get_status(), RunStatus::Fail);
+        assert_eq!(report.get_status().to_string(), "fail");
+
+        let failing =
+            AllRule::new().on_execute(|this| this.get_rule_context().fail(RuleError::failed("no")));
+        let report = Engine::all_runner().run_with_policy(
+            RuleContext::new(),
+            vec![failing, warning(Severity::Warning)],
+            ErrorPolicy::CollectAll,
+        );
+        assert!(report.get_error().is_none());
+        assert_eq!(report.get_status(), RunStatus::Fail);
+    }
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use dredd_rs::rule::*;
+    use dredd_rs::sampling::{JsonLinesSink, Sample, Sampler};
+    use serde_json::json;
+
+    fn rules() -> Vec<std::rc::Rc<std::cell::RefCell<AllRule>>> {
+        vec![AllRule::new()
+            .with_name("high_score")
+            .on_eval(|ctx| *ctx.get::<f64>("score").unwrap() > 0.5)
+            .on_execute(|this| {
+                this.get_rule_context().set("approved", true);
+                this.get_rule_context().set("reason", "score".to_string());
+            })]
+    }
+
+    fn context(score: f64) -> std::rc::Rc<std::cell::RefCell<RuleContext>> {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("score", score);
+        rule_context.set("country", "BR");
+        rule_context.set("ignored", vec![1u8]);
+        rule_context
+    }
+
+    #[test]
+    fn test_sampler_captures_input_fired_and_outputs() {
+        let mut sampler = Sampler::new(Vec::<Sample>::new());
+        sampler
+            .run(&Engine::all_runner(), context(0.9), rules())
+            .unwrap();
+        sampler
+            .run(&Engine::all_runner(), context(0.1), rules())
+            .unwrap();
+
+        let samples = sampler.get_sink();
+        assert_eq!(samples.len(), 2);
+
+        assert_eq!(
+            serde_json::to_value(&samples[0]).unwrap(),
+            json!({
+                "input": { "score": 0.9, "country": "BR" },
+                "fired": ["high_score"],
+                "outputs": { "approved": true, "reason": "score" }
+            })
+        );
+        assert!(samples[1].get_fired().is_empty());
+        assert!(samples[1].get_outputs().is_empty());
+        assert_eq!(samples[1].get_input()["score"], json!(0.1));
+    }
+
+    #[test]
+    fn test_sampler_rate() {
+        let mut sampler = Sampler::new(Vec::<Sample>::new()).with_rate(0.1);
+        for _ in 0..100 {
+            sampler
+                .run(&Engine::all_runner(), context(0.9), rules())
+                .unwrap();
+        }
+        assert_eq!(sampler.get_runs(), 100);
+        assert_eq!(sampler.get_sampled_runs(), 10);
+
+        let mut sampler = Sampler::new(Vec::<Sample>::new()).with_rate(0.0);
+        sampler
+            .run(&Engine::all_runner(), context(0.9), rules())
+            .unwrap();
+        assert_eq!(sampler.get_sampled_runs(), 0);
+    }
+
+    #[test]
+    fn test_sampler_json_lines_and_sink_errors() {
+        let mut sampler = Sampler::new(JsonLinesSink::new(Vec::new()));
+        sampler
+            .run(&Engine::all_runner(), context(0.9), rules())
+            .unwrap();
+        sampler
+            .run(&Engine::all_runner(), context(0.2), rules())
+            .unwrap();
+
+        let output = String::from_utf8(sampler.into_sink().into_inner()).unwrap();
+        let samples: Vec<Sample> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].get_fired(), ["high_score"]);
+
+        let mut sampler = Sampler::new(|_: &Sample| Err(io::Error::other("queue unavailable")));
+        let rule_context = context(0.9);
+        let result = sampler.run(&Engine::all_runner(), rule_context.clone(), rules());
+        assert_eq!(result.unwrap_err().to_string(), "queue unavailable");
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+    }
+}
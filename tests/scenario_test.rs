@@ -0,0 +1,131 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::loader::{self, CallbackRegistry, LoadedRules};
+    use dredd_rs::rule::*;
+    use dredd_rs::scenario::ScenarioSuite;
+
+    fn rules() -> LoadedRules {
+        let mut registry = CallbackRegistry::new();
+        registry
+            .condition("is_adult", |ctx| *ctx.get::<i64>("age").unwrap() >= 18)
+            .condition("is_vip", |ctx| *ctx.get::<bool>("vip").unwrap())
+            .action("approve", |ctx| ctx.set("approved", true))
+            .action("discount", |ctx| ctx.set("discount", 0.2f64));
+
+        loader::from_json(
+            r#"{
+                "type": "chain",
+                "state": "active",
+                "rules": [{
+                    "name": "adult",
+                    "eval": "is_adult",
+                    "execute": "approve",
+                    "children": [{ "name": "vip", "eval": "is_vip", "execute": "discount" }]
+                }]
+            }"#,
+            &registry,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scenarios_pass() {
+        let suite = ScenarioSuite::from_json(
+            r#"{
+                "name": "approval",
+                "scenarios": [
+                    {
+                        "name": "vip adult",
+                        "given": { "age": 42, "vip": true },
+                        "expect": {
+                            "fired": ["adult", "vip"],
+                            "outputs": { "approved": true, "discount": 0.2, "age": 42 }
+                        }
+                    },
+                    {
+                        "name": "minor",
+                        "given": { "age": 12 },
+                        "expect": { "fired": [], "not_fired": ["adult"] }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let report = suite.run(&rules());
+
+        assert!(report.is_success(), "{:?}", report.get_results());
+        assert_eq!(report.get_results().len(), 2);
+        assert!(report
+            .to_junit_xml()
+            .contains(r#"<testsuite name="approval" tests="2" failures="0" errors="0""#));
+    }
+
+    #[test]
+    fn test_scenarios_report_failures_and_errors() {
+        let suite = ScenarioSuite::from_json(
+            r#"{
+                "name": "approval",
+                "scenarios": [
+                    {
+                        "name": "wrong <output>",
+                        "given": { "age": 42, "vip": false },
+                        "expect": { "fired": ["adult", "vip"], "outputs": { "approved": false, "discount": 0.2 } }
+                    },
+                    { "name": "missing age", "given": {} }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let report = suite.run(&rules());
+        let results = report.get_results();
+
+        assert!(!report.is_success());
+        assert_eq!(
+            results[0].get_failures(),
+            [
+                r#"expected fired rules ["adult", "vip"], got ["adult"]"#,
+                "expected `approved` to be false, got true",
+                "missing output `discount`",
+            ]
+        );
+        assert!(results[1].get_error().is_some());
+
+        let xml = report.to_junit_xml();
+        assert!(xml.contains(r#"failures="1" errors="1""#));
+        assert!(xml.contains(r#"<testcase name="wrong &lt;output&gt;""#));
+        assert!(xml.contains("<error message="));
+    }
+
+    #[test]
+    fn test_scenarios_run_with_any_rule_set() {
+        let suite = ScenarioSuite::from_json(
+            r#"{
+                "name": "best first",
+                "scenarios": [{
+                    "name": "tags",
+                    "given": { "tags": ["a", "b"] },
+                    "expect": { "fired": ["count"], "outputs": { "count": 2 } }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let report = suite.run_with(|rule_context| {
+            let rule = BestFirstRule::new().with_name("count").on_execute(|this| {
+                let tags = this
+                    .get_rule_context()
+                    .get::<serde_json::Value>("tags")
+                    .unwrap();
+                let count = tags.as_array().unwrap().len();
+                this.get_rule_context().set("count", count);
+            });
+            Engine::best_first_runner().run_with_report(rule_context, vec![rule])
+        });
+
+        assert!(report.is_success(), "{:?}", report.get_results());
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::context_keys;
+    use dredd_rs::schema::ContextSchema;
+    use serde_json::json;
+
+    context_keys! {
+        mod keys {
+            /// Customer age in years.
+            AGE: i64 = "customer.age";
+            APPROVED: bool = "checkout.approved";
+        }
+    }
+
+    #[test]
+    fn test_schema_should_describe_keys_and_usages() {
+        let schema = ContextSchema::new()
+            .keys(keys::KEYS)
+            .reads("adult_check", [keys::AGE.name()])
+            .writes("adult_check", [keys::APPROVED.name()])
+            .writes("fallback", [keys::APPROVED.name(), "audit.reason"])
+            .writes("fallback", [keys::APPROVED.name()]);
+
+        assert_eq!(
+            schema.to_json_value(),
+            json!({
+                "keys": [
+                    {
+                        "name": "customer.age",
+                        "type": "i64",
+                        "doc": "Customer age in years.",
+                        "read_by": ["adult_check"],
+                        "written_by": []
+                    },
+                    {
+                        "name": "checkout.approved",
+                        "type": "bool",
+                        "doc": "",
+                        "read_by": [],
+                        "written_by": ["adult_check", "fallback"]
+                    },
+                    {
+                        "name": "audit.reason",
+                        "type": null,
+                        "doc": "",
+                        "read_by": [],
+                        "written_by": ["fallback"]
+                    }
+                ]
+            })
+        );
+    }
+}
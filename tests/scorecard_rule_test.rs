@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn credit_scorecard() -> ScorecardRule {
+        ScorecardRule::new("score")
+            .criterion(Condition::key_equals("homeowner", true), 40)
+            .criterion(
+                Condition::new(|ctx| {
+                    ctx.get::<u32>("income")
+                        .is_some_and(|income| *income >= 50_000)
+                }),
+                60,
+            )
+            .criterion(Condition::key_exists("defaulted"), -80)
+            .with_band_key("grade")
+            .band(i64::MIN, "decline")
+            .band(80, "approve")
+            .band(40, "review")
+    }
+
+    fn run(
+        rule: Rc<RefCell<AllRule>>,
+        setup: impl FnOnce(&mut Rc<RefCell<RuleContext>>),
+    ) -> Rc<RefCell<RuleContext>> {
+        let mut rule_context = RuleContext::new();
+        setup(&mut rule_context);
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+        rule_context
+    }
+
+    #[test]
+    fn test_scorecard_sums_points_and_picks_band() {
+        let rule = credit_scorecard().wrap(AllRule::new());
+
+        let rule_context = run(rule.clone(), |ctx| {
+            ctx.set("homeowner", true);
+            ctx.set("income", 70_000u32);
+        });
+        assert_eq!(*rule_context.get::<i64>("score").unwrap(), 100);
+        assert_eq!(*rule_context.get::<&str>("grade").unwrap(), "approve");
+
+        let rule_context = run(rule.clone(), |ctx| ctx.set("income", 50_000u32));
+        assert_eq!(*rule_context.get::<i64>("score").unwrap(), 60);
+        assert_eq!(*rule_context.get::<&str>("grade").unwrap(), "review");
+
+        let rule_context = run(rule, |ctx| {
+            ctx.set("income", 90_000u32);
+            ctx.set("defaulted", true);
+        });
+        assert_eq!(*rule_context.get::<i64>("score").unwrap(), -20);
+        assert_eq!(*rule_context.get::<&str>("grade").unwrap(), "decline");
+    }
+
+    #[test]
+    fn test_scorecard_without_matching_band() {
+        let rule = ScorecardRule::new("score")
+            .criterion(Condition::key_exists("verified"), 10)
+            .band(50, "gold")
+            .with_band_key("tier")
+            .wrap(AllRule::new());
+
+        let rule_context = run(rule, |ctx| ctx.set("verified", true));
+
+        assert_eq!(*rule_context.get::<i64>("score").unwrap(), 10);
+        assert!(rule_context.get::<&str>("tier").is_none());
+    }
+
+    #[test]
+    fn test_scorecard_without_band_key() {
+        let rule = ScorecardRule::new("score")
+            .criterion(Condition::key_exists("verified"), 10)
+            .band(0, "gold")
+            .wrap(AllRule::new());
+
+        let rule_context = run(rule, |_| {});
+
+        assert_eq!(*rule_context.get::<i64>("score").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_scorecard_runs_before_post_execute_and_children() {
+        let rule = credit_scorecard().wrap(
+            AllRule::new()
+                .on_post_execute(|this| {
+                    let score = *this.get_rule_context().get::<i64>("score").unwrap();
+                    this.get_rule_context().set("seen_by_post_execute", score);
+                })
+                .add_child(
+                    AllRule::new()
+                        .on_eval(|ctx| {
+                            ctx.get::<&str>("grade")
+                                .is_some_and(|grade| *grade == "approve")
+                        })
+                        .on_execute(|this| this.get_rule_context().set("approved", true)),
+                ),
+        );
+
+        let rule_context = run(rule, |ctx| {
+            ctx.set("homeowner", true);
+            ctx.set("income", 50_000u32);
+        });
+
+        assert_eq!(
+            *rule_context.get::<i64>("seen_by_post_execute").unwrap(),
+            100
+        );
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+    }
+
+    #[test]
+    fn test_scorecard_skipped_when_rule_does_not_apply() {
+        let rule = credit_scorecard().wrap(AllRule::new().on_eval(|_| false));
+
+        let rule_context = run(rule, |ctx| ctx.set("homeowner", true));
+
+        assert!(rule_context.get::<i64>("score").is_none());
+    }
+
+    #[test]
+    fn test_scorecard_getters() {
+        let scorecard = credit_scorecard();
+
+        assert_eq!(scorecard.get_key(), "score");
+        assert_eq!(scorecard.get_band_key(), Some("grade"));
+
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set("homeowner", true);
+        assert_eq!(scorecard.score(&rule_context.borrow()), 40);
+    }
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "rhai")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use dredd_rs::script::{RuleScripts, Script, ScriptError};
+
+    #[test]
+    fn test_eval_script_reads_context() {
+        let rule = AllRule::new()
+            .eval_script("ctx.total > 100 && ctx[\"customer.tier\"] == \"gold\"")
+            .unwrap()
+            .on_execute(|this| this.get_rule_context().set("approved", true));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 150u32);
+        rule_context.set("customer.tier", "gold");
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("approved").unwrap());
+    }
+
+    #[test]
+    fn test_execute_script_writes_context() {
+        let rule = ChainRule::new()
+            .execute_script(
+                r#"
+                ctx.discount = 0.1;
+                ctx.count = ctx.count + 1;
+                ctx.label = "vip";
+                ctx.flags = [1, 2];
+                "#,
+            )
+            .unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1i64);
+        Engine::chain_runner().run(rule_context.clone(), vec![rule]);
+
+        assert_eq!(*rule_context.get::<f64>("discount").unwrap(), 0.1);
+        assert_eq!(*rule_context.get::<i64>("count").unwrap(), 2);
+        assert_eq!(*rule_context.get::<String>("label").unwrap(), "vip");
+        assert!(rule_context
+            .get::<rhai::Dynamic>("flags")
+            .unwrap()
+            .is_array());
+    }
+
+    #[test]
+    fn test_missing_keys_are_unit() {
+        let rule = AllRule::new()
+            .eval_script("ctx.missing == ()")
+            .unwrap()
+            .on_execute(|this| this.get_rule_context().set("fired", true));
+
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(rule_context.get::<bool>("fired").is_some());
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let result = AllRule::new().eval_script("ctx.total >");
+
+        assert!(matches!(result, Err(ScriptError::Compile(_))));
+    }
+
+    #[test]
+    fn test_runtime_errors_fail_the_rule() {
+        let rule = AllRule::new()
+            .eval_script("ctx.total / ctx.count > 100")
+            .unwrap()
+            .on_execute(|this| this.get_rule_context().set("fired", true));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 500i64);
+        rule_context.set("count", 0i64);
+        let result = Engine::all_runner().try_run(rule_context.clone(), vec![rule]);
+
+        assert!(
+            matches!(result, Err(RuleError::Failed { message, .. }) if message.starts_with("script failed"))
+        );
+        assert!(rule_context.get::<bool>("fired").is_none());
+    }
+
+    #[test]
+    fn test_condition_must_be_boolean() {
+        let script = Script::compile("ctx.total + 1").unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 1i64);
+
+        assert_eq!(
+            script.eval(&rule_context),
+            Err(ScriptError::NotBoolean("i64".to_string()))
+        );
+        assert_eq!(script.get_source(), "ctx.total + 1");
+    }
+
+    #[test]
+    fn test_runaway_scripts_are_stopped() {
+        let script = Script::compile("loop {}").unwrap();
+
+        assert!(matches!(
+            script.run(&RuleContext::new()),
+            Err(ScriptError::Runtime(_))
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+#![cfg(feature = "server")]
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use dredd_rs::loader;
+    use dredd_rs::server::{RuleServer, IO_TIMEOUT, MAX_BODY_SIZE};
+    use serde_json::{json, Value};
+
+    const DOCUMENT: &str = r#"{
+        "conditions": { "is_adult": "age >= 18" },
+        "actions": { "approve": { "approved": true } },
+        "rules": {
+            "type": "chain",
+            "rules": [{ "name": "adult", "eval": "is_adult", "execute": "approve" }]
+        }
+    }"#;
+
+    fn server() -> RuleServer {
+        let mut server = RuleServer::new();
+        server.add("approval", loader::from_document_json(DOCUMENT).unwrap());
+        server
+    }
+
+    fn start() -> String {
+        start_with(IO_TIMEOUT)
+    }
+
+    fn start_with(io_timeout: Duration) -> String {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let served = address.clone();
+        thread::spawn(move || server().with_io_timeout(io_timeout).serve(&served).unwrap());
+        address
+    }
+
+    fn connect(address: &str) -> TcpStream {
+        (0..50)
+            .find_map(|_| {
+                TcpStream::connect(address)
+                    .map_err(|_| thread::sleep(Duration::from_millis(20)))
+                    .ok()
+            })
+            .unwrap()
+    }
+
+    fn send(address: &str, request: &str) -> String {
+        let mut stream = connect(address);
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_evaluate_returns_context_and_trace() {
+        let (status, body) = server().handle(
+            "POST",
+            "/rulesets/approval/evaluate",
+            r#"{ "age": 42, "name": "Ann" }"#,
+        );
+
+        assert_eq!(status, 200);
+        // "name" isn't read by the rules, so it is dropped.
+        assert_eq!(body["context"], json!({ "age": 42, "approved": true }));
+        assert_eq!(body["fired"], json!(["adult"]));
+        assert_eq!(body["trace"][0]["name"], "adult");
+        assert_eq!(body["trace"][0]["outcome"], "fired");
+    }
+
+    #[test]
+    fn test_evaluate_rule_not_applicable() {
+        let (status, body) =
+            server().handle("POST", "/rulesets/approval/evaluate", r#"{ "age": 12 }"#);
+
+        assert_eq!(status, 200);
+        assert_eq!(body["context"], json!({ "age": 12 }));
+        assert_eq!(body["trace"][0]["outcome"], "not applicable");
+    }
+
+    #[test]
+    fn test_request_errors() {
+        let server = server();
+
+        let (status, body) = server.handle("POST", "/rulesets/pricing/evaluate", "{}");
+        assert_eq!(status, 404);
+        assert_eq!(body["error"], "unknown rule set `pricing`");
+
+        let (status, _) = server.handle("POST", "/rulesets/approval/evaluate", "[1, 2]");
+        assert_eq!(status, 400);
+
+        let (status, _) = server.handle("GET", "/rulesets/approval/evaluate", "");
+        assert_eq!(status, 405);
+
+        let (status, _) = server.handle("GET", "/health", "");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = env::temp_dir().join(format!("dredd-server-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("approval.json"), DOCUMENT).unwrap();
+        fs::write(dir.join("notes.txt"), "not a rule set").unwrap();
+
+        let server = RuleServer::load_dir(&dir).unwrap();
+        assert_eq!(server.get_names(), vec!["approval"]);
+        let (status, body) = server.handle("GET", "/rulesets", "");
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "rulesets": ["approval"] }));
+
+        fs::write(dir.join("broken.json"), r#"{ "rules": {} }"#).unwrap();
+        let error = RuleServer::load_dir(&dir).err().unwrap();
+        assert!(error.to_string().contains("broken.json"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_serve_over_http() {
+        let address = start();
+
+        let body = r#"{ "age": 20 }"#;
+        let response = send(
+            &address,
+            &format!(
+                "POST /rulesets/approval/evaluate HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        let json: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["context"]["approved"], true);
+    }
+
+    #[test]
+    fn test_serve_rejects_large_bodies() {
+        let address = start();
+
+        let response = send(
+            &address,
+            &format!(
+                "POST /rulesets/approval/evaluate HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+                MAX_BODY_SIZE + 1
+            ),
+        );
+
+        assert!(response.starts_with("HTTP/1.1 413"));
+    }
+
+    #[test]
+    fn test_slow_client_does_not_block_others() {
+        let address = start();
+        let mut slow = connect(&address);
+        slow.write_all(
+            b"POST /rulesets/approval/evaluate HTTP/1.1\r\nContent-Length: 100\r\n\r\n{",
+        )
+        .unwrap();
+
+        let response = send(&address, "GET /rulesets HTTP/1.1\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with(r#"{"rulesets":["approval"]}"#));
+    }
+
+    #[test]
+    fn test_slow_drip_client_times_out() {
+        let io_timeout = Duration::from_millis(500);
+        let address = start_with(io_timeout);
+        let mut slow = connect(&address);
+        slow.set_read_timeout(Some(io_timeout / 5)).unwrap();
+        let started = Instant::now();
+
+        // Every byte arrives well within the timeout, the whole request
+        // doesn't: waiting on the response paces the drip.
+        let mut response = String::new();
+        for byte in b"POST /rulesets/approval/evaluate HTTP/1.1\r\n"
+            .iter()
+            .cycle()
+        {
+            if started.elapsed() > 10 * io_timeout || slow.write_all(&[*byte]).is_err() {
+                break;
+            }
+            if slow.read_to_string(&mut response).is_ok() {
+                break;
+            }
+        }
+
+        assert!(response.starts_with("HTTP/1.1 408"));
+        assert!(started.elapsed() < 10 * io_timeout);
+    }
+}
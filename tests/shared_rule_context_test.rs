@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_shared_context_across_threads() {
+        let rule_context = SharedRuleContext::new();
+        rule_context.set("base", 10u64);
+
+        let handles: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let rule_context = rule_context.clone();
+                thread::spawn(move || {
+                    let base = *rule_context.get::<u64>("base").unwrap();
+                    rule_context.set(key, base + index as u64);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(*rule_context.get::<u64>("a").unwrap(), 10);
+        assert_eq!(*rule_context.get::<u64>("c").unwrap(), 12);
+    }
+
+    #[test]
+    fn test_shared_context_from_and_to_context() {
+        let mut defaults = RuleContext::new();
+        defaults.set("currency", "USD");
+        defaults.set("limit", 100i64);
+        let mut rule_context = LayeredContext::new(vec![RuleContext::new(), defaults]);
+        rule_context.set("limit", 250i64);
+        rule_context.set("name", "ana".to_string());
+
+        let shared = SharedRuleContext::from_context(&rule_context.borrow()).unwrap();
+
+        assert_eq!(*shared.get::<&str>("currency").unwrap(), "USD");
+        assert_eq!(*shared.get::<i64>("limit").unwrap(), 250);
+        assert_eq!(*shared.get::<String>("name").unwrap(), "ana");
+
+        shared.set("approved", true);
+        let converted = shared.to_context().unwrap();
+
+        assert!(*converted.get::<bool>("approved").unwrap());
+        assert_eq!(*converted.get::<i64>("limit").unwrap(), 250);
+        assert_eq!(
+            converted.borrow().snapshot().get_keys(),
+            vec!["approved", "currency", "limit", "name"]
+        );
+    }
+
+    #[test]
+    fn test_shared_context_unsupported_values() {
+        #[derive(Clone)]
+        struct Order;
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 10);
+        rule_context.set("order", Order);
+
+        let error = SharedRuleContext::from_context(&rule_context.borrow()).unwrap_err();
+        assert_eq!(error, SharedContextError::UnsupportedValue("order"));
+        assert_eq!(
+            error.to_string(),
+            "the value of key `order` can't be converted"
+        );
+
+        let shared = SharedRuleContext::new();
+        shared.set("order", Order);
+        assert_eq!(
+            shared.to_context().unwrap_err(),
+            SharedContextError::UnsupportedValue("order")
+        );
+    }
+
+    #[test]
+    fn test_shared_context_registered_types_and_lists() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Address {
+            city: String,
+        }
+        SharedRuleContext::register::<Address>();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set(
+            "address",
+            Address {
+                city: "Lisbon".to_string(),
+            },
+        );
+        rule_context.push_to_list("notes", "vip".to_string());
+        rule_context.push_to_list(
+            "stops",
+            Address {
+                city: "Porto".to_string(),
+            },
+        );
+
+        let shared = SharedRuleContext::from_context(&rule_context.borrow()).unwrap();
+        assert_eq!(shared.get::<Address>("address").unwrap().city, "Lisbon");
+        assert_eq!(*shared.get::<Vec<String>>("notes").unwrap(), ["vip"]);
+
+        let converted = shared.to_context().unwrap();
+        assert_eq!(
+            converted.get_list::<Address>("stops").unwrap()[0].city,
+            "Porto"
+        );
+    }
+
+    #[test]
+    fn test_shared_context_keeps_aliases_and_settings() {
+        let rule_context = RuleContext::new();
+        rule_context
+            .borrow_mut()
+            .deprecate_key("customer_id", "cust.id");
+        rule_context.borrow_mut().set_seed(7);
+        rule_context.borrow_mut().set_max_depth(5);
+
+        let shared = SharedRuleContext::from_context(&rule_context.borrow()).unwrap();
+        let converted = shared.to_context().unwrap();
+
+        assert_eq!(
+            converted.borrow().get_key_aliases(),
+            rule_context.borrow().get_key_aliases()
+        );
+        assert_eq!(converted.borrow().get_seed(), 7);
+        assert_eq!(converted.borrow().get_max_depth(), 5);
+    }
+}
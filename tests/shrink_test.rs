@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use dredd_rs::testing::Shrinker;
+
+    fn discount_rules(rule_context: std::rc::Rc<std::cell::RefCell<RuleContext>>) -> bool {
+        let rule = AllRule::new()
+            .on_eval(|ctx| {
+                let code = ctx.get::<String>("code");
+                code.is_some_and(|code| code.starts_with('X'))
+            })
+            .on_execute(|this| {
+                let total = *this
+                    .get_rule_context()
+                    .get::<u32>("total")
+                    .unwrap_or_default();
+                let discount = *this
+                    .get_rule_context()
+                    .get::<f64>("discount")
+                    .unwrap_or_default();
+                this.get_rule_context()
+                    .set("negative", total as f64 - discount < 0.0);
+            });
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+        rule_context
+            .get::<bool>("negative")
+            .is_some_and(|negative| *negative)
+    }
+
+    #[test]
+    fn test_shrink_drops_keys_and_shrinks_values() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("code", "XMAS2024".to_string());
+        rule_context.set("total", 20u32);
+        rule_context.set("discount", 35.5f64);
+        rule_context.set("vip", true);
+        rule_context.set("customer", "Joe");
+
+        let reproducer = Shrinker::new(discount_rules).shrink(&rule_context).unwrap();
+        let context = reproducer.to_context();
+
+        assert_eq!(reproducer.get_keys(), vec!["code", "discount"]);
+        assert_eq!(*context.get::<String>("code").unwrap(), "X");
+        assert_eq!(*context.get::<f64>("discount").unwrap(), 1.0);
+        assert_eq!(
+            reproducer.to_string(),
+            "RuleTest::given(|ctx| {\n    ctx.set(\"code\", \"X\".to_string());\n    ctx.set(\"discount\", 1.0f64);\n})"
+        );
+        assert!(context.get::<bool>("negative").is_none());
+    }
+
+    #[test]
+    fn test_shrink_returns_none_when_not_failing() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("code", "NONE".to_string());
+
+        assert!(Shrinker::new(discount_rules)
+            .shrink(&rule_context)
+            .is_none());
+    }
+
+    #[test]
+    fn test_shrink_panicking_with_max_attempts() {
+        let shrinker = Shrinker::panicking(|rule_context| {
+            let rule = ChainRule::new().on_execute(|this| {
+                let count = *this
+                    .get_rule_context()
+                    .get::<usize>("count")
+                    .unwrap_or_default();
+                assert!(count < 10);
+            });
+            Engine::chain_runner().run(rule_context, vec![rule]);
+        })
+        .with_max_attempts(5);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("count", 1000usize);
+        rule_context.set("unprintable", vec![1, 2, 3]);
+
+        let reproducer = shrinker.shrink(&rule_context).unwrap();
+
+        assert_eq!(reproducer.get_keys(), vec!["count"]);
+        assert_eq!(reproducer.get_attempts(), 5);
+        assert!(*reproducer.to_context().get::<usize>("count").unwrap() < 1000);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("a", 1);
+        rule_context.set("b", 2);
+
+        let snapshot = rule_context.borrow().snapshot();
+        rule_context.set("a", 10);
+        rule_context.set("c", 30);
+
+        assert_eq!(snapshot.get_keys(), vec!["a", "b"]);
+
+        rule_context.borrow_mut().restore(snapshot.clone());
+
+        assert_eq!(*rule_context.get::<i32>("a").unwrap(), 1);
+        assert_eq!(*rule_context.get::<i32>("b").unwrap(), 2);
+        assert!(rule_context.get::<i32>("c").is_none());
+
+        // a snapshot can be restored more than once
+        rule_context.set("a", 100);
+        rule_context.borrow_mut().restore(snapshot);
+        assert_eq!(*rule_context.get::<i32>("a").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_try_run_atomic_rolls_back_on_failure() {
+        let rule = ChainRule::new()
+            .on_execute(|this| this.get_rule_context().set("reserved", true))
+            .add_child(ChainRule::new().on_execute(|this| {
+                this.get_rule_context().set("charged", true);
+                this.get_rule_context()
+                    .fail(RuleError::failed("card declined"));
+            }));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("order", 42);
+
+        let result = Engine::chain_runner().try_run_atomic(rule_context.clone(), vec![rule]);
+
+        assert_eq!(result, Err(RuleError::failed("card declined")));
+        assert_eq!(*rule_context.get::<i32>("order").unwrap(), 42);
+        assert!(rule_context.get::<bool>("reserved").is_none());
+        assert!(rule_context.get::<bool>("charged").is_none());
+        assert!(!rule_context.has_failed());
+    }
+
+    #[test]
+    fn test_try_run_atomic_keeps_changes_on_success() {
+        let rule = AllRule::new().on_execute(|this| this.get_rule_context().set("done", true));
+
+        let rule_context = RuleContext::new();
+
+        Engine::all_runner()
+            .try_run_atomic(rule_context.clone(), vec![rule])
+            .unwrap();
+
+        assert!(*rule_context.get::<bool>("done").unwrap());
+    }
+}
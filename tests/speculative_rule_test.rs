@@ -0,0 +1,89 @@
+#![cfg(feature = "rayon")]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use dredd_rs::rule::*;
+
+    fn tiers(evaluations: Arc<Mutex<Vec<u32>>>) -> Vec<Arc<Mutex<ParallelRule>>> {
+        (0..5u32)
+            .map(|tier| {
+                let evaluations = evaluations.clone();
+                ParallelRule::new()
+                    .with_id(&format!("tier_{tier}"))
+                    .on_eval(move |this| {
+                        evaluations.lock().unwrap().push(tier);
+                        *this.get_rule_context().get::<u32>("tier").unwrap() == tier
+                    })
+                    .on_execute(move |this| this.get_rule_context().set("chosen", tier))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_speculative_runner_matches_best_first_order() {
+        for width in [1, 2, 5, 10] {
+            for tier in 0..6u32 {
+                let runner = Engine::speculative_runner(width);
+                let rule_context = SharedRuleContext::new();
+                rule_context.set("tier", tier);
+
+                runner.run(rule_context.clone(), tiers(Arc::default()));
+
+                let chosen = rule_context.get::<u32>("chosen").map(|chosen| *chosen);
+                assert_eq!(chosen, (tier < 5).then_some(tier), "width {width}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_speculative_runner_evaluates_likely_siblings_early() {
+        let runner = Engine::speculative_runner(2);
+        let evaluations = Arc::new(Mutex::new(Vec::new()));
+        let rules = tiers(evaluations.clone());
+        let rule_context = SharedRuleContext::new();
+        rule_context.set("tier", 4u32);
+
+        // Without hit rates, siblings are evaluated two at a time in order.
+        runner.run(rule_context.clone(), rules.clone());
+        let position = |evaluations: &[u32], tier| evaluations.iter().position(|t| *t == tier);
+        let first_run = std::mem::take(&mut *evaluations.lock().unwrap());
+        assert_eq!(first_run.len(), 5);
+        assert!(position(&first_run, 4) > position(&first_run, 2));
+        assert_eq!(runner.get_hit_rate("tier_4"), Some(1.0));
+        assert_eq!(runner.get_hit_rate("tier_0"), Some(0.0));
+        assert_eq!(runner.get_hit_rate("unknown"), None);
+
+        // `tier_4` now passed every time, so it is evaluated alongside the
+        // first sibling.
+        runner.run(rule_context.clone(), rules);
+        let second_run = evaluations.lock().unwrap().clone();
+        assert!(position(&second_run, 4) < position(&second_run, 2));
+        assert_eq!(*rule_context.get::<u32>("chosen").unwrap(), 4);
+    }
+
+    #[test]
+    fn test_speculative_runner_runs_children_and_discards_eval_writes() {
+        let rule = ParallelRule::new()
+            .on_eval(|this| {
+                this.get_rule_context().set("written_by_eval", true);
+                true
+            })
+            .add_child(
+                ParallelRule::new()
+                    .on_eval(|_| false)
+                    .on_execute(|this| this.get_rule_context().set("first", true)),
+            )
+            .add_child(
+                ParallelRule::new().on_execute(|this| this.get_rule_context().set("second", true)),
+            );
+
+        let rule_context = SharedRuleContext::new();
+        Engine::speculative_runner(3).run(rule_context.clone(), vec![rule]);
+
+        assert!(rule_context.get::<bool>("written_by_eval").is_none());
+        assert!(rule_context.get::<bool>("first").is_none());
+        assert!(*rule_context.get::<bool>("second").unwrap());
+    }
+}
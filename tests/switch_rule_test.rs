@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    fn mark(key: &'static str) -> std::rc::Rc<std::cell::RefCell<BestFirstRule>> {
+        BestFirstRule::new()
+            .with_name(key)
+            .on_execute(move |this| this.get_rule_context().set(key, true))
+    }
+
+    fn switch() -> std::rc::Rc<std::cell::RefCell<BestFirstRule>> {
+        SwitchRule::new("plan")
+            .case("free".to_string(), mark("free"))
+            .case("pro".to_string(), mark("pro"))
+            .default(mark("other"))
+            .wrap(
+                BestFirstRule::new()
+                    .with_name("plan")
+                    .add_child(mark("after")),
+            )
+    }
+
+    fn run(plan: Option<&str>) -> ExecutionTrace {
+        let mut rule_context = RuleContext::new();
+        if let Some(plan) = plan {
+            rule_context.set("plan", plan.to_string());
+        }
+        let report = Engine::best_first_runner().run_with_report(rule_context, vec![switch()]);
+        report.get_trace().clone()
+    }
+
+    #[test]
+    fn test_switch_rule_fires_matching_case() {
+        let trace = run(Some("pro"));
+
+        assert_eq!(trace.get_executed_names(), vec!["plan", "pro", "after"]);
+    }
+
+    #[test]
+    fn test_switch_rule_falls_back_to_default() {
+        assert_eq!(
+            run(Some("enterprise")).get_executed_names(),
+            vec!["plan", "other", "after"]
+        );
+        assert_eq!(
+            run(None).get_executed_names(),
+            vec!["plan", "other", "after"]
+        );
+    }
+
+    #[test]
+    fn test_switch_rule_ignores_other_types() {
+        let rule = SwitchRule::new("tier")
+            .case(1u32, mark("one"))
+            .wrap(BestFirstRule::new());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("tier", 1i64);
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(rule_context.get::<bool>("one").is_none());
+    }
+
+    #[test]
+    fn test_switch_rule_first_matching_case_wins() {
+        let rule = SwitchRule::new("tier")
+            .case(1u32, mark("first"))
+            .case(1u32, mark("second"))
+            .wrap(BestFirstRule::new());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("tier", 1u32);
+        Engine::best_first_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(*rule_context.get::<bool>("first").unwrap());
+        assert!(rule_context.get::<bool>("second").is_none());
+    }
+
+    #[test]
+    fn test_switch_rule_branches() {
+        let switch = SwitchRule::new("plan")
+            .case("free", mark("free"))
+            .default(mark("other"));
+
+        assert_eq!(switch.get_key(), "plan");
+        let names: Vec<_> = switch
+            .get_branches()
+            .iter()
+            .map(|rule| rule.borrow().get_name().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["free", "other"]);
+    }
+}
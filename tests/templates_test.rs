@@ -0,0 +1,175 @@
+#![cfg(feature = "templates")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use dredd_rs::templates::{LocalizedTemplates, Templates};
+    use serde_json::json;
+
+    fn templates() -> Templates {
+        let mut templates = Templates::new();
+        templates
+            .add("approved.txt", "Hello {{ name }}! Items: {% for item in order.items %}{{ item }}{% if not loop.last %}, {% endif %}{% endfor %}")
+            .unwrap();
+        templates.add("note.html", "<p>{{ note }}</p>").unwrap();
+        templates
+    }
+
+    #[test]
+    fn test_render_action_writes_result() {
+        let templates = templates();
+        assert!(templates.contains("approved.txt"));
+        assert!(!templates.contains("missing.txt"));
+
+        let rule = BestFirstRule::new()
+            .on_execute(templates.render_action("approved.txt", "message"))
+            .add_child(
+                BestFirstRule::new().on_execute(templates.render_action("note.html", "note_html")),
+            );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("name", "Ana".to_string());
+        rule_context.set("order", json!({ "items": ["book", "pen"] }));
+        rule_context.set("note", "<b>fragile</b>");
+
+        Engine::best_first_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap();
+        assert_eq!(
+            *rule_context.get::<String>("message").unwrap(),
+            "Hello Ana! Items: book, pen"
+        );
+        assert_eq!(
+            *rule_context.get::<String>("note_html").unwrap(),
+            "<p>&lt;b&gt;fragile&lt;&#x2f;b&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let mut templates = templates();
+        assert!(templates.add("broken", "{% if %}").is_err());
+
+        let rule = ChainRule::new().on_execute(templates.render_action("approved.txt", "message"));
+        let result = Engine::chain_runner().try_run(RuleContext::new(), vec![rule]);
+        let Err(RuleError::Failed { message, .. }) = result else {
+            panic!("expected a failure, got {result:?}");
+        };
+        assert!(
+            message.starts_with("template `approved.txt`: undefined value"),
+            "{message}"
+        );
+
+        let error = templates
+            .render("missing.txt", &RuleContext::new().borrow())
+            .unwrap_err();
+        assert!(error.to_string().contains("template `missing.txt`"));
+    }
+
+    fn localized() -> LocalizedTemplates {
+        let mut templates = LocalizedTemplates::new("en");
+        templates
+            .add(
+                "en",
+                "declined",
+                "Declined: {{ total | number }} over the limit.",
+            )
+            .unwrap();
+        templates
+            .add(
+                "pt",
+                "declined",
+                "Recusado: {{ total | number }} acima do limite.",
+            )
+            .unwrap();
+        templates
+            .add(
+                "pt-PT",
+                "declined",
+                "Recusada: {{ total | number }} acima do limite.",
+            )
+            .unwrap();
+        templates
+            .add(
+                "fr",
+                "declined",
+                "Refusée : {{ total | number(1) }} au-dessus de la limite.",
+            )
+            .unwrap();
+        templates
+    }
+
+    #[test]
+    fn test_localized_templates_fall_back_by_locale() {
+        let templates = localized();
+
+        assert_eq!(
+            templates.resolve_locale("declined", "pt_BR").as_deref(),
+            Some("pt")
+        );
+        assert_eq!(
+            templates.resolve_locale("declined", "PT-pt").as_deref(),
+            Some("pt-pt")
+        );
+        assert_eq!(
+            templates.resolve_locale("declined", "ja").as_deref(),
+            Some("en")
+        );
+        assert_eq!(templates.resolve_locale("missing", "en"), None);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", -1234567.891);
+        let render = |locale| {
+            templates
+                .render("declined", locale, &rule_context.borrow())
+                .unwrap()
+        };
+        assert_eq!(render("en-US"), "Declined: -1,234,567.89 over the limit.");
+        assert_eq!(render("pt-BR"), "Recusado: -1.234.567,89 acima do limite.");
+        assert_eq!(render("pt-PT"), "Recusada: -1.234.567,89 acima do limite.");
+        assert_eq!(
+            render("fr"),
+            "Refusée : -1\u{202f}234\u{202f}567,9 au-dessus de la limite."
+        );
+    }
+
+    #[test]
+    fn test_localized_render_action_reads_locale_key() {
+        let templates = localized();
+        let rule = AllRule::new().on_execute(templates.render_action("declined", "reason"));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 1500);
+        Engine::all_runner()
+            .try_run(rule_context.clone(), vec![rule.clone()])
+            .unwrap();
+        assert_eq!(
+            *rule_context.get::<String>("reason").unwrap(),
+            "Declined: 1,500 over the limit."
+        );
+
+        rule_context.set("locale", "pt-BR".to_string());
+        Engine::all_runner()
+            .try_run(rule_context.clone(), vec![rule])
+            .unwrap();
+        assert_eq!(
+            *rule_context.get::<String>("reason").unwrap(),
+            "Recusado: 1.500 acima do limite."
+        );
+    }
+
+    #[test]
+    fn test_localized_missing_template_fails_rule() {
+        let templates = LocalizedTemplates::new("en");
+        let rule = AllRule::new().on_execute(templates.render_action("declined", "reason"));
+
+        let result = Engine::all_runner().try_run(RuleContext::new(), vec![rule]);
+
+        assert_eq!(
+            result,
+            Err(RuleError::failed(
+                "template `declined`: no version for locale `en` or `en`"
+            ))
+        );
+    }
+}
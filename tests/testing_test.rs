@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+    use dredd_rs::testing::{assert_io_contract, RuleTest};
+
+    fn discount_rule() -> Rc<RefCell<BestFirstRule>> {
+        BestFirstRule::new()
+            .with_name("discount")
+            .on_eval(|ctx| *ctx.get::<u32>("total").unwrap() > 100)
+            .add_children(vec![
+                BestFirstRule::new()
+                    .with_name("vip")
+                    .on_eval(|ctx| *ctx.get::<bool>("vip").unwrap())
+                    .on_execute(|this| this.get_rule_context().set("discount", 20u32)),
+                BestFirstRule::new()
+                    .with_name("regular")
+                    .on_execute(|this| this.get_rule_context().set("discount", 5u32)),
+            ])
+    }
+
+    #[test]
+    fn test_rule_test_given_when_then() {
+        RuleTest::given(|ctx| {
+            ctx.set("total", 150u32);
+            ctx.set("vip", false);
+        })
+        .when_fired(discount_rule())
+        .then(|ctx, outcome| {
+            outcome.assert_fired("discount");
+            outcome.assert_fired("regular");
+            outcome.assert_not_fired("vip");
+            outcome.assert_changed("discount");
+            outcome.assert_unchanged("total");
+            assert_eq!(outcome.get_changed_keys(), vec!["discount"]);
+            assert_eq!(*ctx.get::<u32>("discount").unwrap(), 5);
+        })
+        .then(|_, outcome| {
+            assert_eq!(outcome.get_trace().get_entries().len(), 3);
+        });
+    }
+
+    #[test]
+    fn test_rule_test_nothing_changes_on_eval_false() {
+        RuleTest::given(|ctx| ctx.set("total", 10u32))
+            .when_fired(discount_rule())
+            .then(|_, outcome| {
+                outcome.assert_not_fired("discount");
+                assert!(outcome.get_changed_keys().is_empty());
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "expected rule `vip` to fire")]
+    fn test_rule_test_assert_fired_fails() {
+        RuleTest::given(|ctx| {
+            ctx.set("total", 150u32);
+            ctx.set("vip", false);
+        })
+        .when_fired(discount_rule())
+        .then(|_, outcome| outcome.assert_fired("vip"));
+    }
+
+    fn discount_usage() -> KeyUsage {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("total", 150u32);
+        rule_context.set("vip", true);
+        KeyUsage::record(
+            &Engine::best_first_runner(),
+            rule_context,
+            vec![discount_rule()],
+        )
+    }
+
+    #[test]
+    fn test_assert_io_contract_accepts_keys_inside_contract() {
+        assert_io_contract(
+            &discount_usage(),
+            &["total", "vip", "country"],
+            &["discount"],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reads [\"vip\"] and writes [] outside of it")]
+    fn test_assert_io_contract_fails_on_new_read() {
+        assert_io_contract(&discount_usage(), &["total"], &["discount"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "reads [] and writes [\"discount\"] outside of it")]
+    fn test_assert_io_contract_fails_on_new_write() {
+        assert_io_contract(&discount_usage(), &["total", "vip"], &[]);
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    const SIGNALS: [&str; 7] = [
+        "new_device",
+        "foreign_ip",
+        "vpn",
+        "night_time",
+        "large_amount",
+        "new_payee",
+        "velocity",
+    ];
+
+    fn risk_rule() -> Rc<RefCell<AllRule>> {
+        let signals = SIGNALS
+            .iter()
+            .map(|key| {
+                AllRule::new()
+                    .with_name(key)
+                    .on_eval(move |ctx| ctx.get::<bool>(key).is_some())
+                    .on_execute(move |this| this.get_rule_context().push_to_list("flags", *key))
+            })
+            .collect();
+        ThresholdRule::new("risk_signals", 3, signals).wrap(
+            AllRule::new()
+                .with_name("risk")
+                .on_post_execute(|this| this.get_rule_context().set("scored", true)),
+        )
+    }
+
+    fn run(keys: &[&'static str]) -> (Rc<RefCell<RuleContext>>, RunReport) {
+        let mut rule_context = RuleContext::new();
+        for key in keys {
+            rule_context.set(key, true);
+        }
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![risk_rule()]);
+        (rule_context, report)
+    }
+
+    #[test]
+    fn test_threshold_met_fires_matching_children() {
+        let (rule_context, report) = run(&["vpn", "new_device", "velocity"]);
+
+        assert_eq!(*rule_context.get::<usize>("risk_signals").unwrap(), 3);
+        assert_eq!(
+            *rule_context.get_list::<&str>("flags").unwrap(),
+            vec!["new_device", "vpn", "velocity"]
+        );
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["risk", "new_device", "vpn", "velocity"]
+        );
+        assert!(*rule_context.get::<bool>("scored").unwrap());
+    }
+
+    #[test]
+    fn test_threshold_not_met_records_the_count_only() {
+        let (rule_context, report) = run(&["vpn", "velocity"]);
+
+        assert_eq!(*rule_context.get::<usize>("risk_signals").unwrap(), 2);
+        assert!(rule_context.get_list::<&str>("flags").is_none());
+        assert_eq!(report.get_trace().get_executed_names(), vec!["risk"]);
+        assert!(*rule_context.get::<bool>("scored").unwrap());
+    }
+
+    #[test]
+    fn test_threshold_stops_on_failure() {
+        let failing = AllRule::new().on_eval(|ctx| {
+            ctx.fail(RuleError::failed("no device data"));
+            false
+        });
+        let threshold = ThresholdRule::new("matched", 1, vec![failing]);
+        assert_eq!(threshold.get_key(), "matched");
+        assert_eq!(threshold.get_threshold(), 1);
+        assert_eq!(threshold.get_children().len(), 1);
+
+        let rule_context = RuleContext::new();
+        let result = Engine::all_runner()
+            .try_run(rule_context.clone(), vec![threshold.wrap(AllRule::new())]);
+
+        assert_eq!(result, Err(RuleError::failed("no device data")));
+        assert!(rule_context.get::<usize>("matched").is_none());
+    }
+
+    #[test]
+    fn test_threshold_evaluates_children_once() {
+        let evaluations = Rc::new(RefCell::new(0));
+        let signal = |key: &'static str| {
+            let evaluations = evaluations.clone();
+            AllRule::new()
+                .with_name(key)
+                .on_eval(move |ctx| {
+                    *evaluations.borrow_mut() += 1;
+                    ctx.get::<bool>(key).is_some()
+                })
+                .on_execute(move |this| this.get_rule_context().push_to_list("flags", key))
+        };
+        let disabled = signal("velocity");
+        disabled.borrow().get_metadata().get_handle().disable();
+        let rule = ThresholdRule::new(
+            "matched",
+            2,
+            vec![signal("vpn"), signal("new_payee"), disabled],
+        )
+        .wrap(AllRule::new());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("vpn", true);
+        rule_context.set("new_payee", true);
+        rule_context.set("velocity", true);
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        // The disabled child is neither evaluated nor counted.
+        assert_eq!(*evaluations.borrow(), 2);
+        assert_eq!(*rule_context.get::<usize>("matched").unwrap(), 2);
+        assert_eq!(
+            *rule_context.get_list::<&str>("flags").unwrap(),
+            vec!["vpn", "new_payee"]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_best_first_run_report() {
+        // Rule1      Rule2
+        //   |
+        // Rule3  ->  Rule4
+        let rule3 = BestFirstRule::new().with_name("rule3").on_eval(|_| false);
+        let rule4 = BestFirstRule::new().with_name("rule4").on_eval(|_| true);
+        let rule1 = BestFirstRule::new()
+            .with_id("R-1")
+            .with_name("rule1")
+            .on_eval(|_| true)
+            .add_children(vec![rule3, rule4]);
+        let rule2 = BestFirstRule::new().with_name("rule2").on_eval(|_| true);
+
+        let report =
+            Engine::best_first_runner().run_with_report(RuleContext::new(), vec![rule1, rule2]);
+        let entries = report.get_trace().get_entries();
+
+        let names: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.get_name().unwrap())
+            .collect();
+        let depths: Vec<_> = entries.iter().map(|entry| entry.get_depth()).collect();
+        let evals: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.get_eval_result())
+            .collect();
+
+        assert_eq!(names, vec!["rule1", "rule3", "rule4"]);
+        assert_eq!(depths, vec![0, 1, 1]);
+        assert_eq!(evals, vec![true, false, true]);
+        assert_eq!(entries[0].get_id(), Some("R-1"));
+        assert!(entries[0].get_duration() >= entries[2].get_duration());
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["rule1", "rule4"]
+        );
+        assert!(report.get_duration() >= entries[0].get_duration());
+    }
+
+    #[test]
+    fn test_chain_run_report_stops_on_eval_false() {
+        let rule = ChainRule::new().with_name("rule1").add_child(
+            ChainRule::new()
+                .with_name("rule2")
+                .on_eval(|_| false)
+                .add_child(ChainRule::new().with_name("rule3")),
+        );
+
+        let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+
+        let names: Vec<_> = report
+            .get_trace()
+            .get_entries()
+            .iter()
+            .map(|entry| entry.get_name().unwrap())
+            .collect();
+        assert_eq!(names, vec!["rule1", "rule2"]);
+        assert_eq!(report.get_trace().get_executed_names(), vec!["rule1"]);
+    }
+
+    #[test]
+    fn test_run_without_report_does_not_trace() {
+        let rule_context = RuleContext::new();
+        let rule = AllRule::new().with_name("rule1");
+
+        Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+        let report = Engine::all_runner().run_with_report(rule_context, vec![rule]);
+
+        assert_eq!(report.get_trace().get_entries().len(), 1);
+    }
+
+    #[test]
+    fn test_nested_run_report_only_contains_nested_rules() {
+        let rule = AllRule::new().with_name("outer").on_execute(|this| {
+            let inner = ChainRule::new().with_name("inner");
+            let report =
+                Engine::chain_runner().run_with_report(this.get_rule_context(), vec![inner]);
+            let names = report.get_trace().get_executed_names().join(",");
+            this.get_rule_context().set("inner_report", names);
+        });
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![rule]);
+
+        assert_eq!(
+            *rule_context.get::<String>("inner_report").unwrap(),
+            "inner"
+        );
+        assert_eq!(
+            report.get_trace().get_executed_names(),
+            vec!["outer", "inner"]
+        );
+        assert_eq!(report.get_trace().get_entries()[1].get_depth(), 1);
+    }
+
+    #[test]
+    fn test_trace_entry_outcomes() {
+        let rules = vec![
+            AllRule::new().with_name("fired"),
+            AllRule::new()
+                .with_name("not_applicable")
+                .on_eval(|_| false),
+            AllRule::new()
+                .with_name("over_budget")
+                .with_cost(10)
+                .with_optional(true),
+            Canary::wrap(AllRule::new().with_name("canary")),
+            // The run stops at the first failure.
+            AllRule::new()
+                .with_name("errored")
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("boom"))),
+        ];
+
+        let report = Engine::all_runner().run_with_budget(
+            RuleContext::new(),
+            rules,
+            Budget::new().with_max_cost(5),
+        );
+        let outcomes: Vec<_> = report
+            .get_trace()
+            .get_entries()
+            .iter()
+            .map(|entry| (entry.get_name().unwrap(), entry.get_outcome()))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("fired", RuleOutcome::Fired),
+                ("not_applicable", RuleOutcome::NotApplicable),
+                ("over_budget", RuleOutcome::Skipped("budget")),
+                ("canary", RuleOutcome::Skipped("canary")),
+                ("errored", RuleOutcome::Errored),
+            ]
+        );
+        let skipped = report
+            .get_trace()
+            .get_by_outcome(RuleOutcome::Skipped("budget"));
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(
+            RuleOutcome::Skipped("budget").to_string(),
+            "skipped (budget)"
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let rule = AllRule::new().with_name("order").add_children(vec![
+            AllRule::new()
+                .with_name("discount")
+                .add_children(vec![AllRule::new().with_id("D-1")]),
+            AllRule::new().with_name("\"vip\"").on_eval(|_| false),
+            AllRule::new().on_execute(|this| {
+                this.get_rule_context().fail(RuleError::failed("no stock"));
+            }),
+        ]);
+        let skipped = Canary::wrap(AllRule::new().with_name("new pricing"));
+
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), vec![skipped, rule]);
+        let chart = report.get_trace().to_mermaid();
+        let lines: Vec<&str> = chart.lines().collect();
+
+        assert_eq!(
+            lines[..16],
+            [
+                "flowchart TD",
+                "    r0[\"new pricing<br/>skipped (canary)\"]",
+                "    r1[\"order<br/>errored\"]",
+                "    r2[\"discount<br/>fired\"]",
+                "    r1 --> r2",
+                "    r3[\"D-1<br/>fired\"]",
+                "    r2 --> r3",
+                "    r4[\"#quot;vip#quot;<br/>not applicable\"]",
+                "    r1 --> r4",
+                "    r5[\"rule 5<br/>errored\"]",
+                "    r1 --> r5",
+                "    class r0 skipped",
+                "    class r1 errored",
+                "    class r2 fired",
+                "    class r3 fired",
+                "    class r4 not_applicable",
+            ]
+        );
+        assert!(lines.contains(&"    class r5 errored"));
+        assert!(lines.contains(&"    classDef fired fill:#d4edda,stroke:#28a745"));
+        assert_eq!(lines.len(), 21);
+    }
+}
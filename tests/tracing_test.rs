@@ -0,0 +1,138 @@
+#![cfg(feature = "tracing")]
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        fmt,
+        sync::{Arc, Mutex},
+    };
+
+    use dredd_rs::rule::*;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    #[derive(Debug, Clone)]
+    struct RecordedSpan {
+        name: &'static str,
+        parent: Option<usize>,
+        fields: BTreeMap<String, String>,
+    }
+
+    impl Visit for RecordedSpan {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.fields
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    /// Records every span with its fields and the span it was created in.
+    #[derive(Default, Clone)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        stack: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+            let mut span = RecordedSpan {
+                name: attributes.metadata().name(),
+                parent: self.stack.lock().unwrap().last().copied(),
+                fields: BTreeMap::new(),
+            };
+            attributes.record(&mut span);
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, id: &Id, values: &Record<'_>) {
+            values.record(&mut self.spans.lock().unwrap()[id.into_u64() as usize - 1]);
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, id: &Id) {
+            self.stack.lock().unwrap().push(id.into_u64() as usize - 1);
+        }
+
+        fn exit(&self, _: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    fn record(run: impl FnOnce()) -> Vec<RecordedSpan> {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), run);
+        let spans = recorder.spans.lock().unwrap().clone();
+        spans
+    }
+
+    #[test]
+    fn test_spans_per_rule_fire() {
+        let spans = record(|| {
+            let rule = AllRule::new()
+                .with_name("checkout")
+                .with_id("R1")
+                .add_children(vec![
+                    AllRule::new().with_name("fraud_check").on_eval(|_| false),
+                    AllRule::new().with_name("charge").on_execute(|this| {
+                        this.get_rule_context().fail(RuleError::failed("declined"))
+                    }),
+                ]);
+            let _ = Engine::all_runner().try_run(RuleContext::new(), vec![rule]);
+        });
+
+        let rules: Vec<_> = spans.iter().filter(|span| span.name == "rule").collect();
+        let fields: Vec<_> = rules
+            .iter()
+            .map(|span| {
+                (
+                    span.fields["name"].as_str(),
+                    span.fields["depth"].as_str(),
+                    span.fields["outcome"].as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                // The failure stops the parent as well.
+                ("checkout", "0", "failed"),
+                ("fraud_check", "1", "skipped"),
+                ("charge", "1", "failed"),
+            ]
+        );
+        assert_eq!(rules[0].fields["id"], "R1");
+
+        // Children are fired within the `children` phase of their parent.
+        let children = spans[rules[1].parent.unwrap()].clone();
+        assert_eq!(children.name, "children");
+        assert_eq!(spans[children.parent.unwrap()].name, "rule");
+
+        let phases: Vec<_> = spans
+            .iter()
+            .filter(|span| {
+                span.parent.is_some_and(|parent| {
+                    spans[parent].fields.get("name").map(String::as_str) == Some("charge")
+                })
+            })
+            .map(|span| span.name)
+            .collect();
+        assert_eq!(phases, vec!["eval", "pre_execute", "execute"]);
+    }
+}
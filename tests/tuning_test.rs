@@ -0,0 +1,75 @@
+#![cfg(all(feature = "serde", feature = "expr"))]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::sampling::Sample;
+    use dredd_rs::tuning::ThresholdTuner;
+    use serde_json::{json, Value};
+
+    fn sample(input: Value, approved: bool) -> Sample {
+        Sample::new(
+            input.as_object().unwrap().clone(),
+            vec![],
+            json!({ "approved": approved }).as_object().unwrap().clone(),
+        )
+    }
+
+    fn accuracy(sample: &Sample, matched: bool) -> f64 {
+        (sample.get_outputs()["approved"] == json!(matched)) as u8 as f64
+    }
+
+    #[test]
+    fn test_tuner_ranks_candidates() {
+        let samples: Vec<_> = (0..100i64)
+            .map(|age| sample(json!({ "age": age }), age >= 21))
+            .collect();
+
+        let report = ThresholdTuner::new("age >= threshold", "threshold")
+            .unwrap()
+            .with_candidates([16.0, 18.0, 21.0, 25.0])
+            .tune(&samples, accuracy);
+
+        let best = report.get_best().unwrap();
+        assert_eq!(best.get_threshold(), 21.0);
+        assert_eq!(best.get_mean(), 1.0);
+        assert_eq!(best.get_samples(), 100);
+        assert_eq!(best.get_matched(), 79);
+        assert_eq!(best.get_lower(), 1.0);
+
+        let thresholds: Vec<_> = report
+            .get_results()
+            .iter()
+            .map(|result| result.get_threshold())
+            .collect();
+        assert_eq!(thresholds, vec![21.0, 18.0, 25.0, 16.0]);
+
+        let second = &report.get_results()[1];
+        assert_eq!(second.get_mean(), 0.97);
+        assert!(second.get_lower() < 0.97 && second.get_upper() > 0.97);
+        assert!(report.to_string().contains("21.0000"));
+    }
+
+    #[test]
+    fn test_tuner_range_and_missing_keys() {
+        let samples = vec![
+            sample(json!({ "score": 0.8 }), true),
+            sample(json!({ "score": 0.3 }), false),
+            sample(json!({}), false),
+        ];
+
+        let report = ThresholdTuner::new("score > threshold", "threshold")
+            .unwrap()
+            .with_range(0.0, 1.0, 4)
+            .tune(&samples, accuracy);
+
+        assert_eq!(report.get_results().len(), 5);
+        let best = report.get_best().unwrap();
+        assert_eq!(best.get_threshold(), 0.5);
+        assert_eq!(best.get_mean(), 1.0);
+    }
+
+    #[test]
+    fn test_tuner_rejects_invalid_condition() {
+        assert!(ThresholdTuner::new("score >", "threshold").is_err());
+    }
+}
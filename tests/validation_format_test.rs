@@ -0,0 +1,48 @@
+#![cfg(feature = "regex")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+    use dredd_rs::validation::{self, ViolationKind};
+
+    const EMAIL: &str = r"^[^@\s]+@[^@\s]+\.[a-z]+$";
+
+    #[test]
+    fn test_matches_format() {
+        let rule = || validation::matches_format("email", EMAIL).unwrap();
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("email", "ana@example.com");
+        Engine::all_runner().run(rule_context.clone(), vec![rule()]);
+        assert!(validation::get_violations(&rule_context).is_empty());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("email", "ana at example.com".to_string());
+        Engine::all_runner().run(rule_context.clone(), vec![rule()]);
+        let violations = validation::get_violations(&rule_context);
+        assert_eq!(violations[0].get_kind(), ViolationKind::InvalidFormat);
+        assert_eq!(
+            violations[0].get_message(),
+            format!(r#"`email` must match `{EMAIL}`, got "ana at example.com""#)
+        );
+    }
+
+    #[test]
+    fn test_matches_format_wrong_type_and_missing_key() {
+        let rule = || validation::matches_format("zip", r"^\d{5}$").unwrap();
+
+        Engine::all_runner().run(RuleContext::new(), vec![rule()]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("zip", 12345u32);
+        Engine::all_runner().run(rule_context.clone(), vec![rule()]);
+        let violations = validation::get_violations(&rule_context);
+        assert_eq!(violations[0].get_kind(), ViolationKind::WrongType);
+        assert_eq!(violations[0].get_message(), "`zip` must be a string");
+    }
+
+    #[test]
+    fn test_matches_format_invalid_pattern() {
+        assert!(validation::matches_format("zip", r"^\d{5").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+    use dredd_rs::validation::{self, Violation, ViolationKind, VIOLATIONS};
+
+    fn validate(
+        rules: Vec<Rc<RefCell<AllRule>>>,
+        rule_context: Rc<RefCell<RuleContext>>,
+    ) -> Vec<Violation> {
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), rules);
+        assert!(report.get_error().is_none());
+        validation::get_violations(&rule_context)
+    }
+
+    #[test]
+    fn test_require_key() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("name", "Ana".to_string());
+
+        let violations = validate(
+            vec![
+                validation::require_key("name"),
+                validation::require_key("email"),
+            ],
+            rule_context,
+        );
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].get_key(), "email");
+        assert_eq!(violations[0].get_kind(), ViolationKind::Missing);
+        assert_eq!(violations[0].get_message(), "`email` is required");
+    }
+
+    #[test]
+    fn test_in_range() {
+        let rules = || {
+            vec![
+                validation::in_range("age", 18u32..=120),
+                validation::in_range("score", ..1.0f64),
+            ]
+        };
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 18u32);
+        rule_context.set("score", 0.5f64);
+        assert!(validate(rules(), rule_context).is_empty());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 121u32);
+        rule_context.set("score", 1.0f64);
+        let violations = validate(rules(), rule_context);
+        assert_eq!(
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "`age` must be in 18..=120, got 121",
+                "`score` must be in ..1.0, got 1.0"
+            ]
+        );
+        assert!(violations
+            .iter()
+            .all(|violation| violation.get_kind() == ViolationKind::OutOfRange));
+    }
+
+    #[test]
+    fn test_one_of() {
+        let rule = || validation::one_of("plan", ["free".to_string(), "pro".to_string()]);
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan", "pro".to_string());
+        assert!(validate(vec![rule()], rule_context).is_empty());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan", "gold".to_string());
+        let violations = validate(vec![rule()], rule_context);
+        assert_eq!(violations[0].get_kind(), ViolationKind::NotAllowed);
+        assert_eq!(
+            violations[0].get_message(),
+            r#"`plan` must be one of ["free", "pro"], got "gold""#
+        );
+    }
+
+    #[test]
+    fn test_optional_keys_and_wrong_types() {
+        let rules = || {
+            vec![
+                validation::in_range("age", 18u32..=120),
+                validation::one_of("plan", ["free", "pro"]),
+            ]
+        };
+
+        assert!(validate(rules(), RuleContext::new()).is_empty());
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 30i64);
+        let violations = validate(rules(), rule_context);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].get_kind(), ViolationKind::WrongType);
+        assert_eq!(violations[0].get_message(), "`age` must be of type `u32`");
+    }
+
+    #[test]
+    fn test_validation_rules_apply_only_on_violation() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 40u32);
+
+        let report = Engine::all_runner().run_with_report(
+            rule_context.clone(),
+            vec![
+                validation::require_key("age"),
+                validation::in_range("age", 0u32..18),
+            ],
+        );
+
+        let trace = report.get_trace();
+        assert_eq!(trace.get_by_outcome(RuleOutcome::NotApplicable).len(), 1);
+        assert_eq!(trace.get_by_outcome(RuleOutcome::Fired).len(), 1);
+        assert_eq!(
+            trace.get_by_outcome(RuleOutcome::Fired)[0].get_name(),
+            Some("in_range(age)")
+        );
+        assert_eq!(
+            rule_context
+                .get_list::<Violation>(VIOLATIONS)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_violations_are_errors() {
+        let report = Engine::all_runner()
+            .run_with_report(RuleContext::new(), vec![validation::require_key("email")]);
+
+        assert_eq!(report.get_status(), RunStatus::Fail);
+        assert!(report.get_error().is_none());
+        assert_eq!(report.get_warnings().len(), 1);
+        assert_eq!(
+            report.get_warnings()[0].to_string(),
+            "critical in `require_key(email)`: `email` is required"
+        );
+    }
+
+    #[test]
+    fn test_as_warning() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 15u32);
+        let rules = || {
+            vec![
+                validation::as_warning(validation::require_key("phone")),
+                validation::as_warning(validation::in_range("age", 18u32..)),
+            ]
+        };
+
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), rules());
+
+        assert_eq!(report.get_status(), RunStatus::PassWithWarnings);
+        let severities: Vec<Severity> = report
+            .get_warnings()
+            .iter()
+            .map(Warning::get_severity)
+            .collect();
+        assert_eq!(severities, [Severity::Warning, Severity::Warning]);
+        let violations = validation::get_violations(&rule_context);
+        assert_eq!(violations.len(), 2);
+        assert!(violations
+            .iter()
+            .all(|violation| violation.get_severity() == Severity::Warning));
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("phone", "555-0100");
+        rule_context.set("age", 30u32);
+        let report = Engine::all_runner().run_with_report(rule_context, rules());
+        assert_eq!(report.get_status(), RunStatus::Pass);
+    }
+
+    #[test]
+    fn test_warnings_and_errors_mixed() {
+        let mut rule_context = RuleContext::new();
+        rule_context.set("plan", "enterprise");
+
+        let report = Engine::all_runner().run_with_report(
+            rule_context.clone(),
+            vec![
+                validation::as_warning(validation::one_of("plan", ["free", "pro"])),
+                validation::require_key("email"),
+            ],
+        );
+
+        assert_eq!(report.get_status(), RunStatus::Fail);
+        let severities: Vec<Severity> = validation::get_violations(&rule_context)
+            .iter()
+            .map(Violation::get_severity)
+            .collect();
+        assert_eq!(severities, [Severity::Warning, Severity::Critical]);
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_verdicts_returned_by_runner() {
+        let rule = BestFirstRule::new()
+            .on_eval(|_| true)
+            .add_child(
+                BestFirstRule::new()
+                    .with_name("young")
+                    .on_eval(|ctx| *ctx.get::<u32>("age").unwrap() < 18)
+                    .on_execute(|this| this.get_rule_context().deny("under age")),
+            )
+            .add_child(
+                BestFirstRule::new()
+                    .with_name("adult")
+                    .on_eval(|_| true)
+                    .on_execute(|this| {
+                        let mut rule_context = this.get_rule_context();
+                        rule_context.approve();
+                        rule_context.score(0.5);
+                        rule_context.score(0.25);
+                    }),
+            );
+
+        let mut rule_context = RuleContext::new();
+        rule_context.set("age", 30u32);
+        let report = Engine::best_first_runner().run_with_report(rule_context.clone(), vec![rule]);
+        let verdicts = report.get_verdicts();
+
+        assert!(verdicts.is_approved());
+        assert!(!verdicts.is_denied());
+        assert_eq!(verdicts.get_score(), 0.75);
+        assert_eq!(verdicts.get_all().len(), 3);
+        assert_eq!(verdicts.get_all()[0].get_verdict(), &Verdict::Approve);
+        assert_eq!(verdicts.get_all()[0].get_rule(), Some("adult"));
+        // The verdicts of a run are moved to its report.
+        assert!(rule_context.get_verdicts().is_empty());
+    }
+
+    #[test]
+    fn test_denial_wins_over_approvals() {
+        let rules = vec![
+            AllRule::new().on_execute(|this| this.get_rule_context().approve()),
+            AllRule::new().with_id("R-7").on_execute(|this| {
+                this.get_rule_context().deny("blocked card");
+                this.get_rule_context().deny("chargebacks");
+            }),
+        ];
+
+        let report = Engine::all_runner().run_with_report(RuleContext::new(), rules);
+        let verdicts = report.get_verdicts();
+
+        assert!(!verdicts.is_approved());
+        assert_eq!(verdicts.get_denials(), vec!["blocked card", "chargebacks"]);
+        assert_eq!(verdicts.get_score(), 0.0);
+        assert_eq!(
+            verdicts.get_all()[1].to_string(),
+            "deny in `R-7`: blocked card"
+        );
+    }
+
+    #[test]
+    fn test_verdicts_outside_runs() {
+        let mut rule_context = RuleContext::new();
+        rule_context.push_verdict(Verdict::Score(2.0));
+        rule_context.approve();
+
+        let verdicts = rule_context.get_verdicts();
+        assert_eq!(verdicts.get_all()[0].get_rule(), None);
+        assert_eq!(verdicts.get_all()[0].to_string(), "score 2");
+        assert_eq!(verdicts.get_all()[1].to_string(), "approve");
+
+        assert_eq!(rule_context.take_verdicts(), verdicts);
+        assert!(rule_context.get_verdicts().is_empty());
+        assert!(!Verdicts::default().is_approved());
+    }
+
+    #[test]
+    fn test_verdicts_rolled_back_with_the_run() {
+        let rules = vec![
+            AllRule::new().on_execute(|this| this.get_rule_context().approve()),
+            AllRule::new()
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("boom"))),
+        ];
+
+        let mut rule_context = RuleContext::new();
+        rule_context.score(1.0);
+        let result = Engine::all_runner().try_run_atomic(rule_context.clone(), rules);
+
+        assert!(result.is_err());
+        assert_eq!(rule_context.get_verdicts().get_all().len(), 1);
+        assert!(!rule_context.get_verdicts().is_approved());
+    }
+
+    #[test]
+    fn test_verdicts_of_compensated_rules_dropped() {
+        let rules = vec![
+            AllRule::new()
+                .with_name("reserve")
+                .on_execute(|this| this.get_rule_context().approve())
+                .on_compensate(|_| {}),
+            AllRule::new()
+                .with_name("charge")
+                .on_execute(|this| this.get_rule_context().fail(RuleError::failed("declined"))),
+        ];
+
+        let report = Engine::all_runner().run_with_compensation(RuleContext::new(), rules);
+
+        assert_eq!(report.get_compensated(), ["reserve"]);
+        assert!(report.get_verdicts().is_empty());
+    }
+
+    #[test]
+    fn test_verdicts_of_scopes_and_canaries() {
+        let rule_context = RuleContext::new();
+        let mut scope = RuleContext::child_scope(&rule_context);
+        scope.deny("kept");
+        scope.borrow_mut().merge_scope();
+        let mut scope = RuleContext::child_scope(&rule_context);
+        scope.deny("dropped");
+        scope.borrow_mut().discard_scope();
+
+        assert_eq!(rule_context.get_verdicts().get_denials(), vec!["kept"]);
+
+        let rule = Canary::wrap(
+            AllRule::new().on_execute(|this| this.get_rule_context().deny("would deny")),
+        );
+        let report = Engine::all_runner().run_with_report(rule_context, vec![rule]);
+        let canary = report.get_trace().get_canaries()[0].get_canary().unwrap();
+
+        assert_eq!(canary.get_verdicts().get_denials(), vec!["would deny"]);
+        assert!(report.get_verdicts().is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use dredd_rs::rule::*;
+
+    #[test]
+    fn test_warnings_do_not_fail_run() {
+        let rules = vec![
+            AllRule::new().with_name("first").on_execute(|this| {
+                this.get_rule_context()
+                    .warn(Severity::Info, "coerced `age` to an integer")
+            }),
+            AllRule::new()
+                .with_name("second")
+                .on_execute(|this| this.get_rule_context().set("done", true)),
+        ];
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), rules);
+
+        assert!(report.get_error().is_none());
+        assert!(*rule_context.get::<bool>("done").unwrap());
+        assert_eq!(report.get_warnings().len(), 1);
+        let warning = &report.get_warnings()[0];
+        assert_eq!(warning.get_severity(), Severity::Info);
+        assert_eq!(warning.get_message(), "coerced `age` to an integer");
+        assert_eq!(warning.get_rule(), Some("first"));
+        // The report takes the warnings out of the context.
+        assert!(rule_context.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_warning_names_innermost_rule() {
+        let rule = ChainRule::new().with_name("parent").add_child(
+            ChainRule::new().with_id("R-002").on_execute(|this| {
+                this.get_rule_context()
+                    .warn(Severity::Critical, "stale data")
+            }),
+        );
+
+        let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+
+        assert_eq!(report.get_warnings()[0].get_rule(), Some("R-002"));
+        assert_eq!(
+            report.get_warnings()[0].to_string(),
+            "critical in `R-002`: stale data"
+        );
+    }
+
+    #[test]
+    fn test_warnings_stay_in_context_without_report() {
+        let rule = AllRule::new().with_name("legacy").on_execute(|this| {
+            this.get_rule_context()
+                .warn(Severity::Warning, "deprecated")
+        });
+
+        let mut rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        let warnings = rule_context.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].get_rule(), None);
+        assert_eq!(warnings[0].to_string(), "warning: deprecated");
+        assert!(rule_context.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_duration_limit_rule_warns_near_limit() {
+        let rule = ChainRule::new().with_name("slow").on_execute(|this| {
+            thread::sleep(Duration::from_millis(20));
+            this.get_rule_context().set("done", true);
+        });
+        let rule = DurationLimitRule::new(Duration::from_secs(10))
+            .with_warning_at(Duration::from_millis(5))
+            .wrap(rule);
+
+        let report = Engine::chain_runner().run_with_report(RuleContext::new(), vec![rule]);
+
+        assert!(report.get_error().is_none());
+        assert_eq!(report.get_warnings().len(), 1);
+        assert_eq!(report.get_warnings()[0].get_severity(), Severity::Warning);
+        assert_eq!(report.get_warnings()[0].get_rule(), Some("slow"));
+    }
+
+    #[test]
+    fn test_severity_order() {
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Critical);
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "wasm")]
+
+#[cfg(test)]
+mod tests {
+    use dredd_rs::wasm::{WasmContext, WasmRules};
+    use serde_json::{json, Value};
+
+    const DOCUMENT: &str = r#"{
+        "conditions": {
+            "is_adult": "age >= 18",
+            "is_domestic": "country == 'BR'"
+        },
+        "actions": {
+            "approve": { "approved": true },
+            "flag": { "review": "manual", "priority": 2 }
+        },
+        "rules": {
+            "type": "all",
+            "rules": [
+                { "name": "adult", "eval": "is_adult", "execute": "approve" },
+                { "name": "foreign", "eval": "is_domestic", "execute": "flag" }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_evaluate_returns_report() {
+        let rules = WasmRules::new(DOCUMENT).unwrap();
+
+        let report = rules.evaluate(r#"{ "age": 30, "country": "AR" }"#).unwrap();
+        let report: Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(report["fired"], json!(["adult"]));
+        assert_eq!(report["context"]["approved"], json!(true));
+        assert_eq!(report["context"]["age"], json!(30));
+        assert!(report["context"].get("review").is_none());
+        assert_eq!(report["trace"][1]["outcome"], json!("not applicable"));
+        assert_eq!(report["errors"], json!([]));
+    }
+
+    #[test]
+    fn test_run_updates_context() {
+        let rules = WasmRules::new(DOCUMENT).unwrap();
+        let mut context = WasmContext::new();
+        context.set("age", "12").unwrap();
+        context.set("country", "\"BR\"").unwrap();
+
+        rules.run(&mut context);
+
+        assert_eq!(context.get("review").as_deref(), Some("\"manual\""));
+        assert_eq!(context.get("priority").as_deref(), Some("2"));
+        assert_eq!(context.get("approved"), None);
+        let values: Value = serde_json::from_str(&context.to_json()).unwrap();
+        assert_eq!(values["country"], json!("BR"));
+    }
+
+    #[test]
+    fn test_missing_key_does_not_match() {
+        let rules = WasmRules::new(DOCUMENT).unwrap();
+
+        let report = rules.evaluate("{}").unwrap();
+        let report: Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(report["fired"], json!([]));
+    }
+
+    #[test]
+    fn test_invalid_documents_are_rejected() {
+        let invalid_condition = r#"{
+            "conditions": { "broken": "age >=" },
+            "rules": { "type": "all", "rules": [] }
+        }"#;
+        let error = WasmRules::new(invalid_condition).err().unwrap();
+        assert!(error.starts_with("invalid condition `broken`"), "{error}");
+
+        let unknown_action = r#"{
+            "rules": { "type": "all", "rules": [{ "execute": "missing" }] }
+        }"#;
+        assert!(WasmRules::new(unknown_action).is_err());
+
+        assert!(WasmContext::from_json("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "watch")]
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fs, path::PathBuf, rc::Rc, time::Duration};
+
+    use dredd_rs::loader::{self, CallbackRegistry};
+    use dredd_rs::rule::*;
+    use dredd_rs::watch::{RuleWatcher, WatchError};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// A fresh directory holding `rules.json`, removed when dropped.
+    struct RulesFile {
+        dir: PathBuf,
+    }
+
+    impl RulesFile {
+        fn new(name: &str, json: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("dredd-watch-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let file = RulesFile { dir };
+            file.write(json);
+            file
+        }
+
+        fn path(&self) -> PathBuf {
+            self.dir.join("rules.json")
+        }
+
+        fn write(&self, json: &str) {
+            fs::write(self.path(), json).unwrap();
+        }
+    }
+
+    impl Drop for RulesFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn setting(value: &str) -> String {
+        format!(
+            r#"{{ "type": "all", "state": "active", "rules": [{{ "name": "set", "execute": "{value}" }}] }}"#
+        )
+    }
+
+    fn watcher(file: &RulesFile) -> RuleWatcher {
+        let mut callbacks = CallbackRegistry::new();
+        callbacks
+            .action("v1", |ctx| ctx.set("version", 1))
+            .action("v2", |ctx| ctx.set("version", 2));
+        RuleWatcher::new(file.path(), "pricing", move |json| {
+            loader::from_json(json, &callbacks)
+        })
+        .unwrap()
+    }
+
+    fn version(registry: &RuleRegistry) -> Option<i32> {
+        let rule_context = RuleContext::new();
+        registry.execute("pricing", rule_context.clone()).unwrap();
+        rule_context.get::<i32>("version").map(|version| *version)
+    }
+
+    #[test]
+    fn test_reload_registers_rule_set() {
+        let file = RulesFile::new("reload", &setting("v1"));
+        let watcher = watcher(&file);
+        let mut registry = RuleRegistry::new();
+
+        assert!(!registry.contains("pricing"));
+        watcher.reload(&mut registry).unwrap();
+
+        assert_eq!(version(&registry), Some(1));
+        assert_eq!(watcher.get_rule_set(), "pricing");
+        assert_eq!(watcher.get_path(), file.path());
+    }
+
+    #[test]
+    fn test_change_swaps_rule_set() {
+        let file = RulesFile::new("change", &setting("v1"));
+        let watcher = watcher(&file);
+        let mut registry = RuleRegistry::new();
+        watcher.reload(&mut registry).unwrap();
+
+        file.write(&setting("v2"));
+
+        assert!(watcher.poll_timeout(&mut registry, TIMEOUT));
+        assert_eq!(version(&registry), Some(2));
+    }
+
+    #[test]
+    fn test_invalid_change_keeps_rule_set_and_reports_error() {
+        let file = RulesFile::new("invalid", &setting("v1"));
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let watcher = watcher(&file).on_error({
+            let errors = errors.clone();
+            move |error| errors.borrow_mut().push(error.to_string())
+        });
+        let mut registry = RuleRegistry::new();
+        watcher.reload(&mut registry).unwrap();
+
+        file.write(&setting("v3"));
+
+        assert!(!watcher.poll_timeout(&mut registry, TIMEOUT));
+        assert_eq!(version(&registry), Some(1));
+        assert_eq!(
+            *errors.borrow(),
+            vec!["can't load rule definition: unknown action `v3`"]
+        );
+
+        file.write(&setting("v2"));
+
+        assert!(watcher.poll_timeout(&mut registry, TIMEOUT));
+        assert_eq!(version(&registry), Some(2));
+    }
+
+    #[test]
+    fn test_poll_without_change() {
+        let file = RulesFile::new("unchanged", &setting("v1"));
+        let watcher = watcher(&file);
+        let mut registry = RuleRegistry::new();
+        watcher.reload(&mut registry).unwrap();
+
+        assert!(!watcher.poll(&mut registry));
+        assert!(!watcher.poll_timeout(&mut registry, Duration::from_millis(100)));
+        assert_eq!(version(&registry), Some(1));
+    }
+
+    #[test]
+    fn test_other_files_are_ignored() {
+        let file = RulesFile::new("other", &setting("v1"));
+        let watcher = watcher(&file);
+        let mut registry = RuleRegistry::new();
+        watcher.reload(&mut registry).unwrap();
+
+        fs::write(file.dir.join("notes.txt"), "unrelated").unwrap();
+
+        assert!(!watcher.poll_timeout(&mut registry, Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn test_reload_of_missing_file() {
+        let file = RulesFile::new("missing", &setting("v1"));
+        let watcher = watcher(&file);
+        fs::remove_file(file.path()).unwrap();
+
+        let mut registry = RuleRegistry::new();
+
+        assert!(matches!(
+            watcher.reload(&mut registry),
+            Err(WatchError::Io(_))
+        ));
+        assert!(!registry.contains("pricing"));
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use dredd_rs::rule::*;
+
+    fn split(sticky: bool) -> std::rc::Rc<std::cell::RefCell<AllRule>> {
+        let choice = WeightedChoice::new("variant")
+            .alternative("control", 80)
+            .branch(
+                "treatment",
+                20,
+                AllRule::new()
+                    .with_name("treatment")
+                    .on_execute(|this| this.get_rule_context().set("discount", 0.1)),
+            );
+        let choice = if sticky {
+            choice.sticky_by("user_id")
+        } else {
+            choice
+        };
+        choice.wrap(AllRule::new().with_name("split"))
+    }
+
+    fn variant(
+        rule: &std::rc::Rc<std::cell::RefCell<AllRule>>,
+        user: u64,
+        seed: u64,
+    ) -> &'static str {
+        let mut rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_seed(seed);
+        rule_context.set("user_id", user);
+        Engine::all_runner().run(rule_context.clone(), vec![rule.clone()]);
+        let variant = *rule_context.get::<&str>("variant").unwrap();
+        assert_eq!(
+            rule_context.get::<f64>("discount").is_some(),
+            variant == "treatment"
+        );
+        variant
+    }
+
+    #[test]
+    fn test_weights_are_respected() {
+        let rule = split(false);
+        let mut counts = HashMap::new();
+        for seed in 0..2000 {
+            *counts.entry(variant(&rule, 1, seed)).or_insert(0) += 1;
+        }
+
+        assert!((300..500).contains(&counts["treatment"]), "{counts:?}");
+        assert_eq!(counts["control"] + counts["treatment"], 2000);
+    }
+
+    #[test]
+    fn test_random_choice_replays_from_the_seed() {
+        let rule = split(false);
+        let draws: Vec<_> = (0..50).map(|seed| variant(&rule, 1, seed)).collect();
+
+        assert_eq!(
+            draws,
+            (0..50)
+                .map(|seed| variant(&rule, 1, seed))
+                .collect::<Vec<_>>()
+        );
+        assert!(draws.contains(&"control") && draws.contains(&"treatment"));
+    }
+
+    #[test]
+    fn test_sticky_choice_ignores_the_seed() {
+        let rule = split(true);
+        let mut treated = 0;
+        for user in 0..1000 {
+            let first = variant(&rule, user, 1);
+            assert_eq!(first, variant(&rule, user, 2));
+            treated += (first == "treatment") as u32;
+        }
+
+        assert!((120..280).contains(&treated), "{treated} treated");
+    }
+
+    #[test]
+    fn test_sticky_choice_falls_back_to_a_draw() {
+        let rule = split(true);
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(rule_context.get::<&str>("variant").is_some());
+    }
+
+    #[test]
+    fn test_no_choice_without_weight() {
+        let choice =
+            WeightedChoice::new("variant")
+                .alternative("a", 0)
+                .branch("b", 0, AllRule::new());
+        assert_eq!(choice.get_weights(), [0, 0]);
+        assert_eq!(choice.get_branches().len(), 1);
+
+        let rule = choice.wrap(AllRule::new());
+        let rule_context = RuleContext::new();
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+
+        assert!(rule_context.get::<&str>("variant").is_none());
+    }
+}
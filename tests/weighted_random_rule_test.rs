@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use dredd_rs::rule::*;
+
+    fn variant(name: &'static str) -> Rc<RefCell<AllRule>> {
+        AllRule::new()
+            .with_name(name)
+            .on_execute(move |this| this.get_rule_context().push_to_list("variants", name))
+    }
+
+    fn rollout(stable: u32, experimental: u32) -> Rc<RefCell<AllRule>> {
+        WeightedRandomRule::new()
+            .child(variant("stable"), stable)
+            .child(variant("experimental"), experimental)
+            .wrap(AllRule::new())
+    }
+
+    fn fire(rule: Rc<RefCell<AllRule>>, seed: u64) -> Vec<&'static str> {
+        let rule_context = RuleContext::new();
+        rule_context.borrow_mut().set_seed(seed);
+        Engine::all_runner().run(rule_context.clone(), vec![rule]);
+        rule_context
+            .get_list::<&str>("variants")
+            .map(|variants| variants.to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_weighted_random_fires_one_child() {
+        let rule = rollout(50, 50);
+
+        for seed in 0..50 {
+            assert_eq!(fire(rule.clone(), seed).len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_is_deterministic_for_a_seed() {
+        let rule = rollout(50, 50);
+
+        for seed in 0..20 {
+            assert_eq!(fire(rule.clone(), seed), fire(rule.clone(), seed));
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_follows_weights() {
+        let rule = rollout(80, 20);
+
+        let experimental = (0..1000)
+            .filter(|seed| fire(rule.clone(), *seed) == vec!["experimental"])
+            .count();
+
+        assert!((140..260).contains(&experimental), "{experimental}");
+    }
+
+    #[test]
+    fn test_weighted_random_skips_zero_weights() {
+        let rule = rollout(0, 1);
+
+        for seed in 0..20 {
+            assert_eq!(fire(rule.clone(), seed), vec!["experimental"]);
+        }
+        assert!(fire(rollout(0, 0), 1).is_empty());
+    }
+
+    #[test]
+    fn test_weighted_random_replays_from_report_seed() {
+        let rule = rollout(50, 50);
+
+        let rule_context = RuleContext::new();
+        let report = Engine::all_runner().run_with_report(rule_context.clone(), vec![rule.clone()]);
+
+        let replayed = RuleContext::new();
+        replayed.borrow_mut().set_seed(report.get_seed());
+        Engine::all_runner().run(replayed.clone(), vec![rule]);
+
+        assert_eq!(
+            *rule_context.get_list::<&str>("variants").unwrap(),
+            *replayed.get_list::<&str>("variants").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_weighted_random_getters() {
+        let draw = WeightedRandomRule::new()
+            .child(variant("stable"), 9)
+            .child(variant("experimental"), 1);
+
+        assert_eq!(draw.get_weights(), vec![9, 1]);
+        assert_eq!(draw.get_children().len(), 2);
+    }
+}